@@ -1,10 +1,11 @@
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 
+use crate::workers::{Worker, WorkflowAssignment};
+
 #[derive(Component)]
 pub struct GameCamera {
     pub velocity: Vec2,
-    pub base_speed: f32,
     pub acceleration: f32,
     pub deceleration: f32,
     pub min_zoom: f32,
@@ -15,7 +16,6 @@ impl Default for GameCamera {
     fn default() -> Self {
         Self {
             velocity: Vec2::ZERO,
-            base_speed: 650.0,
             acceleration: 8.0,
             deceleration: 12.0,
             min_zoom: 0.3,
@@ -24,13 +24,60 @@ impl Default for GameCamera {
     }
 }
 
+/// Pan speed is in world units per second at zoom 1. Edge scrolling pans
+/// while the cursor is within `edge_margin_px` of the window border.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraConfig {
+    pub pan_speed: f32,
+    pub edge_scroll_enabled: bool,
+    pub edge_margin_px: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            pan_speed: 650.0,
+            edge_scroll_enabled: false,
+            edge_margin_px: 12.0,
+        }
+    }
+}
+
+/// Pan direction from the cursor's window position. Window y grows downward,
+/// so the top edge pans up.
+fn edge_scroll_direction(cursor: Vec2, window_size: Vec2, margin: f32) -> Vec2 {
+    let mut direction = Vec2::ZERO;
+    if cursor.x <= margin {
+        direction.x -= 1.0;
+    } else if cursor.x >= window_size.x - margin {
+        direction.x += 1.0;
+    }
+    if cursor.y <= margin {
+        direction.y += 1.0;
+    } else if cursor.y >= window_size.y - margin {
+        direction.y -= 1.0;
+    }
+    direction
+}
+
+#[derive(Resource, Default)]
+pub struct IdleWorkerCycle {
+    pub next_index: usize,
+    /// 1-based position and total of the idle worker last jumped to.
+    pub focused: Option<(usize, usize)>,
+}
+
 pub fn setup_camera(mut commands: Commands) {
     commands.spawn((Camera2d, GameCamera::default()));
 }
 
+/// Runs on real time so the camera still pans while the simulation is
+/// paused.
 pub fn handle_camera_keyboard_input(
     keyboard: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
+    config: Res<CameraConfig>,
+    windows: Query<&Window>,
     mut camera_query: Query<(&mut Transform, &mut GameCamera), With<Camera2d>>,
     projection_query: Query<&Projection, With<Camera2d>>,
 ) {
@@ -57,23 +104,34 @@ pub fn handle_camera_keyboard_input(
         target_velocity.x += 1.0;
     }
 
+    if config.edge_scroll_enabled {
+        if let Ok(window) = windows.single() {
+            if let Some(cursor) = window.cursor_position() {
+                target_velocity +=
+                    edge_scroll_direction(cursor, window.size(), config.edge_margin_px);
+            }
+        }
+    }
+
     if target_velocity.length() > 0.0 {
         target_velocity = target_velocity.normalize();
     }
 
     let zoom_scale = projection.scale;
-    target_velocity *= game_camera.base_speed * zoom_scale;
+    target_velocity *= config.pan_speed * zoom_scale;
 
     let delta_time = time.delta_secs();
 
+    // Capped at 1 so a long frame settles on the target instead of overshooting.
     if target_velocity.length() > 0.0 {
-        game_camera.velocity = game_camera
-            .velocity
-            .lerp(target_velocity, game_camera.acceleration * delta_time);
+        game_camera.velocity = game_camera.velocity.lerp(
+            target_velocity,
+            (game_camera.acceleration * delta_time).min(1.0),
+        );
     } else {
         game_camera.velocity = game_camera
             .velocity
-            .lerp(Vec2::ZERO, game_camera.deceleration * delta_time);
+            .lerp(Vec2::ZERO, (game_camera.deceleration * delta_time).min(1.0));
     }
 
     camera_transform.translation += game_camera.velocity.extend(0.0) * delta_time;
@@ -155,11 +213,187 @@ pub fn handle_camera_zoom(
     }
 }
 
+pub fn jump_to_idle_worker(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut cycle: ResMut<IdleWorkerCycle>,
+    idle_workers: Query<(Entity, &Transform), (With<Worker>, Without<WorkflowAssignment>)>,
+    mut camera_query: Query<(&mut Transform, &mut GameCamera), (With<Camera2d>, Without<Worker>)>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyI) {
+        return;
+    }
+
+    let mut idle: Vec<(Entity, Vec2)> = idle_workers
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()))
+        .collect();
+
+    if idle.is_empty() {
+        cycle.next_index = 0;
+        cycle.focused = None;
+        return;
+    }
+
+    idle.sort_by_key(|&(entity, _)| entity);
+
+    let Ok((mut camera_transform, mut game_camera)) = camera_query.single_mut() else {
+        return;
+    };
+
+    let index = cycle.next_index % idle.len();
+    let target = idle[index].1;
+    camera_transform.translation.x = target.x;
+    camera_transform.translation.y = target.y;
+    game_camera.velocity = Vec2::ZERO;
+
+    cycle.next_index = index + 1;
+    cycle.focused = Some((index + 1, idle.len()));
+}
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_camera)
-            .add_systems(Update, (handle_camera_keyboard_input, handle_camera_zoom));
+        app.init_resource::<IdleWorkerCycle>()
+            .init_resource::<CameraConfig>()
+            .add_systems(Startup, setup_camera)
+            .add_systems(
+                Update,
+                (
+                    handle_camera_keyboard_input,
+                    handle_camera_zoom,
+                    jump_to_idle_worker,
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn press_idle_key(app: &mut App) {
+        {
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.release(KeyCode::KeyI);
+            keyboard.clear();
+            keyboard.press(KeyCode::KeyI);
+        }
+        app.update();
+    }
+
+    fn camera_position(app: &mut App) -> Vec2 {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<&Transform, With<GameCamera>>();
+        query.single(app.world()).unwrap().translation.truncate()
+    }
+
+    fn pan_app(config: CameraConfig) -> App {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Time<Real>>()
+            .insert_resource(config)
+            .add_systems(Update, handle_camera_keyboard_input);
+        // Instant acceleration so one frame moves exactly pan_speed * delta.
+        app.world_mut().spawn((
+            Camera2d,
+            GameCamera {
+                acceleration: 1000.0,
+                ..default()
+            },
+            Transform::default(),
+            Projection::Orthographic(OrthographicProjection::default_2d()),
+        ));
+        app
+    }
+
+    fn advance_frame(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time<Real>>()
+            .advance_by(std::time::Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    #[test]
+    fn holding_a_pan_key_moves_by_pan_speed_times_delta() {
+        let mut app = pan_app(CameraConfig {
+            pan_speed: 400.0,
+            ..default()
+        });
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyD);
+
+        advance_frame(&mut app, 0.1);
+
+        let moved = camera_position(&mut app);
+        assert!((moved.x - 40.0).abs() < 1e-3, "moved {moved:?}");
+        assert!(moved.y.abs() < 1e-3);
+    }
+
+    #[test]
+    fn cursor_at_the_edge_pans_only_when_enabled() {
+        for enabled in [false, true] {
+            let mut app = pan_app(CameraConfig {
+                edge_scroll_enabled: enabled,
+                ..default()
+            });
+            let mut window = Window::default();
+            window.set_cursor_position(Some(Vec2::new(640.0, 2.0)));
+            app.world_mut().spawn(window);
+
+            advance_frame(&mut app, 0.1);
+
+            let moved = camera_position(&mut app);
+            if enabled {
+                assert!(moved.y > 0.0, "top edge should pan up, moved {moved:?}");
+            } else {
+                assert_eq!(moved, Vec2::ZERO);
+            }
+        }
+    }
+
+    #[test]
+    fn idle_worker_jump_cycles_and_wraps() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<IdleWorkerCycle>()
+            .add_systems(Update, jump_to_idle_worker);
+
+        app.world_mut()
+            .spawn((Camera2d, GameCamera::default(), Transform::default()));
+        let a = app
+            .world_mut()
+            .spawn((Worker, Transform::from_xyz(100.0, 50.0, 1.5)))
+            .id();
+        let b = app
+            .world_mut()
+            .spawn((Worker, Transform::from_xyz(-200.0, 300.0, 1.5)))
+            .id();
+        // Cycling follows entity order, which is not guaranteed to match spawn order.
+        let (first, second) = if a < b {
+            (Vec2::new(100.0, 50.0), Vec2::new(-200.0, 300.0))
+        } else {
+            (Vec2::new(-200.0, 300.0), Vec2::new(100.0, 50.0))
+        };
+
+        press_idle_key(&mut app);
+        assert_eq!(camera_position(&mut app), first);
+        assert_eq!(
+            app.world().resource::<IdleWorkerCycle>().focused,
+            Some((1, 2))
+        );
+
+        press_idle_key(&mut app);
+        assert_eq!(camera_position(&mut app), second);
+        assert_eq!(
+            app.world().resource::<IdleWorkerCycle>().focused,
+            Some((2, 2))
+        );
+
+        press_idle_key(&mut app);
+        assert_eq!(camera_position(&mut app), first);
     }
 }
@@ -12,6 +12,8 @@ pub mod constants;
 pub mod grid;
 pub mod materials;
 pub mod resources;
+pub mod rng;
+pub mod session;
 pub mod structures;
 pub mod systems;
 pub mod ui;
@@ -22,6 +24,14 @@ pub mod invariants;
 
 use bevy::prelude::*;
 
+/// Default log filter for `LogPlugin`. Game events such as completed
+/// constructions and launches log at `info`; per-building chatter (scanner
+/// reveals, drill recipe assignment, grid expansion, item transfers) logs at
+/// `debug` and stays silent at this level. `RUST_LOG` replaces the filter, so
+/// `RUST_LOG=the_factory=debug` brings the detailed logistics logging back
+/// without a rebuild.
+pub const LOG_FILTER: &str = "wgpu=error,naga=warn,the_factory=info";
+
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum GameplaySet {
     GridUpdate,
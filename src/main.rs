@@ -1,27 +1,36 @@
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use the_factory::camera::CameraPlugin;
 use the_factory::configure_system_sets;
 use the_factory::grid::GridPlugin;
 use the_factory::materials::MaterialsPlugin;
 use the_factory::resources::ResourcesPlugin;
+use the_factory::rng::RngPlugin;
+use the_factory::session::SessionPlugin;
 use the_factory::structures::BuildingsPlugin;
 use the_factory::systems::SystemsPlugin;
 use the_factory::ui::UIPlugin;
 use the_factory::workers::WorkersPlugin;
+use the_factory::LOG_FILTER;
 
 fn main() {
     let mut app = App::new();
     configure_system_sets(&mut app);
-    app.add_plugins(DefaultPlugins)
-        .add_plugins((
-            GridPlugin,
-            ResourcesPlugin,
-            MaterialsPlugin,
-            SystemsPlugin,
-            BuildingsPlugin,
-            WorkersPlugin,
-            CameraPlugin,
-            UIPlugin,
-        ))
-        .run();
+    app.add_plugins(DefaultPlugins.set(LogPlugin {
+        filter: LOG_FILTER.to_string(),
+        ..default()
+    }))
+    .add_plugins((
+        GridPlugin,
+        RngPlugin,
+        ResourcesPlugin,
+        MaterialsPlugin,
+        SystemsPlugin,
+        SessionPlugin,
+        BuildingsPlugin,
+        WorkersPlugin,
+        CameraPlugin,
+        UIPlugin,
+    ))
+    .run();
 }
@@ -2,28 +2,118 @@ use bevy::prelude::*;
 use ron;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::{LazyLock, PoisonError, RwLock, RwLockReadGuard};
+
+/// Copy-cheap map key for an item. Ids are handed out by [`ItemRegistry`]
+/// as it loads its definitions, so only registered names have one; the
+/// game's own items are registered from the item assets the first time any
+/// id is needed. An id formats, serializes and deserializes as its name.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemId(u32);
+
+/// Names of every registered item, indexed by id.
+#[derive(Default)]
+struct ItemNames {
+    ids: HashMap<String, ItemId>,
+    names: Vec<String>,
+}
+
+impl ItemNames {
+    fn from_assets() -> Self {
+        let mut names = Self::default();
+        if let Ok(definitions) = ron::from_str::<Vec<ItemDef>>(ITEMS_RON) {
+            for def in definitions {
+                names.register(&def.name);
+            }
+        }
+        names
+    }
+
+    fn register(&mut self, name: &str) -> ItemId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = ItemId(u32::try_from(self.names.len()).unwrap_or(u32::MAX));
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), id);
+        id
+    }
+}
+
+const ITEMS_RON: &str = include_str!("../assets/items.ron");
+
+static ITEM_NAMES: LazyLock<RwLock<ItemNames>> =
+    LazyLock::new(|| RwLock::new(ItemNames::from_assets()));
+
+fn item_names() -> RwLockReadGuard<'static, ItemNames> {
+    ITEM_NAMES.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ItemId {
+    /// The id of a registered item, or `None` for a name no registry has
+    /// loaded, such as a misspelling.
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        item_names().ids.get(name).copied()
+    }
+
+    fn register(name: &str) -> Self {
+        ITEM_NAMES
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .register(name)
+    }
+}
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match item_names().names.get(self.0 as usize) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "#{}", self.0),
+        }
+    }
+}
+
+impl fmt::Debug for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ItemId({:?})", self.to_string())
+    }
+}
+
+impl Serialize for ItemId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
 
-pub type ItemName = String;
+/// Rejects names no registry has loaded.
+impl<'de> Deserialize<'de> for ItemId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::named(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown item {name:?}")))
+    }
+}
 
 pub trait InventoryAccess {
-    fn items(&self) -> &HashMap<ItemName, u32>;
+    fn items(&self) -> &HashMap<ItemId, u32>;
 
-    fn items_mut(&mut self) -> &mut HashMap<ItemName, u32>;
+    fn items_mut(&mut self) -> &mut HashMap<ItemId, u32>;
 
     fn capacity(&self) -> u32;
 
     /// Does not enforce capacity limits.
-    fn add_item(&mut self, item_name: &str, quantity: u32) -> u32 {
-        *self.items_mut().entry(item_name.to_string()).or_insert(0) += quantity;
+    fn add_item(&mut self, item: ItemId, quantity: u32) -> u32 {
+        *self.items_mut().entry(item).or_insert(0) += quantity;
         quantity
     }
 
-    fn remove_item(&mut self, item_name: &str, quantity: u32) -> u32 {
-        if let Some(current_quantity) = self.items_mut().get_mut(item_name) {
+    fn remove_item(&mut self, item: ItemId, quantity: u32) -> u32 {
+        if let Some(current_quantity) = self.items_mut().get_mut(&item) {
             let removed = (*current_quantity).min(quantity);
             *current_quantity -= removed;
             if *current_quantity == 0 {
-                self.items_mut().remove(item_name);
+                self.items_mut().remove(&item);
             }
             removed
         } else {
@@ -31,8 +121,8 @@ pub trait InventoryAccess {
         }
     }
 
-    fn get_item_quantity(&self, item_name: &str) -> u32 {
-        self.items().get(item_name).copied().unwrap_or(0)
+    fn get_item_quantity(&self, item: ItemId) -> u32 {
+        self.items().get(&item).copied().unwrap_or(0)
     }
 
     fn get_total_quantity(&self) -> u32 {
@@ -47,30 +137,41 @@ pub trait InventoryAccess {
         self.items().is_empty()
     }
 
-    fn has_space_for(&self, items: &HashMap<ItemName, u32>) -> bool {
+    fn has_space_for(&self, items: &HashMap<ItemId, u32>) -> bool {
         let current_quantity = self.get_total_quantity();
         let total_quantity = items.values().sum::<u32>();
         current_quantity + total_quantity <= self.capacity()
     }
 
-    fn has_at_least(&self, item_name: &str, required_quantity: u32) -> bool {
-        self.get_item_quantity(item_name) >= required_quantity
+    fn has_at_least(&self, item: ItemId, required_quantity: u32) -> bool {
+        self.get_item_quantity(item) >= required_quantity
     }
 
-    fn has_items_for_recipe(&self, recipe: &HashMap<ItemName, u32>) -> bool {
+    fn has_items_for_recipe(&self, recipe: &HashMap<ItemId, u32>) -> bool {
         recipe
             .iter()
-            .all(|(item_name, quantity)| self.has_at_least(item_name, *quantity))
+            .all(|(&item, &quantity)| self.has_at_least(item, quantity))
     }
 
-    fn get_all_items(&self) -> HashMap<ItemName, u32> {
+    /// Removes every input of `recipe`, or nothing if any input is short.
+    fn consume_recipe_inputs(&mut self, recipe: &HashMap<ItemId, u32>) -> bool {
+        if !self.has_items_for_recipe(recipe) {
+            return false;
+        }
+        for (&item, &quantity) in recipe {
+            self.remove_item(item, quantity);
+        }
+        true
+    }
+
+    fn get_all_items(&self) -> HashMap<ItemId, u32> {
         self.items().clone()
     }
 }
 
 #[derive(Component, Default, Debug, Clone)]
 pub struct OutputPort {
-    pub items: HashMap<ItemName, u32>,
+    pub items: HashMap<ItemId, u32>,
     pub capacity: u32,
 }
 
@@ -85,11 +186,11 @@ impl OutputPort {
 }
 
 impl InventoryAccess for OutputPort {
-    fn items(&self) -> &HashMap<ItemName, u32> {
+    fn items(&self) -> &HashMap<ItemId, u32> {
         &self.items
     }
 
-    fn items_mut(&mut self) -> &mut HashMap<ItemName, u32> {
+    fn items_mut(&mut self) -> &mut HashMap<ItemId, u32> {
         &mut self.items
     }
 
@@ -98,11 +199,73 @@ impl InventoryAccess for OutputPort {
     }
 }
 
+/// Extra output slots keyed by tag, so a recipe can route each product to
+/// its own port. Untagged outputs still land in the building's `OutputPort`.
+#[derive(Component, Default, Debug, Clone)]
+pub struct NamedOutputPorts {
+    pub ports: HashMap<String, OutputPort>,
+}
+
+impl NamedOutputPorts {
+    #[must_use]
+    pub fn get(&self, tag: &str) -> Option<&OutputPort> {
+        self.ports.get(tag)
+    }
+
+    pub fn get_mut(&mut self, tag: &str) -> Option<&mut OutputPort> {
+        self.ports.get_mut(tag)
+    }
+
+    /// Combined contents of every named port.
+    #[must_use]
+    pub fn get_all_items(&self) -> HashMap<ItemId, u32> {
+        let mut items = HashMap::new();
+        for port in self.ports.values() {
+            for (&item, &quantity) in &port.items {
+                *items.entry(item).or_insert(0) += quantity;
+            }
+        }
+        items
+    }
+
+    /// Removes up to `quantity` of `item`, draining ports in turn.
+    pub fn remove_item(&mut self, item: ItemId, quantity: u32) -> u32 {
+        let mut removed = 0;
+        for port in self.ports.values_mut() {
+            if removed == quantity {
+                break;
+            }
+            removed += port.remove_item(item, quantity - removed);
+        }
+        removed
+    }
+}
+
+/// Everything a building offers for pickup across its default and named
+/// output ports.
+#[must_use]
+pub fn combined_output_items(
+    port: Option<&OutputPort>,
+    named: Option<&NamedOutputPorts>,
+) -> HashMap<ItemId, u32> {
+    let mut items = port.map(|port| port.items.clone()).unwrap_or_default();
+    for (item, quantity) in named
+        .map(NamedOutputPorts::get_all_items)
+        .unwrap_or_default()
+    {
+        *items.entry(item).or_insert(0) += quantity;
+    }
+    items
+}
+
 #[derive(Component, Default, Debug, Clone)]
 pub struct InputPort {
-    pub items: HashMap<ItemName, u32>,
+    pub items: HashMap<ItemId, u32>,
     pub capacity: u32,
-    pub item_limits: HashMap<ItemName, u32>,
+    pub item_limits: HashMap<ItemId, u32>,
+    /// Items workers may carry back out, such as inputs a recipe change left
+    /// unused. Input ports are otherwise never drawn from.
+    pub outgoing: HashMap<ItemId, u32>,
 }
 
 impl InputPort {
@@ -112,16 +275,40 @@ impl InputPort {
             items: HashMap::new(),
             capacity,
             item_limits: HashMap::new(),
+            outgoing: HashMap::new(),
+        }
+    }
+
+    /// The outgoing items the port still holds.
+    #[must_use]
+    pub fn releasable(&self) -> HashMap<ItemId, u32> {
+        self.outgoing
+            .iter()
+            .map(|(&item, &qty)| (item, qty.min(self.get_item_quantity(item))))
+            .filter(|&(_, qty)| qty > 0)
+            .collect()
+    }
+
+    /// Removes up to `quantity` of an outgoing item, returning how many were
+    /// removed.
+    pub fn release_item(&mut self, item: ItemId, quantity: u32) -> u32 {
+        let allowed = self.outgoing.get(&item).copied().unwrap_or(0);
+        let removed = self.remove_item(item, quantity.min(allowed));
+        if allowed > removed {
+            self.outgoing.insert(item, allowed - removed);
+        } else {
+            self.outgoing.remove(&item);
         }
+        removed
     }
 }
 
 impl InventoryAccess for InputPort {
-    fn items(&self) -> &HashMap<ItemName, u32> {
+    fn items(&self) -> &HashMap<ItemId, u32> {
         &self.items
     }
 
-    fn items_mut(&mut self) -> &mut HashMap<ItemName, u32> {
+    fn items_mut(&mut self) -> &mut HashMap<ItemId, u32> {
         &mut self.items
     }
 
@@ -132,7 +319,7 @@ impl InventoryAccess for InputPort {
 
 #[derive(Component, Default, Debug, Clone)]
 pub struct StoragePort {
-    pub items: HashMap<ItemName, u32>,
+    pub items: HashMap<ItemId, u32>,
     pub capacity: u32,
 }
 
@@ -147,11 +334,11 @@ impl StoragePort {
 }
 
 impl InventoryAccess for StoragePort {
-    fn items(&self) -> &HashMap<ItemName, u32> {
+    fn items(&self) -> &HashMap<ItemId, u32> {
         &self.items
     }
 
-    fn items_mut(&mut self) -> &mut HashMap<ItemName, u32> {
+    fn items_mut(&mut self) -> &mut HashMap<ItemId, u32> {
         &mut self.items
     }
 
@@ -162,7 +349,7 @@ impl InventoryAccess for StoragePort {
 
 #[derive(Component, Default, Debug, Clone)]
 pub struct Cargo {
-    pub items: HashMap<ItemName, u32>,
+    pub items: HashMap<ItemId, u32>,
     pub capacity: u32,
 }
 
@@ -177,11 +364,11 @@ impl Cargo {
 }
 
 impl InventoryAccess for Cargo {
-    fn items(&self) -> &HashMap<ItemName, u32> {
+    fn items(&self) -> &HashMap<ItemId, u32> {
         &self.items
     }
 
-    fn items_mut(&mut self) -> &mut HashMap<ItemName, u32> {
+    fn items_mut(&mut self) -> &mut HashMap<ItemId, u32> {
         &mut self.items
     }
 
@@ -190,26 +377,104 @@ impl InventoryAccess for Cargo {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemRarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+impl ItemRarity {
+    #[must_use]
+    pub fn from_tier(tier: u32) -> Self {
+        match tier {
+            0 => Self::Common,
+            1 => Self::Uncommon,
+            2 => Self::Rare,
+            _ => Self::Epic,
+        }
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Common => "Common",
+            Self::Uncommon => "Uncommon",
+            Self::Rare => "Rare",
+            Self::Epic => "Epic",
+        }
+    }
+
+    #[must_use]
+    pub fn default_stack_size(self) -> u32 {
+        match self {
+            Self::Common => 100,
+            Self::Uncommon => 50,
+            Self::Rare => 25,
+            Self::Epic => 10,
+        }
+    }
+
+    #[must_use]
+    pub fn color(self) -> Color {
+        match self {
+            Self::Common => Color::srgb(0.75, 0.75, 0.75),
+            Self::Uncommon => Color::srgb(0.45, 0.85, 0.45),
+            Self::Rare => Color::srgb(0.4, 0.6, 1.0),
+            Self::Epic => Color::srgb(0.75, 0.45, 0.95),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ItemDef {
     pub name: String,
     pub tier: u32,
-    // pub stack_size: u32 (not needed yet)
+    #[serde(default)]
+    pub stack_size: Option<u32>,
+    #[serde(default)]
+    pub value: Option<u64>,
+}
+
+impl ItemDef {
+    #[must_use]
+    pub fn rarity(&self) -> ItemRarity {
+        ItemRarity::from_tier(self.tier)
+    }
+
+    /// Explicit `stack_size` from the definition, otherwise the rarity default.
+    #[must_use]
+    pub fn stack_size(&self) -> u32 {
+        self.stack_size
+            .unwrap_or_else(|| self.rarity().default_stack_size())
+    }
+
+    /// Explicit `value` from the definition, otherwise `(tier + 1)^2`.
+    #[must_use]
+    pub fn value(&self) -> u64 {
+        self.value
+            .unwrap_or_else(|| u64::from((self.tier + 1).pow(2)))
+    }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct ItemRegistry {
-    pub definitions: HashMap<ItemName, ItemDef>,
+    pub definitions: HashMap<ItemId, ItemDef>,
 }
 
 impl ItemRegistry {
     pub fn from_ron(ron_content: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let definitions_vec: Vec<ItemDef> = ron::from_str(ron_content)?;
 
-        let mut definitions = HashMap::new();
+        let mut definitions: HashMap<ItemId, ItemDef> = HashMap::new();
 
         for def in definitions_vec {
-            definitions.insert(def.name.clone(), def);
+            let id = ItemId::register(&def.name);
+            if definitions.contains_key(&id) {
+                return Err(format!("item {:?} is defined twice", def.name).into());
+            }
+            definitions.insert(id, def);
         }
 
         Ok(Self { definitions })
@@ -220,19 +485,93 @@ impl ItemRegistry {
     /// # Errors
     /// Returns an error if the embedded RON content fails to parse.
     pub fn load_from_assets() -> Result<Self, Box<dyn std::error::Error>> {
-        let ron_content = include_str!("../assets/items.ron");
-        Self::from_ron(ron_content)
+        Self::from_ron(ITEMS_RON)
+    }
+
+    /// Resolves an item name defined in this registry to its id.
+    pub fn id_of(&self, item_name: &str) -> Option<ItemId> {
+        ItemId::named(item_name).filter(|id| self.definitions.contains_key(id))
     }
 
-    pub fn get_definition(&self, item_name: &str) -> Option<&ItemDef> {
-        self.definitions.get(item_name)
+    pub fn name_of(&self, item: ItemId) -> Option<&str> {
+        self.definitions.get(&item).map(|def| def.name.as_str())
     }
+
+    /// Name to show for `item`; unregistered items read as "Unknown".
+    #[must_use]
+    pub fn display_name(&self, item: ItemId) -> &str {
+        self.name_of(item).unwrap_or("Unknown")
+    }
+
+    pub fn get_definition(&self, item: ItemId) -> Option<&ItemDef> {
+        self.definitions.get(&item)
+    }
+
+    pub fn rarity_of(&self, item: ItemId) -> ItemRarity {
+        self.get_definition(item)
+            .map_or(ItemRarity::Common, ItemDef::rarity)
+    }
+
+    /// Unregistered items stack like common ones.
+    pub fn stack_size_of(&self, item: ItemId) -> u32 {
+        self.get_definition(item).map_or_else(
+            || ItemRarity::Common.default_stack_size(),
+            ItemDef::stack_size,
+        )
+    }
+
+    /// Shared worth of one item, used wherever items are scored or compared.
+    /// Unregistered items are worth 1, the same as an undefined tier 0 item.
+    #[must_use]
+    pub fn value_of(&self, item: ItemId) -> u64 {
+        self.get_definition(item).map_or(1, ItemDef::value)
+    }
+}
+
+/// Quantities a source keeps back. Transfers out of it never draw an item
+/// below its reserve, except into a receiver marked [`DrawsOnReserves`].
+#[derive(Component, Default, Debug, Clone)]
+pub struct ItemReserve {
+    pub items: HashMap<ItemId, u32>,
+}
+
+/// Stock a source can actually give up: what it holds above its reserve.
+/// Planning against this keeps a reserved source from being asked for more
+/// than it may give, which would have the whole transfer rejected.
+#[must_use]
+pub fn drawable_stock(
+    items: &HashMap<ItemId, u32>,
+    reserve: Option<&ItemReserve>,
+) -> HashMap<ItemId, u32> {
+    items
+        .iter()
+        .map(|(&item, &qty)| {
+            let kept = reserve.map_or(0, |reserve| reserve.of(item));
+            (item, qty.saturating_sub(kept))
+        })
+        .filter(|&(_, qty)| qty > 0)
+        .collect()
 }
 
-#[derive(Debug)]
+/// Marks a receiver that reserves are kept for, such as a construction site.
+/// Transfers into it may draw a source below its [`ItemReserve`].
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct DrawsOnReserves;
+
+impl ItemReserve {
+    #[must_use]
+    pub fn of(&self, item: ItemId) -> u32 {
+        self.items.get(&item).copied().unwrap_or(0)
+    }
+}
+
+/// Why a transfer request was rejected as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TransferError {
     ItemNotFound,
     NotEnoughItems,
+    BelowReserve(ItemId),
+    NotAccepted(ItemId),
     DestinationFull,
 }
 
@@ -241,6 +580,8 @@ impl std::fmt::Display for TransferError {
         match self {
             Self::ItemNotFound => write!(f, "Item not found!"),
             Self::NotEnoughItems => write!(f, "Not enough items to transfer!"),
+            Self::BelowReserve(item) => write!(f, "{item} is at the source's reserve!"),
+            Self::NotAccepted(item) => write!(f, "{item} is not accepted by the destination!"),
             Self::DestinationFull => write!(f, "Destination storage full!"),
         }
     }
@@ -250,12 +591,12 @@ impl std::fmt::Display for TransferError {
 pub struct ItemTransferRequestEvent {
     pub sender: Entity,
     pub receiver: Entity,
-    pub items: HashMap<ItemName, u32>,
+    pub items: HashMap<ItemId, u32>,
 }
 
 #[derive(Message)]
 pub struct ItemTransferValidationEvent {
-    pub result: Result<HashMap<ItemName, u32>, TransferError>,
+    pub result: Result<HashMap<ItemId, u32>, TransferError>,
     pub request: ItemTransferRequestEvent,
 }
 
@@ -264,13 +605,13 @@ pub struct ItemTransferValidationEvent {
 pub struct ItemTransferEvent {
     pub sender: Entity,
     pub receiver: Entity,
-    pub items_transferred: HashMap<ItemName, u32>,
+    pub items_transferred: HashMap<ItemId, u32>,
 }
 
 #[allow(dead_code)]
 pub fn print_transferred_items(mut events: MessageReader<ItemTransferEvent>) {
     for event in events.read() {
-        println!("Items transferred: {:?}", event.items_transferred);
+        debug!(items = ?event.items_transferred, "items transferred");
     }
 }
 
@@ -278,114 +619,162 @@ pub fn validate_item_transfer(
     mut requests: MessageReader<ItemTransferRequestEvent>,
     mut validation_events: MessageWriter<ItemTransferValidationEvent>,
     output_ports: Query<&OutputPort>,
+    named_output_ports: Query<&NamedOutputPorts>,
     input_ports: Query<&InputPort>,
     storage_ports: Query<&StoragePort>,
     cargo_query: Query<&Cargo>,
+    reserves: Query<&ItemReserve>,
+    reserve_drawers: Query<(), With<DrawsOnReserves>>,
 ) {
     for request in requests.read() {
-        let sender_data =
-            get_sender_port_data(request.sender, &output_ports, &storage_ports, &cargo_query);
-
-        let Some((sender_items, _sender_capacity)) = sender_data else {
-            validation_events.write(ItemTransferValidationEvent {
-                result: Err(TransferError::ItemNotFound),
-                request: request.clone(),
-            });
-            continue;
-        };
-
+        let sender_data = get_sender_port_data(
+            request.sender,
+            &output_ports,
+            &named_output_ports,
+            &storage_ports,
+            &cargo_query,
+            &input_ports,
+        );
         let receiver_data =
             get_receiver_port_data(request.receiver, &input_ports, &storage_ports, &cargo_query);
 
-        let Some((receiver_total, receiver_capacity)) = receiver_data else {
-            validation_events.write(ItemTransferValidationEvent {
-                result: Err(TransferError::ItemNotFound),
-                request: request.clone(),
-            });
-            continue;
-        };
-
-        let item_limits: HashMap<ItemName, u32> = input_ports
-            .get(request.receiver)
-            .map_or_else(|_| HashMap::new(), |port| port.item_limits.clone());
-
-        let mut validated_transfer = HashMap::new();
-        let mut current_receiver_total = receiver_total;
-
-        for (item_name, &requested_quantity) in &request.items {
-            let available = sender_items.get(item_name).copied().unwrap_or(0);
-
-            if available == 0 {
-                continue;
+        let result = match (sender_data, receiver_data) {
+            (Some((sender_items, _)), Some((receiver_total, receiver_capacity))) => {
+                validate_transfer_items(
+                    &request.items,
+                    &sender_items,
+                    reserves
+                        .get(request.sender)
+                        .ok()
+                        .filter(|_| !reserve_drawers.contains(request.receiver)),
+                    input_ports.get(request.receiver).ok(),
+                    receiver_capacity.saturating_sub(receiver_total),
+                )
             }
+            _ => Err(TransferError::ItemNotFound),
+        };
 
-            if !item_limits.is_empty() && !item_limits.contains_key(item_name) {
-                continue;
-            }
-
-            let transfer_quantity = available.min(requested_quantity);
-            let remaining_capacity = receiver_capacity.saturating_sub(current_receiver_total);
-
-            if remaining_capacity == 0 {
-                break;
-            }
+        validation_events.write(ItemTransferValidationEvent {
+            result,
+            request: request.clone(),
+        });
+    }
+}
 
-            let mut final_quantity = transfer_quantity.min(remaining_capacity);
+/// Room left for `item` under an input port's per-item limit, or `None` when
+/// its filter does not accept the item at all.
+fn item_room(input_port: Option<&InputPort>, item: ItemId) -> Option<u32> {
+    match input_port {
+        Some(port) if !port.item_limits.is_empty() => port
+            .item_limits
+            .get(&item)
+            .map(|&limit| limit.saturating_sub(port.get_item_quantity(item))),
+        _ => Some(u32::MAX),
+    }
+}
 
-            if let Some(&limit) = item_limits.get(item_name) {
-                let current_in_port = input_ports
-                    .get(request.receiver)
-                    .map_or(0, |p| p.get_item_quantity(item_name));
-                let per_item_remaining = limit.saturating_sub(current_in_port);
-                final_quantity = final_quantity.min(per_item_remaining);
-            }
+/// Checks every requested item against the source's stock and reserve and
+/// the destination's filter, per-item limit and free space. The transfer is
+/// all or nothing: the first item, in id order, that cannot move in full
+/// rejects the whole request.
+fn validate_transfer_items(
+    requested: &HashMap<ItemId, u32>,
+    sender_items: &HashMap<ItemId, u32>,
+    reserve: Option<&ItemReserve>,
+    input_port: Option<&InputPort>,
+    free_space: u32,
+) -> Result<HashMap<ItemId, u32>, TransferError> {
+    let mut ordered: Vec<(ItemId, u32)> = requested
+        .iter()
+        .filter(|&(_, &qty)| qty > 0)
+        .map(|(&item, &qty)| (item, qty))
+        .collect();
+    ordered.sort_unstable();
+    if ordered.is_empty() {
+        return Err(TransferError::NotEnoughItems);
+    }
 
-            if final_quantity > 0 {
-                validated_transfer.insert(item_name.clone(), final_quantity);
-                current_receiver_total += final_quantity;
-            }
+    for &(item, quantity) in &ordered {
+        let available = sender_items.get(&item).copied().unwrap_or(0);
+        if available < quantity {
+            return Err(TransferError::NotEnoughItems);
         }
-
-        if validated_transfer.is_empty() {
-            let error = if request
-                .items
-                .iter()
-                .all(|(name, _)| sender_items.get(name).copied().unwrap_or(0) == 0)
-            {
-                TransferError::NotEnoughItems
-            } else {
-                TransferError::DestinationFull
-            };
-
-            validation_events.write(ItemTransferValidationEvent {
-                result: Err(error),
-                request: request.clone(),
-            });
-        } else {
-            validation_events.write(ItemTransferValidationEvent {
-                result: Ok(validated_transfer),
-                request: request.clone(),
-            });
+        let reserved = reserve.map_or(0, |reserve| reserve.of(item));
+        if available - quantity < reserved {
+            return Err(TransferError::BelowReserve(item));
         }
+        match item_room(input_port, item) {
+            None => return Err(TransferError::NotAccepted(item)),
+            Some(room) if room < quantity => return Err(TransferError::DestinationFull),
+            Some(_) => {}
+        }
+    }
+
+    let total = ordered
+        .iter()
+        .fold(0u32, |total, &(_, qty)| total.saturating_add(qty));
+    if total > free_space {
+        return Err(TransferError::DestinationFull);
     }
+    Ok(ordered.into_iter().collect())
+}
+
+/// Trims `items` to what a receiver with `free_space` left can take in one
+/// transfer: items its input port's filter rejects are dropped, each item is
+/// held to its per-item limit, and the rest fills the free space in id
+/// order. Dropoffs that may land only in part send this instead of their
+/// whole cargo.
+#[must_use]
+pub fn fit_transfer(
+    items: &HashMap<ItemId, u32>,
+    input_port: Option<&InputPort>,
+    free_space: u32,
+) -> HashMap<ItemId, u32> {
+    let accepted = items
+        .iter()
+        .filter_map(|(&item, &qty)| Some((item, qty.min(item_room(input_port, item)?))))
+        .filter(|&(_, qty)| qty > 0)
+        .collect();
+    cap_transfer_total(&accepted, free_space)
 }
 
+/// What a sender can give up: its outputs, storage or cargo, plus any
+/// outgoing items in its input port.
 fn get_sender_port_data(
     entity: Entity,
     output_ports: &Query<&OutputPort>,
+    named_output_ports: &Query<&NamedOutputPorts>,
     storage_ports: &Query<&StoragePort>,
     cargo_query: &Query<&Cargo>,
-) -> Option<(HashMap<ItemName, u32>, u32)> {
-    if let Ok(port) = output_ports.get(entity) {
-        return Some((port.items.clone(), port.capacity));
-    }
-    if let Ok(port) = storage_ports.get(entity) {
-        return Some((port.items.clone(), port.capacity));
-    }
-    if let Ok(cargo) = cargo_query.get(entity) {
-        return Some((cargo.items.clone(), cargo.capacity));
+    input_ports: &Query<&InputPort>,
+) -> Option<(HashMap<ItemId, u32>, u32)> {
+    let named = named_output_ports.get(entity).ok();
+    let held = if let Ok(port) = output_ports.get(entity) {
+        Some((combined_output_items(Some(port), named), port.capacity))
+    } else if let Some(named) = named {
+        let capacity = named.ports.values().map(|port| port.capacity).sum();
+        Some((named.get_all_items(), capacity))
+    } else if let Ok(port) = storage_ports.get(entity) {
+        Some((port.items.clone(), port.capacity))
+    } else if let Ok(cargo) = cargo_query.get(entity) {
+        Some((cargo.items.clone(), cargo.capacity))
+    } else {
+        None
+    };
+
+    let Some((input_port, releasable)) = input_ports
+        .get(entity)
+        .ok()
+        .map(|port| (port, port.releasable()))
+        .filter(|(_, releasable)| !releasable.is_empty())
+    else {
+        return held;
+    };
+    let (mut items, capacity) = held.unwrap_or((HashMap::new(), input_port.capacity));
+    for (item, qty) in releasable {
+        *items.entry(item).or_default() += qty;
     }
-    None
+    Some((items, capacity))
 }
 
 fn get_receiver_port_data(
@@ -412,6 +801,7 @@ fn get_receiver_port_data(
 pub fn execute_item_transfer(
     mut validation_events: MessageReader<ItemTransferValidationEvent>,
     mut output_ports: Query<&mut OutputPort>,
+    mut named_output_ports: Query<&mut NamedOutputPorts>,
     mut input_ports: Query<&mut InputPort>,
     mut storage_ports: Query<&mut StoragePort>,
     mut cargo_query: Query<&mut Cargo>,
@@ -435,25 +825,40 @@ pub fn execute_item_transfer(
 
         let mut actual_transfer = HashMap::new();
 
-        if let Ok(mut port) = output_ports.get_mut(sender) {
-            for (item_name, &quantity) in validated_items {
-                let removed = port.remove_item(item_name, quantity);
+        if output_ports.contains(sender) || named_output_ports.contains(sender) {
+            for (&item, &quantity) in validated_items {
+                let mut removed = output_ports
+                    .get_mut(sender)
+                    .map_or(0, |mut port| port.remove_item(item, quantity));
+                if let Ok(mut named) = named_output_ports.get_mut(sender) {
+                    removed += named.remove_item(item, quantity - removed);
+                }
                 if removed > 0 {
-                    actual_transfer.insert(item_name.clone(), removed);
+                    actual_transfer.insert(item, removed);
                 }
             }
         } else if let Ok(mut port) = storage_ports.get_mut(sender) {
-            for (item_name, &quantity) in validated_items {
-                let removed = port.remove_item(item_name, quantity);
+            for (&item, &quantity) in validated_items {
+                let removed = port.remove_item(item, quantity);
                 if removed > 0 {
-                    actual_transfer.insert(item_name.clone(), removed);
+                    actual_transfer.insert(item, removed);
                 }
             }
         } else if let Ok(mut cargo) = cargo_query.get_mut(sender) {
-            for (item_name, &quantity) in validated_items {
-                let removed = cargo.remove_item(item_name, quantity);
+            for (&item, &quantity) in validated_items {
+                let removed = cargo.remove_item(item, quantity);
                 if removed > 0 {
-                    actual_transfer.insert(item_name.clone(), removed);
+                    actual_transfer.insert(item, removed);
+                }
+            }
+        }
+
+        if let Ok(mut port) = input_ports.get_mut(sender) {
+            for (&item, &quantity) in validated_items {
+                let short = quantity - actual_transfer.get(&item).copied().unwrap_or(0);
+                let removed = port.release_item(item, short);
+                if removed > 0 {
+                    *actual_transfer.entry(item).or_default() += removed;
                 }
             }
         }
@@ -463,16 +868,16 @@ pub fn execute_item_transfer(
         }
 
         if let Ok(mut port) = input_ports.get_mut(receiver) {
-            for (item_name, &quantity) in &actual_transfer {
-                port.add_item(item_name, quantity);
+            for (&item, &quantity) in &actual_transfer {
+                port.add_item(item, quantity);
             }
         } else if let Ok(mut port) = storage_ports.get_mut(receiver) {
-            for (item_name, &quantity) in &actual_transfer {
-                port.add_item(item_name, quantity);
+            for (&item, &quantity) in &actual_transfer {
+                port.add_item(item, quantity);
             }
         } else if let Ok(mut cargo) = cargo_query.get_mut(receiver) {
-            for (item_name, &quantity) in &actual_transfer {
-                cargo.add_item(item_name, quantity);
+            for (&item, &quantity) in &actual_transfer {
+                cargo.add_item(item, quantity);
             }
         }
 
@@ -487,7 +892,7 @@ pub fn execute_item_transfer(
 pub fn request_transfer_specific_items(
     sender: Entity,
     receiver: Entity,
-    items: HashMap<ItemName, u32>,
+    items: HashMap<ItemId, u32>,
     transfer_events: &mut MessageWriter<ItemTransferRequestEvent>,
 ) {
     if !items.is_empty() {
@@ -499,6 +904,30 @@ pub fn request_transfer_specific_items(
     }
 }
 
+/// Trims `items` to `total_cap` in total, filling greedily in item order, for
+/// requests that must not move everything. Whatever exceeds the cap stays
+/// with the sender.
+#[must_use]
+pub fn cap_transfer_total(items: &HashMap<ItemId, u32>, total_cap: u32) -> HashMap<ItemId, u32> {
+    let mut ordered: Vec<(ItemId, u32)> = items.iter().map(|(&item, &qty)| (item, qty)).collect();
+    ordered.sort_unstable_by_key(|&(item, _)| item);
+
+    let mut remaining = total_cap;
+    let mut capped = HashMap::new();
+    for (item, quantity) in ordered {
+        if remaining == 0 {
+            break;
+        }
+        let take = quantity.min(remaining);
+        if take > 0 {
+            capped.insert(item, take);
+            remaining -= take;
+        }
+    }
+
+    capped
+}
+
 fn setup(mut commands: Commands) {
     if let Ok(registry) = ItemRegistry::load_from_assets() {
         commands.insert_resource(registry);
@@ -518,6 +947,7 @@ mod tests {
     #![allow(clippy::unwrap_used)]
 
     use super::*;
+    use bevy::ecs::system::RunSystemOnce;
 
     #[test]
     fn test_item_registry_from_ron_valid() {
@@ -533,8 +963,12 @@ mod tests {
         ]"#;
         let registry = ItemRegistry::from_ron(ron_content).unwrap();
         assert_eq!(registry.definitions.len(), 2);
-        assert!(registry.definitions.contains_key("Test Item"));
-        assert!(registry.definitions.contains_key("Another Item"));
+        assert!(registry
+            .definitions
+            .contains_key(&ItemId::named("Test Item").unwrap()));
+        assert!(registry
+            .definitions
+            .contains_key(&ItemId::named("Another Item").unwrap()));
     }
 
     #[test]
@@ -571,21 +1005,159 @@ mod tests {
             ),
         ]"#;
         let registry = ItemRegistry::from_ron(ron_content).unwrap();
-        let def = registry.get_definition("Test Item");
+        let def = registry.get_definition(registry.id_of("Test Item").unwrap());
         assert!(def.is_some());
         let def = def.unwrap();
         assert_eq!(def.name, "Test Item");
         assert_eq!(def.tier, 3);
     }
 
+    #[test]
+    fn test_rare_item_defaults_stack_size_and_color_from_tier() {
+        let ron_content = r#"[
+            (
+                name: "Rare Item",
+                tier: 2,
+            ),
+            (
+                name: "Stacked Item",
+                tier: 2,
+                stack_size: Some(7),
+            ),
+        ]"#;
+        let registry = ItemRegistry::from_ron(ron_content).unwrap();
+
+        let rare = registry
+            .get_definition(registry.id_of("Rare Item").unwrap())
+            .unwrap();
+        assert_eq!(rare.rarity(), ItemRarity::Rare);
+        assert_eq!(rare.stack_size(), ItemRarity::Rare.default_stack_size());
+        assert_eq!(rare.rarity().color(), Color::srgb(0.4, 0.6, 1.0));
+
+        let stacked = registry
+            .get_definition(registry.id_of("Stacked Item").unwrap())
+            .unwrap();
+        assert_eq!(stacked.stack_size(), 7);
+
+        assert_eq!(
+            registry.rarity_of(ItemId::named("Gearbox").unwrap()),
+            ItemRarity::Common
+        );
+    }
+
+    #[test]
+    fn value_of_prefers_defined_value_over_tier_default() {
+        let ron_content = r#"[
+            (
+                name: "Priced Item",
+                tier: 0,
+                value: Some(12),
+            ),
+            (
+                name: "Tiered Item",
+                tier: 2,
+            ),
+        ]"#;
+        let registry = ItemRegistry::from_ron(ron_content).unwrap();
+
+        assert_eq!(registry.value_of(ItemId::named("Priced Item").unwrap()), 12);
+        assert_eq!(registry.value_of(ItemId::named("Tiered Item").unwrap()), 9);
+        assert_eq!(registry.value_of(ItemId::named("Gearbox").unwrap()), 1);
+    }
+
     #[test]
     fn test_item_registry_get_definition_nonexistent() {
         let ron_content = "[]";
         let registry = ItemRegistry::from_ron(ron_content).unwrap();
-        let def = registry.get_definition("Nonexistent");
+        assert!(registry.id_of("Nonexistent").is_none());
+        let def = registry.get_definition(ItemId::named("Iron Ore").unwrap());
         assert!(def.is_none());
     }
 
+    #[test]
+    fn item_id_round_trips_through_registry() {
+        let ron_content = r#"[
+            (
+                name: "Round Trip Ore",
+                tier: 0,
+            ),
+            (
+                name: "Round Trip Plate",
+                tier: 1,
+            ),
+        ]"#;
+        let registry = ItemRegistry::from_ron(ron_content).unwrap();
+
+        let ore = registry.id_of("Round Trip Ore").unwrap();
+        let plate = registry.id_of("Round Trip Plate").unwrap();
+        assert_ne!(ore, plate);
+        assert_eq!(ore, ItemId::named("Round Trip Ore").unwrap());
+        assert_eq!(registry.name_of(ore), Some("Round Trip Ore"));
+        assert_eq!(registry.name_of(plate), Some("Round Trip Plate"));
+        assert_eq!(registry.display_name(plate), "Round Trip Plate");
+
+        // Defined by another registry, but not this one.
+        let stray = ItemId::named("Iron Ore").unwrap();
+        assert!(registry.id_of("Iron Ore").is_none());
+        assert_eq!(registry.name_of(stray), None);
+        assert_eq!(registry.display_name(stray), "Unknown");
+    }
+
+    #[test]
+    fn unregistered_names_have_no_id() {
+        assert!(ItemId::named("Never Registered Ore").is_none());
+    }
+
+    #[test]
+    fn duplicate_item_names_are_rejected() {
+        let result = ItemRegistry::from_ron(
+            r#"[(name: "Duplicate Ore", tier: 0), (name: "Duplicate Ore", tier: 1)]"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn item_id_formats_and_serializes_as_its_name() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        assert_eq!(ore.to_string(), "Iron Ore");
+        assert_eq!(format!("{ore:?}"), r#"ItemId("Iron Ore")"#);
+        assert_eq!(ron::to_string(&ore).unwrap(), r#""Iron Ore""#);
+
+        let items: HashMap<ItemId, u32> = ron::from_str(r#"{"Iron Ore": 3}"#).unwrap();
+        assert_eq!(items.get(&ore), Some(&3));
+        assert!(ron::from_str::<ItemId>(r#""Never Registered Ore""#).is_err());
+    }
+
+    #[test]
+    fn transfer_errors_name_the_item() {
+        let coal = ItemId::named("Coal").unwrap();
+        assert_eq!(
+            TransferError::NotAccepted(coal).to_string(),
+            "Coal is not accepted by the destination!"
+        );
+    }
+
+    #[test]
+    fn inventory_operations_key_by_item_id() {
+        let iron = ItemId::named("Iron Ore").unwrap();
+        let coal = ItemId::named("Coal").unwrap();
+        let mut port = InputPort::new(20);
+
+        port.add_item(iron, 4);
+        port.add_item(coal, 2);
+        port.add_item(iron, 1);
+        assert_eq!(port.get_item_quantity(iron), 5);
+        assert_eq!(port.get_total_quantity(), 7);
+
+        let recipe = HashMap::from([(iron, 3), (coal, 2)]);
+        assert!(port.consume_recipe_inputs(&recipe));
+        assert_eq!(port.get_item_quantity(iron), 2);
+        assert!(!port.items.contains_key(&coal));
+
+        assert_eq!(port.remove_item(iron, 10), 2);
+        assert!(port.is_empty());
+    }
+
     #[test]
     fn test_transfer_error_display_item_not_found() {
         let error = TransferError::ItemNotFound;
@@ -607,21 +1179,21 @@ mod tests {
     #[test]
     fn test_is_full_under_capacity() {
         let mut storage = StoragePort::new(100);
-        storage.add_item("iron", 50);
+        storage.add_item(ItemId::named("Iron Ingot").unwrap(), 50);
         assert!(!storage.is_full());
     }
 
     #[test]
     fn test_is_full_at_capacity() {
         let mut storage = StoragePort::new(100);
-        storage.add_item("iron", 100);
+        storage.add_item(ItemId::named("Iron Ingot").unwrap(), 100);
         assert!(storage.is_full());
     }
 
     #[test]
     fn test_is_full_over_capacity() {
         let mut storage = StoragePort::new(100);
-        storage.add_item("iron", 101);
+        storage.add_item(ItemId::named("Iron Ingot").unwrap(), 101);
         assert!(storage.is_full());
     }
 
@@ -640,19 +1212,348 @@ mod tests {
     #[test]
     fn input_port_item_limits_can_be_set() {
         let mut port = InputPort::new(50);
-        port.item_limits.insert("Iron Ore".to_string(), 34);
-        port.item_limits.insert("Coal".to_string(), 17);
-
-        assert_eq!(port.item_limits.get("Iron Ore").copied().unwrap(), 34);
-        assert_eq!(port.item_limits.get("Coal").copied().unwrap(), 17);
+        port.item_limits
+            .insert(ItemId::named("Iron Ore").unwrap(), 34);
+        port.item_limits.insert(ItemId::named("Coal").unwrap(), 17);
+
+        assert_eq!(
+            port.item_limits
+                .get(&ItemId::named("Iron Ore").unwrap())
+                .copied()
+                .unwrap(),
+            34
+        );
+        assert_eq!(
+            port.item_limits
+                .get(&ItemId::named("Coal").unwrap())
+                .copied()
+                .unwrap(),
+            17
+        );
         assert_eq!(port.item_limits.len(), 2);
     }
 
     #[test]
     fn input_port_item_limits_unlisted_item_defaults_to_zero() {
         let mut port = InputPort::new(50);
-        port.item_limits.insert("Iron Ore".to_string(), 34);
+        port.item_limits
+            .insert(ItemId::named("Iron Ore").unwrap(), 34);
+
+        assert_eq!(
+            port.item_limits
+                .get(&ItemId::named("Coal").unwrap())
+                .copied()
+                .unwrap_or(0),
+            0
+        );
+    }
+
+    #[derive(Resource, Default)]
+    struct ValidationResults(Vec<Result<HashMap<ItemId, u32>, TransferError>>);
+
+    fn record_validations(
+        mut events: MessageReader<ItemTransferValidationEvent>,
+        mut results: ResMut<ValidationResults>,
+    ) {
+        results
+            .0
+            .extend(events.read().map(|event| event.result.clone()));
+    }
+
+    /// Sends `quantity` of `item` from a storage holding `stock` (keeping
+    /// `reserve` back) into `receiver`, and returns the validation result and
+    /// the storage's stock afterwards.
+    fn transfer_from_storage(
+        item: ItemId,
+        stock: u32,
+        reserve: u32,
+        receiver: InputPort,
+        quantity: u32,
+    ) -> (Result<HashMap<ItemId, u32>, TransferError>, u32) {
+        let mut app = App::new();
+        app.add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .init_resource::<ValidationResults>()
+            .add_systems(
+                Update,
+                (
+                    validate_item_transfer,
+                    record_validations,
+                    execute_item_transfer,
+                )
+                    .chain(),
+            );
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(item, stock);
+        let reserve = ItemReserve {
+            items: HashMap::from([(item, reserve)]),
+        };
+        let sender = app.world_mut().spawn((storage, reserve)).id();
+        let receiver = app.world_mut().spawn(receiver).id();
+        app.world_mut().write_message(ItemTransferRequestEvent {
+            sender,
+            receiver,
+            items: HashMap::from([(item, quantity)]),
+        });
+        app.update();
+
+        let results = &app.world().resource::<ValidationResults>().0;
+        assert_eq!(results.len(), 1);
+        let remaining = app
+            .world()
+            .get::<StoragePort>(sender)
+            .unwrap()
+            .get_item_quantity(item);
+        (results[0].clone(), remaining)
+    }
 
-        assert_eq!(port.item_limits.get("Coal").copied().unwrap_or(0), 0);
+    #[test]
+    fn transfer_never_draws_the_source_below_its_reserve() {
+        let plate = ItemId::named("Iron Plate").unwrap();
+
+        let (result, remaining) = transfer_from_storage(plate, 10, 10, InputPort::new(50), 5);
+        assert_eq!(result.unwrap_err(), TransferError::BelowReserve(plate));
+        assert_eq!(remaining, 10);
+
+        let (result, remaining) = transfer_from_storage(plate, 10, 6, InputPort::new(50), 10);
+        assert_eq!(result.unwrap_err(), TransferError::BelowReserve(plate));
+        assert_eq!(remaining, 10);
+
+        let (result, remaining) = transfer_from_storage(plate, 10, 6, InputPort::new(50), 4);
+        assert_eq!(result.unwrap(), HashMap::from([(plate, 4)]));
+        assert_eq!(remaining, 6);
+    }
+
+    #[test]
+    fn marked_receivers_draw_on_the_reserve() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+
+        let mut app = App::new();
+        app.add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .add_systems(
+                Update,
+                (validate_item_transfer, execute_item_transfer).chain(),
+            );
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ore, 10);
+        let reserve = ItemReserve {
+            items: HashMap::from([(ore, 10)]),
+        };
+        let hub = app.world_mut().spawn((storage, reserve)).id();
+        let crafter = app.world_mut().spawn(InputPort::new(50)).id();
+        let site = app
+            .world_mut()
+            .spawn((InputPort::new(50), DrawsOnReserves))
+            .id();
+        for receiver in [crafter, site] {
+            app.world_mut().write_message(ItemTransferRequestEvent {
+                sender: hub,
+                receiver,
+                items: HashMap::from([(ore, 4)]),
+            });
+        }
+        app.update();
+
+        let held = |entity| {
+            app.world()
+                .get::<InputPort>(entity)
+                .unwrap()
+                .get_item_quantity(ore)
+        };
+        assert_eq!(held(crafter), 0);
+        assert_eq!(held(site), 4);
+    }
+
+    #[test]
+    fn only_outgoing_inputs_are_drawn_from_an_input_port() {
+        let coal = ItemId::named("Coal").unwrap();
+        let ore = ItemId::named("Iron Ore").unwrap();
+
+        let mut app = App::new();
+        app.add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .add_systems(
+                Update,
+                (validate_item_transfer, execute_item_transfer).chain(),
+            );
+
+        let mut input_port = InputPort::new(50);
+        input_port.add_item(coal, 6);
+        input_port.add_item(ore, 5);
+        input_port.outgoing.insert(coal, 4);
+        let crafter = app
+            .world_mut()
+            .spawn((input_port, OutputPort::new(10)))
+            .id();
+        let worker = app.world_mut().spawn(Cargo::new(20)).id();
+        for items in [HashMap::from([(coal, 4)]), HashMap::from([(ore, 5)])] {
+            app.world_mut().write_message(ItemTransferRequestEvent {
+                sender: crafter,
+                receiver: worker,
+                items,
+            });
+        }
+        app.update();
+
+        let cargo = app.world().get::<Cargo>(worker).unwrap();
+        assert_eq!(cargo.items, HashMap::from([(coal, 4)]));
+        let input_port = app.world().get::<InputPort>(crafter).unwrap();
+        assert_eq!(input_port.get_item_quantity(coal), 2);
+        assert_eq!(input_port.get_item_quantity(ore), 5);
+        assert!(input_port.outgoing.is_empty());
+    }
+
+    #[test]
+    fn transfer_is_rejected_when_the_destination_filter_excludes_the_item() {
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let mut receiver = InputPort::new(50);
+        receiver
+            .item_limits
+            .insert(ItemId::named("Coal").unwrap(), 50);
+
+        let (result, remaining) = transfer_from_storage(plate, 10, 0, receiver, 5);
+        assert_eq!(result.unwrap_err(), TransferError::NotAccepted(plate));
+        assert_eq!(remaining, 10);
+    }
+
+    #[test]
+    fn transfer_is_rejected_when_the_destination_is_full() {
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let mut receiver = InputPort::new(20);
+        receiver.add_item(ItemId::named("Coal").unwrap(), 20);
+
+        let (result, remaining) = transfer_from_storage(plate, 10, 0, receiver, 5);
+        assert_eq!(result.unwrap_err(), TransferError::DestinationFull);
+        assert_eq!(remaining, 10);
+    }
+
+    #[test]
+    fn transfer_is_rejected_whole_when_one_item_does_not_fit() {
+        let coal = ItemId::named("Coal").unwrap();
+        let plate = ItemId::named("Iron Plate").unwrap();
+
+        let mut app = App::new();
+        app.add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .init_resource::<ValidationResults>()
+            .add_systems(
+                Update,
+                (
+                    validate_item_transfer,
+                    record_validations,
+                    execute_item_transfer,
+                )
+                    .chain(),
+            );
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(coal, 10);
+        storage.add_item(plate, 10);
+        let sender = app.world_mut().spawn(storage).id();
+        let mut receiver = InputPort::new(50);
+        receiver.item_limits.insert(coal, 20);
+        receiver.item_limits.insert(plate, 5);
+        let receiver = app.world_mut().spawn(receiver).id();
+        app.world_mut().write_message(ItemTransferRequestEvent {
+            sender,
+            receiver,
+            items: HashMap::from([(coal, 10), (plate, 10)]),
+        });
+        app.update();
+
+        let results = &app.world().resource::<ValidationResults>().0;
+        assert_eq!(results, &vec![Err(TransferError::DestinationFull)]);
+        let receiver = app.world().get::<InputPort>(receiver).unwrap();
+        assert!(receiver.is_empty());
+        let storage = app.world().get::<StoragePort>(sender).unwrap();
+        assert_eq!(storage.get_total_quantity(), 20);
+    }
+
+    #[test]
+    fn fit_transfer_keeps_what_the_receiver_can_take() {
+        let coal = ItemId::named("Coal").unwrap();
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let wire = ItemId::named("Copper Wire").unwrap();
+        let mut receiver = InputPort::new(50);
+        receiver.item_limits.insert(coal, 20);
+        receiver.item_limits.insert(plate, 5);
+        let cargo = HashMap::from([(coal, 10), (plate, 10), (wire, 10)]);
+
+        assert_eq!(
+            fit_transfer(&cargo, Some(&receiver), 50),
+            HashMap::from([(coal, 10), (plate, 5)])
+        );
+        assert_eq!(
+            fit_transfer(&cargo, Some(&receiver), 12),
+            cap_transfer_total(&HashMap::from([(coal, 10), (plate, 5)]), 12)
+        );
+        assert_eq!(fit_transfer(&cargo, None, 30), cargo);
+    }
+
+    #[test]
+    fn transfer_passing_every_constraint_moves_the_requested_items() {
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let mut receiver = InputPort::new(50);
+        receiver.item_limits.insert(plate, 20);
+
+        let (result, remaining) = transfer_from_storage(plate, 30, 5, receiver, 15);
+        assert_eq!(result.unwrap(), HashMap::from([(plate, 15)]));
+        assert_eq!(remaining, 15);
+    }
+
+    #[test]
+    fn capped_transfer_tops_up_a_partially_loaded_worker() {
+        let coal = ItemId::named("Coal").unwrap();
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let wire = ItemId::named("Copper Wire").unwrap();
+
+        let mut app = App::new();
+        app.add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .add_systems(
+                Update,
+                (validate_item_transfer, execute_item_transfer).chain(),
+            );
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(plate, 5);
+        storage.add_item(wire, 5);
+        let source = app.world_mut().spawn(storage).id();
+        let mut cargo = Cargo::new(10);
+        cargo.add_item(coal, 6);
+        let worker = app.world_mut().spawn(cargo).id();
+
+        app.world_mut()
+            .run_system_once(
+                move |cargo: Query<&Cargo>,
+                      storage: Query<&StoragePort>,
+                      mut transfer_events: MessageWriter<ItemTransferRequestEvent>| {
+                    let cargo = cargo.get(worker).unwrap();
+                    let space = cargo.capacity().saturating_sub(cargo.get_total_quantity());
+                    let available = storage.get(source).unwrap().get_all_items();
+                    request_transfer_specific_items(
+                        source,
+                        worker,
+                        cap_transfer_total(&available, space),
+                        &mut transfer_events,
+                    );
+                },
+            )
+            .unwrap();
+        app.update();
+
+        let cargo = app.world().get::<Cargo>(worker).unwrap();
+        assert_eq!(cargo.get_total_quantity(), 10);
+        assert_eq!(cargo.get_item_quantity(coal), 6);
+        let storage = app.world().get::<StoragePort>(source).unwrap();
+        assert_eq!(storage.get_total_quantity(), 6);
     }
 }
@@ -4,9 +4,11 @@ pub mod items;
 pub mod recipes;
 
 pub use items::{
-    execute_item_transfer, request_transfer_specific_items, validate_item_transfer, Cargo,
-    InputPort, InventoryAccess, ItemName, ItemRegistry, ItemTransferEvent,
-    ItemTransferRequestEvent, ItemTransferValidationEvent, OutputPort, StoragePort,
+    cap_transfer_total, combined_output_items, drawable_stock, execute_item_transfer, fit_transfer,
+    request_transfer_specific_items, validate_item_transfer, Cargo, DrawsOnReserves, InputPort,
+    InventoryAccess, ItemId, ItemRarity, ItemRegistry, ItemReserve, ItemTransferEvent,
+    ItemTransferRequestEvent, ItemTransferValidationEvent, NamedOutputPorts, OutputPort,
+    StoragePort,
 };
 pub use recipes::{RecipeDef, RecipeName, RecipeRegistry};
 
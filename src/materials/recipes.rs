@@ -1,4 +1,4 @@
-use crate::materials::items::ItemName;
+use crate::materials::items::ItemId;
 use bevy::prelude::*;
 use ron;
 use serde::{Deserialize, Serialize};
@@ -6,14 +6,35 @@ use std::collections::HashMap;
 
 pub type RecipeName = String;
 
+/// Shortest craft a loaded recipe may have, so a zero timer cannot complete
+/// every frame.
+pub const MIN_CRAFTING_TIME: f32 = 0.1;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RecipeDef {
     pub name: String,
-    pub inputs: HashMap<ItemName, u32>,
-    pub outputs: HashMap<ItemName, u32>,
+    pub inputs: HashMap<ItemId, u32>,
+    pub outputs: HashMap<ItemId, u32>,
+    /// Outputs routed to a tagged port in `NamedOutputPorts` instead of the
+    /// building's default `OutputPort`.
+    #[serde(default)]
+    pub output_ports: HashMap<ItemId, String>,
+    /// Items that must be present to craft but are never consumed.
+    #[serde(default)]
+    pub catalysts: HashMap<ItemId, u32>,
+    /// Seconds per craft. Missing or non-positive values are raised to
+    /// `MIN_CRAFTING_TIME` on load.
+    #[serde(default)]
     pub crafting_time: f32,
 }
 
+impl RecipeDef {
+    /// True if the item is one of the recipe's inputs or catalysts.
+    pub fn uses_item(&self, item: ItemId) -> bool {
+        self.inputs.contains_key(&item) || self.catalysts.contains_key(&item)
+    }
+}
+
 #[derive(Clone)]
 #[allow(dead_code)] // TODO: Dynamic recipes
 pub enum RecipeType {
@@ -32,7 +53,15 @@ impl RecipeRegistry {
 
         let mut definitions = HashMap::new();
 
-        for def in definitions_vec {
+        for mut def in definitions_vec {
+            if def.crafting_time.is_nan() || def.crafting_time < MIN_CRAFTING_TIME {
+                warn!(
+                    recipe = def.name,
+                    crafting_time = def.crafting_time,
+                    "recipe crafting time below minimum, clamping to {MIN_CRAFTING_TIME}s"
+                );
+                def.crafting_time = MIN_CRAFTING_TIME;
+            }
             definitions.insert(def.name.clone(), def);
         }
 
@@ -52,11 +81,11 @@ impl RecipeRegistry {
         self.definitions.get(recipe_name)
     }
 
-    pub fn get_inputs(&self, recipe_name: &str) -> Option<&HashMap<ItemName, u32>> {
+    pub fn get_inputs(&self, recipe_name: &str) -> Option<&HashMap<ItemId, u32>> {
         self.definitions.get(recipe_name).map(|def| &def.inputs)
     }
 
-    pub fn get_outputs(&self, recipe_name: &str) -> Option<&HashMap<ItemName, u32>> {
+    pub fn get_outputs(&self, recipe_name: &str) -> Option<&HashMap<ItemId, u32>> {
         self.definitions.get(recipe_name).map(|def| &def.outputs)
     }
 }
@@ -66,6 +95,44 @@ mod tests {
     #![allow(clippy::unwrap_used, clippy::float_cmp)]
 
     use super::*;
+    use crate::materials::ItemRegistry;
+
+    /// Registers the made-up items the recipes below are written against.
+    fn register_test_items() {
+        let names = [
+            "Item1",
+            "Item2",
+            "Item3",
+            "Item A",
+            "Item B",
+            "Result",
+            "Input",
+            "Output",
+            "Output A",
+            "Output B",
+            "Raw Material",
+            "Product",
+        ];
+        let ron_content = names
+            .iter()
+            .map(|name| format!("(name: {name:?}, tier: 0)"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        ItemRegistry::from_ron(&format!("[{ron_content}]")).unwrap();
+    }
+
+    #[test]
+    fn recipes_naming_unknown_items_are_rejected() {
+        let ron_content = r#"[
+            (
+                name: "Mystery Recipe",
+                inputs: {"Never Registered Ore": 1},
+                outputs: {"Iron Ingot": 1},
+                crafting_time: 1.0,
+            ),
+        ]"#;
+        assert!(RecipeRegistry::from_ron(ron_content).is_err());
+    }
 
     #[test]
     fn test_recipe_registry_from_ron_valid() {
@@ -105,6 +172,7 @@ mod tests {
                 crafting_time: 2.5,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         assert_eq!(registry.definitions.len(), 2);
         assert!(registry.definitions.contains_key("Recipe A"));
@@ -146,8 +214,14 @@ mod tests {
         let def = def.unwrap();
         assert_eq!(def.name, "Test Recipe");
         assert_eq!(def.crafting_time, 3.5);
-        assert_eq!(def.inputs.get("Iron Ore"), Some(&2));
-        assert_eq!(def.outputs.get("Iron Ingot"), Some(&1));
+        assert_eq!(
+            def.inputs.get(&ItemId::named("Iron Ore").unwrap()),
+            Some(&2)
+        );
+        assert_eq!(
+            def.outputs.get(&ItemId::named("Iron Ingot").unwrap()),
+            Some(&1)
+        );
     }
 
     #[test]
@@ -168,13 +242,14 @@ mod tests {
                 crafting_time: 1.0,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         let inputs = registry.get_inputs("Multi Input Recipe");
         assert!(inputs.is_some());
         let inputs = inputs.unwrap();
         assert_eq!(inputs.len(), 2);
-        assert_eq!(inputs.get("Item A"), Some(&3));
-        assert_eq!(inputs.get("Item B"), Some(&5));
+        assert_eq!(inputs.get(&ItemId::named("Item A").unwrap()), Some(&3));
+        assert_eq!(inputs.get(&ItemId::named("Item B").unwrap()), Some(&5));
     }
 
     #[test]
@@ -187,6 +262,7 @@ mod tests {
                 crafting_time: 1.0,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         let inputs = registry.get_inputs("No Input Recipe");
         assert!(inputs.is_some());
@@ -212,13 +288,14 @@ mod tests {
                 crafting_time: 1.0,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         let outputs = registry.get_outputs("Multi Output Recipe");
         assert!(outputs.is_some());
         let outputs = outputs.unwrap();
         assert_eq!(outputs.len(), 2);
-        assert_eq!(outputs.get("Output A"), Some(&2));
-        assert_eq!(outputs.get("Output B"), Some(&3));
+        assert_eq!(outputs.get(&ItemId::named("Output A").unwrap()), Some(&2));
+        assert_eq!(outputs.get(&ItemId::named("Output B").unwrap()), Some(&3));
     }
 
     #[test]
@@ -231,6 +308,7 @@ mod tests {
                 crafting_time: 1.0,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         let outputs = registry.get_outputs("No Output Recipe");
         assert!(outputs.is_some());
@@ -256,6 +334,7 @@ mod tests {
                 crafting_time: 4.5,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         let def = registry.get_definition("Full Recipe").unwrap();
 
@@ -263,8 +342,10 @@ mod tests {
         assert_eq!(def.inputs.len(), 1);
         assert_eq!(def.outputs.len(), 1);
         assert!((def.crafting_time - 4.5).abs() < f32::EPSILON);
-        assert!(def.inputs.contains_key("Raw Material"));
-        assert!(def.outputs.contains_key("Product"));
+        assert!(def
+            .inputs
+            .contains_key(&ItemId::named("Raw Material").unwrap()));
+        assert!(def.outputs.contains_key(&ItemId::named("Product").unwrap()));
     }
 
     #[test]
@@ -277,8 +358,9 @@ mod tests {
                 crafting_time: 0.0,
             ),
         ]"#;
+        register_test_items();
         let registry = RecipeRegistry::from_ron(ron_content).unwrap();
         let def = registry.get_definition("Instant Recipe").unwrap();
-        assert!((def.crafting_time).abs() < f32::EPSILON);
+        assert_eq!(def.crafting_time, MIN_CRAFTING_TIME);
     }
 }
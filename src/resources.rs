@@ -5,9 +5,9 @@ use crate::{
     },
     grid::{CellChildren, Grid, Layer, NewCellEvent, Position},
     materials::RecipeName,
+    rng::GameRng,
 };
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
 
 #[derive(Component)]
 pub struct ResourceNode;
@@ -40,9 +40,8 @@ const IRON_ORE_PROBABILITY: f32 = 0.4;
 const COPPER_ORE_PROBABILITY: f32 = 0.3;
 // const COAL_PROBABILITY: f32 = 0.3;
 
-fn select_random_ore() -> (RecipeName, Color) {
-    let mut rng = thread_rng();
-    let roll = rng.gen::<f32>();
+fn select_random_ore(rng: &mut GameRng) -> (RecipeName, Color) {
+    let roll = rng.roll();
 
     if roll < IRON_ORE_PROBABILITY {
         (IRON_ORE.to_string(), Color::srgb(0.2, 0.3, 0.5))
@@ -60,9 +59,10 @@ pub fn spawn_resource_node(
     grid: Res<Grid>,
     mut cell_event: MessageReader<NewCellEvent>,
     mut grid_cells: Query<(Entity, &Position, &mut CellChildren)>,
+    mut rng: ResMut<GameRng>,
 ) {
     for event in cell_event.read() {
-        let spawn_resource = thread_rng().gen::<f32>() < 0.038;
+        let spawn_resource = rng.chance(0.038);
         let world_pos = grid.grid_to_world_coordinates(event.x, event.y);
 
         if !spawn_resource {
@@ -73,11 +73,11 @@ pub fn spawn_resource_node(
             .iter_mut()
             .find(|(_, pos, _)| pos.x == event.x && pos.y == event.y)
         else {
-            eprintln!("could not find cell at ({}, {})", event.x, event.y);
+            warn!(x = event.x, y = event.y, "could not find cell");
             continue;
         };
 
-        let (recipe_name, color) = select_random_ore();
+        let (recipe_name, color) = select_random_ore(&mut rng);
 
         let resource_node = commands
             .spawn(ResourceNodeBundle::new(
@@ -0,0 +1,183 @@
+use crate::{
+    materials::ItemRegistry,
+    structures::{Building, CraftHistory},
+    systems::ItemThroughput,
+    workers::Worker,
+};
+use bevy::prelude::*;
+use std::{collections::HashMap, fmt, time::Duration};
+
+/// Counters kept for the whole session and summarized on exit. Items produced
+/// come from `ItemThroughput`, which already sees every craft.
+#[derive(Resource, Debug, Default)]
+pub struct SessionMetrics {
+    pub crafts: u64,
+    pub peak_workers: usize,
+    pub buildings_placed: u32,
+    pub buildings_removed: u32,
+    /// Last `CraftHistory::total` seen per building.
+    craft_totals: HashMap<Entity, u64>,
+}
+
+impl SessionMetrics {
+    #[must_use]
+    pub fn summary(
+        &self,
+        throughput: &ItemThroughput,
+        items: &ItemRegistry,
+        duration: Duration,
+    ) -> SessionSummary {
+        let mut produced: Vec<(String, u64)> = throughput
+            .produced_total()
+            .map(|(item, qty)| (items.display_name(item).to_string(), qty))
+            .collect();
+        produced.sort_unstable_by(|(a, qa), (b, qb)| qb.cmp(qa).then_with(|| a.cmp(b)));
+        SessionSummary {
+            duration,
+            crafts: self.crafts,
+            peak_workers: self.peak_workers,
+            buildings_placed: self.buildings_placed,
+            buildings_removed: self.buildings_removed,
+            produced,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub duration: Duration,
+    pub crafts: u64,
+    pub peak_workers: usize,
+    pub buildings_placed: u32,
+    pub buildings_removed: u32,
+    /// Most produced first.
+    pub produced: Vec<(String, u64)>,
+}
+
+impl fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.duration.as_secs();
+        writeln!(f, "Session lasted {:02}:{:02}", secs / 60, secs % 60)?;
+        writeln!(f, "Crafts: {}", self.crafts)?;
+        writeln!(f, "Peak workers: {}", self.peak_workers)?;
+        writeln!(
+            f,
+            "Buildings placed: {}, removed: {}",
+            self.buildings_placed, self.buildings_removed
+        )?;
+        write!(f, "Produced:")?;
+        if self.produced.is_empty() {
+            write!(f, " nothing")?;
+        }
+        for (item, qty) in &self.produced {
+            write!(f, "\n  {item}: {qty}")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn track_session_metrics(
+    mut metrics: ResMut<SessionMetrics>,
+    histories: Query<(Entity, &CraftHistory), Changed<CraftHistory>>,
+    placed: Query<(), Added<Building>>,
+    mut removed: RemovedComponents<Building>,
+    workers: Query<(), With<Worker>>,
+) {
+    for (entity, history) in &histories {
+        let last = metrics.craft_totals.insert(entity, history.total);
+        metrics.crafts += history.total.saturating_sub(last.unwrap_or(0));
+    }
+
+    let placed = u32::try_from(placed.iter().count()).unwrap_or(u32::MAX);
+    metrics.buildings_placed = metrics.buildings_placed.saturating_add(placed);
+    for entity in removed.read() {
+        metrics.craft_totals.remove(&entity);
+        metrics.buildings_removed += 1;
+    }
+
+    metrics.peak_workers = metrics.peak_workers.max(workers.iter().count());
+}
+
+/// Logs the summary once the app is asked to exit. Runs in `Last` so it sees
+/// an exit requested anywhere earlier in the frame.
+pub fn log_session_summary(
+    mut exits: MessageReader<AppExit>,
+    metrics: Res<SessionMetrics>,
+    throughput: Res<ItemThroughput>,
+    items: Res<ItemRegistry>,
+    time: Res<Time<Virtual>>,
+) {
+    if exits.read().count() == 0 {
+        return;
+    }
+    info!("{}", metrics.summary(&throughput, &items, time.elapsed()));
+}
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionMetrics>()
+            .add_systems(Update, track_session_metrics)
+            .add_systems(Last, log_session_summary);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::materials::{ItemId, RecipeRegistry};
+
+    #[test]
+    fn summary_reflects_the_session_activity() {
+        let mut app = App::new();
+        app.add_message::<AppExit>()
+            .init_resource::<Time<Virtual>>()
+            .init_resource::<ItemThroughput>()
+            .insert_resource(ItemRegistry::from_ron(r#"[(name: "Iron Ingot", tier: 1)]"#).unwrap())
+            .add_plugins(SessionPlugin);
+
+        let registry = RecipeRegistry::from_ron(
+            r#"[(name: "Iron Ingot", inputs: {}, outputs: {"Iron Ingot": 1})]"#,
+        )
+        .unwrap();
+        let recipe = registry.get_definition("Iron Ingot").unwrap();
+        let smelter = app
+            .world_mut()
+            .spawn((Building, CraftHistory::default()))
+            .id();
+        let drill = app.world_mut().spawn(Building).id();
+        app.world_mut().spawn_batch([Worker, Worker, Worker]);
+        app.update();
+
+        let ingot = ItemId::named("Iron Ingot").unwrap();
+        for _ in 0..2 {
+            app.world_mut()
+                .get_mut::<CraftHistory>(smelter)
+                .unwrap()
+                .record(0.0, recipe);
+            app.world_mut()
+                .resource_mut::<ItemThroughput>()
+                .record_produced(ingot, 1);
+            app.update();
+        }
+        app.world_mut().despawn(drill);
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+
+        let metrics = app.world().resource::<SessionMetrics>();
+        assert_eq!(metrics.crafts, 2);
+        assert_eq!(metrics.peak_workers, 3);
+        assert_eq!(metrics.buildings_placed, 2);
+        assert_eq!(metrics.buildings_removed, 1);
+
+        let summary = metrics.summary(
+            app.world().resource::<ItemThroughput>(),
+            app.world().resource::<ItemRegistry>(),
+            Duration::ZERO,
+        );
+        assert_eq!(summary.produced, vec![("Iron Ingot".to_string(), 2)]);
+        assert!(summary.to_string().contains("Iron Ingot: 2"));
+    }
+}
@@ -1,14 +1,18 @@
 use ron;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub use crate::{
     grid::{Layer, Position},
-    materials::items::{InputPort, OutputPort, StoragePort},
+    materials::items::{InputPort, NamedOutputPorts, OutputPort, StoragePort},
     structures::*,
     systems::Operational,
 };
-use crate::{materials::RecipeDef, systems::Scanner};
+use crate::{
+    materials::{ItemId, RecipeDef, RecipeName},
+    structures::crafter_kickstart::PendingInputKickstart,
+    systems::Scanner,
+};
 
 pub type BuildingName = String;
 
@@ -33,6 +37,36 @@ pub struct AppearanceDef {
     pub size: (f32, f32),
     pub color: (f32, f32, f32, f32),
     pub multi_cell: Option<(i32, i32)>, // (width, height)
+    /// Short glyph drawn over the sprite so building types read apart on the map.
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// Marks the glyph child spawned from `AppearanceDef::icon`.
+#[derive(Component, Debug)]
+pub struct BuildingIcon;
+
+impl AppearanceDef {
+    #[must_use]
+    pub fn tint(&self) -> Color {
+        Color::srgba(self.color.0, self.color.1, self.color.2, self.color.3)
+    }
+
+    /// The icon glyph as a child bundle, scaled to the sprite's height.
+    #[must_use]
+    pub fn icon_label(&self) -> Option<impl Bundle> {
+        let icon = self.icon.clone()?;
+        Some((
+            BuildingIcon,
+            Text2d::new(icon),
+            TextFont {
+                font_size: self.size.1 * 0.5,
+                ..Default::default()
+            },
+            TextColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            Transform::from_xyz(0.0, 0.0, 0.05),
+        ))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -43,7 +77,7 @@ pub struct PlacementDef {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CostDef {
-    pub inputs: HashMap<String, u32>,
+    pub inputs: HashMap<ItemId, u32>,
     pub crafting_time: f32,
 }
 
@@ -53,6 +87,8 @@ impl CostDef {
             name: String::new(),
             inputs: self.inputs.clone(),
             outputs: HashMap::new(),
+            output_ports: HashMap::new(),
+            catalysts: HashMap::new(),
             crafting_time: self.crafting_time,
         }
     }
@@ -90,10 +126,29 @@ pub enum BuildingComponentDef {
     OutputPort {
         capacity: u32,
     },
+    NamedOutputPort {
+        tag: String,
+        capacity: u32,
+    },
     StoragePort {
         capacity: u32,
     },
     Launchpad,
+    Sink {
+        accepted: Option<HashSet<ItemId>>,
+        value: SinkValue,
+    },
+    Conveyor {
+        items_per_tick: u32,
+    },
+    Research {
+        target_unlock: RecipeName,
+        required: HashMap<ItemId, u32>,
+    },
+    CapacityUpgrade {
+        tiers: Vec<CapacityUpgradeTier>,
+    },
+    DispatchWeight,
 }
 
 #[derive(Resource)]
@@ -146,6 +201,7 @@ impl BuildingRegistry {
         grid_x: i32,
         grid_y: i32,
         world_pos: Vec2,
+        facing: Facing,
     ) -> Option<Entity> {
         let def = self.get_definition(building_name)?;
         let mut entity_commands = commands.spawn((
@@ -159,17 +215,16 @@ impl BuildingRegistry {
             Operational(Some(Vec::new())),
             Layer(BUILDING_LAYER),
             Sprite::from_color(
-                Color::srgba(
-                    def.appearance.color.0,
-                    def.appearance.color.1,
-                    def.appearance.color.2,
-                    def.appearance.color.3,
-                ),
+                def.appearance.tint(),
                 Vec2::new(def.appearance.size.0, def.appearance.size.1),
             ),
             Transform::from_xyz(world_pos.x, world_pos.y, 1.0),
         ));
 
+        if let Some(icon) = def.appearance.icon_label() {
+            entity_commands.with_child(icon);
+        }
+
         entity_commands.insert(BuildingCost {
             cost: def.placement.cost.to_recipe_def(),
         });
@@ -183,6 +238,7 @@ impl BuildingRegistry {
             });
         }
 
+        let mut named_output_ports = NamedOutputPorts::default();
         for component in &def.components {
             match component {
                 BuildingComponentDef::PowerConsumer { amount } => {
@@ -222,7 +278,9 @@ impl BuildingRegistry {
                         current_recipe: current_recipe.clone(),
                         available_recipes: available_recipes_vec.clone(),
                         timer: Timer::from_seconds(*interval, TimerMode::Repeating),
+                        in_progress: None,
                     });
+                    entity_commands.insert(PendingInputKickstart);
 
                     let is_single_recipe = available_recipes_vec.is_empty();
                     if is_single_recipe {
@@ -246,15 +304,48 @@ impl BuildingRegistry {
                 BuildingComponentDef::OutputPort { capacity } => {
                     entity_commands.insert(OutputPort::new(*capacity));
                 }
+                BuildingComponentDef::NamedOutputPort { tag, capacity } => {
+                    named_output_ports
+                        .ports
+                        .insert(tag.clone(), OutputPort::new(*capacity));
+                }
                 BuildingComponentDef::StoragePort { capacity } => {
                     entity_commands.insert(StoragePort::new(*capacity));
                 }
                 BuildingComponentDef::Launchpad => {
                     entity_commands.insert(Launchpad);
                 }
+                BuildingComponentDef::Sink { accepted, value } => {
+                    entity_commands.insert(Sink {
+                        accepted: accepted.clone(),
+                        value: *value,
+                    });
+                }
+                BuildingComponentDef::Conveyor { items_per_tick } => {
+                    entity_commands.insert(Conveyor {
+                        direction: facing,
+                        items_per_tick: *items_per_tick,
+                    });
+                }
+                BuildingComponentDef::Research {
+                    target_unlock,
+                    required,
+                } => {
+                    entity_commands.insert(Research::new(target_unlock.clone(), required.clone()));
+                }
+                BuildingComponentDef::CapacityUpgrade { tiers } => {
+                    entity_commands.insert(CapacityUpgrades::new(tiers.clone()));
+                }
+                BuildingComponentDef::DispatchWeight => {
+                    entity_commands.insert(DispatchWeight);
+                }
             }
         }
 
+        if !named_output_ports.ports.is_empty() {
+            entity_commands.insert(named_output_ports);
+        }
+
         let entity = entity_commands.id();
         Some(entity)
     }
@@ -299,7 +390,7 @@ mod tests {
             ),
             placement: (
                 cost: (
-                    inputs: {"iron_plate": 5},
+                    inputs: {"Iron Plate": 5},
                     crafting_time: 2.0,
                 ),
                 rules: [RequiresResource],
@@ -324,7 +415,7 @@ mod tests {
             ),
             placement: (
                 cost: (
-                    inputs: {"iron_plate": 2},
+                    inputs: {"Iron Plate": 2},
                     crafting_time: 1.0,
                 ),
                 rules: [AdjacentToNetwork],
@@ -346,7 +437,7 @@ mod tests {
             ),
             placement: (
                 cost: (
-                    inputs: {"iron_plate": 20, "copper_plate": 10},
+                    inputs: {"Iron Plate": 20, "Copper Ingot": 10},
                     crafting_time: 5.0,
                 ),
                 rules: [AdjacentToNetwork],
@@ -370,7 +461,7 @@ mod tests {
             ),
             placement: (
                 cost: (
-                    inputs: {"iron_plate": 8},
+                    inputs: {"Iron Plate": 8},
                     crafting_time: 3.0,
                 ),
                 rules: [],
@@ -466,8 +557,8 @@ mod tests {
     #[test]
     fn cost_def_to_recipe_def_conversion() {
         let mut inputs = HashMap::new();
-        inputs.insert("iron_plate".to_string(), 10);
-        inputs.insert("copper_plate".to_string(), 5);
+        inputs.insert(ItemId::named("Iron Plate").unwrap(), 10);
+        inputs.insert(ItemId::named("Copper Ingot").unwrap(), 5);
 
         let cost = CostDef {
             inputs,
@@ -477,8 +568,14 @@ mod tests {
         let recipe = cost.to_recipe_def();
 
         assert_eq!(recipe.name, "");
-        assert_eq!(recipe.inputs.get("iron_plate"), Some(&10));
-        assert_eq!(recipe.inputs.get("copper_plate"), Some(&5));
+        assert_eq!(
+            recipe.inputs.get(&ItemId::named("Iron Plate").unwrap()),
+            Some(&10)
+        );
+        assert_eq!(
+            recipe.inputs.get(&ItemId::named("Copper Ingot").unwrap()),
+            Some(&5)
+        );
         assert!(recipe.outputs.is_empty());
         assert!((recipe.crafting_time - 3.5).abs() < f32::EPSILON);
     }
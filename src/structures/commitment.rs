@@ -82,6 +82,7 @@ mod tests {
             current_recipe: Some(recipe_name.clone()),
             available_recipes: Vec::new(),
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
         };
 
         let entity = app
@@ -120,6 +121,7 @@ mod tests {
             current_recipe: Some(recipe_name.clone()),
             available_recipes: Vec::new(),
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
         };
 
         let entity = app
@@ -164,6 +166,7 @@ mod tests {
             current_recipe: Some(new_recipe.clone()),
             available_recipes: vec![old_recipe.clone(), new_recipe.clone()],
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
         };
 
         let entity = app
@@ -8,11 +8,14 @@ pub use crate::{
 use crate::{
     constants::structures::MINING_DRILL,
     grid::ExpandGridEvent,
-    materials::{RecipeDef, RecipeName},
+    materials::{DrawsOnReserves, ItemId, ItemReserve, RecipeDef, RecipeName},
     resources::{ResourceNode, ResourceNodeRecipe},
-    systems::NetworkChangedEvent,
+    structures::scenario::ScenarioConfig,
+    systems::{NetworkChangedEvent, NetworkConnectivity, StarvationTimer},
 };
 use bevy::prelude::Name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Component)]
 pub struct Building;
@@ -23,10 +26,13 @@ pub struct ViewRange {
 }
 
 #[derive(Component, Debug)]
+#[require(CraftHistory, StarvationTimer)]
 pub struct RecipeCrafter {
     pub timer: Timer,
     pub current_recipe: Option<RecipeName>,
     pub available_recipes: Vec<RecipeName>,
+    /// Recipe whose inputs were consumed when the current craft started.
+    pub in_progress: Option<RecipeName>,
 }
 
 #[derive(Component, Debug, Default, Clone)]
@@ -38,6 +44,11 @@ pub struct RecipeCommitment {
 #[derive(Component)]
 pub struct NeedsRecipeCommitmentEvaluation;
 
+/// Set when a crafter switches recipes so inputs the new recipe no longer uses
+/// can be sent back to storage.
+#[derive(Component)]
+pub struct NeedsInputEvacuation;
+
 impl RecipeCommitment {
     pub fn new_committed(recipe: Option<RecipeName>) -> Self {
         Self {
@@ -60,10 +71,25 @@ impl RecipeCrafter {
         self.current_recipe.as_ref()
     }
 
-    pub fn set_recipe(&mut self, recipe_name: RecipeName) -> Result<(), String> {
+    /// Switching to a different recipe abandons any craft in progress and
+    /// resets the timer. Returns the abandoned recipe so the caller can give
+    /// back the inputs it consumed. Recipes still locked behind research are
+    /// refused.
+    pub fn set_recipe(
+        &mut self,
+        recipe_name: RecipeName,
+        tech: &TechState,
+    ) -> Result<Option<RecipeName>, String> {
+        if !tech.is_unlocked(&recipe_name) {
+            return Err(format!("Recipe '{recipe_name}' is locked behind research"));
+        }
         if self.is_single_recipe() || self.available_recipes.contains(&recipe_name) {
+            if self.current_recipe.as_ref() == Some(&recipe_name) {
+                return Ok(None);
+            }
             self.current_recipe = Some(recipe_name);
-            Ok(())
+            self.timer.reset();
+            Ok(self.in_progress.take())
         } else {
             Err(format!(
                 "Recipe '{recipe_name}' not available for this crafter"
@@ -72,6 +98,13 @@ impl RecipeCrafter {
     }
 }
 
+/// Asks for a crafter to switch to `recipe_name`.
+#[derive(Message)]
+pub struct RecipeChangeEvent {
+    pub building_entity: Entity,
+    pub recipe_name: String,
+}
+
 #[derive(Component)]
 pub struct PowerGenerator {
     pub amount: i32,
@@ -108,6 +141,111 @@ pub struct MultiCellBuilding {
 #[derive(Component, PartialEq)]
 pub struct NetWorkComponent;
 
+/// Buildings with a higher priority are served before lower ones when a
+/// workflow step resolves a by-type target and when idle workers take queued
+/// deliveries. Buildings without the component are treated as `Medium`.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogisticsPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl LogisticsPriority {
+    pub const ALL: [Self; 3] = [Self::Low, Self::Medium, Self::High];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+        }
+    }
+}
+
+/// Makes a building's share of by-type dispatches follow its `StoragePort`
+/// capacity, one weight per `CAPACITY_PER_WEIGHT`, so upgraded storages draw
+/// more deliveries. Buildings without the component weigh 1.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchWeight;
+
+impl DispatchWeight {
+    pub const CAPACITY_PER_WEIGHT: u32 = 100;
+
+    /// Never below 1, so a small storage still gets its turn.
+    #[must_use]
+    pub fn of(storage: &StoragePort) -> u32 {
+        (storage.capacity / Self::CAPACITY_PER_WEIGHT).max(1)
+    }
+}
+
+/// Player-chosen label for one building instance. UI prefers it over the
+/// type `Name`, which stays untouched for by-type matching.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct DisplayLabel(pub String);
+
+impl DisplayLabel {
+    #[must_use]
+    pub fn or_name<'a>(label: Option<&'a Self>, name: &'a Name) -> &'a str {
+        label.map_or(name.as_str(), |label| label.0.as_str())
+    }
+}
+
+/// Which way a building points. Only conveyors use it so far; it is chosen
+/// while placing and carried through construction.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Facing {
+    North,
+    #[default]
+    East,
+    South,
+    West,
+}
+
+impl Facing {
+    #[must_use]
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Self::North => (0, 1),
+            Self::East => (1, 0),
+            Self::South => (0, -1),
+            Self::West => (-1, 0),
+        }
+    }
+
+    #[must_use]
+    pub fn rotated_clockwise(self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    /// Mirrored across the vertical axis: east and west swap.
+    #[must_use]
+    pub fn flipped_horizontally(self) -> Self {
+        match self {
+            Self::East => Self::West,
+            Self::West => Self::East,
+            other => other,
+        }
+    }
+
+    /// Mirrored across the horizontal axis: north and south swap.
+    #[must_use]
+    pub fn flipped_vertically(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::South => Self::North,
+            other => other,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct PendingDrillRecipeAssignment {
     pub position: Position,
@@ -116,6 +254,7 @@ pub struct PendingDrillRecipeAssignment {
 #[derive(Component)]
 pub struct ConstructionSite {
     pub building_name: String,
+    pub facing: Facing,
 }
 
 #[derive(Bundle)]
@@ -123,33 +262,52 @@ pub struct ConstructionSiteBundle {
     pub construction_site: ConstructionSite,
     pub building_cost: BuildingCost,
     input_port: InputPort,
+    draws_on_reserves: DrawsOnReserves,
     pub position: Position,
     pub layer: Layer,
     pub sprite: Sprite,
     pub transform: Transform,
 }
 
+#[derive(Component)]
+pub struct Scaffold;
+
+pub const SCAFFOLD_COLOR: Color = Color::srgba(0.6, 0.52, 0.35, 0.6);
+
+#[derive(Component)]
+pub struct CompletionFlash {
+    pub timer: Timer,
+}
+
+impl Default for CompletionFlash {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(0.4, TimerMode::Once),
+        }
+    }
+}
+
 impl ConstructionSiteBundle {
     pub fn new(
         building_name: String,
+        facing: Facing,
         building_cost: BuildingCost,
         position: Position,
         world_pos: Vec2,
         appearance: &AppearanceDef,
     ) -> Self {
         Self {
-            construction_site: ConstructionSite { building_name },
+            construction_site: ConstructionSite {
+                building_name,
+                facing,
+            },
             building_cost,
             input_port: InputPort::new(1000),
+            draws_on_reserves: DrawsOnReserves,
             position,
             layer: Layer(BUILDING_LAYER),
             sprite: Sprite::from_color(
-                Color::srgba(
-                    appearance.color.0,
-                    appearance.color.1,
-                    appearance.color.2,
-                    0.7,
-                ),
+                appearance.tint().with_alpha(0.7),
                 Vec2::new(appearance.size.0, appearance.size.1),
             ),
             transform: Transform::from_xyz(world_pos.x, world_pos.y, 0.8),
@@ -195,12 +353,52 @@ pub fn occupy_area(
 #[derive(Component)]
 pub struct Hub;
 
+/// Buildings the player cannot remove. The hub carries it so the network and
+/// worker spawn always have an anchor.
+#[derive(Component)]
+pub struct Indestructible;
+
 #[derive(Component)]
 pub struct Launchpad;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SinkValue {
+    Flat(u64),
+    /// `base` times the item's `ItemRegistry` value per item.
+    Weighted(u64),
+}
+
+impl SinkValue {
+    #[must_use]
+    pub fn item_value(self, registry_value: u64) -> u64 {
+        match self {
+            Self::Flat(points) => points,
+            Self::Weighted(base) => base.saturating_mul(registry_value),
+        }
+    }
+}
+
+/// Consumes delivered items every tick and scores them. `accepted: None`
+/// takes any item.
+#[derive(Component, Debug, Clone)]
+pub struct Sink {
+    pub accepted: Option<HashSet<ItemId>>,
+    pub value: SinkValue,
+}
+
+impl Sink {
+    #[must_use]
+    pub fn accepts(&self, item: ItemId) -> bool {
+        self.accepted
+            .as_ref()
+            .is_none_or(|accepted| accepted.contains(&item))
+    }
+}
+
 pub fn place_hub(
     mut commands: Commands,
     grid: Res<Grid>,
+    scenario_config: Res<ScenarioConfig>,
     mut grid_cells: Query<(Entity, &Position, &mut CellChildren)>,
 ) {
     let center_x = 0;
@@ -208,14 +406,20 @@ pub fn place_hub(
 
     let world_pos = grid.grid_to_world_coordinates(center_x, center_y);
 
+    let starting_ores: Vec<ItemId> = ["Iron Ore", "Copper Ore"]
+        .into_iter()
+        .filter_map(ItemId::named)
+        .collect();
     let mut storage_port = StoragePort::new(10000);
-    storage_port.add_item("Iron Ore", 400);
-    storage_port.add_item("Copper Ore", 400);
+    for &ore in &starting_ores {
+        storage_port.add_item(ore, 400);
+    }
 
     let building_entity = commands
         .spawn((
             Building,
             Hub,
+            Indestructible,
             Name::new("Hub"),
             Position {
                 x: center_x,
@@ -239,10 +443,44 @@ pub fn place_hub(
         ))
         .insert(Transform::from_xyz(world_pos.x, world_pos.y, 1.0))
         .id();
+    if let Some(reserved) = scenario_config.hub_construction_reserve {
+        commands.entity(building_entity).insert(ItemReserve {
+            items: starting_ores.iter().map(|&ore| (ore, reserved)).collect(),
+        });
+    }
 
     occupy_area(&mut grid_cells, center_x, center_y, 3, 3, building_entity);
 }
 
+/// Spawns a finished building, flashing it and queueing a drill for recipe
+/// assignment. The caller adds it to its grid cell.
+pub fn spawn_completed_building(
+    commands: &mut Commands,
+    registry: &BuildingRegistry,
+    building_name: &str,
+    position: Position,
+    world_pos: Vec2,
+    facing: Facing,
+) -> Option<Entity> {
+    let building_entity = registry.spawn_building(
+        commands,
+        building_name,
+        position.x,
+        position.y,
+        world_pos,
+        facing,
+    )?;
+    commands
+        .entity(building_entity)
+        .insert(CompletionFlash::default());
+    if building_name == MINING_DRILL {
+        commands
+            .entity(building_entity)
+            .insert(PendingDrillRecipeAssignment { position });
+    }
+    Some(building_entity)
+}
+
 pub fn monitor_construction_completion(
     mut commands: Commands,
     construction_sites: Query<
@@ -273,20 +511,14 @@ pub fn monitor_construction_completion(
                 cell_children.0.retain(|&entity| entity != site_entity);
             }
 
-            if let Some(building_entity) = registry.spawn_building(
+            if let Some(building_entity) = spawn_completed_building(
                 &mut commands,
+                &registry,
                 &construction_site.building_name,
-                position.x,
-                position.y,
+                *position,
                 transform.translation.truncate(),
+                construction_site.facing,
             ) {
-                if construction_site.building_name == MINING_DRILL {
-                    commands
-                        .entity(building_entity)
-                        .insert(PendingDrillRecipeAssignment {
-                            position: *position,
-                        });
-                }
                 if let Some((_, _, mut cell_children)) = grid_cells
                     .iter_mut()
                     .find(|(_, pos, _)| pos.x == position.x && pos.y == position.y)
@@ -295,37 +527,145 @@ pub fn monitor_construction_completion(
                 }
 
                 network_events.write(NetworkChangedEvent);
-                println!(
-                    "Construction completed: {} at ({}, {})",
-                    construction_site.building_name, position.x, position.y
+                info!(
+                    building = %construction_site.building_name,
+                    x = position.x,
+                    y = position.y,
+                    "construction completed"
                 );
             }
         }
     }
 }
 
+#[derive(Message, Debug, Clone)]
+pub struct CancelConstructionEvent {
+    pub site: Entity,
+}
+
+/// Returns delivered materials to connected storage, nearest first, then
+/// removes the site. Anything that does not fit anywhere is lost. Workers
+/// still heading to the site are released once it despawns.
+pub fn cancel_construction(
+    mut cancel_events: MessageReader<CancelConstructionEvent>,
+    mut sites: Query<(&mut InputPort, &Position), With<ConstructionSite>>,
+    mut storage_ports: Query<(Entity, &mut StoragePort, &Position)>,
+    network: Res<NetworkConnectivity>,
+    mut remove_events: MessageWriter<RemoveBuildingEvent>,
+) {
+    for event in cancel_events.read() {
+        let Ok((mut input_port, site_pos)) = sites.get_mut(event.site) else {
+            continue;
+        };
+
+        let mut targets: Vec<(i32, Entity)> = storage_ports
+            .iter()
+            .filter(|(_, _, pos)| network.is_cell_connected(pos.x, pos.y))
+            .map(|(entity, _, pos)| {
+                let distance = (pos.x - site_pos.x).abs() + (pos.y - site_pos.y).abs();
+                (distance, entity)
+            })
+            .collect();
+        targets.sort_unstable();
+
+        let delivered: Vec<(ItemId, u32)> = input_port
+            .items()
+            .iter()
+            .map(|(&item, &qty)| (item, qty))
+            .collect();
+        for (item, qty) in delivered {
+            let mut remaining = qty;
+            for &(_, storage_entity) in &targets {
+                if remaining == 0 {
+                    break;
+                }
+                let Ok((_, mut storage, _)) = storage_ports.get_mut(storage_entity) else {
+                    continue;
+                };
+                let space = storage
+                    .capacity()
+                    .saturating_sub(storage.get_total_quantity());
+                let moved = input_port.remove_item(item, remaining.min(space));
+                if moved > 0 {
+                    storage.add_item(item, moved);
+                    remaining -= moved;
+                }
+            }
+        }
+
+        if !input_port.is_empty() {
+            warn!(site = ?event.site, "no storage space for all refunded materials");
+        }
+        info!(site = ?event.site, "construction cancelled");
+        remove_events.write(RemoveBuildingEvent {
+            grid_x: site_pos.x,
+            grid_y: site_pos.y,
+        });
+    }
+}
+
+pub fn apply_construction_scaffold(
+    mut commands: Commands,
+    mut new_sites: Query<(Entity, &mut Sprite), Added<ConstructionSite>>,
+) {
+    for (site_entity, mut sprite) in &mut new_sites {
+        sprite.color = SCAFFOLD_COLOR;
+        commands.entity(site_entity).insert(Scaffold);
+    }
+}
+
+pub fn animate_completion_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut buildings: Query<(Entity, &mut CompletionFlash, &mut Transform)>,
+) {
+    for (entity, mut flash, mut transform) in &mut buildings {
+        flash.timer.tick(time.delta());
+
+        if flash.timer.is_finished() {
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<CompletionFlash>();
+        } else {
+            let pulse = 1.0 + 0.15 * (1.0 - flash.timer.fraction());
+            transform.scale = Vec3::new(pulse, pulse, 1.0);
+        }
+    }
+}
+
+/// Drills over a node whose recipe is still locked stay pending until
+/// research unlocks it.
 pub fn assign_drill_recipes(
     mut commands: Commands,
     mut drills: Query<(Entity, &mut RecipeCrafter, &PendingDrillRecipeAssignment), With<Building>>,
     resource_nodes: Query<(&ResourceNodeRecipe, &Position), With<ResourceNode>>,
+    tech: Res<TechState>,
 ) {
     for (drill_entity, mut recipe_crafter, pending) in &mut drills {
         if let Some((resource_recipe, _)) = resource_nodes
             .iter()
             .find(|(_, pos)| pos.x == pending.position.x && pos.y == pending.position.y)
         {
-            if let Err(error) = recipe_crafter.set_recipe(resource_recipe.recipe_name.clone()) {
-                println!(
-                    "Failed to assign recipe to drill at ({}, {}): {}",
-                    pending.position.x, pending.position.y, error
+            if !tech.is_unlocked(&resource_recipe.recipe_name) {
+                continue;
+            }
+            if let Err(error) =
+                recipe_crafter.set_recipe(resource_recipe.recipe_name.clone(), &tech)
+            {
+                warn!(
+                    x = pending.position.x,
+                    y = pending.position.y,
+                    %error,
+                    "failed to assign recipe to drill"
                 );
             } else {
                 commands
                     .entity(drill_entity)
                     .remove::<PendingDrillRecipeAssignment>();
-                println!(
-                    "Assigned recipe '{}' to drill at ({}, {})",
-                    resource_recipe.recipe_name, pending.position.x, pending.position.y
+                debug!(
+                    recipe = %resource_recipe.recipe_name,
+                    x = pending.position.x,
+                    y = pending.position.y,
+                    "assigned recipe to drill"
                 );
             }
         }
@@ -350,9 +690,11 @@ pub fn handle_building_view_range_expansion(
                 radius: view_range.radius,
             });
 
-            println!(
-                "Expanding grid for building at ({}, {}) with radius {}",
-                position.x, position.y, view_range.radius
+            debug!(
+                x = position.x,
+                y = position.y,
+                radius = view_range.radius,
+                "expanding grid for building"
             );
         }
     }
@@ -378,4 +720,36 @@ mod tests {
         assert_eq!(commitment.committed_recipe, None);
         assert_eq!(commitment.pending_recipe, None);
     }
+
+    #[test]
+    fn weighted_sink_values_saturate_instead_of_overflowing() {
+        assert_eq!(SinkValue::Weighted(3).item_value(4), 12);
+        assert_eq!(SinkValue::Weighted(u64::MAX).item_value(2), u64::MAX);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn set_recipe_refuses_recipes_locked_behind_research() {
+        let mut tech = TechState::from_buildings(&BuildingRegistry::load_from_assets().unwrap());
+        let mut crafter = RecipeCrafter {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            current_recipe: Some("Gear".to_string()),
+            available_recipes: vec!["Gear".to_string(), "Electronic Circuit".to_string()],
+            in_progress: None,
+        };
+
+        assert!(crafter
+            .set_recipe("Electronic Circuit".to_string(), &tech)
+            .is_err());
+        assert_eq!(crafter.current_recipe.as_deref(), Some("Gear"));
+
+        tech.unlock("Electronic Circuit");
+        crafter
+            .set_recipe("Electronic Circuit".to_string(), &tech)
+            .unwrap();
+        assert_eq!(
+            crafter.current_recipe.as_deref(),
+            Some("Electronic Circuit")
+        );
+    }
 }
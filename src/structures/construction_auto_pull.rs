@@ -2,7 +2,7 @@
 
 use crate::{
     grid::Position,
-    materials::{InputPort, InventoryAccess, ItemName, ItemTransferRequestEvent, StoragePort},
+    materials::{InputPort, InventoryAccess, ItemId, ItemTransferRequestEvent, StoragePort},
     structures::{BuildingCost, ConstructionSite},
     systems::NetworkConnectivity,
 };
@@ -22,17 +22,65 @@ impl Default for ConstructionAutoPullTimer {
     }
 }
 
-fn compute_deficit(needed: &HashMap<ItemName, u32>, current: &InputPort) -> HashMap<ItemName, u32> {
+fn compute_deficit(needed: &HashMap<ItemId, u32>, current: &InputPort) -> HashMap<ItemId, u32> {
     let mut deficit = HashMap::new();
-    for (item_name, &required) in needed {
-        let on_hand = current.get_item_quantity(item_name);
+    for (&item, &required) in needed {
+        let on_hand = current.get_item_quantity(item);
         if on_hand < required {
-            deficit.insert(item_name.clone(), required - on_hand);
+            deficit.insert(item, required - on_hand);
         }
     }
     deficit
 }
 
+/// Groups a site's deficit into one batch per supplier so a builder's
+/// materials arrive in as few transfers as possible. Suppliers that hold the
+/// whole deficit go first, then the rest by distance, and the batches together
+/// never exceed the site's `free_space`.
+fn consolidate_pulls(
+    deficit: &HashMap<ItemId, u32>,
+    site_pos: Position,
+    suppliers: &[(Entity, Position, &HashMap<ItemId, u32>)],
+    free_space: u32,
+) -> Vec<(Entity, HashMap<ItemId, u32>)> {
+    let covers_all = |items: &HashMap<ItemId, u32>| {
+        deficit
+            .iter()
+            .all(|(item, &needed)| items.get(item).copied().unwrap_or(0) >= needed)
+    };
+    let mut ordered: Vec<&(Entity, Position, &HashMap<ItemId, u32>)> = suppliers.iter().collect();
+    ordered.sort_by_key(|(entity, pos, items)| {
+        let distance = (pos.x - site_pos.x).abs() + (pos.y - site_pos.y).abs();
+        (!covers_all(items), distance, *entity)
+    });
+
+    let mut remaining: Vec<(ItemId, u32)> = deficit.iter().map(|(&i, &q)| (i, q)).collect();
+    remaining.sort_unstable_by_key(|&(item, _)| item);
+    let mut free_space = free_space;
+    let mut batches = Vec::new();
+
+    for (supplier, _, items) in ordered {
+        if free_space == 0 || remaining.iter().all(|&(_, qty)| qty == 0) {
+            break;
+        }
+        let mut batch = HashMap::new();
+        for (item, needed) in &mut remaining {
+            let take = (*needed)
+                .min(items.get(item).copied().unwrap_or(0))
+                .min(free_space);
+            if take > 0 {
+                batch.insert(*item, take);
+                *needed -= take;
+                free_space -= take;
+            }
+        }
+        if !batch.is_empty() {
+            batches.push((*supplier, batch));
+        }
+    }
+    batches
+}
+
 pub fn auto_pull_construction_materials(
     time: Res<Time>,
     mut timer: ResMut<ConstructionAutoPullTimer>,
@@ -49,6 +97,12 @@ pub fn auto_pull_construction_materials(
         return;
     }
 
+    let suppliers: Vec<(Entity, Position, &HashMap<ItemId, u32>)> = storage_ports
+        .iter()
+        .filter(|(_, _, pos)| network.is_cell_connected(pos.x, pos.y))
+        .map(|(entity, port, pos)| (entity, *pos, &port.items))
+        .collect();
+
     for (site_entity, input_port, building_cost, site_pos) in &construction_sites {
         let deficit = compute_deficit(&building_cost.cost.inputs, input_port);
         if deficit.is_empty() {
@@ -59,43 +113,14 @@ pub fn auto_pull_construction_materials(
             continue;
         }
 
-        let mut remaining_deficit = deficit;
-
-        for (storage_entity, storage_port, storage_pos) in &storage_ports {
-            if remaining_deficit.is_empty() {
-                break;
-            }
-
-            if !network.is_cell_connected(storage_pos.x, storage_pos.y) {
-                continue;
-            }
-
-            let mut transfer_items: HashMap<ItemName, u32> = HashMap::new();
-
-            for (item_name, deficit_amount) in &remaining_deficit {
-                let available = storage_port.get_item_quantity(item_name);
-                if available == 0 {
-                    continue;
-                }
-                let to_transfer = (*deficit_amount).min(available);
-                transfer_items.insert(item_name.clone(), to_transfer);
-            }
-
-            if transfer_items.is_empty() {
-                continue;
-            }
-
-            for (item_name, transferred) in &transfer_items {
-                if let Some(remaining) = remaining_deficit.get_mut(item_name) {
-                    *remaining = remaining.saturating_sub(*transferred);
-                }
-            }
-            remaining_deficit.retain(|_, v| *v > 0);
-
+        let free_space = input_port
+            .capacity
+            .saturating_sub(input_port.get_total_quantity());
+        for (supplier, items) in consolidate_pulls(&deficit, *site_pos, &suppliers, free_space) {
             transfer_events.write(ItemTransferRequestEvent {
-                sender: storage_entity,
+                sender: supplier,
                 receiver: site_entity,
-                items: transfer_items,
+                items,
             });
         }
     }
@@ -116,42 +141,42 @@ mod tests {
     #[test]
     fn compute_deficit_full_deficit() {
         let mut needed = HashMap::new();
-        needed.insert("Iron Ore".to_string(), 10);
-        needed.insert("Copper Ore".to_string(), 5);
+        needed.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        needed.insert(ItemId::named("Copper Ore").unwrap(), 5);
 
         let input_port = InputPort::new(1000);
 
         let deficit = compute_deficit(&needed, &input_port);
 
-        assert_eq!(deficit.get("Iron Ore"), Some(&10));
-        assert_eq!(deficit.get("Copper Ore"), Some(&5));
+        assert_eq!(deficit.get(&ItemId::named("Iron Ore").unwrap()), Some(&10));
+        assert_eq!(deficit.get(&ItemId::named("Copper Ore").unwrap()), Some(&5));
     }
 
     #[test]
     fn compute_deficit_partial_deficit() {
         let mut needed = HashMap::new();
-        needed.insert("Iron Ore".to_string(), 10);
-        needed.insert("Copper Ore".to_string(), 5);
+        needed.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        needed.insert(ItemId::named("Copper Ore").unwrap(), 5);
 
         let mut input_port = InputPort::new(1000);
-        input_port.add_item("Iron Ore", 3);
-        input_port.add_item("Copper Ore", 5);
+        input_port.add_item(ItemId::named("Iron Ore").unwrap(), 3);
+        input_port.add_item(ItemId::named("Copper Ore").unwrap(), 5);
 
         let deficit = compute_deficit(&needed, &input_port);
 
-        assert_eq!(deficit.get("Iron Ore"), Some(&7));
-        assert!(!deficit.contains_key("Copper Ore"));
+        assert_eq!(deficit.get(&ItemId::named("Iron Ore").unwrap()), Some(&7));
+        assert!(!deficit.contains_key(&ItemId::named("Copper Ore").unwrap()));
     }
 
     #[test]
     fn compute_deficit_no_deficit() {
         let mut needed = HashMap::new();
-        needed.insert("Iron Ore".to_string(), 10);
-        needed.insert("Copper Ore".to_string(), 5);
+        needed.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        needed.insert(ItemId::named("Copper Ore").unwrap(), 5);
 
         let mut input_port = InputPort::new(1000);
-        input_port.add_item("Iron Ore", 10);
-        input_port.add_item("Copper Ore", 5);
+        input_port.add_item(ItemId::named("Iron Ore").unwrap(), 10);
+        input_port.add_item(ItemId::named("Copper Ore").unwrap(), 5);
 
         let deficit = compute_deficit(&needed, &input_port);
 
@@ -161,10 +186,10 @@ mod tests {
     #[test]
     fn compute_deficit_extra_materials() {
         let mut needed = HashMap::new();
-        needed.insert("Iron Ore".to_string(), 10);
+        needed.insert(ItemId::named("Iron Ore").unwrap(), 10);
 
         let mut input_port = InputPort::new(1000);
-        input_port.add_item("Iron Ore", 20);
+        input_port.add_item(ItemId::named("Iron Ore").unwrap(), 20);
 
         let deficit = compute_deficit(&needed, &input_port);
 
@@ -173,11 +198,47 @@ mod tests {
 
     #[test]
     fn compute_deficit_no_materials_needed() {
-        let needed: HashMap<ItemName, u32> = HashMap::new();
+        let needed: HashMap<ItemId, u32> = HashMap::new();
         let input_port = InputPort::new(1000);
 
         let deficit = compute_deficit(&needed, &input_port);
 
         assert!(deficit.is_empty());
     }
+
+    #[test]
+    fn single_supplier_with_everything_gets_one_consolidated_pull() {
+        let mut world = World::new();
+        let [near, far] = [(); 2].map(|()| world.spawn_empty().id());
+        let mut deficit = HashMap::new();
+        deficit.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        deficit.insert(ItemId::named("Copper Ore").unwrap(), 5);
+
+        let full_stock = deficit.clone();
+        let partial_stock = HashMap::from([(ItemId::named("Iron Ore").unwrap(), 4)]);
+        let suppliers = [
+            (near, Position { x: 1, y: 0 }, &partial_stock),
+            (far, Position { x: 6, y: 0 }, &full_stock),
+        ];
+
+        let batches = consolidate_pulls(&deficit, Position { x: 0, y: 0 }, &suppliers, 1000);
+
+        assert_eq!(batches, vec![(far, deficit)]);
+    }
+
+    #[test]
+    fn consolidated_pull_is_capped_by_site_space() {
+        let mut world = World::new();
+        let storage = world.spawn_empty().id();
+        let mut deficit = HashMap::new();
+        deficit.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        deficit.insert(ItemId::named("Copper Ore").unwrap(), 5);
+        let stock = deficit.clone();
+        let suppliers = [(storage, Position { x: 2, y: 2 }, &stock)];
+
+        let batches = consolidate_pulls(&deficit, Position { x: 0, y: 0 }, &suppliers, 8);
+
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].1.values().sum::<u32>(), 8);
+    }
 }
@@ -0,0 +1,362 @@
+use crate::{
+    grid::{CellChildren, Position},
+    materials::{
+        combined_output_items, InputPort, InventoryAccess, ItemId, NamedOutputPorts, OutputPort,
+        StoragePort,
+    },
+    structures::{Building, Facing},
+};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Each conveyor tick moves up to `items_per_tick` items one tile in
+/// `direction`: into the next conveyor, or into the input or storage port of
+/// the building it points at. A conveyor pulls from the output ports, named
+/// ones included, of a non-conveyor building directly behind it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conveyor {
+    pub direction: Facing,
+    pub items_per_tick: u32,
+}
+
+#[derive(Resource)]
+pub struct ConveyorTimer(pub Timer);
+
+impl Default for ConveyorTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.25, TimerMode::Repeating))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum PortRef {
+    Input(Entity),
+    Output(Entity),
+    Storage(Entity),
+}
+
+struct PlannedMove {
+    from: PortRef,
+    to: PortRef,
+    items: Vec<(ItemId, u32)>,
+}
+
+fn step(pos: Position, (dx, dy): (i32, i32)) -> (i32, i32) {
+    (pos.x + dx, pos.y + dy)
+}
+
+/// Free space left in a receiving port once this tick's planned moves land,
+/// honouring input item limits.
+fn receivable(
+    target: PortRef,
+    item: ItemId,
+    incoming: &HashMap<PortRef, HashMap<ItemId, u32>>,
+    input_ports: &Query<&mut InputPort>,
+    storage_ports: &Query<&mut StoragePort>,
+) -> u32 {
+    let planned = incoming.get(&target);
+    let planned_total: u32 = planned.map_or(0, |items| items.values().sum());
+    let planned_item = planned
+        .and_then(|items| items.get(&item))
+        .copied()
+        .unwrap_or(0);
+
+    match target {
+        PortRef::Input(entity) => {
+            let Ok(port) = input_ports.get(entity) else {
+                return 0;
+            };
+            let space = port
+                .capacity()
+                .saturating_sub(port.get_total_quantity() + planned_total);
+            if port.item_limits.is_empty() {
+                return space;
+            }
+            port.item_limits.get(&item).map_or(0, |&limit| {
+                space.min(limit.saturating_sub(port.get_item_quantity(item) + planned_item))
+            })
+        }
+        PortRef::Storage(entity) => storage_ports.get(entity).map_or(0, |port| {
+            port.capacity()
+                .saturating_sub(port.get_total_quantity() + planned_total)
+        }),
+        PortRef::Output(_) => 0,
+    }
+}
+
+fn plan_move(
+    from: PortRef,
+    to: PortRef,
+    available: &HashMap<ItemId, u32>,
+    budget: u32,
+    incoming: &mut HashMap<PortRef, HashMap<ItemId, u32>>,
+    input_ports: &Query<&mut InputPort>,
+    storage_ports: &Query<&mut StoragePort>,
+) -> Option<PlannedMove> {
+    let mut stock: Vec<(ItemId, u32)> = available.iter().map(|(&i, &q)| (i, q)).collect();
+    stock.sort_unstable();
+
+    let mut remaining = budget;
+    let mut items = Vec::new();
+    for (item, qty) in stock {
+        if remaining == 0 {
+            break;
+        }
+        let amount =
+            qty.min(remaining)
+                .min(receivable(to, item, incoming, input_ports, storage_ports));
+        if amount == 0 {
+            continue;
+        }
+        *incoming.entry(to).or_default().entry(item).or_default() += amount;
+        remaining -= amount;
+        items.push((item, amount));
+    }
+
+    (!items.is_empty()).then_some(PlannedMove { from, to, items })
+}
+
+/// Moves are planned against the inventories at the start of the tick and
+/// applied afterwards, so an item never travels more than one tile per tick.
+/// Neighbours are looked up through the grid cells, so a conveyor reaches a
+/// multi-cell building from any of the cells it covers.
+pub fn move_conveyor_items(
+    time: Res<Time>,
+    mut timer: ResMut<ConveyorTimer>,
+    conveyors: Query<(Entity, &Conveyor, &Position)>,
+    grid_cells: Query<(&Position, &CellChildren)>,
+    buildings: Query<(), With<Building>>,
+    mut input_ports: Query<&mut InputPort>,
+    mut output_ports: Query<&mut OutputPort>,
+    mut named_output_ports: Query<&mut NamedOutputPorts>,
+    mut storage_ports: Query<&mut StoragePort>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let occupants: HashMap<(i32, i32), Entity> = grid_cells
+        .iter()
+        .filter_map(|(pos, children)| {
+            let building = children
+                .0
+                .iter()
+                .copied()
+                .find(|&e| buildings.contains(e))?;
+            Some(((pos.x, pos.y), building))
+        })
+        .collect();
+
+    let mut ordered: Vec<_> = conveyors.iter().collect();
+    ordered.sort_unstable_by_key(|(entity, _, _)| *entity);
+
+    let mut incoming: HashMap<PortRef, HashMap<ItemId, u32>> = HashMap::new();
+    let mut moves = Vec::new();
+
+    for (entity, conveyor, pos) in ordered {
+        let Ok(belt) = input_ports.get(entity) else {
+            continue;
+        };
+        let carried = belt.get_all_items();
+
+        let (dx, dy) = conveyor.direction.offset();
+        let ahead = occupants.get(&step(*pos, (dx, dy))).copied();
+        let target = ahead.and_then(|next| {
+            if input_ports.contains(next) {
+                Some(PortRef::Input(next))
+            } else if storage_ports.contains(next) {
+                Some(PortRef::Storage(next))
+            } else {
+                None
+            }
+        });
+        if let Some(target) = target {
+            moves.extend(plan_move(
+                PortRef::Input(entity),
+                target,
+                &carried,
+                conveyor.items_per_tick,
+                &mut incoming,
+                &input_ports,
+                &storage_ports,
+            ));
+        }
+
+        let behind = occupants.get(&step(*pos, (-dx, -dy))).copied();
+        let source = behind.filter(|&prev| !conveyors.contains(prev));
+        if let Some(source) = source {
+            let available = combined_output_items(
+                output_ports.get(source).ok(),
+                named_output_ports.get(source).ok(),
+            );
+            moves.extend(plan_move(
+                PortRef::Output(source),
+                PortRef::Input(entity),
+                &available,
+                conveyor.items_per_tick,
+                &mut incoming,
+                &input_ports,
+                &storage_ports,
+            ));
+        }
+    }
+
+    for planned in moves {
+        for (item, quantity) in planned.items {
+            let removed = match planned.from {
+                PortRef::Input(entity) => input_ports
+                    .get_mut(entity)
+                    .map_or(0, |mut port| port.remove_item(item, quantity)),
+                PortRef::Output(entity) => {
+                    let removed = output_ports
+                        .get_mut(entity)
+                        .map_or(0, |mut port| port.remove_item(item, quantity));
+                    removed
+                        + named_output_ports
+                            .get_mut(entity)
+                            .map_or(0, |mut named| named.remove_item(item, quantity - removed))
+                }
+                PortRef::Storage(entity) => storage_ports
+                    .get_mut(entity)
+                    .map_or(0, |mut port| port.remove_item(item, quantity)),
+            };
+            if removed == 0 {
+                continue;
+            }
+            match planned.to {
+                PortRef::Input(entity) => {
+                    if let Ok(mut port) = input_ports.get_mut(entity) {
+                        port.add_item(item, removed);
+                    }
+                }
+                PortRef::Storage(entity) => {
+                    if let Ok(mut port) = storage_ports.get_mut(entity) {
+                        port.add_item(item, removed);
+                    }
+                }
+                PortRef::Output(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conveyor_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<ConveyorTimer>()
+            .add_systems(Update, move_conveyor_items);
+        app
+    }
+
+    /// Spawns `building` and records it in the grid cells it covers.
+    fn place(app: &mut App, cells: &[(i32, i32)], building: impl Bundle) -> Entity {
+        let entity = app.world_mut().spawn(building).id();
+        for &(x, y) in cells {
+            app.world_mut()
+                .spawn((Position { x, y }, CellChildren(vec![entity])));
+        }
+        entity
+    }
+
+    fn tick_conveyors(app: &mut App) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs_f32(0.25));
+        app.update();
+    }
+
+    #[test]
+    fn facing_offsets_point_one_tile_away() {
+        let origin = Position { x: 0, y: 0 };
+        assert_eq!(step(origin, Facing::North.offset()), (0, 1));
+        assert_eq!(step(origin, Facing::West.offset()), (-1, 0));
+        assert_eq!(Facing::West.rotated_clockwise(), Facing::North);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn conveyors_pull_from_named_output_ports() {
+        let scrap = ItemId::named("Iron Plate").unwrap();
+        let mut app = conveyor_app();
+
+        let mut named = NamedOutputPorts::default();
+        let mut port = OutputPort::new(10);
+        port.add_item(scrap, 3);
+        named.ports.insert("scrap".to_string(), port);
+        let source = place(
+            &mut app,
+            &[(0, 0)],
+            (Building, Position { x: 0, y: 0 }, named),
+        );
+        let belt = place(
+            &mut app,
+            &[(1, 0)],
+            (
+                Building,
+                Position { x: 1, y: 0 },
+                Conveyor {
+                    direction: Facing::East,
+                    items_per_tick: 2,
+                },
+                InputPort::new(4),
+            ),
+        );
+
+        tick_conveyors(&mut app);
+
+        assert_eq!(
+            app.world()
+                .get::<InputPort>(belt)
+                .unwrap()
+                .get_item_quantity(scrap),
+            2
+        );
+        let named = app.world().get::<NamedOutputPorts>(source).unwrap();
+        assert_eq!(named.get("scrap").unwrap().get_item_quantity(scrap), 1);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn conveyors_feed_a_multi_cell_building_through_any_of_its_cells() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = conveyor_app();
+
+        let hub_cells: Vec<(i32, i32)> = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| (x, y)))
+            .collect();
+        let hub = place(
+            &mut app,
+            &hub_cells,
+            (Building, Position { x: 0, y: 0 }, StoragePort::new(100)),
+        );
+        let mut carried = InputPort::new(4);
+        carried.add_item(ore, 3);
+        place(
+            &mut app,
+            &[(2, 0)],
+            (
+                Building,
+                Position { x: 2, y: 0 },
+                Conveyor {
+                    direction: Facing::West,
+                    items_per_tick: 2,
+                },
+                carried,
+            ),
+        );
+
+        tick_conveyors(&mut app);
+
+        assert_eq!(
+            app.world()
+                .get::<StoragePort>(hub)
+                .unwrap()
+                .get_item_quantity(ore),
+            2
+        );
+    }
+}
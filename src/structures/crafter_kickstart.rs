@@ -0,0 +1,1023 @@
+use crate::{
+    grid::{Grid, Position},
+    materials::{
+        combined_output_items, drawable_stock, InputPort, InventoryAccess, ItemId, ItemReserve,
+        NamedOutputPorts, OutputPort, RecipeDef, RecipeRegistry, StoragePort,
+    },
+    structures::{recipe_item_limits, NeedsInputEvacuation, RecipeCrafter},
+    systems::NetworkConnectivity,
+    workers::{
+        calculate_path_within,
+        workflows::components::{DeliveryRequest, Errand, LogisticsConfig, PendingDeliveries},
+        PathfindingConfig,
+    },
+};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Marks a freshly built crafter whose inputs have not been requested yet.
+/// Cleared after the one-time request, or immediately for crafters without an
+/// input port.
+#[derive(Component, Debug, Default)]
+pub struct PendingInputKickstart;
+
+/// Sources a crafter's inputs were last requested from, with the amounts
+/// asked of each. Replaced on every new plan; the building menu draws it.
+#[derive(Component, Debug, Default, Clone)]
+pub struct SupplyPlan {
+    pub sources: HashMap<Entity, HashMap<ItemId, u32>>,
+}
+
+/// A player's request, from the building menu, for `quantity` of `item` to be
+/// delivered to `building` regardless of its recipe. The quantity is capped
+/// to the building's free space, and workers carry it over from the nearest
+/// sources.
+#[derive(Message, Debug, Clone)]
+pub struct ManualSupplyRequest {
+    pub building: Entity,
+    pub item: ItemId,
+    pub quantity: u32,
+}
+
+/// Asks for inputs stranded by a recipe switch to be moved from the crafter
+/// back into the nearest connected storage.
+#[derive(Message, Debug, Clone)]
+pub struct InputEvacuationRequest {
+    pub crafter: Entity,
+    pub items: HashMap<ItemId, u32>,
+}
+
+/// Opts a building into having its output port drained to storage once it
+/// is at least `threshold` full, so a crafter with no outbound workflow keeps
+/// running. Players switch it on from the building menu.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AutoEvacuateOutput {
+    pub threshold: f32,
+}
+
+impl Default for AutoEvacuateOutput {
+    fn default() -> Self {
+        Self { threshold: 0.8 }
+    }
+}
+
+/// Asks for the output items in `has` to be picked up from `crafter` and
+/// stored in the nearest connected storage.
+#[derive(Message, Debug, Clone)]
+pub struct OutputEvacuationRequest {
+    pub crafter: Entity,
+    pub has: HashMap<ItemId, u32>,
+}
+
+fn nearest_source(
+    item: ItemId,
+    origin: Position,
+    requester: Entity,
+    sources: &[(Entity, Position, HashMap<ItemId, u32>)],
+    reachable: &impl Fn(Position, Position) -> bool,
+) -> Option<(Entity, u32)> {
+    sources
+        .iter()
+        .filter(|(entity, pos, _)| *entity != requester && reachable(origin, *pos))
+        .filter_map(|(entity, pos, items)| {
+            let available = items.get(&item).copied().unwrap_or(0);
+            (available > 0).then(|| {
+                let distance = (pos.x - origin.x).abs() + (pos.y - origin.y).abs();
+                (distance, *entity, available)
+            })
+        })
+        .min_by_key(|&(distance, entity, _)| (distance, entity))
+        .map(|(_, entity, available)| (entity, available))
+}
+
+/// Whether workers can carry items between two buildings: both sit on the
+/// network and the route between them fits the pathfinding length limit.
+fn reachable(
+    from: Position,
+    to: Position,
+    network: &NetworkConnectivity,
+    grid: &Grid,
+    pathfinding: &PathfindingConfig,
+) -> bool {
+    network.is_cell_connected(from.x, from.y)
+        && network.is_cell_connected(to.x, to.y)
+        && pathfinding.max_path_length.is_none_or(|max_len| {
+            calculate_path_within((from.x, from.y), (to.x, to.y), network, grid, Some(max_len))
+                .is_some()
+        })
+}
+
+/// What one crafter still needs to fill its input port.
+#[derive(Debug, Clone)]
+pub struct SupplyDemand {
+    pub crafter: Entity,
+    pub position: Position,
+    pub wanted: HashMap<ItemId, u32>,
+}
+
+/// Whether the sources `reachable` allows for `demand` hold all of it.
+fn covers(
+    stock: &[(Entity, Position, HashMap<ItemId, u32>)],
+    demand: &SupplyDemand,
+    reachable: &impl Fn(Position, Position) -> bool,
+) -> bool {
+    demand.wanted.iter().all(|(item, &wanted)| {
+        let available: u32 = stock
+            .iter()
+            .filter(|(entity, pos, _)| {
+                *entity != demand.crafter && reachable(demand.position, *pos)
+            })
+            .map(|(_, _, items)| items.get(item).copied().unwrap_or(0))
+            .sum();
+        available >= wanted
+    })
+}
+
+/// Gives `demand` as much of each item as the nearest sources hold, taking
+/// it out of `stock`.
+fn allocate(
+    stock: &mut [(Entity, Position, HashMap<ItemId, u32>)],
+    demand: &SupplyDemand,
+    reachable: &impl Fn(Position, Position) -> bool,
+    plan: &mut HashMap<Entity, HashMap<ItemId, u32>>,
+) {
+    let mut wanted: Vec<(ItemId, u32)> = demand
+        .wanted
+        .iter()
+        .map(|(&item, &qty)| (item, qty))
+        .collect();
+    wanted.sort_unstable();
+
+    for (item, mut wanted) in wanted {
+        while wanted > 0 {
+            let Some((source, available)) =
+                nearest_source(item, demand.position, demand.crafter, stock, reachable)
+            else {
+                break;
+            };
+            let taken = wanted.min(available);
+            wanted -= taken;
+            if let Some((_, _, items)) = stock.iter_mut().find(|(e, _, _)| *e == source) {
+                *items.entry(item).or_default() -= taken;
+            }
+            *plan.entry(source).or_default().entry(item).or_default() += taken;
+        }
+    }
+}
+
+/// Splits source stock between crafters, drawing each item from the nearest
+/// sources first. Crafters are served one whole demand at a time, smallest
+/// first, so scarce items complete one crafter's set of inputs instead of
+/// leaving partial sets in all of them. Demands the stock can cover in full
+/// go first; the rest then share what is left in the same order. Sources
+/// `reachable` rejects for a crafter are skipped in favour of the
+/// next-nearest.
+#[must_use]
+pub fn calculate_supply_plan(
+    demands: &[SupplyDemand],
+    sources: &[(Entity, Position, &HashMap<ItemId, u32>)],
+    reachable: impl Fn(Position, Position) -> bool,
+) -> HashMap<Entity, HashMap<Entity, HashMap<ItemId, u32>>> {
+    let mut stock: Vec<(Entity, Position, HashMap<ItemId, u32>)> = sources
+        .iter()
+        .map(|(entity, pos, items)| (*entity, *pos, (*items).clone()))
+        .collect();
+
+    let mut order: Vec<&SupplyDemand> = demands
+        .iter()
+        .filter(|demand| demand.wanted.values().any(|&qty| qty > 0))
+        .collect();
+    order.sort_by_key(|demand| (demand.wanted.values().sum::<u32>(), demand.crafter));
+
+    let mut plans: HashMap<Entity, HashMap<Entity, HashMap<ItemId, u32>>> = HashMap::new();
+    let mut served = vec![false; order.len()];
+    for (demand, served) in order.iter().zip(&mut served) {
+        if covers(&stock, demand, &reachable) {
+            allocate(
+                &mut stock,
+                demand,
+                &reachable,
+                plans.entry(demand.crafter).or_default(),
+            );
+            *served = true;
+        }
+    }
+    for (demand, _) in order.iter().zip(&served).filter(|&(_, served)| !*served) {
+        let plan = plans.entry(demand.crafter).or_default();
+        allocate(&mut stock, demand, &reachable, plan);
+    }
+    plans.retain(|_, plan| !plan.is_empty());
+    plans
+}
+
+/// Free room in a manual request's target: its input port, or its storage
+/// port for buildings without one.
+fn free_space(input_port: Option<&InputPort>, storage_port: Option<&StoragePort>) -> u32 {
+    input_port
+        .map(|port| port.capacity().saturating_sub(port.get_total_quantity()))
+        .or_else(|| {
+            storage_port.map(|port| port.capacity().saturating_sub(port.get_total_quantity()))
+        })
+        .unwrap_or(0)
+}
+
+/// Requests each recipe input from the nearest connected producer or storage
+/// as soon as a new crafter knows its recipe, instead of waiting for a
+/// workflow to bring them. Crafters started in the same frame share one supply
+/// plan with manual requests from the building menu, and every share of it is
+/// handed to workers as a delivery request.
+pub fn kickstart_new_crafters(
+    mut commands: Commands,
+    crafters: Query<
+        (Entity, &RecipeCrafter, &Position, Option<&InputPort>),
+        With<PendingInputKickstart>,
+    >,
+    mut manual_requests: MessageReader<ManualSupplyRequest>,
+    targets: Query<(&Position, Option<&InputPort>, Option<&StoragePort>)>,
+    storage_ports: Query<(Entity, &StoragePort, &Position, Option<&ItemReserve>)>,
+    output_ports: Query<
+        (
+            Entity,
+            Option<&OutputPort>,
+            Option<&NamedOutputPorts>,
+            &Position,
+            Option<&ItemReserve>,
+        ),
+        Or<(With<OutputPort>, With<NamedOutputPorts>)>,
+    >,
+    recipes: Res<RecipeRegistry>,
+    network: Res<NetworkConnectivity>,
+    grid: Res<Grid>,
+    pathfinding: Res<PathfindingConfig>,
+    mut delivery_requests: MessageWriter<DeliveryRequest>,
+) {
+    let mut demands = Vec::new();
+    for (crafter_entity, crafter, crafter_pos, input_port) in &crafters {
+        let Some(input_port) = input_port else {
+            commands
+                .entity(crafter_entity)
+                .remove::<PendingInputKickstart>();
+            continue;
+        };
+
+        let Some(recipe) = crafter
+            .get_active_recipe()
+            .and_then(|name| recipes.get_definition(name))
+        else {
+            continue;
+        };
+
+        if !network.is_adjacent_to_connected_network(crafter_pos.x, crafter_pos.y) {
+            continue;
+        }
+
+        let wanted = recipe_item_limits(input_port.capacity, recipe)
+            .into_iter()
+            .map(|(item, limit)| {
+                (
+                    item,
+                    limit.saturating_sub(input_port.get_item_quantity(item)),
+                )
+            })
+            .filter(|&(_, wanted)| wanted > 0)
+            .collect();
+        demands.push(SupplyDemand {
+            crafter: crafter_entity,
+            position: *crafter_pos,
+            wanted,
+        });
+        commands
+            .entity(crafter_entity)
+            .remove::<PendingInputKickstart>();
+    }
+    for request in manual_requests.read() {
+        let Ok((position, input_port, storage_port)) = targets.get(request.building) else {
+            continue;
+        };
+        let quantity = request.quantity.min(free_space(input_port, storage_port));
+        if quantity == 0 || !network.is_adjacent_to_connected_network(position.x, position.y) {
+            continue;
+        }
+        demands.push(SupplyDemand {
+            crafter: request.building,
+            position: *position,
+            wanted: HashMap::from([(request.item, quantity)]),
+        });
+    }
+    if demands.is_empty() {
+        return;
+    }
+
+    let stock: Vec<(Entity, Position, HashMap<ItemId, u32>)> = storage_ports
+        .iter()
+        .map(|(entity, port, pos, reserve)| (entity, *pos, drawable_stock(&port.items, reserve)))
+        .chain(
+            output_ports
+                .iter()
+                .map(|(entity, port, named, pos, reserve)| {
+                    let items = combined_output_items(port, named);
+                    (entity, *pos, drawable_stock(&items, reserve))
+                }),
+        )
+        .filter(|(_, pos, _)| network.is_cell_connected(pos.x, pos.y))
+        .collect();
+    let sources: Vec<(Entity, Position, &HashMap<ItemId, u32>)> = stock
+        .iter()
+        .map(|(entity, pos, items)| (*entity, *pos, items))
+        .collect();
+
+    let within_range =
+        |from: Position, to: Position| reachable(from, to, &network, &grid, &pathfinding);
+    for (crafter_entity, requests) in calculate_supply_plan(&demands, &sources, within_range) {
+        for (&source, items) in &requests {
+            delivery_requests.write(DeliveryRequest {
+                source,
+                destination: crafter_entity,
+                items: items.clone(),
+            });
+        }
+        commands
+            .entity(crafter_entity)
+            .insert(SupplyPlan { sources: requests });
+    }
+}
+
+fn unused_inputs(input_port: &InputPort, recipe: Option<&RecipeDef>) -> HashMap<ItemId, u32> {
+    input_port
+        .items
+        .iter()
+        .filter(|&(&item, &qty)| qty > 0 && recipe.is_none_or(|recipe| !recipe.uses_item(item)))
+        .map(|(&item, &qty)| (item, qty))
+        .collect()
+}
+
+pub fn request_input_evacuation(
+    mut commands: Commands,
+    mut crafters: Query<(Entity, &RecipeCrafter, &mut InputPort), With<NeedsInputEvacuation>>,
+    recipes: Res<RecipeRegistry>,
+    config: Res<LogisticsConfig>,
+    mut evacuation_requests: MessageWriter<InputEvacuationRequest>,
+) {
+    for (crafter_entity, crafter, mut input_port) in &mut crafters {
+        commands
+            .entity(crafter_entity)
+            .remove::<NeedsInputEvacuation>();
+
+        let recipe = crafter
+            .get_active_recipe()
+            .and_then(|name| recipes.get_definition(name));
+        // Inputs still waiting for a worker may be needed again.
+        input_port
+            .outgoing
+            .retain(|&item, _| recipe.is_none_or(|recipe| !recipe.uses_item(item)));
+
+        if !config.evacuate_unused_inputs {
+            continue;
+        }
+
+        let items = unused_inputs(&input_port, recipe);
+        if items.is_empty() {
+            continue;
+        }
+
+        info!(crafter = ?crafter_entity, items = items.len(), "evacuating unused inputs");
+        evacuation_requests.write(InputEvacuationRequest {
+            crafter: crafter_entity,
+            items,
+        });
+    }
+}
+
+/// Output items above `threshold` fullness, taken from the largest stacks
+/// first so the crafter is freed of what it makes most of.
+fn excess_outputs(output_port: &OutputPort, threshold: f32) -> HashMap<ItemId, u32> {
+    #[allow(clippy::cast_precision_loss)]
+    let capacity = output_port.capacity as f32;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let keep = (capacity * threshold.clamp(0.0, 1.0)).floor() as u32;
+    let total = output_port.get_total_quantity();
+    if total <= keep {
+        return HashMap::new();
+    }
+
+    let mut stacks: Vec<(ItemId, u32)> = output_port
+        .items
+        .iter()
+        .map(|(&item, &qty)| (item, qty))
+        .collect();
+    stacks.sort_unstable_by_key(|&(item, qty)| (std::cmp::Reverse(qty), item));
+
+    let mut excess = total - keep;
+    let mut has = HashMap::new();
+    for (item, qty) in stacks {
+        if excess == 0 {
+            break;
+        }
+        let taken = qty.min(excess);
+        has.insert(item, taken);
+        excess -= taken;
+    }
+    has
+}
+
+pub fn request_output_evacuation(
+    crafters: Query<
+        (Entity, &OutputPort, &AutoEvacuateOutput),
+        Or<(Changed<OutputPort>, Added<AutoEvacuateOutput>)>,
+    >,
+    mut evacuation_requests: MessageWriter<OutputEvacuationRequest>,
+) {
+    for (crafter_entity, output_port, auto_evacuate) in &crafters {
+        let has = excess_outputs(output_port, auto_evacuate.threshold);
+        if has.is_empty() {
+            continue;
+        }
+        debug!(crafter = ?crafter_entity, items = has.len(), "evacuating backed-up outputs");
+        evacuation_requests.write(OutputEvacuationRequest {
+            crafter: crafter_entity,
+            has,
+        });
+    }
+}
+
+/// Asks a worker to carry evacuated outputs to the nearest storage with free
+/// space that it can walk to. A crafter waits for its last evacuation to be
+/// picked up before asking again, and whatever the storage cannot take is
+/// left to the worker's emergency dropoff.
+pub fn evacuate_crafter_outputs(
+    mut evacuation_requests: MessageReader<OutputEvacuationRequest>,
+    positions: Query<&Position>,
+    storage_ports: Query<(Entity, &StoragePort, &Position)>,
+    outstanding: (Res<PendingDeliveries>, Query<&Errand>),
+    routing: (Res<NetworkConnectivity>, Res<Grid>, Res<PathfindingConfig>),
+    mut delivery_requests: MessageWriter<DeliveryRequest>,
+) {
+    let (pending, errands) = outstanding;
+    let (network, grid, pathfinding) = routing;
+    for request in evacuation_requests.read() {
+        let Ok(crafter_pos) = positions.get(request.crafter) else {
+            continue;
+        };
+        let awaiting_pickup = pending
+            .requests
+            .iter()
+            .any(|delivery| delivery.source == request.crafter)
+            || errands
+                .iter()
+                .any(|errand| errand.request.source == request.crafter && !errand.loaded);
+        if awaiting_pickup {
+            continue;
+        }
+        let Some(storage_entity) = storage_ports
+            .iter()
+            .filter(|(entity, port, pos)| {
+                *entity != request.crafter
+                    && port.get_total_quantity() < port.capacity()
+                    && reachable(*crafter_pos, **pos, &network, &grid, &pathfinding)
+            })
+            .min_by_key(|(entity, _, pos)| {
+                let distance = (pos.x - crafter_pos.x).abs() + (pos.y - crafter_pos.y).abs();
+                (distance, *entity)
+            })
+            .map(|(entity, _, _)| entity)
+        else {
+            continue;
+        };
+
+        delivery_requests.write(DeliveryRequest {
+            source: request.crafter,
+            destination: storage_entity,
+            items: request.has.clone(),
+        });
+    }
+}
+
+/// Marks evacuated inputs as outgoing and asks a worker to carry them to the
+/// nearest storage with free space that it can walk to. They stay in the
+/// crafter until the worker picks them up, and whatever the storage cannot
+/// take is left to the worker's emergency dropoff.
+pub fn evacuate_crafter_inputs(
+    mut evacuation_requests: MessageReader<InputEvacuationRequest>,
+    mut input_ports: Query<(&mut InputPort, &Position)>,
+    storage_ports: Query<(Entity, &StoragePort, &Position)>,
+    network: Res<NetworkConnectivity>,
+    grid: Res<Grid>,
+    pathfinding: Res<PathfindingConfig>,
+    mut delivery_requests: MessageWriter<DeliveryRequest>,
+) {
+    for request in evacuation_requests.read() {
+        let Ok((mut input_port, crafter_pos)) = input_ports.get_mut(request.crafter) else {
+            continue;
+        };
+
+        let Some(storage_entity) = storage_ports
+            .iter()
+            .filter(|(entity, port, pos)| {
+                *entity != request.crafter
+                    && port.get_total_quantity() < port.capacity()
+                    && reachable(*crafter_pos, **pos, &network, &grid, &pathfinding)
+            })
+            .min_by_key(|(entity, _, pos)| {
+                let distance = (pos.x - crafter_pos.x).abs() + (pos.y - crafter_pos.y).abs();
+                (distance, *entity)
+            })
+            .map(|(entity, _, _)| entity)
+        else {
+            warn!(crafter = ?request.crafter, "no storage with space for evacuated inputs");
+            continue;
+        };
+
+        input_port.outgoing.clone_from(&request.items);
+        delivery_requests.write(DeliveryRequest {
+            source: request.crafter,
+            destination: storage_entity,
+            items: request.items.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::structures::TechState;
+
+    /// An app running only the kickstart, with an "Iron Ingot" recipe and
+    /// the network laid along `y = 0` from `x = 0` to `x = 3`.
+    fn setup_app() -> App {
+        let ron = r#"[
+            (
+                name: "Iron Ingot",
+                inputs: {"Iron Ore": 1},
+                outputs: {"Iron Ingot": 1},
+                crafting_time: 1.0,
+            ),
+        ]"#;
+        let mut network = NetworkConnectivity::default();
+        for x in 0..=3 {
+            network.add_connected_cell(x, 0);
+        }
+
+        let mut app = App::new();
+        app.insert_resource(RecipeRegistry::from_ron(ron).unwrap())
+            .insert_resource(network)
+            .insert_resource(Grid::new(64.0))
+            .init_resource::<PathfindingConfig>()
+            .add_message::<ManualSupplyRequest>()
+            .add_message::<DeliveryRequest>()
+            .add_systems(Update, kickstart_new_crafters);
+        app
+    }
+
+    /// An "Iron Ingot" crafter at `(1, 0)` waiting for its first inputs.
+    fn spawn_crafter(app: &mut App) -> Entity {
+        app.world_mut()
+            .spawn((
+                RecipeCrafter {
+                    timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    current_recipe: Some("Iron Ingot".to_string()),
+                    available_recipes: vec!["Iron Ingot".to_string()],
+                    in_progress: None,
+                },
+                InputPort::new(20),
+                Position { x: 1, y: 0 },
+                PendingInputKickstart,
+            ))
+            .id()
+    }
+
+    #[test]
+    fn nearest_source_prefers_closest_stocked_building() {
+        let mut world = World::new();
+        let [empty, far, near, requester] = [(); 4].map(|()| world.spawn_empty().id());
+        let ore = ItemId::named("Iron Ore").unwrap();
+
+        let sources = vec![
+            (empty, Position { x: 1, y: 0 }, HashMap::new()),
+            (far, Position { x: 6, y: 0 }, HashMap::from([(ore, 5)])),
+            (near, Position { x: 0, y: -2 }, HashMap::from([(ore, 2)])),
+            (
+                requester,
+                Position { x: 0, y: 0 },
+                HashMap::from([(ore, 9)]),
+            ),
+        ];
+        let origin = Position { x: 0, y: 0 };
+
+        assert_eq!(
+            nearest_source(ore, origin, requester, &sources, &|_, _| true),
+            Some((near, 2))
+        );
+        assert_eq!(
+            nearest_source(
+                ItemId::named("Coal").unwrap(),
+                origin,
+                requester,
+                &sources,
+                &|_, _| { true }
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn scarce_input_completes_one_crafter_before_splitting() {
+        let mut world = World::new();
+        let [storage, first, second] = [(); 3].map(|()| world.spawn_empty().id());
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let stock = HashMap::from([(ore, 10)]);
+        let sources = vec![(storage, Position { x: 0, y: 0 }, &stock)];
+        let demands = [first, second].map(|crafter| SupplyDemand {
+            crafter,
+            position: Position { x: 1, y: 0 },
+            wanted: HashMap::from([(ore, 10)]),
+        });
+
+        let plan = calculate_supply_plan(&demands, &sources, |_, _| true);
+
+        assert_eq!(plan.len(), 1, "only one crafter is served: {plan:?}");
+        let served = plan.values().next().unwrap();
+        assert_eq!(served[&storage][&ore], 10);
+    }
+
+    #[test]
+    fn a_coverable_demand_wins_the_whole_set_over_a_partial_one() {
+        let mut world = World::new();
+        let [storage, smelter, forge] = [(); 3].map(|()| world.spawn_empty().id());
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let coal = ItemId::named("Coal").unwrap();
+        let stock = HashMap::from([(ore, 5), (coal, 2)]);
+        let sources = vec![(storage, Position { x: 0, y: 0 }, &stock)];
+        let demands = [
+            // The smaller demand, but there is not enough coal to cover it.
+            SupplyDemand {
+                crafter: forge,
+                position: Position { x: 1, y: 0 },
+                wanted: HashMap::from([(ore, 2), (coal, 3)]),
+            },
+            SupplyDemand {
+                crafter: smelter,
+                position: Position { x: 2, y: 0 },
+                wanted: HashMap::from([(ore, 5), (coal, 1)]),
+            },
+        ];
+
+        let plan = calculate_supply_plan(&demands, &sources, |_, _| true);
+
+        assert_eq!(
+            plan[&smelter][&storage],
+            HashMap::from([(ore, 5), (coal, 1)])
+        );
+        // The forge only gets what the smelter's full set left behind.
+        assert_eq!(plan[&forge][&storage], HashMap::from([(coal, 1)]));
+    }
+
+    #[test]
+    fn kickstart_records_the_supplying_storage() {
+        let mut app = setup_app();
+
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ore, 30);
+        let storage = app
+            .world_mut()
+            .spawn((storage, Position { x: 0, y: 0 }))
+            .id();
+        let crafter = spawn_crafter(&mut app);
+
+        app.update();
+
+        let plan = app.world().get::<SupplyPlan>(crafter).unwrap();
+        assert_eq!(plan.sources.len(), 1);
+        assert_eq!(plan.sources[&storage].get(&ore), Some(&20));
+
+        // A worker carries the ore over; nothing moves on its own.
+        let deliveries: Vec<_> = app
+            .world()
+            .resource::<Messages<DeliveryRequest>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect();
+        assert_eq!(
+            deliveries,
+            vec![DeliveryRequest {
+                source: storage,
+                destination: crafter,
+                items: HashMap::from([(ore, 20)]),
+            }]
+        );
+        let input_port = app.world().get::<InputPort>(crafter).unwrap();
+        assert_eq!(input_port.get_total_quantity(), 0);
+    }
+
+    #[test]
+    fn kickstart_draws_on_named_output_ports() {
+        let mut app = setup_app();
+
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut port = OutputPort::new(50);
+        port.add_item(ore, 12);
+        let producer = app
+            .world_mut()
+            .spawn((
+                NamedOutputPorts {
+                    ports: HashMap::from([("ore".to_string(), port)]),
+                },
+                Position { x: 0, y: 0 },
+            ))
+            .id();
+        let crafter = spawn_crafter(&mut app);
+
+        app.update();
+
+        let plan = app.world().get::<SupplyPlan>(crafter).unwrap();
+        assert_eq!(plan.sources[&producer].get(&ore), Some(&12));
+    }
+
+    #[test]
+    fn kickstart_skips_a_storage_whose_stock_is_reserved() {
+        let mut app = setup_app();
+
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut spawn_storage = |x: i32, reserved: u32| {
+            let mut storage = StoragePort::new(100);
+            storage.add_item(ore, 30);
+            app.world_mut()
+                .spawn((
+                    storage,
+                    ItemReserve {
+                        items: HashMap::from([(ore, reserved)]),
+                    },
+                    Position { x, y: 0 },
+                ))
+                .id()
+        };
+        let nearest = spawn_storage(0, 30);
+        let next = spawn_storage(3, 0);
+        let crafter = spawn_crafter(&mut app);
+
+        app.update();
+
+        let plan = app.world().get::<SupplyPlan>(crafter).unwrap();
+        assert!(!plan.sources.contains_key(&nearest));
+        assert_eq!(plan.sources[&next].get(&ore), Some(&20));
+    }
+
+    #[test]
+    fn manual_request_plans_a_delivery_when_stock_exists() {
+        let mut app = setup_app();
+
+        let iron = ItemId::named("Iron Ingot").unwrap();
+        let mut storage = StoragePort::new(100);
+        storage.add_item(iron, 25);
+        let storage = app
+            .world_mut()
+            .spawn((storage, Position { x: 0, y: 0 }))
+            .id();
+        let mut input_port = InputPort::new(50);
+        input_port.add_item(iron, 44);
+        let building = app
+            .world_mut()
+            .spawn((input_port, Position { x: 1, y: 0 }))
+            .id();
+
+        app.world_mut().write_message(ManualSupplyRequest {
+            building,
+            item: iron,
+            quantity: 10,
+        });
+        app.update();
+
+        // Only the six free slots are asked for, and a worker carries them.
+        let plan = app.world().get::<SupplyPlan>(building).unwrap();
+        assert_eq!(plan.sources.len(), 1);
+        assert_eq!(plan.sources[&storage].get(&iron), Some(&6));
+        let deliveries: Vec<_> = app
+            .world()
+            .resource::<Messages<DeliveryRequest>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect();
+        assert_eq!(
+            deliveries,
+            vec![DeliveryRequest {
+                source: storage,
+                destination: building,
+                items: HashMap::from([(iron, 6)]),
+            }]
+        );
+    }
+
+    #[test]
+    fn switching_recipes_asks_a_worker_to_evacuate_stranded_inputs() {
+        let ron = r#"[
+            (
+                name: "Iron Ingot",
+                inputs: {"Iron Ore": 1},
+                outputs: {"Iron Ingot": 1},
+                crafting_time: 1.0,
+            ),
+            (
+                name: "Copper Ingot",
+                inputs: {"Copper Ore": 1},
+                outputs: {"Copper Ingot": 1},
+                crafting_time: 1.0,
+            ),
+        ]"#;
+
+        let mut network = NetworkConnectivity::default();
+        for x in 0..=3 {
+            network.add_connected_cell(x, 0);
+        }
+
+        let mut app = App::new();
+        app.insert_resource(RecipeRegistry::from_ron(ron).unwrap())
+            .insert_resource(LogisticsConfig {
+                evacuate_unused_inputs: true,
+                ..Default::default()
+            })
+            .insert_resource(network)
+            .insert_resource(Grid::new(64.0))
+            .init_resource::<PathfindingConfig>()
+            .add_message::<InputEvacuationRequest>()
+            .add_message::<DeliveryRequest>()
+            .add_systems(
+                Update,
+                (request_input_evacuation, evacuate_crafter_inputs).chain(),
+            );
+
+        let iron_ore = ItemId::named("Iron Ore").unwrap();
+        let copper_ore = ItemId::named("Copper Ore").unwrap();
+        let mut input_port = InputPort::new(50);
+        input_port.add_item(iron_ore, 4);
+        input_port.add_item(copper_ore, 2);
+        let mut crafter = RecipeCrafter {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            current_recipe: Some("Iron Ingot".to_string()),
+            available_recipes: vec!["Iron Ingot".to_string(), "Copper Ingot".to_string()],
+            in_progress: None,
+        };
+        crafter
+            .set_recipe("Copper Ingot".to_string(), &TechState::default())
+            .unwrap();
+        let entity = app
+            .world_mut()
+            .spawn((
+                crafter,
+                input_port,
+                Position { x: 0, y: 0 },
+                NeedsInputEvacuation,
+            ))
+            .id();
+        let storage = app
+            .world_mut()
+            .spawn((StoragePort::new(100), Position { x: 3, y: 0 }))
+            .id();
+        // Nearer, but off the network.
+        app.world_mut()
+            .spawn((StoragePort::new(100), Position { x: 0, y: 1 }));
+
+        app.update();
+
+        let requests: Vec<_> = app
+            .world()
+            .resource::<Messages<InputEvacuationRequest>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].crafter, entity);
+        assert_eq!(requests[0].items, HashMap::from([(iron_ore, 4)]));
+        assert!(!app
+            .world()
+            .entity(entity)
+            .contains::<NeedsInputEvacuation>());
+
+        let deliveries: Vec<_> = app
+            .world()
+            .resource::<Messages<DeliveryRequest>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect();
+        assert_eq!(
+            deliveries,
+            vec![DeliveryRequest {
+                source: entity,
+                destination: storage,
+                items: HashMap::from([(iron_ore, 4)]),
+            }]
+        );
+
+        // The ore waits in the crafter for the worker to pick it up.
+        let input_port = app.world().get::<InputPort>(entity).unwrap();
+        assert_eq!(input_port.get_item_quantity(iron_ore), 4);
+        assert_eq!(input_port.get_item_quantity(copper_ore), 2);
+        assert_eq!(input_port.outgoing, HashMap::from([(iron_ore, 4)]));
+        let stored = app
+            .world()
+            .get::<StoragePort>(storage)
+            .unwrap()
+            .get_total_quantity();
+        assert_eq!(stored, 0);
+    }
+
+    #[test]
+    fn near_full_output_requests_pickup_of_the_excess() {
+        let mut app = App::new();
+        app.add_message::<OutputEvacuationRequest>()
+            .add_systems(Update, request_output_evacuation);
+
+        let ingot = ItemId::named("Iron Ingot").unwrap();
+        let slag = ItemId::named("Copper Wire").unwrap();
+        let mut output_port = OutputPort::new(10);
+        output_port.add_item(ingot, 7);
+        output_port.add_item(slag, 2);
+        let crafter = app
+            .world_mut()
+            .spawn((output_port, AutoEvacuateOutput { threshold: 0.5 }))
+            .id();
+        let unflagged = app.world_mut().spawn(OutputPort::new(10)).id();
+        app.world_mut()
+            .get_mut::<OutputPort>(unflagged)
+            .unwrap()
+            .add_item(ingot, 10);
+
+        app.update();
+
+        let requests: Vec<_> = app
+            .world()
+            .resource::<Messages<OutputEvacuationRequest>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].crafter, crafter);
+        assert_eq!(requests[0].has, HashMap::from([(ingot, 4)]));
+    }
+
+    #[test]
+    fn evacuated_outputs_are_left_for_a_worker_to_carry() {
+        let mut network = NetworkConnectivity::default();
+        for x in 0..=2 {
+            network.add_connected_cell(x, 0);
+        }
+
+        let mut app = App::new();
+        app.insert_resource(network)
+            .insert_resource(Grid::new(64.0))
+            .init_resource::<PathfindingConfig>()
+            .init_resource::<PendingDeliveries>()
+            .add_message::<OutputEvacuationRequest>()
+            .add_message::<DeliveryRequest>()
+            .add_systems(Update, evacuate_crafter_outputs);
+
+        let ingot = ItemId::named("Iron Ingot").unwrap();
+        let mut output_port = OutputPort::new(10);
+        output_port.add_item(ingot, 9);
+        let crafter = app
+            .world_mut()
+            .spawn((output_port, Position { x: 0, y: 0 }))
+            .id();
+        let storage = app
+            .world_mut()
+            .spawn((StoragePort::new(100), Position { x: 2, y: 0 }))
+            .id();
+        // Off the network, so out of reach however close it is.
+        app.world_mut()
+            .spawn((StoragePort::new(100), Position { x: 0, y: 1 }));
+
+        let evacuate = |app: &mut App| {
+            app.world_mut().write_message(OutputEvacuationRequest {
+                crafter,
+                has: HashMap::from([(ingot, 4)]),
+            });
+            app.update();
+            app.world()
+                .resource::<Messages<DeliveryRequest>>()
+                .iter_current_update_messages()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let expected = DeliveryRequest {
+            source: crafter,
+            destination: storage,
+            items: HashMap::from([(ingot, 4)]),
+        };
+        assert_eq!(evacuate(&mut app), vec![expected.clone()]);
+        assert_eq!(
+            app.world()
+                .get::<OutputPort>(crafter)
+                .unwrap()
+                .get_item_quantity(ingot),
+            9
+        );
+
+        // Nothing more is asked for while the first pickup is still queued.
+        app.world_mut()
+            .resource_mut::<PendingDeliveries>()
+            .requests
+            .push_back(expected);
+        assert!(evacuate(&mut app).is_empty());
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+
+use crate::{
+    materials::{InputPort, InventoryAccess},
+    structures::{BuildingCost, ConstructionSite},
+};
+
+/// For testing layouts: with `enabled`, placed buildings skip construction
+/// and spawn complete.
+#[derive(Resource, Debug, Default)]
+pub struct CreativeMode {
+    pub enabled: bool,
+}
+
+/// Asks for every construction site to be finished at once.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct FinishAllConstructionEvent;
+
+/// Tops up each site's delivered materials to its full cost, so
+/// `monitor_construction_completion` finishes it the usual way.
+pub fn finish_all_construction(
+    mut events: MessageReader<FinishAllConstructionEvent>,
+    mut sites: Query<(&mut InputPort, &BuildingCost), With<ConstructionSite>>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let mut finished = 0;
+    for (mut input_port, building_cost) in &mut sites {
+        for (&item, &quantity) in &building_cost.cost.inputs {
+            let missing = quantity.saturating_sub(input_port.get_item_quantity(item));
+            if missing > 0 {
+                input_port.add_item(item, missing);
+            }
+        }
+        finished += 1;
+    }
+    info!(sites = finished, "finished all construction");
+}
@@ -2,13 +2,23 @@ pub mod building_config;
 pub mod commitment;
 pub mod construction;
 pub mod construction_auto_pull;
+pub mod conveyor;
+pub mod crafter_kickstart;
+pub mod creative;
 pub mod placement;
 pub mod production;
+pub mod research;
+pub mod scenario;
+pub mod upgrades;
 pub mod validation;
 
 pub use construction::*;
+pub use conveyor::*;
+pub use creative::{CreativeMode, FinishAllConstructionEvent};
 pub use placement::*;
 pub use production::*;
+pub use research::*;
+pub use upgrades::*;
 pub use validation::*;
 
 use bevy::prelude::*;
@@ -41,20 +51,45 @@ impl Plugin for BuildingsPlugin {
     fn build(&self, app: &mut App) {
         configure_building_system_sets(app);
 
+        let scenario_config = app
+            .world()
+            .get_resource::<scenario::ScenarioConfig>()
+            .cloned()
+            .unwrap_or_default();
+        app.insert_resource(scenario::Scenario::from_config(&scenario_config))
+            .insert_resource(scenario_config);
+
         match BuildingRegistry::load_from_assets() {
             Ok(registry) => {
+                app.insert_resource(TechState::from_buildings(&registry));
                 app.insert_resource(registry);
             }
             Err(e) => {
                 error!("failed to load building registry: {e}");
+                app.init_resource::<TechState>();
             }
         }
 
         app.add_message::<PlaceBuildingRequestEvent>()
             .add_message::<PlaceBuildingValidationEvent>()
             .add_message::<RemoveBuildingEvent>()
+            .add_message::<crafter_kickstart::InputEvacuationRequest>()
+            .add_message::<crafter_kickstart::ManualSupplyRequest>()
+            .add_message::<crafter_kickstart::OutputEvacuationRequest>()
+            .add_message::<ResearchCompletedEvent>()
+            .add_message::<CapacityUpgradeEvent>()
+            .add_message::<RecipeChangeEvent>()
+            .add_message::<CancelConstructionEvent>()
+            .add_message::<FinishAllConstructionEvent>()
             .init_resource::<construction_auto_pull::ConstructionAutoPullTimer>()
-            .add_systems(Startup, place_hub)
+            .init_resource::<ConveyorTimer>()
+            .init_resource::<LastPlaced>()
+            .init_resource::<PlacementQueue>()
+            .init_resource::<CreativeMode>()
+            .add_systems(
+                Startup,
+                (place_hub, scenario::place_scenario_buildings).chain(),
+            )
             .add_systems(
                 Update,
                 (
@@ -64,9 +99,13 @@ impl Plugin for BuildingsPlugin {
                     validate_placement.in_set(BuildingSystemSet::Validation),
                     (
                         place_building,
+                        apply_construction_scaffold,
+                        creative::finish_all_construction,
                         monitor_construction_completion,
+                        animate_completion_flash,
                         handle_building_view_range_expansion,
                         assign_drill_recipes.run_if(drill_awaiting_assignment),
+                        cancel_construction,
                         remove_building,
                     )
                         .chain()
@@ -76,9 +115,18 @@ impl Plugin for BuildingsPlugin {
                             .run_if(commitment::any_needs_evaluation),
                         commitment::commit_pending_recipes,
                         sync_input_port_limits,
+                        crafter_kickstart::kickstart_new_crafters,
+                        crafter_kickstart::request_input_evacuation,
+                        crafter_kickstart::evacuate_crafter_inputs,
+                        crafter_kickstart::request_output_evacuation,
+                        crafter_kickstart::evacuate_crafter_outputs,
                         update_port_crafters,
                         update_source_port_crafters,
                         update_sink_port_crafters,
+                        move_conveyor_items,
+                        consume_sink_items,
+                        advance_research,
+                        apply_capacity_upgrades,
                         construction_auto_pull::auto_pull_construction_materials,
                     )
                         .chain())
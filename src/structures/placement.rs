@@ -1,19 +1,29 @@
 use crate::{
     grid::{CellChildren, Grid, Layer, Position},
     structures::{
-        Building, BuildingComponentDef, BuildingCost, BuildingRegistry, ConstructionSite,
-        ConstructionSiteBundle, NetWorkComponent, PlaceBuildingValidationEvent,
+        spawn_completed_building, Building, BuildingComponentDef, BuildingCost, BuildingRegistry,
+        ConstructionSite, ConstructionSiteBundle, CreativeMode, Facing, Indestructible,
+        NetWorkComponent, PlaceBuildingValidationEvent,
     },
     systems::NetworkChangedEvent,
     ui::SelectedBuilding,
 };
 use bevy::prelude::*;
 
-#[derive(Message, Clone)]
+#[derive(Message, Debug, Clone)]
 pub struct PlaceBuildingRequestEvent {
     pub building_name: String,
     pub grid_x: i32,
     pub grid_y: i32,
+    pub facing: Facing,
+}
+
+/// The building type and facing of the most recent accepted placement, for
+/// the repeat-last hotkey.
+#[derive(Resource, Debug, Default)]
+pub struct LastPlaced {
+    pub building_name: Option<String>,
+    pub facing: Facing,
 }
 
 #[derive(Message)]
@@ -50,6 +60,7 @@ pub fn handle_building_input(
                 building_name: building_name.clone(),
                 grid_x: coords.grid_x,
                 grid_y: coords.grid_y,
+                facing: selected_building.facing,
             });
         }
     }
@@ -69,6 +80,8 @@ pub fn place_building(
     registry: Res<BuildingRegistry>,
     mut grid_cells: Query<(Entity, &Position, &mut CellChildren)>,
     mut network_events: MessageWriter<NetworkChangedEvent>,
+    mut last_placed: ResMut<LastPlaced>,
+    creative: Res<CreativeMode>,
 ) {
     for event in validation_events.read() {
         if event.result.is_ok() {
@@ -91,9 +104,27 @@ pub fn place_building(
                     y: event.request.grid_y,
                 };
 
+                if creative.enabled {
+                    if let Some(building_entity) = spawn_completed_building(
+                        &mut commands,
+                        &registry,
+                        &event.request.building_name,
+                        position,
+                        world_pos,
+                        event.request.facing,
+                    ) {
+                        cell_children.0.push(building_entity);
+                        last_placed.building_name = Some(event.request.building_name.clone());
+                        last_placed.facing = event.request.facing;
+                        network_events.write(NetworkChangedEvent);
+                    }
+                    continue;
+                }
+
                 let construction_site_entity = commands
                     .spawn(ConstructionSiteBundle::new(
                         event.request.building_name.clone(),
+                        event.request.facing,
                         building_cost,
                         position,
                         world_pos,
@@ -101,6 +132,10 @@ pub fn place_building(
                     ))
                     .id();
 
+                if let Some(icon) = def.appearance.icon_label() {
+                    commands.entity(construction_site_entity).with_child(icon);
+                }
+
                 if def
                     .components
                     .iter()
@@ -112,6 +147,8 @@ pub fn place_building(
                 }
 
                 cell_children.0.push(construction_site_entity);
+                last_placed.building_name = Some(event.request.building_name.clone());
+                last_placed.facing = event.request.facing;
 
                 network_events.write(NetworkChangedEvent);
             }
@@ -126,6 +163,7 @@ pub fn remove_building(
     mut grid_cells: Query<(Entity, &Position, &mut CellChildren)>,
     building_layers: Query<&Layer, Or<(With<Building>, With<ConstructionSite>)>>,
     building_positions: Query<&Position, Or<(With<Building>, With<ConstructionSite>)>>,
+    indestructible: Query<(), With<Indestructible>>,
 ) {
     for event in remove_events.read() {
         let Some((_, _, mut cell_children)) = grid_cells
@@ -138,6 +176,14 @@ pub fn remove_building(
         let mut to_remove = Vec::new();
 
         for (index, &building_entity) in cell_children.0.iter().enumerate() {
+            if indestructible.contains(building_entity) {
+                warn!(
+                    x = event.grid_x,
+                    y = event.grid_y,
+                    "this building cannot be removed"
+                );
+                continue;
+            }
             if building_layers.contains(building_entity) {
                 if let Ok(pos) = building_positions.get(building_entity) {
                     if pos.x == event.grid_x && pos.y == event.grid_y {
@@ -155,3 +201,69 @@ pub fn remove_building(
         network_events.write(NetworkChangedEvent);
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::structures::building_config::BuildingIcon;
+
+    #[test]
+    fn placed_building_gets_its_configured_tint_and_icon() {
+        let registry = BuildingRegistry::from_ron(
+            r#"[
+                (
+                    name: "Smelter",
+                    category: Production,
+                    appearance: (
+                        size: (32.0, 32.0),
+                        color: (0.7, 0.5, 0.2, 1.0),
+                        multi_cell: None,
+                        icon: Some("S"),
+                    ),
+                    placement: (
+                        cost: (inputs: {}, crafting_time: 0.0),
+                        rules: [],
+                    ),
+                    components: [],
+                ),
+            ]"#,
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.add_message::<PlaceBuildingValidationEvent>()
+            .add_message::<NetworkChangedEvent>()
+            .insert_resource(Grid::new(64.0))
+            .insert_resource(registry)
+            .init_resource::<LastPlaced>()
+            .init_resource::<CreativeMode>()
+            .add_systems(Update, place_building);
+
+        let cell = app
+            .world_mut()
+            .spawn((Position { x: 0, y: 0 }, CellChildren(Vec::new())))
+            .id();
+        app.world_mut().write_message(PlaceBuildingValidationEvent {
+            result: Ok(()),
+            request: PlaceBuildingRequestEvent {
+                building_name: "Smelter".to_string(),
+                grid_x: 0,
+                grid_y: 0,
+                facing: Facing::default(),
+            },
+        });
+        app.update();
+
+        let site = app.world().get::<CellChildren>(cell).unwrap().0[0];
+        let sprite = app.world().get::<Sprite>(site).unwrap();
+        assert_eq!(sprite.color, Color::srgba(0.7, 0.5, 0.2, 0.7));
+
+        let children = app.world().get::<Children>(site).unwrap();
+        let icon = children
+            .iter()
+            .find(|&child| app.world().get::<BuildingIcon>(child).is_some())
+            .unwrap();
+        assert_eq!(app.world().get::<Text2d>(icon).unwrap().0, "S");
+    }
+}
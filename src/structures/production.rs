@@ -1,18 +1,77 @@
 use crate::{
     materials::{
-        items::{InputPort, InventoryAccess, ItemName, OutputPort},
-        ItemRegistry, RecipeRegistry,
+        items::{InputPort, InventoryAccess, ItemId, NamedOutputPorts, OutputPort},
+        ItemRegistry, RecipeDef, RecipeName, RecipeRegistry,
     },
-    structures::{ConstructionSite, Launchpad, RecipeCrafter},
-    systems::{GameScore, Operational},
+    structures::{ConstructionSite, Launchpad, RecipeCrafter, Sink},
+    systems::{GameScore, ItemThroughput, Operational},
 };
 use bevy::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+
+/// How many completed crafts each building remembers.
+pub const CRAFT_HISTORY_LEN: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraftRecord {
+    /// Game time in seconds when the craft finished.
+    pub completed_at: f32,
+    pub recipe: RecipeName,
+    pub outputs: HashMap<ItemId, u32>,
+}
+
+impl CraftRecord {
+    /// "[01:05] 1 Iron Ingot", falling back to the recipe name for crafts
+    /// without outputs.
+    #[must_use]
+    pub fn summary(&self, items: &ItemRegistry) -> String {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let secs = self.completed_at.max(0.0) as u32;
+        let produced = if self.outputs.is_empty() {
+            self.recipe.clone()
+        } else {
+            let mut outputs: Vec<String> = self
+                .outputs
+                .iter()
+                .map(|(&item, qty)| format!("{qty} {}", items.display_name(item)))
+                .collect();
+            outputs.sort_unstable();
+            outputs.join(", ")
+        };
+        format!("[{:02}:{:02}] {produced}", secs / 60, secs % 60)
+    }
+}
+
+/// The most recent crafts of one building, oldest first. `total` counts every
+/// craft ever recorded, so readers can tell when a new one lands.
+#[derive(Component, Debug, Default, Clone)]
+pub struct CraftHistory {
+    records: VecDeque<CraftRecord>,
+    pub total: u64,
+}
+
+impl CraftHistory {
+    pub fn record(&mut self, completed_at: f32, recipe: &RecipeDef) {
+        if self.records.len() == CRAFT_HISTORY_LEN {
+            self.records.pop_front();
+        }
+        self.records.push_back(CraftRecord {
+            completed_at,
+            recipe: recipe.name.clone(),
+            outputs: recipe.outputs.clone(),
+        });
+        self.total += 1;
+    }
+
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &CraftRecord> {
+        self.records.iter()
+    }
+}
 
 pub fn compute_item_limits(
     capacity: u32,
-    recipe_inputs: &HashMap<ItemName, u32>,
-) -> HashMap<ItemName, u32> {
+    recipe_inputs: &HashMap<ItemId, u32>,
+) -> HashMap<ItemId, u32> {
     if recipe_inputs.is_empty() {
         return HashMap::new();
     }
@@ -27,11 +86,21 @@ pub fn compute_item_limits(
             )
             .unwrap_or(u32::MAX);
             let limit = proportional.max(qty);
-            (item.clone(), limit)
+            (*item, limit)
         })
         .collect()
 }
 
+/// Input limits for a recipe: catalysts are capped at exactly what the recipe
+/// needs so they are delivered once, and the regular inputs share the rest of
+/// the capacity.
+pub fn recipe_item_limits(capacity: u32, recipe: &RecipeDef) -> HashMap<ItemId, u32> {
+    let reserved: u32 = recipe.catalysts.values().sum();
+    let mut limits = compute_item_limits(capacity.saturating_sub(reserved), &recipe.inputs);
+    limits.extend(recipe.catalysts.iter().map(|(&item, &qty)| (item, qty)));
+    limits
+}
+
 pub fn sync_input_port_limits(
     mut query: Query<(&mut InputPort, &RecipeCrafter), Without<ConstructionSite>>,
     recipes: Res<RecipeRegistry>,
@@ -41,7 +110,7 @@ pub fn sync_input_port_limits(
             .get_active_recipe()
             .and_then(|name| recipes.get_definition(name))
             .map_or_else(HashMap::new, |recipe| {
-                compute_item_limits(input_port.capacity, &recipe.inputs)
+                recipe_item_limits(input_port.capacity, recipe)
             });
 
         if input_port.item_limits != new_limits {
@@ -50,61 +119,140 @@ pub fn sync_input_port_limits(
     }
 }
 
+/// Groups a recipe's outputs by destination: `Some(tag)` for a port in
+/// `NamedOutputPorts`, `None` for the default `OutputPort`. Tags the building
+/// doesn't have fall back to the default port.
+fn route_outputs<'a>(
+    recipe: &'a RecipeDef,
+    named_ports: Option<&NamedOutputPorts>,
+) -> HashMap<Option<&'a str>, HashMap<ItemId, u32>> {
+    let mut routes: HashMap<Option<&str>, HashMap<ItemId, u32>> = HashMap::new();
+    for (&item, &qty) in &recipe.outputs {
+        let tag = recipe
+            .output_ports
+            .get(&item)
+            .map(String::as_str)
+            .filter(|tag| named_ports.is_some_and(|ports| ports.get(tag).is_some()));
+        *routes.entry(tag).or_default().entry(item).or_insert(0) += qty;
+    }
+    routes
+}
+
+fn has_space_for_outputs(
+    recipe: &RecipeDef,
+    output_port: &OutputPort,
+    named_ports: Option<&NamedOutputPorts>,
+) -> bool {
+    route_outputs(recipe, named_ports)
+        .iter()
+        .all(|(tag, items)| match tag {
+            Some(tag) => named_ports
+                .and_then(|ports| ports.get(tag))
+                .is_some_and(|port| port.has_space_for(items)),
+            None => output_port.has_space_for(items),
+        })
+}
+
+fn deposit_outputs(
+    recipe: &RecipeDef,
+    output_port: &mut OutputPort,
+    mut named_ports: Option<&mut NamedOutputPorts>,
+    throughput: &mut ItemThroughput,
+) {
+    for (tag, items) in route_outputs(recipe, named_ports.as_deref()) {
+        let port = match (tag, named_ports.as_deref_mut()) {
+            (Some(tag), Some(ports)) => ports.get_mut(tag),
+            _ => None,
+        }
+        .unwrap_or(&mut *output_port);
+        for (item, qty) in items {
+            port.add_item(item, qty);
+            throughput.record_produced(item, qty);
+        }
+    }
+}
+
+/// A craft starts only once every input and catalyst of the active recipe is
+/// present, and the inputs are consumed together at that moment. Catalysts
+/// stay in the port. Outputs are added when the crafting timer finishes.
 pub fn update_port_crafters(
     mut query: Query<(
         &mut InputPort,
         &mut OutputPort,
+        Option<&mut NamedOutputPorts>,
         &mut RecipeCrafter,
+        &mut CraftHistory,
         &Operational,
     )>,
     recipes: Res<RecipeRegistry>,
     time: Res<Time>,
+    mut throughput: ResMut<ItemThroughput>,
 ) {
-    for (mut input_port, mut output_port, mut crafter, operational) in &mut query {
+    for (mut input_port, mut output_port, mut named_ports, mut crafter, mut history, operational) in
+        &mut query
+    {
         if !operational.get_status() {
             continue;
         }
 
-        if !crafter.timer.tick(time.delta()).just_finished() {
-            continue;
-        }
+        if crafter.in_progress.is_none() {
+            let Some(recipe_name) = crafter.get_active_recipe().cloned() else {
+                continue;
+            };
 
-        let Some(recipe_name) = crafter.get_active_recipe() else {
-            crafter.timer.reset();
-            continue;
-        };
-
-        let Some(recipe) = recipes.get_definition(recipe_name) else {
-            crafter.timer.reset();
-            continue;
-        };
+            let Some(recipe) = recipes.get_definition(&recipe_name) else {
+                continue;
+            };
 
-        let has_inputs = recipe
-            .inputs
-            .iter()
-            .all(|(item, qty)| input_port.get_item_quantity(item) >= *qty);
-
-        let has_space = output_port.has_space_for(&recipe.outputs);
-
-        if has_inputs && has_space {
-            for (item, qty) in &recipe.inputs {
-                input_port.remove_item(item, *qty);
+            if !has_space_for_outputs(recipe, &output_port, named_ports.as_deref())
+                || !input_port.has_items_for_recipe(&recipe.catalysts)
+                || !input_port.consume_recipe_inputs(&recipe.inputs)
+            {
+                continue;
             }
-            for (item, qty) in &recipe.outputs {
-                output_port.add_item(item, *qty);
+            for (&item, &qty) in &recipe.inputs {
+                throughput.record_consumed(item, qty);
             }
+
+            crafter.in_progress = Some(recipe_name);
+            crafter.timer.reset();
         }
 
+        if !crafter.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let finished = crafter.in_progress.take();
         crafter.timer.reset();
+
+        if let Some(recipe) = finished.and_then(|name| recipes.get_definition(&name)) {
+            deposit_outputs(
+                recipe,
+                &mut output_port,
+                named_ports.as_deref_mut(),
+                &mut throughput,
+            );
+            history.record(time.elapsed_secs(), recipe);
+        }
     }
 }
 
 pub fn update_source_port_crafters(
-    mut query: Query<(&mut OutputPort, &mut RecipeCrafter, &Operational), Without<InputPort>>,
+    mut query: Query<
+        (
+            &mut OutputPort,
+            Option<&mut NamedOutputPorts>,
+            &mut RecipeCrafter,
+            &mut CraftHistory,
+            &Operational,
+        ),
+        Without<InputPort>,
+    >,
     recipes: Res<RecipeRegistry>,
     time: Res<Time>,
+    mut throughput: ResMut<ItemThroughput>,
 ) {
-    for (mut output_port, mut crafter, operational) in &mut query {
+    for (mut output_port, mut named_ports, mut crafter, mut history, operational) in &mut query {
         if !operational.get_status() {
             continue;
         }
@@ -123,12 +271,14 @@ pub fn update_source_port_crafters(
             continue;
         };
 
-        let has_space = output_port.has_space_for(&recipe.outputs);
-
-        if has_space {
-            for (item, qty) in &recipe.outputs {
-                output_port.add_item(item, *qty);
-            }
+        if has_space_for_outputs(recipe, &output_port, named_ports.as_deref()) {
+            deposit_outputs(
+                recipe,
+                &mut output_port,
+                named_ports.as_deref_mut(),
+                &mut throughput,
+            );
+            history.record(time.elapsed_secs(), recipe);
         }
 
         crafter.timer.reset();
@@ -140,6 +290,7 @@ pub fn update_sink_port_crafters(
         (
             &mut InputPort,
             &mut RecipeCrafter,
+            &mut CraftHistory,
             &Operational,
             Option<&Launchpad>,
         ),
@@ -149,53 +300,94 @@ pub fn update_sink_port_crafters(
     item_registry: Res<ItemRegistry>,
     mut score: ResMut<GameScore>,
     time: Res<Time>,
+    mut throughput: ResMut<ItemThroughput>,
 ) {
-    for (mut input_port, mut crafter, operational, is_launchpad) in &mut query {
+    for (mut input_port, mut crafter, mut history, operational, is_launchpad) in &mut query {
         if !operational.get_status() {
             continue;
         }
 
+        if crafter.in_progress.is_none() {
+            let Some(recipe_name) = crafter.get_active_recipe().cloned() else {
+                continue;
+            };
+
+            let Some(recipe) = recipes.get_definition(&recipe_name) else {
+                continue;
+            };
+
+            if !input_port.has_items_for_recipe(&recipe.catalysts)
+                || !input_port.consume_recipe_inputs(&recipe.inputs)
+            {
+                continue;
+            }
+            for (&item, &qty) in &recipe.inputs {
+                throughput.record_consumed(item, qty);
+            }
+
+            crafter.in_progress = Some(recipe_name);
+            crafter.timer.reset();
+        }
+
         if !crafter.timer.tick(time.delta()).just_finished() {
             continue;
         }
 
-        let Some(recipe_name) = crafter.get_active_recipe() else {
-            crafter.timer.reset();
+        let finished = crafter.in_progress.take();
+        crafter.timer.reset();
+
+        let Some(recipe) = finished.and_then(|name| recipes.get_definition(&name)) else {
             continue;
         };
+        history.record(time.elapsed_secs(), recipe);
 
-        let Some(recipe) = recipes.get_definition(recipe_name) else {
-            crafter.timer.reset();
+        if is_launchpad.is_none() {
             continue;
-        };
+        }
 
-        let has_inputs = recipe
-            .inputs
-            .iter()
-            .all(|(item, qty)| input_port.get_item_quantity(item) >= *qty);
+        if let Some((&item, _)) = recipe.inputs.iter().next() {
+            let points = item_registry.value_of(item).saturating_mul(10);
+            score.total_score = score.total_score.saturating_add(points);
+            score.launches_completed += 1;
+            info!(?item, points, total = score.total_score, "launch completed");
+        }
+    }
+}
 
-        if has_inputs {
-            for (item, qty) in &recipe.inputs {
-                input_port.remove_item(item, *qty);
+pub fn consume_sink_items(
+    mut query: Query<(&mut InputPort, Ref<Sink>, &Operational)>,
+    item_registry: Res<ItemRegistry>,
+    mut score: ResMut<GameScore>,
+    mut throughput: ResMut<ItemThroughput>,
+) {
+    for (mut input_port, sink, operational) in &mut query {
+        if sink.is_added() {
+            if let Some(accepted) = &sink.accepted {
+                let capacity = input_port.capacity;
+                input_port.item_limits = accepted.iter().map(|&item| (item, capacity)).collect();
             }
+        }
 
-            if is_launchpad.is_some() {
-                if let Some((item_name, _)) = recipe.inputs.iter().next() {
-                    let tier = item_registry
-                        .get_definition(item_name)
-                        .map_or(0, |def| def.tier);
-                    let points = 10 * u64::from((tier + 1).pow(2));
-                    score.total_score += points;
-                    score.launches_completed += 1;
-                    println!(
-                        "Launch completed! {} items launched for {} points (total: {})",
-                        item_name, points, score.total_score
-                    );
-                }
-            }
+        if !operational.get_status() || input_port.is_empty() {
+            continue;
         }
 
-        crafter.timer.reset();
+        let delivered: Vec<(ItemId, u32)> = input_port
+            .items
+            .iter()
+            .filter(|(&item, _)| sink.accepts(item))
+            .map(|(&item, &quantity)| (item, quantity))
+            .collect();
+
+        for (item, quantity) in delivered {
+            let consumed = input_port.remove_item(item, quantity);
+            let value = sink.value.item_value(item_registry.value_of(item));
+            score.total_score = score
+                .total_score
+                .saturating_add(value.saturating_mul(u64::from(consumed)));
+            score.items_sunk = score.items_sunk.saturating_add(u64::from(consumed));
+            throughput.record_consumed(item, consumed);
+        }
     }
 }
 
@@ -204,35 +396,66 @@ pub fn update_sink_port_crafters(
 mod tests {
     use super::*;
     use crate::materials::RecipeName;
-    use bevy::ecs::system::SystemState;
+    use crate::structures::{Facing, SinkValue};
+    use bevy::ecs::system::{RunSystemOnce, SystemState};
 
+    /// Parses `ron` after registering the made-up items the test recipes use.
     fn make_recipe_registry(ron: &str) -> RecipeRegistry {
+        ItemRegistry::from_ron(
+            r#"[
+                (name: "A", tier: 0),
+                (name: "B", tier: 0),
+                (name: "Scrap", tier: 0),
+                (name: "Crude Oil", tier: 0),
+                (name: "Platinum Mesh", tier: 2),
+                (name: "Refined Oil", tier: 1),
+            ]"#,
+        )
+        .unwrap();
         RecipeRegistry::from_ron(ron).unwrap()
     }
 
     #[test]
     fn compute_item_limits_multi_input_proportional() {
         let mut inputs = HashMap::new();
-        inputs.insert("Iron Ore".to_string(), 2);
-        inputs.insert("Coal".to_string(), 1);
+        inputs.insert(ItemId::named("Iron Ore").unwrap(), 2);
+        inputs.insert(ItemId::named("Coal").unwrap(), 1);
 
         let limits = compute_item_limits(50, &inputs);
 
         // ceil(50 * 2/3) = 34, max(34, 2) = 34
-        assert_eq!(limits.get("Iron Ore").copied().unwrap(), 34);
+        assert_eq!(
+            limits
+                .get(&ItemId::named("Iron Ore").unwrap())
+                .copied()
+                .unwrap(),
+            34
+        );
         // ceil(50 * 1/3) = 17, max(17, 1) = 17
-        assert_eq!(limits.get("Coal").copied().unwrap(), 17);
+        assert_eq!(
+            limits
+                .get(&ItemId::named("Coal").unwrap())
+                .copied()
+                .unwrap(),
+            17
+        );
         assert_eq!(limits.len(), 2);
     }
 
     #[test]
     fn compute_item_limits_single_input_gets_full_capacity() {
         let mut inputs = HashMap::new();
-        inputs.insert("Coal".to_string(), 1);
+        inputs.insert(ItemId::named("Coal").unwrap(), 1);
 
         let limits = compute_item_limits(50, &inputs);
 
-        assert_eq!(limits.get("Coal").copied().unwrap(), 50);
+        assert_eq!(
+            limits
+                .get(&ItemId::named("Coal").unwrap())
+                .copied()
+                .unwrap(),
+            50
+        );
         assert_eq!(limits.len(), 1);
     }
 
@@ -246,25 +469,49 @@ mod tests {
     #[test]
     fn compute_item_limits_guarantees_at_least_one_batch() {
         let mut inputs = HashMap::new();
-        inputs.insert("Rare".to_string(), 10);
-        inputs.insert("Common".to_string(), 1);
+        inputs.insert(ItemId::named("Gearbox").unwrap(), 10);
+        inputs.insert(ItemId::named("Iron Ore").unwrap(), 1);
 
-        // capacity=5, Rare: ceil(5*10/11)=5, max(5,10)=10
+        // capacity=5, Gearbox: ceil(5*10/11)=5, max(5,10)=10
         let limits = compute_item_limits(5, &inputs);
-        assert!(limits.get("Rare").copied().unwrap() >= 10);
-        assert!(limits.get("Common").copied().unwrap() >= 1);
+        assert!(
+            limits
+                .get(&ItemId::named("Gearbox").unwrap())
+                .copied()
+                .unwrap()
+                >= 10
+        );
+        assert!(
+            limits
+                .get(&ItemId::named("Iron Ore").unwrap())
+                .copied()
+                .unwrap()
+                >= 1
+        );
     }
 
     #[test]
     fn compute_item_limits_equal_inputs() {
         let mut inputs = HashMap::new();
-        inputs.insert("A".to_string(), 1);
-        inputs.insert("B".to_string(), 1);
+        inputs.insert(ItemId::named("Iron Ore").unwrap(), 1);
+        inputs.insert(ItemId::named("Coal").unwrap(), 1);
 
         let limits = compute_item_limits(100, &inputs);
 
-        assert_eq!(limits.get("A").copied().unwrap(), 50);
-        assert_eq!(limits.get("B").copied().unwrap(), 50);
+        assert_eq!(
+            limits
+                .get(&ItemId::named("Iron Ore").unwrap())
+                .copied()
+                .unwrap(),
+            50
+        );
+        assert_eq!(
+            limits
+                .get(&ItemId::named("Coal").unwrap())
+                .copied()
+                .unwrap(),
+            50
+        );
     }
 
     #[test]
@@ -287,6 +534,7 @@ mod tests {
             current_recipe: Some(recipe_name),
             available_recipes: Vec::new(),
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
         };
 
         let entity = app.world_mut().spawn((InputPort::new(50), crafter)).id();
@@ -301,8 +549,20 @@ mod tests {
         system_state.apply(app.world_mut());
 
         let port = app.world().entity(entity).get::<InputPort>().unwrap();
-        assert_eq!(port.item_limits.get("Iron Ore").copied().unwrap(), 34);
-        assert_eq!(port.item_limits.get("Coal").copied().unwrap(), 17);
+        assert_eq!(
+            port.item_limits
+                .get(&ItemId::named("Iron Ore").unwrap())
+                .copied()
+                .unwrap(),
+            34
+        );
+        assert_eq!(
+            port.item_limits
+                .get(&ItemId::named("Coal").unwrap())
+                .copied()
+                .unwrap(),
+            17
+        );
         assert_eq!(port.item_limits.len(), 2);
     }
 
@@ -318,10 +578,11 @@ mod tests {
             current_recipe: None,
             available_recipes: Vec::new(),
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
         };
 
         let mut port = InputPort::new(50);
-        port.item_limits.insert("Stale".to_string(), 25);
+        port.item_limits.insert(ItemId::named("Gear").unwrap(), 25);
 
         let entity = app.world_mut().spawn((port, crafter)).id();
 
@@ -357,6 +618,7 @@ mod tests {
             current_recipe: Some("Test".to_string()),
             available_recipes: Vec::new(),
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
         };
 
         let entity = app
@@ -366,6 +628,7 @@ mod tests {
                 crafter,
                 ConstructionSite {
                     building_name: "Smelter".to_string(),
+                    facing: Facing::default(),
                 },
             ))
             .id();
@@ -382,4 +645,311 @@ mod tests {
         let port = app.world().entity(entity).get::<InputPort>().unwrap();
         assert!(port.item_limits.is_empty());
     }
+
+    #[test]
+    fn port_crafter_consumes_nothing_until_all_inputs_present() {
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        app.init_resource::<ItemThroughput>();
+
+        let ron = r#"[
+            (
+                name: "Iron Ingot",
+                inputs: {"Iron Ore": 2, "Coal": 1},
+                outputs: {"Iron Ingot": 1},
+                crafting_time: 2.0,
+            ),
+        ]"#;
+        app.insert_resource(make_recipe_registry(ron));
+
+        let crafter = RecipeCrafter {
+            current_recipe: Some("Iron Ingot".to_string()),
+            available_recipes: Vec::new(),
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
+        };
+        let mut input = InputPort::new(50);
+        input.add_item(ItemId::named("Iron Ore").unwrap(), 4);
+
+        let entity = app
+            .world_mut()
+            .spawn((input, OutputPort::new(10), crafter, Operational(None)))
+            .id();
+
+        let mut system_state: SystemState<(
+            Query<(
+                &mut InputPort,
+                &mut OutputPort,
+                Option<&mut NamedOutputPorts>,
+                &mut RecipeCrafter,
+                &mut CraftHistory,
+                &Operational,
+            )>,
+            Res<RecipeRegistry>,
+            Res<Time>,
+            ResMut<ItemThroughput>,
+        )> = SystemState::new(app.world_mut());
+
+        let (query, recipes, time, throughput) = system_state.get_mut(app.world_mut());
+        update_port_crafters(query, recipes, time, throughput);
+        system_state.apply(app.world_mut());
+
+        let port = app.world().get::<InputPort>(entity).unwrap();
+        assert_eq!(
+            port.get_item_quantity(ItemId::named("Iron Ore").unwrap()),
+            4
+        );
+        assert!(app
+            .world()
+            .get::<RecipeCrafter>(entity)
+            .unwrap()
+            .in_progress
+            .is_none());
+
+        app.world_mut()
+            .get_mut::<InputPort>(entity)
+            .unwrap()
+            .add_item(ItemId::named("Coal").unwrap(), 1);
+
+        let (query, recipes, time, throughput) = system_state.get_mut(app.world_mut());
+        update_port_crafters(query, recipes, time, throughput);
+        system_state.apply(app.world_mut());
+
+        let port = app.world().get::<InputPort>(entity).unwrap();
+        assert_eq!(
+            port.get_item_quantity(ItemId::named("Iron Ore").unwrap()),
+            2
+        );
+        assert_eq!(port.get_item_quantity(ItemId::named("Coal").unwrap()), 0);
+        assert_eq!(
+            app.world()
+                .get::<RecipeCrafter>(entity)
+                .unwrap()
+                .in_progress
+                .as_deref(),
+            Some("Iron Ingot")
+        );
+    }
+
+    #[test]
+    fn tagged_outputs_land_in_their_named_ports() {
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        app.init_resource::<ItemThroughput>();
+
+        let ron = r#"[
+            (
+                name: "Iron Plate",
+                inputs: {"Iron Ingot": 1},
+                outputs: {"Iron Plate": 1, "Scrap": 1},
+                output_ports: {"Iron Plate": "plates", "Scrap": "scrap"},
+                crafting_time: 1.0,
+            ),
+        ]"#;
+        app.insert_resource(make_recipe_registry(ron));
+
+        let crafter = RecipeCrafter {
+            current_recipe: Some("Iron Plate".to_string()),
+            available_recipes: Vec::new(),
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            in_progress: None,
+        };
+        let mut input = InputPort::new(10);
+        input.add_item(ItemId::named("Iron Ingot").unwrap(), 1);
+        let named = NamedOutputPorts {
+            ports: HashMap::from([
+                ("plates".to_string(), OutputPort::new(10)),
+                ("scrap".to_string(), OutputPort::new(10)),
+            ]),
+        };
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                input,
+                OutputPort::new(10),
+                named,
+                crafter,
+                Operational(None),
+            ))
+            .id();
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(1));
+        app.world_mut()
+            .run_system_once(update_port_crafters)
+            .unwrap();
+
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let scrap = ItemId::named("Scrap").unwrap();
+        let named = app.world().get::<NamedOutputPorts>(entity).unwrap();
+        let plates = named.get("plates").unwrap();
+        let scraps = named.get("scrap").unwrap();
+        assert_eq!(plates.get_item_quantity(plate), 1);
+        assert_eq!(plates.get_item_quantity(scrap), 0);
+        assert_eq!(scraps.get_item_quantity(scrap), 1);
+        assert_eq!(scraps.get_item_quantity(plate), 0);
+        assert!(app.world().get::<OutputPort>(entity).unwrap().is_empty());
+    }
+
+    #[test]
+    fn catalysts_gate_crafting_without_being_consumed() {
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        app.init_resource::<ItemThroughput>();
+
+        let ron = r#"[
+            (
+                name: "Refined Oil",
+                inputs: {"Crude Oil": 2},
+                outputs: {"Refined Oil": 1},
+                catalysts: {"Platinum Mesh": 1},
+                crafting_time: 1.0,
+            ),
+        ]"#;
+        let registry = make_recipe_registry(ron);
+        let crude = ItemId::named("Crude Oil").unwrap();
+        let mesh = ItemId::named("Platinum Mesh").unwrap();
+        let refined = ItemId::named("Refined Oil").unwrap();
+
+        let limits = recipe_item_limits(21, registry.get_definition("Refined Oil").unwrap());
+        assert_eq!(limits.get(&mesh), Some(&1));
+        assert_eq!(limits.get(&crude), Some(&20));
+        app.insert_resource(registry);
+
+        let mut input = InputPort::new(20);
+        input.add_item(crude, 6);
+        let entity = app
+            .world_mut()
+            .spawn((
+                input,
+                OutputPort::new(10),
+                RecipeCrafter {
+                    current_recipe: Some("Refined Oil".to_string()),
+                    available_recipes: Vec::new(),
+                    timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    in_progress: None,
+                },
+                Operational(None),
+            ))
+            .id();
+
+        let craft = |app: &mut App| {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(std::time::Duration::from_secs(1));
+            app.world_mut()
+                .run_system_once(update_port_crafters)
+                .unwrap();
+        };
+
+        craft(&mut app);
+        assert!(app.world().get::<OutputPort>(entity).unwrap().is_empty());
+        assert_eq!(
+            app.world()
+                .get::<InputPort>(entity)
+                .unwrap()
+                .get_item_quantity(crude),
+            6
+        );
+
+        app.world_mut()
+            .get_mut::<InputPort>(entity)
+            .unwrap()
+            .add_item(mesh, 1);
+        craft(&mut app);
+        craft(&mut app);
+
+        let input = app.world().get::<InputPort>(entity).unwrap();
+        assert_eq!(input.get_item_quantity(mesh), 1);
+        assert_eq!(input.get_item_quantity(crude), 2);
+        assert_eq!(
+            app.world()
+                .get::<OutputPort>(entity)
+                .unwrap()
+                .get_item_quantity(refined),
+            2
+        );
+    }
+
+    #[test]
+    fn completed_crafts_are_logged_in_order() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<ItemThroughput>()
+            .insert_resource(make_recipe_registry(
+                r#"[
+                    (
+                        name: "Copper Wire",
+                        inputs: {"Copper Ingot": 1},
+                        outputs: {"Copper Wire": 2},
+                        crafting_time: 1.0,
+                    ),
+                ]"#,
+            ))
+            .add_systems(Update, update_port_crafters);
+
+        let mut input = InputPort::new(10);
+        input.add_item(ItemId::named("Copper Ingot").unwrap(), 2);
+        let entity = app
+            .world_mut()
+            .spawn((
+                input,
+                OutputPort::new(10),
+                RecipeCrafter {
+                    current_recipe: Some("Copper Wire".to_string()),
+                    available_recipes: Vec::new(),
+                    timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    in_progress: None,
+                },
+                Operational(None),
+            ))
+            .id();
+
+        for _ in 0..2 {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(std::time::Duration::from_secs(1));
+            app.update();
+        }
+
+        let items = ItemRegistry::from_ron(r#"[(name: "Copper Wire", tier: 1)]"#).unwrap();
+        let history = app.world().get::<CraftHistory>(entity).unwrap();
+        let lines: Vec<String> = history
+            .records()
+            .map(|record| record.summary(&items))
+            .collect();
+        assert_eq!(lines, ["[00:01] 2 Copper Wire", "[00:02] 2 Copper Wire"]);
+        assert_eq!(history.total, 2);
+    }
+
+    #[test]
+    fn sink_scores_saturate_instead_of_overflowing() {
+        let mut app = App::new();
+        app.insert_resource(ItemRegistry::load_from_assets().unwrap())
+            .insert_resource(GameScore {
+                total_score: u64::MAX - 1,
+                ..default()
+            })
+            .init_resource::<ItemThroughput>()
+            .add_systems(Update, consume_sink_items);
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let mut input = InputPort::new(100);
+        input.add_item(plate, 5);
+        app.world_mut().spawn((
+            input,
+            Sink {
+                accepted: None,
+                value: SinkValue::Weighted(u64::MAX / 2),
+            },
+            Operational(None),
+        ));
+
+        app.update();
+
+        let score = app.world().resource::<GameScore>();
+        assert_eq!(score.total_score, u64::MAX);
+        assert_eq!(score.items_sunk, 5);
+    }
 }
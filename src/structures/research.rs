@@ -0,0 +1,136 @@
+use crate::{
+    materials::{InputPort, InventoryAccess, ItemId, RecipeName},
+    structures::building_config::{BuildingComponentDef, BuildingRegistry},
+    systems::{ItemThroughput, Operational},
+};
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Consumes delivered science items until every requirement is met, then
+/// unlocks `target_unlock` in `TechState`.
+#[derive(Component, Debug, Clone)]
+pub struct Research {
+    pub target_unlock: RecipeName,
+    pub required: HashMap<ItemId, u32>,
+    pub progress: HashMap<ItemId, u32>,
+}
+
+impl Research {
+    pub fn new(target_unlock: RecipeName, required: HashMap<ItemId, u32>) -> Self {
+        Self {
+            target_unlock,
+            required,
+            progress: HashMap::new(),
+        }
+    }
+
+    pub fn remaining(&self, item: ItemId) -> u32 {
+        let required = self.required.get(&item).copied().unwrap_or(0);
+        required.saturating_sub(self.progress.get(&item).copied().unwrap_or(0))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.required.keys().all(|&item| self.remaining(item) == 0)
+    }
+}
+
+/// Recipes gated behind research. Anything a research building can unlock
+/// starts locked; every other recipe is always available.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct TechState {
+    locked: HashSet<RecipeName>,
+}
+
+impl TechState {
+    pub fn from_buildings(registry: &BuildingRegistry) -> Self {
+        let locked = registry
+            .definitions
+            .values()
+            .flat_map(|def| &def.components)
+            .filter_map(|component| match component {
+                BuildingComponentDef::Research { target_unlock, .. } => Some(target_unlock.clone()),
+                _ => None,
+            })
+            .collect();
+        Self { locked }
+    }
+
+    pub fn is_unlocked(&self, recipe_name: &str) -> bool {
+        !self.locked.contains(recipe_name)
+    }
+
+    /// Returns true if the recipe was locked before this call.
+    pub fn unlock(&mut self, recipe_name: &str) -> bool {
+        self.locked.remove(recipe_name)
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+pub struct ResearchCompletedEvent {
+    pub building: Entity,
+    pub unlocked: RecipeName,
+}
+
+pub fn advance_research(
+    mut labs: Query<(Entity, &mut Research, &mut InputPort, &Operational)>,
+    mut tech: ResMut<TechState>,
+    mut throughput: ResMut<ItemThroughput>,
+    mut completed: MessageWriter<ResearchCompletedEvent>,
+) {
+    for (entity, mut research, mut input_port, operational) in &mut labs {
+        if research.is_added() {
+            input_port.item_limits = research.required.clone();
+        }
+
+        if research.is_complete() || !operational.get_status() || input_port.is_empty() {
+            continue;
+        }
+
+        let wanted: Vec<(ItemId, u32)> = research
+            .required
+            .keys()
+            .map(|&item| (item, research.remaining(item)))
+            .filter(|&(_, remaining)| remaining > 0)
+            .collect();
+        for (item, remaining) in wanted {
+            let consumed = input_port.remove_item(item, remaining);
+            if consumed > 0 {
+                *research.progress.entry(item).or_default() += consumed;
+                throughput.record_consumed(item, consumed);
+            }
+        }
+
+        if research.is_complete() && tech.unlock(&research.target_unlock) {
+            info!(recipe = %research.target_unlock, "research complete");
+            completed.write(ResearchCompletedEvent {
+                building: entity,
+                unlocked: research.target_unlock.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn research_tracks_remaining_per_item() {
+        let gear = ItemId::named("Gear").unwrap();
+        let wire = ItemId::named("Copper Wire").unwrap();
+        let mut research = Research::new(
+            "Electronic Circuit".to_string(),
+            HashMap::from([(gear, 4), (wire, 2)]),
+        );
+        research.progress.insert(gear, 4);
+        research.progress.insert(wire, 1);
+
+        assert_eq!(research.remaining(gear), 0);
+        assert_eq!(research.remaining(wire), 1);
+        assert!(!research.is_complete());
+
+        research.progress.insert(wire, 2);
+        assert!(research.is_complete());
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    grid::{CellChildren, Grid, Position},
+    structures::{
+        occupy_area, BuildingRegistry, Facing, RecipeCommitment, RecipeCrafter, TechState,
+    },
+    systems::NetworkChangedEvent,
+};
+
+/// How a game starts. Set before adding `BuildingsPlugin`.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ScenarioConfig {
+    /// Scenario file to start from. `None` starts with just the hub; set a
+    /// path to begin with a prepared layout.
+    pub path: Option<String>,
+    /// Ore of each kind the hub keeps back for construction sites, whatever
+    /// logistics asks of it. `None`, the default, keeps nothing back.
+    pub hub_construction_reserve: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ScenarioBuilding {
+    pub name: String,
+    pub position: (i32, i32),
+    #[serde(default)]
+    pub recipe: Option<String>,
+    #[serde(default)]
+    pub facing: Facing,
+}
+
+/// Buildings placed, already complete, next to the hub at startup.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct Scenario {
+    pub buildings: Vec<ScenarioBuilding>,
+}
+
+impl Scenario {
+    /// # Errors
+    /// Returns an error if the RON content fails to parse.
+    pub fn from_ron(ron_content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(ron::from_str(ron_content)?)
+    }
+
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    /// Loads the configured scenario, falling back to the hub-only start.
+    #[must_use]
+    pub fn from_config(config: &ScenarioConfig) -> Self {
+        let Some(path) = &config.path else {
+            return Self::default();
+        };
+        match Self::load_from_file(path) {
+            Ok(scenario) => {
+                info!(%path, buildings = scenario.buildings.len(), "loaded scenario");
+                scenario
+            }
+            Err(e) => {
+                error!("failed to load scenario '{path}': {e}");
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Spawns the scenario's buildings straight from the registry, skipping
+/// construction, so they start out complete.
+pub fn place_scenario_buildings(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    registry: Res<BuildingRegistry>,
+    tech: Res<TechState>,
+    grid: Res<Grid>,
+    mut grid_cells: Query<(Entity, &Position, &mut CellChildren)>,
+    mut network_events: MessageWriter<NetworkChangedEvent>,
+) {
+    for placement in &scenario.buildings {
+        let (x, y) = placement.position;
+        let Some(def) = registry.get_definition(&placement.name) else {
+            warn!(building = %placement.name, "unknown building in scenario");
+            continue;
+        };
+        let (width, height) = def.appearance.multi_cell.unwrap_or((1, 1));
+        let world_pos = grid.grid_to_world_coordinates(x, y);
+        let Some(entity) = registry.spawn_building(
+            &mut commands,
+            &placement.name,
+            x,
+            y,
+            world_pos,
+            placement.facing,
+        ) else {
+            continue;
+        };
+
+        if let Some(recipe) = placement.recipe.clone() {
+            if tech.is_unlocked(&recipe) {
+                let tech = tech.clone();
+                commands
+                    .entity(entity)
+                    .insert(RecipeCommitment::new_committed(Some(recipe.clone())))
+                    .entry::<RecipeCrafter>()
+                    .and_modify(move |mut crafter| {
+                        if let Err(error) = crafter.set_recipe(recipe, &tech) {
+                            warn!(%error, "failed to set scenario recipe");
+                        }
+                    });
+            } else {
+                warn!(%recipe, "scenario recipe is locked behind research");
+            }
+        }
+
+        occupy_area(&mut grid_cells, x, y, width, height, entity);
+        info!(building = %placement.name, x, y, "placed scenario building");
+    }
+
+    if !scenario.buildings.is_empty() {
+        network_events.write(NetworkChangedEvent);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{structures::Building, systems::Operational};
+
+    #[test]
+    fn two_building_scenario_starts_complete_and_operational() {
+        let scenario =
+            Scenario::from_ron(include_str!("../assets/scenarios/smelting_start.ron")).unwrap();
+        assert_eq!(scenario.buildings.len(), 2);
+
+        let mut app = App::new();
+        app.insert_resource(scenario)
+            .insert_resource(BuildingRegistry::load_from_assets().unwrap())
+            .insert_resource(Grid::new(64.0))
+            .init_resource::<TechState>()
+            .add_message::<NetworkChangedEvent>()
+            .add_systems(Startup, place_scenario_buildings);
+        app.update();
+
+        let mut buildings = app
+            .world_mut()
+            .query_filtered::<(&Name, &Position, &Operational), With<Building>>();
+        let placed: Vec<(String, (i32, i32), bool)> = buildings
+            .iter(app.world())
+            .map(|(name, pos, operational)| {
+                (name.to_string(), (pos.x, pos.y), operational.get_status())
+            })
+            .collect();
+        assert_eq!(placed.len(), 2);
+        assert!(placed.contains(&("Smelter".to_string(), (2, 0), true)));
+        assert!(placed.contains(&("Storage".to_string(), (0, 2), true)));
+
+        let mut crafters = app.world_mut().query::<&RecipeCrafter>();
+        let smelter = crafters.single(app.world()).unwrap();
+        assert_eq!(smelter.current_recipe.as_deref(), Some("Iron Ingot"));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::materials::{InventoryAccess, ItemId, StoragePort};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CapacityUpgradeTier {
+    pub cost: HashMap<ItemId, u32>,
+    pub capacity_bonus: u32,
+}
+
+/// Storage capacity tiers bought in order. Each tier's cost is paid out of the
+/// building's own storage, so materials have to be delivered there first.
+#[derive(Component, Debug, Clone)]
+pub struct CapacityUpgrades {
+    pub tiers: Vec<CapacityUpgradeTier>,
+    pub level: usize,
+}
+
+impl CapacityUpgrades {
+    #[must_use]
+    pub fn new(tiers: Vec<CapacityUpgradeTier>) -> Self {
+        Self { tiers, level: 0 }
+    }
+
+    #[must_use]
+    pub fn next_tier(&self) -> Option<&CapacityUpgradeTier> {
+        self.tiers.get(self.level)
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+pub struct CapacityUpgradeEvent {
+    pub building: Entity,
+}
+
+pub fn apply_capacity_upgrades(
+    mut events: MessageReader<CapacityUpgradeEvent>,
+    mut buildings: Query<(&mut CapacityUpgrades, &mut StoragePort)>,
+) {
+    for event in events.read() {
+        let Ok((mut upgrades, mut storage)) = buildings.get_mut(event.building) else {
+            continue;
+        };
+        let Some(tier) = upgrades.next_tier().cloned() else {
+            continue;
+        };
+
+        if !storage.consume_recipe_inputs(&tier.cost) {
+            warn!(building = ?event.building, "capacity upgrade is missing materials");
+            continue;
+        }
+
+        storage.capacity += tier.capacity_bonus;
+        upgrades.level += 1;
+        info!(
+            building = ?event.building,
+            level = upgrades.level,
+            capacity = storage.capacity,
+            "storage capacity upgraded"
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::structures::DispatchWeight;
+
+    #[test]
+    fn upgrade_consumes_materials_and_raises_capacity() {
+        let mut app = App::new();
+        app.add_message::<CapacityUpgradeEvent>()
+            .add_systems(Update, apply_capacity_upgrades);
+
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let tiers = vec![
+            CapacityUpgradeTier {
+                cost: HashMap::from([(plate, 10)]),
+                capacity_bonus: 100,
+            },
+            CapacityUpgradeTier {
+                cost: HashMap::from([(plate, 25)]),
+                capacity_bonus: 100,
+            },
+        ];
+        let mut storage = StoragePort::new(200);
+        storage.add_item(plate, 15);
+        let building = app
+            .world_mut()
+            .spawn((CapacityUpgrades::new(tiers), storage))
+            .id();
+
+        app.world_mut()
+            .write_message(CapacityUpgradeEvent { building });
+        app.update();
+
+        let storage = app.world().get::<StoragePort>(building).unwrap();
+        assert_eq!(storage.capacity, 300);
+        assert_eq!(storage.get_item_quantity(plate), 5);
+        assert_eq!(DispatchWeight::of(storage), 3);
+        assert_eq!(
+            app.world().get::<CapacityUpgrades>(building).unwrap().level,
+            1
+        );
+
+        // The second tier costs more than what is left, so nothing changes.
+        app.world_mut()
+            .write_message(CapacityUpgradeEvent { building });
+        app.update();
+
+        let storage = app.world().get::<StoragePort>(building).unwrap();
+        assert_eq!(storage.capacity, 300);
+        assert_eq!(storage.get_item_quantity(plate), 5);
+    }
+}
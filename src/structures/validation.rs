@@ -8,7 +8,10 @@ use crate::{
 };
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+};
 
 #[derive(Message)]
 pub struct PlaceBuildingValidationEvent {
@@ -43,8 +46,19 @@ impl fmt::Display for PlacementError {
     }
 }
 
+/// Placement requests waiting to be validated, oldest first. A request for
+/// a cell another request claimed earlier in the same frame is held here
+/// until the next frame, when the grid shows the first placement.
+#[derive(Resource, Debug, Default)]
+pub struct PlacementQueue {
+    pub pending: VecDeque<PlaceBuildingRequestEvent>,
+}
+
+/// Drains the queue in order, validating each request against the grid and
+/// the cells already claimed this frame.
 pub fn validate_placement(
     mut place_request: MessageReader<PlaceBuildingRequestEvent>,
+    mut queue: ResMut<PlacementQueue>,
     mut validation_events: MessageWriter<PlaceBuildingValidationEvent>,
     registry: Res<BuildingRegistry>,
     grid_cells: Query<(Entity, &Position, &CellChildren)>,
@@ -52,66 +66,80 @@ pub fn validate_placement(
     resources: Query<&ResourceNode>,
     network_connectivity: Res<NetworkConnectivity>,
 ) {
-    'event_loop: for event in place_request.read() {
-        let Some((_, _, cell_children)) = grid_cells
-            .iter()
-            .find(|(_, pos, _)| pos.x == event.grid_x && pos.y == event.grid_y)
-        else {
-            validation_events.write(PlaceBuildingValidationEvent {
-                result: Err(PlacementError::CellNotFound),
-                request: event.clone(),
-            });
-            continue 'event_loop;
-        };
+    queue.pending.extend(place_request.read().cloned());
 
-        for &entity in &cell_children.0 {
-            if let Ok(layer) = building_layers.get(entity) {
-                if layer.0 == BUILDING_LAYER {
-                    validation_events.write(PlaceBuildingValidationEvent {
-                        result: Err(PlacementError::CellOccupied),
-                        request: event.clone(),
-                    });
-                    continue 'event_loop;
-                }
-            }
+    let mut claimed = HashSet::new();
+    let mut deferred = VecDeque::new();
+    while let Some(request) = queue.pending.pop_front() {
+        let cell = (request.grid_x, request.grid_y);
+        if claimed.contains(&cell) {
+            deferred.push_back(request);
+            continue;
+        }
+
+        let result = check_placement(
+            &request,
+            &registry,
+            &grid_cells,
+            &building_layers,
+            &resources,
+            &network_connectivity,
+        );
+        if result.is_ok() {
+            claimed.insert(cell);
         }
+        validation_events.write(PlaceBuildingValidationEvent { result, request });
+    }
+    queue.pending = deferred;
+}
 
-        if let Some(definition) = registry.get_definition(&event.building_name) {
-            for rule in &definition.placement.rules {
-                match rule {
-                    PlacementRule::RequiresResource => {
-                        let has_resource = cell_children
-                            .0
-                            .iter()
-                            .any(|&entity| resources.contains(entity));
-                        if !has_resource {
-                            validation_events.write(PlaceBuildingValidationEvent {
-                                result: Err(PlacementError::RequiresResourceNode),
-                                request: event.clone(),
-                            });
-                            continue 'event_loop;
-                        }
-                    }
-                    PlacementRule::AdjacentToNetwork => {
-                        if !network_connectivity
-                            .is_adjacent_to_core_network(event.grid_x, event.grid_y)
-                        {
-                            validation_events.write(PlaceBuildingValidationEvent {
-                                result: Err(PlacementError::NotAdjacentToNetwork),
-                                request: event.clone(),
-                            });
-                            continue 'event_loop;
-                        }
-                    }
+fn check_placement(
+    request: &PlaceBuildingRequestEvent,
+    registry: &BuildingRegistry,
+    grid_cells: &Query<(Entity, &Position, &CellChildren)>,
+    building_layers: &Query<&Layer>,
+    resources: &Query<&ResourceNode>,
+    network_connectivity: &NetworkConnectivity,
+) -> Result<(), PlacementError> {
+    let Some((_, _, cell_children)) = grid_cells
+        .iter()
+        .find(|(_, pos, _)| pos.x == request.grid_x && pos.y == request.grid_y)
+    else {
+        return Err(PlacementError::CellNotFound);
+    };
+
+    let occupied = cell_children.0.iter().any(|&entity| {
+        building_layers
+            .get(entity)
+            .is_ok_and(|layer| layer.0 == BUILDING_LAYER)
+    });
+    if occupied {
+        return Err(PlacementError::CellOccupied);
+    }
+
+    let Some(definition) = registry.get_definition(&request.building_name) else {
+        return Ok(());
+    };
+    for rule in &definition.placement.rules {
+        match rule {
+            PlacementRule::RequiresResource => {
+                let has_resource = cell_children
+                    .0
+                    .iter()
+                    .any(|&entity| resources.contains(entity));
+                if !has_resource {
+                    return Err(PlacementError::RequiresResourceNode);
+                }
+            }
+            PlacementRule::AdjacentToNetwork => {
+                if !network_connectivity.is_adjacent_to_core_network(request.grid_x, request.grid_y)
+                {
+                    return Err(PlacementError::NotAdjacentToNetwork);
                 }
             }
         }
-
-        validation_events.write(PlaceBuildingValidationEvent {
-            result: Ok(()),
-            request: event.clone(),
-        });
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -119,6 +147,72 @@ pub fn validate_placement(
 mod tests {
     use super::*;
 
+    #[test]
+    fn overlapping_requests_in_one_frame_place_once_and_reject_the_rest() {
+        use crate::{
+            grid::Grid,
+            structures::{place_building, CreativeMode, Facing, LastPlaced},
+            systems::NetworkChangedEvent,
+        };
+
+        let registry = BuildingRegistry::from_ron(
+            r#"[
+                (
+                    name: "Smelter",
+                    category: Production,
+                    appearance: (size: (32.0, 32.0), color: (0.7, 0.5, 0.2, 1.0), multi_cell: None),
+                    placement: (cost: (inputs: {}, crafting_time: 0.0), rules: []),
+                    components: [],
+                ),
+            ]"#,
+        )
+        .unwrap();
+        let mut app = App::new();
+        app.add_message::<PlaceBuildingRequestEvent>()
+            .add_message::<PlaceBuildingValidationEvent>()
+            .add_message::<NetworkChangedEvent>()
+            .insert_resource(Grid::new(64.0))
+            .insert_resource(registry)
+            .init_resource::<NetworkConnectivity>()
+            .init_resource::<PlacementQueue>()
+            .init_resource::<LastPlaced>()
+            .init_resource::<CreativeMode>()
+            .add_systems(Update, (validate_placement, place_building).chain());
+
+        let cell = app
+            .world_mut()
+            .spawn((Position { x: 0, y: 0 }, CellChildren(Vec::new())))
+            .id();
+        for _ in 0..2 {
+            app.world_mut().write_message(PlaceBuildingRequestEvent {
+                building_name: "Smelter".to_string(),
+                grid_x: 0,
+                grid_y: 0,
+                facing: Facing::default(),
+            });
+        }
+        let results = |app: &App| {
+            app.world()
+                .resource::<Messages<PlaceBuildingValidationEvent>>()
+                .iter_current_update_messages()
+                .map(|event| event.result.as_ref().err().map(ToString::to_string))
+                .collect::<Vec<_>>()
+        };
+
+        app.update();
+        assert_eq!(results(&app), vec![None]);
+        assert_eq!(app.world().resource::<PlacementQueue>().pending.len(), 1);
+        assert_eq!(app.world().get::<CellChildren>(cell).unwrap().0.len(), 1);
+
+        app.update();
+        assert_eq!(
+            results(&app),
+            vec![Some(PlacementError::CellOccupied.to_string())]
+        );
+        assert!(app.world().resource::<PlacementQueue>().pending.is_empty());
+        assert_eq!(app.world().get::<CellChildren>(cell).unwrap().0.len(), 1);
+    }
+
     #[test]
     fn placement_error_display_cell_not_found() {
         let error = PlacementError::CellNotFound;
@@ -0,0 +1,159 @@
+use crate::{
+    systems::{Operational, OperationalCondition},
+    workers::workflows::Workflow,
+};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// Which problems stop the simulation so they can be looked at as they
+/// happen. Everything is off by default; the top bar's auto-pause button
+/// switches all of it on or off.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AutoPauseConfig {
+    /// A running workflow is left with no buildings to work on.
+    pub on_empty_workflow: bool,
+    /// A crafter's missing inputs are in stock but no supplier can be reached.
+    pub on_unreachable_supplier: bool,
+}
+
+impl AutoPauseConfig {
+    #[must_use]
+    pub fn any_enabled(&self) -> bool {
+        self.on_empty_workflow || self.on_unreachable_supplier
+    }
+
+    pub fn set_all(&mut self, enabled: bool) {
+        self.on_empty_workflow = enabled;
+        self.on_unreachable_supplier = enabled;
+    }
+}
+
+/// Sent when the simulation was paused because of a detected problem.
+#[derive(Message, Debug, Clone)]
+pub struct AutoPauseEvent {
+    pub reason: String,
+}
+
+fn auto_pause(
+    time: &mut Time<Virtual>,
+    events: &mut MessageWriter<AutoPauseEvent>,
+    reason: String,
+) {
+    time.pause();
+    info!(%reason, "simulation auto-paused");
+    events.write(AutoPauseEvent { reason });
+}
+
+/// Each workflow is reported once per time its building set runs dry.
+pub fn pause_on_empty_workflow(
+    config: Res<AutoPauseConfig>,
+    workflows: Query<(Entity, &Workflow)>,
+    mut reported: Local<HashSet<Entity>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut events: MessageWriter<AutoPauseEvent>,
+) {
+    reported.retain(|&entity| {
+        workflows
+            .get(entity)
+            .is_ok_and(|(_, workflow)| workflow.building_set.is_empty())
+    });
+    if !config.on_empty_workflow {
+        return;
+    }
+
+    for (entity, workflow) in &workflows {
+        if workflow.is_paused || !workflow.building_set.is_empty() || !reported.insert(entity) {
+            continue;
+        }
+        auto_pause(
+            &mut time,
+            &mut events,
+            format!("Workflow \"{}\" has no buildings", workflow.name),
+        );
+    }
+}
+
+/// Each crafter is reported once per time it loses its last reachable
+/// supplier.
+pub fn pause_on_unreachable_supplier(
+    config: Res<AutoPauseConfig>,
+    crafters: Query<(Entity, &Operational, Option<&Name>)>,
+    mut reported: Local<HashSet<Entity>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut events: MessageWriter<AutoPauseEvent>,
+) {
+    let unreachable = |operational: &Operational| {
+        operational.0.as_ref().is_some_and(|conditions| {
+            conditions.contains(&OperationalCondition::SupplierReachable(false))
+        })
+    };
+    reported.retain(|&entity| {
+        crafters
+            .get(entity)
+            .is_ok_and(|(_, operational, _)| unreachable(operational))
+    });
+    if !config.on_unreachable_supplier {
+        return;
+    }
+
+    for (entity, operational, name) in &crafters {
+        if !unreachable(operational) || !reported.insert(entity) {
+            continue;
+        }
+        let name = name.map_or("A crafter", Name::as_str);
+        auto_pause(
+            &mut time,
+            &mut events,
+            format!("{name} has no reachable supplier"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Reasons(Vec<String>);
+
+    fn collect_reasons(mut events: MessageReader<AutoPauseEvent>, mut reasons: ResMut<Reasons>) {
+        reasons
+            .0
+            .extend(events.read().map(|event| event.reason.clone()));
+    }
+
+    #[test]
+    fn empty_workflow_pauses_the_simulation_once() {
+        let mut app = App::new();
+        app.add_message::<AutoPauseEvent>()
+            .init_resource::<Time<Virtual>>()
+            .init_resource::<Reasons>()
+            .insert_resource(AutoPauseConfig {
+                on_empty_workflow: true,
+                ..default()
+            })
+            .add_systems(Update, (pause_on_empty_workflow, collect_reasons).chain());
+
+        app.world_mut().spawn(Workflow {
+            name: "Ore run".to_string(),
+            building_set: HashSet::new(),
+            steps: Vec::new(),
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 1,
+            round_robin_counters: std::collections::HashMap::new(),
+            step_waits: std::collections::HashMap::new(),
+        });
+
+        app.update();
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+        app.world_mut().resource_mut::<Time<Virtual>>().unpause();
+        app.update();
+        assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+        assert_eq!(
+            app.world().resource::<Reasons>().0,
+            vec!["Workflow \"Ore run\" has no buildings".to_string()]
+        );
+    }
+}
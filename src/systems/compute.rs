@@ -4,7 +4,9 @@ use crate::{
 };
 use bevy::prelude::*;
 
-#[derive(Resource, Default)]
+/// Only written when the totals move, so change detection means a real
+/// change.
+#[derive(Resource, Default, PartialEq, Eq)]
 pub struct ComputeGrid {
     pub capacity: i32,
     pub usage: i32,
@@ -27,7 +29,9 @@ pub fn update_compute(
 
     let total_consumption: i32 = consumers.iter().map(|c| c.amount).sum();
 
-    compute_grid.capacity = total_compute;
-    compute_grid.usage = total_consumption;
-    compute_grid.available = total_compute - total_consumption;
+    compute_grid.set_if_neq(ComputeGrid {
+        capacity: total_compute,
+        usage: total_consumption,
+        available: total_compute - total_consumption,
+    });
 }
@@ -1,26 +1,72 @@
 use crate::{
     materials::{
-        items::{Cargo, InputPort, OutputPort, StoragePort},
-        InventoryAccess, ItemRegistry,
+        combined_output_items,
+        items::{Cargo, InputPort, NamedOutputPorts, OutputPort, StoragePort},
+        InventoryAccess, ItemId, ItemRegistry,
     },
     structures::Building,
     systems::Operational,
-    workers::Worker,
+    workers::{
+        workflows::{WaitingForItems, WaitingForSpace, WorkflowAssignment},
+        Worker, WorkerPath,
+    },
 };
 use bevy::prelude::*;
+use std::collections::HashMap;
 
 #[derive(Component)]
 pub struct InventoryDisplay;
 
+/// Order items are listed in wherever an inventory is shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InventorySort {
+    #[default]
+    Name,
+    QuantityDescending,
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct DisplayConfig {
+    pub inventory_sort: InventorySort,
+}
+
+/// `items` in `sort` order. Ties fall back to the item name, so the order
+/// stays put from frame to frame.
+pub fn sorted_inventory(
+    items: &HashMap<ItemId, u32>,
+    item_registry: &ItemRegistry,
+    sort: InventorySort,
+) -> Vec<(ItemId, u32)> {
+    let mut sorted: Vec<(ItemId, u32)> = items.iter().map(|(&item, &qty)| (item, qty)).collect();
+    let name = |item| item_registry.display_name(item);
+    match sort {
+        InventorySort::Name => sorted.sort_by_key(|&(item, _)| name(item)),
+        InventorySort::QuantityDescending => {
+            sorted.sort_by_key(|&(item, qty)| (std::cmp::Reverse(qty), name(item)));
+        }
+    }
+    sorted
+}
+
 #[derive(Component)]
 pub struct NonOperationalIndicator;
 
+/// Toggles a floating "#index state" label over every worker.
+#[derive(Resource, Debug, Default)]
+pub struct WorkerDebugLabels {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+pub struct WorkerDebugLabel;
+
 pub fn update_inventory_display(
     mut commands: Commands,
     buildings_and_workers: Query<
         (
             Entity,
             Option<&OutputPort>,
+            Option<&NamedOutputPorts>,
             Option<&InputPort>,
             Option<&StoragePort>,
             Option<&Cargo>,
@@ -35,6 +81,7 @@ pub fn update_inventory_display(
             Or<(With<Worker>, With<Building>)>,
             Or<(
                 Changed<OutputPort>,
+                Changed<NamedOutputPorts>,
                 Changed<InputPort>,
                 Changed<StoragePort>,
                 Changed<Cargo>,
@@ -42,9 +89,12 @@ pub fn update_inventory_display(
         ),
     >,
     item_registry: Res<ItemRegistry>,
+    config: Res<DisplayConfig>,
 ) {
-    for (entity, output_port, input_port, storage_port, cargo) in buildings_and_workers.iter() {
-        let should_update = changed_inventories.contains(entity);
+    for (entity, output_port, named_output_ports, input_port, storage_port, cargo) in
+        buildings_and_workers.iter()
+    {
+        let should_update = changed_inventories.contains(entity) || config.is_changed();
 
         let existing_display = children.get(entity).ok().and_then(|children| {
             children
@@ -52,8 +102,9 @@ pub fn update_inventory_display(
                 .find(|&child| inventory_displays.contains(child))
         });
 
-        let items_to_display: Option<std::collections::HashMap<String, u32>> = output_port
-            .map(InventoryAccess::get_all_items)
+        let has_outputs = output_port.is_some() || named_output_ports.is_some();
+        let items_to_display: Option<HashMap<ItemId, u32>> = has_outputs
+            .then(|| combined_output_items(output_port, named_output_ports))
             .or_else(|| input_port.map(InventoryAccess::get_all_items))
             .or_else(|| storage_port.map(InventoryAccess::get_all_items))
             .or_else(|| cargo.map(InventoryAccess::get_all_items));
@@ -65,14 +116,9 @@ pub fn update_inventory_display(
         let display_text = if items.is_empty() {
             "Empty".to_string()
         } else {
-            items
-                .iter()
-                .map(|(item_name, &quantity)| {
-                    let name = item_registry
-                        .get_definition(item_name)
-                        .map_or("Unknown", |def| def.name.as_str());
-                    format!("{name}: {quantity}")
-                })
+            sorted_inventory(&items, &item_registry, config.inventory_sort)
+                .into_iter()
+                .map(|(item, quantity)| format!("{}: {quantity}", item_registry.display_name(item)))
                 .collect::<Vec<_>>()
                 .join("\n")
         };
@@ -138,3 +184,265 @@ pub fn update_operational_indicators(
         }
     }
 }
+
+/// Bar under a storage building whose length and tint track how full it is.
+#[derive(Component)]
+pub struct StorageFillBar;
+
+const FILL_BAR_WIDTH: f32 = 40.0;
+const FILL_BAR_HEIGHT: f32 = 4.0;
+const FILL_EMPTY_COLOR: Color = Color::srgb(0.2, 0.8, 0.2);
+const FILL_HALF_COLOR: Color = Color::srgb(0.9, 0.8, 0.2);
+const FILL_FULL_COLOR: Color = Color::srgb(0.9, 0.2, 0.2);
+
+/// Green when empty, yellow at half, red when full.
+pub fn storage_fill_color(fraction: f32) -> Color {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction < 0.5 {
+        FILL_EMPTY_COLOR.mix(&FILL_HALF_COLOR, fraction * 2.0)
+    } else {
+        FILL_HALF_COLOR.mix(&FILL_FULL_COLOR, (fraction - 0.5) * 2.0)
+    }
+}
+
+fn fill_bar_geometry(fraction: f32) -> (Vec2, f32) {
+    let width = (FILL_BAR_WIDTH * fraction.clamp(0.0, 1.0)).max(2.0);
+    (
+        Vec2::new(width, FILL_BAR_HEIGHT),
+        (width - FILL_BAR_WIDTH) / 2.0,
+    )
+}
+
+pub fn update_storage_fill_bars(
+    mut commands: Commands,
+    storages: Query<(Entity, &StoragePort), (With<Building>, Changed<StoragePort>)>,
+    mut bars: Query<(&mut Sprite, &mut Transform), With<StorageFillBar>>,
+    children: Query<&Children>,
+) {
+    for (entity, storage) in &storages {
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = if storage.capacity == 0 {
+            1.0
+        } else {
+            storage.get_total_quantity() as f32 / storage.capacity as f32
+        };
+        let color = storage_fill_color(fraction);
+        let (size, offset_x) = fill_bar_geometry(fraction);
+
+        let existing = children
+            .get(entity)
+            .ok()
+            .and_then(|children| children.iter().find(|&child| bars.contains(child)));
+
+        if let Some((mut sprite, mut transform)) = existing.and_then(|bar| bars.get_mut(bar).ok()) {
+            sprite.color = color;
+            sprite.custom_size = Some(size);
+            transform.translation.x = offset_x;
+        } else {
+            let bar = commands
+                .spawn((
+                    StorageFillBar,
+                    Sprite::from_color(color, size),
+                    Transform::from_xyz(offset_x, -26.0, 1.1),
+                ))
+                .id();
+            commands.entity(entity).add_child(bar);
+        }
+    }
+}
+
+type WorkerStateQuery<'a> = (
+    Entity,
+    &'a WorkerPath,
+    Option<&'a WorkflowAssignment>,
+    Option<&'a WaitingForItems>,
+    Option<&'a WaitingForSpace>,
+);
+
+fn worker_state(
+    path: &WorkerPath,
+    assignment: Option<&WorkflowAssignment>,
+    waiting_items: Option<&WaitingForItems>,
+    waiting_space: Option<&WaitingForSpace>,
+) -> &'static str {
+    if waiting_items.is_some() {
+        "waiting for items"
+    } else if waiting_space.is_some() {
+        "waiting for space"
+    } else if path.current_target.is_some() {
+        "moving"
+    } else if assignment.is_some() {
+        "working"
+    } else {
+        "idle"
+    }
+}
+
+pub fn update_worker_debug_labels(
+    mut commands: Commands,
+    toggle: Res<WorkerDebugLabels>,
+    workers: Query<WorkerStateQuery, With<Worker>>,
+    mut labels: Query<(Entity, &mut Text2d), With<WorkerDebugLabel>>,
+    children: Query<&Children>,
+) {
+    if !toggle.enabled {
+        if toggle.is_changed() {
+            for (label, _) in &labels {
+                commands.entity(label).despawn();
+            }
+        }
+        return;
+    }
+
+    for (worker, path, assignment, waiting_items, waiting_space) in &workers {
+        let text = format!(
+            "#{} {}",
+            worker.index_u32(),
+            worker_state(path, assignment, waiting_items, waiting_space)
+        );
+        let existing = children
+            .get(worker)
+            .ok()
+            .and_then(|children| children.iter().find(|&child| labels.contains(child)));
+
+        if let Some(label) = existing {
+            if let Ok((_, mut label_text)) = labels.get_mut(label) {
+                if label_text.0 != text {
+                    label_text.0 = text;
+                }
+            }
+        } else {
+            let label = commands
+                .spawn((
+                    WorkerDebugLabel,
+                    Text2d::new(text),
+                    TextFont {
+                        font_size: 11.0,
+                        ..Default::default()
+                    },
+                    TextColor(Color::srgb(1.0, 1.0, 0.6)),
+                    Transform::from_xyz(0.0, -18.0, 1.1),
+                ))
+                .id();
+            commands.entity(worker).add_child(label);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn label_count(app: &mut App) -> usize {
+        app.world_mut()
+            .query_filtered::<(), With<WorkerDebugLabel>>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn toggling_debug_labels_spawns_and_removes_one_per_worker() {
+        let mut app = App::new();
+        app.init_resource::<WorkerDebugLabels>()
+            .add_systems(Update, update_worker_debug_labels);
+        for _ in 0..3 {
+            app.world_mut().spawn((
+                Worker,
+                WorkerPath {
+                    waypoints: VecDeque::new(),
+                    current_target: None,
+                },
+            ));
+        }
+
+        app.update();
+        assert_eq!(label_count(&mut app), 0);
+
+        app.world_mut().resource_mut::<WorkerDebugLabels>().enabled = true;
+        app.update();
+        app.update();
+        assert_eq!(label_count(&mut app), 3);
+
+        app.world_mut().resource_mut::<WorkerDebugLabels>().enabled = false;
+        app.update();
+        assert_eq!(label_count(&mut app), 0);
+    }
+
+    #[test]
+    fn fill_bar_tint_tracks_storage_level() {
+        let mut app = App::new();
+        app.add_systems(Update, update_storage_fill_bars);
+
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut nearly_full = StoragePort::new(100);
+        nearly_full.add_item(ore, 90);
+        let full_storage = app.world_mut().spawn((Building, nearly_full)).id();
+        let empty_storage = app
+            .world_mut()
+            .spawn((Building, StoragePort::new(100)))
+            .id();
+
+        app.update();
+
+        let bar_color = |app: &App, storage: Entity| {
+            let bar = app.world().get::<Children>(storage).unwrap()[0];
+            app.world().get::<Sprite>(bar).unwrap().color.to_srgba()
+        };
+        let near_full = bar_color(&app, full_storage);
+        assert!(near_full.red > 0.8 && near_full.green < 0.4);
+        assert_eq!(bar_color(&app, empty_storage), FILL_EMPTY_COLOR.to_srgba());
+    }
+
+    #[test]
+    fn inventory_labels_follow_the_configured_sort() {
+        let mut app = App::new();
+        app.insert_resource(
+            ItemRegistry::from_ron(
+                r#"[(name: "Iron Ore", tier: 0), (name: "Coal", tier: 0), (name: "Copper Ore", tier: 0)]"#,
+            )
+            .unwrap(),
+        )
+        .init_resource::<DisplayConfig>()
+        .add_systems(Update, update_inventory_display);
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ItemId::named("Iron Ore").unwrap(), 5);
+        storage.add_item(ItemId::named("Coal").unwrap(), 12);
+        storage.add_item(ItemId::named("Copper Ore").unwrap(), 5);
+        app.world_mut().spawn((Building, storage));
+
+        let label = |app: &mut App| {
+            app.update();
+            app.world_mut()
+                .query_filtered::<&Text2d, With<InventoryDisplay>>()
+                .single(app.world())
+                .unwrap()
+                .0
+                .clone()
+        };
+
+        assert_eq!(label(&mut app), "Coal: 12\nCopper Ore: 5\nIron Ore: 5");
+
+        app.world_mut()
+            .resource_mut::<DisplayConfig>()
+            .inventory_sort = InventorySort::QuantityDescending;
+        assert_eq!(label(&mut app), "Coal: 12\nCopper Ore: 5\nIron Ore: 5");
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ItemId::named("Iron Ore").unwrap(), 30);
+        storage.add_item(ItemId::named("Coal").unwrap(), 2);
+        storage.add_item(ItemId::named("Copper Ore").unwrap(), 7);
+        let mut stores = app
+            .world_mut()
+            .query_filtered::<&mut StoragePort, With<Building>>();
+        *stores.single_mut(app.world_mut()).unwrap() = storage;
+        assert_eq!(label(&mut app), "Iron Ore: 30\nCopper Ore: 7\nCoal: 2");
+
+        app.world_mut()
+            .resource_mut::<DisplayConfig>()
+            .inventory_sort = InventorySort::Name;
+        assert_eq!(label(&mut app), "Coal: 2\nCopper Ore: 7\nIron Ore: 30");
+    }
+}
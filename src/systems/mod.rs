@@ -1,16 +1,22 @@
 #![allow(unused_imports)]
 
+pub mod auto_pause;
 pub mod compute;
 pub mod display;
 pub mod network;
 pub mod operational;
 pub mod power;
 pub mod scanning;
+pub mod starvation;
+pub mod throughput;
+pub mod transit;
 
+pub use auto_pause::{AutoPauseConfig, AutoPauseEvent};
 pub use compute::{update_compute, ComputeGrid};
 pub use display::{
-    update_inventory_display, update_operational_indicators, InventoryDisplay,
-    NonOperationalIndicator,
+    sorted_inventory, update_inventory_display, update_operational_indicators,
+    update_storage_fill_bars, update_worker_debug_labels, DisplayConfig, InventoryDisplay,
+    InventorySort, NonOperationalIndicator, StorageFillBar, WorkerDebugLabel, WorkerDebugLabels,
 };
 pub use network::{
     calculate_network_connectivity, update_network_connectivity, update_visual_network_connections,
@@ -18,9 +24,13 @@ pub use network::{
 };
 pub use operational::{
     populate_operational_conditions, update_operational_status, Operational, OperationalCondition,
+    SupplyStatusCache,
 };
 pub use power::{update_power_grid, PowerGrid};
 pub use scanning::{handle_progressive_scanning, Scanner};
+pub use starvation::{StarvationAlert, StarvationConfig, StarvationTimer};
+pub use throughput::{sample_item_throughput, ItemThroughput, ThroughputSample};
+pub use transit::{count_items_in_transit, InTransit};
 
 use bevy::prelude::*;
 
@@ -28,6 +38,7 @@ use bevy::prelude::*;
 pub struct GameScore {
     pub total_score: u64,
     pub launches_completed: u32,
+    pub items_sunk: u64,
 }
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
@@ -45,7 +56,16 @@ impl Plugin for SystemsPlugin {
             .insert_resource(ComputeGrid::default())
             .insert_resource(NetworkConnectivity::default())
             .init_resource::<GameScore>()
+            .init_resource::<ItemThroughput>()
+            .init_resource::<InTransit>()
+            .init_resource::<WorkerDebugLabels>()
+            .init_resource::<DisplayConfig>()
+            .init_resource::<AutoPauseConfig>()
+            .init_resource::<StarvationConfig>()
+            .init_resource::<SupplyStatusCache>()
             .add_message::<NetworkChangedEvent>()
+            .add_message::<AutoPauseEvent>()
+            .add_message::<StarvationAlert>()
             .configure_sets(
                 Update,
                 (
@@ -71,10 +91,20 @@ impl Plugin for SystemsPlugin {
                     (populate_operational_conditions, update_operational_status)
                         .chain()
                         .in_set(SystemsSet::Operational),
+                    (
+                        auto_pause::pause_on_empty_workflow,
+                        auto_pause::pause_on_unreachable_supplier.after(update_operational_status),
+                        starvation::track_input_starvation.after(update_operational_status),
+                    )
+                        .in_set(SystemsSet::Operational),
                     (
                         update_inventory_display,
                         update_operational_indicators,
+                        update_storage_fill_bars,
+                        update_worker_debug_labels,
                         update_visual_network_connections,
+                        sample_item_throughput,
+                        count_items_in_transit,
                     )
                         .in_set(SystemsSet::Display),
                 ),
@@ -1,20 +1,31 @@
 use core::fmt;
 
+use std::collections::HashMap;
+
 use crate::{
-    grid::Position,
-    materials::{InputPort, InventoryAccess, OutputPort, RecipeRegistry},
+    grid::{Grid, Position},
+    materials::{InputPort, InventoryAccess, ItemId, OutputPort, RecipeRegistry, StoragePort},
     structures::{Building, ComputeConsumer, PowerConsumer, RecipeCrafter},
-    systems::{ComputeGrid, NetworkConnectivity, PowerGrid},
+    systems::{ComputeGrid, NetworkChangedEvent, NetworkConnectivity, PowerGrid},
+    workers::{calculate_path_within, PathfindingConfig},
 };
 use bevy::prelude::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OperationalCondition {
     Network(bool),
     Power(bool),
     Compute(bool),
     HasItems(bool),
+    /// Some storage or producer holds each missing input.
+    HasSupply(bool),
+    /// Every missing input that is in stock somewhere has a supplier the
+    /// crafter can path to.
+    SupplierReachable(bool),
     HasInventorySpace(bool),
+    /// No needed input has sat at zero past the starvation threshold. Raises
+    /// an alert but does not stop the building.
+    Supplied(bool),
 }
 
 impl fmt::Display for OperationalCondition {
@@ -24,12 +35,37 @@ impl fmt::Display for OperationalCondition {
             OperationalCondition::Power(false) => write!(f, "Insufficient power"),
             OperationalCondition::Compute(false) => write!(f, "Insufficient compute"),
             OperationalCondition::HasItems(false) => write!(f, "Missing required items"),
+            OperationalCondition::HasSupply(false) => write!(f, "No supply of required items"),
+            OperationalCondition::SupplierReachable(false) => write!(f, "No reachable supplier"),
             OperationalCondition::HasInventorySpace(false) => write!(f, "Output full"),
+            OperationalCondition::Supplied(false) => write!(f, "Starved of inputs"),
             _ => Ok(()),
         }
     }
 }
 
+/// How long a cached supply status stands while the network is unchanged.
+pub const SUPPLY_RECHECK_SECS: f32 = 1.0;
+
+/// Last supply status worked out for each starved crafter, with the inputs it
+/// was missing at the time. The supplier path searches only rerun when the
+/// network changes, the crafter's missing inputs change, or the recheck timer
+/// runs out.
+#[derive(Resource)]
+pub struct SupplyStatusCache {
+    recheck: Timer,
+    statuses: HashMap<Entity, (Vec<ItemId>, (bool, bool))>,
+}
+
+impl Default for SupplyStatusCache {
+    fn default() -> Self {
+        Self {
+            recheck: Timer::from_seconds(SUPPLY_RECHECK_SECS, TimerMode::Repeating),
+            statuses: HashMap::new(),
+        }
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct Operational(pub Option<Vec<OperationalCondition>>);
 
@@ -45,7 +81,10 @@ impl Operational {
                         | OperationalCondition::Power(s)
                         | OperationalCondition::Compute(s)
                         | OperationalCondition::HasItems(s)
+                        | OperationalCondition::HasSupply(s)
+                        | OperationalCondition::SupplierReachable(s)
                         | OperationalCondition::HasInventorySpace(s) => s,
+                        OperationalCondition::Supplied(_) => &true,
                     };
                     *status
                 })
@@ -99,6 +138,9 @@ pub fn populate_operational_conditions(
 
         if recipe_crafter.is_some() && input_port.is_some() {
             conditions.push(OperationalCondition::HasItems(false));
+            conditions.push(OperationalCondition::HasSupply(true));
+            conditions.push(OperationalCondition::SupplierReachable(true));
+            conditions.push(OperationalCondition::Supplied(true));
         }
 
         if output_port.is_some() {
@@ -109,73 +151,182 @@ pub fn populate_operational_conditions(
     }
 }
 
+/// Recipe inputs the crafter is short of, or `None` when that cannot be told
+/// (no recipe selected, or no input port).
+fn missing_inputs(
+    crafter: Option<&RecipeCrafter>,
+    input_port: Option<&InputPort>,
+    recipe_registry: &RecipeRegistry,
+) -> Option<Vec<ItemId>> {
+    let crafter = crafter?;
+    if crafter.in_progress.is_some() {
+        return Some(Vec::new());
+    }
+    let recipe = recipe_registry.get_definition(crafter.get_active_recipe()?)?;
+    let input_port = input_port?;
+    Some(
+        recipe
+            .inputs
+            .iter()
+            .chain(&recipe.catalysts)
+            .filter(|&(&item, &quantity)| !input_port.has_at_least(item, quantity))
+            .map(|(&item, _)| item)
+            .collect(),
+    )
+}
+
+/// Whether every missing item is stocked somewhere, and whether every stocked
+/// one can be reached within `max_len`. Suppliers are tried nearest first, so
+/// the path search usually stops at the first candidate.
+fn supply_status(
+    missing: &[ItemId],
+    crafter: Entity,
+    crafter_pos: Position,
+    suppliers: &[(Entity, Position, &HashMap<ItemId, u32>)],
+    network: &NetworkConnectivity,
+    grid: &Grid,
+    max_len: Option<usize>,
+) -> (bool, bool) {
+    let mut has_supply = true;
+    let mut reachable = true;
+    for &item in missing {
+        let mut stocked: Vec<&(Entity, Position, &HashMap<ItemId, u32>)> = suppliers
+            .iter()
+            .filter(|(entity, _, items)| {
+                *entity != crafter && items.get(&item).is_some_and(|&qty| qty > 0)
+            })
+            .collect();
+        if stocked.is_empty() {
+            has_supply = false;
+            continue;
+        }
+        stocked.sort_by_key(|(entity, pos, _)| {
+            let distance = (pos.x - crafter_pos.x).abs() + (pos.y - crafter_pos.y).abs();
+            (distance, *entity)
+        });
+        let can_reach = stocked.iter().any(|(_, pos, _)| {
+            calculate_path_within(
+                (crafter_pos.x, crafter_pos.y),
+                (pos.x, pos.y),
+                network,
+                grid,
+                max_len,
+            )
+            .is_some()
+        });
+        reachable &= can_reach;
+    }
+    (has_supply, reachable)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn update_operational_status(
     mut operational_query: Query<(
+        Entity,
         &mut Operational,
         Option<&RecipeCrafter>,
         Option<&InputPort>,
         Option<&OutputPort>,
         &Position,
     )>,
+    storage_ports: Query<(Entity, &StoragePort, &Position)>,
+    output_ports: Query<(Entity, &OutputPort, &Position)>,
     network_connectivity: Res<NetworkConnectivity>,
     power_grid: Res<PowerGrid>,
     compute_grid: Res<ComputeGrid>,
     recipe_registry: Res<RecipeRegistry>,
+    grid: Res<Grid>,
+    pathfinding: Res<PathfindingConfig>,
+    time: Res<Time>,
+    mut network_events: MessageReader<NetworkChangedEvent>,
+    mut supply_cache: ResMut<SupplyStatusCache>,
 ) {
-    for (mut operational, crafter, input_port, output_port, pos) in &mut operational_query {
-        let Some(ref mut conditions) = operational.0 else {
+    let network_changed = network_events.read().count() > 0;
+    if supply_cache.recheck.tick(time.delta()).just_finished() || network_changed {
+        supply_cache.statuses.clear();
+    }
+
+    for (entity, mut operational, crafter, input_port, output_port, pos) in &mut operational_query {
+        let Some(ref mut conditions) = operational.bypass_change_detection().0 else {
             continue;
         };
-
-        for condition in conditions.iter_mut() {
-            match condition {
-                OperationalCondition::Network(ref mut status) => {
-                    *status = network_connectivity.is_adjacent_to_connected_network(pos.x, pos.y);
-                }
-
-                OperationalCondition::Power(ref mut status) => {
-                    *status = power_grid.available >= 0;
-                }
-
-                OperationalCondition::Compute(ref mut status) => {
-                    *status = compute_grid.available >= 0;
-                }
-
-                OperationalCondition::HasItems(ref mut status) => {
-                    let Some(crafter) = crafter else {
-                        continue;
-                    };
-                    let Some(recipe_name) = crafter.get_active_recipe() else {
-                        continue;
-                    };
-                    let Some(recipe) = recipe_registry.get_definition(recipe_name) else {
-                        continue;
-                    };
-
-                    let has_inputs = if let Some(input_port) = input_port {
-                        recipe.inputs.iter().all(|(item_name, quantity)| {
-                            input_port.has_at_least(item_name, *quantity)
-                        })
-                    } else {
-                        continue;
-                    };
-
-                    *status = has_inputs;
-                }
-
-                OperationalCondition::HasInventorySpace(ref mut status) => {
-                    if let Some(output_port) = output_port {
-                        *status = !output_port.is_full();
-                    } else {
-                        *status = false;
-                    }
+        let missing = missing_inputs(crafter, input_port, &recipe_registry);
+        let supply = missing.as_ref().map(|missing| {
+            if missing.is_empty() {
+                supply_cache.statuses.remove(&entity);
+                return (true, true);
+            }
+            if let Some((cached_missing, status)) = supply_cache.statuses.get(&entity) {
+                if cached_missing == missing {
+                    return *status;
                 }
             }
+            let suppliers: Vec<(Entity, Position, &HashMap<ItemId, u32>)> = storage_ports
+                .iter()
+                .map(|(entity, port, pos)| (entity, *pos, &port.items))
+                .chain(
+                    output_ports
+                        .iter()
+                        .map(|(entity, port, pos)| (entity, *pos, &port.items)),
+                )
+                .collect();
+            let status = supply_status(
+                missing,
+                entity,
+                *pos,
+                &suppliers,
+                &network_connectivity,
+                &grid,
+                pathfinding.max_path_length,
+            );
+            supply_cache
+                .statuses
+                .insert(entity, (missing.clone(), status));
+            status
+        });
+
+        let mut changed = false;
+        for condition in conditions.iter_mut() {
+            let (status, value) = match condition {
+                OperationalCondition::Network(status) => (
+                    status,
+                    network_connectivity.is_adjacent_to_connected_network(pos.x, pos.y),
+                ),
+                OperationalCondition::Power(status) => (status, power_grid.available >= 0),
+                OperationalCondition::Compute(status) => (status, compute_grid.available >= 0),
+                OperationalCondition::HasItems(status) => match &missing {
+                    Some(missing) => (status, missing.is_empty()),
+                    None => continue,
+                },
+                OperationalCondition::HasSupply(status) => match supply {
+                    Some((has_supply, _)) => (status, has_supply),
+                    None => continue,
+                },
+                OperationalCondition::SupplierReachable(status) => match supply {
+                    Some((_, reachable)) => (status, reachable),
+                    None => continue,
+                },
+                OperationalCondition::HasInventorySpace(status) => (
+                    status,
+                    output_port.is_some_and(|output_port| !output_port.is_full()),
+                ),
+                // Kept by `track_input_starvation`.
+                OperationalCondition::Supplied(_) => continue,
+            };
+            if *status != value {
+                *status = value;
+                changed = true;
+            }
+        }
+
+        if changed {
+            operational.set_changed();
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -218,7 +369,10 @@ mod tests {
             OperationalCondition::Power(true),
             OperationalCondition::Compute(true),
             OperationalCondition::HasItems(true),
+            OperationalCondition::HasSupply(true),
+            OperationalCondition::SupplierReachable(true),
             OperationalCondition::HasInventorySpace(true),
+            OperationalCondition::Supplied(true),
         ];
 
         for condition in conditions {
@@ -327,4 +481,118 @@ mod tests {
         let operational = Operational(Some(conditions));
         assert!(!operational.get_status());
     }
+
+    const INGOT_RECIPE: &str = r#"[
+        (
+            name: "Iron Ingot",
+            inputs: {"Iron Ore": 1},
+            outputs: {"Iron Ingot": 1},
+            crafting_time: 1.0,
+        ),
+    ]"#;
+
+    fn lay_road_to_storage(network: &mut NetworkConnectivity) {
+        for x in 1..4 {
+            network.add_connected_cell(x, 0);
+            network.add_core_network_cell(x, 0);
+        }
+    }
+
+    /// A starved smelter at (0, 0) next to a storage at (4, 0) holding
+    /// `stock` ore.
+    fn supply_app(stock: u32, road_to_storage: bool) -> (App, Entity) {
+        let mut network = NetworkConnectivity::default();
+        network.add_connected_cell(0, 0);
+        network.add_connected_cell(4, 0);
+        if road_to_storage {
+            lay_road_to_storage(&mut network);
+        }
+
+        let mut app = App::new();
+        app.insert_resource(RecipeRegistry::from_ron(INGOT_RECIPE).unwrap())
+            .insert_resource(network)
+            .insert_resource(Grid::new(64.0))
+            .init_resource::<PathfindingConfig>()
+            .init_resource::<PowerGrid>()
+            .init_resource::<ComputeGrid>()
+            .init_resource::<Time>()
+            .init_resource::<SupplyStatusCache>()
+            .add_message::<NetworkChangedEvent>()
+            .add_systems(Update, update_operational_status);
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ItemId::named("Iron Ore").unwrap(), stock);
+        app.world_mut().spawn((storage, Position { x: 4, y: 0 }));
+        let smelter = app
+            .world_mut()
+            .spawn((
+                Operational(Some(vec![
+                    OperationalCondition::HasItems(false),
+                    OperationalCondition::HasSupply(true),
+                    OperationalCondition::SupplierReachable(true),
+                ])),
+                RecipeCrafter {
+                    timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    current_recipe: Some("Iron Ingot".to_string()),
+                    available_recipes: vec!["Iron Ingot".to_string()],
+                    in_progress: None,
+                },
+                InputPort::new(20),
+                Position { x: 0, y: 0 },
+            ))
+            .id();
+        (app, smelter)
+    }
+
+    fn supply_of(app: &App, smelter: Entity) -> (bool, bool) {
+        let conditions = app
+            .world()
+            .get::<Operational>(smelter)
+            .unwrap()
+            .0
+            .clone()
+            .unwrap();
+        let has_supply = conditions.contains(&OperationalCondition::HasSupply(true));
+        let reachable = conditions.contains(&OperationalCondition::SupplierReachable(true));
+        assert!(conditions.contains(&OperationalCondition::HasItems(false)));
+        (has_supply, reachable)
+    }
+
+    /// Runs the smelter for a frame and returns the supply conditions it ends
+    /// up with.
+    fn supply_conditions(stock: u32, road_to_storage: bool) -> (bool, bool) {
+        let (mut app, smelter) = supply_app(stock, road_to_storage);
+        app.update();
+        supply_of(&app, smelter)
+    }
+
+    #[test]
+    fn starved_crafter_without_any_stock_reports_no_supply() {
+        assert_eq!(supply_conditions(0, true), (false, true));
+    }
+
+    #[test]
+    fn starved_crafter_with_stock_but_no_path_reports_unreachable_supplier() {
+        assert_eq!(supply_conditions(10, false), (true, false));
+    }
+
+    #[test]
+    fn starved_crafter_with_a_reachable_supplier_reports_neither() {
+        assert_eq!(supply_conditions(10, true), (true, true));
+    }
+
+    #[test]
+    fn supplier_reachability_is_cached_until_the_network_changes() {
+        let (mut app, smelter) = supply_app(10, false);
+        app.update();
+        assert_eq!(supply_of(&app, smelter), (true, false));
+
+        lay_road_to_storage(&mut app.world_mut().resource_mut::<NetworkConnectivity>());
+        app.update();
+        assert_eq!(supply_of(&app, smelter), (true, false));
+
+        app.world_mut().write_message(NetworkChangedEvent);
+        app.update();
+        assert_eq!(supply_of(&app, smelter), (true, true));
+    }
 }
@@ -4,7 +4,9 @@ use crate::{
 };
 use bevy::prelude::*;
 
-#[derive(Resource, Default)]
+/// Only written when the totals move, so change detection means a real
+/// change.
+#[derive(Resource, Default, PartialEq, Eq)]
 pub struct PowerGrid {
     pub capacity: i32,
     pub usage: i32,
@@ -27,9 +29,11 @@ pub fn update_power_grid(
 
     let total_consumption: i32 = consumers.iter().map(|c| c.amount).sum();
 
-    power_grid.capacity = total_production;
-    power_grid.usage = total_consumption;
-    power_grid.available = total_production - total_consumption;
+    power_grid.set_if_neq(PowerGrid {
+        capacity: total_production,
+        usage: total_consumption,
+        available: total_production - total_consumption,
+    });
 }
 
 #[cfg(test)]
@@ -186,12 +186,12 @@ pub fn handle_progressive_scanning(
 
                 scanner.reset_timer_for_distance(target_distance);
 
-                println!(
-                    "Scanner at ({}, {}) revealed cluster at distance {} (scan time: {:.1}s)",
-                    scanner.position.x,
-                    scanner.position.y,
-                    target_distance,
-                    scanner.calculate_scan_time(target_distance)
+                debug!(
+                    x = scanner.position.x,
+                    y = scanner.position.y,
+                    distance = target_distance,
+                    scan_secs = scanner.calculate_scan_time(target_distance),
+                    "scanner revealed cluster"
                 );
             }
         }
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    materials::{InputPort, InventoryAccess, ItemId, RecipeRegistry},
+    structures::RecipeCrafter,
+    systems::{Operational, OperationalCondition},
+};
+
+/// How long a needed input may sit at zero before its crafter raises a
+/// starvation alert.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct StarvationConfig {
+    pub threshold_secs: f32,
+}
+
+impl Default for StarvationConfig {
+    fn default() -> Self {
+        Self {
+            threshold_secs: 30.0,
+        }
+    }
+}
+
+/// Seconds each needed input has been at zero while the crafter sat idle
+/// waiting for it. An entry is dropped as soon as the item is supplied, the
+/// recipe stops needing it, or a craft starts.
+#[derive(Component, Debug, Default, Clone)]
+pub struct StarvationTimer {
+    pub elapsed: HashMap<ItemId, f32>,
+}
+
+impl StarvationTimer {
+    /// Inputs starved for at least `threshold_secs`, sorted.
+    #[must_use]
+    pub fn starved(&self, threshold_secs: f32) -> Vec<ItemId> {
+        let mut items: Vec<ItemId> = self
+            .elapsed
+            .iter()
+            .filter(|&(_, &secs)| secs >= threshold_secs)
+            .map(|(&item, _)| item)
+            .collect();
+        items.sort_unstable();
+        items
+    }
+}
+
+/// Sent once when a crafter becomes starved of `items`.
+#[derive(Message, Debug, Clone)]
+pub struct StarvationAlert {
+    pub building: Entity,
+    pub items: Vec<ItemId>,
+}
+
+/// True when something other than its inputs keeps the crafter from
+/// running, such as power or a full output.
+fn stalled_elsewhere(operational: &Operational) -> bool {
+    operational.0.as_ref().is_some_and(|conditions| {
+        conditions.iter().any(|condition| {
+            matches!(
+                condition,
+                OperationalCondition::Network(false)
+                    | OperationalCondition::Power(false)
+                    | OperationalCondition::Compute(false)
+                    | OperationalCondition::HasInventorySpace(false)
+            )
+        })
+    })
+}
+
+/// Advances each crafter's starvation timers and keeps its `Supplied`
+/// condition in step. The alert fires when the condition turns false and
+/// can fire again once the crafter has been supplied. A crafter mid-craft,
+/// or stopped for another reason, is not waiting on its inputs, so its
+/// timers are cleared instead.
+pub fn track_input_starvation(
+    config: Res<StarvationConfig>,
+    time: Res<Time>,
+    recipes: Res<RecipeRegistry>,
+    mut crafters: Query<(
+        Entity,
+        &RecipeCrafter,
+        &InputPort,
+        &mut StarvationTimer,
+        &mut Operational,
+    )>,
+    mut alerts: MessageWriter<StarvationAlert>,
+) {
+    let delta = time.delta_secs();
+    for (building, crafter, input_port, mut timer, mut operational) in &mut crafters {
+        let waiting_on_inputs = crafter.in_progress.is_none() && !stalled_elsewhere(&operational);
+        let recipe = crafter
+            .get_active_recipe()
+            .and_then(|name| recipes.get_definition(name))
+            .filter(|_| waiting_on_inputs);
+        let empty: Vec<ItemId> = recipe
+            .map(|recipe| {
+                recipe
+                    .inputs
+                    .keys()
+                    .chain(recipe.catalysts.keys())
+                    .copied()
+                    .filter(|&item| input_port.get_item_quantity(item) == 0)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        timer.elapsed.retain(|item, _| empty.contains(item));
+        for item in empty {
+            *timer.elapsed.entry(item).or_default() += delta;
+        }
+        let starved = timer.starved(config.threshold_secs);
+
+        let now_supplied = starved.is_empty();
+        let Some(supplied) =
+            operational
+                .bypass_change_detection()
+                .0
+                .as_mut()
+                .and_then(|conditions| {
+                    conditions.iter_mut().find_map(|condition| match condition {
+                        OperationalCondition::Supplied(status) => Some(status),
+                        _ => None,
+                    })
+                })
+        else {
+            continue;
+        };
+        if *supplied == now_supplied {
+            continue;
+        }
+        *supplied = now_supplied;
+        operational.set_changed();
+        if !now_supplied {
+            warn!(?building, items = ?starved, "crafter starved of inputs");
+            alerts.write(StarvationAlert {
+                building,
+                items: starved,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn supplied(app: &App, building: Entity) -> bool {
+        let operational = app.world().get::<Operational>(building).unwrap();
+        operational
+            .0
+            .as_ref()
+            .unwrap()
+            .contains(&OperationalCondition::Supplied(true))
+    }
+
+    fn advance(app: &mut App, secs: u64) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(secs));
+        app.update();
+    }
+
+    #[test]
+    fn starved_crafter_alerts_past_the_threshold_and_clears_once_supplied() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(StarvationConfig {
+                threshold_secs: 10.0,
+            })
+            .insert_resource(
+                RecipeRegistry::from_ron(
+                    r#"[(name: "Iron Ingot", inputs: {"Iron Ore": 1}, outputs: {"Iron Ingot": 1})]"#,
+                )
+                .unwrap(),
+            )
+            .add_message::<StarvationAlert>()
+            .add_systems(Update, track_input_starvation);
+
+        let building = app
+            .world_mut()
+            .spawn((
+                RecipeCrafter {
+                    timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    current_recipe: Some("Iron Ingot".to_string()),
+                    available_recipes: Vec::new(),
+                    in_progress: None,
+                },
+                InputPort::new(10),
+                Operational(Some(vec![OperationalCondition::Supplied(true)])),
+            ))
+            .id();
+        let alerts = |app: &App| {
+            app.world()
+                .resource::<Messages<StarvationAlert>>()
+                .iter_current_update_messages()
+                .map(|alert| alert.items.clone())
+                .collect::<Vec<_>>()
+        };
+
+        advance(&mut app, 6);
+        assert!(supplied(&app, building));
+        assert!(alerts(&app).is_empty());
+
+        advance(&mut app, 6);
+        assert!(!supplied(&app, building));
+        assert_eq!(alerts(&app), vec![vec![ore]]);
+        assert!(app
+            .world()
+            .get::<Operational>(building)
+            .unwrap()
+            .get_status());
+
+        app.world_mut()
+            .get_mut::<InputPort>(building)
+            .unwrap()
+            .add_item(ore, 1);
+        advance(&mut app, 1);
+        assert!(supplied(&app, building));
+        assert!(app
+            .world()
+            .get::<StarvationTimer>(building)
+            .unwrap()
+            .elapsed
+            .is_empty());
+    }
+
+    #[test]
+    fn only_idle_operational_crafters_count_as_starving() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(StarvationConfig {
+                threshold_secs: 10.0,
+            })
+            .insert_resource(
+                RecipeRegistry::from_ron(
+                    r#"[(name: "Iron Ingot", inputs: {"Iron Ore": 1}, outputs: {"Iron Ingot": 1})]"#,
+                )
+                .unwrap(),
+            )
+            .add_message::<StarvationAlert>()
+            .add_systems(Update, track_input_starvation);
+
+        let mut spawn_crafter = |in_progress: bool, power: bool| {
+            app.world_mut()
+                .spawn((
+                    RecipeCrafter {
+                        timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                        current_recipe: Some("Iron Ingot".to_string()),
+                        available_recipes: Vec::new(),
+                        in_progress: in_progress.then(|| "Iron Ingot".to_string()),
+                    },
+                    InputPort::new(10),
+                    Operational(Some(vec![
+                        OperationalCondition::Power(power),
+                        OperationalCondition::Supplied(true),
+                    ])),
+                ))
+                .id()
+        };
+        let crafting = spawn_crafter(true, true);
+        let unpowered = spawn_crafter(false, false);
+        let idle = spawn_crafter(false, true);
+
+        advance(&mut app, 6);
+        advance(&mut app, 6);
+        assert!(supplied(&app, crafting));
+        assert!(supplied(&app, unpowered));
+        assert!(!supplied(&app, idle));
+
+        app.world_mut()
+            .get_mut::<RecipeCrafter>(idle)
+            .unwrap()
+            .in_progress = Some("Iron Ingot".to_string());
+        advance(&mut app, 1);
+        assert!(supplied(&app, idle));
+        assert!(app
+            .world()
+            .get::<StarvationTimer>(idle)
+            .unwrap()
+            .elapsed
+            .is_empty());
+    }
+}
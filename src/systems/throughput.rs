@@ -0,0 +1,230 @@
+use crate::materials::{ItemId, ItemRegistry};
+use bevy::prelude::*;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+pub const THROUGHPUT_SAMPLE_SECS: f32 = 5.0;
+/// Three minutes of history at the default sample interval.
+pub const THROUGHPUT_HISTORY_LEN: usize = 36;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThroughputSample {
+    pub produced: u32,
+    pub consumed: u32,
+}
+
+/// Per-item production and consumption. Crafters and sinks report into the
+/// current window, which is closed into a fixed-length history every
+/// `THROUGHPUT_SAMPLE_SECS`.
+#[derive(Resource)]
+pub struct ItemThroughput {
+    window: HashMap<ItemId, ThroughputSample>,
+    history: HashMap<ItemId, VecDeque<ThroughputSample>>,
+    /// Everything produced since startup, never trimmed.
+    produced_total: HashMap<ItemId, u64>,
+    /// Bumped each time a window closes. Readers compare it rather than
+    /// using change detection, which also fires on every timer tick and
+    /// every record into the open window.
+    generation: u64,
+    timer: Timer,
+}
+
+impl Default for ItemThroughput {
+    fn default() -> Self {
+        Self {
+            window: HashMap::new(),
+            history: HashMap::new(),
+            produced_total: HashMap::new(),
+            generation: 0,
+            timer: Timer::from_seconds(THROUGHPUT_SAMPLE_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl ItemThroughput {
+    pub fn record_produced(&mut self, item: ItemId, quantity: u32) {
+        let sample = self.window.entry(item).or_default();
+        sample.produced = sample.produced.saturating_add(quantity);
+        *self.produced_total.entry(item).or_default() += u64::from(quantity);
+    }
+
+    pub fn record_consumed(&mut self, item: ItemId, quantity: u32) {
+        let sample = self.window.entry(item).or_default();
+        sample.consumed = sample.consumed.saturating_add(quantity);
+    }
+
+    /// Takes back consumption that did not happen after all, such as the
+    /// inputs of an abandoned craft. Only the open window can be corrected,
+    /// so inputs consumed before the last sample closed stay counted.
+    pub fn unrecord_consumed(&mut self, item: ItemId, quantity: u32) {
+        if let Some(sample) = self.window.get_mut(&item) {
+            sample.consumed = sample.consumed.saturating_sub(quantity);
+        }
+    }
+
+    /// Closes the current window. Items already tracked get a sample even when
+    /// idle so their history keeps moving.
+    pub fn push_sample(&mut self) {
+        for item in self.window.keys() {
+            self.history.entry(*item).or_default();
+        }
+        for (item, samples) in &mut self.history {
+            samples.push_back(self.window.get(item).copied().unwrap_or_default());
+            while samples.len() > THROUGHPUT_HISTORY_LEN {
+                samples.pop_front();
+            }
+        }
+        self.window.clear();
+        self.generation += 1;
+    }
+
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Oldest first.
+    pub fn samples(&self, item: ItemId) -> impl Iterator<Item = &ThroughputSample> {
+        self.history.get(&item).into_iter().flatten()
+    }
+
+    pub fn tracked_items(&self) -> impl Iterator<Item = ItemId> + '_ {
+        self.history.keys().copied()
+    }
+
+    pub fn produced_total(&self) -> impl Iterator<Item = (ItemId, u64)> + '_ {
+        self.produced_total.iter().map(|(&item, &qty)| (item, qty))
+    }
+
+    /// One row per item with its totals over the recorded history plus the
+    /// open window. `rate` is net items per minute across the closed samples.
+    #[must_use]
+    pub fn to_csv(&self, item_registry: &ItemRegistry) -> String {
+        let mut items: Vec<ItemId> = self
+            .history
+            .keys()
+            .chain(self.window.keys())
+            .copied()
+            .collect();
+        items.sort_unstable_by_key(|&item| (item_registry.display_name(item), item));
+        items.dedup();
+
+        let mut lines = vec!["item,produced,consumed,net,rate".to_string()];
+        for item in items {
+            let current = self.window.get(&item).copied().unwrap_or_default();
+            let (produced, consumed) = self.samples(item).fold(
+                (i64::from(current.produced), i64::from(current.consumed)),
+                |(p, c), s| (p + i64::from(s.produced), c + i64::from(s.consumed)),
+            );
+            let closed_net: i64 = self
+                .samples(item)
+                .map(|s| i64::from(s.produced) - i64::from(s.consumed))
+                .sum();
+            #[allow(clippy::cast_precision_loss)]
+            let minutes = self.samples(item).count() as f32 * THROUGHPUT_SAMPLE_SECS / 60.0;
+            #[allow(clippy::cast_precision_loss)]
+            let rate = if minutes > 0.0 {
+                closed_net as f32 / minutes
+            } else {
+                0.0
+            };
+            lines.push(format!(
+                "{},{produced},{consumed},{},{rate:.2}",
+                csv_field(item_registry.display_name(item)),
+                produced - consumed
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Writes `to_csv` into `dir` as `production_stats_<timestamp>.csv`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn export_csv(
+        &self,
+        item_registry: &ItemRegistry,
+        dir: &Path,
+        timestamp: u64,
+    ) -> std::io::Result<PathBuf> {
+        let path = dir.join(format!("production_stats_{timestamp}.csv"));
+        std::fs::write(&path, self.to_csv(item_registry))?;
+        Ok(path)
+    }
+}
+
+/// Quotes a field holding a comma, quote or line break, doubling any quotes
+/// inside it.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn sample_item_throughput(time: Res<Time>, mut throughput: ResMut<ItemThroughput>) {
+    if throughput.timer.tick(time.delta()).just_finished() {
+        throughput.push_sample();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_capped_and_idle_items_keep_sampling() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut throughput = ItemThroughput::default();
+        throughput.record_produced(ore, 3);
+        throughput.record_consumed(ore, 1);
+        throughput.push_sample();
+        for _ in 0..THROUGHPUT_HISTORY_LEN {
+            throughput.push_sample();
+        }
+
+        let samples: Vec<_> = throughput.samples(ore).copied().collect();
+        assert_eq!(samples.len(), THROUGHPUT_HISTORY_LEN);
+        assert!(samples.iter().all(|s| *s == ThroughputSample::default()));
+        assert_eq!(throughput.tracked_items().collect::<Vec<_>>(), vec![ore]);
+    }
+
+    #[test]
+    fn csv_export_writes_a_header_and_a_row_per_item() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut throughput = ItemThroughput::default();
+        throughput.record_produced(ore, 12);
+        throughput.record_consumed(ore, 2);
+        throughput.push_sample();
+
+        let items = ItemRegistry::from_ron(r#"[(name: "Iron Ore", tier: 0)]"#).unwrap();
+        let path = throughput
+            .export_csv(&items, &std::env::temp_dir(), std::process::id().into())
+            .unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("item,produced,consumed,net,rate"));
+        // One 5 s sample netting 10 items is 120 per minute.
+        assert_eq!(lines.next(), Some("Iron Ore,12,2,10,120.00"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn csv_export_quotes_item_names_with_commas_and_quotes() {
+        let mut throughput = ItemThroughput::default();
+        let items = ItemRegistry::from_ron(r#"[(name: "Plate, \"Heavy\"", tier: 1)]"#).unwrap();
+        throughput.record_produced(ItemId::named("Plate, \"Heavy\"").unwrap(), 1);
+
+        let csv = throughput.to_csv(&items);
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("\"Plate, \"\"Heavy\"\"\",1,0,1,0.00")
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    materials::{items::Cargo, ItemId},
+    workers::Worker,
+};
+
+pub const IN_TRANSIT_SAMPLE_SECS: f32 = 1.0;
+
+/// Items currently carried by workers, summed across the whole factory.
+/// Recounted every `IN_TRANSIT_SAMPLE_SECS` rather than every frame.
+#[derive(Resource)]
+pub struct InTransit {
+    items: HashMap<ItemId, u32>,
+    /// Bumped on each recount. Readers compare it rather than using change
+    /// detection, which also fires on every timer tick.
+    generation: u64,
+    timer: Timer,
+}
+
+impl Default for InTransit {
+    fn default() -> Self {
+        Self {
+            items: HashMap::new(),
+            generation: 0,
+            timer: Timer::from_seconds(IN_TRANSIT_SAMPLE_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl InTransit {
+    #[must_use]
+    pub fn get(&self, item: ItemId) -> u32 {
+        self.items.get(&item).copied().unwrap_or(0)
+    }
+
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.items.values().sum()
+    }
+
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+pub fn count_items_in_transit(
+    time: Res<Time>,
+    mut in_transit: ResMut<InTransit>,
+    workers: Query<&Cargo, With<Worker>>,
+) {
+    if !in_transit.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut items: HashMap<ItemId, u32> = HashMap::new();
+    for cargo in &workers {
+        for (&item, &quantity) in &cargo.items {
+            *items.entry(item).or_default() += quantity;
+        }
+    }
+    in_transit.items = items;
+    in_transit.generation += 1;
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::materials::InventoryAccess;
+    use std::time::Duration;
+
+    fn advance(app: &mut App, secs: u64) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(secs));
+        app.update();
+    }
+
+    #[test]
+    fn carried_items_count_until_dropped_off() {
+        let iron = ItemId::named("Iron Ingot").unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<InTransit>()
+            .add_systems(Update, count_items_in_transit);
+
+        let mut cargo = Cargo::new(10);
+        cargo.add_item(iron, 5);
+        let worker = app.world_mut().spawn((Worker, cargo)).id();
+        // Cargo on anything but a worker is not in transit.
+        app.world_mut().spawn(Cargo {
+            items: HashMap::from([(iron, 3)]),
+            capacity: 10,
+        });
+
+        advance(&mut app, 1);
+        assert_eq!(app.world().resource::<InTransit>().get(iron), 5);
+        assert_eq!(app.world().resource::<InTransit>().generation(), 1);
+
+        // Frames between recounts leave the generation alone.
+        advance(&mut app, 0);
+        assert_eq!(app.world().resource::<InTransit>().generation(), 1);
+
+        app.world_mut()
+            .get_mut::<Cargo>(worker)
+            .unwrap()
+            .remove_item(iron, 5);
+        advance(&mut app, 1);
+        assert_eq!(app.world().resource::<InTransit>().get(iron), 0);
+        assert_eq!(app.world().resource::<InTransit>().total(), 0);
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use crate::{
+    structures::Building,
+    ui::{style::UiTheme, UISystemSet},
+};
+
+pub const BUILDING_PICK_RADIUS: f32 = 32.0;
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct CursorWorldPosition(pub Option<Vec2>);
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HoveredBuilding(pub Option<Entity>);
+
+/// Pointer movement between press and release shorter than
+/// `drag_threshold_px` screen pixels is a click, not a drag.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct InteractionConfig {
+    pub drag_threshold_px: f32,
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            drag_threshold_px: 8.0,
+        }
+    }
+}
+
+impl InteractionConfig {
+    /// `from` and `to` are world positions; `world_per_px` is the camera's
+    /// orthographic scale.
+    #[must_use]
+    pub fn is_drag(&self, from: Vec2, to: Vec2, world_per_px: f32) -> bool {
+        from.distance(to) / world_per_px >= self.drag_threshold_px
+    }
+}
+
+/// World units per screen pixel for the 2D camera, 1.0 when there is none.
+pub fn world_per_px(projections: &Query<&Projection, With<Camera2d>>) -> f32 {
+    match projections.single() {
+        Ok(Projection::Orthographic(projection)) => projection.scale,
+        _ => 1.0,
+    }
+}
+
+pub fn building_at<'a>(
+    world_pos: Vec2,
+    buildings: impl IntoIterator<Item = (Entity, &'a Transform)>,
+) -> Option<Entity> {
+    buildings
+        .into_iter()
+        .find(|(_, transform)| {
+            world_pos.distance(transform.translation.truncate()) < BUILDING_PICK_RADIUS
+        })
+        .map(|(entity, _)| entity)
+}
+
+pub fn track_cursor_world_position(
+    windows: Query<&Window>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut cursor: ResMut<CursorWorldPosition>,
+) {
+    let world_pos = windows.single().ok().and_then(|window| {
+        let (camera, camera_transform) = camera_q.single().ok()?;
+        let cursor_pos = window.cursor_position()?;
+        camera
+            .viewport_to_world(camera_transform, cursor_pos)
+            .ok()
+            .map(|ray| ray.origin.truncate())
+    });
+    cursor.set_if_neq(CursorWorldPosition(world_pos));
+}
+
+pub fn update_hovered_building(
+    cursor: Res<CursorWorldPosition>,
+    ui_interactions: Query<&Interaction, With<Button>>,
+    buildings: Query<(Entity, &Transform), With<Building>>,
+    mut hovered: ResMut<HoveredBuilding>,
+) {
+    let over_ui = ui_interactions
+        .iter()
+        .any(|i| matches!(i, Interaction::Pressed | Interaction::Hovered));
+
+    let target = if over_ui {
+        None
+    } else {
+        cursor.0.and_then(|pos| building_at(pos, buildings))
+    };
+    hovered.set_if_neq(HoveredBuilding(target));
+}
+
+pub fn draw_hover_highlight(
+    hovered: Res<HoveredBuilding>,
+    buildings: Query<(&Transform, Option<&Sprite>), With<Building>>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    let Some((transform, sprite)) = hovered.0.and_then(|entity| buildings.get(entity).ok()) else {
+        return;
+    };
+
+    let size = sprite
+        .and_then(|sprite| sprite.custom_size)
+        .unwrap_or(Vec2::splat(BUILDING_PICK_RADIUS * 2.0));
+    gizmos.rect_2d(
+        Isometry2d::from_translation(transform.translation.truncate()),
+        size + Vec2::splat(6.0),
+        theme.header_color,
+    );
+}
+
+pub struct HoverPlugin;
+
+impl Plugin for HoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorWorldPosition>()
+            .init_resource::<HoveredBuilding>()
+            .init_resource::<InteractionConfig>()
+            .add_systems(
+                Update,
+                (
+                    (track_cursor_world_position, update_hovered_building)
+                        .chain()
+                        .in_set(UISystemSet::InputDetection),
+                    draw_hover_highlight.in_set(UISystemSet::VisualUpdates),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn move_cursor(app: &mut App, pos: Option<Vec2>) -> Option<Entity> {
+        app.world_mut().resource_mut::<CursorWorldPosition>().0 = pos;
+        app.update();
+        app.world().resource::<HoveredBuilding>().0
+    }
+
+    fn hover_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<CursorWorldPosition>()
+            .init_resource::<HoveredBuilding>()
+            .add_systems(Update, update_hovered_building);
+        app
+    }
+
+    #[test]
+    fn hovered_building_follows_cursor() {
+        let mut app = hover_app();
+        let building = app
+            .world_mut()
+            .spawn((Building, Transform::from_xyz(64.0, 0.0, 0.0)))
+            .id();
+
+        assert_eq!(
+            move_cursor(&mut app, Some(Vec2::new(70.0, 5.0))),
+            Some(building)
+        );
+        assert_eq!(move_cursor(&mut app, Some(Vec2::new(200.0, 0.0))), None);
+        assert_eq!(
+            move_cursor(&mut app, Some(Vec2::new(64.0, 0.0))),
+            Some(building)
+        );
+        assert_eq!(move_cursor(&mut app, None), None);
+    }
+
+    #[test]
+    fn hovering_ui_suppresses_building_highlight() {
+        let mut app = hover_app();
+        app.world_mut()
+            .spawn((Building, Transform::from_xyz(0.0, 0.0, 0.0)));
+        let button = app.world_mut().spawn((Button, Interaction::Hovered)).id();
+
+        assert_eq!(move_cursor(&mut app, Some(Vec2::ZERO)), None);
+
+        app.world_mut().entity_mut(button).insert(Interaction::None);
+        assert!(move_cursor(&mut app, Some(Vec2::ZERO)).is_some());
+    }
+}
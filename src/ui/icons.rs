@@ -1,12 +1,15 @@
+use bevy::asset::LoadState;
 use bevy::prelude::*;
 
+use crate::ui::{style::UiTheme, UISystemSet};
+
 #[derive(Resource)]
 pub struct IconAtlas {
     pub image: Handle<Image>,
     pub layout: Handle<TextureAtlasLayout>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub enum GameIcon {
     Power = 0,
     Compute = 1,
@@ -18,29 +21,83 @@ pub enum GameIcon {
     FactoryInfo = 7,
 }
 
+impl GameIcon {
+    /// Glyph shown in place of the icon when the atlas is unavailable.
+    #[must_use]
+    pub fn placeholder(self) -> &'static str {
+        match self {
+            Self::Power => "P",
+            Self::Compute => "C",
+            Self::Workers | Self::Workflows => "W",
+            Self::Score => "$",
+            Self::Build => "B",
+            Self::SpawnWorker => "+",
+            Self::FactoryInfo => "i",
+        }
+    }
+}
+
+/// An icon drawn from the atlas, so it can be swapped for its placeholder if
+/// the atlas image fails to load.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AtlasIcon(pub GameIcon);
+
+/// The atlas image and cell for `icon`, or its placeholder glyph without an
+/// atlas.
+pub fn spawn_icon_in(
+    parent: &mut ChildSpawnerCommands,
+    icon_atlas: Option<&IconAtlas>,
+    icon: GameIcon,
+    size: f32,
+    color: Color,
+) -> Entity {
+    match icon_atlas {
+        Some(icon_atlas) => parent.spawn(atlas_icon(icon_atlas, icon, size)).id(),
+        None => parent.spawn(icon_placeholder(icon, size, color)).id(),
+    }
+}
+
 pub fn spawn_icon(
     commands: &mut Commands,
-    icon_atlas: &IconAtlas,
+    icon_atlas: Option<&IconAtlas>,
     icon: GameIcon,
     size: f32,
+    color: Color,
 ) -> Entity {
-    commands
-        .spawn((
-            ImageNode {
-                image: icon_atlas.image.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: icon_atlas.layout.clone(),
-                    index: icon as usize,
-                }),
-                ..default()
-            },
-            Node {
-                width: Val::Px(size),
-                height: Val::Px(size),
-                ..default()
-            },
-        ))
-        .id()
+    match icon_atlas {
+        Some(icon_atlas) => commands.spawn(atlas_icon(icon_atlas, icon, size)).id(),
+        None => commands.spawn(icon_placeholder(icon, size, color)).id(),
+    }
+}
+
+fn atlas_icon(icon_atlas: &IconAtlas, icon: GameIcon, size: f32) -> impl Bundle {
+    (
+        ImageNode {
+            image: icon_atlas.image.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: icon_atlas.layout.clone(),
+                index: icon as usize,
+            }),
+            ..default()
+        },
+        Node {
+            width: Val::Px(size),
+            height: Val::Px(size),
+            ..default()
+        },
+        AtlasIcon(icon),
+    )
+}
+
+fn icon_placeholder(icon: GameIcon, size: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(icon.placeholder()),
+        TextFont {
+            font_size: size * 0.8,
+            ..default()
+        },
+        TextColor(color),
+    )
 }
 
 fn load_icon_atlas(
@@ -58,10 +115,46 @@ fn load_icon_atlas(
     });
 }
 
+/// If the atlas image fails to load, drops `IconAtlas` so later UI spawns
+/// placeholders, and swaps every icon already on screen for its glyph.
+fn fall_back_on_failed_atlas(
+    mut commands: Commands,
+    icon_atlas: Option<Res<IconAtlas>>,
+    asset_server: Res<AssetServer>,
+    icons: Query<(Entity, &AtlasIcon, &Node)>,
+    theme: Res<UiTheme>,
+) {
+    let Some(icon_atlas) = icon_atlas else {
+        return;
+    };
+    if !matches!(
+        asset_server.get_load_state(icon_atlas.image.id()),
+        Some(LoadState::Failed(_))
+    ) {
+        return;
+    }
+
+    warn!("icon atlas failed to load, showing placeholder labels");
+    commands.remove_resource::<IconAtlas>();
+    for (entity, &AtlasIcon(icon), node) in &icons {
+        let size = match node.width {
+            Val::Px(size) => size,
+            _ => 16.0,
+        };
+        commands
+            .entity(entity)
+            .remove::<(ImageNode, AtlasIcon)>()
+            .insert(icon_placeholder(icon, size, theme.text_color));
+    }
+}
+
 pub struct IconPlugin;
 
 impl Plugin for IconPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, load_icon_atlas);
+        app.add_systems(Startup, load_icon_atlas).add_systems(
+            Update,
+            fall_back_on_failed_atlas.in_set(UISystemSet::EntityManagement),
+        );
     }
 }
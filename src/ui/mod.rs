@@ -3,12 +3,17 @@ use bevy::prelude::*;
 use bevy::ui::Checked;
 use bevy::ui_widgets::UiWidgetsPlugins;
 
+pub mod hover;
 pub mod icons;
 pub mod modes;
+pub mod overlay;
 pub mod panels;
 pub mod popups;
+pub mod reachability;
 pub mod scroll;
 pub mod style;
+pub mod worker_groups;
+pub mod zones;
 
 pub use panels::action_bar::build_panel::SelectedBuilding;
 
@@ -19,7 +24,9 @@ use panels::action_bar::build_panel::BuildingButton;
 use panels::action_bar::ActivePanel;
 use popups::building_menu::{BuildingMenu, CloseMenuEvent};
 use scroll::handle_ui_scroll;
-use style::StylePlugin;
+use style::{StylePlugin, UiTheme};
+
+use crate::workers::RallyPoint;
 
 #[derive(States, Debug, Default, Hash, PartialEq, Eq, Clone)]
 pub enum UiMode {
@@ -180,6 +187,8 @@ fn on_exit_workflow_create(
 ) {
     state.name.clear();
     state.steps.clear();
+    state.template_conditions.clear();
+    state.is_dedicated = false;
     state.desired_worker_count = 1;
     state.building_set.clear();
     state.phase = modes::workflow_create::CreationPhase::SelectBuildings;
@@ -199,6 +208,52 @@ fn on_exit_workflow_create(
     }
 }
 
+fn set_rally_point_at_cursor(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    grid: Res<crate::grid::Grid>,
+    windows: Query<&Window>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut rally_point: ResMut<RallyPoint>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+    if let Some(coords) = grid.get_cursor_grid_coordinates(&windows, &camera_q) {
+        rally_point.rally = (coords.grid_x, coords.grid_y);
+        info!(rally = ?rally_point.rally, "rally point set");
+    }
+}
+
+fn draw_rally_point(
+    rally_point: Res<RallyPoint>,
+    grid: Res<crate::grid::Grid>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    if rally_point.rally == rally_point.spawn {
+        return;
+    }
+    let (x, y) = rally_point.rally;
+    let center = grid.grid_to_world_coordinates(x, y);
+    gizmos.circle_2d(
+        Isometry2d::from_translation(center),
+        grid.cell_size * 0.4,
+        theme.worker_color,
+    );
+}
+
+/// P pauses and resumes the simulation, including after an auto-pause.
+fn toggle_simulation_pause(keyboard: Res<ButtonInput<KeyCode>>, mut time: ResMut<Time<Virtual>>) {
+    if !keyboard.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    if time.is_paused() {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
+
 pub struct UIPlugin;
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
@@ -211,9 +266,13 @@ impl Plugin for UIPlugin {
         app.add_systems(
             Update,
             (
-                (handle_escape, handle_ui_scroll).in_set(UISystemSet::InputDetection),
+                (handle_escape, handle_ui_scroll, toggle_simulation_pause)
+                    .in_set(UISystemSet::InputDetection),
+                set_rally_point_at_cursor
+                    .run_if(in_state(UiMode::Observe))
+                    .in_set(UISystemSet::InputDetection),
                 sync_selected_building_to_mode.in_set(UISystemSet::EntityManagement),
-                update_mode_status_label.in_set(UISystemSet::VisualUpdates),
+                (update_mode_status_label, draw_rally_point).in_set(UISystemSet::VisualUpdates),
             ),
         );
 
@@ -224,16 +283,29 @@ impl Plugin for UIPlugin {
             InputDispatchPlugin,
             UiWidgetsPlugins,
             StylePlugin,
+            hover::HoverPlugin,
             icons::IconPlugin,
             modes::PlacementPlugin,
             modes::workflow_create::WorkflowCreationPlugin,
             modes::workflow_builder::WorkflowBuilderPlugin,
-            panels::TopBarPlugin,
-            panels::ActionBarPlugin,
-            panels::action_bar::build_panel::BuildPanelPlugin,
-            panels::WorkflowListPlugin,
-            popups::BuildingMenuPlugin,
-            popups::TooltipsPlugin,
+            overlay::OverlayPlugin,
+            zones::ZonePlugin,
+            reachability::ReachabilityPlugin,
+            worker_groups::WorkerGroupsPlugin,
+            (
+                panels::TopBarPlugin,
+                panels::ActionBarPlugin,
+                panels::action_bar::build_panel::BuildPanelPlugin,
+                panels::WorkflowListPlugin,
+                panels::ThroughputPanelPlugin,
+                panels::BuildingSearchPlugin,
+                panels::WorkflowDebugPlugin,
+            ),
+            (
+                popups::BuildingMenuPlugin,
+                popups::TooltipsPlugin,
+                popups::ToastPlugin,
+            ),
         ));
     }
 }
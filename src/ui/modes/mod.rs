@@ -10,14 +10,27 @@ pub struct PlacementPlugin;
 
 impl Plugin for PlacementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                placement::update_placement_ghost.run_if(in_state(UiMode::Place)),
-                placement::display_placement_error,
-                placement::cleanup_placement_errors,
-            )
-                .in_set(UISystemSet::VisualUpdates),
-        );
+        app.init_resource::<placement::AlignmentGuides>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        placement::rotate_selected_building,
+                        placement::flip_selected_building,
+                    )
+                        .run_if(in_state(UiMode::Place))
+                        .in_set(UISystemSet::InputDetection),
+                    placement::repeat_last_placed
+                        .run_if(in_state(UiMode::Observe))
+                        .in_set(UISystemSet::InputDetection),
+                    (
+                        placement::update_placement_ghost.run_if(in_state(UiMode::Place)),
+                        placement::display_placement_error,
+                        placement::cleanup_placement_errors,
+                        placement::draw_conveyor_arrows,
+                    )
+                        .in_set(UISystemSet::VisualUpdates),
+                ),
+            );
     }
 }
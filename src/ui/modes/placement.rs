@@ -1,8 +1,11 @@
 use bevy::prelude::*;
 
 use crate::{
-    grid::Grid,
-    structures::{building_config::BuildingRegistry, PlaceBuildingValidationEvent},
+    grid::{Grid, Position},
+    structures::{
+        building_config::{BuildingComponentDef, BuildingRegistry},
+        Building, Conveyor, Facing, LastPlaced, PlaceBuildingValidationEvent,
+    },
     ui::SelectedBuilding,
 };
 
@@ -16,19 +19,92 @@ pub struct PlacementErrorMessage {
     pub timer: Timer,
 }
 
+/// Faint guide lines from the placement ghost to the nearest buildings that
+/// share its row or column, up to `range` cells away.
+#[derive(Resource, Debug)]
+pub struct AlignmentGuides {
+    pub enabled: bool,
+    pub range: i32,
+}
+
+impl Default for AlignmentGuides {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            range: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlignmentGuide {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+}
+
+const ALIGNMENT_GUIDE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+
+/// Finds the closest building in each of the four grid directions from the
+/// cursor cell, ignoring anything further than `range` cells.
+pub fn alignment_guides(
+    cursor: (i32, i32),
+    buildings: impl IntoIterator<Item = Position>,
+    range: i32,
+) -> Vec<AlignmentGuide> {
+    // Up, down, right, left.
+    let mut nearest: [Option<(i32, (i32, i32))>; 4] = [None; 4];
+    for pos in buildings {
+        let (dx, dy) = (pos.x - cursor.0, pos.y - cursor.1);
+        let (slot, distance) = match (dx, dy) {
+            (0, 0) => continue,
+            (0, dy) if dy > 0 => (0, dy),
+            (0, dy) => (1, -dy),
+            (dx, 0) if dx > 0 => (2, dx),
+            (dx, 0) => (3, -dx),
+            _ => continue,
+        };
+        if distance > range {
+            continue;
+        }
+        if nearest[slot].is_none_or(|(best, _)| distance < best) {
+            nearest[slot] = Some((distance, (pos.x, pos.y)));
+        }
+    }
+
+    nearest
+        .into_iter()
+        .flatten()
+        .map(|(_, to)| AlignmentGuide { from: cursor, to })
+        .collect()
+}
+
 pub fn update_placement_ghost(
     mut commands: Commands,
     selected_building: Res<SelectedBuilding>,
     building_registry: Res<BuildingRegistry>,
     grid: Res<Grid>,
+    guides: Res<AlignmentGuides>,
     windows: Query<&Window>,
     camera_q: Query<(&Camera, &GlobalTransform)>,
+    buildings: Query<&Position, With<Building>>,
     mut ghost_query: Query<(Entity, &mut Transform, &mut Sprite, &mut PlacementGhost)>,
+    mut gizmos: Gizmos,
 ) {
     let cursor_coords = grid.get_cursor_grid_coordinates(&windows, &camera_q);
 
     match (&selected_building.building_name, cursor_coords) {
         (Some(building_name), Some(coords)) => {
+            if guides.enabled {
+                let cursor = (coords.grid_x, coords.grid_y);
+                for guide in alignment_guides(cursor, buildings.iter().copied(), guides.range) {
+                    gizmos.line_2d(
+                        grid.grid_to_world_coordinates(guide.from.0, guide.from.1),
+                        grid.grid_to_world_coordinates(guide.to.0, guide.to.1),
+                        ALIGNMENT_GUIDE_COLOR,
+                    );
+                }
+            }
+
             if let Some(def) = building_registry.get_definition(building_name) {
                 if let Ok((_, mut transform, mut sprite, mut ghost)) = ghost_query.single_mut() {
                     let world_pos = grid.grid_to_world_coordinates(coords.grid_x, coords.grid_y);
@@ -116,3 +192,137 @@ pub fn cleanup_placement_errors(
         }
     }
 }
+
+pub fn rotate_selected_building(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut selected_building: ResMut<SelectedBuilding>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        selected_building.facing = selected_building.facing.rotated_clockwise();
+    }
+}
+
+/// X mirrors the selected building left to right and V top to bottom.
+/// Footprints are anchored rectangles, so only the facing changes.
+pub fn flip_selected_building(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut selected_building: ResMut<SelectedBuilding>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyX) {
+        selected_building.facing = selected_building.facing.flipped_horizontally();
+    }
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        selected_building.facing = selected_building.facing.flipped_vertically();
+    }
+}
+
+/// Picks up the last placed building type and facing again, which switches
+/// the UI into placement mode.
+pub fn repeat_last_placed(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    last_placed: Res<LastPlaced>,
+    mut selected_building: ResMut<SelectedBuilding>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyQ) {
+        return;
+    }
+    let Some(building_name) = &last_placed.building_name else {
+        return;
+    };
+    selected_building.building_name = Some(building_name.clone());
+    selected_building.facing = last_placed.facing;
+}
+
+const CONVEYOR_ARROW_COLOR: Color = Color::srgb(0.95, 0.85, 0.3);
+
+fn draw_facing_arrow(gizmos: &mut Gizmos, center: Vec2, facing: Facing, length: f32) {
+    let (dx, dy) = facing.offset();
+    #[allow(clippy::cast_precision_loss)]
+    let direction = Vec2::new(dx as f32, dy as f32);
+    let half = direction * length / 2.0;
+    gizmos.arrow_2d(center - half, center + half, CONVEYOR_ARROW_COLOR);
+}
+
+pub fn draw_conveyor_arrows(
+    mut gizmos: Gizmos,
+    conveyors: Query<(&Conveyor, &Transform)>,
+    ghosts: Query<(&PlacementGhost, &Transform)>,
+    selected_building: Res<SelectedBuilding>,
+    building_registry: Res<BuildingRegistry>,
+) {
+    for (conveyor, transform) in &conveyors {
+        draw_facing_arrow(
+            &mut gizmos,
+            transform.translation.truncate(),
+            conveyor.direction,
+            16.0,
+        );
+    }
+
+    for (ghost, transform) in &ghosts {
+        let is_conveyor = building_registry
+            .get_definition(&ghost.building_name)
+            .is_some_and(|def| {
+                def.components
+                    .iter()
+                    .any(|component| matches!(component, BuildingComponentDef::Conveyor { .. }))
+            });
+        if is_conveyor {
+            draw_facing_arrow(
+                &mut gizmos,
+                transform.translation.truncate(),
+                selected_building.facing,
+                16.0,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn building_in_the_same_column_gets_a_vertical_guide() {
+        let buildings = [
+            Position { x: 5, y: 0 },
+            Position { x: 8, y: 3 },
+            Position { x: 5, y: 30 },
+        ];
+
+        let guides = alignment_guides((5, 7), buildings, 12);
+
+        assert_eq!(
+            guides,
+            vec![AlignmentGuide {
+                from: (5, 7),
+                to: (5, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn flipping_mirrors_the_selected_facing() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(SelectedBuilding {
+                building_name: Some("Conveyor".to_string()),
+                facing: Facing::East,
+            })
+            .add_systems(Update, flip_selected_building);
+
+        let press = |app: &mut App, key: KeyCode| {
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.reset_all();
+            keyboard.press(key);
+            app.update();
+            app.world().resource::<SelectedBuilding>().facing
+        };
+
+        assert_eq!(press(&mut app, KeyCode::KeyX), Facing::West);
+        assert_eq!(press(&mut app, KeyCode::KeyV), Facing::West);
+        app.world_mut().resource_mut::<SelectedBuilding>().facing = Facing::North;
+        assert_eq!(press(&mut app, KeyCode::KeyV), Facing::South);
+        assert_eq!(press(&mut app, KeyCode::KeyX), Facing::South);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use bevy::picking::hover::Hovered;
 use bevy::prelude::*;
@@ -6,18 +6,17 @@ use bevy::ui::UiGlobalTransform;
 
 use crate::{
     grid::Position,
-    materials::ItemRegistry,
+    materials::{ItemId, ItemRarity, ItemRegistry},
+    structures::DisplayLabel,
     ui::{
         modes::workflow_create::{CreationPhase, WorkflowCreationState},
         scroll::Scrollable,
-        style::{
-            ButtonStyle, BUTTON_BG, CANCEL_BG, CONFIRM_BG, DIM_TEXT, HEADER_COLOR, PANEL_BG,
-            PANEL_BORDER, POPUP_BG, SELECTED_BG, TEXT_COLOR,
-        },
+        style::{ButtonStyle, ThemeRole, UiTheme},
         UISystemSet,
     },
     workers::workflows::components::{
-        CreateWorkflowEvent, StepTarget, UpdateWorkflowEvent, WorkflowAction, WorkflowStep,
+        CreateWorkflowEvent, StepCondition, StepTarget, UpdateWorkflowEvent, WorkflowAction,
+        WorkflowConfig, WorkflowStep,
     },
 };
 
@@ -47,6 +46,11 @@ pub struct StepFilterButton {
     pub step_index: usize,
 }
 
+#[derive(Component)]
+pub struct StepConditionButton {
+    pub step_index: usize,
+}
+
 #[derive(Component)]
 pub struct StepRemoveButton {
     pub step_index: usize,
@@ -73,6 +77,9 @@ pub struct BuilderWorkerIncrementButton;
 #[derive(Component)]
 pub struct BuilderWorkerDecrementButton;
 
+#[derive(Component)]
+pub struct BuilderWorkerResetButton;
+
 #[derive(Component)]
 pub struct TargetDropdown {
     pub step_index: usize,
@@ -92,17 +99,48 @@ pub struct FilterDropdown {
 #[derive(Component)]
 pub struct FilterCheckbox {
     pub step_index: usize,
-    pub item_name: String,
+    pub item: ItemId,
+}
+
+#[derive(Component)]
+pub struct FilterGroupHeader {
+    pub group: ItemRarity,
+}
+
+/// Holds one group's checkboxes so collapsing hides them without touching
+/// the step's filter.
+#[derive(Component)]
+pub struct FilterGroupRows {
+    pub group: ItemRarity,
 }
 
 #[derive(Component)]
 pub struct BuilderPoolSummary;
 
+/// Lives in the step list so any step rebuild clears it.
+#[derive(Component)]
+pub struct BuilderValidationMessage;
+
+/// Indices of steps whose specific target is no longer in the building pool.
+fn steps_outside_pool(steps: &[WorkflowStep], building_set: &HashSet<Entity>) -> Vec<usize> {
+    steps
+        .iter()
+        .enumerate()
+        .filter(|(_, step)| {
+            matches!(step.target, StepTarget::Specific(entity) if !building_set.contains(&entity))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
 fn spawn_builder_modal_on_phase(
     state: Res<WorkflowCreationState>,
     mut commands: Commands,
     existing_modals: Query<Entity, With<WorkflowBuilderModal>>,
     names: Query<&Name>,
+    labels: Query<&DisplayLabel>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
 ) {
     if !state.is_changed() {
         return;
@@ -114,13 +152,23 @@ fn spawn_builder_modal_on_phase(
         return;
     }
 
-    spawn_builder_modal(&mut commands, &state, &names);
+    spawn_builder_modal(
+        &mut commands,
+        &state,
+        &names,
+        &labels,
+        &item_registry,
+        &theme,
+    );
 }
 
 fn spawn_builder_modal(
     commands: &mut Commands,
     state: &WorkflowCreationState,
     names: &Query<&Name>,
+    labels: &Query<&DisplayLabel>,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
     commands
         .spawn((
@@ -151,29 +199,29 @@ fn spawn_builder_modal(
                         overflow: Overflow::scroll_y(),
                         ..default()
                     },
-                    BackgroundColor(PANEL_BG),
-                    BorderColor::all(PANEL_BORDER),
+                    theme.background(ThemeRole::PanelBg),
+                    theme.border(ThemeRole::PanelBorder),
                     ScrollPosition::default(),
                     Scrollable,
                 ))
                 .with_children(|modal| {
-                    spawn_modal_header(modal, &state.name);
-                    spawn_pool_summary(modal, &state.building_set, names);
-                    spawn_step_section(modal, state, names);
-                    spawn_worker_count_section(modal, state.desired_worker_count);
-                    spawn_modal_buttons(modal);
+                    spawn_modal_header(modal, &state.name, theme);
+                    spawn_pool_summary(modal, &state.building_set, names, theme);
+                    spawn_step_section(modal, state, names, labels, item_registry, theme);
+                    spawn_worker_count_section(modal, state.desired_worker_count, theme);
+                    spawn_modal_buttons(modal, theme);
                 });
         });
 }
 
-fn spawn_modal_header(parent: &mut ChildSpawnerCommands, name: &str) {
+fn spawn_modal_header(parent: &mut ChildSpawnerCommands, name: &str, theme: &UiTheme) {
     parent.spawn((
         Text::new(format!("Workflow Builder: \"{name}\"")),
         TextFont {
             font_size: 16.0,
             ..default()
         },
-        TextColor(HEADER_COLOR),
+        theme.text(ThemeRole::Header),
     ));
 }
 
@@ -181,6 +229,7 @@ fn spawn_pool_summary(
     parent: &mut ChildSpawnerCommands,
     building_set: &HashSet<Entity>,
     names: &Query<&Name>,
+    theme: &UiTheme,
 ) {
     let mut type_counts: HashMap<String, u32> = HashMap::new();
     for &entity in building_set {
@@ -211,7 +260,7 @@ fn spawn_pool_summary(
                 border: UiRect::bottom(Val::Px(1.0)),
                 ..default()
             },
-            BorderColor::all(PANEL_BORDER),
+            theme.border(ThemeRole::PanelBorder),
             BuilderPoolSummary,
         ))
         .with_children(|section| {
@@ -221,7 +270,7 @@ fn spawn_pool_summary(
                     font_size: 12.0,
                     ..default()
                 },
-                TextColor(DIM_TEXT),
+                theme.text(ThemeRole::DimText),
             ));
         });
 }
@@ -230,6 +279,9 @@ fn spawn_step_section(
     parent: &mut ChildSpawnerCommands,
     state: &WorkflowCreationState,
     names: &Query<&Name>,
+    labels: &Query<&DisplayLabel>,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
     parent.spawn((
         Text::new("Steps:"),
@@ -237,7 +289,7 @@ fn spawn_step_section(
             font_size: 13.0,
             ..default()
         },
-        TextColor(TEXT_COLOR),
+        theme.text(ThemeRole::Text),
     ));
 
     parent
@@ -259,11 +311,20 @@ fn spawn_step_section(
                         font_size: 11.0,
                         ..default()
                     },
-                    TextColor(DIM_TEXT),
+                    theme.text(ThemeRole::DimText),
                 ));
             } else {
                 for (i, step) in state.steps.iter().enumerate() {
-                    spawn_step_row(step_list, i, step, &state.building_set, names);
+                    spawn_step_row(
+                        step_list,
+                        i,
+                        step,
+                        &state.building_set,
+                        names,
+                        labels,
+                        item_registry,
+                        theme,
+                    );
                 }
             }
         });
@@ -278,8 +339,8 @@ fn spawn_step_section(
                 align_items: AlignItems::Center,
                 ..default()
             },
-            BackgroundColor(BUTTON_BG),
-            ButtonStyle::default_button(),
+            theme.background(ThemeRole::Button),
+            ButtonStyle::default_button(theme),
             Hovered::default(),
             AddStepButton,
         ))
@@ -290,47 +351,97 @@ fn spawn_step_section(
                     font_size: 12.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
         });
 }
 
-fn step_labels(step: &WorkflowStep, names: &Query<&Name>) -> (String, String, String, String) {
-    let action = match &step.action {
-        WorkflowAction::Pickup(_) => "Pickup",
-        WorkflowAction::Dropoff(_) => "Dropoff",
-    }
-    .to_string();
-    let preposition = match &step.action {
-        WorkflowAction::Pickup(_) => "from",
-        WorkflowAction::Dropoff(_) => "to",
+/// Thresholds the condition button cycles through before going back to
+/// unconditional.
+const CONDITION_THRESHOLDS: [u32; 3] = [10, 25, 50];
+
+fn condition_label(condition: Option<&StepCondition>) -> String {
+    match condition {
+        None => "always".to_string(),
+        Some(StepCondition::ItemBelow { threshold, .. }) => format!("if <{threshold}"),
     }
-    .to_string();
+}
+
+/// What a new condition on step `index` watches: the first item the step or
+/// the one after it filters on, held at the next step's specific target.
+/// That covers the usual "pick up only while the crafter I feed is low".
+fn condition_subject(
+    steps: &[WorkflowStep],
+    index: usize,
+    item_registry: &ItemRegistry,
+) -> Option<(Entity, ItemId)> {
+    let step = steps.get(index)?;
+    let next = steps.get((index + 1) % steps.len())?;
+    let StepTarget::Specific(building) = next.target else {
+        return None;
+    };
+    let first_filtered = |step: &WorkflowStep| {
+        let (WorkflowAction::Pickup(Some(items)) | WorkflowAction::Dropoff(Some(items))) =
+            &step.action
+        else {
+            return None;
+        };
+        items
+            .keys()
+            .copied()
+            .min_by_key(|&item| item_registry.display_name(item))
+    };
+    let item = first_filtered(step).or_else(|| first_filtered(next))?;
+    Some((building, item))
+}
+
+/// Next condition in the cycle: unconditional, then each threshold in turn.
+/// Stays unconditional when there is nothing for a condition to watch.
+fn cycle_condition(
+    steps: &[WorkflowStep],
+    index: usize,
+    item_registry: &ItemRegistry,
+) -> Option<StepCondition> {
+    let next_threshold = match steps.get(index)?.condition {
+        None => CONDITION_THRESHOLDS.first(),
+        Some(StepCondition::ItemBelow { threshold, .. }) => CONDITION_THRESHOLDS
+            .iter()
+            .find(|&&candidate| candidate > threshold),
+    }?;
+    let (building, item) = condition_subject(steps, index, item_registry)?;
+    Some(StepCondition::ItemBelow {
+        building,
+        item,
+        threshold: *next_threshold,
+    })
+}
+
+fn step_labels(
+    step: &WorkflowStep,
+    names: &Query<&Name>,
+    labels: &Query<&DisplayLabel>,
+    item_registry: &ItemRegistry,
+) -> (String, String, String, String, String) {
+    let action = step.action.verb().to_string();
+    let preposition = step.action.preposition().to_string();
     let target = match &step.target {
-        StepTarget::Specific(entity) => names
-            .get(*entity)
-            .map_or_else(|_| "Unknown".to_string(), |n| n.as_str().to_string()),
+        StepTarget::Specific(entity) => names.get(*entity).map_or_else(
+            |_| "Unknown".to_string(),
+            |n| DisplayLabel::or_name(labels.get(*entity).ok(), n).to_string(),
+        ),
         StepTarget::ByType(type_name) => format!("any {type_name}"),
     };
+    // The filter button is narrow, so longer filters collapse to a count.
     let filter = match &step.action {
-        WorkflowAction::Pickup(Some(items)) | WorkflowAction::Dropoff(Some(items)) => {
-            if items.is_empty() {
-                "All".to_string()
-            } else {
-                let keys: Vec<_> = items.keys().collect();
-                if keys.len() <= 2 {
-                    keys.iter()
-                        .map(|s| s.as_str())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                } else {
-                    format!("{} items", keys.len())
-                }
-            }
+        WorkflowAction::Pickup(Some(items)) | WorkflowAction::Dropoff(Some(items))
+            if items.len() > 2 =>
+        {
+            format!("{} items", items.len())
         }
-        _ => "All".to_string(),
+        _ => step.action.filter_summary(item_registry),
     };
-    (action, preposition, target, filter)
+    let condition = condition_label(step.condition.as_ref());
+    (action, preposition, target, filter, condition)
 }
 
 fn spawn_step_row(
@@ -339,8 +450,12 @@ fn spawn_step_row(
     step: &WorkflowStep,
     _building_set: &HashSet<Entity>,
     names: &Query<&Name>,
+    labels: &Query<&DisplayLabel>,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
-    let (action_label, preposition, target_label, filter_label) = step_labels(step, names);
+    let (action_label, preposition, target_label, filter_label, condition_label) =
+        step_labels(step, names, labels, item_registry);
 
     parent
         .spawn((
@@ -362,6 +477,8 @@ fn spawn_step_row(
                 &preposition,
                 &target_label,
                 &filter_label,
+                &condition_label,
+                theme,
             );
         });
 }
@@ -372,6 +489,7 @@ fn spawn_step_button(
     width: Val,
     style: ButtonStyle,
     marker: impl Component,
+    theme: &UiTheme,
 ) {
     let bg = style.default_bg;
     parent
@@ -396,7 +514,7 @@ fn spawn_step_button(
                     font_size: 11.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
         });
 }
@@ -408,6 +526,8 @@ fn spawn_step_row_children(
     preposition: &str,
     target_label: &str,
     filter_label: &str,
+    condition_label: &str,
+    theme: &UiTheme,
 ) {
     row.spawn((
         Text::new(format!("{}.", index + 1)),
@@ -415,7 +535,7 @@ fn spawn_step_row_children(
             font_size: 12.0,
             ..default()
         },
-        TextColor(DIM_TEXT),
+        theme.text(ThemeRole::DimText),
         Node {
             width: Val::Px(20.0),
             ..default()
@@ -426,8 +546,9 @@ fn spawn_step_row_children(
         row,
         action_label,
         Val::Px(70.0),
-        ButtonStyle::default_button(),
+        ButtonStyle::default_button(theme),
         StepActionButton { step_index: index },
+        theme,
     );
 
     row.spawn((
@@ -436,35 +557,79 @@ fn spawn_step_row_children(
             font_size: 11.0,
             ..default()
         },
-        TextColor(DIM_TEXT),
+        theme.text(ThemeRole::DimText),
     ));
 
     spawn_step_button(
         row,
         target_label,
         Val::Px(140.0),
-        ButtonStyle::default_button(),
+        ButtonStyle::default_button(theme),
         StepTargetButton { step_index: index },
+        theme,
     );
 
     spawn_step_button(
         row,
         filter_label,
         Val::Px(60.0),
-        ButtonStyle::default_button(),
+        ButtonStyle::default_button(theme),
         StepFilterButton { step_index: index },
+        theme,
+    );
+
+    spawn_step_button(
+        row,
+        condition_label,
+        Val::Px(56.0),
+        ButtonStyle::default_button(theme),
+        StepConditionButton { step_index: index },
+        theme,
     );
 
     spawn_step_button(
         row,
         "x",
         Val::Px(24.0),
-        ButtonStyle::cancel(),
+        ButtonStyle::cancel(theme),
         StepRemoveButton { step_index: index },
+        theme,
     );
 }
 
-fn spawn_worker_count_section(parent: &mut ChildSpawnerCommands, count: u32) {
+fn spawn_worker_step_button(
+    row: &mut ChildSpawnerCommands,
+    label: &str,
+    marker: impl Bundle,
+    theme: &UiTheme,
+) {
+    row.spawn((
+        Button,
+        Node {
+            width: Val::Px(28.0),
+            height: Val::Px(28.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        theme.background(ThemeRole::Button),
+        ButtonStyle::default_button(theme),
+        Hovered::default(),
+        marker,
+    ))
+    .with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            theme.text(ThemeRole::Text),
+        ));
+    });
+}
+
+fn spawn_worker_count_section(parent: &mut ChildSpawnerCommands, count: u32, theme: &UiTheme) {
     parent
         .spawn((
             Node {
@@ -477,7 +642,7 @@ fn spawn_worker_count_section(parent: &mut ChildSpawnerCommands, count: u32) {
                 border: UiRect::top(Val::Px(1.0)),
                 ..default()
             },
-            BorderColor::all(PANEL_BORDER),
+            theme.border(ThemeRole::PanelBorder),
         ))
         .with_children(|row| {
             row.spawn((
@@ -486,33 +651,10 @@ fn spawn_worker_count_section(parent: &mut ChildSpawnerCommands, count: u32) {
                     font_size: 13.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
 
-            row.spawn((
-                Button,
-                Node {
-                    width: Val::Px(28.0),
-                    height: Val::Px(28.0),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..default()
-                },
-                BackgroundColor(BUTTON_BG),
-                ButtonStyle::default_button(),
-                Hovered::default(),
-                BuilderWorkerDecrementButton,
-            ))
-            .with_children(|btn| {
-                btn.spawn((
-                    Text::new("-"),
-                    TextFont {
-                        font_size: 16.0,
-                        ..default()
-                    },
-                    TextColor(TEXT_COLOR),
-                ));
-            });
+            spawn_worker_step_button(row, "-", BuilderWorkerDecrementButton, theme);
 
             row.spawn((
                 Text::new(format!("{count}")),
@@ -520,38 +662,40 @@ fn spawn_worker_count_section(parent: &mut ChildSpawnerCommands, count: u32) {
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
                 BuilderWorkerCountLabel,
             ));
 
+            spawn_worker_step_button(row, "+", BuilderWorkerIncrementButton, theme);
+
             row.spawn((
                 Button,
                 Node {
-                    width: Val::Px(28.0),
                     height: Val::Px(28.0),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
                     ..default()
                 },
-                BackgroundColor(BUTTON_BG),
-                ButtonStyle::default_button(),
+                theme.background(ThemeRole::Button),
+                ButtonStyle::default_button(theme),
                 Hovered::default(),
-                BuilderWorkerIncrementButton,
+                BuilderWorkerResetButton,
             ))
             .with_children(|btn| {
                 btn.spawn((
-                    Text::new("+"),
+                    Text::new("Reset"),
                     TextFont {
-                        font_size: 16.0,
+                        font_size: 12.0,
                         ..default()
                     },
-                    TextColor(TEXT_COLOR),
+                    theme.text(ThemeRole::Text),
                 ));
             });
         });
 }
 
-fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
+fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands, theme: &UiTheme) {
     parent
         .spawn((
             Node {
@@ -565,7 +709,7 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                 padding: UiRect::top(Val::Px(8.0)),
                 ..default()
             },
-            BorderColor::all(PANEL_BORDER),
+            theme.border(ThemeRole::PanelBorder),
         ))
         .with_children(|row| {
             row.spawn((
@@ -577,8 +721,8 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                     align_items: AlignItems::Center,
                     ..default()
                 },
-                BackgroundColor(CANCEL_BG),
-                ButtonStyle::cancel(),
+                theme.background(ThemeRole::Cancel),
+                ButtonStyle::cancel(theme),
                 Hovered::default(),
                 BuilderCancelButton,
             ))
@@ -589,7 +733,7 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                         font_size: 13.0,
                         ..default()
                     },
-                    TextColor(TEXT_COLOR),
+                    theme.text(ThemeRole::Text),
                 ));
             });
 
@@ -609,8 +753,8 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        BackgroundColor(BUTTON_BG),
-                        ButtonStyle::default_button(),
+                        theme.background(ThemeRole::Button),
+                        ButtonStyle::default_button(theme),
                         Hovered::default(),
                         BuilderBackButton,
                     ))
@@ -621,7 +765,7 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                                 font_size: 12.0,
                                 ..default()
                             },
-                            TextColor(TEXT_COLOR),
+                            theme.text(ThemeRole::Text),
                         ));
                     });
 
@@ -635,8 +779,8 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        BackgroundColor(CONFIRM_BG),
-                        ButtonStyle::confirm(),
+                        theme.background(ThemeRole::Confirm),
+                        ButtonStyle::confirm(theme),
                         Hovered::default(),
                         BuilderSaveButton,
                     ))
@@ -647,14 +791,48 @@ fn spawn_modal_buttons(parent: &mut ChildSpawnerCommands) {
                                 font_size: 13.0,
                                 ..default()
                             },
-                            TextColor(TEXT_COLOR),
+                            theme.text(ThemeRole::Text),
                         ));
                     });
             });
         });
 }
 
-#[allow(clippy::too_many_arguments)]
+/// One-based, comma separated, as shown to the player.
+fn format_step_numbers(indices: &[usize]) -> String {
+    indices
+        .iter()
+        .map(|index| (index + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn show_validation_message(
+    commands: &mut Commands,
+    step_lists: &Query<(Entity, &Children), With<BuilderStepList>>,
+    validation_messages: &Query<Entity, With<BuilderValidationMessage>>,
+    message: &str,
+    theme: &UiTheme,
+) {
+    for entity in validation_messages {
+        commands.entity(entity).despawn();
+    }
+    for (list_entity, _) in step_lists {
+        commands.entity(list_entity).with_children(|list| {
+            list.spawn((
+                Text::new(message),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Danger),
+                BuilderValidationMessage,
+            ));
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 fn handle_builder_controls(
     mut state: ResMut<WorkflowCreationState>,
     save_buttons: Query<&Interaction, (Changed<Interaction>, With<BuilderSaveButton>)>,
@@ -662,28 +840,40 @@ fn handle_builder_controls(
     back_buttons: Query<&Interaction, (Changed<Interaction>, With<BuilderBackButton>)>,
     add_step_buttons: Query<&Interaction, (Changed<Interaction>, With<AddStepButton>)>,
     remove_buttons: Query<(&Interaction, &StepRemoveButton), Changed<Interaction>>,
-    increment_buttons: Query<
-        &Interaction,
-        (Changed<Interaction>, With<BuilderWorkerIncrementButton>),
-    >,
-    decrement_buttons: Query<
-        &Interaction,
-        (Changed<Interaction>, With<BuilderWorkerDecrementButton>),
-    >,
     mut commands: Commands,
     modals: Query<Entity, With<WorkflowBuilderModal>>,
     mut create_events: MessageWriter<CreateWorkflowEvent>,
     mut update_events: MessageWriter<UpdateWorkflowEvent>,
     mut next_mode: ResMut<NextState<crate::ui::UiMode>>,
-    names: Query<&Name>,
+    step_text: (Query<&Name>, Query<&DisplayLabel>, Res<ItemRegistry>),
     step_lists: Query<(Entity, &Children), With<BuilderStepList>>,
+    validation_messages: Query<Entity, With<BuilderValidationMessage>>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::BuilderModal {
         return;
     }
+    let (names, labels, item_registry) = step_text;
 
     for interaction in &save_buttons {
         if *interaction == Interaction::Pressed && !state.steps.is_empty() {
+            let invalid = steps_outside_pool(&state.steps, &state.building_set);
+            if !invalid.is_empty() {
+                let step_numbers = format_step_numbers(&invalid);
+                warn!(name = %state.name, steps = %step_numbers, "workflow save blocked");
+                show_validation_message(
+                    &mut commands,
+                    &step_lists,
+                    &validation_messages,
+                    &format!(
+                        "Step {step_numbers} targets a building outside the pool. \
+                         Pick a new target before saving."
+                    ),
+                    &theme,
+                );
+                return;
+            }
+
             if let Some(editing_entity) = state.editing {
                 update_events.write(UpdateWorkflowEvent {
                     entity: editing_entity,
@@ -698,6 +888,7 @@ fn handle_builder_controls(
                     name: state.name.clone(),
                     building_set: state.building_set.clone(),
                     steps: state.steps.clone(),
+                    is_dedicated: state.is_dedicated,
                     desired_worker_count: state.desired_worker_count,
                 });
                 info!(name = %state.name, steps = state.steps.len(), "workflow created");
@@ -736,8 +927,17 @@ fn handle_builder_controls(
             state.steps.push(WorkflowStep {
                 target: default_target,
                 action: WorkflowAction::Pickup(None),
+                condition: None,
             });
-            rebuild_modal_steps(&mut commands, &step_lists, &state, &names);
+            rebuild_modal_steps(
+                &mut commands,
+                &step_lists,
+                &state,
+                &names,
+                &labels,
+                &item_registry,
+                &theme,
+            );
             return;
         }
     }
@@ -750,11 +950,41 @@ fn handle_builder_controls(
         }
     }
     if step_removed {
-        rebuild_modal_steps(&mut commands, &step_lists, &state, &names);
+        rebuild_modal_steps(
+            &mut commands,
+            &step_lists,
+            &state,
+            &names,
+            &labels,
+            &item_registry,
+            &theme,
+        );
+    }
+}
+
+fn handle_worker_count_controls(
+    mut state: ResMut<WorkflowCreationState>,
+    config: Res<WorkflowConfig>,
+    increment_buttons: Query<
+        &Interaction,
+        (Changed<Interaction>, With<BuilderWorkerIncrementButton>),
+    >,
+    decrement_buttons: Query<
+        &Interaction,
+        (Changed<Interaction>, With<BuilderWorkerDecrementButton>),
+    >,
+    reset_buttons: Query<&Interaction, (Changed<Interaction>, With<BuilderWorkerResetButton>)>,
+) {
+    if state.phase != CreationPhase::BuilderModal {
+        return;
+    }
+
+    if reset_buttons.iter().any(|i| *i == Interaction::Pressed) {
+        state.desired_worker_count = config.min_workers;
         return;
     }
 
-    let mut worker_delta: i32 = 0;
+    let mut worker_delta: i64 = 0;
     for interaction in &increment_buttons {
         if *interaction == Interaction::Pressed {
             worker_delta += 1;
@@ -766,10 +996,8 @@ fn handle_builder_controls(
         }
     }
     if worker_delta != 0 {
-        #[allow(clippy::cast_sign_loss)]
-        let new_count =
-            (i64::from(state.desired_worker_count) + i64::from(worker_delta)).clamp(1, 10) as u32;
-        state.desired_worker_count = new_count;
+        state.desired_worker_count =
+            config.clamp_workers(i64::from(state.desired_worker_count) + worker_delta);
     }
 }
 
@@ -779,6 +1007,9 @@ fn handle_step_action_toggle(
     mut commands: Commands,
     step_lists: Query<(Entity, &Children), With<BuilderStepList>>,
     names: Query<&Name>,
+    labels: Query<&DisplayLabel>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::BuilderModal {
         return;
@@ -793,7 +1024,50 @@ fn handle_step_action_toggle(
                 WorkflowAction::Pickup(filter) => WorkflowAction::Dropoff(filter.clone()),
                 WorkflowAction::Dropoff(filter) => WorkflowAction::Pickup(filter.clone()),
             };
-            rebuild_modal_steps(&mut commands, &step_lists, &state, &names);
+            rebuild_modal_steps(
+                &mut commands,
+                &step_lists,
+                &state,
+                &names,
+                &labels,
+                &item_registry,
+                &theme,
+            );
+            return;
+        }
+    }
+}
+
+fn handle_step_condition_toggle(
+    mut state: ResMut<WorkflowCreationState>,
+    condition_buttons: Query<(&Interaction, &StepConditionButton), Changed<Interaction>>,
+    mut commands: Commands,
+    step_lists: Query<(Entity, &Children), With<BuilderStepList>>,
+    names: Query<&Name>,
+    labels: Query<&DisplayLabel>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
+) {
+    if state.phase != CreationPhase::BuilderModal {
+        return;
+    }
+
+    for (interaction, btn) in &condition_buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let condition = cycle_condition(&state.steps, btn.step_index, &item_registry);
+        if let Some(step) = state.steps.get_mut(btn.step_index) {
+            step.condition = condition;
+            rebuild_modal_steps(
+                &mut commands,
+                &step_lists,
+                &state,
+                &names,
+                &labels,
+                &item_registry,
+                &theme,
+            );
             return;
         }
     }
@@ -808,8 +1082,10 @@ fn handle_step_target_button(
     mut commands: Commands,
     existing_dropdowns: Query<Entity, With<TargetDropdown>>,
     names: Query<&Name>,
+    labels: Query<&DisplayLabel>,
     positions: Query<&Position>,
     modals: Query<Entity, With<WorkflowBuilderModal>>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::BuilderModal {
         return;
@@ -857,8 +1133,8 @@ fn handle_step_target_button(
                     overflow: Overflow::scroll_y(),
                     ..default()
                 },
-                BackgroundColor(POPUP_BG),
-                BorderColor::all(PANEL_BORDER),
+                theme.background(ThemeRole::PopupBg),
+                theme.border(ThemeRole::PanelBorder),
                 ScrollPosition::default(),
                 Scrollable,
                 TargetDropdown {
@@ -872,18 +1148,23 @@ fn handle_step_target_button(
                         &format!("any {type_name}"),
                         btn.step_index,
                         StepTarget::ByType(type_name.clone()),
+                        &theme,
                     );
 
                     for (entity, pos) in buildings {
+                        let name = labels
+                            .get(*entity)
+                            .map_or(type_name.as_str(), |l| l.0.as_str());
                         let label = match pos {
-                            Some((x, y)) => format!("{type_name} at ({x},{y})"),
-                            None => type_name.clone(),
+                            Some((x, y)) => format!("{name} at ({x},{y})"),
+                            None => name.to_string(),
                         };
                         spawn_dropdown_option(
                             dropdown,
                             &label,
                             btn.step_index,
                             StepTarget::Specific(*entity),
+                            &theme,
                         );
                     }
                 }
@@ -898,6 +1179,7 @@ fn spawn_dropdown_option(
     label: &str,
     step_index: usize,
     target: StepTarget,
+    theme: &UiTheme,
 ) {
     parent
         .spawn((
@@ -910,8 +1192,8 @@ fn spawn_dropdown_option(
                 padding: UiRect::horizontal(Val::Px(6.0)),
                 ..default()
             },
-            BackgroundColor(BUTTON_BG),
-            ButtonStyle::default_button(),
+            theme.background(ThemeRole::Button),
+            ButtonStyle::default_button(theme),
             Hovered::default(),
             TargetDropdownOption { step_index, target },
         ))
@@ -922,7 +1204,7 @@ fn spawn_dropdown_option(
                     font_size: 11.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
         });
 }
@@ -934,6 +1216,9 @@ fn handle_target_dropdown_selection(
     dropdowns: Query<Entity, With<TargetDropdown>>,
     step_lists: Query<(Entity, &Children), With<BuilderStepList>>,
     names: Query<&Name>,
+    labels: Query<&DisplayLabel>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::BuilderModal {
         return;
@@ -952,7 +1237,15 @@ fn handle_target_dropdown_selection(
             commands.entity(entity).despawn();
         }
 
-        rebuild_modal_steps(&mut commands, &step_lists, &state, &names);
+        rebuild_modal_steps(
+            &mut commands,
+            &step_lists,
+            &state,
+            &names,
+            &labels,
+            &item_registry,
+            &theme,
+        );
         return;
     }
 }
@@ -967,6 +1260,7 @@ fn handle_step_filter_button(
     existing_dropdowns: Query<Entity, Or<(With<FilterDropdown>, With<TargetDropdown>)>>,
     item_registry: Res<ItemRegistry>,
     modals: Query<Entity, With<WorkflowBuilderModal>>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::BuilderModal {
         return;
@@ -988,9 +1282,9 @@ fn handle_step_filter_button(
                 WorkflowAction::Pickup(filter) | WorkflowAction::Dropoff(filter) => filter.clone(),
             });
 
-        let selected_items: HashSet<String> = current_filter
+        let selected_items: HashSet<ItemId> = current_filter
             .as_ref()
-            .map(|f| f.keys().cloned().collect())
+            .map(|f| f.keys().copied().collect())
             .unwrap_or_default();
 
         let Ok(modal_entity) = modals.single() else {
@@ -1014,8 +1308,8 @@ fn handle_step_filter_button(
                     overflow: Overflow::scroll_y(),
                     ..default()
                 },
-                BackgroundColor(POPUP_BG),
-                BorderColor::all(PANEL_BORDER),
+                theme.background(ThemeRole::PopupBg),
+                theme.border(ThemeRole::PanelBorder),
                 ScrollPosition::default(),
                 Scrollable,
                 FilterDropdown {
@@ -1027,7 +1321,9 @@ fn handle_step_filter_button(
                     dropdown,
                     btn.step_index,
                     &selected_items,
+                    &state.collapsed_filter_groups,
                     &item_registry,
+                    &theme,
                 );
             })
             .id();
@@ -1035,11 +1331,30 @@ fn handle_step_filter_button(
     }
 }
 
+/// Registered items grouped by rarity, each group sorted by name.
+fn filter_groups(item_registry: &ItemRegistry) -> Vec<(ItemRarity, Vec<ItemId>)> {
+    let mut groups: BTreeMap<ItemRarity, Vec<ItemId>> = BTreeMap::new();
+    for (&item, definition) in &item_registry.definitions {
+        groups.entry(definition.rarity()).or_default().push(item);
+    }
+    for items in groups.values_mut() {
+        items.sort_by_key(|&item| item_registry.display_name(item));
+    }
+    groups.into_iter().collect()
+}
+
+fn filter_group_label(group: ItemRarity, count: usize, collapsed: bool) -> String {
+    let marker = if collapsed { "[+]" } else { "[-]" };
+    format!("{marker} {} ({count})", group.label())
+}
+
 fn spawn_filter_dropdown_items(
     dropdown: &mut ChildSpawnerCommands,
     step_index: usize,
-    selected_items: &HashSet<String>,
+    selected_items: &HashSet<ItemId>,
+    collapsed: &HashSet<ItemRarity>,
     item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
     dropdown.spawn((
         Text::new("Item Filter (empty = all):"),
@@ -1047,53 +1362,158 @@ fn spawn_filter_dropdown_items(
             font_size: 10.0,
             ..default()
         },
-        TextColor(DIM_TEXT),
+        theme.text(ThemeRole::DimText),
         Node {
             margin: UiRect::bottom(Val::Px(2.0)),
             ..default()
         },
     ));
 
-    let mut item_names: Vec<_> = item_registry.definitions.keys().cloned().collect();
-    item_names.sort();
-
-    for item_name in item_names {
-        let is_selected = selected_items.contains(&item_name);
-        let label = if is_selected {
-            format!("[x] {item_name}")
-        } else {
-            format!("[ ] {item_name}")
-        };
-
+    for (group, items) in filter_groups(item_registry) {
+        let is_collapsed = collapsed.contains(&group);
         dropdown
             .spawn((
                 Button,
                 Node {
                     width: Val::Percent(100.0),
-                    height: Val::Px(22.0),
-                    justify_content: JustifyContent::FlexStart,
+                    height: Val::Px(20.0),
                     align_items: AlignItems::Center,
-                    padding: UiRect::horizontal(Val::Px(6.0)),
+                    padding: UiRect::horizontal(Val::Px(4.0)),
                     ..default()
                 },
-                BackgroundColor(if is_selected { SELECTED_BG } else { BUTTON_BG }),
-                ButtonStyle::default_button(),
+                theme.background(ThemeRole::CardBg),
+                ButtonStyle::default_button(theme),
                 Hovered::default(),
-                FilterCheckbox {
-                    step_index,
-                    item_name: item_name.clone(),
-                },
+                FilterGroupHeader { group },
             ))
-            .with_children(|checkbox_btn| {
-                checkbox_btn.spawn((
-                    Text::new(label),
+            .with_children(|header| {
+                header.spawn((
+                    Text::new(filter_group_label(group, items.len(), is_collapsed)),
                     TextFont {
                         font_size: 11.0,
                         ..default()
                     },
-                    TextColor(TEXT_COLOR),
+                    TextColor(group.color()),
                 ));
             });
+
+        dropdown
+            .spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.0),
+                    display: if is_collapsed {
+                        Display::None
+                    } else {
+                        Display::Flex
+                    },
+                    ..default()
+                },
+                FilterGroupRows { group },
+            ))
+            .with_children(|rows| {
+                for item in items {
+                    spawn_filter_checkbox(
+                        rows,
+                        step_index,
+                        item,
+                        item_registry.display_name(item),
+                        selected_items,
+                        theme,
+                    );
+                }
+            });
+    }
+}
+
+fn spawn_filter_checkbox(
+    rows: &mut ChildSpawnerCommands,
+    step_index: usize,
+    item: ItemId,
+    name: &str,
+    selected_items: &HashSet<ItemId>,
+    theme: &UiTheme,
+) {
+    let is_selected = selected_items.contains(&item);
+    let label = if is_selected {
+        format!("[x] {name}")
+    } else {
+        format!("[ ] {name}")
+    };
+
+    rows.spawn((
+        Button,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Px(22.0),
+            justify_content: JustifyContent::FlexStart,
+            align_items: AlignItems::Center,
+            padding: UiRect::horizontal(Val::Px(6.0)),
+            ..default()
+        },
+        theme.background(if is_selected {
+            ThemeRole::Selected
+        } else {
+            ThemeRole::Button
+        }),
+        ButtonStyle::default_button(theme),
+        Hovered::default(),
+        FilterCheckbox { step_index, item },
+    ))
+    .with_children(|checkbox_btn| {
+        checkbox_btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 11.0,
+                ..default()
+            },
+            theme.text(ThemeRole::Text),
+        ));
+    });
+}
+
+/// Folds or unfolds a group in place. Checked items live in the step filter,
+/// so hidden rows keep their state.
+fn handle_filter_group_toggle(
+    mut state: ResMut<WorkflowCreationState>,
+    headers: Query<(&Interaction, &FilterGroupHeader, &Children), Changed<Interaction>>,
+    mut rows: Query<(&FilterGroupRows, &mut Node, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if state.phase != CreationPhase::BuilderModal {
+        return;
+    }
+
+    for (interaction, header, header_children) in &headers {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let collapsed = if state.collapsed_filter_groups.remove(&header.group) {
+            false
+        } else {
+            state.collapsed_filter_groups.insert(header.group);
+            true
+        };
+
+        let mut count = 0;
+        for (group_rows, mut node, children) in &mut rows {
+            if group_rows.group != header.group {
+                continue;
+            }
+            node.display = if collapsed {
+                Display::None
+            } else {
+                Display::Flex
+            };
+            count = children.len();
+        }
+        for &child in header_children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                **text = filter_group_label(header.group, count, collapsed);
+            }
+        }
     }
 }
 
@@ -1104,6 +1524,9 @@ fn handle_filter_checkbox_toggle(
     filter_dropdowns: Query<Entity, With<FilterDropdown>>,
     step_lists: Query<(Entity, &Children), With<BuilderStepList>>,
     names: Query<&Name>,
+    labels: Query<&DisplayLabel>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::BuilderModal {
         return;
@@ -1120,10 +1543,8 @@ fn handle_filter_checkbox_toggle(
             };
 
             let mut items = filter.take().unwrap_or_default();
-            if items.contains_key(&checkbox.item_name) {
-                items.remove(&checkbox.item_name);
-            } else {
-                items.insert(checkbox.item_name.clone(), u32::MAX);
+            if items.remove(&checkbox.item).is_none() {
+                items.insert(checkbox.item, u32::MAX);
             }
 
             if items.is_empty() {
@@ -1136,7 +1557,15 @@ fn handle_filter_checkbox_toggle(
         for entity in &filter_dropdowns {
             commands.entity(entity).despawn();
         }
-        rebuild_modal_steps(&mut commands, &step_lists, &state, &names);
+        rebuild_modal_steps(
+            &mut commands,
+            &step_lists,
+            &state,
+            &names,
+            &labels,
+            &item_registry,
+            &theme,
+        );
         return;
     }
 }
@@ -1160,6 +1589,9 @@ fn rebuild_modal_steps(
     step_lists: &Query<(Entity, &Children), With<BuilderStepList>>,
     state: &WorkflowCreationState,
     names: &Query<&Name>,
+    labels: &Query<&DisplayLabel>,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
     for (list_entity, children) in step_lists {
         for &child in children {
@@ -1174,13 +1606,22 @@ fn rebuild_modal_steps(
                         font_size: 11.0,
                         ..default()
                     },
-                    TextColor(DIM_TEXT),
+                    theme.text(ThemeRole::DimText),
                 ));
                 return;
             }
 
             for (i, step) in state.steps.iter().enumerate() {
-                spawn_step_row(parent, i, step, &state.building_set, names);
+                spawn_step_row(
+                    parent,
+                    i,
+                    step,
+                    &state.building_set,
+                    names,
+                    labels,
+                    item_registry,
+                    theme,
+                );
             }
         });
     }
@@ -1205,6 +1646,7 @@ fn close_dropdowns_on_outside_click(
             Changed<Interaction>,
             Without<TargetDropdownOption>,
             Without<FilterCheckbox>,
+            Without<FilterGroupHeader>,
             Without<StepTargetButton>,
             Without<StepFilterButton>,
             Without<WorkflowBuilderModal>,
@@ -1244,11 +1686,14 @@ impl Plugin for WorkflowBuilderPlugin {
                 (
                     (
                         handle_builder_controls,
+                        handle_worker_count_controls,
                         handle_step_action_toggle,
+                        handle_step_condition_toggle,
                         handle_step_target_button,
                         handle_target_dropdown_selection,
                         handle_step_filter_button,
                         handle_filter_checkbox_toggle,
+                        handle_filter_group_toggle,
                     ),
                     close_dropdowns_on_outside_click,
                 )
@@ -1262,3 +1707,248 @@ impl Plugin for WorkflowBuilderPlugin {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn target_dropdown_prefers_display_label() {
+        let mut app = App::new();
+        app.init_resource::<WorkflowCreationState>()
+            .init_resource::<UiTheme>()
+            .add_systems(Update, handle_step_target_button);
+
+        let world = app.world_mut();
+        let labelled = world
+            .spawn((
+                Name::new("Smelter"),
+                Position { x: 2, y: 3 },
+                DisplayLabel("Main Iron Smelter".to_string()),
+            ))
+            .id();
+        let plain = world
+            .spawn((Name::new("Smelter"), Position { x: 5, y: 3 }))
+            .id();
+        world.spawn(WorkflowBuilderModal);
+        world.spawn((
+            Interaction::Pressed,
+            StepTargetButton { step_index: 0 },
+            UiGlobalTransform::default(),
+        ));
+        {
+            let mut state = world.resource_mut::<WorkflowCreationState>();
+            state.phase = CreationPhase::BuilderModal;
+            state.building_set = HashSet::from([labelled, plain]);
+        }
+
+        app.update();
+
+        let mut options = app
+            .world_mut()
+            .query::<(&TargetDropdownOption, &Children)>();
+        let mut texts = app.world_mut().query::<&Text>();
+        let world = app.world();
+        let mut option_label = |target: Entity| {
+            options
+                .iter(world)
+                .find(|(option, _)| option.target == StepTarget::Specific(target))
+                .and_then(|(_, children)| {
+                    children
+                        .iter()
+                        .find_map(|child| texts.get(world, child).ok())
+                })
+                .map(|text| text.0.clone())
+                .unwrap()
+        };
+
+        assert_eq!(option_label(labelled), "Main Iron Smelter at (2,3)");
+        assert_eq!(option_label(plain), "Smelter at (5,3)");
+    }
+
+    #[test]
+    fn save_is_blocked_when_a_specific_target_left_the_pool() {
+        let mut app = App::new();
+        app.add_plugins(bevy::state::app::StatesPlugin)
+            .init_state::<crate::ui::UiMode>()
+            .init_resource::<WorkflowCreationState>()
+            .init_resource::<UiTheme>()
+            .insert_resource(ItemRegistry::from_ron("[]").unwrap())
+            .add_message::<CreateWorkflowEvent>()
+            .add_message::<UpdateWorkflowEvent>()
+            .add_systems(Update, handle_builder_controls);
+
+        let world = app.world_mut();
+        let kept = world.spawn(Name::new("Storage")).id();
+        let removed = world.spawn(Name::new("Smelter")).id();
+        world.spawn(BuilderStepList).with_children(|list| {
+            list.spawn(Text::new("step rows"));
+        });
+        world.spawn((BuilderSaveButton, Interaction::Pressed));
+        {
+            let mut state = world.resource_mut::<WorkflowCreationState>();
+            state.phase = CreationPhase::BuilderModal;
+            state.building_set = HashSet::from([kept]);
+            state.steps = vec![
+                WorkflowStep {
+                    target: StepTarget::Specific(kept),
+                    action: WorkflowAction::Pickup(None),
+                    condition: None,
+                },
+                WorkflowStep {
+                    target: StepTarget::Specific(removed),
+                    action: WorkflowAction::Dropoff(None),
+                    condition: None,
+                },
+            ];
+        }
+
+        app.update();
+
+        let world = app.world_mut();
+        assert!(world.resource::<Messages<CreateWorkflowEvent>>().is_empty());
+        let mut messages = world.query_filtered::<&Text, With<BuilderValidationMessage>>();
+        let message = messages.single(world).unwrap();
+        assert!(message.0.starts_with("Step 2 "));
+    }
+
+    #[test]
+    fn raised_worker_ceiling_lets_the_stepper_pass_ten() {
+        let mut app = App::new();
+        app.init_resource::<WorkflowCreationState>()
+            .init_resource::<UiTheme>()
+            .insert_resource(WorkflowConfig {
+                max_workers: 20,
+                ..default()
+            })
+            .add_systems(Update, handle_worker_count_controls);
+
+        let increment = app
+            .world_mut()
+            .spawn((BuilderWorkerIncrementButton, Interaction::None))
+            .id();
+        {
+            let mut state = app.world_mut().resource_mut::<WorkflowCreationState>();
+            state.phase = CreationPhase::BuilderModal;
+            state.desired_worker_count = 10;
+        }
+
+        for _ in 0..12 {
+            *app.world_mut().get_mut::<Interaction>(increment).unwrap() = Interaction::Pressed;
+            app.update();
+            *app.world_mut().get_mut::<Interaction>(increment).unwrap() = Interaction::None;
+        }
+        assert_eq!(
+            app.world()
+                .resource::<WorkflowCreationState>()
+                .desired_worker_count,
+            20
+        );
+
+        app.world_mut()
+            .spawn((BuilderWorkerResetButton, Interaction::Pressed));
+        app.update();
+        assert_eq!(
+            app.world()
+                .resource::<WorkflowCreationState>()
+                .desired_worker_count,
+            1
+        );
+    }
+
+    #[test]
+    fn filter_items_are_grouped_and_collapsing_keeps_selection() {
+        let mut app = App::new();
+        app.init_resource::<WorkflowCreationState>()
+            .init_resource::<UiTheme>()
+            .insert_resource(
+                ItemRegistry::from_ron(
+                    r#"[(name: "Iron Ore", tier: 0), (name: "Copper Ore", tier: 0), (name: "Iron Plate", tier: 1)]"#,
+                )
+                .unwrap(),
+            )
+            .add_systems(
+                Update,
+                (handle_step_filter_button, handle_filter_group_toggle).chain(),
+            );
+
+        let copper = ItemId::named("Copper Ore").unwrap();
+        let world = app.world_mut();
+        world.spawn(WorkflowBuilderModal);
+        world.spawn((
+            Interaction::Pressed,
+            StepFilterButton { step_index: 0 },
+            UiGlobalTransform::default(),
+        ));
+        {
+            let mut state = world.resource_mut::<WorkflowCreationState>();
+            state.phase = CreationPhase::BuilderModal;
+            state.steps = vec![WorkflowStep {
+                target: StepTarget::ByType("Storage".to_string()),
+                action: WorkflowAction::Pickup(Some(HashMap::from([(copper, u32::MAX)]))),
+                condition: None,
+            }];
+        }
+        app.update();
+
+        let group_items = |app: &mut App, group: ItemRarity| {
+            let mut rows = app.world_mut().query::<(&FilterGroupRows, &Children)>();
+            let mut checkboxes = app.world_mut().query::<&FilterCheckbox>();
+            let world = app.world();
+            let items = world.resource::<ItemRegistry>();
+            let (_, children) = rows
+                .iter(world)
+                .find(|(rows, _)| rows.group == group)
+                .unwrap();
+            children
+                .iter()
+                .filter_map(|child| checkboxes.get(world, child).ok())
+                .map(|checkbox| items.display_name(checkbox.item).to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            group_items(&mut app, ItemRarity::Common),
+            vec!["Copper Ore", "Iron Ore"]
+        );
+        assert_eq!(
+            group_items(&mut app, ItemRarity::Uncommon),
+            vec!["Iron Plate"]
+        );
+
+        let mut headers = app.world_mut().query::<(Entity, &FilterGroupHeader)>();
+        let common_header = headers
+            .iter(app.world())
+            .find(|(_, header)| header.group == ItemRarity::Common)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.world_mut()
+            .entity_mut(common_header)
+            .insert(Interaction::Pressed);
+        app.update();
+
+        let mut rows = app.world_mut().query::<(&FilterGroupRows, &Node)>();
+        for (group_rows, node) in rows.iter(app.world()) {
+            let expected = if group_rows.group == ItemRarity::Common {
+                Display::None
+            } else {
+                Display::Flex
+            };
+            assert_eq!(node.display, expected);
+        }
+        assert_eq!(
+            group_items(&mut app, ItemRarity::Common),
+            vec!["Copper Ore", "Iron Ore"]
+        );
+        let mut texts = app.world_mut().query::<&Text>();
+        let labels: Vec<String> = texts.iter(app.world()).map(|text| text.0.clone()).collect();
+        assert!(labels.contains(&"[+] Common (2)".to_string()));
+        assert!(labels.contains(&"[x] Copper Ore".to_string()));
+        let state = app.world().resource::<WorkflowCreationState>();
+        assert!(state.collapsed_filter_groups.contains(&ItemRarity::Common));
+        assert_eq!(
+            state.steps[0].action,
+            WorkflowAction::Pickup(Some(HashMap::from([(copper, u32::MAX)])))
+        );
+    }
+}
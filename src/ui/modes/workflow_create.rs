@@ -4,15 +4,19 @@ use bevy::picking::hover::Hovered;
 use bevy::prelude::*;
 
 use crate::{
+    materials::ItemRarity,
+    structures::Building,
     ui::{
+        hover::{world_per_px, CursorWorldPosition, InteractionConfig},
         popups::building_menu::BuildingClickEvent,
-        style::{
-            ButtonStyle, CANCEL_BG, CONFIRM_BG, DIM_TEXT, HEADER_COLOR, PANEL_BG, PANEL_BORDER,
-            TEXT_COLOR,
-        },
+        style::{ButtonStyle, ThemeRole, UiTheme},
+        zones::Zones,
         UISystemSet,
     },
-    workers::workflows::components::WorkflowStep,
+    workers::workflows::{
+        components::{WorkflowConfig, WorkflowStep},
+        templates::{TemplateCondition, WorkflowTemplate},
+    },
 };
 
 #[derive(Default, Clone, PartialEq, Eq)]
@@ -27,9 +31,54 @@ pub struct WorkflowCreationState {
     pub name: String,
     pub building_set: HashSet<Entity>,
     pub steps: Vec<WorkflowStep>,
+    /// Conditions from an applied template, by step index, waiting for the
+    /// pool they get bound against.
+    pub template_conditions: HashMap<usize, TemplateCondition>,
+    /// Carried from an applied template into the workflow it creates.
+    pub is_dedicated: bool,
     pub desired_worker_count: u32,
     pub phase: CreationPhase,
     pub editing: Option<Entity>,
+    /// Item groups folded away in the step filter dropdown.
+    pub collapsed_filter_groups: HashSet<ItemRarity>,
+}
+
+impl WorkflowCreationState {
+    /// Resets the state to a fresh workflow preloaded with the template's steps.
+    /// Buildings still have to be selected since templates only carry types;
+    /// step conditions are bound once they are.
+    /// The worker count is clamped like the builder's stepper, since templates
+    /// on disk can be edited by hand.
+    pub fn apply_template(&mut self, template: &WorkflowTemplate, config: &WorkflowConfig) {
+        self.name.clone_from(&template.name);
+        self.steps = template.to_steps();
+        self.template_conditions = template
+            .steps
+            .iter()
+            .enumerate()
+            .filter_map(|(index, step)| Some((index, step.condition.clone()?)))
+            .collect();
+        self.is_dedicated = template.is_dedicated;
+        self.desired_worker_count = config.clamp_workers(i64::from(template.desired_worker_count));
+        self.building_set.clear();
+        self.phase = CreationPhase::SelectBuildings;
+        self.editing = None;
+    }
+
+    /// Binds the applied template's conditions against the chosen pool.
+    pub fn bind_template_conditions(&mut self, type_of: impl Fn(Entity) -> Option<String>) {
+        for (index, condition) in std::mem::take(&mut self.template_conditions) {
+            if let Some(step) = self.steps.get_mut(index) {
+                step.condition = condition.bind(&self.building_set, &type_of);
+            }
+        }
+    }
+}
+
+/// World-space anchor of an in-progress drag box.
+#[derive(Resource, Default)]
+pub struct DragSelection {
+    pub start: Option<Vec2>,
 }
 
 #[derive(Resource, Default)]
@@ -49,6 +98,16 @@ pub struct BuildingPoolList;
 #[derive(Component)]
 pub struct BuildWorkflowButton;
 
+/// Row that holds one "add to pool" button per zone.
+#[derive(Component)]
+pub struct ZonePoolButtons;
+
+/// Adds every building inside the zone at `zone` in `Zones` to the pool.
+#[derive(Component)]
+pub struct AddZoneToPoolButton {
+    pub zone: usize,
+}
+
 fn toggle_workflow_creation_mode(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut state: ResMut<WorkflowCreationState>,
@@ -56,6 +115,7 @@ fn toggle_workflow_creation_mode(
     mut commands: Commands,
     existing_panels: Query<Entity, With<WorkflowCreationPanel>>,
     mut next_mode: ResMut<NextState<crate::ui::UiMode>>,
+    theme: Res<UiTheme>,
 ) {
     if !keyboard.just_pressed(KeyCode::KeyN) {
         return;
@@ -64,6 +124,8 @@ fn toggle_workflow_creation_mode(
     counter.count += 1;
     state.name = format!("Workflow {}", counter.count);
     state.steps.clear();
+    state.template_conditions.clear();
+    state.is_dedicated = false;
     state.desired_worker_count = 1;
     state.building_set.clear();
     state.phase = CreationPhase::SelectBuildings;
@@ -72,11 +134,15 @@ fn toggle_workflow_creation_mode(
         commands.entity(entity).despawn();
     }
 
-    spawn_creation_panel(&mut commands, &state);
+    spawn_creation_panel(&mut commands, &state, &theme);
     next_mode.set(crate::ui::UiMode::WorkflowCreate);
 }
 
-pub(crate) fn spawn_creation_panel(commands: &mut Commands, state: &WorkflowCreationState) {
+pub(crate) fn spawn_creation_panel(
+    commands: &mut Commands,
+    state: &WorkflowCreationState,
+    theme: &UiTheme,
+) {
     commands
         .spawn((
             Node {
@@ -93,8 +159,8 @@ pub(crate) fn spawn_creation_panel(commands: &mut Commands, state: &WorkflowCrea
                 row_gap: Val::Px(6.0),
                 ..default()
             },
-            BackgroundColor(PANEL_BG),
-            BorderColor::all(PANEL_BORDER),
+            theme.background(ThemeRole::PanelBg),
+            theme.border(ThemeRole::PanelBorder),
             WorkflowCreationPanel,
         ))
         .with_children(|parent| {
@@ -104,7 +170,7 @@ pub(crate) fn spawn_creation_panel(commands: &mut Commands, state: &WorkflowCrea
                     font_size: 15.0,
                     ..default()
                 },
-                TextColor(HEADER_COLOR),
+                theme.text(ThemeRole::Header),
                 Node {
                     margin: UiRect::bottom(Val::Px(4.0)),
                     ..default()
@@ -112,12 +178,26 @@ pub(crate) fn spawn_creation_panel(commands: &mut Commands, state: &WorkflowCrea
             ));
 
             parent.spawn((
-                Text::new("Click buildings on the grid to add/remove them from the pool."),
+                Text::new(
+                    "Click buildings to add/remove them. Drag to add an area, shift-drag to remove.",
+                ),
                 TextFont {
                     font_size: 11.0,
                     ..default()
                 },
-                TextColor(DIM_TEXT),
+                theme.text(ThemeRole::DimText),
+            ));
+
+            parent.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(4.0),
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ZonePoolButtons,
             ));
 
             parent
@@ -137,15 +217,15 @@ pub(crate) fn spawn_creation_panel(commands: &mut Commands, state: &WorkflowCrea
                             font_size: 12.0,
                             ..default()
                         },
-                        TextColor(DIM_TEXT),
+                        theme.text(ThemeRole::DimText),
                     ));
                 });
 
-            spawn_phase1_buttons(parent);
+            spawn_phase1_buttons(parent, theme);
         });
 }
 
-fn spawn_phase1_buttons(parent: &mut ChildSpawnerCommands) {
+fn spawn_phase1_buttons(parent: &mut ChildSpawnerCommands, theme: &UiTheme) {
     parent
         .spawn(Node {
             width: Val::Percent(100.0),
@@ -168,9 +248,9 @@ fn spawn_phase1_buttons(parent: &mut ChildSpawnerCommands) {
                     border: UiRect::all(Val::Px(1.0)),
                     ..default()
                 },
-                BackgroundColor(CANCEL_BG),
-                BorderColor::all(Color::srgb(0.5, 0.3, 0.3)),
-                ButtonStyle::cancel(),
+                theme.background(ThemeRole::Cancel),
+                theme.border(ThemeRole::CancelBorder),
+                ButtonStyle::cancel(theme),
                 Hovered::default(),
                 WorkflowCancelButton,
             ))
@@ -181,7 +261,7 @@ fn spawn_phase1_buttons(parent: &mut ChildSpawnerCommands) {
                         font_size: 13.0,
                         ..default()
                     },
-                    TextColor(TEXT_COLOR),
+                    theme.text(ThemeRole::Text),
                 ));
             });
 
@@ -195,9 +275,9 @@ fn spawn_phase1_buttons(parent: &mut ChildSpawnerCommands) {
                     border: UiRect::all(Val::Px(1.0)),
                     ..default()
                 },
-                BackgroundColor(CONFIRM_BG),
-                BorderColor::all(Color::srgb(0.3, 0.5, 0.3)),
-                ButtonStyle::confirm(),
+                theme.background(ThemeRole::Confirm),
+                theme.border(ThemeRole::ConfirmBorder),
+                ButtonStyle::confirm(theme),
                 Hovered::default(),
                 BuildWorkflowButton,
             ))
@@ -208,7 +288,7 @@ fn spawn_phase1_buttons(parent: &mut ChildSpawnerCommands) {
                         font_size: 13.0,
                         ..default()
                     },
-                    TextColor(TEXT_COLOR),
+                    theme.text(ThemeRole::Text),
                 ));
             });
         });
@@ -220,6 +300,7 @@ fn handle_building_pool_clicks(
     mut commands: Commands,
     pool_lists: Query<(Entity, &Children), With<BuildingPoolList>>,
     names: Query<&Name>,
+    theme: Res<UiTheme>,
 ) {
     if state.phase != CreationPhase::SelectBuildings {
         return;
@@ -233,15 +314,192 @@ fn handle_building_pool_clicks(
             state.building_set.insert(entity);
         }
 
-        rebuild_building_pool_list(&mut commands, &pool_lists, &state.building_set, &names);
+        rebuild_building_pool_list(
+            &mut commands,
+            &pool_lists,
+            &state.building_set,
+            &names,
+            &theme,
+        );
     }
 }
 
+fn buildings_in_rect<'a>(
+    rect: Rect,
+    buildings: impl IntoIterator<Item = (Entity, &'a Transform)>,
+) -> Vec<Entity> {
+    buildings
+        .into_iter()
+        .filter(|(_, transform)| rect.contains(transform.translation.truncate()))
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+fn handle_drag_selection(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    cursor: Res<CursorWorldPosition>,
+    mut drag: ResMut<DragSelection>,
+    interaction: Res<InteractionConfig>,
+    projections: Query<&Projection, With<Camera2d>>,
+    mut state: ResMut<WorkflowCreationState>,
+    buildings: Query<(Entity, &Transform), With<Building>>,
+    ui_interactions: Query<&Interaction, With<Button>>,
+    mut commands: Commands,
+    pool_lists: Query<(Entity, &Children), With<BuildingPoolList>>,
+    names: Query<&Name>,
+    theme: Res<UiTheme>,
+) {
+    if state.phase != CreationPhase::SelectBuildings {
+        drag.start = None;
+        return;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        let over_ui = ui_interactions
+            .iter()
+            .any(|i| matches!(i, Interaction::Pressed | Interaction::Hovered));
+        drag.start = if over_ui { None } else { cursor.0 };
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(start), Some(end)) = (drag.start.take(), cursor.0) else {
+        return;
+    };
+    // Short movements are clicks, which the click handler already took.
+    if !interaction.is_drag(start, end, world_per_px(&projections)) {
+        return;
+    }
+
+    let removing = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let enclosed = buildings_in_rect(Rect::from_corners(start, end), buildings);
+    if enclosed.is_empty() {
+        return;
+    }
+
+    for entity in enclosed {
+        if removing {
+            state.building_set.remove(&entity);
+        } else {
+            state.building_set.insert(entity);
+        }
+    }
+
+    rebuild_building_pool_list(
+        &mut commands,
+        &pool_lists,
+        &state.building_set,
+        &names,
+        &theme,
+    );
+}
+
+/// Fills new zone rows, and refills all of them when the zones change.
+fn sync_zone_pool_buttons(
+    mut commands: Commands,
+    zones: Res<Zones>,
+    rows: Query<(Entity, Option<&Children>, Ref<ZonePoolButtons>)>,
+    theme: Res<UiTheme>,
+) {
+    for (row, children, marker) in &rows {
+        if !zones.is_changed() && !marker.is_added() {
+            continue;
+        }
+        for &child in children.into_iter().flatten() {
+            commands.entity(child).despawn();
+        }
+        commands.entity(row).with_children(|row| {
+            for (index, zone) in zones.zones.iter().enumerate() {
+                row.spawn((
+                    Button,
+                    Node {
+                        height: Val::Px(24.0),
+                        padding: UiRect::horizontal(Val::Px(8.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    theme.background(ThemeRole::Button),
+                    theme.border(ThemeRole::PanelBorder),
+                    ButtonStyle::building_button(&theme),
+                    Hovered::default(),
+                    AddZoneToPoolButton { zone: index },
+                ))
+                .with_child((
+                    Text::new(format!("+ {}", zone.name)),
+                    TextFont {
+                        font_size: 11.0,
+                        ..default()
+                    },
+                    theme.text(ThemeRole::Header),
+                ));
+            }
+        });
+    }
+}
+
+fn handle_add_zone_to_pool(
+    buttons: Query<(&AddZoneToPoolButton, &Interaction), Changed<Interaction>>,
+    zones: Res<Zones>,
+    mut state: ResMut<WorkflowCreationState>,
+    buildings: Query<(Entity, &Transform), With<Building>>,
+    mut commands: Commands,
+    pool_lists: Query<(Entity, &Children), With<BuildingPoolList>>,
+    names: Query<&Name>,
+    theme: Res<UiTheme>,
+) {
+    if state.phase != CreationPhase::SelectBuildings {
+        return;
+    }
+
+    for (button, interaction) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(zone) = zones.zones.get(button.zone) else {
+            continue;
+        };
+
+        state
+            .building_set
+            .extend(buildings_in_rect(zone.rect, buildings));
+        rebuild_building_pool_list(
+            &mut commands,
+            &pool_lists,
+            &state.building_set,
+            &names,
+            &theme,
+        );
+    }
+}
+
+fn draw_drag_selection(
+    drag: Res<DragSelection>,
+    cursor: Res<CursorWorldPosition>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    let (Some(start), Some(end)) = (drag.start, cursor.0) else {
+        return;
+    };
+    let rect = Rect::from_corners(start, end);
+    gizmos.rect_2d(
+        Isometry2d::from_translation(rect.center()),
+        rect.size(),
+        theme.selected_border,
+    );
+}
+
 fn rebuild_building_pool_list(
     commands: &mut Commands,
     pool_lists: &Query<(Entity, &Children), With<BuildingPoolList>>,
     building_set: &HashSet<Entity>,
     names: &Query<&Name>,
+    theme: &UiTheme,
 ) {
     for (list_entity, children) in pool_lists {
         for &child in children {
@@ -256,7 +514,7 @@ fn rebuild_building_pool_list(
                         font_size: 12.0,
                         ..default()
                     },
-                    TextColor(DIM_TEXT),
+                    theme.text(ThemeRole::DimText),
                 ));
                 return;
             }
@@ -290,7 +548,7 @@ fn rebuild_building_pool_list(
                     font_size: 12.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
         });
     }
@@ -302,6 +560,7 @@ fn handle_phase1_controls(
     build_buttons: Query<&Interaction, (Changed<Interaction>, With<BuildWorkflowButton>)>,
     mut commands: Commands,
     panels: Query<Entity, With<WorkflowCreationPanel>>,
+    names: Query<&Name>,
     mut next_mode: ResMut<NextState<crate::ui::UiMode>>,
 ) {
     if state.phase != CreationPhase::SelectBuildings {
@@ -317,6 +576,9 @@ fn handle_phase1_controls(
 
     for interaction in &build_buttons {
         if *interaction == Interaction::Pressed && state.building_set.len() >= 2 {
+            state.bind_template_conditions(|entity| {
+                names.get(entity).ok().map(|n| n.as_str().to_string())
+            });
             state.phase = CreationPhase::BuilderModal;
             for entity in &panels {
                 commands.entity(entity).despawn();
@@ -330,6 +592,7 @@ fn respawn_panel_on_phase_back(
     state: Res<WorkflowCreationState>,
     mut commands: Commands,
     existing_panels: Query<Entity, With<WorkflowCreationPanel>>,
+    theme: Res<UiTheme>,
 ) {
     if !state.is_changed() {
         return;
@@ -340,7 +603,7 @@ fn respawn_panel_on_phase_back(
     if !existing_panels.is_empty() {
         return;
     }
-    spawn_creation_panel(&mut commands, &state);
+    spawn_creation_panel(&mut commands, &state, &theme);
 }
 
 pub struct WorkflowCreationPlugin;
@@ -349,6 +612,7 @@ impl Plugin for WorkflowCreationPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<WorkflowCreationState>()
             .init_resource::<WorkflowCreationCounter>()
+            .init_resource::<DragSelection>()
             .add_systems(
                 Update,
                 (
@@ -358,11 +622,247 @@ impl Plugin for WorkflowCreationPlugin {
                     (
                         handle_phase1_controls,
                         handle_building_pool_clicks,
+                        handle_drag_selection,
+                        handle_add_zone_to_pool,
+                        sync_zone_pool_buttons,
                         respawn_panel_on_phase_back,
                     )
                         .in_set(UISystemSet::EntityManagement)
                         .run_if(in_state(crate::ui::UiMode::WorkflowCreate)),
+                    draw_drag_selection
+                        .in_set(UISystemSet::VisualUpdates)
+                        .run_if(in_state(crate::ui::UiMode::WorkflowCreate)),
                 ),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::materials::ItemId;
+    use crate::workers::workflows::{
+        components::{StepCondition, StepTarget, Workflow, WorkflowAction},
+        templates::{TemplateAction, TemplateStep, WorkflowTemplateLibrary},
+    };
+
+    #[test]
+    fn test_template_instantiates_identical_steps() {
+        let mut filter = HashMap::new();
+        filter.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        let steps = vec![
+            WorkflowStep {
+                target: StepTarget::ByType("Mining Drill".to_string()),
+                action: WorkflowAction::Pickup(Some(filter)),
+                condition: None,
+            },
+            WorkflowStep {
+                target: StepTarget::ByType("Smelter".to_string()),
+                action: WorkflowAction::Dropoff(None),
+                condition: None,
+            },
+        ];
+        let workflow = Workflow {
+            name: "Ore Run".to_string(),
+            building_set: HashSet::new(),
+            steps: steps.clone(),
+            is_paused: false,
+            is_dedicated: true,
+            desired_worker_count: 2,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        };
+
+        let mut library = WorkflowTemplateLibrary::default();
+        library.upsert(WorkflowTemplate::from_workflow(&workflow, |_| None));
+        let saved = WorkflowTemplateLibrary::from_ron(&library.to_ron().unwrap()).unwrap();
+
+        let mut state = WorkflowCreationState::default();
+        state.apply_template(saved.get("Ore Run").unwrap(), &WorkflowConfig::default());
+
+        assert_eq!(state.steps, steps);
+        assert_eq!(state.name, "Ore Run");
+        assert_eq!(state.desired_worker_count, 2);
+        assert!(state.is_dedicated);
+        assert!(state.phase == CreationPhase::SelectBuildings);
+        assert!(state.editing.is_none());
+    }
+
+    #[test]
+    fn template_conditions_are_bound_once_the_pool_is_chosen() {
+        let iron = ItemId::named("Iron Plate").unwrap();
+        let template = WorkflowTemplate {
+            name: "Feed".to_string(),
+            steps: vec![TemplateStep {
+                building_type: "Storage".to_string(),
+                action: TemplateAction::Pickup(None),
+                condition: Some(TemplateCondition::ItemBelow {
+                    building_type: "Assembler".to_string(),
+                    item: iron,
+                    threshold: 10,
+                }),
+            }],
+            is_dedicated: false,
+            desired_worker_count: 1,
+        };
+        let mut state = WorkflowCreationState::default();
+        state.apply_template(&template, &WorkflowConfig::default());
+        assert_eq!(state.steps[0].condition, None);
+
+        let mut world = World::new();
+        let assembler = world.spawn(Name::new("Assembler")).id();
+        let storage = world.spawn(Name::new("Storage")).id();
+        state.building_set = HashSet::from([assembler, storage]);
+        state.bind_template_conditions(|entity| {
+            world
+                .get::<Name>(entity)
+                .map(|name| name.as_str().to_string())
+        });
+
+        assert_eq!(
+            state.steps[0].condition,
+            Some(StepCondition::ItemBelow {
+                building: assembler,
+                item: iron,
+                threshold: 10,
+            })
+        );
+        assert!(state.template_conditions.is_empty());
+    }
+
+    #[test]
+    fn template_worker_count_is_clamped_to_the_configured_range() {
+        let config = WorkflowConfig {
+            min_workers: 1,
+            max_workers: 10,
+        };
+        let mut template = WorkflowTemplate {
+            name: "Edited".to_string(),
+            steps: Vec::new(),
+            is_dedicated: false,
+            desired_worker_count: 50,
+        };
+        let mut state = WorkflowCreationState::default();
+
+        state.apply_template(&template, &config);
+        assert_eq!(state.desired_worker_count, 10);
+
+        template.desired_worker_count = 0;
+        state.apply_template(&template, &config);
+        assert_eq!(state.desired_worker_count, 1);
+    }
+
+    fn drag_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<CursorWorldPosition>()
+            .init_resource::<DragSelection>()
+            .insert_resource(InteractionConfig {
+                drag_threshold_px: 5.0,
+            })
+            .init_resource::<WorkflowCreationState>()
+            .init_resource::<UiTheme>()
+            .add_systems(Update, handle_drag_selection);
+        app
+    }
+
+    fn drag(app: &mut App, from: Vec2, to: Vec2) {
+        app.world_mut().resource_mut::<CursorWorldPosition>().0 = Some(from);
+        app.world_mut()
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        app.update();
+
+        app.world_mut().resource_mut::<CursorWorldPosition>().0 = Some(to);
+        {
+            let mut mouse = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+            mouse.clear();
+            mouse.release(MouseButton::Left);
+        }
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .clear();
+    }
+
+    #[test]
+    fn drag_box_adds_enclosed_buildings_and_shift_drag_removes() {
+        let mut app = drag_app();
+        let inside: Vec<Entity> = [(0.0, 0.0), (64.0, 0.0), (64.0, 64.0)]
+            .into_iter()
+            .map(|(x, y)| {
+                app.world_mut()
+                    .spawn((Building, Transform::from_xyz(x, y, 0.0)))
+                    .id()
+            })
+            .collect();
+        let outside = app
+            .world_mut()
+            .spawn((Building, Transform::from_xyz(256.0, 0.0, 0.0)))
+            .id();
+
+        drag(&mut app, Vec2::new(-32.0, -32.0), Vec2::new(96.0, 96.0));
+
+        let state = app.world().resource::<WorkflowCreationState>();
+        assert_eq!(state.building_set.len(), 3);
+        assert!(inside.iter().all(|e| state.building_set.contains(e)));
+        assert!(!state.building_set.contains(&outside));
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::ShiftLeft);
+        drag(&mut app, Vec2::new(32.0, -32.0), Vec2::new(96.0, 32.0));
+
+        let state = app.world().resource::<WorkflowCreationState>();
+        assert_eq!(state.building_set.len(), 2);
+        assert!(!state.building_set.contains(&inside[1]));
+    }
+
+    #[test]
+    fn movement_under_the_drag_threshold_is_a_click() {
+        let mut app = drag_app();
+        let building = app
+            .world_mut()
+            .spawn((Building, Transform::from_xyz(1.0, 0.0, 0.0)))
+            .id();
+
+        drag(&mut app, Vec2::ZERO, Vec2::new(2.0, 0.0));
+        let state = app.world().resource::<WorkflowCreationState>();
+        assert!(state.building_set.is_empty());
+
+        drag(&mut app, Vec2::new(-4.0, -3.0), Vec2::new(4.0, 3.0));
+        let state = app.world().resource::<WorkflowCreationState>();
+        assert!(state.building_set.contains(&building));
+    }
+
+    #[test]
+    fn add_zone_to_pool_inserts_every_building_in_the_zone() {
+        let mut app = App::new();
+        app.init_resource::<Zones>()
+            .init_resource::<WorkflowCreationState>()
+            .init_resource::<UiTheme>()
+            .add_systems(Update, handle_add_zone_to_pool);
+
+        let [first, second, outside] = [(0.0, 0.0), (64.0, 0.0), (256.0, 0.0)].map(|(x, y)| {
+            app.world_mut()
+                .spawn((Building, Transform::from_xyz(x, y, 0.0)))
+                .id()
+        });
+        app.world_mut()
+            .resource_mut::<Zones>()
+            .add(Rect::from_corners(
+                Vec2::new(-32.0, -32.0),
+                Vec2::new(96.0, 32.0),
+            ));
+        app.world_mut()
+            .spawn((AddZoneToPoolButton { zone: 0 }, Interaction::Pressed));
+
+        app.update();
+
+        let state = app.world().resource::<WorkflowCreationState>();
+        assert_eq!(state.building_set, HashSet::from([first, second]));
+        assert!(!state.building_set.contains(&outside));
+    }
+}
@@ -0,0 +1,752 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::GameCamera,
+    grid::{Grid, Position},
+    materials::ItemRegistry,
+    structures::{
+        crafter_kickstart::SupplyPlan, Building, CreativeMode, FinishAllConstructionEvent,
+    },
+    systems::{ComputeGrid, NetworkConnectivity, PowerGrid, WorkerDebugLabels},
+    ui::{
+        panels::workflow_debug::WorkflowDebugPanel,
+        popups::building_menu::BuildingMenu,
+        style::{ThemeRole, UiTheme, TOP_BAR_HEIGHT},
+        UISystemSet,
+    },
+};
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMode {
+    #[default]
+    None,
+    Power,
+    Compute,
+    Network,
+}
+
+impl OverlayMode {
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            Self::None => Self::Power,
+            Self::Power => Self::Compute,
+            Self::Compute => Self::Network,
+            Self::Network => Self::None,
+        }
+    }
+
+    fn tint(self, theme: &UiTheme) -> Option<Color> {
+        match self {
+            Self::None => None,
+            Self::Power => Some(theme.power_color),
+            Self::Compute => Some(theme.compute_color),
+            Self::Network => Some(theme.selected_border),
+        }
+    }
+}
+
+/// Power and compute are pooled across the whole network, so a cell is
+/// covered when it is network-connected and the pool has unused capacity.
+pub fn is_cell_covered(
+    mode: OverlayMode,
+    x: i32,
+    y: i32,
+    network: &NetworkConnectivity,
+    power: &PowerGrid,
+    compute: &ComputeGrid,
+) -> bool {
+    let connected = network.is_adjacent_to_connected_network(x, y);
+    match mode {
+        OverlayMode::None => false,
+        OverlayMode::Power => connected && power.capacity > 0 && power.available >= 0,
+        OverlayMode::Compute => connected && compute.capacity > 0 && compute.available >= 0,
+        OverlayMode::Network => connected,
+    }
+}
+
+pub fn covered_cells(
+    mode: OverlayMode,
+    grid: &Grid,
+    network: &NetworkConnectivity,
+    power: &PowerGrid,
+    compute: &ComputeGrid,
+) -> Vec<(i32, i32)> {
+    grid.valid_coordinates
+        .iter()
+        .copied()
+        .filter(|&(x, y)| is_cell_covered(mode, x, y, network, power, compute))
+        .collect()
+}
+
+pub fn cycle_overlay_mode(keyboard: Res<ButtonInput<KeyCode>>, mut mode: ResMut<OverlayMode>) {
+    if keyboard.just_pressed(KeyCode::KeyO) {
+        *mode = mode.next();
+    }
+}
+
+pub fn toggle_worker_debug_labels(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut labels: ResMut<WorkerDebugLabels>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        labels.enabled = !labels.enabled;
+    }
+}
+
+/// Seconds a first F7 press stays armed, waiting for the confirming press.
+pub const FINISH_ALL_CONFIRM_SECS: f32 = 2.0;
+
+/// When F7 was last pressed without acting, so a stray press cannot finish
+/// every construction site on its own.
+#[derive(Resource, Debug, Default)]
+pub struct FinishAllConfirm {
+    pub armed_at: Option<f32>,
+}
+
+/// In creative mode, F7 twice within `FINISH_ALL_CONFIRM_SECS` finishes
+/// every construction site. F8 toggles creative mode itself, and only in
+/// debug builds, so a release player cannot stumble into either.
+pub fn creative_hotkeys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut confirm: ResMut<FinishAllConfirm>,
+    mut creative: ResMut<CreativeMode>,
+    mut finish_events: MessageWriter<FinishAllConstructionEvent>,
+) {
+    if creative.enabled && keyboard.just_pressed(KeyCode::F7) {
+        let now = time.elapsed_secs();
+        match confirm.armed_at.take() {
+            Some(armed_at) if now - armed_at <= FINISH_ALL_CONFIRM_SECS => {
+                finish_events.write(FinishAllConstructionEvent);
+            }
+            _ => {
+                confirm.armed_at = Some(now);
+                warn!("press F7 again to finish all construction");
+            }
+        }
+    }
+    if cfg!(debug_assertions) && keyboard.just_pressed(KeyCode::F8) {
+        creative.enabled = !creative.enabled;
+        info!(enabled = creative.enabled, "creative mode toggled");
+    }
+}
+
+pub fn draw_coverage_overlay(
+    mode: Res<OverlayMode>,
+    grid: Res<Grid>,
+    network: Res<NetworkConnectivity>,
+    power: Res<PowerGrid>,
+    compute: Res<ComputeGrid>,
+    buildings: Query<&Position, With<Building>>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    let Some(tint) = mode.tint(&theme) else {
+        return;
+    };
+
+    let cell_size = Vec2::splat(grid.cell_size * 0.9);
+    for (x, y) in covered_cells(*mode, &grid, &network, &power, &compute) {
+        let center = grid.grid_to_world_coordinates(x, y);
+        gizmos.rect_2d(
+            Isometry2d::from_translation(center),
+            cell_size,
+            tint.with_alpha(0.35),
+        );
+    }
+
+    for pos in &buildings {
+        if is_cell_covered(*mode, pos.x, pos.y, &network, &power, &compute) {
+            continue;
+        }
+        let center = grid.grid_to_world_coordinates(pos.x, pos.y);
+        gizmos.rect_2d(
+            Isometry2d::from_translation(center),
+            Vec2::splat(grid.cell_size),
+            theme.danger_color,
+        );
+    }
+}
+
+/// Whether cell boundaries are drawn over the visible part of the map.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GridLinesConfig {
+    pub enabled: bool,
+}
+
+pub fn toggle_grid_lines(keyboard: Res<ButtonInput<KeyCode>>, mut config: ResMut<GridLinesConfig>) {
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        config.enabled = !config.enabled;
+    }
+}
+
+/// Set of debug overlays, one bit per overlay. Mirrors the individual toggles
+/// so the master toggle and the on-screen list can treat them as a group.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DebugOverlays(u8);
+
+impl DebugOverlays {
+    pub const NONE: Self = Self(0);
+    pub const WORKER_LABELS: Self = Self(1);
+    pub const GRID_LINES: Self = Self(1 << 1);
+    pub const COVERAGE: Self = Self(1 << 2);
+    pub const WORKFLOW_PANEL: Self = Self(1 << 3);
+    pub const ALL: Self = Self(0b1111);
+
+    const NAMED: [(Self, &'static str); 4] = [
+        (Self::WORKER_LABELS, "Worker labels"),
+        (Self::GRID_LINES, "Grid lines"),
+        (Self::COVERAGE, "Coverage"),
+        (Self::WORKFLOW_PANEL, "Workflow panel"),
+    ];
+
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[must_use]
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn set(&mut self, other: Self, on: bool) {
+        if on {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+
+    /// Display names of the overlays in the set, in a fixed order.
+    #[must_use]
+    pub fn names(self) -> Vec<&'static str> {
+        Self::NAMED
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl std::ops::BitOr for DebugOverlays {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// The overlays the master debug toggle switches together.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct DebugOverlayGroup {
+    pub overlays: DebugOverlays,
+}
+
+impl Default for DebugOverlayGroup {
+    fn default() -> Self {
+        Self {
+            overlays: DebugOverlays::ALL,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DebugOverlayList;
+
+fn active_debug_overlays(
+    labels: &WorkerDebugLabels,
+    grid_lines: GridLinesConfig,
+    mode: OverlayMode,
+    panel: &WorkflowDebugPanel,
+) -> DebugOverlays {
+    let mut active = DebugOverlays::NONE;
+    active.set(DebugOverlays::WORKER_LABELS, labels.enabled);
+    active.set(DebugOverlays::GRID_LINES, grid_lines.enabled);
+    active.set(DebugOverlays::COVERAGE, mode != OverlayMode::None);
+    active.set(DebugOverlays::WORKFLOW_PANEL, panel.enabled);
+    active
+}
+
+/// F2 turns the whole configured group off if any of it is showing, and on
+/// otherwise. Coverage comes back in its first mode.
+pub fn toggle_debug_overlays(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    group: Res<DebugOverlayGroup>,
+    mut active: ResMut<DebugOverlays>,
+    mut labels: ResMut<WorkerDebugLabels>,
+    mut grid_lines: ResMut<GridLinesConfig>,
+    mut mode: ResMut<OverlayMode>,
+    mut panel: ResMut<WorkflowDebugPanel>,
+) {
+    if !keyboard.just_pressed(KeyCode::F2) {
+        return;
+    }
+
+    let current = active_debug_overlays(&labels, *grid_lines, *mode, &panel);
+    let enable = !current.intersects(group.overlays);
+    let in_group = |flag| group.overlays.contains(flag);
+    if in_group(DebugOverlays::WORKER_LABELS) {
+        labels.enabled = enable;
+    }
+    if in_group(DebugOverlays::GRID_LINES) {
+        grid_lines.enabled = enable;
+    }
+    if in_group(DebugOverlays::COVERAGE) {
+        *mode = if enable {
+            OverlayMode::None.next()
+        } else {
+            OverlayMode::None
+        };
+    }
+    if in_group(DebugOverlays::WORKFLOW_PANEL) {
+        panel.enabled = enable;
+    }
+    active.set_if_neq(active_debug_overlays(&labels, *grid_lines, *mode, &panel));
+}
+
+/// Picks up overlays switched by their own hotkeys.
+pub fn sync_debug_overlays(
+    mut active: ResMut<DebugOverlays>,
+    labels: Res<WorkerDebugLabels>,
+    grid_lines: Res<GridLinesConfig>,
+    mode: Res<OverlayMode>,
+    panel: Res<WorkflowDebugPanel>,
+) {
+    active.set_if_neq(active_debug_overlays(&labels, *grid_lines, *mode, &panel));
+}
+
+/// Small corner list naming the debug overlays currently showing.
+pub fn update_debug_overlay_list(
+    mut commands: Commands,
+    active: Res<DebugOverlays>,
+    lists: Query<Entity, With<DebugOverlayList>>,
+    theme: Res<UiTheme>,
+) {
+    if !active.is_changed() {
+        return;
+    }
+    for list in &lists {
+        commands.entity(list).despawn();
+    }
+    let names = active.names();
+    if names.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(4.0),
+            top: Val::Px(TOP_BAR_HEIGHT + 4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(theme.panel_bg.with_alpha(0.7)),
+        Text::new(format!("Debug: {}", names.join(", "))),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        theme.text(ThemeRole::DimText),
+        ZIndex(50),
+        DebugOverlayList,
+    ));
+}
+
+/// Inclusive range of cells touched by a viewport of `viewport_size` pixels
+/// centered on `camera_center`, where `zoom` is world units per pixel.
+#[must_use]
+pub fn visible_cell_range(
+    grid: &Grid,
+    camera_center: Vec2,
+    viewport_size: Vec2,
+    zoom: f32,
+) -> (IVec2, IVec2) {
+    let half_extent = viewport_size * zoom / 2.0;
+    let to_cell = |world: Vec2| (world / grid.cell_size).round().as_ivec2();
+    (
+        to_cell(camera_center - half_extent),
+        to_cell(camera_center + half_extent),
+    )
+}
+
+pub fn draw_grid_lines(
+    config: Res<GridLinesConfig>,
+    grid: Res<Grid>,
+    windows: Query<&Window>,
+    camera: Query<(&GlobalTransform, &Projection), With<GameCamera>>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    if !config.enabled {
+        return;
+    }
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Ok((transform, Projection::Orthographic(projection))) = camera.single() else {
+        return;
+    };
+
+    let (min, max) = visible_cell_range(
+        &grid,
+        transform.translation().truncate(),
+        window.size(),
+        projection.scale,
+    );
+    // Cells are centered on their coordinates, so boundaries sit half a cell out.
+    #[allow(clippy::cast_precision_loss)]
+    let edge = |cell: i32| (cell as f32 - 0.5) * grid.cell_size;
+    let color = theme.dim_text.with_alpha(0.3);
+    for x in min.x..=max.x + 1 {
+        gizmos.line_2d(
+            Vec2::new(edge(x), edge(min.y)),
+            Vec2::new(edge(x), edge(max.y + 1)),
+            color,
+        );
+    }
+    for y in min.y..=max.y + 1 {
+        gizmos.line_2d(
+            Vec2::new(edge(min.x), edge(y)),
+            Vec2::new(edge(max.x + 1), edge(y)),
+            color,
+        );
+    }
+}
+
+/// Label for one supply link, naming the items sent along it.
+#[derive(Component, Debug, PartialEq, Eq)]
+pub struct SupplyLinkLabel {
+    pub requester: Entity,
+    pub source: Entity,
+    pub text: String,
+}
+
+/// Each source in the plan with the items it was asked for, sorted so the
+/// labels come out stable.
+fn supply_links(plan: &SupplyPlan, item_registry: &ItemRegistry) -> Vec<(Entity, String)> {
+    let mut links: Vec<(Entity, String)> = plan
+        .sources
+        .iter()
+        .map(|(&source, items)| {
+            let mut names: Vec<&str> = items
+                .keys()
+                .map(|&item| item_registry.display_name(item))
+                .collect();
+            names.sort_unstable();
+            (source, names.join(", "))
+        })
+        .collect();
+    links.sort_unstable();
+    links
+}
+
+/// Lines from every crafter with an open building menu to the storages its
+/// inputs were last requested from.
+pub fn draw_supply_links(
+    menus: Query<&BuildingMenu>,
+    plans: Query<&SupplyPlan>,
+    transforms: Query<&Transform>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    for menu in &menus {
+        let Ok(plan) = plans.get(menu.target_building) else {
+            continue;
+        };
+        let Ok(requester) = transforms.get(menu.target_building) else {
+            continue;
+        };
+        for &source in plan.sources.keys() {
+            let Ok(source) = transforms.get(source) else {
+                continue;
+            };
+            gizmos.line_2d(
+                source.translation.truncate(),
+                requester.translation.truncate(),
+                theme.worker_color,
+            );
+        }
+    }
+}
+
+/// Rebuilds the link labels only when the set of shown links changes.
+pub fn update_supply_link_labels(
+    mut commands: Commands,
+    menus: Query<&BuildingMenu>,
+    plans: Query<&SupplyPlan>,
+    transforms: Query<&Transform>,
+    labels: Query<(Entity, &SupplyLinkLabel)>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
+) {
+    let mut desired = Vec::new();
+    for menu in &menus {
+        let Ok(plan) = plans.get(menu.target_building) else {
+            continue;
+        };
+        let Ok(requester) = transforms.get(menu.target_building) else {
+            continue;
+        };
+        for (source, text) in supply_links(plan, &item_registry) {
+            let Ok(source_transform) = transforms.get(source) else {
+                continue;
+            };
+            let midpoint =
+                (source_transform.translation.truncate() + requester.translation.truncate()) / 2.0;
+            desired.push((
+                SupplyLinkLabel {
+                    requester: menu.target_building,
+                    source,
+                    text,
+                },
+                midpoint,
+            ));
+        }
+    }
+
+    let unchanged = labels.iter().count() == desired.len()
+        && labels
+            .iter()
+            .all(|(_, label)| desired.iter().any(|(wanted, _)| wanted == label));
+    if unchanged {
+        return;
+    }
+
+    for (entity, _) in &labels {
+        commands.entity(entity).despawn();
+    }
+    for (label, midpoint) in desired {
+        commands.spawn((
+            Text2d::new(label.text.clone()),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            theme.text(ThemeRole::Worker),
+            Transform::from_translation(midpoint.extend(5.0)),
+            label,
+        ));
+    }
+}
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OverlayMode>()
+            .init_resource::<GridLinesConfig>()
+            .init_resource::<DebugOverlays>()
+            .init_resource::<DebugOverlayGroup>()
+            .init_resource::<FinishAllConfirm>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        cycle_overlay_mode,
+                        toggle_worker_debug_labels,
+                        toggle_grid_lines,
+                        toggle_debug_overlays,
+                        creative_hotkeys,
+                    )
+                        .in_set(UISystemSet::InputDetection),
+                    (sync_debug_overlays, update_debug_overlay_list)
+                        .chain()
+                        .in_set(UISystemSet::VisualUpdates),
+                    (
+                        draw_coverage_overlay,
+                        draw_grid_lines,
+                        draw_supply_links,
+                        update_supply_link_labels,
+                    )
+                        .in_set(UISystemSet::VisualUpdates),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn connected_network(cells: &[(i32, i32)]) -> NetworkConnectivity {
+        let mut network = NetworkConnectivity::default();
+        for &(x, y) in cells {
+            network.add_connected_cell(x, y);
+        }
+        network
+    }
+
+    fn press_master_toggle(app: &mut App) {
+        {
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.release(KeyCode::F2);
+            keyboard.clear();
+            keyboard.press(KeyCode::F2);
+        }
+        app.update();
+    }
+
+    #[test]
+    fn master_toggle_flips_the_configured_overlays_and_back() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<DebugOverlays>()
+            .init_resource::<WorkerDebugLabels>()
+            .init_resource::<GridLinesConfig>()
+            .init_resource::<OverlayMode>()
+            .init_resource::<WorkflowDebugPanel>()
+            .insert_resource(DebugOverlayGroup {
+                overlays: DebugOverlays::WORKER_LABELS | DebugOverlays::GRID_LINES,
+            })
+            .add_systems(Update, toggle_debug_overlays);
+
+        press_master_toggle(&mut app);
+        let active = *app.world().resource::<DebugOverlays>();
+        assert!(active.contains(DebugOverlays::WORKER_LABELS | DebugOverlays::GRID_LINES));
+        assert!(!active.intersects(DebugOverlays::COVERAGE | DebugOverlays::WORKFLOW_PANEL));
+        assert!(app.world().resource::<WorkerDebugLabels>().enabled);
+        assert!(app.world().resource::<GridLinesConfig>().enabled);
+        assert!(!app.world().resource::<WorkflowDebugPanel>().enabled);
+
+        press_master_toggle(&mut app);
+        assert_eq!(
+            *app.world().resource::<DebugOverlays>(),
+            DebugOverlays::NONE
+        );
+        assert!(!app.world().resource::<WorkerDebugLabels>().enabled);
+        assert!(!app.world().resource::<GridLinesConfig>().enabled);
+    }
+
+    #[test]
+    fn finish_all_needs_creative_mode_and_a_second_f7_within_the_window() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Time>()
+            .init_resource::<FinishAllConfirm>()
+            .insert_resource(CreativeMode { enabled: false })
+            .add_message::<FinishAllConstructionEvent>()
+            .add_systems(Update, creative_hotkeys);
+        let press_f7 = |app: &mut App, secs: f32| {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(secs));
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.release(KeyCode::F7);
+            keyboard.clear();
+            keyboard.press(KeyCode::F7);
+            app.update();
+            app.world()
+                .resource::<Messages<FinishAllConstructionEvent>>()
+                .iter_current_update_messages()
+                .count()
+        };
+
+        assert_eq!(press_f7(&mut app, 1.0), 0, "outside creative mode");
+        assert_eq!(press_f7(&mut app, 0.5), 0, "outside creative mode");
+        assert!(app
+            .world()
+            .resource::<FinishAllConfirm>()
+            .armed_at
+            .is_none());
+
+        app.world_mut().resource_mut::<CreativeMode>().enabled = true;
+        assert_eq!(press_f7(&mut app, 1.0), 0, "a single press only arms");
+        assert_eq!(press_f7(&mut app, 3.0), 0, "the window has lapsed");
+        assert_eq!(press_f7(&mut app, 1.5), 1, "second press confirms");
+        assert_eq!(press_f7(&mut app, 0.5), 0, "confirming disarms");
+    }
+
+    #[test]
+    fn visible_cell_range_follows_camera_position_and_zoom() {
+        let grid = Grid::new(64.0);
+
+        // 640x320 px at zoom 1 spans 640x320 world units around (128, 64).
+        let (min, max) =
+            visible_cell_range(&grid, Vec2::new(128.0, 64.0), Vec2::new(640.0, 320.0), 1.0);
+        assert_eq!(min, IVec2::new(-3, -2));
+        assert_eq!(max, IVec2::new(7, 4));
+
+        // Zooming out doubles the world extent on both axes.
+        let (min, max) = visible_cell_range(&grid, Vec2::ZERO, Vec2::new(640.0, 320.0), 2.0);
+        assert_eq!(min, IVec2::new(-10, -5));
+        assert_eq!(max, IVec2::new(10, 5));
+    }
+
+    #[test]
+    fn overlay_mode_cycles_through_all_modes() {
+        let mode = OverlayMode::None;
+        assert_eq!(mode.next(), OverlayMode::Power);
+        assert_eq!(mode.next().next(), OverlayMode::Compute);
+        assert_eq!(mode.next().next().next(), OverlayMode::Network);
+        assert_eq!(mode.next().next().next().next(), OverlayMode::None);
+    }
+
+    #[test]
+    fn power_overlay_marks_powered_region_only() {
+        let mut grid = Grid::new(64.0);
+        for x in -2..=2 {
+            grid.add_coordinate(x, 0);
+        }
+        for x in 20..=22 {
+            grid.add_coordinate(x, 0);
+        }
+
+        let network = connected_network(&[(-1, 0), (0, 0), (1, 0)]);
+        let power = PowerGrid {
+            capacity: 100,
+            usage: 40,
+            available: 60,
+        };
+        let compute = ComputeGrid::default();
+
+        let covered = covered_cells(OverlayMode::Power, &grid, &network, &power, &compute);
+
+        for x in -2..=2 {
+            assert!(covered.contains(&(x, 0)), "cell ({x}, 0) should be powered");
+        }
+        for x in 20..=22 {
+            assert!(
+                !covered.contains(&(x, 0)),
+                "cell ({x}, 0) should be untinted"
+            );
+        }
+    }
+
+    #[test]
+    fn power_overlay_empty_when_overdrawn() {
+        let mut grid = Grid::new(64.0);
+        grid.add_coordinate(0, 0);
+        let network = connected_network(&[(1, 0)]);
+        let power = PowerGrid {
+            capacity: 100,
+            usage: 150,
+            available: -50,
+        };
+
+        let covered = covered_cells(
+            OverlayMode::Power,
+            &grid,
+            &network,
+            &power,
+            &ComputeGrid::default(),
+        );
+        assert!(covered.is_empty());
+    }
+
+    #[test]
+    fn no_overlay_covers_nothing() {
+        let network = connected_network(&[(1, 0)]);
+        assert!(!is_cell_covered(
+            OverlayMode::None,
+            0,
+            0,
+            &network,
+            &PowerGrid::default(),
+            &ComputeGrid::default(),
+        ));
+    }
+}
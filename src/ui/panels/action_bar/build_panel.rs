@@ -4,15 +4,13 @@ use bevy::ui::Checked;
 use std::collections::HashSet;
 
 use crate::{
-    structures::{BuildingCategory, BuildingRegistry},
+    materials::{ItemId, ItemRegistry},
+    structures::{BuildingCategory, BuildingRegistry, Facing},
     ui::{
         icons::IconAtlas,
         popups::tooltip::TooltipTarget,
         scroll::Scrollable,
-        style::{
-            ButtonStyle, ACTION_BAR_WIDTH, BUTTON_BG, CANCEL_BG, DIM_TEXT, HEADER_COLOR, PANEL_BG,
-            PANEL_BORDER, TEXT_COLOR, TOP_BAR_HEIGHT,
-        },
+        style::{ButtonStyle, ThemeRole, UiTheme, ACTION_BAR_WIDTH, TOP_BAR_HEIGHT},
         UISystemSet,
     },
 };
@@ -20,6 +18,7 @@ use crate::{
 #[derive(Resource, Default)]
 pub struct SelectedBuilding {
     pub building_name: Option<String>,
+    pub facing: Facing,
 }
 
 #[derive(Component)]
@@ -55,7 +54,8 @@ impl BuildingButton {
 pub fn spawn_build_panel(
     commands: &mut Commands,
     registry: &BuildingRegistry,
-    _icon_atlas: &IconAtlas,
+    _icon_atlas: Option<&IconAtlas>,
+    theme: &UiTheme,
 ) {
     let panel = commands
         .spawn((
@@ -70,8 +70,8 @@ pub fn spawn_build_panel(
                 border: UiRect::all(Val::Px(2.0)),
                 ..default()
             },
-            BackgroundColor(PANEL_BG),
-            BorderColor::all(PANEL_BORDER),
+            theme.background(ThemeRole::PanelBg),
+            theme.border(ThemeRole::PanelBorder),
             Interaction::None,
             BuildPanel,
         ))
@@ -96,7 +96,7 @@ pub fn spawn_build_panel(
                 font_size: 16.0,
                 ..default()
             },
-            TextColor(HEADER_COLOR),
+            theme.text(ThemeRole::Header),
         ))
         .id();
 
@@ -110,8 +110,8 @@ pub fn spawn_build_panel(
                 align_items: AlignItems::Center,
                 ..default()
             },
-            BackgroundColor(CANCEL_BG),
-            ButtonStyle::close(),
+            theme.background(ThemeRole::Cancel),
+            ButtonStyle::close(theme),
             Hovered::default(),
             BuildPanelCloseButton,
         ))
@@ -122,14 +122,14 @@ pub fn spawn_build_panel(
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
         })
         .id();
 
     commands.entity(header).add_children(&[title, close_btn]);
 
-    let tab_container = spawn_build_tabs(commands, registry);
+    let tab_container = spawn_build_tabs(commands, registry, theme);
 
     let content = commands
         .spawn((
@@ -155,7 +155,11 @@ pub fn despawn_build_panel(commands: &mut Commands, entity: Entity) {
     commands.entity(entity).despawn();
 }
 
-fn spawn_build_tabs(commands: &mut Commands, registry: &BuildingRegistry) -> Entity {
+fn spawn_build_tabs(
+    commands: &mut Commands,
+    registry: &BuildingRegistry,
+    theme: &UiTheme,
+) -> Entity {
     let available = get_available_building_categories(registry);
 
     let container = commands
@@ -186,9 +190,9 @@ fn spawn_build_tabs(commands: &mut Commands, registry: &BuildingRegistry) -> Ent
                 column_gap: Val::Px(4.0),
                 ..default()
             },
-            BackgroundColor(BUTTON_BG),
-            BorderColor::all(PANEL_BORDER),
-            ButtonStyle::tab(),
+            theme.background(ThemeRole::Button),
+            theme.border(ThemeRole::PanelBorder),
+            ButtonStyle::tab(theme),
             Hovered::default(),
             BuildPanelTab {
                 building_type,
@@ -217,7 +221,7 @@ fn spawn_build_tabs(commands: &mut Commands, registry: &BuildingRegistry) -> Ent
                         font_size: 11.0,
                         ..default()
                     },
-                    TextColor(TEXT_COLOR),
+                    theme.text(ThemeRole::Text),
                 ));
             })
             .id();
@@ -305,6 +309,8 @@ pub fn update_building_buttons_on_tab_change(
     content_query: Query<Entity, With<BuildPanelContent>>,
     existing_buttons: Query<Entity, With<BuildingButton>>,
     registry: Res<BuildingRegistry>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
 ) {
     if tab_query.is_empty() {
         return;
@@ -322,7 +328,13 @@ pub fn update_building_buttons_on_tab_change(
 
         if let Some(building_category) = active_tab_type {
             commands.entity(content_entity).with_children(|parent| {
-                spawn_building_buttons_for_category(parent, building_category, &registry);
+                spawn_building_buttons_for_category(
+                    parent,
+                    building_category,
+                    &registry,
+                    &item_registry,
+                    &theme,
+                );
             });
         }
     }
@@ -355,6 +367,8 @@ fn spawn_building_buttons_for_category(
     parent: &mut ChildSpawnerCommands,
     building_category: BuildingCategory,
     registry: &BuildingRegistry,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
     let buildings = registry.get_buildings_by_category(building_category);
 
@@ -376,9 +390,9 @@ fn spawn_building_buttons_for_category(
                         border: UiRect::all(Val::Px(2.0)),
                         ..default()
                     },
-                    BackgroundColor(BUTTON_BG),
-                    BorderColor::all(PANEL_BORDER),
-                    ButtonStyle::building_button(),
+                    theme.background(ThemeRole::Button),
+                    theme.border(ThemeRole::PanelBorder),
+                    ButtonStyle::building_button(theme),
                     Hovered::default(),
                     button,
                     TooltipTarget,
@@ -422,14 +436,15 @@ fn spawn_building_buttons_for_category(
                         ..default()
                     })
                     .with_children(|cost_col| {
-                        let cost_text = format_cost_display(&definition.placement.cost.inputs);
+                        let cost_text =
+                            format_cost_display(&definition.placement.cost.inputs, item_registry);
                         cost_col.spawn((
                             Text::new(cost_text),
                             TextFont {
                                 font_size: 9.0,
                                 ..default()
                             },
-                            TextColor(DIM_TEXT),
+                            theme.text(ThemeRole::DimText),
                         ));
                     });
                 });
@@ -437,13 +452,19 @@ fn spawn_building_buttons_for_category(
     }
 }
 
-fn format_cost_display(inputs: &std::collections::HashMap<String, u32>) -> String {
+fn format_cost_display(
+    inputs: &std::collections::HashMap<ItemId, u32>,
+    item_registry: &ItemRegistry,
+) -> String {
     if inputs.is_empty() {
         return "Free".to_string();
     }
 
-    let mut sorted_inputs: Vec<_> = inputs.iter().collect();
-    sorted_inputs.sort_by_key(|(name, _)| name.as_str());
+    let mut sorted_inputs: Vec<_> = inputs
+        .iter()
+        .map(|(&item, quantity)| (item_registry.display_name(item), quantity))
+        .collect();
+    sorted_inputs.sort_by_key(|&(name, _)| name);
 
     if sorted_inputs.len() <= 3 {
         sorted_inputs
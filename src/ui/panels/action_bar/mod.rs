@@ -5,16 +5,19 @@ use bevy::prelude::*;
 use bevy::ui::Checked;
 
 use crate::{
-    grid::Grid,
+    grid::{Grid, Position},
+    structures::{Building, Hub},
     ui::{
-        icons::{GameIcon, IconAtlas},
+        icons::{spawn_icon_in, GameIcon, IconAtlas},
         style::{
-            ButtonStyle, ACTION_BAR_BG, ACTION_BAR_WIDTH, ACTION_BUTTON_SIZE, PANEL_BORDER,
-            TOP_BAR_HEIGHT,
+            ButtonStyle, ThemeRole, UiTheme, ACTION_BAR_WIDTH, ACTION_BUTTON_SIZE, TOP_BAR_HEIGHT,
         },
         UISystemSet, UiMode,
     },
-    workers::{WorkerBundle, WorkersSystemSet},
+    workers::{
+        resolve_spawn_cell, PauseWorkflowEvent, RallyPoint, WorkerBundle, WorkersSystemSet,
+        Workflow,
+    },
 };
 
 use build_panel::{despawn_build_panel, spawn_build_panel, BuildPanel};
@@ -25,6 +28,7 @@ pub enum ActivePanel {
     None,
     Build,
     Workflows,
+    FactoryInfo,
 }
 
 #[derive(Component)]
@@ -33,15 +37,73 @@ pub struct ActionBarContainer;
 #[derive(Component)]
 pub struct ActionBar;
 
-#[derive(Component, Clone, Copy, PartialEq, Eq)]
+/// The command a button runs when clicked.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ActionBarButton {
     Build,
     Workflows,
     SpawnWorker,
     FactoryInfo,
+    /// Pauses every running workflow, or resumes them all when none is
+    /// running.
+    PauseAllWorkflows,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActionBarEntry {
+    pub action: ActionBarButton,
+    pub icon: GameIcon,
+    /// Short text shown in place of the icon, for custom buttons without an
+    /// icon of their own.
+    pub label: Option<String>,
+}
+
+impl ActionBarEntry {
+    #[must_use]
+    pub fn new(action: ActionBarButton, icon: GameIcon) -> Self {
+        Self {
+            action,
+            icon,
+            label: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
 }
 
-fn setup_action_bar(mut commands: Commands, icon_atlas: Res<IconAtlas>) {
+/// Buttons on the action bar, top to bottom. Insert before `UIPlugin` to
+/// change the layout.
+#[derive(Resource, Debug, Clone)]
+pub struct ActionBarConfig {
+    pub buttons: Vec<ActionBarEntry>,
+}
+
+impl Default for ActionBarConfig {
+    fn default() -> Self {
+        Self {
+            buttons: vec![
+                ActionBarEntry::new(ActionBarButton::Build, GameIcon::Build),
+                ActionBarEntry::new(ActionBarButton::Workflows, GameIcon::Workflows),
+                ActionBarEntry::new(ActionBarButton::SpawnWorker, GameIcon::SpawnWorker),
+                ActionBarEntry::new(ActionBarButton::FactoryInfo, GameIcon::FactoryInfo),
+            ],
+        }
+    }
+}
+
+fn setup_action_bar(
+    mut commands: Commands,
+    config: Res<ActionBarConfig>,
+    icon_atlas: Option<Res<IconAtlas>>,
+    theme: Res<UiTheme>,
+) {
+    if icon_atlas.is_none() {
+        warn!("icon atlas unavailable, action bar shows placeholder labels");
+    }
     commands
         .spawn((
             Node {
@@ -68,43 +130,22 @@ fn setup_action_bar(mut commands: Commands, icon_atlas: Res<IconAtlas>) {
                         row_gap: Val::Px(4.0),
                         ..default()
                     },
-                    BackgroundColor(ACTION_BAR_BG),
+                    theme.background(ThemeRole::ActionBar),
                     ActionBar,
                 ))
                 .with_children(|parent| {
-                    spawn_action_button(
-                        parent,
-                        &icon_atlas,
-                        GameIcon::Build,
-                        ActionBarButton::Build,
-                    );
-                    spawn_action_button(
-                        parent,
-                        &icon_atlas,
-                        GameIcon::Workflows,
-                        ActionBarButton::Workflows,
-                    );
-                    spawn_action_button(
-                        parent,
-                        &icon_atlas,
-                        GameIcon::SpawnWorker,
-                        ActionBarButton::SpawnWorker,
-                    );
-                    spawn_action_button(
-                        parent,
-                        &icon_atlas,
-                        GameIcon::FactoryInfo,
-                        ActionBarButton::FactoryInfo,
-                    );
+                    for entry in &config.buttons {
+                        spawn_action_button(parent, icon_atlas.as_deref(), entry, &theme);
+                    }
                 });
         });
 }
 
 fn spawn_action_button(
     parent: &mut ChildSpawnerCommands,
-    icon_atlas: &IconAtlas,
-    icon: GameIcon,
-    action: ActionBarButton,
+    icon_atlas: Option<&IconAtlas>,
+    entry: &ActionBarEntry,
+    theme: &UiTheme,
 ) {
     parent
         .spawn((
@@ -117,28 +158,25 @@ fn spawn_action_button(
                 border: UiRect::all(Val::Px(1.0)),
                 ..default()
             },
-            BackgroundColor(crate::ui::style::ACTION_BUTTON_BG),
-            BorderColor::all(PANEL_BORDER),
-            ButtonStyle::action_bar(),
+            theme.background(ThemeRole::ActionButton),
+            theme.border(ThemeRole::PanelBorder),
+            ButtonStyle::action_bar(theme),
             Hovered::default(),
-            action,
+            entry.action,
         ))
         .with_children(|btn| {
-            btn.spawn((
-                ImageNode {
-                    image: icon_atlas.image.clone(),
-                    texture_atlas: Some(TextureAtlas {
-                        layout: icon_atlas.layout.clone(),
-                        index: icon as usize,
-                    }),
-                    ..default()
-                },
-                Node {
-                    width: Val::Px(20.0),
-                    height: Val::Px(20.0),
-                    ..default()
-                },
-            ));
+            if let Some(label) = &entry.label {
+                btn.spawn((
+                    Text::new(label.clone()),
+                    TextFont {
+                        font_size: 9.0,
+                        ..default()
+                    },
+                    theme.text(ThemeRole::Text),
+                ));
+                return;
+            }
+            spawn_icon_in(btn, icon_atlas, entry.icon, 20.0, theme.text_color);
         });
 }
 
@@ -150,6 +188,11 @@ fn handle_action_bar_clicks(
     >,
     mut active_panel: ResMut<ActivePanel>,
     grid: Res<Grid>,
+    rally_point: Res<RallyPoint>,
+    hubs: Query<(), With<Hub>>,
+    buildings: Query<&Position, With<Building>>,
+    workflows: Query<(Entity, &Workflow)>,
+    mut pause_events: MessageWriter<PauseWorkflowEvent>,
 ) {
     for (_entity, action, interaction) in &button_query {
         if *interaction != Interaction::Pressed {
@@ -172,12 +215,31 @@ fn handle_action_bar_clicks(
                 }
             }
             ActionBarButton::SpawnWorker => {
-                let spawn_world_pos = grid.grid_to_world_coordinates(0, 0);
-                commands.spawn(WorkerBundle::new(spawn_world_pos));
+                let (x, y) =
+                    resolve_spawn_cell(&rally_point, !hubs.is_empty(), buildings.iter().copied());
+                let spawn_world_pos = grid.grid_to_world_coordinates(x, y);
+                commands.spawn(WorkerBundle {
+                    position: Position { x, y },
+                    ..WorkerBundle::new(spawn_world_pos)
+                });
                 info!("manual worker spawned at world position: {spawn_world_pos:?}");
             }
             ActionBarButton::FactoryInfo => {
-                info!("factory info placeholder");
+                if *active_panel == ActivePanel::FactoryInfo {
+                    *active_panel = ActivePanel::None;
+                } else {
+                    *active_panel = ActivePanel::FactoryInfo;
+                }
+            }
+            ActionBarButton::PauseAllWorkflows => {
+                let pause = workflows.iter().any(|(_, workflow)| !workflow.is_paused);
+                for (workflow, _) in workflows
+                    .iter()
+                    .filter(|(_, workflow)| workflow.is_paused != pause)
+                {
+                    pause_events.write(PauseWorkflowEvent { workflow });
+                }
+                info!(pause, "toggled all workflows");
             }
         }
     }
@@ -218,8 +280,12 @@ fn manage_panel_lifecycle(
     active_panel: Res<ActivePanel>,
     build_panels: Query<Entity, With<BuildPanel>>,
     workflow_panels: Query<Entity, With<crate::ui::panels::workflow_list::WorkflowPanel>>,
+    throughput_panels: Query<Entity, With<crate::ui::panels::throughput::ThroughputPanel>>,
+    throughput_selection: Res<crate::ui::panels::throughput::ThroughputSelection>,
     registry: Res<crate::structures::BuildingRegistry>,
-    icon_atlas: Res<IconAtlas>,
+    item_registry: Res<crate::materials::ItemRegistry>,
+    icon_atlas: Option<Res<IconAtlas>>,
+    theme: Res<UiTheme>,
 ) {
     if !active_panel.is_changed() {
         return;
@@ -231,13 +297,24 @@ fn manage_panel_lifecycle(
     for entity in &workflow_panels {
         commands.entity(entity).despawn();
     }
+    for entity in &throughput_panels {
+        commands.entity(entity).despawn();
+    }
 
     match *active_panel {
         ActivePanel::Build => {
-            spawn_build_panel(&mut commands, &registry, &icon_atlas);
+            spawn_build_panel(&mut commands, &registry, icon_atlas.as_deref(), &theme);
         }
         ActivePanel::Workflows => {
-            crate::ui::panels::workflow_list::spawn_workflow_panel(&mut commands);
+            crate::ui::panels::workflow_list::spawn_workflow_panel(&mut commands, &theme);
+        }
+        ActivePanel::FactoryInfo => {
+            crate::ui::panels::throughput::spawn_throughput_panel(
+                &mut commands,
+                &throughput_selection,
+                &item_registry,
+                &theme,
+            );
         }
         ActivePanel::None => {}
     }
@@ -256,7 +333,8 @@ fn sync_action_bar_checked(
         let should_be_checked = match action {
             ActionBarButton::Build => *active_panel == ActivePanel::Build,
             ActionBarButton::Workflows => *active_panel == ActivePanel::Workflows,
-            _ => false,
+            ActionBarButton::FactoryInfo => *active_panel == ActivePanel::FactoryInfo,
+            ActionBarButton::SpawnWorker | ActionBarButton::PauseAllWorkflows => false,
         };
 
         if should_be_checked {
@@ -284,6 +362,7 @@ pub struct ActionBarPlugin;
 impl Plugin for ActionBarPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ActivePanel>()
+            .init_resource::<ActionBarConfig>()
             .add_systems(PostStartup, setup_action_bar)
             .add_systems(
                 Update,
@@ -299,3 +378,101 @@ impl Plugin for ActionBarPlugin {
             );
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::workers::handle_pause_workflow;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn missing_icon_atlas_spawns_buttons_with_placeholder_glyphs() {
+        let mut app = App::new();
+        app.init_resource::<UiTheme>()
+            .init_resource::<ActionBarConfig>()
+            .add_systems(Startup, setup_action_bar);
+        app.update();
+
+        let mut buttons = app
+            .world_mut()
+            .query_filtered::<&Children, With<ActionBarButton>>();
+        let placeholders: Vec<String> = buttons
+            .iter(app.world())
+            .flat_map(RelationshipTarget::iter)
+            .filter_map(|child| app.world().get::<Text>(child))
+            .map(|text| text.0.clone())
+            .collect();
+        assert_eq!(placeholders.len(), 4, "every button still spawns");
+        for letter in ["B", "W", "+", "i"] {
+            assert!(placeholders.iter().any(|text| text == letter));
+        }
+
+        let mut images = app.world_mut().query::<&ImageNode>();
+        assert_eq!(images.iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn custom_pause_all_button_pauses_every_workflow() {
+        let mut app = App::new();
+        app.init_resource::<ActivePanel>()
+            .init_resource::<UiTheme>()
+            .init_resource::<RallyPoint>()
+            .insert_resource(Grid::new(64.0))
+            .insert_resource(IconAtlas {
+                image: Handle::default(),
+                layout: Handle::default(),
+            })
+            .insert_resource(ActionBarConfig {
+                buttons: vec![ActionBarEntry::new(
+                    ActionBarButton::PauseAllWorkflows,
+                    GameIcon::Workflows,
+                )
+                .with_label("Pause All")],
+            })
+            .add_message::<PauseWorkflowEvent>()
+            .add_systems(Startup, setup_action_bar)
+            .add_systems(
+                Update,
+                (handle_action_bar_clicks, handle_pause_workflow).chain(),
+            );
+
+        let mut spawn_workflow = |is_paused: bool| {
+            app.world_mut()
+                .spawn(Workflow {
+                    name: String::new(),
+                    building_set: HashSet::new(),
+                    steps: Vec::new(),
+                    is_paused,
+                    is_dedicated: false,
+                    desired_worker_count: 0,
+                    round_robin_counters: HashMap::new(),
+                    step_waits: HashMap::new(),
+                })
+                .id()
+        };
+        let workflows = [
+            spawn_workflow(false),
+            spawn_workflow(true),
+            spawn_workflow(false),
+        ];
+        app.update();
+
+        let mut buttons = app.world_mut().query::<(Entity, &ActionBarButton)>();
+        let spawned: Vec<(Entity, ActionBarButton)> = buttons
+            .iter(app.world())
+            .map(|(entity, action)| (entity, *action))
+            .collect();
+        assert_eq!(spawned.len(), 1);
+        assert_eq!(spawned[0].1, ActionBarButton::PauseAllWorkflows);
+
+        app.world_mut()
+            .entity_mut(spawned[0].0)
+            .insert(Interaction::Pressed);
+        app.update();
+
+        for workflow in workflows {
+            assert!(app.world().get::<Workflow>(workflow).unwrap().is_paused);
+        }
+    }
+}
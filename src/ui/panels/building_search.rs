@@ -0,0 +1,383 @@
+use bevy::input::{
+    keyboard::{Key, KeyboardInput},
+    ButtonState, InputSystems,
+};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+
+use crate::{
+    camera::GameCamera,
+    grid::Position,
+    structures::{Building, DisplayLabel},
+    ui::{
+        style::{ButtonStyle, ThemeRole, UiTheme},
+        UISystemSet,
+    },
+};
+
+const MAX_QUERY_LEN: usize = 32;
+
+/// Text typed into the factory info search field. While `focused`, keyboard
+/// input goes to the query instead of hotkeys.
+#[derive(Resource, Debug, Default)]
+pub struct BuildingSearch {
+    pub query: String,
+    pub focused: bool,
+}
+
+#[derive(Component)]
+pub struct BuildingSearchField;
+
+#[derive(Component)]
+pub struct BuildingSearchFieldText;
+
+#[derive(Component)]
+pub struct BuildingSearchResults;
+
+#[derive(Component)]
+pub struct BuildingSearchResult {
+    pub building: Entity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub building: Entity,
+    pub type_name: String,
+    pub label: Option<String>,
+    pub position: (i32, i32),
+}
+
+impl SearchHit {
+    /// "Smelter \"Iron line\" (4, -2)", or without the label when unset.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let (x, y) = self.position;
+        match &self.label {
+            Some(label) => format!("{} \"{label}\" ({x}, {y})", self.type_name),
+            None => format!("{} ({x}, {y})", self.type_name),
+        }
+    }
+}
+
+/// Buildings whose type name or label contains `query`, ignoring case,
+/// sorted by type and then position. An empty query matches nothing.
+pub fn search_buildings<'a>(
+    query: &str,
+    buildings: impl IntoIterator<Item = (Entity, &'a Name, Option<&'a DisplayLabel>, &'a Position)>,
+) -> Vec<SearchHit> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<SearchHit> = buildings
+        .into_iter()
+        .filter(|(_, name, label, _)| {
+            name.as_str().to_lowercase().contains(&needle)
+                || label.is_some_and(|label| label.0.to_lowercase().contains(&needle))
+        })
+        .map(|(building, name, label, pos)| SearchHit {
+            building,
+            type_name: name.as_str().to_string(),
+            label: label.map(|label| label.0.clone()),
+            position: (pos.x, pos.y),
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        (&a.type_name, a.position, a.building).cmp(&(&b.type_name, b.position, b.building))
+    });
+    hits
+}
+
+pub fn spawn_building_search(parent: &mut ChildSpawnerCommands, theme: &UiTheme) {
+    parent.spawn((
+        Text::new("Find Building"),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        theme.text(ThemeRole::DimText),
+    ));
+    parent
+        .spawn((
+            Button,
+            Node {
+                height: Val::Px(24.0),
+                padding: UiRect::horizontal(Val::Px(8.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            theme.background(ThemeRole::CardBg),
+            ButtonStyle::default_button(theme),
+            Hovered::default(),
+            BuildingSearchField,
+        ))
+        .with_children(|field| {
+            field.spawn((
+                Text::new("Click to search..."),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                BuildingSearchFieldText,
+            ));
+        });
+    parent.spawn((
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            max_height: Val::Px(180.0),
+            overflow: Overflow::clip_y(),
+            ..default()
+        },
+        BuildingSearchResults,
+    ));
+}
+
+fn focus_building_search(
+    fields: Query<&Interaction, (Changed<Interaction>, With<BuildingSearchField>)>,
+    mut search: ResMut<BuildingSearch>,
+) {
+    if fields.iter().any(|i| *i == Interaction::Pressed) {
+        search.focused = true;
+    }
+}
+
+/// Runs right after input collection, like label editing, so typed keys do
+/// not trigger hotkeys. Enter or Escape ends the edit; closing the panel
+/// drops focus.
+pub fn capture_search_input(
+    mut search: ResMut<BuildingSearch>,
+    fields: Query<(), With<BuildingSearchField>>,
+    mut key_events: MessageReader<KeyboardInput>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+) {
+    if search.focused && fields.is_empty() {
+        search.focused = false;
+    }
+    if !search.focused {
+        key_events.clear();
+        return;
+    }
+
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter | Key::Escape => {
+                search.focused = false;
+                break;
+            }
+            Key::Backspace => {
+                search.query.pop();
+            }
+            Key::Space => push_query_text(&mut search.query, " "),
+            Key::Character(text) => push_query_text(&mut search.query, text),
+            _ => {}
+        }
+    }
+
+    keyboard.reset_all();
+}
+
+fn push_query_text(query: &mut String, text: &str) {
+    for c in text.chars().filter(|c| !c.is_control()) {
+        if query.chars().count() >= MAX_QUERY_LEN {
+            return;
+        }
+        query.push(c);
+    }
+}
+
+fn update_search_field_text(
+    search: Res<BuildingSearch>,
+    mut texts: Query<&mut Text, With<BuildingSearchFieldText>>,
+) {
+    let desired = match (search.focused, search.query.is_empty()) {
+        (true, _) => format!("{}_", search.query),
+        (false, true) => "Click to search...".to_string(),
+        (false, false) => search.query.clone(),
+    };
+    for mut text in &mut texts {
+        if text.0 != desired {
+            text.0.clone_from(&desired);
+        }
+    }
+}
+
+/// Rebuilds the result rows when the query changes, the panel opens, or a
+/// building is added or removed.
+pub fn update_search_results(
+    mut commands: Commands,
+    search: Res<BuildingSearch>,
+    results: Query<(Entity, Ref<BuildingSearchResults>)>,
+    buildings: Query<(Entity, &Name, Option<&DisplayLabel>, &Position), With<Building>>,
+    added: Query<(), Added<Building>>,
+    mut removed: RemovedComponents<Building>,
+    theme: Res<UiTheme>,
+) {
+    let buildings_changed = !added.is_empty() || removed.read().count() > 0;
+    for (list, list_ref) in &results {
+        if !search.is_changed() && !buildings_changed && !list_ref.is_added() {
+            continue;
+        }
+        commands.entity(list).despawn_related::<Children>();
+
+        let hits = search_buildings(&search.query, buildings);
+        commands.entity(list).with_children(|list| {
+            for hit in hits {
+                list.spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+                        ..default()
+                    },
+                    theme.background(ThemeRole::Button),
+                    ButtonStyle::default_button(&theme),
+                    Hovered::default(),
+                    BuildingSearchResult {
+                        building: hit.building,
+                    },
+                ))
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(hit.describe()),
+                        TextFont {
+                            font_size: 11.0,
+                            ..default()
+                        },
+                        theme.text(ThemeRole::Text),
+                    ));
+                });
+            }
+        });
+    }
+}
+
+/// Clicking a result centers the camera on that building.
+pub fn locate_search_result(
+    results: Query<(&Interaction, &BuildingSearchResult), Changed<Interaction>>,
+    buildings: Query<&Transform, (With<Building>, Without<GameCamera>)>,
+    mut camera_query: Query<(&mut Transform, &mut GameCamera), With<Camera2d>>,
+) {
+    for (interaction, result) in &results {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(target) = buildings.get(result.building) else {
+            continue;
+        };
+        let Ok((mut camera_transform, mut game_camera)) = camera_query.single_mut() else {
+            return;
+        };
+        camera_transform.translation.x = target.translation.x;
+        camera_transform.translation.y = target.translation.y;
+        game_camera.velocity = Vec2::ZERO;
+    }
+}
+
+pub struct BuildingSearchPlugin;
+
+impl Plugin for BuildingSearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildingSearch>()
+            .add_systems(PreUpdate, capture_search_input.after(InputSystems))
+            .add_systems(
+                Update,
+                (
+                    (focus_building_search, locate_search_result)
+                        .in_set(UISystemSet::InputDetection),
+                    update_search_results.in_set(UISystemSet::EntityManagement),
+                    update_search_field_text.in_set(UISystemSet::VisualUpdates),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn result_buildings(app: &mut App) -> Vec<Entity> {
+        app.world_mut()
+            .query::<&BuildingSearchResult>()
+            .iter(app.world())
+            .map(|result| result.building)
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn searching_lists_matching_buildings_and_clicking_one_centers_the_camera() {
+        let mut app = App::new();
+        app.init_resource::<UiTheme>()
+            .insert_resource(BuildingSearch {
+                query: "Smelter".to_string(),
+                focused: false,
+            })
+            .add_systems(
+                Update,
+                (update_search_results, locate_search_result).chain(),
+            );
+
+        let mut spawn_building = |name: &str, x: i32, y: i32| {
+            app.world_mut()
+                .spawn((
+                    Building,
+                    Name::new(name.to_string()),
+                    Position { x, y },
+                    Transform::from_xyz(x as f32 * 64.0, y as f32 * 64.0, 1.0),
+                ))
+                .id()
+        };
+        let smelter = spawn_building("Smelter", 4, -2);
+        let labelled = spawn_building("Smelter", 1, 3);
+        spawn_building("Storage", 0, 2);
+        spawn_building("Connector", 2, 0);
+        app.world_mut()
+            .entity_mut(labelled)
+            .insert(DisplayLabel("Copper line".to_string()));
+
+        let camera = app
+            .world_mut()
+            .spawn((Camera2d, GameCamera::default(), Transform::default()))
+            .id();
+        app.world_mut().spawn(BuildingSearchResults);
+        app.update();
+
+        let mut found = result_buildings(&mut app);
+        found.sort();
+        let mut smelters = vec![smelter, labelled];
+        smelters.sort();
+        assert_eq!(found, smelters, "only smelters should be listed");
+
+        let mut rows = app.world_mut().query::<(Entity, &BuildingSearchResult)>();
+        let row = rows
+            .iter(app.world())
+            .find(|(_, result)| result.building == smelter)
+            .map(|(row, _)| row)
+            .unwrap();
+        app.world_mut().entity_mut(row).insert(Interaction::Pressed);
+        app.update();
+
+        let camera_pos = app.world().get::<Transform>(camera).unwrap().translation;
+        assert_eq!(camera_pos.truncate(), Vec2::new(256.0, -128.0));
+    }
+
+    #[test]
+    fn labels_match_and_describe_the_hit() {
+        let mut world = World::new();
+        let building = world.spawn_empty().id();
+        let name = Name::new("Storage");
+        let label = DisplayLabel("Ore Buffer".to_string());
+        let pos = Position { x: -3, y: 5 };
+
+        let hits = search_buildings("buffer", [(building, &name, Some(&label), &pos)]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].describe(), "Storage \"Ore Buffer\" (-3, 5)");
+        assert!(search_buildings("  ", [(building, &name, Some(&label), &pos)]).is_empty());
+    }
+}
@@ -1,7 +1,13 @@
 pub mod action_bar;
+pub mod building_search;
+pub mod throughput;
 pub mod top_bar;
+pub mod workflow_debug;
 pub mod workflow_list;
 
 pub use action_bar::ActionBarPlugin;
+pub use building_search::BuildingSearchPlugin;
+pub use throughput::ThroughputPanelPlugin;
 pub use top_bar::TopBarPlugin;
+pub use workflow_debug::WorkflowDebugPlugin;
 pub use workflow_list::WorkflowListPlugin;
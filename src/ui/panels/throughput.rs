@@ -0,0 +1,493 @@
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+
+use crate::{
+    materials::{ItemId, ItemRegistry},
+    systems::{throughput::THROUGHPUT_HISTORY_LEN, InTransit, ItemThroughput, ThroughputSample},
+    ui::{
+        panels::{action_bar::ActivePanel, building_search::spawn_building_search},
+        style::{ButtonStyle, ThemeRole, UiTheme, ACTION_BAR_WIDTH, TOP_BAR_HEIGHT},
+        UISystemSet,
+    },
+};
+
+const SPARKLINE_HEIGHT: f32 = 40.0;
+
+#[derive(Resource, Default)]
+pub struct ThroughputSelection {
+    pub item: Option<ItemId>,
+}
+
+#[derive(Component)]
+pub struct ThroughputPanel;
+
+#[derive(Component)]
+pub struct ThroughputItemButton;
+
+#[derive(Component)]
+pub struct ThroughputItemButtonText;
+
+#[derive(Component)]
+pub struct ThroughputItemList;
+
+#[derive(Component)]
+pub struct InTransitText;
+
+#[derive(Component)]
+pub struct ThroughputItemOption {
+    pub item: ItemId,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum SparklineKind {
+    Produced,
+    Consumed,
+}
+
+#[derive(Component)]
+pub struct SparklineBar {
+    pub kind: SparklineKind,
+    pub index: usize,
+}
+
+/// Scales samples against the largest value in the series, right-aligned so
+/// the newest sample is always the last bar. Missing history stays at zero.
+fn sparkline_heights(samples: &[u32], bars: usize) -> Vec<f32> {
+    let mut heights = vec![0.0; bars];
+    let max = samples.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return heights;
+    }
+    let shown = samples.len().min(bars);
+    let offset = bars - shown;
+    for (slot, &value) in heights[offset..]
+        .iter_mut()
+        .zip(&samples[samples.len() - shown..])
+    {
+        #[allow(clippy::cast_precision_loss)]
+        let normalized = value as f32 / max as f32;
+        *slot = normalized;
+    }
+    heights
+}
+
+fn series(throughput: &ItemThroughput, item: ItemId, kind: SparklineKind) -> Vec<u32> {
+    throughput
+        .samples(item)
+        .map(|sample: &ThroughputSample| match kind {
+            SparklineKind::Produced => sample.produced,
+            SparklineKind::Consumed => sample.consumed,
+        })
+        .collect()
+}
+
+fn item_label(item: Option<ItemId>, item_registry: &ItemRegistry) -> String {
+    item.map_or_else(
+        || "Select item".to_string(),
+        |item| item_registry.display_name(item).to_string(),
+    )
+}
+
+fn in_transit_label(in_transit: &InTransit, item: Option<ItemId>) -> String {
+    item.map_or_else(String::new, |item| {
+        format!("In transit: {}", in_transit.get(item))
+    })
+}
+
+pub fn spawn_throughput_panel(
+    commands: &mut Commands,
+    selection: &ThroughputSelection,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
+) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(ACTION_BAR_WIDTH + 4.0),
+                top: Val::Px(TOP_BAR_HEIGHT + 4.0),
+                width: Val::Px(300.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            theme.background(ThemeRole::PanelBg),
+            theme.border(ThemeRole::PanelBorder),
+            Interaction::None,
+            ThroughputPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("Item Throughput"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Header),
+            ));
+
+            panel
+                .spawn((
+                    Button,
+                    Node {
+                        height: Val::Px(24.0),
+                        padding: UiRect::horizontal(Val::Px(8.0)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    theme.background(ThemeRole::Button),
+                    ButtonStyle::default_button(theme),
+                    Hovered::default(),
+                    ThroughputItemButton,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(format!("{} v", item_label(selection.item, item_registry))),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        theme.text(ThemeRole::Text),
+                        ThroughputItemButtonText,
+                    ));
+                });
+
+            panel.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                InTransitText,
+            ));
+
+            spawn_sparkline(
+                panel,
+                "Produced",
+                SparklineKind::Produced,
+                theme.worker_color,
+                theme,
+            );
+            spawn_sparkline(
+                panel,
+                "Consumed",
+                SparklineKind::Consumed,
+                theme.danger_color,
+                theme,
+            );
+
+            spawn_building_search(panel, theme);
+        });
+}
+
+fn spawn_sparkline(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    kind: SparklineKind,
+    color: Color,
+    theme: &UiTheme,
+) {
+    parent.spawn((
+        Text::new(label),
+        TextFont {
+            font_size: 11.0,
+            ..default()
+        },
+        theme.text(ThemeRole::DimText),
+    ));
+    parent
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(SPARKLINE_HEIGHT),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexEnd,
+                column_gap: Val::Px(1.0),
+                ..default()
+            },
+            theme.background(ThemeRole::CardBg),
+        ))
+        .with_children(|line| {
+            for index in 0..THROUGHPUT_HISTORY_LEN {
+                line.spawn((
+                    Node {
+                        flex_grow: 1.0,
+                        height: Val::Percent(0.0),
+                        ..default()
+                    },
+                    BackgroundColor(color),
+                    SparklineBar { kind, index },
+                ));
+            }
+        });
+}
+
+fn toggle_throughput_item_list(
+    mut commands: Commands,
+    buttons: Query<(Entity, &Interaction), (Changed<Interaction>, With<ThroughputItemButton>)>,
+    lists: Query<Entity, With<ThroughputItemList>>,
+    throughput: Res<ItemThroughput>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
+) {
+    for (button, interaction) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if !lists.is_empty() {
+            for list in &lists {
+                commands.entity(list).despawn();
+            }
+            continue;
+        }
+
+        let mut items: Vec<(ItemId, &str)> = throughput
+            .tracked_items()
+            .map(|item| {
+                let name = item_registry.display_name(item);
+                (item, name)
+            })
+            .collect();
+        items.sort_unstable_by(|a, b| a.1.cmp(b.1));
+
+        commands.entity(button).with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(26.0),
+                        left: Val::Px(0.0),
+                        min_width: Val::Px(160.0),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(4.0)),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    theme.background(ThemeRole::PopupBg),
+                    theme.border(ThemeRole::PanelBorder),
+                    GlobalZIndex(10),
+                    ThroughputItemList,
+                ))
+                .with_children(|list| {
+                    if items.is_empty() {
+                        list.spawn((
+                            Text::new("No production yet"),
+                            TextFont {
+                                font_size: 11.0,
+                                ..default()
+                            },
+                            theme.text(ThemeRole::DimText),
+                        ));
+                    }
+                    for (item, name) in items {
+                        list.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+                                ..default()
+                            },
+                            theme.background(ThemeRole::Button),
+                            ButtonStyle::default_button(&theme),
+                            Hovered::default(),
+                            ThroughputItemOption { item },
+                        ))
+                        .with_children(|option| {
+                            option.spawn((
+                                Text::new(name),
+                                TextFont {
+                                    font_size: 11.0,
+                                    ..default()
+                                },
+                                theme.text(ThemeRole::Text),
+                            ));
+                        });
+                    }
+                });
+        });
+    }
+}
+
+fn handle_throughput_item_selection(
+    mut commands: Commands,
+    options: Query<(&Interaction, &ThroughputItemOption), Changed<Interaction>>,
+    lists: Query<Entity, With<ThroughputItemList>>,
+    mut selection: ResMut<ThroughputSelection>,
+    mut button_texts: Query<&mut Text, With<ThroughputItemButtonText>>,
+    item_registry: Res<ItemRegistry>,
+) {
+    for (interaction, option) in &options {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        selection.item = Some(option.item);
+        for mut text in &mut button_texts {
+            **text = format!("{} v", item_label(selection.item, &item_registry));
+        }
+        for list in &lists {
+            commands.entity(list).despawn();
+        }
+    }
+}
+
+fn update_sparklines(
+    throughput: Res<ItemThroughput>,
+    selection: Res<ThroughputSelection>,
+    added_bars: Query<(), Added<SparklineBar>>,
+    mut bars: Query<(&SparklineBar, &mut Node)>,
+    mut drawn_generation: Local<Option<u64>>,
+) {
+    let new_sample = *drawn_generation != Some(throughput.generation());
+    if !new_sample && !selection.is_changed() && added_bars.is_empty() {
+        return;
+    }
+    *drawn_generation = Some(throughput.generation());
+
+    let [produced, consumed] = [SparklineKind::Produced, SparklineKind::Consumed].map(|kind| {
+        let values = selection
+            .item
+            .map(|item| series(&throughput, item, kind))
+            .unwrap_or_default();
+        sparkline_heights(&values, THROUGHPUT_HISTORY_LEN)
+    });
+
+    for (bar, mut node) in &mut bars {
+        let heights = match bar.kind {
+            SparklineKind::Produced => &produced,
+            SparklineKind::Consumed => &consumed,
+        };
+        let height = heights.get(bar.index).copied().unwrap_or(0.0);
+        node.height = Val::Percent(height * 100.0);
+    }
+}
+
+fn update_in_transit_text(
+    in_transit: Res<InTransit>,
+    selection: Res<ThroughputSelection>,
+    added_texts: Query<(), Added<InTransitText>>,
+    mut texts: Query<&mut Text, With<InTransitText>>,
+    mut shown_generation: Local<Option<u64>>,
+) {
+    let recounted = *shown_generation != Some(in_transit.generation());
+    if !recounted && !selection.is_changed() && added_texts.is_empty() {
+        return;
+    }
+    *shown_generation = Some(in_transit.generation());
+
+    let label = in_transit_label(&in_transit, selection.item);
+    for mut text in &mut texts {
+        if text.0 != label {
+            text.0.clone_from(&label);
+        }
+    }
+}
+
+/// F5 dumps the production stats to a timestamped CSV in the working
+/// directory.
+pub fn export_throughput_on_hotkey(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    throughput: Res<ItemThroughput>,
+    item_registry: Res<ItemRegistry>,
+) {
+    if !keyboard.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    match throughput.export_csv(&item_registry, std::path::Path::new("."), timestamp) {
+        Ok(path) => info!(path = %path.display(), "production stats exported"),
+        Err(error) => warn!(%error, "production stats export failed"),
+    }
+}
+
+pub struct ThroughputPanelPlugin;
+
+impl Plugin for ThroughputPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ThroughputSelection>().add_systems(
+            Update,
+            (
+                export_throughput_on_hotkey.in_set(UISystemSet::InputDetection),
+                (
+                    toggle_throughput_item_list,
+                    handle_throughput_item_selection,
+                )
+                    .chain()
+                    .in_set(UISystemSet::EntityManagement),
+                (update_sparklines, update_in_transit_text)
+                    .in_set(UISystemSet::VisualUpdates)
+                    .run_if(|active: Res<ActivePanel>| *active == ActivePanel::FactoryInfo),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn sparkline_normalizes_samples_against_the_peak() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut throughput = ItemThroughput::default();
+        for produced in [2, 8, 4] {
+            throughput.record_produced(ore, produced);
+            throughput.push_sample();
+        }
+
+        let values = series(&throughput, ore, SparklineKind::Produced);
+        let percents: Vec<u32> = sparkline_heights(&values, 5)
+            .iter()
+            .map(|h| (h * 100.0).round() as u32)
+            .collect();
+        assert_eq!(percents, vec![0, 0, 25, 100, 50]);
+
+        let consumed = series(&throughput, ore, SparklineKind::Consumed);
+        assert!(sparkline_heights(&consumed, 5).iter().all(|&h| h <= 0.0));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn sparklines_redraw_only_when_a_sample_closes() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = App::new();
+        app.init_resource::<ItemThroughput>()
+            .insert_resource(ThroughputSelection { item: Some(ore) })
+            .add_systems(Update, update_sparklines);
+        let bar = app
+            .world_mut()
+            .spawn((
+                SparklineBar {
+                    kind: SparklineKind::Produced,
+                    index: THROUGHPUT_HISTORY_LEN - 1,
+                },
+                Node::default(),
+            ))
+            .id();
+        app.update();
+
+        let height = |app: &App| app.world().get::<Node>(bar).unwrap().height;
+        let mark = Val::Px(-1.0);
+        app.world_mut().get_mut::<Node>(bar).unwrap().height = mark;
+
+        // Recording into the open window is not a new sample.
+        app.world_mut()
+            .resource_mut::<ItemThroughput>()
+            .record_produced(ore, 4);
+        app.update();
+        assert_eq!(height(&app), mark);
+
+        app.world_mut()
+            .resource_mut::<ItemThroughput>()
+            .push_sample();
+        app.update();
+        assert_eq!(height(&app), Val::Percent(100.0));
+    }
+}
@@ -1,16 +1,20 @@
+use bevy::picking::hover::Hovered;
 use bevy::prelude::*;
 
 use crate::{
-    systems::{ComputeGrid, GameScore, PowerGrid},
+    camera::IdleWorkerCycle,
+    systems::{AutoPauseConfig, ComputeGrid, GameScore, PowerGrid},
     ui::{
         icons::{spawn_icon, GameIcon, IconAtlas},
-        style::{
-            COMPUTE_COLOR, DANGER_COLOR, PANEL_BORDER, POWER_COLOR, SCORE_COLOR, TOP_BAR_BG,
-            TOP_BAR_HEIGHT, WARNING_COLOR, WORKER_COLOR,
-        },
+        panels::workflow_list::{assign_workers, unassign_one_worker, SelectedWorkflow},
+        style::{ButtonStyle, ThemeRole, UiTheme, TOP_BAR_HEIGHT},
+        worker_groups::SelectedWorkers,
         UISystemSet,
     },
-    workers::Worker,
+    workers::{
+        workflows::{AssignWorkersEvent, UnassignWorkersEvent, Workflow, WorkflowAssignment},
+        Worker,
+    },
 };
 
 #[derive(Component)]
@@ -28,7 +32,30 @@ pub struct TopBarWorkerText;
 #[derive(Component)]
 pub struct TopBarScoreText;
 
-fn setup_top_bar(mut commands: Commands, icon_atlas: Res<IconAtlas>) {
+/// Holds the selected workflow's worker controls; hidden with no selection.
+#[derive(Component)]
+pub struct TopBarWorkflowGroup;
+
+#[derive(Component)]
+pub struct TopBarWorkflowText;
+
+#[derive(Component)]
+pub struct TopBarWorkerAddButton;
+
+#[derive(Component)]
+pub struct TopBarWorkerRemoveButton;
+
+/// Turns every auto-pause trigger on or off together.
+#[derive(Component)]
+pub struct TopBarAutoPauseButton;
+
+#[derive(Component)]
+pub struct TopBarAutoPauseText;
+
+fn setup_top_bar(mut commands: Commands, icon_atlas: Option<Res<IconAtlas>>, theme: Res<UiTheme>) {
+    if icon_atlas.is_none() {
+        warn!("icon atlas unavailable, top bar shows placeholder labels");
+    }
     let bar = commands
         .spawn((
             Node {
@@ -44,8 +71,8 @@ fn setup_top_bar(mut commands: Commands, icon_atlas: Res<IconAtlas>) {
                 border: UiRect::bottom(Val::Px(1.0)),
                 ..default()
             },
-            BackgroundColor(TOP_BAR_BG),
-            BorderColor::all(PANEL_BORDER),
+            theme.background(ThemeRole::TopBar),
+            theme.border(ThemeRole::PanelBorder),
             TopBar,
         ))
         .id();
@@ -61,28 +88,31 @@ fn setup_top_bar(mut commands: Commands, icon_atlas: Res<IconAtlas>) {
 
     let power_group = spawn_stat_group(
         &mut commands,
-        &icon_atlas,
+        icon_atlas.as_deref(),
         GameIcon::Power,
         "0/0",
-        POWER_COLOR,
+        &theme,
+        ThemeRole::Power,
         TopBarPowerText,
     );
 
     let compute_group = spawn_stat_group(
         &mut commands,
-        &icon_atlas,
+        icon_atlas.as_deref(),
         GameIcon::Compute,
         "0/0",
-        COMPUTE_COLOR,
+        &theme,
+        ThemeRole::Compute,
         TopBarComputeText,
     );
 
     let worker_group = spawn_stat_group(
         &mut commands,
-        &icon_atlas,
+        icon_atlas.as_deref(),
         GameIcon::Workers,
         "0",
-        WORKER_COLOR,
+        &theme,
+        ThemeRole::Worker,
         TopBarWorkerText,
     );
 
@@ -94,31 +124,138 @@ fn setup_top_bar(mut commands: Commands, icon_atlas: Res<IconAtlas>) {
         .spawn(Node {
             flex_direction: FlexDirection::Row,
             align_items: AlignItems::Center,
+            column_gap: Val::Px(20.0),
             ..default()
         })
         .id();
 
+    let auto_pause_toggle = spawn_auto_pause_toggle(&mut commands, &theme);
+
     let score_group = spawn_stat_group(
         &mut commands,
-        &icon_atlas,
+        icon_atlas.as_deref(),
         GameIcon::Score,
         "0",
-        SCORE_COLOR,
+        &theme,
+        ThemeRole::Score,
         TopBarScoreText,
     );
 
-    commands.entity(right_section).add_child(score_group);
+    commands
+        .entity(right_section)
+        .add_children(&[auto_pause_toggle, score_group]);
+    let workflow_group = spawn_workflow_group(&mut commands, &theme);
     commands
         .entity(bar)
-        .add_children(&[left_section, right_section]);
+        .add_children(&[left_section, workflow_group, right_section]);
+}
+
+fn spawn_workflow_group(commands: &mut Commands, theme: &UiTheme) -> Entity {
+    commands
+        .spawn((
+            Node {
+                display: Display::None,
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            TopBarWorkflowGroup,
+        ))
+        .with_children(|group| {
+            spawn_worker_button(group, "-", TopBarWorkerRemoveButton, theme);
+            group.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                TopBarWorkflowText,
+            ));
+            spawn_worker_button(group, "+", TopBarWorkerAddButton, theme);
+        })
+        .id()
+}
+
+fn spawn_worker_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    marker: impl Component,
+    theme: &UiTheme,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(24.0),
+                height: Val::Px(24.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            theme.background(ThemeRole::Button),
+            ButtonStyle::default_button(theme),
+            Hovered::default(),
+            marker,
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+            ));
+        });
+}
+
+fn auto_pause_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Auto-pause: on"
+    } else {
+        "Auto-pause: off"
+    }
+}
+
+fn spawn_auto_pause_toggle(commands: &mut Commands, theme: &UiTheme) -> Entity {
+    commands
+        .spawn((
+            Button,
+            Node {
+                height: Val::Px(24.0),
+                padding: UiRect::horizontal(Val::Px(8.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            theme.background(ThemeRole::Button),
+            ButtonStyle::default_button(theme),
+            Hovered::default(),
+            TopBarAutoPauseButton,
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(auto_pause_label(false)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                TopBarAutoPauseText,
+            ));
+        })
+        .id()
 }
 
 fn spawn_stat_group(
     commands: &mut Commands,
-    icon_atlas: &IconAtlas,
+    icon_atlas: Option<&IconAtlas>,
     icon: GameIcon,
     initial_text: &str,
-    color: Color,
+    theme: &UiTheme,
+    role: ThemeRole,
     marker: impl Component,
 ) -> Entity {
     let group = commands
@@ -130,7 +267,7 @@ fn spawn_stat_group(
         })
         .id();
 
-    let icon_entity = spawn_icon(commands, icon_atlas, icon, 18.0);
+    let icon_entity = spawn_icon(commands, icon_atlas, icon, 18.0, theme.color(role));
 
     let text_entity = commands
         .spawn((
@@ -139,7 +276,7 @@ fn spawn_stat_group(
                 font_size: 16.0,
                 ..default()
             },
-            TextColor(color),
+            theme.text(role),
             marker,
         ))
         .id();
@@ -153,6 +290,7 @@ fn spawn_stat_group(
 fn update_power_text(
     power_grid: Res<PowerGrid>,
     mut text_query: Query<(&mut Text, &mut TextColor), With<TopBarPowerText>>,
+    theme: Res<UiTheme>,
 ) {
     if !power_grid.is_changed() {
         return;
@@ -160,13 +298,19 @@ fn update_power_text(
 
     if let Ok((mut text, mut color)) = text_query.single_mut() {
         **text = format!("{}/{}", power_grid.available, power_grid.capacity);
-        color.0 = stat_color(power_grid.available, power_grid.capacity, POWER_COLOR);
+        color.0 = stat_color(
+            &theme,
+            power_grid.available,
+            power_grid.capacity,
+            theme.power_color,
+        );
     }
 }
 
 fn update_compute_text(
     compute_grid: Res<ComputeGrid>,
     mut text_query: Query<(&mut Text, &mut TextColor), With<TopBarComputeText>>,
+    theme: Res<UiTheme>,
 ) {
     if !compute_grid.is_changed() {
         return;
@@ -174,17 +318,26 @@ fn update_compute_text(
 
     if let Ok((mut text, mut color)) = text_query.single_mut() {
         **text = format!("{}/{}", compute_grid.available, compute_grid.capacity);
-        color.0 = stat_color(compute_grid.available, compute_grid.capacity, COMPUTE_COLOR);
+        color.0 = stat_color(
+            &theme,
+            compute_grid.available,
+            compute_grid.capacity,
+            theme.compute_color,
+        );
     }
 }
 
 fn update_worker_text(
     workers: Query<(), With<Worker>>,
+    idle_cycle: Res<IdleWorkerCycle>,
     mut text_query: Query<&mut Text, With<TopBarWorkerText>>,
 ) {
     if let Ok(mut text) = text_query.single_mut() {
         let count = workers.iter().count();
-        **text = format!("{count}");
+        **text = match idle_cycle.focused {
+            Some((index, total)) => format!("{count} (idle {index}/{total})"),
+            None => format!("{count}"),
+        };
     }
 }
 
@@ -201,15 +354,103 @@ fn update_score_text(
     }
 }
 
-fn stat_color(available: i32, capacity: i32, default_color: Color) -> Color {
+fn handle_workflow_worker_buttons(
+    add_buttons: Query<&Interaction, (Changed<Interaction>, With<TopBarWorkerAddButton>)>,
+    remove_buttons: Query<&Interaction, (Changed<Interaction>, With<TopBarWorkerRemoveButton>)>,
+    selected: Res<SelectedWorkflow>,
+    mut selected_workers: ResMut<SelectedWorkers>,
+    idle_workers: Query<Entity, (With<Worker>, Without<WorkflowAssignment>)>,
+    assigned_workers: Query<(Entity, &WorkflowAssignment), With<Worker>>,
+    mut assign_events: MessageWriter<AssignWorkersEvent>,
+    mut unassign_events: MessageWriter<UnassignWorkersEvent>,
+) {
+    let Some(workflow) = selected.0 else {
+        return;
+    };
+
+    if add_buttons.iter().any(|i| *i == Interaction::Pressed) {
+        assign_workers(
+            workflow,
+            &mut selected_workers,
+            &idle_workers,
+            &mut assign_events,
+        );
+    }
+    if remove_buttons.iter().any(|i| *i == Interaction::Pressed) {
+        unassign_one_worker(workflow, &assigned_workers, &mut unassign_events);
+    }
+}
+
+fn handle_auto_pause_toggle(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<TopBarAutoPauseButton>)>,
+    mut config: ResMut<AutoPauseConfig>,
+) {
+    if buttons.iter().any(|i| *i == Interaction::Pressed) {
+        let enabled = !config.any_enabled();
+        config.set_all(enabled);
+        info!(enabled, "auto-pause toggled");
+    }
+}
+
+fn update_auto_pause_text(
+    config: Res<AutoPauseConfig>,
+    mut text_query: Query<&mut Text, With<TopBarAutoPauseText>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        **text = auto_pause_label(config.any_enabled()).to_string();
+    }
+}
+
+fn update_workflow_group(
+    mut selected: ResMut<SelectedWorkflow>,
+    workflows: Query<&Workflow>,
+    assignments: Query<&WorkflowAssignment, With<Worker>>,
+    mut group_query: Query<&mut Node, With<TopBarWorkflowGroup>>,
+    mut text_query: Query<&mut Text, With<TopBarWorkflowText>>,
+) {
+    let workflow = selected.0.and_then(|entity| workflows.get(entity).ok());
+    if selected.0.is_some() && workflow.is_none() {
+        selected.0 = None;
+    }
+
+    if let Ok(mut node) = group_query.single_mut() {
+        let display = if workflow.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+
+    let (Some(workflow), Some(entity)) = (workflow, selected.0) else {
+        return;
+    };
+    if let Ok(mut text) = text_query.single_mut() {
+        let current = assignments
+            .iter()
+            .filter(|assignment| assignment.workflow == entity)
+            .count();
+        **text = format!(
+            "{}: {current}/{}",
+            workflow.name, workflow.desired_worker_count
+        );
+    }
+}
+
+fn stat_color(theme: &UiTheme, available: i32, capacity: i32, default_color: Color) -> Color {
     if available <= 0 {
-        return DANGER_COLOR;
+        return theme.danger_color;
     }
     if capacity > 0 {
         #[allow(clippy::cast_precision_loss)]
         let ratio = available as f32 / capacity as f32;
         if ratio < 0.25 {
-            return WARNING_COLOR;
+            return theme.warning_color;
         }
     }
     default_color
@@ -242,12 +483,144 @@ impl Plugin for TopBarPlugin {
         app.add_systems(PostStartup, setup_top_bar).add_systems(
             Update,
             (
-                update_power_text,
-                update_compute_text,
-                update_worker_text,
-                update_score_text,
-            )
-                .in_set(UISystemSet::VisualUpdates),
+                (handle_workflow_worker_buttons, handle_auto_pause_toggle)
+                    .in_set(UISystemSet::EntityManagement),
+                (
+                    update_power_text,
+                    update_compute_text,
+                    update_worker_text,
+                    update_score_text,
+                    update_auto_pause_text,
+                    update_workflow_group,
+                )
+                    .in_set(UISystemSet::VisualUpdates),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ui::panels::workflow_list::{select_workflow_card, WorkflowEntry};
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn plus_control_assigns_an_idle_worker_to_the_selected_workflow() {
+        let mut app = App::new();
+        app.add_message::<AssignWorkersEvent>()
+            .add_message::<UnassignWorkersEvent>()
+            .init_resource::<SelectedWorkflow>()
+            .init_resource::<SelectedWorkers>()
+            .add_systems(
+                Update,
+                (select_workflow_card, handle_workflow_worker_buttons).chain(),
+            );
+
+        let world = app.world_mut();
+        let workflow = world
+            .spawn(Workflow {
+                name: "Smelting".to_string(),
+                building_set: HashSet::new(),
+                steps: Vec::new(),
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 2,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+        let worker = world.spawn(Worker).id();
+        let card = world
+            .spawn((Interaction::None, WorkflowEntry { workflow }))
+            .id();
+        let plus = world.spawn((Interaction::None, TopBarWorkerAddButton)).id();
+        app.update();
+
+        *app.world_mut().get_mut::<Interaction>(card).unwrap() = Interaction::Pressed;
+        app.update();
+        assert_eq!(
+            *app.world().resource::<SelectedWorkflow>(),
+            SelectedWorkflow(Some(workflow))
         );
+
+        *app.world_mut().get_mut::<Interaction>(plus).unwrap() = Interaction::Pressed;
+        app.update();
+
+        let assigned: Vec<_> = app
+            .world()
+            .resource::<Messages<AssignWorkersEvent>>()
+            .iter_current_update_messages()
+            .map(|event| (event.workflow, event.workers.clone()))
+            .collect();
+        assert_eq!(assigned, vec![(workflow, vec![worker])]);
+    }
+
+    #[test]
+    fn plus_control_uses_up_the_worker_selection() {
+        let mut app = App::new();
+        app.add_message::<AssignWorkersEvent>()
+            .add_message::<UnassignWorkersEvent>()
+            .init_resource::<SelectedWorkers>()
+            .add_systems(Update, handle_workflow_worker_buttons);
+
+        let workflow = app.world_mut().spawn_empty().id();
+        app.insert_resource(SelectedWorkflow(Some(workflow)));
+        let [first, second] = [(); 2].map(|()| app.world_mut().spawn(Worker).id());
+        app.world_mut().resource_mut::<SelectedWorkers>().workers = HashSet::from([first, second]);
+        let plus = app
+            .world_mut()
+            .spawn((Interaction::None, TopBarWorkerAddButton))
+            .id();
+        let press_plus = |app: &mut App| {
+            *app.world_mut().get_mut::<Interaction>(plus).unwrap() = Interaction::None;
+            app.update();
+            *app.world_mut().get_mut::<Interaction>(plus).unwrap() = Interaction::Pressed;
+            app.update();
+            app.world()
+                .resource::<Messages<AssignWorkersEvent>>()
+                .iter_current_update_messages()
+                .map(|event| event.workers.len())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(press_plus(&mut app), vec![2]);
+        assert!(app.world().resource::<SelectedWorkers>().workers.is_empty());
+        assert_eq!(press_plus(&mut app), vec![1]);
+    }
+
+    #[test]
+    fn auto_pause_button_toggles_every_trigger() {
+        let mut app = App::new();
+        app.init_resource::<AutoPauseConfig>().add_systems(
+            Update,
+            (handle_auto_pause_toggle, update_auto_pause_text).chain(),
+        );
+        let button = app
+            .world_mut()
+            .spawn((Interaction::None, TopBarAutoPauseButton))
+            .id();
+        let text = app
+            .world_mut()
+            .spawn((Text::new(""), TopBarAutoPauseText))
+            .id();
+        let label = |app: &App| app.world().get::<Text>(text).unwrap().0.clone();
+
+        app.update();
+        assert_eq!(label(&app), "Auto-pause: off");
+
+        *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+        app.update();
+        let config = *app.world().resource::<AutoPauseConfig>();
+        assert!(config.on_empty_workflow && config.on_unreachable_supplier);
+        assert_eq!(label(&app), "Auto-pause: on");
+
+        *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::None;
+        app.update();
+        *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+        app.update();
+        assert!(!app.world().resource::<AutoPauseConfig>().any_enabled());
+        assert_eq!(label(&app), "Auto-pause: off");
     }
 }
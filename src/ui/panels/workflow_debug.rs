@@ -0,0 +1,366 @@
+use bevy::prelude::*;
+
+use crate::{
+    grid::Position,
+    materials::ItemRegistry,
+    structures::LogisticsPriority,
+    ui::{
+        style::{ThemeRole, UiTheme, TOP_BAR_HEIGHT},
+        UISystemSet,
+    },
+    workers::{
+        workflows::execution::workflow_priority, StepTarget, WaitingForItems, WaitingForSpace,
+        WaitingForTarget, Workflow, WorkflowAssignment,
+    },
+};
+
+const REFRESH_SECS: f32 = 0.5;
+
+type AssignmentRow<'a> = (
+    Entity,
+    &'a WorkflowAssignment,
+    Has<WaitingForItems>,
+    Has<WaitingForSpace>,
+    Has<WaitingForTarget>,
+);
+
+/// Toggles a debug panel listing every workflow with its steps, priority and
+/// the workers currently running it. The text is rebuilt every
+/// `REFRESH_SECS` rather than every frame.
+#[derive(Resource, Debug)]
+pub struct WorkflowDebugPanel {
+    pub enabled: bool,
+    pub refresh: Timer,
+}
+
+impl Default for WorkflowDebugPanel {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh: Timer::from_seconds(REFRESH_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct WorkflowDebugRoot;
+
+#[derive(Component)]
+pub struct WorkflowDebugText;
+
+fn format_position(position: Position) -> String {
+    format!("({}, {})", position.x, position.y)
+}
+
+fn target_label(target: &StepTarget, names: &Query<&Name>, positions: &Query<&Position>) -> String {
+    match target {
+        StepTarget::Specific(entity) => {
+            let name = names.get(*entity).map_or("Building", |n| n.as_str());
+            positions.get(*entity).map_or_else(
+                |_| name.to_string(),
+                |pos| format!("{name} {}", format_position(*pos)),
+            )
+        }
+        StepTarget::ByType(type_name) => format!("any {type_name}"),
+    }
+}
+
+/// What a worker is stuck on, if anything. A missing target outranks the
+/// other two, since the worker is not at a building to wait on.
+fn wait_label(for_items: bool, for_space: bool, for_target: bool) -> &'static str {
+    if for_target {
+        " (waiting for a target)"
+    } else if for_items {
+        " (waiting for items)"
+    } else if for_space {
+        " (waiting for space)"
+    } else {
+        ""
+    }
+}
+
+/// One block per workflow, in a stable order between refreshes.
+fn workflow_report(
+    workflows: &Query<(Entity, &Workflow)>,
+    assignments: &Query<AssignmentRow>,
+    names: &Query<&Name>,
+    positions: &Query<&Position>,
+    priorities: &Query<&LogisticsPriority>,
+    item_registry: &ItemRegistry,
+) -> String {
+    let mut workflows: Vec<(Entity, &Workflow)> = workflows.iter().collect();
+    workflows.sort_unstable_by_key(|(entity, _)| *entity);
+    if workflows.is_empty() {
+        return "No workflows".to_string();
+    }
+
+    let mut lines = Vec::new();
+    for (entity, workflow) in workflows {
+        let priority = workflow_priority(workflow, names, priorities);
+        let paused = if workflow.is_paused { " (paused)" } else { "" };
+        lines.push(format!("{} [{}]{paused}", workflow.name, priority.label()));
+
+        for (index, step) in workflow.steps.iter().enumerate() {
+            let target = target_label(&step.target, names, positions);
+            lines.push(format!(
+                "  {}. {}",
+                index + 1,
+                step.action.describe(&target, item_registry)
+            ));
+        }
+
+        let mut workers: Vec<_> = assignments
+            .iter()
+            .filter(|(_, assignment, ..)| assignment.workflow == entity)
+            .collect();
+        workers.sort_unstable_by_key(|(worker, ..)| *worker);
+        if workers.is_empty() {
+            lines.push("  unassigned".to_string());
+        }
+        for (worker, assignment, for_items, for_space, for_target) in workers {
+            let target = assignment
+                .resolved_target
+                .and_then(|target| positions.get(target).ok())
+                .map(|pos| format!(" -> {}", format_position(*pos)))
+                .unwrap_or_default();
+            let waiting = wait_label(for_items, for_space, for_target);
+            lines.push(format!(
+                "  worker {worker}: step {}{target}{waiting}",
+                assignment.current_step + 1
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn toggle_workflow_debug_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<WorkflowDebugPanel>,
+) {
+    if keyboard.just_pressed(KeyCode::F4) {
+        panel.enabled = !panel.enabled;
+    }
+}
+
+fn spawn_workflow_debug_panel(commands: &mut Commands, report: String, theme: &UiTheme) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(4.0),
+                top: Val::Px(TOP_BAR_HEIGHT + 4.0),
+                max_width: Val::Px(360.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            theme.background(ThemeRole::PanelBg),
+            theme.border(ThemeRole::PanelBorder),
+            ZIndex(50),
+            WorkflowDebugRoot,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(report),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                WorkflowDebugText,
+            ));
+        });
+}
+
+/// Spawns the panel filled in as soon as it is enabled, then refreshes its
+/// text on the timer until it is toggled off again.
+pub fn update_workflow_debug_panel(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut panel: ResMut<WorkflowDebugPanel>,
+    roots: Query<Entity, With<WorkflowDebugRoot>>,
+    mut texts: Query<&mut Text, With<WorkflowDebugText>>,
+    workflows: Query<(Entity, &Workflow)>,
+    assignments: Query<AssignmentRow>,
+    names: Query<&Name>,
+    positions: Query<&Position>,
+    priorities: Query<&LogisticsPriority>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
+) {
+    if !panel.enabled {
+        for root in &roots {
+            commands.entity(root).despawn();
+        }
+        return;
+    }
+
+    if roots.is_empty() {
+        panel.refresh.reset();
+        let report = workflow_report(
+            &workflows,
+            &assignments,
+            &names,
+            &positions,
+            &priorities,
+            &item_registry,
+        );
+        spawn_workflow_debug_panel(&mut commands, report, &theme);
+        return;
+    }
+
+    panel.refresh.tick(time.delta());
+    if !panel.refresh.just_finished() {
+        return;
+    }
+    let report = workflow_report(
+        &workflows,
+        &assignments,
+        &names,
+        &positions,
+        &priorities,
+        &item_registry,
+    );
+    for mut text in &mut texts {
+        text.0.clone_from(&report);
+    }
+}
+
+pub struct WorkflowDebugPlugin;
+
+impl Plugin for WorkflowDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorkflowDebugPanel>().add_systems(
+            Update,
+            (
+                toggle_workflow_debug_panel.in_set(UISystemSet::InputDetection),
+                update_workflow_debug_panel.in_set(UISystemSet::VisualUpdates),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use super::*;
+    use crate::workers::{WorkflowAction, WorkflowStep};
+
+    fn panel_text(app: &mut App) -> String {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<&Text, With<WorkflowDebugText>>();
+        query
+            .single(app.world())
+            .map(|text| text.0.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn panel_lists_workflows_and_their_workers() {
+        let mut app = App::new();
+        app.init_resource::<UiTheme>()
+            .init_resource::<Time>()
+            .init_resource::<ItemRegistry>()
+            .insert_resource(WorkflowDebugPanel {
+                enabled: true,
+                ..default()
+            })
+            .add_systems(Update, update_workflow_debug_panel);
+
+        let mine = app
+            .world_mut()
+            .spawn((
+                Name::new("Mining Drill"),
+                Position { x: 2, y: 3 },
+                LogisticsPriority::High,
+            ))
+            .id();
+        let storage = app
+            .world_mut()
+            .spawn((Name::new("Storage"), Position { x: 6, y: 3 }))
+            .id();
+        let workflow = app
+            .world_mut()
+            .spawn(Workflow {
+                name: "Ore Run".to_string(),
+                building_set: HashSet::from([mine, storage]),
+                steps: vec![
+                    WorkflowStep {
+                        target: StepTarget::Specific(mine),
+                        action: WorkflowAction::Pickup(None),
+                        condition: None,
+                    },
+                    WorkflowStep {
+                        target: StepTarget::ByType("Storage".to_string()),
+                        action: WorkflowAction::Dropoff(None),
+                        condition: None,
+                    },
+                ],
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: default(),
+                step_waits: default(),
+            })
+            .id();
+
+        app.update();
+        app.update();
+        let text = panel_text(&mut app);
+        assert!(text.contains("Ore Run [High]"), "{text}");
+        assert!(
+            text.contains("1. Pickup all from Mining Drill (2, 3)"),
+            "{text}"
+        );
+        assert!(text.contains("2. Dropoff all to any Storage"), "{text}");
+        assert!(text.contains("unassigned"), "{text}");
+
+        let worker = app
+            .world_mut()
+            .spawn(WorkflowAssignment {
+                workflow,
+                current_step: 0,
+                resolved_target: Some(mine),
+                resolved_action: None,
+            })
+            .id();
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(REFRESH_SECS));
+        app.update();
+        let text = panel_text(&mut app);
+        assert!(!text.contains("unassigned"), "{text}");
+        assert!(
+            text.contains(&format!("worker {worker}: step 1 -> (2, 3)")),
+            "{text}"
+        );
+
+        app.world_mut()
+            .entity_mut(worker)
+            .insert(WaitingForSpace::default());
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(REFRESH_SECS));
+        app.update();
+        let text = panel_text(&mut app);
+        assert!(
+            text.contains(&format!(
+                "worker {worker}: step 1 -> (2, 3) (waiting for space)"
+            )),
+            "{text}"
+        );
+
+        app.world_mut()
+            .entity_mut(worker)
+            .remove::<WaitingForSpace>()
+            .insert(WaitingForTarget { step: 0 });
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(REFRESH_SECS));
+        app.update();
+        let text = panel_text(&mut app);
+        assert!(text.contains("(waiting for a target)"), "{text}");
+    }
+}
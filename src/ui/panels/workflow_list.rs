@@ -4,24 +4,99 @@ use bevy::picking::hover::Hovered;
 use bevy::prelude::*;
 
 use crate::{
+    materials::ItemRegistry,
     ui::{
         panels::action_bar::ActivePanel,
-        style::{
-            ButtonStyle, ACTION_BAR_WIDTH, BUTTON_BG, CARD_BG, CONFIRM_BG, DIM_TEXT, HEADER_COLOR,
-            PANEL_BG, PANEL_BORDER, TEXT_COLOR, TOP_BAR_HEIGHT, WARNING_COLOR,
-        },
+        style::{ButtonStyle, ThemeRole, UiTheme, ACTION_BAR_WIDTH, TOP_BAR_HEIGHT},
+        worker_groups::SelectedWorkers,
         UISystemSet,
     },
     workers::{
-        workflows::components::{
-            AssignWorkersEvent, DeleteWorkflowEvent, PauseWorkflowEvent, StepTarget,
-            UnassignWorkersEvent, WaitingForItems, WaitingForSpace, Workflow, WorkflowAction,
-            WorkflowAssignment, WorkflowRegistry,
+        workflows::{
+            components::{
+                AssignWorkersEvent, AutoStaffConfig, DedicateWorkflowEvent, DeleteWorkflowEvent,
+                PauseWorkflowEvent, StepTarget, UnassignWorkersEvent, WaitingForItems,
+                WaitingForSpace, WaitingForTarget, Workflow, WorkflowAssignment, WorkflowConfig,
+                WorkflowRegistry,
+            },
+            templates::{SaveWorkflowTemplateEvent, WorkflowTemplateLibrary},
+            throughput::WorkflowThroughput,
         },
         Worker,
     },
 };
 
+/// The workflow picked by clicking its card. The top bar shows quick worker
+/// controls for it.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SelectedWorkflow(pub Option<Entity>);
+
+/// How many workers a workflow has, how many of them are stuck waiting, and
+/// how many it wants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkflowStaffing {
+    pub current: u32,
+    pub waiting: u32,
+    pub desired: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowHealth {
+    /// Every workflow is fully staffed and nobody is waiting.
+    Healthy,
+    /// Fully staffed, but some workers are waiting for items or space.
+    Waiting,
+    /// At least one workflow has fewer workers than it wants.
+    Understaffed,
+}
+
+impl WorkflowHealth {
+    #[must_use]
+    pub fn color(self, theme: &UiTheme) -> Color {
+        match self {
+            Self::Healthy => theme.success_color,
+            Self::Waiting => theme.warning_color,
+            Self::Understaffed => theme.danger_color,
+        }
+    }
+}
+
+/// Totals shown in the row above the workflow cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkflowSummary {
+    pub workflows: u32,
+    pub assigned: u32,
+    pub waiting: u32,
+    pub health: WorkflowHealth,
+}
+
+impl WorkflowSummary {
+    #[must_use]
+    pub fn from_staffing<'a>(staffing: impl IntoIterator<Item = &'a WorkflowStaffing>) -> Self {
+        let mut summary = Self {
+            workflows: 0,
+            assigned: 0,
+            waiting: 0,
+            health: WorkflowHealth::Healthy,
+        };
+        let mut understaffed = false;
+        for staffing in staffing {
+            summary.workflows += 1;
+            summary.assigned += staffing.current;
+            summary.waiting += staffing.waiting;
+            understaffed |= staffing.current < staffing.desired;
+        }
+        summary.health = if understaffed {
+            WorkflowHealth::Understaffed
+        } else if summary.waiting > 0 {
+            WorkflowHealth::Waiting
+        } else {
+            WorkflowHealth::Healthy
+        };
+        summary
+    }
+}
+
 #[derive(Component)]
 pub struct WorkflowPanel;
 
@@ -38,6 +113,11 @@ pub struct WorkflowPauseButton {
     pub workflow: Entity,
 }
 
+#[derive(Component)]
+pub struct WorkflowDedicateButton {
+    pub workflow: Entity,
+}
+
 #[derive(Component)]
 pub struct WorkflowDeleteButton {
     pub workflow: Entity,
@@ -69,8 +149,30 @@ pub struct WorkflowPanelCloseButton;
 #[derive(Component)]
 pub struct NewWorkflowButton;
 
+#[derive(Component)]
+pub struct NewFromTemplateButton;
+
+#[derive(Component)]
+pub struct AutoStaffToggleButton;
+
+#[derive(Component)]
+pub struct AutoStaffToggleText;
+
+#[derive(Component)]
+pub struct TemplatePicker;
+
+#[derive(Component)]
+pub struct TemplateOption {
+    pub template_name: String,
+}
+
+#[derive(Component)]
+pub struct WorkflowSaveTemplateButton {
+    pub workflow: Entity,
+}
+
 #[allow(clippy::too_many_lines)]
-pub fn spawn_workflow_panel(commands: &mut Commands) {
+pub fn spawn_workflow_panel(commands: &mut Commands, theme: &UiTheme) {
     commands
         .spawn((
             Node {
@@ -86,8 +188,8 @@ pub fn spawn_workflow_panel(commands: &mut Commands) {
                 row_gap: Val::Px(6.0),
                 ..default()
             },
-            BackgroundColor(PANEL_BG),
-            BorderColor::all(PANEL_BORDER),
+            theme.background(ThemeRole::PanelBg),
+            theme.border(ThemeRole::PanelBorder),
             Interaction::None,
             WorkflowPanel,
         ))
@@ -108,7 +210,7 @@ pub fn spawn_workflow_panel(commands: &mut Commands) {
                             font_size: 16.0,
                             ..default()
                         },
-                        TextColor(HEADER_COLOR),
+                        theme.text(ThemeRole::Header),
                     ));
 
                     header
@@ -129,8 +231,8 @@ pub fn spawn_workflow_panel(commands: &mut Commands) {
                                         align_items: AlignItems::Center,
                                         ..default()
                                     },
-                                    BackgroundColor(CONFIRM_BG),
-                                    ButtonStyle::confirm(),
+                                    theme.background(ThemeRole::Confirm),
+                                    ButtonStyle::confirm(theme),
                                     Hovered::default(),
                                     NewWorkflowButton,
                                 ))
@@ -141,7 +243,60 @@ pub fn spawn_workflow_panel(commands: &mut Commands) {
                                             font_size: 11.0,
                                             ..default()
                                         },
-                                        TextColor(TEXT_COLOR),
+                                        theme.text(ThemeRole::Text),
+                                    ));
+                                });
+
+                            right
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        height: Val::Px(24.0),
+                                        padding: UiRect::horizontal(Val::Px(8.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    theme.background(ThemeRole::Button),
+                                    ButtonStyle::default_button(theme),
+                                    Hovered::default(),
+                                    NewFromTemplateButton,
+                                ))
+                                .with_children(|btn| {
+                                    btn.spawn((
+                                        Text::new("From Template"),
+                                        TextFont {
+                                            font_size: 11.0,
+                                            ..default()
+                                        },
+                                        theme.text(ThemeRole::Text),
+                                    ));
+                                });
+
+                            right
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        height: Val::Px(24.0),
+                                        padding: UiRect::horizontal(Val::Px(8.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    theme.background(ThemeRole::Button),
+                                    ButtonStyle::default_button(theme),
+                                    Hovered::default(),
+                                    AutoStaffToggleButton,
+                                ))
+                                .with_children(|btn| {
+                                    btn.spawn((
+                                        Text::new(auto_staff_label(false)),
+                                        TextFont {
+                                            font_size: 11.0,
+                                            ..default()
+                                        },
+                                        theme.text(ThemeRole::Text),
+                                        AutoStaffToggleText,
                                     ));
                                 });
 
@@ -155,8 +310,8 @@ pub fn spawn_workflow_panel(commands: &mut Commands) {
                                         align_items: AlignItems::Center,
                                         ..default()
                                     },
-                                    BackgroundColor(BUTTON_BG),
-                                    ButtonStyle::close(),
+                                    theme.background(ThemeRole::Button),
+                                    ButtonStyle::close(theme),
                                     Hovered::default(),
                                     WorkflowPanelCloseButton,
                                 ))
@@ -167,7 +322,7 @@ pub fn spawn_workflow_panel(commands: &mut Commands) {
                                             font_size: 12.0,
                                             ..default()
                                         },
-                                        TextColor(TEXT_COLOR),
+                                        theme.text(ThemeRole::Text),
                                     ));
                                 });
                         });
@@ -194,15 +349,13 @@ fn handle_workflow_panel_buttons(
     mut active_panel: ResMut<ActivePanel>,
     close_buttons: Query<&Interaction, (Changed<Interaction>, With<WorkflowPanelCloseButton>)>,
     pause_buttons: Query<(&Interaction, &WorkflowPauseButton), Changed<Interaction>>,
+    dedicate_buttons: Query<(&Interaction, &WorkflowDedicateButton), Changed<Interaction>>,
+    save_template_buttons: Query<(&Interaction, &WorkflowSaveTemplateButton), Changed<Interaction>>,
     delete_buttons: Query<(&Interaction, &WorkflowDeleteButton), Changed<Interaction>>,
-    add_buttons: Query<(&Interaction, &WorkflowWorkerAddButton), Changed<Interaction>>,
-    remove_buttons: Query<(&Interaction, &WorkflowWorkerRemoveButton), Changed<Interaction>>,
     mut pause_events: MessageWriter<PauseWorkflowEvent>,
+    mut dedicate_events: MessageWriter<DedicateWorkflowEvent>,
+    mut save_template_events: MessageWriter<SaveWorkflowTemplateEvent>,
     mut delete_events: MessageWriter<DeleteWorkflowEvent>,
-    mut assign_events: MessageWriter<AssignWorkersEvent>,
-    mut unassign_events: MessageWriter<UnassignWorkersEvent>,
-    idle_workers: Query<Entity, (With<Worker>, Without<WorkflowAssignment>)>,
-    assigned_workers: Query<(Entity, &WorkflowAssignment), With<Worker>>,
 ) {
     for interaction in &close_buttons {
         if *interaction == Interaction::Pressed {
@@ -219,6 +372,22 @@ fn handle_workflow_panel_buttons(
         }
     }
 
+    for (interaction, btn) in &dedicate_buttons {
+        if *interaction == Interaction::Pressed {
+            dedicate_events.write(DedicateWorkflowEvent {
+                workflow: btn.workflow,
+            });
+        }
+    }
+
+    for (interaction, btn) in &save_template_buttons {
+        if *interaction == Interaction::Pressed {
+            save_template_events.write(SaveWorkflowTemplateEvent {
+                workflow: btn.workflow,
+            });
+        }
+    }
+
     for (interaction, btn) in &delete_buttons {
         if *interaction == Interaction::Pressed {
             delete_events.write(DeleteWorkflowEvent {
@@ -226,34 +395,105 @@ fn handle_workflow_panel_buttons(
             });
         }
     }
+}
 
+fn handle_card_worker_buttons(
+    add_buttons: Query<(&Interaction, &WorkflowWorkerAddButton), Changed<Interaction>>,
+    remove_buttons: Query<(&Interaction, &WorkflowWorkerRemoveButton), Changed<Interaction>>,
+    mut selected_workers: ResMut<SelectedWorkers>,
+    idle_workers: Query<Entity, (With<Worker>, Without<WorkflowAssignment>)>,
+    assigned_workers: Query<(Entity, &WorkflowAssignment), With<Worker>>,
+    mut assign_events: MessageWriter<AssignWorkersEvent>,
+    mut unassign_events: MessageWriter<UnassignWorkersEvent>,
+) {
     for (interaction, btn) in &add_buttons {
         if *interaction == Interaction::Pressed {
-            if let Some(worker) = idle_workers.iter().next() {
-                assign_events.write(AssignWorkersEvent {
-                    workflow: btn.workflow,
-                    workers: vec![worker],
-                });
-            }
+            assign_workers(
+                btn.workflow,
+                &mut selected_workers,
+                &idle_workers,
+                &mut assign_events,
+            );
         }
     }
 
     for (interaction, btn) in &remove_buttons {
         if *interaction == Interaction::Pressed {
-            let worker = assigned_workers
-                .iter()
-                .find(|(_, assignment)| assignment.workflow == btn.workflow)
-                .map(|(entity, _)| entity);
-
-            if let Some(worker_entity) = worker {
-                unassign_events.write(UnassignWorkersEvent {
-                    workers: vec![worker_entity],
-                });
-            }
+            unassign_one_worker(btn.workflow, &assigned_workers, &mut unassign_events);
         }
     }
 }
 
+/// Assigns the selected workers to `workflow`, or one idle worker when no
+/// workers are selected. The selection is used up, so the next press adds
+/// one idle worker again; saved groups keep it for recall.
+pub(crate) fn assign_workers(
+    workflow: Entity,
+    selected_workers: &mut SelectedWorkers,
+    idle_workers: &Query<Entity, (With<Worker>, Without<WorkflowAssignment>)>,
+    assign_events: &mut MessageWriter<AssignWorkersEvent>,
+) {
+    if !selected_workers.workers.is_empty() {
+        assign_events.write(AssignWorkersEvent {
+            workflow,
+            workers: selected_workers.workers.drain().collect(),
+        });
+        return;
+    }
+    if let Some(worker) = idle_workers.iter().next() {
+        assign_events.write(AssignWorkersEvent {
+            workflow,
+            workers: vec![worker],
+        });
+    }
+}
+
+/// Unassigns one of `workflow`'s workers, if it has any.
+pub(crate) fn unassign_one_worker(
+    workflow: Entity,
+    assigned_workers: &Query<(Entity, &WorkflowAssignment), With<Worker>>,
+    unassign_events: &mut MessageWriter<UnassignWorkersEvent>,
+) {
+    let worker = assigned_workers
+        .iter()
+        .find(|(_, assignment)| assignment.workflow == workflow)
+        .map(|(entity, _)| entity);
+
+    if let Some(worker) = worker {
+        unassign_events.write(UnassignWorkersEvent {
+            workers: vec![worker],
+        });
+    }
+}
+
+/// Clicking a card selects its workflow; clicking the selected card again
+/// clears the selection. Card buttons block the click from reaching the card.
+pub(crate) fn select_workflow_card(
+    cards: Query<(&Interaction, &WorkflowEntry), Changed<Interaction>>,
+    mut selected: ResMut<SelectedWorkflow>,
+) {
+    for (interaction, entry) in &cards {
+        if *interaction == Interaction::Pressed {
+            selected.0 = (selected.0 != Some(entry.workflow)).then_some(entry.workflow);
+        }
+    }
+}
+
+fn highlight_selected_workflow_card(
+    selected: Res<SelectedWorkflow>,
+    mut cards: Query<(&WorkflowEntry, &mut BorderColor)>,
+    theme: Res<UiTheme>,
+) {
+    for (entry, mut border) in &mut cards {
+        let color = if selected.0 == Some(entry.workflow) {
+            theme.selected_border
+        } else {
+            theme.panel_border
+        };
+        border.set_all(color);
+    }
+}
+
 fn handle_edit_workflow_button(
     mut commands: Commands,
     edit_buttons: Query<(&Interaction, &WorkflowEditButton), Changed<Interaction>>,
@@ -268,6 +508,7 @@ fn handle_edit_workflow_button(
                 state.name.clone_from(&workflow.name);
                 state.building_set.clone_from(&workflow.building_set);
                 state.steps.clone_from(&workflow.steps);
+                state.is_dedicated = workflow.is_dedicated;
                 state.desired_worker_count = workflow.desired_worker_count;
                 state.phase = crate::ui::modes::workflow_create::CreationPhase::BuilderModal;
                 state.editing = Some(btn.workflow);
@@ -283,6 +524,36 @@ fn handle_edit_workflow_button(
     }
 }
 
+/// Card warning naming the steps whose workers are held because no building
+/// in the pool matches their type, e.g. "No target for step 2".
+fn no_target_warning(steps: &[usize]) -> Option<String> {
+    let mut steps: Vec<usize> = steps.iter().map(|step| step + 1).collect();
+    steps.sort_unstable();
+    steps.dedup();
+    match steps.as_slice() {
+        [] => None,
+        [step] => Some(format!("No target for step {step}")),
+        _ => {
+            let listed: Vec<String> = steps.iter().map(ToString::to_string).collect();
+            Some(format!("No target for steps {}", listed.join(", ")))
+        }
+    }
+}
+
+/// Flags a pickup step that keeps finding nothing, e.g. "12 waits".
+fn step_wait_label(waits: u32) -> String {
+    if waits == 1 {
+        "1 wait".to_string()
+    } else {
+        format!("{waits} waits")
+    }
+}
+
+/// Items the workflow's workers delivered over the last minute.
+fn throughput_label(items_per_minute: u32) -> String {
+    format!("Moved: {items_per_minute} items/min")
+}
+
 fn update_workflow_panel_content(
     mut commands: Commands,
     list_containers: Query<Entity, With<WorkflowListContainer>>,
@@ -293,10 +564,14 @@ fn update_workflow_panel_content(
             &WorkflowAssignment,
             Has<WaitingForItems>,
             Has<WaitingForSpace>,
+            Option<&WaitingForTarget>,
         ),
         With<Worker>,
     >,
     names: Query<&Name>,
+    throughput: Res<WorkflowThroughput>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
 ) {
     for container in &list_containers {
         commands.entity(container).despawn_related::<Children>();
@@ -309,7 +584,7 @@ fn update_workflow_panel_content(
                         font_size: 12.0,
                         ..default()
                     },
-                    TextColor(DIM_TEXT),
+                    theme.text(ThemeRole::DimText),
                     Node {
                         margin: UiRect::top(Val::Px(8.0)),
                         ..default()
@@ -319,43 +594,94 @@ fn update_workflow_panel_content(
             continue;
         }
 
-        commands.entity(container).with_children(|parent| {
-            for &workflow_entity in &registry.workflows {
-                let Ok(workflow) = workflows.get(workflow_entity) else {
-                    continue;
+        let staffed: Vec<(Entity, &Workflow, WorkflowStaffing, Vec<usize>)> = registry
+            .workflows
+            .iter()
+            .filter_map(|&entity| {
+                let workflow = workflows.get(entity).ok()?;
+                let mut staffing = WorkflowStaffing {
+                    desired: workflow.desired_worker_count,
+                    ..default()
                 };
-
-                let mut current_workers = 0u32;
-                let mut waiting_workers = 0u32;
-                for (assignment, is_waiting_items, is_waiting_space) in &assigned_workers {
-                    if assignment.workflow == workflow_entity {
-                        current_workers += 1;
-                        if is_waiting_items || is_waiting_space {
-                            waiting_workers += 1;
+                let mut untargeted = Vec::new();
+                for (assignment, is_waiting_items, is_waiting_space, waiting_target) in
+                    &assigned_workers
+                {
+                    if assignment.workflow == entity {
+                        staffing.current += 1;
+                        if is_waiting_items || is_waiting_space || waiting_target.is_some() {
+                            staffing.waiting += 1;
                         }
+                        untargeted.extend(waiting_target.map(|waiting| waiting.step));
                     }
                 }
+                Some((entity, workflow, staffing, untargeted))
+            })
+            .collect();
+        let summary =
+            WorkflowSummary::from_staffing(staffed.iter().map(|(_, _, staffing, _)| staffing));
 
+        commands.entity(container).with_children(|parent| {
+            spawn_summary_row(parent, summary, &theme);
+            for (workflow_entity, workflow, staffing, untargeted) in &staffed {
                 spawn_workflow_card(
                     parent,
-                    workflow_entity,
+                    *workflow_entity,
                     workflow,
-                    current_workers,
-                    waiting_workers,
+                    *staffing,
+                    throughput.items_per_minute(*workflow_entity),
+                    no_target_warning(untargeted),
                     &names,
+                    &item_registry,
+                    &theme,
                 );
             }
         });
     }
 }
 
+fn spawn_summary_row(parent: &mut ChildSpawnerCommands, summary: WorkflowSummary, theme: &UiTheme) {
+    parent
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(6.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Node {
+                    width: Val::Px(10.0),
+                    height: Val::Px(10.0),
+                    ..default()
+                },
+                BackgroundColor(summary.health.color(theme)),
+            ));
+            row.spawn((
+                Text::new(format!(
+                    "{} workflows, {} workers, {} waiting",
+                    summary.workflows, summary.assigned, summary.waiting
+                )),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+            ));
+        });
+}
+
 fn spawn_workflow_card(
     parent: &mut ChildSpawnerCommands,
     workflow_entity: Entity,
     workflow: &Workflow,
-    current_workers: u32,
-    waiting_workers: u32,
+    staffing: WorkflowStaffing,
+    items_per_minute: u32,
+    warning: Option<String>,
     names: &Query<&Name>,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
     parent
         .spawn((
@@ -367,27 +693,47 @@ fn spawn_workflow_card(
                 row_gap: Val::Px(4.0),
                 ..default()
             },
-            BackgroundColor(CARD_BG),
-            BorderColor::all(PANEL_BORDER),
+            Button,
+            theme.background(ThemeRole::CardBg),
+            theme.border(ThemeRole::PanelBorder),
             WorkflowEntry {
                 workflow: workflow_entity,
             },
         ))
         .with_children(|card| {
-            spawn_card_header(card, workflow);
+            spawn_card_header(card, workflow, theme);
             spawn_card_details(
                 card,
                 workflow_entity,
                 workflow,
-                current_workers,
-                waiting_workers,
+                staffing,
                 names,
+                item_registry,
+                theme,
             );
-            spawn_card_buttons(card, workflow_entity, workflow.is_paused);
+            card.spawn((
+                Text::new(throughput_label(items_per_minute)),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::DimText),
+            ));
+            if let Some(warning) = warning {
+                card.spawn((
+                    Text::new(warning),
+                    TextFont {
+                        font_size: 11.0,
+                        ..default()
+                    },
+                    theme.text(ThemeRole::Warning),
+                ));
+            }
+            spawn_card_buttons(card, workflow_entity, workflow, theme);
         });
 }
 
-fn spawn_card_header(card: &mut ChildSpawnerCommands, workflow: &Workflow) {
+fn spawn_card_header(card: &mut ChildSpawnerCommands, workflow: &Workflow, theme: &UiTheme) {
     card.spawn(Node {
         width: Val::Percent(100.0),
         flex_direction: FlexDirection::Row,
@@ -402,7 +748,7 @@ fn spawn_card_header(card: &mut ChildSpawnerCommands, workflow: &Workflow) {
                 font_size: 14.0,
                 ..default()
             },
-            TextColor(HEADER_COLOR),
+            theme.text(ThemeRole::Header),
         ));
 
         if workflow.is_paused {
@@ -412,7 +758,7 @@ fn spawn_card_header(card: &mut ChildSpawnerCommands, workflow: &Workflow) {
                     font_size: 11.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.9, 0.7, 0.2)),
+                theme.text(ThemeRole::Warning),
             ));
         }
     });
@@ -422,10 +768,16 @@ fn spawn_card_details(
     card: &mut ChildSpawnerCommands,
     workflow_entity: Entity,
     workflow: &Workflow,
-    current_workers: u32,
-    waiting_workers: u32,
+    staffing: WorkflowStaffing,
     names: &Query<&Name>,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
 ) {
+    let WorkflowStaffing {
+        current: current_workers,
+        waiting: waiting_workers,
+        ..
+    } = staffing;
     let pool_summary = build_pool_summary(&workflow.building_set, names);
     card.spawn((
         Text::new(format!("Buildings: {pool_summary}")),
@@ -433,7 +785,7 @@ fn spawn_card_details(
             font_size: 11.0,
             ..default()
         },
-        TextColor(DIM_TEXT),
+        theme.text(ThemeRole::DimText),
     ));
 
     let step_details: Vec<String> = workflow
@@ -441,17 +793,21 @@ fn spawn_card_details(
         .iter()
         .enumerate()
         .map(|(i, step)| {
-            let action_label = match &step.action {
-                WorkflowAction::Pickup(_) => "Pickup from",
-                WorkflowAction::Dropoff(_) => "Dropoff to",
-            };
             let target_label = match &step.target {
                 StepTarget::Specific(entity) => names
                     .get(*entity)
                     .map_or_else(|_| "???".to_string(), |n| n.as_str().to_string()),
                 StepTarget::ByType(type_name) => format!("any {type_name}"),
             };
-            format!("  {}. {} {}", i + 1, action_label, target_label)
+            let line = format!(
+                "  {}. {}",
+                i + 1,
+                step.action.describe(&target_label, item_registry)
+            );
+            match workflow.step_waits.get(&i) {
+                Some(&waits) => format!("{line} ({})", step_wait_label(waits)),
+                None => line,
+            }
         })
         .collect();
 
@@ -465,18 +821,18 @@ fn spawn_card_details(
             font_size: 11.0,
             ..default()
         },
-        TextColor(DIM_TEXT),
+        theme.text(ThemeRole::DimText),
         WorkflowDetailText {
             workflow: workflow_entity,
         },
     ));
 
     let worker_color = if current_workers >= workflow.desired_worker_count {
-        Color::srgb(0.3, 0.8, 0.3)
+        theme.success_color
     } else if waiting_workers > 0 {
-        WARNING_COLOR
+        theme.warning_color
     } else {
-        TEXT_COLOR
+        theme.text_color
     };
 
     let worker_text = if waiting_workers > 0 {
@@ -530,7 +886,12 @@ fn build_pool_summary(
         .join(", ")
 }
 
-fn spawn_card_buttons(card: &mut ChildSpawnerCommands, workflow_entity: Entity, is_paused: bool) {
+fn spawn_card_buttons(
+    card: &mut ChildSpawnerCommands,
+    workflow_entity: Entity,
+    workflow: &Workflow,
+    theme: &UiTheme,
+) {
     card.spawn(Node {
         width: Val::Percent(100.0),
         flex_direction: FlexDirection::Row,
@@ -539,47 +900,79 @@ fn spawn_card_buttons(card: &mut ChildSpawnerCommands, workflow_entity: Entity,
         ..default()
     })
     .with_children(|button_row| {
-        let pause_label = if is_paused { "Resume" } else { "Pause" };
+        let pause_label = if workflow.is_paused {
+            "Resume"
+        } else {
+            "Pause"
+        };
+        let dedicate_label = if workflow.is_dedicated {
+            "Release"
+        } else {
+            "Dedicate"
+        };
 
         spawn_panel_button(
             button_row,
             pause_label,
-            ButtonStyle::confirm(),
+            ButtonStyle::confirm(theme),
             WorkflowPauseButton {
                 workflow: workflow_entity,
             },
+            theme,
+        );
+        spawn_panel_button(
+            button_row,
+            dedicate_label,
+            ButtonStyle::default_button(theme),
+            WorkflowDedicateButton {
+                workflow: workflow_entity,
+            },
+            theme,
         );
         spawn_panel_button(
             button_row,
             "Delete",
-            ButtonStyle::cancel(),
+            ButtonStyle::cancel(theme),
             WorkflowDeleteButton {
                 workflow: workflow_entity,
             },
+            theme,
         );
         spawn_panel_button(
             button_row,
             "Edit",
-            ButtonStyle::default_button(),
+            ButtonStyle::default_button(theme),
             WorkflowEditButton {
                 workflow: workflow_entity,
             },
+            theme,
+        );
+        spawn_panel_button(
+            button_row,
+            "Save",
+            ButtonStyle::default_button(theme),
+            WorkflowSaveTemplateButton {
+                workflow: workflow_entity,
+            },
+            theme,
         );
         spawn_panel_button(
             button_row,
             "+W",
-            ButtonStyle::default_button(),
+            ButtonStyle::default_button(theme),
             WorkflowWorkerAddButton {
                 workflow: workflow_entity,
             },
+            theme,
         );
         spawn_panel_button(
             button_row,
             "-W",
-            ButtonStyle::default_button(),
+            ButtonStyle::default_button(theme),
             WorkflowWorkerRemoveButton {
                 workflow: workflow_entity,
             },
+            theme,
         );
     });
 }
@@ -589,6 +982,7 @@ fn spawn_panel_button(
     label: &str,
     style: ButtonStyle,
     marker: impl Component,
+    theme: &UiTheme,
 ) {
     parent
         .spawn((
@@ -613,11 +1007,43 @@ fn spawn_panel_button(
                     font_size: 11.0,
                     ..default()
                 },
-                TextColor(TEXT_COLOR),
+                theme.text(ThemeRole::Text),
             ));
         });
 }
 
+fn auto_staff_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Auto: On"
+    } else {
+        "Auto: Off"
+    }
+}
+
+fn handle_auto_staff_toggle(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<AutoStaffToggleButton>)>,
+    mut config: ResMut<AutoStaffConfig>,
+) {
+    for interaction in &buttons {
+        if *interaction == Interaction::Pressed {
+            config.enabled = !config.enabled;
+        }
+    }
+}
+
+fn update_auto_staff_label(
+    config: Res<AutoStaffConfig>,
+    added: Query<(), Added<AutoStaffToggleText>>,
+    mut labels: Query<&mut Text, With<AutoStaffToggleText>>,
+) {
+    if !config.is_changed() && added.is_empty() {
+        return;
+    }
+    for mut text in &mut labels {
+        **text = auto_staff_label(config.enabled).to_string();
+    }
+}
+
 fn handle_new_workflow_button(
     new_buttons: Query<&Interaction, (Changed<Interaction>, With<NewWorkflowButton>)>,
     mut state: ResMut<crate::ui::modes::workflow_create::WorkflowCreationState>,
@@ -625,12 +1051,15 @@ fn handle_new_workflow_button(
     mut next_mode: ResMut<NextState<crate::ui::UiMode>>,
     mut commands: Commands,
     existing_panels: Query<Entity, With<crate::ui::modes::workflow_create::WorkflowCreationPanel>>,
+    theme: Res<UiTheme>,
 ) {
     for interaction in &new_buttons {
         if *interaction == Interaction::Pressed {
             counter.count += 1;
             state.name = format!("Workflow {}", counter.count);
             state.steps.clear();
+            state.template_conditions.clear();
+            state.is_dedicated = false;
             state.desired_worker_count = 1;
             state.building_set.clear();
             state.phase = crate::ui::modes::workflow_create::CreationPhase::SelectBuildings;
@@ -639,27 +1068,272 @@ fn handle_new_workflow_button(
                 commands.entity(entity).despawn();
             }
 
-            crate::ui::modes::workflow_create::spawn_creation_panel(&mut commands, &state);
+            crate::ui::modes::workflow_create::spawn_creation_panel(&mut commands, &state, &theme);
             next_mode.set(crate::ui::UiMode::WorkflowCreate);
             return;
         }
     }
 }
 
+fn toggle_template_picker(
+    mut commands: Commands,
+    template_buttons: Query<&Interaction, (Changed<Interaction>, With<NewFromTemplateButton>)>,
+    pickers: Query<Entity, With<TemplatePicker>>,
+    panels: Query<Entity, With<WorkflowPanel>>,
+    library: Res<WorkflowTemplateLibrary>,
+    theme: Res<UiTheme>,
+) {
+    if !template_buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+
+    if !pickers.is_empty() {
+        for entity in &pickers {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let Ok(panel) = panels.single() else {
+        return;
+    };
+
+    let picker = commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            theme.background(ThemeRole::CardBg),
+            theme.border(ThemeRole::PanelBorder),
+            TemplatePicker,
+        ))
+        .with_children(|picker| {
+            if library.templates.is_empty() {
+                picker.spawn((
+                    Text::new("No saved templates. Use Save on a workflow."),
+                    TextFont {
+                        font_size: 11.0,
+                        ..default()
+                    },
+                    theme.text(ThemeRole::DimText),
+                ));
+                return;
+            }
+
+            for template in &library.templates {
+                spawn_panel_button(
+                    picker,
+                    &format!("{} ({} steps)", template.name, template.steps.len()),
+                    ButtonStyle::default_button(&theme),
+                    TemplateOption {
+                        template_name: template.name.clone(),
+                    },
+                    &theme,
+                );
+            }
+        })
+        .id();
+
+    commands.entity(panel).insert_children(1, &[picker]);
+}
+
+fn handle_template_selection(
+    mut commands: Commands,
+    options: Query<(&Interaction, &TemplateOption), Changed<Interaction>>,
+    library: Res<WorkflowTemplateLibrary>,
+    workflow_config: Res<WorkflowConfig>,
+    mut state: ResMut<crate::ui::modes::workflow_create::WorkflowCreationState>,
+    mut next_mode: ResMut<NextState<crate::ui::UiMode>>,
+    existing_panels: Query<Entity, With<crate::ui::modes::workflow_create::WorkflowCreationPanel>>,
+    pickers: Query<Entity, With<TemplatePicker>>,
+    theme: Res<UiTheme>,
+) {
+    for (interaction, option) in &options {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(template) = library.get(&option.template_name) else {
+            continue;
+        };
+
+        state.apply_template(template, &workflow_config);
+
+        for entity in existing_panels.iter().chain(pickers.iter()) {
+            commands.entity(entity).despawn();
+        }
+
+        crate::ui::modes::workflow_create::spawn_creation_panel(&mut commands, &state, &theme);
+        next_mode.set(crate::ui::UiMode::WorkflowCreate);
+        return;
+    }
+}
+
 pub struct WorkflowListPlugin;
 
 impl Plugin for WorkflowListPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<SelectedWorkflow>().add_systems(
             Update,
             (
-                handle_workflow_panel_buttons.in_set(UISystemSet::EntityManagement),
+                (
+                    handle_workflow_panel_buttons,
+                    handle_card_worker_buttons,
+                    select_workflow_card,
+                )
+                    .in_set(UISystemSet::EntityManagement),
                 handle_edit_workflow_button.in_set(UISystemSet::EntityManagement),
-                handle_new_workflow_button.in_set(UISystemSet::EntityManagement),
-                (update_workflow_panel_content,)
+                (handle_new_workflow_button, handle_auto_staff_toggle)
+                    .in_set(UISystemSet::EntityManagement),
+                (toggle_template_picker, handle_template_selection)
+                    .in_set(UISystemSet::EntityManagement),
+                (
+                    update_workflow_panel_content,
+                    highlight_selected_workflow_card,
+                )
+                    .chain()
                     .in_set(UISystemSet::VisualUpdates)
                     .run_if(|active: Res<ActivePanel>| *active == ActivePanel::Workflows),
+                update_auto_staff_label.in_set(UISystemSet::VisualUpdates),
             ),
         );
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_totals_workers_and_flags_understaffing() {
+        let staffing = [
+            WorkflowStaffing {
+                current: 2,
+                waiting: 0,
+                desired: 2,
+            },
+            WorkflowStaffing {
+                current: 1,
+                waiting: 1,
+                desired: 3,
+            },
+        ];
+
+        let summary = WorkflowSummary::from_staffing(&staffing);
+
+        assert_eq!(summary.workflows, 2);
+        assert_eq!(summary.assigned, 3);
+        assert_eq!(summary.waiting, 1);
+        assert_eq!(summary.health, WorkflowHealth::Understaffed);
+        assert_ne!(
+            summary.health.color(&UiTheme::dark()),
+            UiTheme::dark().success_color
+        );
+
+        let healthy = WorkflowSummary::from_staffing(&staffing[..1]);
+        assert_eq!(healthy.health, WorkflowHealth::Healthy);
+    }
+
+    #[test]
+    fn card_shows_items_moved_over_the_last_minute() {
+        use crate::materials::ItemTransferEvent;
+        use crate::workers::workflows::throughput::record_workflow_deliveries;
+        use std::collections::HashSet;
+        use std::time::Duration;
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<UiTheme>()
+            .init_resource::<WorkflowThroughput>()
+            .insert_resource(ItemRegistry::from_ron("[]").unwrap())
+            .add_message::<ItemTransferEvent>()
+            .add_systems(
+                Update,
+                (record_workflow_deliveries, update_workflow_panel_content).chain(),
+            );
+
+        let workflow = app
+            .world_mut()
+            .spawn(Workflow {
+                name: "ore run".to_string(),
+                building_set: HashSet::new(),
+                steps: Vec::new(),
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+        app.insert_resource(WorkflowRegistry {
+            workflows: vec![workflow],
+        });
+        let worker = app
+            .world_mut()
+            .spawn((
+                Worker,
+                WorkflowAssignment {
+                    workflow,
+                    current_step: 0,
+                    resolved_target: None,
+                    resolved_action: None,
+                },
+            ))
+            .id();
+        let smelter = app.world_mut().spawn_empty().id();
+        app.world_mut().spawn(WorkflowListContainer);
+
+        let ore = crate::materials::ItemId::named("Iron Ore").unwrap();
+        let advance = |app: &mut App, secs: u64, delivered: u32| {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(secs));
+            if delivered > 0 {
+                app.world_mut().write_message(ItemTransferEvent {
+                    sender: worker,
+                    receiver: smelter,
+                    items_transferred: HashMap::from([(ore, delivered)]),
+                });
+            }
+            app.update();
+        };
+        let card_rate = |app: &mut App| {
+            let mut texts = app.world_mut().query::<&Text>();
+            texts
+                .iter(app.world())
+                .find(|text| text.0.starts_with("Moved:"))
+                .map(|text| text.0.clone())
+                .unwrap()
+        };
+
+        for _ in 0..6 {
+            advance(&mut app, 10, 5);
+        }
+        assert_eq!(card_rate(&mut app), "Moved: 30 items/min");
+
+        // A minute later every delivery has left the window.
+        advance(&mut app, 60, 0);
+        assert_eq!(card_rate(&mut app), "Moved: 0 items/min");
+    }
+
+    #[test]
+    fn card_warns_about_steps_without_a_target() {
+        assert_eq!(no_target_warning(&[]), None);
+        assert_eq!(
+            no_target_warning(&[1, 1]).as_deref(),
+            Some("No target for step 2")
+        );
+        assert_eq!(
+            no_target_warning(&[2, 0]).as_deref(),
+            Some("No target for steps 1, 3")
+        );
+    }
+}
@@ -1,16 +1,43 @@
-use crate::ui::style::{
-    ButtonStyle, BUTTON_BG, CANCEL_BG, PANEL_BG, PANEL_BORDER, SELECTED_BG, SELECTED_BORDER,
-};
+use crate::ui::style::{ButtonStyle, ThemeRole, UiTheme, TOP_BAR_HEIGHT};
 use crate::{
     grid::Position,
-    materials::{InputPort, InventoryAccess, OutputPort, RecipeRegistry, StoragePort},
-    structures::{Building, NeedsRecipeCommitmentEvaluation, RecipeCrafter},
-    systems::Operational,
-    ui::UISystemSet,
+    materials::{
+        InputPort, InventoryAccess, ItemId, ItemRegistry, NamedOutputPorts, OutputPort,
+        RecipeRegistry, StoragePort,
+    },
+    structures::{
+        crafter_kickstart::{AutoEvacuateOutput, ManualSupplyRequest},
+        Building, CancelConstructionEvent, CapacityUpgradeEvent, CapacityUpgrades, ComputeConsumer,
+        ConstructionSite, CraftHistory, DisplayLabel, LogisticsPriority, NeedsInputEvacuation,
+        NeedsRecipeCommitmentEvaluation, PowerConsumer, RecipeChangeEvent, RecipeCrafter,
+        TechState,
+    },
+    systems::{
+        sorted_inventory, ComputeGrid, DisplayConfig, InventorySort, ItemThroughput,
+        NetworkConnectivity, Operational, PowerGrid,
+    },
+    ui::{hover::building_at, UISystemSet},
+};
+use bevy::ecs::{change_detection::Tick, system::SystemChangeTick};
+use bevy::input::{
+    keyboard::{Key, KeyboardInput},
+    ButtonState, InputSystems,
 };
 use bevy::prelude::*;
 use bevy::{picking::hover::Hovered, ui::Checked};
 
+const MAX_LABEL_LEN: usize = 32;
+const MAX_MENU_REBUILDS_PER_FRAME: usize = 4;
+const MANUAL_REQUEST_STEP: u32 = 5;
+const MAX_MANUAL_REQUEST: u32 = 100;
+const MENU_WIDTH: f32 = 280.0;
+/// Gap kept between a menu and the window edge.
+const MENU_MARGIN: f32 = 10.0;
+/// Each further pinned menu docks this far down and left of the previous
+/// one, wrapping after `MAX_PIN_CASCADE` so none is pushed off screen.
+const PIN_CASCADE_STEP: f32 = 24.0;
+const MAX_PIN_CASCADE: usize = 8;
+
 #[derive(Message)]
 pub struct BuildingClickEvent {
     pub building_entity: Entity,
@@ -26,6 +53,14 @@ pub struct CloseMenuEvent {
 pub struct BuildingMenu {
     pub target_building: Entity,
     pub world_position: Vec2,
+    /// Pinned menus stay docked in a screen corner instead of following
+    /// their building around as the camera pans.
+    pub pinned: bool,
+}
+
+#[derive(Component)]
+pub struct MenuPinButton {
+    pub menu_entity: Entity,
 }
 
 #[derive(Component)]
@@ -33,11 +68,33 @@ pub struct MenuCloseButton {
     pub menu_entity: Entity,
 }
 
+#[derive(Component)]
+pub struct MenuTitle {
+    pub target_building: Entity,
+}
+
+#[derive(Component)]
+pub struct MenuRenameButton {
+    pub target_building: Entity,
+}
+
+/// The building whose label is being typed into its menu header. While set,
+/// keyboard input is captured so hotkeys do not fire.
+#[derive(Resource, Default)]
+pub struct LabelEditor {
+    pub target: Option<Entity>,
+    pub buffer: String,
+}
+
+/// `last_updated` is the tick the section was last rebuilt at; it is compared
+/// against the source components' change ticks. Crafting sections compare
+/// `recipe_state` instead since the crafter's timer changes every frame.
 #[derive(Component)]
 pub struct MenuContent {
     pub target_building: Entity,
     pub content_type: ContentType,
-    pub last_updated: Option<u32>,
+    pub last_updated: Option<Tick>,
+    pub recipe_state: Option<u64>,
 }
 
 #[derive(PartialEq, Clone)]
@@ -45,6 +102,7 @@ pub enum ContentType {
     Status,
     Storage,
     Crafting,
+    Logistics,
 }
 
 #[derive(Component)]
@@ -53,17 +111,70 @@ pub struct RecipeSelector {
     pub recipe_name: String,
 }
 
-#[derive(Message)]
-pub struct RecipeChangeEvent {
-    pub building_entity: Entity,
-    pub recipe_name: String,
+/// Switches `AutoEvacuateOutput` on or off for `target_building`.
+#[derive(Component)]
+pub struct OverflowToggle {
+    pub target_building: Entity,
+}
+
+#[derive(Component)]
+pub struct PrioritySelector {
+    pub target_building: Entity,
+    pub priority: LogisticsPriority,
+}
+
+/// The item and quantity picked in a building's manual request form.
+/// `item_index` points into `requestable_items`.
+#[derive(Component, Debug)]
+pub struct ManualRequestForm {
+    pub target_building: Entity,
+    pub item_index: usize,
+    pub quantity: u32,
+}
+
+#[derive(Component)]
+pub struct ManualRequestLabel {
+    pub form: Entity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManualRequestAction {
+    PreviousItem,
+    NextItem,
+    Less,
+    More,
+    Submit,
+}
+
+#[derive(Component)]
+pub struct ManualRequestButton {
+    pub form: Entity,
+    pub action: ManualRequestAction,
+}
+
+#[derive(Component)]
+pub struct CapacityUpgradeButton {
+    pub target_building: Entity,
+}
+
+/// Applies `source_building`'s current recipe to every building of the same
+/// type that offers it.
+#[derive(Component)]
+pub struct CopyRecipeButton {
+    pub source_building: Entity,
+}
+
+#[derive(Component)]
+pub struct CancelConstructionButton {
+    pub target_site: Entity,
+    pub menu_entity: Entity,
 }
 
 pub fn detect_building_clicks(
     mouse_button: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window>,
     camera_q: Query<(&Camera, &GlobalTransform)>,
-    buildings: Query<(Entity, &Position, &Transform), With<Building>>,
+    buildings: Query<(Entity, &Transform), Or<(With<Building>, With<ConstructionSite>)>>,
     mut click_events: MessageWriter<BuildingClickEvent>,
     ui_interactions: Query<&Interaction, With<Button>>,
 ) {
@@ -95,15 +206,14 @@ pub fn detect_building_clicks(
         return;
     };
 
-    for (entity, _position, transform) in buildings.iter() {
-        let building_world_pos = transform.translation.truncate();
-        if world_pos.distance(building_world_pos) < 32.0 {
-            click_events.write(BuildingClickEvent {
-                building_entity: entity,
-                world_position: building_world_pos,
-            });
-            break;
-        }
+    let Some(entity) = building_at(world_pos, buildings) else {
+        return;
+    };
+    if let Ok((_, transform)) = buildings.get(entity) {
+        click_events.write(BuildingClickEvent {
+            building_entity: entity,
+            world_position: transform.translation.truncate(),
+        });
     }
 }
 
@@ -113,7 +223,9 @@ pub fn spawn_building_menu(
     existing_menus: Query<(&BuildingMenu, Entity)>,
     camera_q: Query<(&Camera, &GlobalTransform)>,
     windows: Query<&Window>,
-    buildings: Query<&Name, With<Building>>,
+    buildings: Query<(&Name, Option<&DisplayLabel>), With<Building>>,
+    sites: Query<&ConstructionSite>,
+    theme: Res<UiTheme>,
 ) {
     for click in click_events.read() {
         if existing_menus
@@ -136,13 +248,20 @@ pub fn spawn_building_menu(
             continue;
         };
 
-        let building_name = buildings
-            .get(click.building_entity)
-            .map(Name::as_str)
-            .unwrap_or("Unknown Building");
+        let site = sites.get(click.building_entity).ok();
+        let building_name = match site {
+            Some(site) => format!("{} (under construction)", site.building_name),
+            None => buildings
+                .get(click.building_entity)
+                .map_or("Unknown Building", |(name, label)| {
+                    DisplayLabel::or_name(label, name)
+                })
+                .to_string(),
+        };
 
-        let menu_x = (screen_pos.x + 50.0).clamp(10.0, window.width() - 300.0);
-        let menu_y = (screen_pos.y - 100.0).clamp(44.0, window.height() - 250.0);
+        let menu_x = (screen_pos.x + 50.0)
+            .clamp(MENU_MARGIN, window.width() - MENU_WIDTH - 2.0 * MENU_MARGIN);
+        let menu_y = (screen_pos.y - 100.0).clamp(TOP_BAR_HEIGHT, window.height() - 250.0);
 
         let menu_entity = commands
             .spawn((
@@ -150,25 +269,32 @@ pub fn spawn_building_menu(
                     position_type: PositionType::Absolute,
                     left: Val::Px(menu_x),
                     top: Val::Px(menu_y),
-                    width: Val::Px(280.0),
+                    width: Val::Px(MENU_WIDTH),
                     max_height: Val::Vh(50.0),
                     flex_direction: FlexDirection::Column,
                     padding: UiRect::all(Val::Px(8.0)),
                     border: UiRect::all(Val::Px(2.0)),
                     ..default()
                 },
-                BackgroundColor(PANEL_BG),
-                BorderColor::all(PANEL_BORDER),
+                theme.background(ThemeRole::PanelBg),
+                theme.border(ThemeRole::PanelBorder),
                 Interaction::None,
                 BuildingMenu {
                     target_building: click.building_entity,
                     world_position: click.world_position,
+                    pinned: false,
                 },
             ))
             .id();
 
         commands.entity(menu_entity).with_children(|parent| {
-            spawn_menu_header(parent, building_name, menu_entity);
+            spawn_menu_header(
+                parent,
+                &building_name,
+                click.building_entity,
+                menu_entity,
+                &theme,
+            );
 
             parent
                 .spawn((
@@ -183,19 +309,80 @@ pub fn spawn_building_menu(
                     crate::ui::scroll::Scrollable,
                 ))
                 .with_children(|scroll_area| {
-                    spawn_content_section(scroll_area, click.building_entity, ContentType::Status);
-                    spawn_content_section(scroll_area, click.building_entity, ContentType::Storage);
-                    spawn_content_section(
+                    spawn_menu_sections(
                         scroll_area,
                         click.building_entity,
-                        ContentType::Crafting,
+                        menu_entity,
+                        site.is_some(),
+                        &theme,
                     );
                 });
         });
     }
 }
 
-fn spawn_menu_header(parent: &mut ChildSpawnerCommands, title: &str, menu_entity: Entity) {
+fn spawn_menu_sections(
+    scroll_area: &mut ChildSpawnerCommands,
+    building: Entity,
+    menu_entity: Entity,
+    under_construction: bool,
+    theme: &UiTheme,
+) {
+    if under_construction {
+        spawn_cancel_construction_button(scroll_area, building, menu_entity, theme);
+        return;
+    }
+    for content_type in [
+        ContentType::Status,
+        ContentType::Storage,
+        ContentType::Crafting,
+        ContentType::Logistics,
+    ] {
+        spawn_content_section(scroll_area, building, content_type, theme);
+    }
+}
+
+/// A small text button in the menu header, tagged with `marker`.
+fn spawn_header_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    marker: impl Bundle,
+    theme: &UiTheme,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                height: Val::Px(24.0),
+                padding: UiRect::horizontal(Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            theme.background(ThemeRole::Button),
+            ButtonStyle::default_button(theme),
+            Hovered::default(),
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Header),
+            ));
+        });
+}
+
+fn spawn_menu_header(
+    parent: &mut ChildSpawnerCommands,
+    title: &str,
+    target_building: Entity,
+    menu_entity: Entity,
+    theme: &UiTheme,
+) {
     parent
         .spawn(Node {
             width: Val::Percent(100.0),
@@ -213,46 +400,103 @@ fn spawn_menu_header(parent: &mut ChildSpawnerCommands, title: &str, menu_entity
                     font_size: 14.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                theme.text(ThemeRole::Header),
+                MenuTitle { target_building },
             ));
 
             parent
-                .spawn((
-                    Button,
-                    Node {
-                        width: Val::Px(24.0),
-                        height: Val::Px(24.0),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BackgroundColor(CANCEL_BG),
-                    ButtonStyle::close(),
-                    Hovered::default(),
-                    MenuCloseButton { menu_entity },
-                ))
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                })
                 .with_children(|parent| {
-                    parent.spawn((
-                        Text::new("x"),
-                        TextFont {
-                            font_size: 16.0,
-                            ..default()
-                        },
-                        TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                    ));
+                    spawn_header_button(
+                        parent,
+                        "Rename",
+                        MenuRenameButton { target_building },
+                        theme,
+                    );
+                    spawn_header_button(parent, "Pin", MenuPinButton { menu_entity }, theme);
+
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(24.0),
+                                height: Val::Px(24.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            theme.background(ThemeRole::Cancel),
+                            ButtonStyle::close(theme),
+                            Hovered::default(),
+                            MenuCloseButton { menu_entity },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new("x"),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                theme.text(ThemeRole::Header),
+                            ));
+                        });
                 });
         });
 }
 
+fn spawn_cancel_construction_button(
+    parent: &mut ChildSpawnerCommands,
+    target_site: Entity,
+    menu_entity: Entity,
+    theme: &UiTheme,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(28.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            theme.background(ThemeRole::Cancel),
+            theme.border(ThemeRole::PanelBorder),
+            ButtonStyle::close(theme),
+            Hovered::default(),
+            CancelConstructionButton {
+                target_site,
+                menu_entity,
+            },
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new("Cancel construction"),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Header),
+            ));
+        });
+}
+
 fn spawn_content_section(
     parent: &mut ChildSpawnerCommands,
     building_entity: Entity,
     content_type: ContentType,
+    theme: &UiTheme,
 ) {
     let section_title = match content_type {
         ContentType::Status => "Status",
         ContentType::Storage => "Storage",
         ContentType::Crafting => "Production",
+        ContentType::Logistics => "Logistics",
     };
 
     parent
@@ -267,6 +511,7 @@ fn spawn_content_section(
                 target_building: building_entity,
                 content_type,
                 last_updated: None,
+                recipe_state: None,
             },
         ))
         .with_children(|parent| {
@@ -276,7 +521,7 @@ fn spawn_content_section(
                     font_size: 13.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                theme.text(ThemeRole::Header),
                 Node {
                     margin: UiRect::bottom(Val::Px(4.0)),
                     ..default()
@@ -289,7 +534,7 @@ fn spawn_content_section(
                     font_size: 12.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                theme.text(ThemeRole::DimText),
             ));
         });
 }
@@ -310,15 +555,157 @@ pub fn handle_menu_close_buttons_interaction(
 pub fn process_menu_close_events(
     mut commands: Commands,
     mut close_events: MessageReader<CloseMenuEvent>,
-    menu_query: Query<Entity, With<BuildingMenu>>,
+    menu_query: Query<&BuildingMenu>,
+    mut editor: ResMut<LabelEditor>,
 ) {
     for close_event in close_events.read() {
-        if menu_query.contains(close_event.menu_entity) {
+        if let Ok(menu) = menu_query.get(close_event.menu_entity) {
+            if editor.target == Some(menu.target_building) {
+                editor.target = None;
+            }
             commands.entity(close_event.menu_entity).despawn();
         }
     }
 }
 
+/// Where the menu pinned after `already_pinned` others docks: under the top
+/// bar at the right edge, stepped down and left so it does not cover them.
+fn pinned_menu_position(window_width: f32, already_pinned: usize) -> (f32, f32) {
+    #[allow(clippy::cast_precision_loss)]
+    let offset = (already_pinned % MAX_PIN_CASCADE) as f32 * PIN_CASCADE_STEP;
+    (
+        window_width - MENU_WIDTH - MENU_MARGIN - offset,
+        TOP_BAR_HEIGHT + offset,
+    )
+}
+
+/// Toggles a menu between following its building and sitting docked in the
+/// top-right corner of the window.
+pub fn handle_menu_pin_buttons(
+    pin_buttons: Query<(&MenuPinButton, &Interaction, &Children), Changed<Interaction>>,
+    mut menus: Query<(&mut BuildingMenu, &mut Node)>,
+    mut texts: Query<&mut Text>,
+    windows: Query<&Window>,
+) {
+    for (button, interaction, children) in &pin_buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let already_pinned = menus.iter().filter(|(menu, _)| menu.pinned).count();
+        let Ok((mut menu, mut node)) = menus.get_mut(button.menu_entity) else {
+            continue;
+        };
+
+        menu.pinned = !menu.pinned;
+        if menu.pinned {
+            if let Ok(window) = windows.single() {
+                let (left, top) = pinned_menu_position(window.width(), already_pinned);
+                node.left = Val::Px(left);
+                node.top = Val::Px(top);
+            }
+        }
+
+        let label = if menu.pinned { "Unpin" } else { "Pin" };
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.0 = label.to_string();
+            }
+        }
+    }
+}
+
+pub fn handle_rename_buttons(
+    rename_buttons: Query<(&Interaction, &MenuRenameButton), Changed<Interaction>>,
+    labels: Query<&DisplayLabel>,
+    mut editor: ResMut<LabelEditor>,
+) {
+    for (interaction, button) in &rename_buttons {
+        if *interaction == Interaction::Pressed {
+            editor.target = Some(button.target_building);
+            editor.buffer = labels
+                .get(button.target_building)
+                .map(|label| label.0.clone())
+                .unwrap_or_default();
+        }
+    }
+}
+
+/// Runs right after input collection so the keys typed into a label never
+/// reach gameplay or camera hotkeys. Enter commits, Escape cancels, and an
+/// empty label falls back to the type name.
+pub fn capture_label_input(
+    mut commands: Commands,
+    mut editor: ResMut<LabelEditor>,
+    mut key_events: MessageReader<KeyboardInput>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+) {
+    let Some(target) = editor.target else {
+        key_events.clear();
+        return;
+    };
+
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let label = editor.buffer.trim();
+                if label.is_empty() {
+                    commands.entity(target).try_remove::<DisplayLabel>();
+                } else {
+                    commands
+                        .entity(target)
+                        .try_insert(DisplayLabel(label.to_string()));
+                }
+                editor.target = None;
+                break;
+            }
+            Key::Escape => {
+                editor.target = None;
+                break;
+            }
+            Key::Backspace => {
+                editor.buffer.pop();
+            }
+            Key::Space => push_label_text(&mut editor.buffer, " "),
+            Key::Character(text) => push_label_text(&mut editor.buffer, text),
+            _ => {}
+        }
+    }
+
+    keyboard.reset_all();
+}
+
+fn push_label_text(buffer: &mut String, text: &str) {
+    for c in text.chars().filter(|c| !c.is_control()) {
+        if buffer.chars().count() >= MAX_LABEL_LEN {
+            return;
+        }
+        buffer.push(c);
+    }
+}
+
+pub fn update_menu_titles(
+    editor: Res<LabelEditor>,
+    mut titles: Query<(&mut Text, &MenuTitle)>,
+    buildings: Query<(&Name, Option<&DisplayLabel>)>,
+) {
+    for (mut text, title) in &mut titles {
+        let desired = if editor.target == Some(title.target_building) {
+            format!("{}_", editor.buffer)
+        } else {
+            let Ok((name, label)) = buildings.get(title.target_building) else {
+                continue;
+            };
+            DisplayLabel::or_name(label, name).to_string()
+        };
+        if text.0 != desired {
+            text.0 = desired;
+        }
+    }
+}
+
 pub fn update_menu_positions(
     mut menu_query: Query<(&mut Node, &BuildingMenu)>,
     camera_q: Query<(&Camera, &GlobalTransform)>,
@@ -332,139 +719,333 @@ pub fn update_menu_positions(
     };
 
     for (mut node, menu) in &mut menu_query {
+        if menu.pinned {
+            continue;
+        }
         if let Ok(screen_pos) =
             camera.world_to_viewport(camera_transform, menu.world_position.extend(0.0))
         {
-            let max_x = window.width() - 300.0;
+            let max_x = window.width() - MENU_WIDTH - 2.0 * MENU_MARGIN;
             let max_y = window.height() - 250.0;
 
-            node.left = Val::Px((screen_pos.x + 50.0).clamp(10.0, max_x));
-            node.top = Val::Px((screen_pos.y - 100.0).clamp(44.0, max_y));
+            node.left = Val::Px((screen_pos.x + 50.0).clamp(MENU_MARGIN, max_x));
+            node.top = Val::Px((screen_pos.y - 100.0).clamp(TOP_BAR_HEIGHT, max_y));
         }
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+fn changed_since<T: Component>(component: Option<Ref<T>>, last: Option<Tick>, now: Tick) -> bool {
+    component.is_some_and(|component| {
+        last.is_none_or(|last| component.last_changed().is_newer_than(last, now))
+    })
+}
+
+fn resource_changed_since<T: Resource>(resource: &Res<T>, last: Option<Tick>, now: Tick) -> bool {
+    last.is_none_or(|last| resource.last_changed().is_newer_than(last, now))
+}
+
+/// The port queries behind a menu's storage and logistics sections.
+type BuildingPorts<'w, 's> = (
+    Query<'w, 's, Ref<'static, InputPort>, With<Building>>,
+    Query<'w, 's, Ref<'static, OutputPort>, With<Building>>,
+    Query<'w, 's, Ref<'static, NamedOutputPorts>, With<Building>>,
+    Query<'w, 's, Ref<'static, StoragePort>, With<Building>>,
+    Query<'w, 's, Ref<'static, CapacityUpgrades>, With<Building>>,
+);
+
+type BuildingStatus<'a> = (
+    Ref<'a, Operational>,
+    Option<&'a PowerConsumer>,
+    Option<&'a ComputeConsumer>,
+    &'a Position,
+);
+
+type Infrastructure<'w> = (
+    Res<'w, PowerGrid>,
+    Res<'w, ComputeGrid>,
+    Res<'w, NetworkConnectivity>,
+);
+
+/// The infrastructure lines read the grids and the network, which move
+/// without the building itself changing.
+fn status_stale(
+    status: Option<BuildingStatus>,
+    infrastructure: &Infrastructure,
+    last: Option<Tick>,
+    now: Tick,
+) -> bool {
+    let (power_grid, compute_grid, network) = infrastructure;
+    status.is_some_and(|(operational, power, compute, _)| {
+        changed_since(Some(operational), last, now)
+            || resource_changed_since(network, last, now)
+            || (power.is_some() && resource_changed_since(power_grid, last, now))
+            || (compute.is_some() && resource_changed_since(compute_grid, last, now))
+    })
+}
+
+fn ports_changed(ports: &BuildingPorts, target: Entity, last: Option<Tick>, now: Tick) -> bool {
+    let (input_ports, output_ports, named_output_ports, storage_ports, upgrades) = ports;
+    changed_since(input_ports.get(target).ok(), last, now)
+        || changed_since(output_ports.get(target).ok(), last, now)
+        || changed_since(named_output_ports.get(target).ok(), last, now)
+        || changed_since(storage_ports.get(target).ok(), last, now)
+        || changed_since(upgrades.get(target).ok(), last, now)
+}
+
 pub fn update_menu_content(
     mut content_query: Query<(Entity, &mut MenuContent)>,
     mut commands: Commands,
     children: Query<&Children>,
-    buildings_operational: Query<&Operational, With<Building>>,
-    buildings_input_port: Query<&InputPort, With<Building>>,
-    buildings_output_port: Query<&OutputPort, With<Building>>,
-    buildings_storage_port: Query<&StoragePort, With<Building>>,
-    buildings_crafting: Query<&RecipeCrafter, With<Building>>,
-    recipe_registry: Res<RecipeRegistry>,
-) {
-    for (content_entity, mut menu_content) in &mut content_query {
-        let should_update = match menu_content.content_type {
-            ContentType::Status => buildings_operational
-                .get(menu_content.target_building)
-                .map(simple_hash)
-                .map(|hash| menu_content.last_updated != Some(hash))
-                .unwrap_or(false),
+    buildings_operational: Query<
+        (
+            Ref<Operational>,
+            Option<&PowerConsumer>,
+            Option<&ComputeConsumer>,
+            &Position,
+        ),
+        With<Building>,
+    >,
+    building_ports: BuildingPorts,
+    buildings_crafting: Query<(&RecipeCrafter, Option<&CraftHistory>), With<Building>>,
+    buildings_logistics: Query<
+        (Option<Ref<LogisticsPriority>>, Has<AutoEvacuateOutput>),
+        With<Building>,
+    >,
+    registries: (Res<RecipeRegistry>, Res<ItemRegistry>),
+    tech: Res<TechState>,
+    display: Res<DisplayConfig>,
+    infrastructure: Infrastructure,
+    ticks: SystemChangeTick,
+    theme: Res<UiTheme>,
+) {
+    let (recipe_registry, item_registry) = registries;
+    let now = ticks.this_run();
+    let needs_rebuild = |menu_content: &MenuContent| {
+        let target = menu_content.target_building;
+        let last = menu_content.last_updated;
+        match menu_content.content_type {
+            ContentType::Status => status_stale(
+                buildings_operational.get(target).ok(),
+                &infrastructure,
+                last,
+                now,
+            ),
             ContentType::Storage => {
-                let input_hash = buildings_input_port
-                    .get(menu_content.target_building)
-                    .map(simple_hash);
-                let output_hash = buildings_output_port
-                    .get(menu_content.target_building)
-                    .map(simple_hash);
-                let storage_hash = buildings_storage_port
-                    .get(menu_content.target_building)
-                    .map(simple_hash);
-
-                let combined_hash = input_hash.ok().or(output_hash.ok()).or(storage_hash.ok());
-
-                combined_hash.is_some_and(|hash| menu_content.last_updated != Some(hash))
+                ports_changed(&building_ports, target, last, now) || display.is_changed()
             }
             ContentType::Crafting => buildings_crafting
-                .get(menu_content.target_building)
-                .map(hash_crafter_recipe_state)
-                .is_ok_and(|hash| menu_content.last_updated != Some(hash)),
+                .get(target)
+                .map(|(crafter, history)| hash_crafter_recipe_state(crafter, history))
+                .is_ok_and(|hash| menu_content.recipe_state != Some(hash) || tech.is_changed()),
+            ContentType::Logistics => match buildings_logistics.get(target) {
+                Ok((Some(priority), _)) => changed_since(Some(priority), last, now),
+                Ok((None, _)) => last.is_none(),
+                Err(_) => false,
+            },
+        }
+    };
+
+    let stale = stalest_first(
+        content_query
+            .iter()
+            .filter(|(_, menu_content)| needs_rebuild(menu_content))
+            .map(|(entity, menu_content)| (entity, menu_content.last_updated)),
+        now,
+    );
+    for content_entity in stale {
+        let Ok((_, mut menu_content)) = content_query.get_mut(content_entity) else {
+            continue;
         };
+        let target = menu_content.target_building;
 
-        if should_update {
-            if let Ok(content_children) = children.get(content_entity) {
-                for child in content_children.iter().skip(1) {
-                    commands.entity(child).despawn();
-                }
+        if let Ok(content_children) = children.get(content_entity) {
+            for child in content_children.iter().skip(1) {
+                commands.entity(child).despawn();
             }
+        }
 
-            commands.entity(content_entity).with_children(|parent| {
-                match menu_content.content_type {
-                    ContentType::Status => {
-                        if let Ok(operational) =
-                            buildings_operational.get(menu_content.target_building)
-                        {
-                            spawn_status_content(parent, operational);
-                            menu_content.last_updated = Some(simple_hash(operational));
-                        }
-                    }
-                    ContentType::Storage => {
-                        let entity = menu_content.target_building;
-                        if let Ok(input_port) = buildings_input_port.get(entity) {
-                            let output_port = buildings_output_port.get(entity).ok();
-                            spawn_port_inventory_content(
-                                parent,
-                                Some(input_port),
-                                output_port,
-                                None,
-                            );
-                            menu_content.last_updated = Some(simple_hash(input_port));
-                        } else if let Ok(output_port) = buildings_output_port.get(entity) {
-                            spawn_port_inventory_content(parent, None, Some(output_port), None);
-                            menu_content.last_updated = Some(simple_hash(output_port));
-                        } else if let Ok(storage_port) = buildings_storage_port.get(entity) {
-                            spawn_port_inventory_content(parent, None, None, Some(storage_port));
-                            menu_content.last_updated = Some(simple_hash(storage_port));
-                        }
+        menu_content.last_updated = Some(now);
+        commands
+            .entity(content_entity)
+            .with_children(|parent| match menu_content.content_type {
+                ContentType::Status => {
+                    if let Ok(status) = buildings_operational.get(target) {
+                        spawn_status_section(parent, &status, &infrastructure, &theme);
                     }
-                    ContentType::Crafting => {
-                        if let Ok(crafter) = buildings_crafting.get(menu_content.target_building) {
-                            spawn_crafting_content(
-                                parent,
-                                crafter,
-                                &recipe_registry,
-                                menu_content.target_building,
-                            );
-                            menu_content.last_updated = Some(hash_crafter_recipe_state(crafter));
-                        }
+                }
+                ContentType::Storage => spawn_storage_content(
+                    parent,
+                    &item_registry,
+                    &building_ports,
+                    display.inventory_sort,
+                    target,
+                    &theme,
+                ),
+                ContentType::Crafting => {
+                    if let Ok((crafter, history)) = buildings_crafting.get(target) {
+                        spawn_crafting_content(
+                            parent,
+                            crafter,
+                            history,
+                            &recipe_registry,
+                            &item_registry,
+                            &tech,
+                            target,
+                            &theme,
+                        );
+                        menu_content.recipe_state =
+                            Some(hash_crafter_recipe_state(crafter, history));
                     }
                 }
+                ContentType::Logistics => spawn_logistics_content(
+                    parent,
+                    &item_registry,
+                    buildings_logistics
+                        .get(target)
+                        .ok()
+                        .map(|(priority, overflow)| (priority.as_deref().copied(), overflow)),
+                    &building_ports,
+                    target,
+                    &theme,
+                ),
             });
-        }
     }
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn simple_hash<T: std::fmt::Debug>(value: &T) -> u32 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+/// Sections due a rebuild, stalest first and at most
+/// `MAX_MENU_REBUILDS_PER_FRAME` of them, so a few busy menus cannot starve
+/// the rest. Sections never built count as the stalest.
+fn stalest_first(stale: impl Iterator<Item = (Entity, Option<Tick>)>, now: Tick) -> Vec<Entity> {
+    let mut by_age: Vec<(Entity, u32)> = stale
+        .map(|(entity, last)| {
+            let age = last.map_or(u32::MAX, |last| now.get().wrapping_sub(last.get()));
+            (entity, age)
+        })
+        .collect();
+    by_age.sort_unstable_by_key(|&(_, age)| std::cmp::Reverse(age));
+    by_age.truncate(MAX_MENU_REBUILDS_PER_FRAME);
+    by_age.into_iter().map(|(entity, _)| entity).collect()
+}
 
-    let debug_string = format!("{value:?}");
-    let mut hasher = DefaultHasher::new();
-    debug_string.hash(&mut hasher);
-    hasher.finish() as u32
+/// The building's output ports, the plain one first and then the named ones
+/// by tag.
+fn labelled_output_ports<'a>(
+    output_port: Option<&'a OutputPort>,
+    named_output_ports: Option<&'a NamedOutputPorts>,
+) -> Vec<(String, &'a OutputPort)> {
+    let mut output_ports: Vec<(String, &OutputPort)> = output_port
+        .map(|port| ("Output".to_string(), port))
+        .into_iter()
+        .collect();
+    if let Some(named) = named_output_ports {
+        let mut tagged: Vec<_> = named.ports.iter().collect();
+        tagged.sort_unstable_by_key(|&(tag, _)| tag);
+        output_ports.extend(
+            tagged
+                .into_iter()
+                .map(|(tag, port)| (format!("Output ({tag})"), port)),
+        );
+    }
+    output_ports
+}
+
+fn spawn_storage_content(
+    parent: &mut ChildSpawnerCommands,
+    item_registry: &ItemRegistry,
+    ports: &BuildingPorts,
+    sort: InventorySort,
+    target: Entity,
+    theme: &UiTheme,
+) {
+    let (input_ports, output_ports, named_output_ports, storage_ports, upgrades) = ports;
+    let input_port = input_ports.get(target).ok();
+    let output_port = output_ports.get(target).ok();
+    let named_output_ports = named_output_ports.get(target).ok();
+    let labelled = labelled_output_ports(output_port.as_deref(), named_output_ports.as_deref());
+    // Storage is only listed for buildings without crafting ports.
+    let storage_port = storage_ports
+        .get(target)
+        .ok()
+        .filter(|_| input_port.is_none() && labelled.is_empty());
+    spawn_port_inventory_content(
+        parent,
+        item_registry,
+        input_port.as_deref(),
+        &labelled,
+        storage_port.as_deref(),
+        sort,
+        theme,
+    );
+    if let Ok(upgrades) = upgrades.get(target) {
+        spawn_capacity_upgrade(parent, &upgrades, item_registry, target, theme);
+    }
+}
+
+/// `logistics` is the building's priority, if set, and whether it evacuates
+/// its output; buildings without logistics settings show only the request
+/// form, and only when they have an input or storage port.
+fn spawn_logistics_content(
+    parent: &mut ChildSpawnerCommands,
+    item_registry: &ItemRegistry,
+    logistics: Option<(Option<LogisticsPriority>, bool)>,
+    ports: &BuildingPorts,
+    target: Entity,
+    theme: &UiTheme,
+) {
+    let (input_ports, output_ports, _, storage_ports, _) = ports;
+    if let Some((priority, overflow)) = logistics {
+        spawn_priority_selector(parent, priority.unwrap_or_default(), target, theme);
+        if output_ports.contains(target) {
+            spawn_overflow_toggle(parent, overflow, target, theme);
+        }
+    }
+    if input_ports.contains(target) || storage_ports.contains(target) {
+        let input_port = input_ports.get(target).ok();
+        spawn_manual_request_form(parent, item_registry, input_port.as_deref(), target, theme);
+    }
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn hash_crafter_recipe_state(crafter: &RecipeCrafter) -> u32 {
+/// Includes the craft count so the section refreshes as crafts complete.
+fn hash_crafter_recipe_state(crafter: &RecipeCrafter, history: Option<&CraftHistory>) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
     let mut hasher = DefaultHasher::new();
     crafter.current_recipe.hash(&mut hasher);
     crafter.available_recipes.hash(&mut hasher);
-    hasher.finish() as u32
+    history.map(|history| history.total).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn spawn_status_section(
+    parent: &mut ChildSpawnerCommands,
+    status: &BuildingStatus,
+    infrastructure: &Infrastructure,
+    theme: &UiTheme,
+) {
+    let (operational, power, compute, pos) = status;
+    let (power_grid, compute_grid, network) = infrastructure;
+    spawn_status_content(parent, operational, theme);
+    let lines = infrastructure_lines(
+        *power,
+        *compute,
+        network.is_adjacent_to_connected_network(pos.x, pos.y),
+        power_grid,
+        compute_grid,
+    );
+    spawn_infrastructure_lines(parent, &lines, theme);
 }
 
-fn spawn_status_content(parent: &mut ChildSpawnerCommands, operational: &Operational) {
+fn spawn_status_content(
+    parent: &mut ChildSpawnerCommands,
+    operational: &Operational,
+    theme: &UiTheme,
+) {
     let is_operational = operational.get_status();
     let status_color = if is_operational {
-        Color::srgb(0.2, 0.8, 0.2)
+        theme.success_color
     } else {
-        Color::srgb(0.8, 0.2, 0.2)
+        theme.danger_color
     };
 
     parent.spawn((
@@ -480,33 +1061,112 @@ fn spawn_status_content(parent: &mut ChildSpawnerCommands, operational: &Operati
         TextColor(status_color),
     ));
 
-    if !is_operational {
-        if let Some(conditions) = &operational.0 {
-            for condition in conditions {
-                let condition_text = format!("{condition}");
-                if !condition_text.is_empty() {
-                    parent.spawn((
-                        Text::new(format!("  - {condition_text}")),
-                        TextFont {
-                            font_size: 10.0,
-                            ..default()
-                        },
-                        TextColor(Color::srgb(0.8, 0.6, 0.4)),
-                    ));
-                }
+    // Advisory conditions such as starvation can fail while still operational.
+    if let Some(conditions) = &operational.0 {
+        for condition in conditions {
+            let condition_text = format!("{condition}");
+            if !condition_text.is_empty() {
+                parent.spawn((
+                    Text::new(format!("  - {condition_text}")),
+                    TextFont {
+                        font_size: 10.0,
+                        ..default()
+                    },
+                    theme.text(ThemeRole::Warning),
+                ));
             }
         }
     }
 }
 
-fn spawn_port_inventory_content(
+/// Raw infrastructure behind the operational conditions: the building's own
+/// power and compute draw, whether each grid covers it, and its network link.
+/// Each line comes with whether it is satisfied.
+fn infrastructure_lines(
+    power: Option<&PowerConsumer>,
+    compute: Option<&ComputeConsumer>,
+    connected: bool,
+    power_grid: &PowerGrid,
+    compute_grid: &ComputeGrid,
+) -> [(String, bool); 3] {
+    let draw_line = |label: &str, draw: Option<i32>, available: i32| {
+        let draw = draw.unwrap_or(0);
+        let met = draw == 0 || available >= 0;
+        let state = if met { "met" } else { "unmet" };
+        (format!("{label}: {draw} draw ({state})"), met)
+    };
+    [
+        draw_line("Power", power.map(|p| p.amount), power_grid.available),
+        draw_line("Compute", compute.map(|c| c.amount), compute_grid.available),
+        (
+            if connected {
+                "Network: connected".to_string()
+            } else {
+                "Network: disconnected".to_string()
+            },
+            connected,
+        ),
+    ]
+}
+
+fn spawn_infrastructure_lines(
     parent: &mut ChildSpawnerCommands,
-    input_port: Option<&InputPort>,
-    output_port: Option<&OutputPort>,
-    storage_port: Option<&StoragePort>,
+    lines: &[(String, bool)],
+    theme: &UiTheme,
 ) {
-    let spawn_port_items = |parent: &mut ChildSpawnerCommands,
-                            label: &str,
+    for (line, satisfied) in lines {
+        parent.spawn((
+            Text::new(line.clone()),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            theme.text(if *satisfied {
+                ThemeRole::DimText
+            } else {
+                ThemeRole::Danger
+            }),
+        ));
+    }
+}
+
+/// "  5/10 (50%)" for one port, measured against that port's own capacity.
+/// A full port blocks deliveries into it or crafting out of it, so it is
+/// flagged.
+/// Quantities spanning more than one stack also show the stack count.
+fn inventory_row_label(name: &str, quantity: u32, stack_size: u32) -> String {
+    let stacks = quantity.div_ceil(stack_size.max(1));
+    if stacks > 1 {
+        format!("  {name}: {quantity} ({stacks} stacks)")
+    } else {
+        format!("  {name}: {quantity}")
+    }
+}
+
+fn port_usage_label(access: &dyn InventoryAccess) -> String {
+    let total = access.get_total_quantity();
+    let capacity = access.capacity();
+    let percent = (u64::from(total) * 100)
+        .checked_div(u64::from(capacity))
+        .unwrap_or(0);
+    if access.is_full() {
+        format!("  {total}/{capacity} ({percent}%) - Full")
+    } else {
+        format!("  {total}/{capacity} ({percent}%)")
+    }
+}
+
+fn spawn_port_inventory_content(
+    parent: &mut ChildSpawnerCommands,
+    item_registry: &ItemRegistry,
+    input_port: Option<&InputPort>,
+    output_ports: &[(String, &OutputPort)],
+    storage_port: Option<&StoragePort>,
+    sort: InventorySort,
+    theme: &UiTheme,
+) {
+    let spawn_port_items = |parent: &mut ChildSpawnerCommands,
+                            label: &str,
                             access: &dyn InventoryAccess,
                             color: Color| {
         parent.spawn((
@@ -529,40 +1189,37 @@ fn spawn_port_inventory_content(
                     font_size: 12.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                theme.text(ThemeRole::DimText),
             ));
         } else {
-            for (item_name, &quantity) in access.items() {
+            for (item, quantity) in sorted_inventory(access.items(), item_registry, sort) {
                 parent.spawn((
-                    Text::new(format!("  {item_name}: {quantity}")),
+                    Text::new(inventory_row_label(
+                        item_registry.display_name(item),
+                        quantity,
+                        item_registry.stack_size_of(item),
+                    )),
                     TextFont {
                         font_size: 12.0,
                         ..default()
                     },
-                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                    TextColor(item_registry.rarity_of(item).color()),
                 ));
             }
         }
 
-        #[allow(
-            clippy::cast_possible_truncation,
-            clippy::cast_sign_loss,
-            clippy::cast_precision_loss
-        )]
-        let usage_percent =
-            (access.get_total_quantity() as f32 / access.capacity() as f32 * 100.0) as u32;
+        let full = access.is_full();
         parent.spawn((
-            Text::new(format!(
-                "  {}/{} ({}%)",
-                access.get_total_quantity(),
-                access.capacity(),
-                usage_percent
-            )),
+            Text::new(port_usage_label(access)),
             TextFont {
                 font_size: 10.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.5, 0.5, 0.5)),
+            theme.text(if full {
+                ThemeRole::Danger
+            } else {
+                ThemeRole::DimText
+            }),
         ));
     };
 
@@ -575,19 +1232,102 @@ fn spawn_port_inventory_content(
         spawn_port_items(parent, "Input", input, Color::srgb(0.6, 0.7, 0.9));
     }
 
-    if let Some(output) = output_port {
-        spawn_port_items(parent, "Output", output, Color::srgb(0.9, 0.7, 0.6));
+    for (label, output) in output_ports {
+        spawn_port_items(parent, label, *output, Color::srgb(0.9, 0.7, 0.6));
     }
 }
 
+fn spawn_capacity_upgrade(
+    parent: &mut ChildSpawnerCommands,
+    upgrades: &CapacityUpgrades,
+    item_registry: &ItemRegistry,
+    building_entity: Entity,
+    theme: &UiTheme,
+) {
+    let Some(tier) = upgrades.next_tier() else {
+        parent.spawn((
+            Text::new("Capacity fully upgraded"),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            theme.text(ThemeRole::DimText),
+        ));
+        return;
+    };
+
+    let mut cost: Vec<String> = tier
+        .cost
+        .iter()
+        .map(|(&item, quantity)| format!("{quantity} {}", item_registry.display_name(item)))
+        .collect();
+    cost.sort_unstable();
+
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Percent(100.0),
+                margin: UiRect::top(Val::Px(4.0)),
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(4.0)),
+                flex_direction: FlexDirection::Column,
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            theme.background(ThemeRole::Button),
+            theme.border(ThemeRole::PanelBorder),
+            ButtonStyle::building_button(theme),
+            Hovered::default(),
+            CapacityUpgradeButton {
+                target_building: building_entity,
+            },
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(format!(
+                    "Upgrade capacity (tier {}): +{}",
+                    upgrades.level + 1,
+                    tier.capacity_bonus
+                )),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Header),
+            ));
+            btn.spawn((
+                Text::new(format!("Costs {}", cost.join(", "))),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::DimText),
+            ));
+        });
+}
+
+/// Share of the current craft done, in `0.0..=1.0`. A zero-length timer
+/// reads as not started rather than dividing by zero.
+fn crafting_progress(timer: &Timer) -> f32 {
+    let duration = timer.duration().as_secs_f32();
+    if duration <= 0.0 {
+        return 0.0;
+    }
+    (timer.elapsed_secs() / duration).clamp(0.0, 1.0)
+}
+
 fn spawn_crafting_content(
     parent: &mut ChildSpawnerCommands,
     crafter: &RecipeCrafter,
+    history: Option<&CraftHistory>,
     recipe_registry: &RecipeRegistry,
+    item_registry: &ItemRegistry,
+    tech: &TechState,
     building_entity: Entity,
+    theme: &UiTheme,
 ) {
     if crafter.is_multi_recipe() {
-        spawn_recipe_selector(parent, crafter, building_entity);
+        spawn_recipe_selector(parent, crafter, tech, building_entity, theme);
     }
 
     if let Some(recipe_name) = crafter.get_active_recipe() {
@@ -597,10 +1337,10 @@ fn spawn_crafting_content(
                 font_size: 12.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            theme.text(ThemeRole::Text),
         ));
 
-        let progress = crafter.timer.elapsed_secs() / crafter.timer.duration().as_secs_f32();
+        let progress = crafting_progress(&crafter.timer);
         #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
         let progress_percent = (progress * 100.0) as u32;
 
@@ -610,7 +1350,7 @@ fn spawn_crafting_content(
                 font_size: 12.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.7, 0.9, 0.7)),
+            theme.text(ThemeRole::Success),
         ));
 
         if let Some(recipe_def) = recipe_registry.get_definition(recipe_name) {
@@ -621,16 +1361,16 @@ fn spawn_crafting_content(
                         font_size: 10.0,
                         ..default()
                     },
-                    TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                    theme.text(ThemeRole::DimText),
                 ));
-                for (item, quantity) in &recipe_def.inputs {
+                for (&item, quantity) in &recipe_def.inputs {
                     parent.spawn((
-                        Text::new(format!("  {quantity} {item}")),
+                        Text::new(format!("  {quantity} {}", item_registry.display_name(item))),
                         TextFont {
                             font_size: 10.0,
                             ..default()
                         },
-                        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                        theme.text(ThemeRole::Text),
                     ));
                 }
             }
@@ -642,16 +1382,16 @@ fn spawn_crafting_content(
                         font_size: 10.0,
                         ..default()
                     },
-                    TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                    theme.text(ThemeRole::DimText),
                 ));
-                for (item, quantity) in &recipe_def.outputs {
+                for (&item, quantity) in &recipe_def.outputs {
                     parent.spawn((
-                        Text::new(format!("  {quantity} {item}")),
+                        Text::new(format!("  {quantity} {}", item_registry.display_name(item))),
                         TextFont {
                             font_size: 10.0,
                             ..default()
                         },
-                        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                        theme.text(ThemeRole::Text),
                     ));
                 }
             }
@@ -663,7 +1403,41 @@ fn spawn_crafting_content(
                 font_size: 12.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            theme.text(ThemeRole::DimText),
+        ));
+    }
+
+    if let Some(history) = history {
+        spawn_craft_history(parent, history, item_registry, theme);
+    }
+}
+
+/// Recent completed crafts, newest first.
+fn spawn_craft_history(
+    parent: &mut ChildSpawnerCommands,
+    history: &CraftHistory,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
+) {
+    if history.total == 0 {
+        return;
+    }
+    parent.spawn((
+        Text::new("Recent crafts:"),
+        TextFont {
+            font_size: 10.0,
+            ..default()
+        },
+        theme.text(ThemeRole::DimText),
+    ));
+    for record in history.records().rev() {
+        parent.spawn((
+            Text::new(format!("  {}", record.summary(item_registry))),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            theme.text(ThemeRole::Text),
         ));
     }
 }
@@ -671,7 +1445,9 @@ fn spawn_crafting_content(
 fn spawn_recipe_selector(
     parent: &mut ChildSpawnerCommands,
     crafter: &RecipeCrafter,
+    tech: &TechState,
     building_entity: Entity,
+    theme: &UiTheme,
 ) {
     parent.spawn((
         Text::new("Available Recipes:"),
@@ -679,7 +1455,7 @@ fn spawn_recipe_selector(
             font_size: 10.0,
             ..default()
         },
-        TextColor(Color::srgb(0.6, 0.6, 0.6)),
+        theme.text(ThemeRole::DimText),
         Node {
             margin: UiRect::bottom(Val::Px(4.0)),
             ..default()
@@ -687,6 +1463,11 @@ fn spawn_recipe_selector(
     ));
 
     for recipe_name in &crafter.available_recipes {
+        if !tech.is_unlocked(recipe_name) {
+            spawn_locked_recipe(parent, recipe_name, theme);
+            continue;
+        }
+
         let is_selected = crafter.get_active_recipe() == Some(recipe_name);
 
         let mut entity_commands = parent.spawn((
@@ -700,13 +1481,17 @@ fn spawn_recipe_selector(
                 border: UiRect::all(Val::Px(1.0)),
                 ..default()
             },
-            BackgroundColor(if is_selected { SELECTED_BG } else { BUTTON_BG }),
-            BorderColor::all(if is_selected {
-                SELECTED_BORDER
+            theme.background(if is_selected {
+                ThemeRole::Selected
+            } else {
+                ThemeRole::Button
+            }),
+            theme.border(if is_selected {
+                ThemeRole::SelectedBorder
             } else {
-                PANEL_BORDER
+                ThemeRole::PanelBorder
             }),
-            ButtonStyle::building_button(),
+            ButtonStyle::building_button(theme),
             Hovered::default(),
             RecipeSelector {
                 target_building: building_entity,
@@ -723,9 +1508,573 @@ fn spawn_recipe_selector(
                     font_size: 11.0,
                     ..default()
                 },
-                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                theme.text(ThemeRole::Header),
+            ));
+        });
+    }
+
+    if crafter.get_active_recipe().is_some() {
+        spawn_copy_recipe_button(parent, building_entity, theme);
+    }
+}
+
+/// A recipe the crafter lists but research has not unlocked yet.
+fn spawn_locked_recipe(parent: &mut ChildSpawnerCommands, recipe_name: &str, theme: &UiTheme) {
+    parent
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(24.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::bottom(Val::Px(2.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            theme.border(ThemeRole::PanelBorder),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("{recipe_name} (locked)")),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::DimText),
+            ));
+        });
+}
+
+fn spawn_copy_recipe_button(
+    parent: &mut ChildSpawnerCommands,
+    building_entity: Entity,
+    theme: &UiTheme,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(22.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::vertical(Val::Px(2.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            theme.background(ThemeRole::Button),
+            theme.border(ThemeRole::PanelBorder),
+            ButtonStyle::building_button(theme),
+            Hovered::default(),
+            CopyRecipeButton {
+                source_building: building_entity,
+            },
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new("Apply to all of this type"),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+            ));
+        });
+}
+
+fn spawn_priority_selector(
+    parent: &mut ChildSpawnerCommands,
+    current: LogisticsPriority,
+    building_entity: Entity,
+    theme: &UiTheme,
+) {
+    parent
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|row| {
+            for priority in LogisticsPriority::ALL {
+                let is_selected = priority == current;
+
+                let mut entity_commands = row.spawn((
+                    Button,
+                    Node {
+                        flex_grow: 1.0,
+                        height: Val::Px(24.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    theme.background(if is_selected {
+                        ThemeRole::Selected
+                    } else {
+                        ThemeRole::Button
+                    }),
+                    theme.border(if is_selected {
+                        ThemeRole::SelectedBorder
+                    } else {
+                        ThemeRole::PanelBorder
+                    }),
+                    ButtonStyle::building_button(theme),
+                    Hovered::default(),
+                    PrioritySelector {
+                        target_building: building_entity,
+                        priority,
+                    },
+                ));
+                if is_selected {
+                    entity_commands.insert(Checked);
+                }
+                entity_commands.with_children(|btn| {
+                    btn.spawn((
+                        Text::new(priority.label()),
+                        TextFont {
+                            font_size: 11.0,
+                            ..default()
+                        },
+                        theme.text(ThemeRole::Header),
+                    ));
+                });
+            }
+        });
+}
+
+fn overflow_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Overflow to storage: On"
+    } else {
+        "Overflow to storage: Off"
+    }
+}
+
+fn spawn_overflow_toggle(
+    parent: &mut ChildSpawnerCommands,
+    enabled: bool,
+    building_entity: Entity,
+    theme: &UiTheme,
+) {
+    let mut entity_commands = parent.spawn((
+        Button,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Px(24.0),
+            margin: UiRect::top(Val::Px(4.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        theme.background(if enabled {
+            ThemeRole::Selected
+        } else {
+            ThemeRole::Button
+        }),
+        theme.border(if enabled {
+            ThemeRole::SelectedBorder
+        } else {
+            ThemeRole::PanelBorder
+        }),
+        ButtonStyle::building_button(theme),
+        Hovered::default(),
+        OverflowToggle {
+            target_building: building_entity,
+        },
+    ));
+    if enabled {
+        entity_commands.insert(Checked);
+    }
+    entity_commands.with_children(|btn| {
+        btn.spawn((
+            Text::new(overflow_label(enabled)),
+            TextFont {
+                font_size: 11.0,
+                ..default()
+            },
+            theme.text(ThemeRole::Header),
+        ));
+    });
+}
+
+/// Items offered by the manual request form, sorted by name. A crafter's
+/// input port only offers the items its recipe takes; storages offer all.
+fn requestable_items<'a>(
+    item_registry: &'a ItemRegistry,
+    input_port: Option<&InputPort>,
+) -> Vec<(ItemId, &'a str)> {
+    let mut items: Vec<(ItemId, &str)> = item_registry
+        .definitions
+        .iter()
+        .filter(|(item, _)| {
+            input_port.is_none_or(|port| {
+                port.item_limits.is_empty() || port.item_limits.contains_key(item)
+            })
+        })
+        .map(|(&item, def)| (item, def.name.as_str()))
+        .collect();
+    items.sort_unstable_by_key(|&(_, name)| name);
+    items
+}
+
+fn manual_request_summary(
+    form: &ManualRequestForm,
+    item_registry: &ItemRegistry,
+    input_port: Option<&InputPort>,
+) -> String {
+    let items = requestable_items(item_registry, input_port);
+    match items.get(form.item_index) {
+        Some((_, name)) => format!("{} x {name}", form.quantity),
+        None => "No items".to_string(),
+    }
+}
+
+fn spawn_manual_request_form(
+    parent: &mut ChildSpawnerCommands,
+    item_registry: &ItemRegistry,
+    input_port: Option<&InputPort>,
+    building_entity: Entity,
+    theme: &UiTheme,
+) {
+    let form_state = ManualRequestForm {
+        target_building: building_entity,
+        item_index: 0,
+        quantity: 2 * MANUAL_REQUEST_STEP,
+    };
+    let summary = manual_request_summary(&form_state, item_registry, input_port);
+
+    parent.spawn((
+        Text::new("Request delivery:"),
+        TextFont {
+            font_size: 10.0,
+            ..default()
+        },
+        theme.text(ThemeRole::DimText),
+        Node {
+            margin: UiRect::vertical(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+
+    let mut form = parent.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        },
+        form_state,
+    ));
+    let form_entity = form.id();
+    form.with_children(|form| {
+        form.spawn(Node {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_manual_request_button(
+                row,
+                "<",
+                form_entity,
+                ManualRequestAction::PreviousItem,
+                theme,
+            );
+            row.spawn((
+                Text::new(summary),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                Node {
+                    flex_grow: 1.0,
+                    ..default()
+                },
+                ManualRequestLabel { form: form_entity },
             ));
+            spawn_manual_request_button(
+                row,
+                ">",
+                form_entity,
+                ManualRequestAction::NextItem,
+                theme,
+            );
+        });
+        form.spawn(Node {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_manual_request_button(row, "-", form_entity, ManualRequestAction::Less, theme);
+            spawn_manual_request_button(row, "+", form_entity, ManualRequestAction::More, theme);
+            spawn_manual_request_button(
+                row,
+                "Request",
+                form_entity,
+                ManualRequestAction::Submit,
+                theme,
+            );
         });
+    });
+}
+
+fn spawn_manual_request_button(
+    row: &mut ChildSpawnerCommands,
+    label: &str,
+    form: Entity,
+    action: ManualRequestAction,
+    theme: &UiTheme,
+) {
+    row.spawn((
+        Button,
+        Node {
+            flex_grow: if action == ManualRequestAction::Submit {
+                1.0
+            } else {
+                0.0
+            },
+            min_width: Val::Px(24.0),
+            height: Val::Px(24.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        theme.background(ThemeRole::Button),
+        theme.border(ThemeRole::PanelBorder),
+        ButtonStyle::building_button(theme),
+        Hovered::default(),
+        ManualRequestButton { form, action },
+    ))
+    .with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 11.0,
+                ..default()
+            },
+            theme.text(ThemeRole::Header),
+        ));
+    });
+}
+
+pub fn handle_manual_request_buttons(
+    buttons: Query<(&ManualRequestButton, &Interaction), Changed<Interaction>>,
+    mut forms: Query<&mut ManualRequestForm>,
+    input_ports: Query<&InputPort>,
+    item_registry: Res<ItemRegistry>,
+    mut requests: MessageWriter<ManualSupplyRequest>,
+) {
+    for (button, interaction) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(mut form) = forms.get_mut(button.form) else {
+            continue;
+        };
+        let items = requestable_items(&item_registry, input_ports.get(form.target_building).ok());
+        if items.is_empty() {
+            continue;
+        }
+
+        match button.action {
+            ManualRequestAction::PreviousItem => {
+                form.item_index = (form.item_index + items.len() - 1) % items.len();
+            }
+            ManualRequestAction::NextItem => {
+                form.item_index = (form.item_index + 1) % items.len();
+            }
+            ManualRequestAction::Less => {
+                form.quantity = form
+                    .quantity
+                    .saturating_sub(MANUAL_REQUEST_STEP)
+                    .max(MANUAL_REQUEST_STEP);
+            }
+            ManualRequestAction::More => {
+                form.quantity = (form.quantity + MANUAL_REQUEST_STEP).min(MAX_MANUAL_REQUEST);
+            }
+            ManualRequestAction::Submit => {
+                let Some(&(item, name)) = items.get(form.item_index) else {
+                    continue;
+                };
+                info!(
+                    building = ?form.target_building,
+                    item = name,
+                    quantity = form.quantity,
+                    "manual delivery requested"
+                );
+                requests.write(ManualSupplyRequest {
+                    building: form.target_building,
+                    item,
+                    quantity: form.quantity,
+                });
+            }
+        }
+    }
+}
+
+pub fn update_manual_request_labels(
+    forms: Query<&ManualRequestForm, Changed<ManualRequestForm>>,
+    mut labels: Query<(&ManualRequestLabel, &mut Text)>,
+    input_ports: Query<&InputPort>,
+    item_registry: Res<ItemRegistry>,
+) {
+    for (label, mut text) in &mut labels {
+        if let Ok(form) = forms.get(label.form) {
+            let input_port = input_ports.get(form.target_building).ok();
+            text.0 = manual_request_summary(form, &item_registry, input_port);
+        }
+    }
+}
+
+pub fn handle_priority_selection(
+    mut commands: Commands,
+    selectors: Query<(&PrioritySelector, &Interaction), Changed<Interaction>>,
+    buildings: Query<(), With<Building>>,
+) {
+    for (selector, interaction) in &selectors {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if !buildings.contains(selector.target_building) {
+            continue;
+        }
+
+        commands
+            .entity(selector.target_building)
+            .insert(selector.priority);
+        info!(
+            building = ?selector.target_building,
+            priority = selector.priority.label(),
+            "logistics priority changed"
+        );
+    }
+}
+
+/// Switches a building's output overflow on or off. Removing the component
+/// leaves no change tick for the menu to notice, so its logistics section is
+/// marked stale directly.
+pub fn handle_overflow_toggles(
+    mut commands: Commands,
+    toggles: Query<(&OverflowToggle, &Interaction), Changed<Interaction>>,
+    buildings: Query<Has<AutoEvacuateOutput>, (With<Building>, With<OutputPort>)>,
+    mut menus: Query<&mut MenuContent>,
+) {
+    for (toggle, interaction) in &toggles {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(enabled) = buildings.get(toggle.target_building) else {
+            continue;
+        };
+
+        let mut building = commands.entity(toggle.target_building);
+        if enabled {
+            building.remove::<AutoEvacuateOutput>();
+        } else {
+            building.insert(AutoEvacuateOutput::default());
+        }
+        for mut menu in &mut menus {
+            if menu.target_building == toggle.target_building
+                && menu.content_type == ContentType::Logistics
+            {
+                menu.last_updated = None;
+            }
+        }
+        info!(
+            building = ?toggle.target_building,
+            enabled = !enabled,
+            "output overflow toggled"
+        );
+    }
+}
+
+pub fn handle_capacity_upgrade_buttons(
+    buttons: Query<(&CapacityUpgradeButton, &Interaction), Changed<Interaction>>,
+    mut upgrade_events: MessageWriter<CapacityUpgradeEvent>,
+) {
+    for (button, interaction) in &buttons {
+        if *interaction == Interaction::Pressed {
+            upgrade_events.write(CapacityUpgradeEvent {
+                building: button.target_building,
+            });
+        }
+    }
+}
+
+pub fn handle_cancel_construction_buttons(
+    buttons: Query<(&CancelConstructionButton, &Interaction), Changed<Interaction>>,
+    mut cancel_events: MessageWriter<CancelConstructionEvent>,
+    mut close_events: MessageWriter<CloseMenuEvent>,
+) {
+    for (button, interaction) in &buttons {
+        if *interaction == Interaction::Pressed {
+            cancel_events.write(CancelConstructionEvent {
+                site: button.target_site,
+            });
+            close_events.write(CloseMenuEvent {
+                menu_entity: button.menu_entity,
+            });
+        }
+    }
+}
+
+/// Buildings other than `source` named `type_name` that offer `recipe` and are
+/// not already running it.
+fn similar_buildings_for_recipe<'a>(
+    source: Entity,
+    type_name: &str,
+    recipe: &str,
+    buildings: impl IntoIterator<Item = (Entity, &'a Name, &'a RecipeCrafter)>,
+) -> Vec<Entity> {
+    buildings
+        .into_iter()
+        .filter(|&(entity, name, crafter)| {
+            entity != source
+                && name.as_str() == type_name
+                && crafter.available_recipes.iter().any(|r| r == recipe)
+                && crafter.get_active_recipe().map(String::as_str) != Some(recipe)
+        })
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+pub fn handle_copy_recipe_buttons(
+    buttons: Query<(&CopyRecipeButton, &Interaction), Changed<Interaction>>,
+    crafters: Query<(Entity, &Name, &RecipeCrafter), With<Building>>,
+    mut recipe_change_events: MessageWriter<RecipeChangeEvent>,
+) {
+    for (button, interaction) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok((source, type_name, crafter)) = crafters.get(button.source_building) else {
+            continue;
+        };
+        let Some(recipe_name) = crafter.get_active_recipe() else {
+            continue;
+        };
+
+        let targets = similar_buildings_for_recipe(source, type_name, recipe_name, crafters);
+        info!(
+            building = %type_name,
+            recipe = %recipe_name,
+            count = targets.len(),
+            "copying recipe to similar buildings"
+        );
+        for building_entity in targets {
+            recipe_change_events.write(RecipeChangeEvent {
+                building_entity,
+                recipe_name: recipe_name.clone(),
+            });
+        }
     }
 }
 
@@ -757,26 +2106,75 @@ pub fn handle_recipe_selection(
     }
 }
 
+/// Puts the inputs an abandoned craft consumed back into the input port, as
+/// far as it has room, and takes the refunded part back out of throughput.
+/// Whatever does not fit is lost and logged, and stays counted as consumed.
+fn refund_abandoned_craft(
+    building: Entity,
+    recipe_name: &str,
+    input_port: Option<&mut InputPort>,
+    recipes: &RecipeRegistry,
+    throughput: &mut ItemThroughput,
+) {
+    let Some(recipe) = recipes.get_definition(recipe_name) else {
+        return;
+    };
+    let Some(input_port) = input_port else {
+        return;
+    };
+    for (&item, &qty) in &recipe.inputs {
+        let room = input_port
+            .capacity()
+            .saturating_sub(input_port.get_total_quantity());
+        let refunded = qty.min(room);
+        if refunded > 0 {
+            input_port.add_item(item, refunded);
+            throughput.unrecord_consumed(item, refunded);
+        }
+        if refunded < qty {
+            warn!(
+                ?building,
+                ?item,
+                lost = qty - refunded,
+                "no room to return inputs of abandoned '{recipe_name}' craft"
+            );
+        }
+    }
+}
+
 pub fn apply_recipe_changes(
     mut commands: Commands,
     mut recipe_events: MessageReader<RecipeChangeEvent>,
-    mut buildings: Query<&mut RecipeCrafter, With<Building>>,
+    mut buildings: Query<(&mut RecipeCrafter, Option<&mut InputPort>), With<Building>>,
+    recipes: Res<RecipeRegistry>,
+    tech: Res<TechState>,
+    mut throughput: ResMut<ItemThroughput>,
 ) {
     for event in recipe_events.read() {
-        if let Ok(mut crafter) = buildings.get_mut(event.building_entity) {
-            if let Err(error) = crafter.set_recipe(event.recipe_name.clone()) {
-                warn!(
+        if let Ok((mut crafter, input_port)) = buildings.get_mut(event.building_entity) {
+            match crafter.set_recipe(event.recipe_name.clone(), &tech) {
+                Err(error) => warn!(
                     "failed to set recipe '{}' on building: {}",
                     event.recipe_name, error
-                );
-            } else {
-                info!(
-                    "recipe changed to '{}' for building {:?}",
-                    event.recipe_name, event.building_entity
-                );
-                commands
-                    .entity(event.building_entity)
-                    .insert(NeedsRecipeCommitmentEvaluation);
+                ),
+                Ok(abandoned) => {
+                    if let Some(abandoned) = abandoned {
+                        refund_abandoned_craft(
+                            event.building_entity,
+                            &abandoned,
+                            input_port.map(Mut::into_inner),
+                            &recipes,
+                            &mut throughput,
+                        );
+                    }
+                    info!(
+                        "recipe changed to '{}' for building {:?}",
+                        event.recipe_name, event.building_entity
+                    );
+                    commands
+                        .entity(event.building_entity)
+                        .insert((NeedsRecipeCommitmentEvaluation, NeedsInputEvacuation));
+                }
             }
         }
     }
@@ -788,7 +2186,8 @@ impl Plugin for BuildingMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<BuildingClickEvent>()
             .add_message::<CloseMenuEvent>()
-            .add_message::<RecipeChangeEvent>()
+            .init_resource::<LabelEditor>()
+            .add_systems(PreUpdate, capture_label_input.after(InputSystems))
             .add_systems(
                 Update,
                 (
@@ -796,10 +2195,20 @@ impl Plugin for BuildingMenuPlugin {
                     (
                         spawn_building_menu.run_if(in_state(crate::ui::UiMode::Observe)),
                         handle_menu_close_buttons_interaction,
+                        handle_menu_pin_buttons,
                         process_menu_close_events,
                         handle_recipe_selection,
+                        handle_copy_recipe_buttons,
+                        handle_priority_selection,
+                        handle_overflow_toggles,
+                        handle_manual_request_buttons,
+                        handle_capacity_upgrade_buttons,
+                        handle_cancel_construction_buttons,
+                        handle_rename_buttons,
                     )
                         .in_set(UISystemSet::EntityManagement),
+                    (update_menu_titles, update_manual_request_labels)
+                        .in_set(UISystemSet::VisualUpdates),
                     (
                         update_menu_positions,
                         update_menu_content,
@@ -810,3 +2219,528 @@ impl Plugin for BuildingMenuPlugin {
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::materials::{recipes::MIN_CRAFTING_TIME, ItemId};
+    use bevy::camera::{CameraProjection, ComputedCameraValues, RenderTargetInfo};
+
+    fn menu_app() -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.insert_resource(ItemRegistry::from_ron("[]").unwrap())
+            .insert_resource(RecipeRegistry::from_ron("[]").unwrap())
+            .init_resource::<TechState>()
+            .init_resource::<DisplayConfig>()
+            .init_resource::<UiTheme>()
+            .init_resource::<PowerGrid>()
+            .init_resource::<ComputeGrid>()
+            .init_resource::<NetworkConnectivity>()
+            .add_systems(Update, update_menu_content);
+
+        let building = app.world_mut().spawn((Building, InputPort::new(10))).id();
+        let content = app
+            .world_mut()
+            .spawn(MenuContent {
+                target_building: building,
+                content_type: ContentType::Storage,
+                last_updated: None,
+                recipe_state: None,
+            })
+            .id();
+        (app, building, content)
+    }
+
+    #[test]
+    fn manual_requests_offer_only_what_the_input_port_accepts() {
+        let items = ItemRegistry::from_ron(
+            r#"[
+                (name: "Iron Ore", tier: 0),
+                (name: "Coal", tier: 0),
+                (name: "Gear", tier: 1),
+            ]"#,
+        )
+        .unwrap();
+        let names = |port: Option<&InputPort>| -> Vec<&str> {
+            requestable_items(&items, port)
+                .into_iter()
+                .map(|(_, name)| name)
+                .collect()
+        };
+
+        let mut smelter = InputPort::new(20);
+        smelter
+            .item_limits
+            .insert(ItemId::named("Iron Ore").unwrap(), 10);
+        smelter
+            .item_limits
+            .insert(ItemId::named("Coal").unwrap(), 10);
+        assert_eq!(names(Some(&smelter)), ["Coal", "Iron Ore"]);
+        assert_eq!(
+            names(Some(&InputPort::new(20))),
+            ["Coal", "Gear", "Iron Ore"]
+        );
+        assert_eq!(names(None), ["Coal", "Gear", "Iron Ore"]);
+    }
+
+    fn last_rebuild(app: &App, content: Entity) -> Option<Tick> {
+        app.world()
+            .get::<MenuContent>(content)
+            .unwrap()
+            .last_updated
+    }
+
+    #[test]
+    fn storage_section_rebuilds_only_when_inventory_changes() {
+        let (mut app, building, content) = menu_app();
+
+        app.update();
+        let first = last_rebuild(&app, content);
+        assert!(first.is_some());
+
+        app.update();
+        assert_eq!(last_rebuild(&app, content), first);
+
+        app.world_mut()
+            .get_mut::<InputPort>(building)
+            .unwrap()
+            .add_item(ItemId::named("Iron Ore").unwrap(), 3);
+        app.update();
+        assert_ne!(last_rebuild(&app, content), first);
+    }
+
+    #[test]
+    fn crafter_ports_show_their_own_fill_and_flag_a_full_output() {
+        let (mut app, building, _) = menu_app();
+        let mut output = OutputPort::new(5);
+        output.add_item(ItemId::named("Iron Ingot").unwrap(), 5);
+        app.world_mut().entity_mut(building).insert(output);
+        app.update();
+
+        let mut texts = app.world_mut().query::<(&Text, &TextColor)>();
+        let usage: Vec<(String, Color)> = texts
+            .iter(app.world())
+            .filter(|(text, _)| text.0.contains('%'))
+            .map(|(text, color)| (text.0.clone(), color.0))
+            .collect();
+        let theme = UiTheme::default();
+        assert!(usage.contains(&("  0/10 (0%)".to_string(), theme.dim_text)));
+        assert!(usage.contains(&("  5/5 (100%) - Full".to_string(), theme.danger_color)));
+        assert_eq!(usage.len(), 2);
+    }
+
+    fn status_lines(app: &mut App, building: Entity) -> Vec<(String, Color)> {
+        app.world_mut().spawn(MenuContent {
+            target_building: building,
+            content_type: ContentType::Status,
+            last_updated: None,
+            recipe_state: None,
+        });
+        app.update();
+        let mut texts = app.world_mut().query::<(&Text, &TextColor)>();
+        texts
+            .iter(app.world())
+            .filter(|(text, _)| {
+                ["Power:", "Compute:", "Network:"]
+                    .iter()
+                    .any(|prefix| text.0.starts_with(prefix))
+            })
+            .map(|(text, color)| (text.0.clone(), color.0))
+            .collect()
+    }
+
+    #[test]
+    fn status_shows_power_compute_and_network_and_flags_a_power_shortfall() {
+        let theme = UiTheme::default();
+        let (mut app, building, _) = menu_app();
+        app.world_mut().entity_mut(building).insert((
+            Operational(Some(Vec::new())),
+            PowerConsumer { amount: 10 },
+            ComputeConsumer { amount: 5 },
+            Position { x: 2, y: 0 },
+        ));
+        app.world_mut()
+            .resource_mut::<NetworkConnectivity>()
+            .add_connected_cell(1, 0);
+        app.world_mut().resource_mut::<PowerGrid>().available = 20;
+
+        let mut lines = status_lines(&mut app, building);
+        lines.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            lines,
+            vec![
+                ("Compute: 5 draw (met)".to_string(), theme.dim_text),
+                ("Network: connected".to_string(), theme.dim_text),
+                ("Power: 10 draw (met)".to_string(), theme.dim_text),
+            ]
+        );
+
+        let (mut app, building, _) = menu_app();
+        app.world_mut().entity_mut(building).insert((
+            Operational(Some(Vec::new())),
+            PowerConsumer { amount: 10 },
+            Position { x: 2, y: 0 },
+        ));
+        app.world_mut().resource_mut::<PowerGrid>().available = -5;
+
+        let lines = status_lines(&mut app, building);
+        assert!(lines.contains(&("Power: 10 draw (unmet)".to_string(), theme.danger_color)));
+        assert!(lines.contains(&("Compute: 0 draw (met)".to_string(), theme.dim_text)));
+    }
+
+    #[test]
+    fn status_rebuilds_when_the_power_grid_changes() {
+        let theme = UiTheme::default();
+        let (mut app, building, _) = menu_app();
+        app.world_mut().entity_mut(building).insert((
+            Operational(Some(Vec::new())),
+            PowerConsumer { amount: 10 },
+            Position { x: 2, y: 0 },
+        ));
+        app.world_mut().resource_mut::<PowerGrid>().available = 20;
+        let lines = status_lines(&mut app, building);
+        assert!(lines.contains(&("Power: 10 draw (met)".to_string(), theme.dim_text)));
+
+        app.update();
+        app.world_mut().resource_mut::<PowerGrid>().available = -5;
+        app.update();
+        let mut texts = app.world_mut().query::<(&Text, &TextColor)>();
+        let power: Vec<(String, Color)> = texts
+            .iter(app.world())
+            .filter(|(text, _)| text.0.starts_with("Power:"))
+            .map(|(text, color)| (text.0.clone(), color.0))
+            .collect();
+        assert_eq!(
+            power,
+            vec![("Power: 10 draw (unmet)".to_string(), theme.danger_color)]
+        );
+    }
+
+    #[test]
+    fn status_rebuilds_when_the_network_changes() {
+        let (mut app, building, _) = menu_app();
+        app.world_mut()
+            .entity_mut(building)
+            .insert((Operational(Some(Vec::new())), Position { x: 2, y: 0 }));
+        let lines = status_lines(&mut app, building);
+        assert!(lines
+            .iter()
+            .any(|(text, _)| text == "Network: disconnected"));
+
+        app.update();
+        app.world_mut()
+            .resource_mut::<NetworkConnectivity>()
+            .add_connected_cell(1, 0);
+        app.update();
+        let mut texts = app.world_mut().query::<&Text>();
+        let network: Vec<String> = texts
+            .iter(app.world())
+            .filter(|text| text.0.starts_with("Network:"))
+            .map(|text| text.0.clone())
+            .collect();
+        assert_eq!(network, vec!["Network: connected".to_string()]);
+    }
+
+    #[test]
+    fn menu_rebuilds_are_capped_per_frame() {
+        let (mut app, building, _) = menu_app();
+        for _ in 0..MAX_MENU_REBUILDS_PER_FRAME {
+            app.world_mut().spawn(MenuContent {
+                target_building: building,
+                content_type: ContentType::Storage,
+                last_updated: None,
+                recipe_state: None,
+            });
+        }
+
+        app.update();
+        let mut contents = app.world_mut().query::<&MenuContent>();
+        let rebuilt = contents
+            .iter(app.world())
+            .filter(|content| content.last_updated.is_some())
+            .count();
+        assert_eq!(rebuilt, MAX_MENU_REBUILDS_PER_FRAME);
+
+        app.update();
+        let rebuilt = contents
+            .iter(app.world())
+            .filter(|content| content.last_updated.is_some())
+            .count();
+        assert_eq!(rebuilt, MAX_MENU_REBUILDS_PER_FRAME + 1);
+    }
+
+    #[test]
+    fn zero_time_recipes_are_clamped_and_show_finite_progress() {
+        let registry = RecipeRegistry::from_ron(
+            r#"[
+                (name: "Instant", inputs: {}, outputs: {"Iron Ore": 1}, crafting_time: 0.0),
+                (name: "Untimed", inputs: {}, outputs: {"Iron Ore": 1}),
+            ]"#,
+        )
+        .unwrap();
+
+        for name in ["Instant", "Untimed"] {
+            let recipe = registry.get_definition(name).unwrap();
+            assert!((recipe.crafting_time - MIN_CRAFTING_TIME).abs() < f32::EPSILON);
+
+            let mut timer = Timer::from_seconds(recipe.crafting_time, TimerMode::Repeating);
+            timer.tick(std::time::Duration::from_secs_f32(0.05));
+            let progress = crafting_progress(&timer);
+            assert!(progress.is_finite() && (0.0..=1.0).contains(&progress));
+        }
+
+        let empty = Timer::from_seconds(0.0, TimerMode::Repeating);
+        assert!(crafting_progress(&empty).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn changing_recipe_mid_craft_refunds_inputs_and_resets_the_timer() {
+        let mut app = App::new();
+        app.insert_resource(
+            RecipeRegistry::from_ron(
+                r#"[
+                    (name: "Iron Ingot", inputs: {"Iron Ore": 2}, outputs: {"Iron Ingot": 1}, crafting_time: 4.0),
+                    (name: "Copper Ingot", inputs: {"Copper Ore": 2}, outputs: {"Copper Ingot": 1}, crafting_time: 4.0),
+                ]"#,
+            )
+            .unwrap(),
+        )
+        .init_resource::<TechState>()
+        .init_resource::<ItemThroughput>()
+        .add_message::<RecipeChangeEvent>()
+        .add_systems(Update, apply_recipe_changes);
+        app.world_mut()
+            .resource_mut::<ItemThroughput>()
+            .record_consumed(ItemId::named("Iron Ore").unwrap(), 2);
+
+        let mut timer = Timer::from_seconds(4.0, TimerMode::Repeating);
+        timer.tick(std::time::Duration::from_secs(2));
+        let building = app
+            .world_mut()
+            .spawn((
+                Building,
+                InputPort::new(10),
+                RecipeCrafter {
+                    timer,
+                    current_recipe: Some("Iron Ingot".to_string()),
+                    available_recipes: vec!["Iron Ingot".to_string(), "Copper Ingot".to_string()],
+                    in_progress: Some("Iron Ingot".to_string()),
+                },
+            ))
+            .id();
+
+        app.world_mut().write_message(RecipeChangeEvent {
+            building_entity: building,
+            recipe_name: "Copper Ingot".to_string(),
+        });
+        app.update();
+
+        let crafter = app.world().get::<RecipeCrafter>(building).unwrap();
+        assert_eq!(crafter.current_recipe.as_deref(), Some("Copper Ingot"));
+        assert!(crafter.in_progress.is_none());
+        assert!(crafter.timer.elapsed().is_zero());
+        let input_port = app.world().get::<InputPort>(building).unwrap();
+        assert_eq!(
+            input_port.get_item_quantity(ItemId::named("Iron Ore").unwrap()),
+            2
+        );
+
+        let mut throughput = app.world_mut().resource_mut::<ItemThroughput>();
+        throughput.push_sample();
+        let sample = throughput
+            .samples(ItemId::named("Iron Ore").unwrap())
+            .last()
+            .copied();
+        assert_eq!(sample.unwrap_or_default().consumed, 0);
+    }
+
+    #[test]
+    fn copying_a_recipe_reaches_every_other_smelter_only() {
+        let mut app = App::new();
+        app.insert_resource(RecipeRegistry::from_ron("[]").unwrap())
+            .init_resource::<TechState>()
+            .init_resource::<ItemThroughput>()
+            .add_message::<RecipeChangeEvent>()
+            .add_systems(
+                Update,
+                (handle_copy_recipe_buttons, apply_recipe_changes).chain(),
+            );
+
+        let mut spawn_crafter = |name: &str, recipe: Option<&str>| {
+            app.world_mut()
+                .spawn((
+                    Building,
+                    Name::new(name.to_string()),
+                    RecipeCrafter {
+                        timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                        current_recipe: recipe.map(str::to_string),
+                        available_recipes: vec![
+                            "Iron Ingot".to_string(),
+                            "Copper Ingot".to_string(),
+                        ],
+                        in_progress: None,
+                    },
+                ))
+                .id()
+        };
+        let configured = spawn_crafter("Smelter", Some("Copper Ingot"));
+        let smelters = [
+            spawn_crafter("Smelter", None),
+            spawn_crafter("Smelter", Some("Iron Ingot")),
+        ];
+        let foundry = spawn_crafter("Foundry", Some("Iron Ingot"));
+        let iron_only = spawn_crafter("Smelter", Some("Iron Ingot"));
+        app.world_mut()
+            .get_mut::<RecipeCrafter>(iron_only)
+            .unwrap()
+            .available_recipes = vec!["Iron Ingot".to_string()];
+
+        app.world_mut().spawn((
+            CopyRecipeButton {
+                source_building: configured,
+            },
+            Interaction::Pressed,
+        ));
+        app.update();
+
+        let recipe_of = |building| {
+            app.world()
+                .get::<RecipeCrafter>(building)
+                .unwrap()
+                .current_recipe
+                .clone()
+        };
+        for smelter in smelters {
+            assert_eq!(recipe_of(smelter).as_deref(), Some("Copper Ingot"));
+        }
+        assert_eq!(recipe_of(foundry).as_deref(), Some("Iron Ingot"));
+        assert_eq!(recipe_of(iron_only).as_deref(), Some("Iron Ingot"));
+    }
+
+    #[test]
+    fn overflow_toggle_switches_output_evacuation_and_refreshes_the_section() {
+        let mut app = App::new();
+        app.add_systems(Update, handle_overflow_toggles);
+
+        let building = app.world_mut().spawn((Building, OutputPort::new(10))).id();
+        let content = app
+            .world_mut()
+            .spawn(MenuContent {
+                target_building: building,
+                content_type: ContentType::Logistics,
+                last_updated: Some(Tick::new(1)),
+                recipe_state: None,
+            })
+            .id();
+        let toggle = app
+            .world_mut()
+            .spawn((
+                OverflowToggle {
+                    target_building: building,
+                },
+                Interaction::Pressed,
+            ))
+            .id();
+
+        app.update();
+        assert!(app
+            .world()
+            .entity(building)
+            .contains::<AutoEvacuateOutput>());
+        assert_eq!(
+            app.world()
+                .get::<MenuContent>(content)
+                .unwrap()
+                .last_updated,
+            None
+        );
+
+        app.world_mut()
+            .get_mut::<Interaction>(toggle)
+            .unwrap()
+            .set_changed();
+        app.update();
+        assert!(!app
+            .world()
+            .entity(building)
+            .contains::<AutoEvacuateOutput>());
+    }
+
+    fn menu_left(app: &App, menu: Entity) -> Val {
+        app.world().get::<Node>(menu).unwrap().left
+    }
+
+    #[test]
+    fn pinned_menu_stays_put_when_the_camera_moves() {
+        let mut app = App::new();
+        app.add_systems(Update, update_menu_positions);
+
+        let mut projection = OrthographicProjection::default_2d();
+        projection.update(1280.0, 720.0);
+        app.world_mut().spawn(Window::default());
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera {
+                    computed: ComputedCameraValues {
+                        clip_from_view: projection.get_clip_from_view(),
+                        target_info: Some(RenderTargetInfo {
+                            physical_size: UVec2::new(1280, 720),
+                            scale_factor: 1.0,
+                        }),
+                        ..default()
+                    },
+                    ..default()
+                },
+                GlobalTransform::default(),
+            ))
+            .id();
+
+        let [floating, pinned] = [false, true].map(|pinned| {
+            app.world_mut()
+                .spawn((
+                    Node::default(),
+                    BuildingMenu {
+                        target_building: Entity::PLACEHOLDER,
+                        world_position: Vec2::ZERO,
+                        pinned,
+                    },
+                ))
+                .id()
+        });
+
+        app.update();
+        let floating_before = menu_left(&app, floating);
+        let pinned_before = menu_left(&app, pinned);
+
+        *app.world_mut().get_mut::<GlobalTransform>(camera).unwrap() =
+            GlobalTransform::from_xyz(100.0, 0.0, 0.0);
+        app.update();
+
+        assert_ne!(menu_left(&app, floating), floating_before);
+        assert_eq!(menu_left(&app, pinned), pinned_before);
+    }
+
+    #[test]
+    fn successive_pins_cascade_from_the_top_right_corner() {
+        let first = pinned_menu_position(1280.0, 0);
+        assert_eq!(first, (1280.0 - MENU_WIDTH - MENU_MARGIN, TOP_BAR_HEIGHT));
+
+        let second = pinned_menu_position(1280.0, 1);
+        assert_eq!(
+            second,
+            (first.0 - PIN_CASCADE_STEP, first.1 + PIN_CASCADE_STEP)
+        );
+        assert_eq!(pinned_menu_position(1280.0, MAX_PIN_CASCADE), first);
+    }
+
+    #[test]
+    fn inventory_rows_count_stacks_past_the_first() {
+        assert_eq!(inventory_row_label("Circuit", 25, 25), "  Circuit: 25");
+        assert_eq!(
+            inventory_row_label("Circuit", 26, 25),
+            "  Circuit: 26 (2 stacks)"
+        );
+    }
+}
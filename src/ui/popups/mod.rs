@@ -1,5 +1,7 @@
 pub mod building_menu;
+pub mod toast;
 pub mod tooltip;
 
 pub use building_menu::BuildingMenuPlugin;
+pub use toast::ToastPlugin;
 pub use tooltip::TooltipsPlugin;
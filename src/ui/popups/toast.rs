@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+
+use crate::{
+    materials::ItemRegistry,
+    structures::ResearchCompletedEvent,
+    systems::{AutoPauseEvent, StarvationAlert},
+    ui::{
+        reachability::ReachabilityReport,
+        style::{ThemeRole, UiTheme, TOP_BAR_HEIGHT},
+        UISystemSet,
+    },
+};
+
+#[derive(Component)]
+pub struct Toast {
+    pub timer: Timer,
+}
+
+fn spawn_toast(commands: &mut Commands, message: String, theme: &UiTheme) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(TOP_BAR_HEIGHT + 12.0),
+                left: Val::Percent(50.0),
+                padding: UiRect::axes(Val::Px(14.0), Val::Px(8.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            theme.background(ThemeRole::PopupBg),
+            theme.border(ThemeRole::PanelBorder),
+            Toast {
+                timer: Timer::from_seconds(4.0, TimerMode::Once),
+            },
+        ))
+        .with_children(|toast| {
+            toast.spawn((
+                Text::new(message),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Header),
+            ));
+        });
+}
+
+fn show_research_toasts(
+    mut commands: Commands,
+    mut completed: MessageReader<ResearchCompletedEvent>,
+    theme: Res<UiTheme>,
+) {
+    for event in completed.read() {
+        spawn_toast(
+            &mut commands,
+            format!("Unlocked: {}", event.unlocked),
+            &theme,
+        );
+    }
+}
+
+fn show_auto_pause_toasts(
+    mut commands: Commands,
+    mut paused: MessageReader<AutoPauseEvent>,
+    theme: Res<UiTheme>,
+) {
+    for event in paused.read() {
+        spawn_toast(
+            &mut commands,
+            format!("Paused: {} (P to resume)", event.reason),
+            &theme,
+        );
+    }
+}
+
+fn show_reachability_toasts(
+    mut commands: Commands,
+    mut reports: MessageReader<ReachabilityReport>,
+    names: Query<&Name>,
+    theme: Res<UiTheme>,
+) {
+    let name_of = |entity| names.get(entity).map_or("?", Name::as_str);
+    for report in reports.read() {
+        let (from, to) = (name_of(report.from), name_of(report.to));
+        spawn_toast(&mut commands, format!("{from} -> {to}: {report}"), &theme);
+    }
+}
+
+fn show_starvation_toasts(
+    mut commands: Commands,
+    mut alerts: MessageReader<StarvationAlert>,
+    names: Query<&Name>,
+    item_registry: Res<ItemRegistry>,
+    theme: Res<UiTheme>,
+) {
+    for alert in alerts.read() {
+        let name = names.get(alert.building).map_or("A crafter", Name::as_str);
+        let items: Vec<&str> = alert
+            .items
+            .iter()
+            .map(|&item| item_registry.display_name(item))
+            .collect();
+        spawn_toast(
+            &mut commands,
+            format!("{name} starved of {}", items.join(", ")),
+            &theme,
+        );
+    }
+}
+
+/// Counts real time so toasts still expire while the simulation is paused.
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toasts: Query<(Entity, &mut Toast)>,
+) {
+    for (entity, mut toast) in &mut toasts {
+        if toast.timer.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                show_research_toasts,
+                show_auto_pause_toasts,
+                show_reachability_toasts,
+                show_starvation_toasts,
+                expire_toasts,
+            )
+                .in_set(UISystemSet::EntityManagement),
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ui::style::restyle_on_theme_change;
+
+    fn toast_backgrounds(app: &mut App) -> Vec<Color> {
+        app.world_mut()
+            .query_filtered::<&BackgroundColor, With<Toast>>()
+            .iter(app.world())
+            .map(|bg| bg.0)
+            .collect()
+    }
+
+    #[test]
+    fn toasts_spawned_after_a_theme_switch_use_the_new_palette() {
+        let mut app = App::new();
+        app.add_message::<ResearchCompletedEvent>()
+            .init_resource::<UiTheme>()
+            .add_systems(
+                Update,
+                (show_research_toasts, restyle_on_theme_change).chain(),
+            );
+
+        let unlocked = |building| ResearchCompletedEvent {
+            building,
+            unlocked: "Electronic Circuit".to_string(),
+        };
+        let lab = app.world_mut().spawn_empty().id();
+        app.world_mut().write_message(unlocked(lab));
+        app.update();
+        assert_eq!(toast_backgrounds(&mut app), vec![UiTheme::dark().popup_bg]);
+
+        *app.world_mut().resource_mut::<UiTheme>() = UiTheme::high_contrast();
+        app.update();
+        app.world_mut().write_message(unlocked(lab));
+        app.update();
+
+        let high_contrast = UiTheme::high_contrast().popup_bg;
+        assert_ne!(high_contrast, UiTheme::dark().popup_bg);
+        assert_eq!(
+            toast_backgrounds(&mut app),
+            vec![high_contrast, high_contrast],
+            "the new toast uses the new theme and the old one is restyled"
+        );
+    }
+}
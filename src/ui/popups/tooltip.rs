@@ -1,6 +1,10 @@
+use crate::materials::ItemRegistry;
 use crate::structures::{BuildingComponentDef, BuildingRegistry};
 use crate::ui::panels::action_bar::build_panel::BuildingButton;
-use crate::ui::UISystemSet;
+use crate::ui::{
+    style::{ThemeRole, UiTheme},
+    UISystemSet,
+};
 use bevy::prelude::*;
 use bevy::ui::UiGlobalTransform;
 
@@ -71,8 +75,10 @@ pub fn update_tooltip_timers(
     mut timer_query: Query<(Entity, &mut TooltipTimer)>,
     button_query: Query<(&BuildingButton, &UiGlobalTransform), With<TooltipTarget>>,
     registry: Res<BuildingRegistry>,
+    item_registry: Res<ItemRegistry>,
     time: Res<Time>,
     existing_tooltips: Query<Entity, With<Tooltip>>,
+    theme: Res<UiTheme>,
 ) {
     for (timer_entity, mut tooltip_timer) in &mut timer_query {
         tooltip_timer.timer.tick(time.delta());
@@ -88,8 +94,13 @@ pub fn update_tooltip_timers(
                 button_query.get(tooltip_timer.target_entity)
             {
                 if let Some(definition) = registry.get_definition(&building_button.building_name) {
-                    let tooltip_content = generate_tooltip_content(definition);
-                    spawn_tooltip(&mut commands, tooltip_content, button_transform.translation);
+                    let tooltip_lines = generate_tooltip_lines(definition, &item_registry, &theme);
+                    spawn_tooltip(
+                        &mut commands,
+                        tooltip_lines,
+                        button_transform.translation,
+                        &theme,
+                    );
                 } else {
                     warn!(
                         "Building definition not found for tooltip: {}",
@@ -101,7 +112,13 @@ pub fn update_tooltip_timers(
     }
 }
 
-fn spawn_tooltip(commands: &mut Commands, content: String, position: Vec2) {
+fn spawn_tooltip(
+    commands: &mut Commands,
+    lines: Vec<(String, Color)>,
+    position: Vec2,
+    theme: &UiTheme,
+) {
+    let content = lines.iter().map(|(line, _)| line.as_str()).collect();
     commands
         .spawn((
             Node {
@@ -111,49 +128,71 @@ fn spawn_tooltip(commands: &mut Commands, content: String, position: Vec2) {
                 max_width: Val::Px(300.0),
                 padding: UiRect::all(Val::Px(12.0)),
                 border: UiRect::all(Val::Px(2.0)),
+                flex_direction: FlexDirection::Column,
                 ..default()
             },
-            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.95)),
-            BorderColor::all(Color::srgb(0.6, 0.6, 0.6)),
-            Tooltip {
-                content: content.clone(),
-            },
+            theme.background(ThemeRole::PopupBg),
+            theme.border(ThemeRole::PanelBorder),
+            Tooltip { content },
             TooltipContainer,
         ))
         .with_children(|parent| {
-            parent.spawn((
-                Text::new(content),
-                TextFont {
-                    font_size: 12.0,
-                    ..default()
-                },
-                TextColor(Color::srgb(0.9, 0.9, 0.9)),
-            ));
+            for (line, color) in lines {
+                parent.spawn((
+                    Text::new(line.trim_end_matches('\n')),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+            }
         });
 }
 
-#[allow(clippy::too_many_lines)]
-fn generate_tooltip_content(definition: &crate::structures::BuildingDef) -> String {
-    use std::fmt::Write;
-
-    let mut content = String::new();
-
-    let _ = writeln!(content, "{}", definition.name);
-    let _ = writeln!(content, "Category: {:?}\n", definition.category);
+/// Tooltip text split into coloured blocks: cost lines take their item's
+/// rarity colour, everything else the header colour.
+fn generate_tooltip_lines(
+    definition: &crate::structures::BuildingDef,
+    item_registry: &ItemRegistry,
+    theme: &UiTheme,
+) -> Vec<(String, Color)> {
+    let mut lines = vec![(
+        format!(
+            "{}\nCategory: {:?}\n\nCost:\n",
+            definition.name, definition.category
+        ),
+        theme.header_color,
+    )];
 
-    content.push_str("Cost:\n");
     if definition.placement.cost.inputs.is_empty() {
-        content.push_str("  Free\n");
+        lines.push(("  Free\n".to_string(), theme.header_color));
     } else {
         let mut sorted_inputs: Vec<_> = definition.placement.cost.inputs.iter().collect();
-        sorted_inputs.sort_by_key(|(name, _)| name.as_str());
+        sorted_inputs.sort_by_key(|&(&item, _)| item_registry.display_name(item));
 
-        for (item_name, quantity) in sorted_inputs {
-            let _ = writeln!(content, "  {quantity} {item_name}");
+        for (&item, quantity) in sorted_inputs {
+            lines.push((
+                format!("  {quantity} {}\n", item_registry.display_name(item)),
+                item_registry.rarity_of(item).color(),
+            ));
         }
     }
 
-    content.push_str("\nCapabilities:\n");
+    lines.push((
+        format!("\n{}", generate_capabilities_content(definition)),
+        theme.header_color,
+    ));
+    lines
+}
+
+#[allow(clippy::too_many_lines)]
+fn generate_capabilities_content(definition: &crate::structures::BuildingDef) -> String {
+    use std::fmt::Write;
+
+    let mut content = String::new();
+
+    content.push_str("Capabilities:\n");
     let mut has_capabilities = false;
 
     for component in &definition.components {
@@ -206,6 +245,10 @@ fn generate_tooltip_content(definition: &crate::structures::BuildingDef) -> Stri
                 let _ = writeln!(content, "  - Output port: {capacity} capacity");
                 has_capabilities = true;
             }
+            BuildingComponentDef::NamedOutputPort { tag, capacity } => {
+                let _ = writeln!(content, "  - Output port '{tag}': {capacity} capacity");
+                has_capabilities = true;
+            }
             BuildingComponentDef::StoragePort { capacity } => {
                 let _ = writeln!(
                     content,
@@ -217,6 +260,42 @@ fn generate_tooltip_content(definition: &crate::structures::BuildingDef) -> Stri
                 content.push_str("  - Launches items for score\n");
                 has_capabilities = true;
             }
+            BuildingComponentDef::Sink { accepted, .. } => {
+                match accepted {
+                    Some(items) => {
+                        let _ =
+                            writeln!(content, "  - Consumes {} item types for score", items.len());
+                    }
+                    None => content.push_str("  - Consumes any item for score\n"),
+                }
+                has_capabilities = true;
+            }
+            BuildingComponentDef::Research {
+                target_unlock,
+                required,
+            } => {
+                let total: u32 = required.values().sum();
+                let _ = writeln!(
+                    content,
+                    "  - Unlocks '{target_unlock}' after {total} science items"
+                );
+                has_capabilities = true;
+            }
+            BuildingComponentDef::CapacityUpgrade { tiers } => {
+                let _ = writeln!(content, "  - Upgradable capacity: {} tiers", tiers.len());
+                has_capabilities = true;
+            }
+            BuildingComponentDef::DispatchWeight => {
+                let _ = writeln!(content, "  - Receives deliveries in proportion to capacity");
+                has_capabilities = true;
+            }
+            BuildingComponentDef::Conveyor { items_per_tick } => {
+                let _ = writeln!(
+                    content,
+                    "  - Moves {items_per_tick} items per tick in its facing direction"
+                );
+                has_capabilities = true;
+            }
         }
     }
 
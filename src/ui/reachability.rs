@@ -0,0 +1,175 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{
+    grid::{Grid, Position},
+    systems::NetworkConnectivity,
+    ui::{popups::building_menu::BuildingClickEvent, UISystemSet, UiMode},
+    workers::pathfinding::calculate_path,
+};
+
+/// Buildings picked for the next reachability test, in click order.
+#[derive(Resource, Debug, Default)]
+pub struct ReachabilityProbe {
+    pub picks: Vec<Entity>,
+}
+
+/// Outcome of a reachability test between two buildings.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct ReachabilityReport {
+    pub from: Entity,
+    pub to: Entity,
+    /// Number of steps in the path `calculate_path` found, if any.
+    pub path_len: Option<usize>,
+    /// Whether `NetworkConnectivity` marks both buildings' cells as connected.
+    pub network_connected: bool,
+}
+
+impl ReachabilityReport {
+    #[must_use]
+    pub fn reachable(&self) -> bool {
+        self.path_len.is_some()
+    }
+}
+
+impl fmt::Display for ReachabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path_len {
+            Some(len) => write!(f, "reachable in {len} steps")?,
+            None => write!(f, "unreachable")?,
+        }
+        let network = if self.network_connected {
+            "connected"
+        } else {
+            "disconnected"
+        };
+        write!(f, ", network {network}")
+    }
+}
+
+fn probe_key_held(keyboard: &ButtonInput<KeyCode>) -> bool {
+    keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
+/// Alt-clicking a building adds it to the reachability probe.
+fn pick_reachability_buildings(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut click_events: MessageReader<BuildingClickEvent>,
+    mut probe: ResMut<ReachabilityProbe>,
+) {
+    for click in click_events.read() {
+        if !probe_key_held(&keyboard) || probe.picks.contains(&click.building_entity) {
+            continue;
+        }
+        probe.picks.push(click.building_entity);
+    }
+}
+
+/// Once two buildings are picked, runs the pathfinder and the network check
+/// between them and clears the probe for the next pair.
+pub fn run_reachability_test(
+    mut probe: ResMut<ReachabilityProbe>,
+    positions: Query<&Position>,
+    network: Res<NetworkConnectivity>,
+    grid: Res<Grid>,
+    mut reports: MessageWriter<ReachabilityReport>,
+) {
+    if probe.picks.len() < 2 {
+        return;
+    }
+    let picks: Vec<Entity> = probe.picks.drain(..).collect();
+    let (from, to) = (picks[0], picks[1]);
+    let (Ok(start), Ok(end)) = (positions.get(from), positions.get(to)) else {
+        return;
+    };
+
+    let path = calculate_path((start.x, start.y), (end.x, end.y), &network, &grid);
+    let report = ReachabilityReport {
+        from,
+        to,
+        path_len: path.map(|waypoints| waypoints.len()),
+        network_connected: network.is_cell_connected(start.x, start.y)
+            && network.is_cell_connected(end.x, end.y),
+    };
+    info!(?from, ?to, %report, "reachability test");
+    reports.write(report);
+}
+
+pub struct ReachabilityPlugin;
+
+impl Plugin for ReachabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReachabilityProbe>()
+            .add_message::<ReachabilityReport>()
+            .add_systems(
+                Update,
+                (
+                    pick_reachability_buildings
+                        .run_if(in_state(UiMode::Observe))
+                        .in_set(UISystemSet::InputDetection),
+                    run_reachability_test.in_set(UISystemSet::EntityManagement),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_probe(app: &mut App, from: (i32, i32), to: (i32, i32)) -> Vec<ReachabilityReport> {
+        let picks = [from, to].map(|(x, y)| app.world_mut().spawn(Position { x, y }).id());
+        app.world_mut().resource_mut::<ReachabilityProbe>().picks = picks.to_vec();
+        app.update();
+        app.world()
+            .resource::<Messages<ReachabilityReport>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect()
+    }
+
+    fn probe_app() -> App {
+        let mut network = NetworkConnectivity::default();
+        let mut grid = Grid::new(64.0);
+        for x in 0..=3 {
+            network.add_connected_cell(x, 0);
+            network.add_core_network_cell(x, 0);
+            grid.add_coordinate(x, 0);
+        }
+        grid.add_coordinate(8, 0);
+
+        let mut app = App::new();
+        app.insert_resource(network)
+            .insert_resource(grid)
+            .init_resource::<ReachabilityProbe>()
+            .add_message::<ReachabilityReport>()
+            .add_systems(Update, run_reachability_test);
+        app
+    }
+
+    #[test]
+    fn connected_buildings_report_the_path_length() {
+        let mut app = probe_app();
+
+        let reports = run_probe(&mut app, (0, 0), (3, 0));
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].reachable());
+        assert_eq!(reports[0].path_len, Some(3));
+        assert!(reports[0].network_connected);
+        assert!(app.world().resource::<ReachabilityProbe>().picks.is_empty());
+    }
+
+    #[test]
+    fn disconnected_buildings_report_unreachable() {
+        let mut app = probe_app();
+
+        let reports = run_probe(&mut app, (0, 0), (8, 0));
+
+        assert_eq!(reports.len(), 1);
+        assert!(!reports[0].reachable());
+        assert!(!reports[0].network_connected);
+        assert_eq!(reports[0].to_string(), "unreachable, network disconnected");
+    }
+}
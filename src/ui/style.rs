@@ -2,43 +2,308 @@ use bevy::{picking::hover::Hovered, prelude::*, ui::Checked, ui::Pressed};
 
 use crate::ui::UISystemSet;
 
-pub const PANEL_BG: Color = Color::srgba(0.1, 0.1, 0.15, 0.9);
-pub const CARD_BG: Color = Color::srgba(0.15, 0.15, 0.2, 0.8);
-pub const POPUP_BG: Color = Color::srgba(0.1, 0.1, 0.15, 0.95);
-pub const PANEL_BORDER: Color = Color::srgb(0.3, 0.4, 0.6);
+/// The UI palette. Spawn code reads colors from this resource, and replacing
+/// it restyles the UI already on screen (see `restyle_on_theme_change`).
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct UiTheme {
+    pub name: &'static str,
 
-pub const HEADER_COLOR: Color = Color::srgb(0.85, 0.85, 0.95);
-pub const TEXT_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
-pub const DIM_TEXT: Color = Color::srgb(0.5, 0.5, 0.5);
+    pub panel_bg: Color,
+    pub card_bg: Color,
+    pub popup_bg: Color,
+    pub panel_border: Color,
 
-pub const BUTTON_BG: Color = Color::srgb(0.2, 0.2, 0.3);
-pub const BUTTON_HOVER: Color = Color::srgb(0.3, 0.3, 0.45);
+    pub header_color: Color,
+    pub text_color: Color,
+    pub dim_text: Color,
 
-pub const CONFIRM_BG: Color = Color::srgb(0.15, 0.35, 0.15);
-pub const CONFIRM_HOVER: Color = Color::srgb(0.2, 0.5, 0.2);
+    pub button_bg: Color,
+    pub button_hover: Color,
 
-pub const CANCEL_BG: Color = Color::srgb(0.35, 0.15, 0.15);
-pub const CANCEL_HOVER: Color = Color::srgb(0.5, 0.2, 0.2);
+    pub confirm_bg: Color,
+    pub confirm_hover: Color,
+    pub confirm_border: Color,
 
-pub const SELECTED_BG: Color = Color::srgb(0.15, 0.25, 0.35);
-pub const SELECTED_BORDER: Color = Color::srgb(0.3, 0.5, 0.7);
+    pub cancel_bg: Color,
+    pub cancel_hover: Color,
+    pub cancel_border: Color,
 
-pub const TOP_BAR_BG: Color = Color::srgba(0.06, 0.06, 0.1, 0.95);
-pub const ACTION_BAR_BG: Color = Color::srgba(0.08, 0.08, 0.12, 0.95);
-pub const ACTION_BUTTON_BG: Color = Color::srgba(0.15, 0.15, 0.22, 0.9);
-pub const ACTION_BUTTON_HOVER: Color = Color::srgba(0.22, 0.22, 0.35, 0.95);
+    pub selected_bg: Color,
+    pub selected_border: Color,
 
-pub const POWER_COLOR: Color = Color::srgb(1.0, 0.86, 0.2);
-pub const COMPUTE_COLOR: Color = Color::srgb(0.31, 0.78, 1.0);
-pub const WORKER_COLOR: Color = Color::srgb(0.31, 0.86, 0.39);
-pub const SCORE_COLOR: Color = Color::srgb(1.0, 0.78, 0.2);
-pub const WARNING_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
-pub const DANGER_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+    pub top_bar_bg: Color,
+    pub action_bar_bg: Color,
+    pub action_button_bg: Color,
+    pub action_button_hover: Color,
+
+    pub power_color: Color,
+    pub compute_color: Color,
+    pub worker_color: Color,
+    pub score_color: Color,
+    pub warning_color: Color,
+    pub danger_color: Color,
+    pub success_color: Color,
+}
+
+/// A palette entry. Colors spawned through `UiTheme::background`, `text` and
+/// `border` carry their role, so a theme switch maps them by role even where
+/// a palette gives two roles the same value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeRole {
+    PanelBg,
+    CardBg,
+    PopupBg,
+    PanelBorder,
+    Header,
+    Text,
+    DimText,
+    Button,
+    ButtonHover,
+    Confirm,
+    ConfirmHover,
+    ConfirmBorder,
+    Cancel,
+    CancelHover,
+    CancelBorder,
+    Selected,
+    SelectedBorder,
+    TopBar,
+    ActionBar,
+    ActionButton,
+    ActionButtonHover,
+    Power,
+    Compute,
+    Worker,
+    Score,
+    Warning,
+    Danger,
+    Success,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedBackground(pub ThemeRole);
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedText(pub ThemeRole);
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ThemedBorder(pub ThemeRole);
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl UiTheme {
+    #[must_use]
+    pub fn dark() -> Self {
+        Self {
+            name: "Dark",
+            panel_bg: Color::srgba(0.1, 0.1, 0.15, 0.9),
+            card_bg: Color::srgba(0.15, 0.15, 0.2, 0.8),
+            popup_bg: Color::srgba(0.1, 0.1, 0.15, 0.95),
+            panel_border: Color::srgb(0.3, 0.4, 0.6),
+            header_color: Color::srgb(0.85, 0.85, 0.95),
+            text_color: Color::srgb(0.8, 0.8, 0.8),
+            dim_text: Color::srgb(0.5, 0.5, 0.5),
+            button_bg: Color::srgb(0.2, 0.2, 0.3),
+            button_hover: Color::srgb(0.3, 0.3, 0.45),
+            confirm_bg: Color::srgb(0.15, 0.35, 0.15),
+            confirm_hover: Color::srgb(0.2, 0.5, 0.2),
+            confirm_border: Color::srgb(0.3, 0.5, 0.3),
+            cancel_bg: Color::srgb(0.35, 0.15, 0.15),
+            cancel_hover: Color::srgb(0.5, 0.2, 0.2),
+            cancel_border: Color::srgb(0.5, 0.3, 0.3),
+            selected_bg: Color::srgb(0.15, 0.25, 0.35),
+            selected_border: Color::srgb(0.3, 0.5, 0.7),
+            top_bar_bg: Color::srgba(0.06, 0.06, 0.1, 0.95),
+            action_bar_bg: Color::srgba(0.08, 0.08, 0.12, 0.95),
+            action_button_bg: Color::srgba(0.15, 0.15, 0.22, 0.9),
+            action_button_hover: Color::srgba(0.22, 0.22, 0.35, 0.95),
+            power_color: Color::srgb(1.0, 0.86, 0.2),
+            compute_color: Color::srgb(0.31, 0.78, 1.0),
+            worker_color: Color::srgb(0.31, 0.86, 0.39),
+            score_color: Color::srgb(1.0, 0.78, 0.2),
+            warning_color: Color::srgb(1.0, 0.85, 0.3),
+            danger_color: Color::srgb(1.0, 0.3, 0.3),
+            success_color: Color::srgb(0.3, 0.8, 0.3),
+        }
+    }
+
+    #[must_use]
+    pub fn light() -> Self {
+        Self {
+            name: "Light",
+            panel_bg: Color::srgba(0.92, 0.92, 0.95, 0.95),
+            card_bg: Color::srgba(0.85, 0.86, 0.9, 0.9),
+            popup_bg: Color::srgba(0.96, 0.96, 0.98, 0.97),
+            panel_border: Color::srgb(0.55, 0.6, 0.75),
+            header_color: Color::srgb(0.1, 0.1, 0.2),
+            text_color: Color::srgb(0.2, 0.2, 0.25),
+            dim_text: Color::srgb(0.45, 0.45, 0.5),
+            button_bg: Color::srgb(0.78, 0.8, 0.88),
+            button_hover: Color::srgb(0.68, 0.72, 0.85),
+            confirm_bg: Color::srgb(0.65, 0.85, 0.65),
+            confirm_hover: Color::srgb(0.55, 0.8, 0.55),
+            confirm_border: Color::srgb(0.35, 0.6, 0.35),
+            cancel_bg: Color::srgb(0.9, 0.68, 0.68),
+            cancel_hover: Color::srgb(0.85, 0.55, 0.55),
+            cancel_border: Color::srgb(0.65, 0.35, 0.35),
+            selected_bg: Color::srgb(0.7, 0.8, 0.95),
+            selected_border: Color::srgb(0.25, 0.45, 0.8),
+            top_bar_bg: Color::srgba(0.88, 0.88, 0.92, 0.97),
+            action_bar_bg: Color::srgba(0.86, 0.86, 0.9, 0.97),
+            action_button_bg: Color::srgba(0.8, 0.82, 0.88, 0.95),
+            action_button_hover: Color::srgba(0.7, 0.74, 0.86, 0.97),
+            power_color: Color::srgb(0.75, 0.55, 0.0),
+            compute_color: Color::srgb(0.0, 0.45, 0.75),
+            worker_color: Color::srgb(0.1, 0.55, 0.2),
+            score_color: Color::srgb(0.7, 0.45, 0.0),
+            warning_color: Color::srgb(0.75, 0.5, 0.0),
+            danger_color: Color::srgb(0.8, 0.1, 0.1),
+            success_color: Color::srgb(0.15, 0.55, 0.15),
+        }
+    }
+
+    #[must_use]
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "High Contrast",
+            panel_bg: Color::srgba(0.0, 0.0, 0.0, 0.97),
+            card_bg: Color::srgba(0.08, 0.08, 0.08, 0.97),
+            popup_bg: Color::srgb(0.0, 0.0, 0.0),
+            panel_border: Color::srgb(1.0, 1.0, 1.0),
+            header_color: Color::srgb(1.0, 1.0, 1.0),
+            text_color: Color::srgb(1.0, 1.0, 1.0),
+            dim_text: Color::srgb(0.8, 0.8, 0.8),
+            button_bg: Color::srgb(0.15, 0.15, 0.15),
+            button_hover: Color::srgb(0.35, 0.35, 0.0),
+            confirm_bg: Color::srgb(0.0, 0.4, 0.0),
+            confirm_hover: Color::srgb(0.0, 0.6, 0.0),
+            confirm_border: Color::srgb(0.0, 1.0, 0.0),
+            cancel_bg: Color::srgb(0.45, 0.0, 0.0),
+            cancel_hover: Color::srgb(0.7, 0.0, 0.0),
+            cancel_border: Color::srgb(1.0, 0.2, 0.2),
+            selected_bg: Color::srgb(0.0, 0.2, 0.5),
+            selected_border: Color::srgb(1.0, 1.0, 0.0),
+            top_bar_bg: Color::srgb(0.0, 0.0, 0.0),
+            action_bar_bg: Color::srgba(0.02, 0.02, 0.02, 1.0),
+            action_button_bg: Color::srgb(0.12, 0.12, 0.12),
+            action_button_hover: Color::srgb(0.3, 0.3, 0.0),
+            power_color: Color::srgb(1.0, 1.0, 0.0),
+            compute_color: Color::srgb(0.0, 1.0, 1.0),
+            worker_color: Color::srgb(0.0, 1.0, 0.0),
+            score_color: Color::srgb(1.0, 0.8, 0.0),
+            warning_color: Color::srgb(1.0, 0.65, 0.0),
+            danger_color: Color::srgb(1.0, 0.0, 0.0),
+            success_color: Color::srgb(0.2, 1.0, 0.2),
+        }
+    }
+
+    /// The built-in themes, in the order the theme hotkey cycles through them.
+    #[must_use]
+    pub fn builtin() -> [Self; 3] {
+        [Self::dark(), Self::light(), Self::high_contrast()]
+    }
+
+    #[must_use]
+    pub fn next_builtin(&self) -> Self {
+        let themes = Self::builtin();
+        let current = themes
+            .iter()
+            .position(|theme| theme.name == self.name)
+            .unwrap_or(0);
+        themes[(current + 1) % themes.len()].clone()
+    }
+
+    /// Indexed by `ThemeRole`, in declaration order.
+    fn palette(&self) -> [Color; 28] {
+        [
+            self.panel_bg,
+            self.card_bg,
+            self.popup_bg,
+            self.panel_border,
+            self.header_color,
+            self.text_color,
+            self.dim_text,
+            self.button_bg,
+            self.button_hover,
+            self.confirm_bg,
+            self.confirm_hover,
+            self.confirm_border,
+            self.cancel_bg,
+            self.cancel_hover,
+            self.cancel_border,
+            self.selected_bg,
+            self.selected_border,
+            self.top_bar_bg,
+            self.action_bar_bg,
+            self.action_button_bg,
+            self.action_button_hover,
+            self.power_color,
+            self.compute_color,
+            self.worker_color,
+            self.score_color,
+            self.warning_color,
+            self.danger_color,
+            self.success_color,
+        ]
+    }
+
+    #[must_use]
+    pub fn color(&self, role: ThemeRole) -> Color {
+        self.palette()[role as usize]
+    }
+
+    #[must_use]
+    pub fn background(&self, role: ThemeRole) -> (BackgroundColor, ThemedBackground) {
+        (BackgroundColor(self.color(role)), ThemedBackground(role))
+    }
+
+    #[must_use]
+    pub fn text(&self, role: ThemeRole) -> (TextColor, ThemedText) {
+        (TextColor(self.color(role)), ThemedText(role))
+    }
+
+    #[must_use]
+    pub fn border(&self, role: ThemeRole) -> (BorderColor, ThemedBorder) {
+        (BorderColor::all(self.color(role)), ThemedBorder(role))
+    }
+
+    /// Maps a color from `previous` to this theme. A color still showing its
+    /// spawn `role` follows that role; any other color is matched by value,
+    /// but only when exactly one role in `previous` has it. Everything else
+    /// (item rarities, port tints, values shared by two roles) is kept.
+    #[must_use]
+    pub fn translate(&self, previous: &Self, role: Option<ThemeRole>, color: Color) -> Color {
+        if let Some(role) = role.filter(|&role| previous.color(role) == color) {
+            return self.color(role);
+        }
+        let palette = previous.palette();
+        let mut roles = (0..palette.len()).filter(|&role| palette[role] == color);
+        match (roles.next(), roles.next()) {
+            (Some(role), None) => self.palette()[role],
+            _ => color,
+        }
+    }
+}
 
 pub const TOP_BAR_HEIGHT: f32 = 44.0;
 pub const ACTION_BAR_WIDTH: f32 = 48.0;
 pub const ACTION_BUTTON_SIZE: f32 = 40.0;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonKind {
+    Default,
+    Confirm,
+    Cancel,
+    Close,
+    Tab,
+    ActionBar,
+    Building,
+}
+
 #[derive(Component, Clone)]
 pub struct ButtonStyle {
     pub default_bg: Color,
@@ -47,90 +312,112 @@ pub struct ButtonStyle {
     pub hovered_border: Option<Color>,
     pub selected_bg: Option<Color>,
     pub selected_border: Option<Color>,
+    kind: ButtonKind,
 }
 
 impl ButtonStyle {
+    /// The same kind of style, built from `theme`.
+    #[must_use]
+    pub fn restyled(&self, theme: &UiTheme) -> Self {
+        match self.kind {
+            ButtonKind::Default => Self::default_button(theme),
+            ButtonKind::Confirm => Self::confirm(theme),
+            ButtonKind::Cancel => Self::cancel(theme),
+            ButtonKind::Close => Self::close(theme),
+            ButtonKind::Tab => Self::tab(theme),
+            ButtonKind::ActionBar => Self::action_bar(theme),
+            ButtonKind::Building => Self::building_button(theme),
+        }
+    }
+
     #[must_use]
-    pub fn default_button() -> Self {
+    pub fn default_button(theme: &UiTheme) -> Self {
         Self {
-            default_bg: BUTTON_BG,
-            hovered_bg: BUTTON_HOVER,
+            default_bg: theme.button_bg,
+            hovered_bg: theme.button_hover,
             default_border: None,
             hovered_border: None,
             selected_bg: None,
             selected_border: None,
+            kind: ButtonKind::Default,
         }
     }
 
     #[must_use]
-    pub fn confirm() -> Self {
+    pub fn confirm(theme: &UiTheme) -> Self {
         Self {
-            default_bg: CONFIRM_BG,
-            hovered_bg: CONFIRM_HOVER,
+            default_bg: theme.confirm_bg,
+            hovered_bg: theme.confirm_hover,
             default_border: None,
             hovered_border: None,
             selected_bg: None,
             selected_border: None,
+            kind: ButtonKind::Confirm,
         }
     }
 
     #[must_use]
-    pub fn cancel() -> Self {
+    pub fn cancel(theme: &UiTheme) -> Self {
         Self {
-            default_bg: CANCEL_BG,
-            hovered_bg: CANCEL_HOVER,
+            default_bg: theme.cancel_bg,
+            hovered_bg: theme.cancel_hover,
             default_border: None,
             hovered_border: None,
             selected_bg: None,
             selected_border: None,
+            kind: ButtonKind::Cancel,
         }
     }
 
     #[must_use]
-    pub fn close() -> Self {
+    pub fn close(theme: &UiTheme) -> Self {
         Self {
-            default_bg: CANCEL_BG,
-            hovered_bg: CANCEL_HOVER,
+            default_bg: theme.cancel_bg,
+            hovered_bg: theme.cancel_hover,
             default_border: None,
             hovered_border: None,
             selected_bg: None,
             selected_border: None,
+            kind: ButtonKind::Close,
         }
     }
 
     #[must_use]
-    pub fn tab() -> Self {
+    pub fn tab(theme: &UiTheme) -> Self {
         Self {
-            default_bg: BUTTON_BG,
-            hovered_bg: BUTTON_HOVER,
-            default_border: Some(PANEL_BORDER),
-            hovered_border: Some(PANEL_BORDER),
-            selected_bg: Some(SELECTED_BG),
-            selected_border: Some(SELECTED_BORDER),
+            default_bg: theme.button_bg,
+            hovered_bg: theme.button_hover,
+            default_border: Some(theme.panel_border),
+            hovered_border: Some(theme.panel_border),
+            selected_bg: Some(theme.selected_bg),
+            selected_border: Some(theme.selected_border),
+            kind: ButtonKind::Tab,
         }
     }
 
     #[must_use]
-    pub fn action_bar() -> Self {
+    pub fn action_bar(theme: &UiTheme) -> Self {
         Self {
-            default_bg: ACTION_BUTTON_BG,
-            hovered_bg: ACTION_BUTTON_HOVER,
-            default_border: Some(PANEL_BORDER),
-            hovered_border: Some(PANEL_BORDER),
-            selected_bg: Some(SELECTED_BG),
-            selected_border: Some(SELECTED_BORDER),
+            default_bg: theme.action_button_bg,
+            hovered_bg: theme.action_button_hover,
+            default_border: Some(theme.panel_border),
+            hovered_border: Some(theme.panel_border),
+            selected_bg: Some(theme.selected_bg),
+            selected_border: Some(theme.selected_border),
+            kind: ButtonKind::ActionBar,
         }
     }
 
     #[must_use]
-    pub fn building_button() -> Self {
+    pub fn building_button(theme: &UiTheme) -> Self {
         Self {
-            default_bg: BUTTON_BG,
-            hovered_bg: BUTTON_HOVER,
-            default_border: Some(PANEL_BORDER),
-            hovered_border: Some(PANEL_BORDER),
-            selected_bg: Some(SELECTED_BG),
-            selected_border: Some(SELECTED_BORDER),
+            default_bg: theme.button_bg,
+            hovered_bg: theme.button_hover,
+            default_border: Some(theme.panel_border),
+            hovered_border: Some(theme.panel_border),
+            selected_bg: Some(theme.selected_bg),
+            selected_border: Some(theme.selected_border),
+            kind: ButtonKind::Building,
         }
     }
 }
@@ -207,14 +494,142 @@ fn apply_style(
     }
 }
 
+/// Recolors UI that was spawned under the previous theme. Tagged colors are
+/// mapped by role, and buttons are rebuilt from their style's kind.
+pub fn restyle_on_theme_change(
+    theme: Res<UiTheme>,
+    mut previous: Local<Option<UiTheme>>,
+    mut backgrounds: Query<(&mut BackgroundColor, Option<&ThemedBackground>), Without<ButtonStyle>>,
+    mut borders: Query<(&mut BorderColor, Option<&ThemedBorder>), Without<ButtonStyle>>,
+    mut texts: Query<(&mut TextColor, Option<&ThemedText>)>,
+    mut buttons: Query<(
+        &mut ButtonStyle,
+        &mut BackgroundColor,
+        Option<&mut BorderColor>,
+        Option<&ThemedBorder>,
+        Has<Pressed>,
+        Option<&Hovered>,
+        Has<Checked>,
+    )>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+    let Some(old) = previous.replace(theme.clone()) else {
+        return;
+    };
+    if old == *theme {
+        return;
+    }
+    let translate = |role: Option<ThemeRole>, color: Color| theme.translate(&old, role, color);
+    let translate_border = |border: &mut BorderColor, role: Option<&ThemedBorder>| {
+        let role = role.map(|role| role.0);
+        border.top = translate(role, border.top);
+        border.right = translate(role, border.right);
+        border.bottom = translate(role, border.bottom);
+        border.left = translate(role, border.left);
+    };
+
+    for (mut bg, role) in &mut backgrounds {
+        bg.0 = translate(role.map(|role| role.0), bg.0);
+    }
+    for (mut border, role) in &mut borders {
+        translate_border(&mut border, role);
+    }
+    for (mut text, role) in &mut texts {
+        text.0 = translate(role.map(|role| role.0), text.0);
+    }
+    for (mut style, mut bg, mut border, border_role, pressed, hovered, checked) in &mut buttons {
+        if let Some(border) = border.as_mut() {
+            translate_border(border, border_role);
+        }
+        *style = style.restyled(&theme);
+        let hovered = hovered.is_some_and(|hovered| hovered.0);
+        apply_style(&style, &mut bg, border, pressed, hovered, checked);
+    }
+    info!(theme = theme.name, "UI theme changed");
+}
+
+fn cycle_theme(keyboard: Res<ButtonInput<KeyCode>>, mut theme: ResMut<UiTheme>) {
+    if keyboard.just_pressed(KeyCode::F6) {
+        *theme = theme.next_builtin();
+    }
+}
+
 pub struct StylePlugin;
 
 impl Plugin for StylePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<UiTheme>().add_systems(
             Update,
-            (apply_button_styles, apply_button_styles_on_uncheck)
-                .in_set(UISystemSet::VisualUpdates),
+            (
+                cycle_theme.in_set(UISystemSet::InputDetection),
+                (
+                    restyle_on_theme_change,
+                    apply_button_styles,
+                    apply_button_styles_on_uncheck,
+                )
+                    .chain()
+                    .in_set(UISystemSet::VisualUpdates),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_shared_by_two_roles_follow_their_own_role() {
+        let mut app = App::new();
+        app.insert_resource(UiTheme::high_contrast())
+            .add_systems(Update, restyle_on_theme_change);
+        app.update();
+
+        let high_contrast = UiTheme::high_contrast();
+        assert_eq!(high_contrast.header_color, high_contrast.panel_border);
+        let header = app
+            .world_mut()
+            .spawn(high_contrast.text(ThemeRole::Header))
+            .id();
+        let border = app
+            .world_mut()
+            .spawn((
+                TextColor(high_contrast.panel_border),
+                ThemedText(ThemeRole::PanelBorder),
+            ))
+            .id();
+        let untagged = app
+            .world_mut()
+            .spawn(TextColor(high_contrast.text_color))
+            .id();
+        let button = app
+            .world_mut()
+            .spawn((
+                ButtonStyle::confirm(&high_contrast),
+                BackgroundColor(high_contrast.confirm_bg),
+            ))
+            .id();
+
+        *app.world_mut().resource_mut::<UiTheme>() = UiTheme::dark();
+        app.update();
+
+        let dark = UiTheme::dark();
+        let text = |entity| app.world().get::<TextColor>(entity).unwrap().0;
+        assert_eq!(text(header), dark.header_color);
+        assert_eq!(text(border), dark.panel_border);
+        assert_eq!(
+            text(untagged),
+            high_contrast.text_color,
+            "an ambiguous untagged color is left alone"
+        );
+        let style = app.world().get::<ButtonStyle>(button).unwrap();
+        assert_eq!(style.default_bg, dark.confirm_bg);
+        assert_eq!(
+            app.world().get::<BackgroundColor>(button).unwrap().0,
+            dark.confirm_bg
         );
     }
 }
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    ui::{
+        hover::{world_per_px, CursorWorldPosition, InteractionConfig},
+        panels::action_bar::ActivePanel,
+        style::UiTheme,
+        UISystemSet, UiMode,
+    },
+    workers::Worker,
+};
+
+const GROUP_KEYS: [(KeyCode, u8); 9] = [
+    (KeyCode::Digit1, 1),
+    (KeyCode::Digit2, 2),
+    (KeyCode::Digit3, 3),
+    (KeyCode::Digit4, 4),
+    (KeyCode::Digit5, 5),
+    (KeyCode::Digit6, 6),
+    (KeyCode::Digit7, 7),
+    (KeyCode::Digit8, 8),
+    (KeyCode::Digit9, 9),
+];
+
+/// Workers picked with a Shift-drag box or recalled from a group. Worker
+/// assignment buttons hand out the selection when there is one, and clear
+/// it once they have.
+#[derive(Resource, Debug, Default)]
+pub struct SelectedWorkers {
+    pub workers: HashSet<Entity>,
+}
+
+/// Saved selections, numbered 1 to 9.
+#[derive(Resource, Debug, Default)]
+pub struct WorkerGroups {
+    pub groups: HashMap<u8, HashSet<Entity>>,
+}
+
+impl WorkerGroups {
+    /// Saving an empty selection clears the group.
+    pub fn save(&mut self, group: u8, workers: &HashSet<Entity>) {
+        if workers.is_empty() {
+            self.groups.remove(&group);
+        } else {
+            self.groups.insert(group, workers.clone());
+        }
+    }
+
+    #[must_use]
+    pub fn recall(&self, group: u8) -> HashSet<Entity> {
+        self.groups.get(&group).cloned().unwrap_or_default()
+    }
+
+    /// Drops `worker` from every group, and any group it leaves empty.
+    pub fn forget(&mut self, worker: Entity) {
+        self.groups.retain(|_, workers| {
+            workers.remove(&worker);
+            !workers.is_empty()
+        });
+    }
+}
+
+/// World-space anchor of an in-progress worker selection box.
+#[derive(Resource, Default)]
+pub struct WorkerSelectionDrag {
+    pub start: Option<Vec2>,
+}
+
+fn shift_held(keyboard: &ButtonInput<KeyCode>) -> bool {
+    keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+fn workers_in_rect<'a>(
+    rect: Rect,
+    workers: impl IntoIterator<Item = (Entity, &'a Transform)>,
+) -> HashSet<Entity> {
+    workers
+        .into_iter()
+        .filter(|(_, transform)| rect.contains(transform.translation.truncate()))
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Holding Shift while dragging replaces the selection with the workers
+/// inside the box.
+fn handle_worker_box_select(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    cursor: Res<CursorWorldPosition>,
+    mut drag: ResMut<WorkerSelectionDrag>,
+    interaction: Res<InteractionConfig>,
+    projections: Query<&Projection, With<Camera2d>>,
+    ui_interactions: Query<&Interaction, With<Button>>,
+    workers: Query<(Entity, &Transform), With<Worker>>,
+    mut selected: ResMut<SelectedWorkers>,
+) {
+    if mouse_button.just_pressed(MouseButton::Left) {
+        let over_ui = ui_interactions
+            .iter()
+            .any(|i| matches!(i, Interaction::Pressed | Interaction::Hovered));
+        drag.start = if over_ui || !shift_held(&keyboard) {
+            None
+        } else {
+            cursor.0
+        };
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(start), Some(end)) = (drag.start.take(), cursor.0) else {
+        return;
+    };
+    if !interaction.is_drag(start, end, world_per_px(&projections)) {
+        return;
+    }
+
+    selected.workers = workers_in_rect(Rect::from_corners(start, end), workers);
+    info!(count = selected.workers.len(), "workers selected");
+}
+
+/// Ctrl+1..9 saves the selection as a group; 1..9 recalls it. The digits
+/// belong to the build panel tabs while that panel is open.
+fn handle_worker_group_hotkeys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    active_panel: Res<ActivePanel>,
+    mut groups: ResMut<WorkerGroups>,
+    mut selected: ResMut<SelectedWorkers>,
+) {
+    let Some(group) = GROUP_KEYS
+        .iter()
+        .find(|(key, _)| keyboard.just_pressed(*key))
+        .map(|&(_, group)| group)
+    else {
+        return;
+    };
+
+    if keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        groups.save(group, &selected.workers);
+        info!(group, count = selected.workers.len(), "worker group saved");
+    } else if *active_panel != ActivePanel::Build {
+        selected.workers = groups.recall(group);
+        info!(
+            group,
+            count = selected.workers.len(),
+            "worker group recalled"
+        );
+    }
+}
+
+fn prune_despawned_workers(
+    mut removed: RemovedComponents<Worker>,
+    mut groups: ResMut<WorkerGroups>,
+    mut selected: ResMut<SelectedWorkers>,
+) {
+    for worker in removed.read() {
+        groups.forget(worker);
+        selected.workers.remove(&worker);
+    }
+}
+
+fn draw_worker_selection(
+    drag: Res<WorkerSelectionDrag>,
+    cursor: Res<CursorWorldPosition>,
+    selected: Res<SelectedWorkers>,
+    workers: Query<&Transform, With<Worker>>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    if let (Some(start), Some(end)) = (drag.start, cursor.0) {
+        let rect = Rect::from_corners(start, end);
+        gizmos.rect_2d(
+            Isometry2d::from_translation(rect.center()),
+            rect.size(),
+            theme.worker_color,
+        );
+    }
+
+    for worker in &selected.workers {
+        if let Ok(transform) = workers.get(*worker) {
+            gizmos.circle_2d(
+                Isometry2d::from_translation(transform.translation.truncate()),
+                10.0,
+                theme.worker_color,
+            );
+        }
+    }
+}
+
+pub struct WorkerGroupsPlugin;
+
+impl Plugin for WorkerGroupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedWorkers>()
+            .init_resource::<WorkerGroups>()
+            .init_resource::<WorkerSelectionDrag>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        prune_despawned_workers,
+                        handle_worker_box_select.run_if(in_state(UiMode::Observe)),
+                        handle_worker_group_hotkeys,
+                    )
+                        .chain()
+                        .in_set(UISystemSet::InputDetection),
+                    draw_worker_selection.in_set(UISystemSet::VisualUpdates),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release_all();
+        keyboard.clear();
+        for key in keys {
+            keyboard.press(*key);
+        }
+        app.update();
+    }
+
+    #[test]
+    fn recalled_group_drops_despawned_workers() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ActivePanel>()
+            .init_resource::<SelectedWorkers>()
+            .init_resource::<WorkerGroups>()
+            .add_systems(
+                Update,
+                (prune_despawned_workers, handle_worker_group_hotkeys).chain(),
+            );
+
+        let kept = app.world_mut().spawn(Worker).id();
+        let lost = app.world_mut().spawn(Worker).id();
+        app.world_mut().resource_mut::<SelectedWorkers>().workers = HashSet::from([kept, lost]);
+        press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit1]);
+        assert_eq!(
+            app.world().resource::<WorkerGroups>().recall(1),
+            HashSet::from([kept, lost])
+        );
+
+        app.world_mut()
+            .resource_mut::<SelectedWorkers>()
+            .workers
+            .clear();
+        app.world_mut().despawn(lost);
+        press(&mut app, &[KeyCode::Digit1]);
+
+        assert_eq!(
+            app.world().resource::<SelectedWorkers>().workers,
+            HashSet::from([kept])
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use crate::ui::{
+    hover::{world_per_px, CursorWorldPosition, InteractionConfig},
+    style::{ThemeRole, UiTheme},
+    UISystemSet, UiMode,
+};
+
+/// A named world-space rectangle that groups the buildings inside it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub rect: Rect,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Zones {
+    pub zones: Vec<Zone>,
+}
+
+impl Zones {
+    /// Adds a zone named after its position in the list.
+    pub fn add(&mut self, rect: Rect) -> &Zone {
+        let name = format!("Zone {}", self.zones.len() + 1);
+        self.zones.push(Zone { name, rect });
+        &self.zones[self.zones.len() - 1]
+    }
+}
+
+/// World-space anchor of an in-progress zone drag.
+#[derive(Resource, Default)]
+pub struct ZoneDrag {
+    pub start: Option<Vec2>,
+}
+
+#[derive(Component)]
+pub struct ZoneMarker;
+
+fn zone_key_held(keyboard: &ButtonInput<KeyCode>) -> bool {
+    keyboard.pressed(KeyCode::KeyZ)
+}
+
+/// Holding Z while dragging marks out a new zone.
+fn handle_zone_drag(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    cursor: Res<CursorWorldPosition>,
+    mut drag: ResMut<ZoneDrag>,
+    interaction: Res<InteractionConfig>,
+    projections: Query<&Projection, With<Camera2d>>,
+    ui_interactions: Query<&Interaction, With<Button>>,
+    mut zones: ResMut<Zones>,
+) {
+    if mouse_button.just_pressed(MouseButton::Left) {
+        let over_ui = ui_interactions
+            .iter()
+            .any(|i| matches!(i, Interaction::Pressed | Interaction::Hovered));
+        drag.start = if over_ui || !zone_key_held(&keyboard) {
+            None
+        } else {
+            cursor.0
+        };
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(start), Some(end)) = (drag.start.take(), cursor.0) else {
+        return;
+    };
+    if !interaction.is_drag(start, end, world_per_px(&projections)) {
+        return;
+    }
+
+    let zone = zones.add(Rect::from_corners(start, end));
+    info!(name = %zone.name, "zone created");
+}
+
+fn draw_zone_drag(
+    drag: Res<ZoneDrag>,
+    cursor: Res<CursorWorldPosition>,
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+) {
+    let (Some(start), Some(end)) = (drag.start, cursor.0) else {
+        return;
+    };
+    let rect = Rect::from_corners(start, end);
+    gizmos.rect_2d(
+        Isometry2d::from_translation(rect.center()),
+        rect.size(),
+        theme.selected_border,
+    );
+}
+
+/// Respawns the translucent zone rectangles and their labels whenever the
+/// zone list changes.
+fn sync_zone_markers(
+    mut commands: Commands,
+    zones: Res<Zones>,
+    markers: Query<Entity, With<ZoneMarker>>,
+    theme: Res<UiTheme>,
+) {
+    if !zones.is_changed() {
+        return;
+    }
+    for marker in &markers {
+        commands.entity(marker).despawn();
+    }
+
+    for zone in &zones.zones {
+        let center = zone.rect.center();
+        let size = zone.rect.size();
+        commands
+            .spawn((
+                ZoneMarker,
+                Sprite::from_color(theme.selected_border.with_alpha(0.12), size),
+                Transform::from_xyz(center.x, center.y, 0.3),
+            ))
+            .with_child((
+                Text2d::new(zone.name.clone()),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                theme.text(ThemeRole::Text),
+                Transform::from_xyz(0.0, size.y / 2.0 - 10.0, 0.1),
+            ));
+    }
+}
+
+pub struct ZonePlugin;
+
+impl Plugin for ZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Zones>()
+            .init_resource::<ZoneDrag>()
+            .add_systems(
+                Update,
+                (
+                    handle_zone_drag
+                        .run_if(in_state(UiMode::Observe))
+                        .in_set(UISystemSet::InputDetection),
+                    (draw_zone_drag, sync_zone_markers).in_set(UISystemSet::VisualUpdates),
+                ),
+            );
+    }
+}
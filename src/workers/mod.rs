@@ -23,6 +23,8 @@ pub struct WorkersPlugin;
 impl Plugin for WorkersPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<WorkerArrivedEvent>()
+            .init_resource::<RallyPoint>()
+            .init_resource::<PathfindingConfig>()
             .add_plugins(WorkflowsPlugin)
             .configure_sets(
                 Update,
@@ -39,7 +41,11 @@ impl Plugin for WorkersPlugin {
             .add_systems(
                 Update,
                 (
-                    validate_and_displace_stranded_workers.in_set(WorkersSystemSet::Lifecycle),
+                    (
+                        validate_and_displace_stranded_workers,
+                        send_new_workers_to_rally_point,
+                    )
+                        .in_set(WorkersSystemSet::Lifecycle),
                     move_workers.in_set(WorkersSystemSet::Movement),
                 ),
             );
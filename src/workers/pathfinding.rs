@@ -1,7 +1,7 @@
 use crate::{
     grid::{Grid, Position},
     systems::NetworkConnectivity,
-    workers::{Speed, Worker, WorkflowAssignment},
+    workers::{DedicatedWorker, Speed, Worker, WorkflowAssignment},
 };
 use bevy::prelude::*;
 use std::collections::{HashSet, VecDeque};
@@ -12,6 +12,13 @@ pub struct WorkerPath {
     pub current_target: Option<Vec2>,
 }
 
+/// With `max_path_length`, logistics paths longer than that many cells are
+/// treated as unreachable so workers are not tied up on cross-map hauls.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PathfindingConfig {
+    pub max_path_length: Option<usize>,
+}
+
 #[derive(Message)]
 pub struct WorkerArrivedEvent {
     pub worker: Entity,
@@ -82,6 +89,18 @@ pub fn calculate_path(
     end: (i32, i32),
     network: &NetworkConnectivity,
     grid: &Grid,
+) -> Option<VecDeque<Vec2>> {
+    calculate_path_within(start, end, network, grid, None)
+}
+
+/// Like `calculate_path`, but gives up once every unexplored cell is more
+/// than `max_len` steps from the start.
+pub fn calculate_path_within(
+    start: (i32, i32),
+    end: (i32, i32),
+    network: &NetworkConnectivity,
+    grid: &Grid,
+    max_len: Option<usize>,
 ) -> Option<VecDeque<Vec2>> {
     use std::collections::HashMap;
 
@@ -93,14 +112,20 @@ pub fn calculate_path(
         return None;
     }
 
+    let max_len = max_len.unwrap_or(usize::MAX);
+    if usize::try_from(manhattan_distance_coords(start, end)).is_ok_and(|d| d > max_len) {
+        debug!(?start, ?end, max_len, "path target beyond length limit");
+        return None;
+    }
+
     let mut queue = VecDeque::new();
     let mut visited = HashSet::new();
     let mut parent = HashMap::new();
 
-    queue.push_back(start);
+    queue.push_back((start, 0));
     visited.insert(start);
 
-    while let Some(current) = queue.pop_front() {
+    while let Some((current, depth)) = queue.pop_front() {
         if current == end {
             let mut path = Vec::new();
             let mut current_pos = end;
@@ -120,6 +145,10 @@ pub fn calculate_path(
             return Some(world_path);
         }
 
+        if depth >= max_len {
+            continue;
+        }
+
         for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
             let next = (current.0 + dx, current.1 + dy);
 
@@ -133,14 +162,33 @@ pub fn calculate_path(
             if can_move_to_cell {
                 visited.insert(next);
                 parent.insert(next, current);
-                queue.push_back(next);
+                queue.push_back((next, depth + 1));
             }
         }
     }
-    println!("No path found");
+    debug!(?start, ?end, "no path found");
     None
 }
 
+/// Stranded workers lose their workflow, except dedicated ones, which only
+/// drop the step they were on and resolve it again from their new cell.
+fn release_stranded_assignment(
+    commands: &mut Commands,
+    worker: Entity,
+    assignment: Option<Mut<WorkflowAssignment>>,
+    dedicated: bool,
+) {
+    let Some(mut assignment) = assignment else {
+        return;
+    };
+    if dedicated {
+        assignment.resolved_target = None;
+        assignment.resolved_action = None;
+    } else {
+        commands.entity(worker).remove::<WorkflowAssignment>();
+    }
+}
+
 pub fn validate_and_displace_stranded_workers(
     mut commands: Commands,
     mut workers: Query<
@@ -149,7 +197,8 @@ pub fn validate_and_displace_stranded_workers(
             &mut Transform,
             &mut Position,
             &mut WorkerPath,
-            Option<&WorkflowAssignment>,
+            Option<&mut WorkflowAssignment>,
+            Has<DedicatedWorker>,
         ),
         With<Worker>,
     >,
@@ -158,8 +207,14 @@ pub fn validate_and_displace_stranded_workers(
 ) {
     let mut displaced_count = 0;
 
-    for (worker_entity, mut transform, mut worker_position, mut worker_path, has_assignment) in
-        &mut workers
+    for (
+        worker_entity,
+        mut transform,
+        mut worker_position,
+        mut worker_path,
+        assignment,
+        dedicated,
+    ) in &mut workers
     {
         let worker_pos = (worker_position.x, worker_position.y);
 
@@ -167,8 +222,11 @@ pub fn validate_and_displace_stranded_workers(
             if let Some(displacement_target) =
                 find_nearest_valid_network_cell(worker_pos, &network, 10)
             {
-                println!(
-                    "Emergency displacement: Worker {worker_entity:?} stranded at {worker_pos:?}, moving to {displacement_target:?}"
+                debug!(
+                    worker = ?worker_entity,
+                    from = ?worker_pos,
+                    to = ?displacement_target,
+                    "displacing stranded worker"
                 );
 
                 worker_position.x = displacement_target.0;
@@ -181,31 +239,25 @@ pub fn validate_and_displace_stranded_workers(
                 worker_path.waypoints.clear();
                 worker_path.current_target = None;
 
-                if has_assignment.is_some() {
-                    commands
-                        .entity(worker_entity)
-                        .remove::<WorkflowAssignment>();
-                }
+                release_stranded_assignment(&mut commands, worker_entity, assignment, dedicated);
 
                 displaced_count += 1;
             } else {
-                println!(
-                    "Critical: Worker {worker_entity:?} stranded at {worker_pos:?} with no reachable valid network cells"
+                debug!(
+                    worker = ?worker_entity,
+                    position = ?worker_pos,
+                    "stranded worker has no reachable network cell"
                 );
 
                 worker_path.waypoints.clear();
                 worker_path.current_target = None;
-                if has_assignment.is_some() {
-                    commands
-                        .entity(worker_entity)
-                        .remove::<WorkflowAssignment>();
-                }
+                release_stranded_assignment(&mut commands, worker_entity, assignment, dedicated);
             }
         }
     }
 
     if displaced_count > 0 {
-        println!("Emergency displacement system relocated {displaced_count} stranded workers");
+        info!(count = displaced_count, "relocated stranded workers");
     }
 }
 
@@ -385,6 +437,25 @@ mod tests {
         assert_eq!(path.len(), 3); // Should include (0,1), (0,2), (0,3)
     }
 
+    #[test]
+    fn calculate_path_within_gives_up_past_the_length_limit() {
+        let mut network = NetworkConnectivity::default();
+        let mut grid = Grid::new(64.0);
+        // A U-shaped road: the ends are two cells apart but the route is six.
+        for cell in [(0, 0), (0, 1), (0, 2), (1, 2), (2, 2), (2, 1), (2, 0)] {
+            network.add_connected_cell(cell.0, cell.1);
+            network.add_core_network_cell(cell.0, cell.1);
+            grid.add_coordinate(cell.0, cell.1);
+        }
+
+        assert!(calculate_path_within((0, 0), (2, 0), &network, &grid, Some(5)).is_none());
+        assert!(calculate_path_within((0, 0), (0, 2), &network, &grid, Some(1)).is_none());
+        let path = calculate_path_within((0, 0), (2, 0), &network, &grid, Some(6)).unwrap();
+        assert_eq!(path.len(), 6);
+        let short = calculate_path_within((0, 0), (0, 2), &network, &grid, Some(5)).unwrap();
+        assert_eq!(short.len(), 2);
+    }
+
     #[test]
     fn calculate_path_with_disconnected_network_returns_none() {
         let mut network = NetworkConnectivity::default();
@@ -1,9 +1,41 @@
 use crate::{
-    grid::Position, materials::items::Cargo, structures::ComputeConsumer, workers::WorkerPath,
+    grid::{Grid, Position},
+    materials::items::Cargo,
+    structures::ComputeConsumer,
+    systems::NetworkConnectivity,
+    workers::{calculate_path, WorkerPath},
 };
 use bevy::prelude::*;
 use std::collections::VecDeque;
 
+/// Where new workers appear and where they walk to once spawned. Both default
+/// to the hub; the rally cell can be moved by the player.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RallyPoint {
+    pub spawn: (i32, i32),
+    pub rally: (i32, i32),
+}
+
+/// Cell new workers appear on. While a hub stands that is the configured
+/// spawn; without one, the remaining building closest to it, or the spawn
+/// itself when nothing is left.
+pub fn resolve_spawn_cell(
+    rally_point: &RallyPoint,
+    hub_present: bool,
+    buildings: impl IntoIterator<Item = Position>,
+) -> (i32, i32) {
+    let spawn = rally_point.spawn;
+    if hub_present {
+        return spawn;
+    }
+    warn!("no hub left, spawning workers at the nearest building instead");
+    buildings
+        .into_iter()
+        .map(|pos| (pos.x, pos.y))
+        .min_by_key(|&(x, y)| ((x - spawn.0).abs() + (y - spawn.1).abs(), x, y))
+        .unwrap_or(spawn)
+}
+
 #[derive(Component)]
 pub struct Worker;
 
@@ -45,3 +77,28 @@ impl WorkerBundle {
         }
     }
 }
+
+pub fn send_new_workers_to_rally_point(
+    rally_point: Res<RallyPoint>,
+    network: Res<NetworkConnectivity>,
+    grid: Res<Grid>,
+    mut workers: Query<(&Position, &mut WorkerPath), Added<Worker>>,
+) {
+    if rally_point.rally == rally_point.spawn {
+        return;
+    }
+
+    for (position, mut path) in &mut workers {
+        let start = (position.x, position.y);
+        if start == rally_point.rally {
+            continue;
+        }
+
+        let Some(mut waypoints) = calculate_path(start, rally_point.rally, &network, &grid) else {
+            debug!(?start, rally = ?rally_point.rally, "rally point unreachable");
+            continue;
+        };
+        path.current_target = waypoints.pop_front();
+        path.waypoints = waypoints;
+    }
+}
@@ -1,34 +1,126 @@
 use bevy::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
-use crate::materials::ItemName;
+use crate::materials::{ItemId, ItemRegistry};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum WorkflowAction {
-    Pickup(Option<HashMap<ItemName, u32>>),
-    Dropoff(Option<HashMap<ItemName, u32>>),
+    Pickup(Option<HashMap<ItemId, u32>>),
+    Dropoff(Option<HashMap<ItemId, u32>>),
 }
 
-#[derive(Clone, Debug)]
+impl WorkflowAction {
+    #[must_use]
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Pickup(_) => "Pickup",
+            Self::Dropoff(_) => "Dropoff",
+        }
+    }
+
+    #[must_use]
+    pub fn preposition(&self) -> &'static str {
+        match self {
+            Self::Pickup(_) => "from",
+            Self::Dropoff(_) => "to",
+        }
+    }
+
+    /// "all" without a filter, otherwise each item with its quantity, sorted
+    /// by name.
+    #[must_use]
+    pub fn filter_summary(&self, items: &ItemRegistry) -> String {
+        let (Self::Pickup(Some(filter)) | Self::Dropoff(Some(filter))) = self else {
+            return "all".to_string();
+        };
+        if filter.is_empty() {
+            return "all".to_string();
+        }
+        let mut entries: Vec<String> = filter
+            .iter()
+            .map(|(&item, qty)| format!("{} x{qty}", items.display_name(item)))
+            .collect();
+        entries.sort_unstable();
+        entries.join(", ")
+    }
+
+    /// One-line description such as "Pickup Iron Ore x50 from Smelter".
+    #[must_use]
+    pub fn describe(&self, target: &str, items: &ItemRegistry) -> String {
+        format!(
+            "{} {} {} {target}",
+            self.verb(),
+            self.filter_summary(items),
+            self.preposition()
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum StepTarget {
     Specific(Entity),
     ByType(String),
 }
 
-#[derive(Clone, Debug)]
+/// Gate on a step. While it does not hold, workers skip the step as
+/// `Workflow::skip_step` describes. Conditions on removed buildings are
+/// dropped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StepCondition {
+    /// Holds while `building` has fewer than `threshold` of `item` in its
+    /// input or storage port.
+    ItemBelow {
+        building: Entity,
+        item: ItemId,
+        threshold: u32,
+    },
+}
+
+impl StepCondition {
+    /// The building the condition watches.
+    #[must_use]
+    pub fn building(&self) -> Entity {
+        match *self {
+            Self::ItemBelow { building, .. } => building,
+        }
+    }
+
+    /// `held` reports how much of an item a building holds, or `None` for a
+    /// building without an input or storage port, which never passes.
+    #[must_use]
+    pub fn holds(&self, held: impl Fn(Entity, ItemId) -> Option<u32>) -> bool {
+        match *self {
+            Self::ItemBelow {
+                building,
+                item,
+                threshold,
+            } => held(building, item).is_some_and(|quantity| quantity < threshold),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct WorkflowStep {
     pub target: StepTarget,
     pub action: WorkflowAction,
+    pub condition: Option<StepCondition>,
 }
 
+/// With `is_dedicated`, workers assigned to the workflow carry a
+/// `DedicatedWorker` marker and stay bound to it until manually unassigned.
 #[derive(Component)]
 pub struct Workflow {
     pub name: String,
     pub building_set: HashSet<Entity>,
     pub steps: Vec<WorkflowStep>,
     pub is_paused: bool,
+    pub is_dedicated: bool,
     pub desired_worker_count: u32,
     pub round_robin_counters: HashMap<usize, usize>,
+    /// Times a pickup step found nothing to take, by step index, since that
+    /// step last succeeded.
+    pub step_waits: HashMap<usize, u32>,
 }
 
 impl Workflow {
@@ -38,6 +130,33 @@ impl Workflow {
         }
         (current + 1) % self.steps.len()
     }
+
+    /// Step to move to when `current` is skipped. A skipped pickup also
+    /// skips the dropoffs right after it while the worker carries nothing,
+    /// since they would have nothing to deliver; with no other step to go
+    /// to, the worker stays on the pickup.
+    #[must_use]
+    pub fn skip_step(&self, current: usize, carrying: bool) -> usize {
+        let mut next = self.next_step(current);
+        let skipped_pickup = matches!(
+            self.steps.get(current).map(|step| &step.action),
+            Some(WorkflowAction::Pickup(_))
+        );
+        if skipped_pickup && !carrying {
+            while next != current && matches!(self.steps[next].action, WorkflowAction::Dropoff(_)) {
+                next = self.next_step(next);
+            }
+        }
+        next
+    }
+
+    pub fn record_step_wait(&mut self, step: usize) {
+        *self.step_waits.entry(step).or_default() += 1;
+    }
+
+    pub fn clear_step_waits(&mut self, step: usize) {
+        self.step_waits.remove(&step);
+    }
 }
 
 #[derive(Component)]
@@ -48,15 +167,63 @@ pub struct WorkflowAssignment {
     pub resolved_action: Option<WorkflowAction>,
 }
 
+/// Sticky marker for workers of a dedicated workflow. Assignment requests for
+/// other workflows skip these workers; only `UnassignWorkersEvent` frees them.
+#[derive(Component, Debug)]
+pub struct DedicatedWorker;
+
+/// Set on a worker the player unassigned. Auto-staffing leaves it idle until
+/// the player hands it a workflow again.
+#[derive(Component, Debug)]
+pub struct ManuallyUnassigned;
+
 #[derive(Component)]
 pub struct WaitingForItems {
     pub timer: Timer,
 }
 
+impl WaitingForItems {
+    pub fn new(retry_secs: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(retry_secs, TimerMode::Repeating),
+        }
+    }
+
+    /// Stretches the retry interval after a recheck that found nothing.
+    pub fn back_off(&mut self, factor: f32, max_secs: f32) {
+        let current = self.timer.duration().as_secs_f32();
+        let next = (current * factor).min(max_secs);
+        if next > current {
+            self.timer.set_duration(Duration::from_secs_f32(next));
+        }
+    }
+}
+
 impl Default for WaitingForItems {
     fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+/// Set on a worker whose by-type step has no matching building in the
+/// workflow's pool. The worker holds that step until one joins instead of
+/// cycling through the remaining steps.
+#[derive(Component, Debug)]
+pub struct WaitingForTarget {
+    pub step: usize,
+}
+
+/// Set on a building when a dropoff is dispatched to it, so a starved building
+/// does not draw a second delivery for the same need.
+#[derive(Component)]
+pub struct RequestCooldown {
+    pub timer: Timer,
+}
+
+impl RequestCooldown {
+    pub fn new(secs: f32) -> Self {
         Self {
-            timer: Timer::from_seconds(0.5, TimerMode::Repeating),
+            timer: Timer::from_seconds(secs, TimerMode::Once),
         }
     }
 }
@@ -78,11 +245,64 @@ impl Default for WaitingForSpace {
     }
 }
 
+/// Emergency dropoffs tried since an unassigned worker last managed to
+/// unload. Once `LogisticsConfig::emergency_dropoff_retries` is spent the
+/// worker holds its cargo instead of retrying every frame.
+#[derive(Component, Debug, Default)]
+pub struct EmergencyDropoffAttempts {
+    pub attempts: u32,
+    pub last_total: u32,
+    pub holding: bool,
+}
+
+/// Set on a worker whose long dropoff was split for a relay. It walks only
+/// to `cell`, hands its cargo to `partner` there and moves on to its next
+/// step instead of finishing the trip.
+#[derive(Component, Debug)]
+pub struct RelayHandoff {
+    pub partner: Entity,
+    pub cell: (i32, i32),
+}
+
+/// Set on the idle worker that meets a relay carrier at `cell` and finishes
+/// the delivery to `target`. `delivering` flips once the cargo is handed over.
+#[derive(Component, Debug)]
+pub struct RelayLeg {
+    pub carrier: Entity,
+    pub cell: (i32, i32),
+    pub target: Entity,
+    pub delivering: bool,
+}
+
+/// A one-off delivery of `items` from `source` to `destination` outside any
+/// workflow, carried out by the first idle worker free to take it.
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct DeliveryRequest {
+    pub source: Entity,
+    pub destination: Entity,
+    pub items: HashMap<ItemId, u32>,
+}
+
+/// Set on the idle worker carrying out a `DeliveryRequest`. `loaded` flips
+/// once it has picked the items up at the source.
+#[derive(Component, Debug)]
+pub struct Errand {
+    pub request: DeliveryRequest,
+    pub loaded: bool,
+}
+
+/// Delivery requests no worker has taken yet, oldest first.
+#[derive(Resource, Default)]
+pub struct PendingDeliveries {
+    pub requests: VecDeque<DeliveryRequest>,
+}
+
 #[derive(Message)]
 pub struct CreateWorkflowEvent {
     pub name: String,
     pub building_set: HashSet<Entity>,
     pub steps: Vec<WorkflowStep>,
+    pub is_dedicated: bool,
     pub desired_worker_count: u32,
 }
 
@@ -105,6 +325,11 @@ pub struct PauseWorkflowEvent {
     pub workflow: Entity,
 }
 
+#[derive(Message)]
+pub struct DedicateWorkflowEvent {
+    pub workflow: Entity,
+}
+
 #[derive(Message)]
 pub struct AssignWorkersEvent {
     pub workflow: Entity,
@@ -127,6 +352,105 @@ pub struct WorkflowRegistry {
     pub workflows: Vec<Entity>,
 }
 
+/// Tuning for how workers are dispatched along workflows. Every limit and
+/// optional behaviour is off by default.
+#[derive(Resource)]
+pub struct LogisticsConfig {
+    /// Most sequences in flight at once. A sequence is one worker trip to a
+    /// resolved step target; workers travelling to or waiting at their target
+    /// count as active.
+    pub max_active_sequences: usize,
+    /// Most pending requests resolved and pathfound in one update, so large
+    /// worker counts spread the work over several frames.
+    pub max_dispatches_per_frame: usize,
+    /// Caps how much of any single item one pickup takes.
+    pub max_per_item_per_trip: Option<u32>,
+    /// Sends inputs a crafter's new recipe does not use back to storage when
+    /// its recipe changes.
+    pub evacuate_unused_inputs: bool,
+    /// How often a worker waiting on an empty source checks it again.
+    pub wait_retry_secs: f32,
+    /// Multiplies the retry interval after each miss; 1.0 disables backoff.
+    pub wait_backoff_factor: f32,
+    /// Longest the retry interval grows to under backoff.
+    pub max_wait_retry_secs: f32,
+    /// Sends unassigned workers to drop their cargo at the nearest storage
+    /// that takes it. When cleared they keep it until the player directs them.
+    pub emergency_dropoff: bool,
+    /// Storages tried before an emergency dropoff gives up and the worker
+    /// holds its cargo.
+    pub emergency_dropoff_retries: u32,
+    /// After a dropoff is dispatched to a building, it takes no other
+    /// dropoff for this long or until that delivery lands; 0.0 disables it.
+    pub request_cooldown_secs: f32,
+    /// Dropoffs whose path is at least this many cells long are split at
+    /// the midpoint, and an idle worker carries the cargo the rest of the way.
+    pub relay_min_path: Option<usize>,
+    /// Sends by-type dropoffs to the crafter whose active recipe yields the
+    /// most item value per craft, among those whose recipe uses the cargo and
+    /// whose input port has room for it. Falls back to the usual rotation
+    /// when none does.
+    pub prioritize_by_output_value: bool,
+}
+
+impl Default for LogisticsConfig {
+    fn default() -> Self {
+        Self {
+            max_active_sequences: usize::MAX,
+            max_dispatches_per_frame: 16,
+            max_per_item_per_trip: None,
+            evacuate_unused_inputs: false,
+            wait_retry_secs: 0.5,
+            wait_backoff_factor: 1.0,
+            max_wait_retry_secs: 4.0,
+            emergency_dropoff: true,
+            emergency_dropoff_retries: 20,
+            request_cooldown_secs: 0.0,
+            relay_min_path: None,
+            prioritize_by_output_value: false,
+        }
+    }
+}
+
+/// Bounds for a workflow's desired worker count. The builder's stepper clamps
+/// to this range and its reset button returns to `min_workers`.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct WorkflowConfig {
+    pub min_workers: u32,
+    pub max_workers: u32,
+}
+
+impl Default for WorkflowConfig {
+    fn default() -> Self {
+        Self {
+            min_workers: 1,
+            max_workers: 10,
+        }
+    }
+}
+
+impl WorkflowConfig {
+    #[must_use]
+    pub fn clamp_workers(&self, count: i64) -> u32 {
+        let clamped = count.clamp(i64::from(self.min_workers), i64::from(self.max_workers));
+        u32::try_from(clamped).unwrap_or(self.min_workers)
+    }
+}
+
+/// With `enabled`, idle workers are handed to the highest-priority workflow
+/// that is still below its desired worker count.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct AutoStaffConfig {
+    pub enabled: bool,
+}
+
+/// Workers whose next step is ready to resolve but are held back by
+/// `LogisticsConfig::max_active_sequences`, in dispatch order.
+#[derive(Resource, Default)]
+pub struct PendingSequenceQueue {
+    pub requests: Vec<Entity>,
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::panic)]
 mod tests {
@@ -137,6 +461,7 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::Specific(Entity::PLACEHOLDER),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
         assert!(matches!(step.target, StepTarget::Specific(_)));
         assert!(matches!(step.action, WorkflowAction::Pickup(None)));
@@ -147,6 +472,7 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::ByType("Smelter".to_string()),
             action: WorkflowAction::Dropoff(None),
+            condition: None,
         };
         match &step.target {
             StepTarget::ByType(name) => assert_eq!(name, "Smelter"),
@@ -163,16 +489,39 @@ mod tests {
     #[test]
     fn workflow_action_pickup_some() {
         let mut items = HashMap::new();
-        items.insert("iron_ore".to_string(), 5);
+        items.insert(ItemId::named("Iron Ore").unwrap(), 5);
         let action = WorkflowAction::Pickup(Some(items));
         match &action {
             WorkflowAction::Pickup(Some(map)) => {
-                assert_eq!(map.get("iron_ore"), Some(&5));
+                assert_eq!(map.get(&ItemId::named("Iron Ore").unwrap()), Some(&5));
             }
             _ => panic!("expected Pickup(Some)"),
         }
     }
 
+    #[test]
+    fn describe_lists_filtered_items_and_all_otherwise() {
+        let registry =
+            ItemRegistry::from_ron(r#"[(name: "Iron Ore", tier: 0), (name: "Coal", tier: 0)]"#)
+                .unwrap();
+        let items = HashMap::from([
+            (ItemId::named("Iron Ore").unwrap(), 50),
+            (ItemId::named("Coal").unwrap(), 10),
+        ]);
+        assert_eq!(
+            WorkflowAction::Pickup(Some(items)).describe("Smelter", &registry),
+            "Pickup Coal x10, Iron Ore x50 from Smelter"
+        );
+        assert_eq!(
+            WorkflowAction::Pickup(None).describe("Smelter", &registry),
+            "Pickup all from Smelter"
+        );
+        assert_eq!(
+            WorkflowAction::Dropoff(Some(HashMap::new())).describe("any Storage", &registry),
+            "Dropoff all to any Storage"
+        );
+    }
+
     #[test]
     fn workflow_action_dropoff_none() {
         let action = WorkflowAction::Dropoff(None);
@@ -182,11 +531,11 @@ mod tests {
     #[test]
     fn workflow_action_dropoff_some() {
         let mut items = HashMap::new();
-        items.insert("copper_plate".to_string(), 10);
+        items.insert(ItemId::named("Copper Ingot").unwrap(), 10);
         let action = WorkflowAction::Dropoff(Some(items));
         match &action {
             WorkflowAction::Dropoff(Some(map)) => {
-                assert_eq!(map.get("copper_plate"), Some(&10));
+                assert_eq!(map.get(&ItemId::named("Copper Ingot").unwrap()), Some(&10));
             }
             _ => panic!("expected Dropoff(Some)"),
         }
@@ -199,8 +548,10 @@ mod tests {
             building_set: HashSet::new(),
             steps: vec![],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         };
         assert!(!workflow.is_paused);
     }
@@ -240,21 +591,57 @@ mod tests {
                 WorkflowStep {
                     target: StepTarget::Specific(Entity::PLACEHOLDER),
                     action: WorkflowAction::Pickup(None),
+                    condition: None,
                 },
                 WorkflowStep {
                     target: StepTarget::Specific(Entity::PLACEHOLDER),
                     action: WorkflowAction::Dropoff(None),
+                    condition: None,
                 },
             ],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         };
 
         assert_eq!(workflow.next_step(0), 1);
         assert_eq!(workflow.next_step(1), 0);
     }
 
+    #[test]
+    fn skipping_an_empty_handed_pickup_skips_its_dropoffs() {
+        let step = |action| WorkflowStep {
+            target: StepTarget::Specific(Entity::PLACEHOLDER),
+            action,
+            condition: None,
+        };
+        let workflow = Workflow {
+            name: "two legs".to_string(),
+            building_set: HashSet::new(),
+            steps: vec![
+                step(WorkflowAction::Pickup(None)),
+                step(WorkflowAction::Dropoff(None)),
+                step(WorkflowAction::Dropoff(None)),
+                step(WorkflowAction::Pickup(None)),
+                step(WorkflowAction::Dropoff(None)),
+            ],
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 1,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        };
+
+        assert_eq!(workflow.skip_step(0, false), 3);
+        assert_eq!(workflow.skip_step(3, false), 0);
+        // Cargo from an earlier pickup still needs delivering.
+        assert_eq!(workflow.skip_step(0, true), 1);
+        // Skipped dropoffs only move on by one.
+        assert_eq!(workflow.skip_step(1, false), 2);
+    }
+
     #[test]
     fn next_step_empty_workflow() {
         let workflow = Workflow {
@@ -262,8 +649,10 @@ mod tests {
             building_set: HashSet::new(),
             steps: vec![],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 0,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         };
         assert_eq!(workflow.next_step(0), 0);
     }
@@ -271,7 +660,7 @@ mod tests {
     #[test]
     fn workflow_action_clone() {
         let mut items = HashMap::new();
-        items.insert("coal".to_string(), 3);
+        items.insert(ItemId::named("Coal").unwrap(), 3);
         let original = WorkflowAction::Pickup(Some(items));
         let cloned = original.clone();
         match (&original, &cloned) {
@@ -287,6 +676,7 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::Specific(Entity::PLACEHOLDER),
             action: WorkflowAction::Dropoff(None),
+            condition: None,
         };
         let cloned = step.clone();
         assert!(matches!(cloned.target, StepTarget::Specific(_)));
@@ -302,8 +692,10 @@ mod tests {
             building_set: set,
             steps: vec![],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         };
         assert!(workflow.building_set.contains(&Entity::PLACEHOLDER));
         assert_eq!(workflow.building_set.len(), 1);
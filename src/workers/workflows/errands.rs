@@ -0,0 +1,438 @@
+use super::components::{DeliveryRequest, Errand, PendingDeliveries, RelayLeg, WorkflowAssignment};
+use super::execution::{get_available_items_at, get_available_space_at};
+use crate::{
+    grid::{Grid, Position},
+    materials::{
+        cap_transfer_total, drawable_stock, fit_transfer, request_transfer_specific_items, Cargo,
+        InputPort, InventoryAccess, ItemId, ItemReserve, ItemTransferRequestEvent,
+        NamedOutputPorts, OutputPort, StoragePort,
+    },
+    structures::LogisticsPriority,
+    systems::NetworkConnectivity,
+    workers::{
+        pathfinding::{calculate_path_within, manhattan_distance_coords, PathfindingConfig},
+        Worker, WorkerArrivedEvent, WorkerPath,
+    },
+};
+use bevy::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Hands queued deliveries to idle, empty-handed workers, the one nearest the
+/// source first. Deliveries to buildings with a higher `LogisticsPriority` are
+/// served first, oldest first within a priority. Requests wait in the queue
+/// until a worker is free, and are dropped once their source or destination
+/// is gone.
+pub fn dispatch_delivery_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<DeliveryRequest>,
+    mut pending: ResMut<PendingDeliveries>,
+    mut idle_workers: Query<
+        (Entity, &Position, &Cargo, &mut WorkerPath),
+        (
+            With<Worker>,
+            Without<WorkflowAssignment>,
+            Without<RelayLeg>,
+            Without<Errand>,
+        ),
+    >,
+    positions: Query<&Position, Without<Worker>>,
+    priorities: Query<&LogisticsPriority>,
+    routing: (Res<NetworkConnectivity>, Res<Grid>, Res<PathfindingConfig>),
+    mut arrival_events: MessageWriter<WorkerArrivedEvent>,
+) {
+    let (network, grid, pathfinding) = routing;
+    pending.requests.extend(requests.read().cloned());
+    pending.requests.make_contiguous().sort_by_key(|request| {
+        Reverse(
+            priorities
+                .get(request.destination)
+                .copied()
+                .unwrap_or_default(),
+        )
+    });
+
+    let mut taken = HashSet::new();
+    pending.requests.retain(|request| {
+        if !positions.contains(request.destination) {
+            return false;
+        }
+        let Ok(source_pos) = positions.get(request.source) else {
+            return false;
+        };
+        let source_cell = (source_pos.x, source_pos.y);
+        let Some((worker, mut waypoints)) = idle_workers
+            .iter()
+            .filter(|(entity, _, cargo, path)| {
+                !taken.contains(entity) && cargo.is_empty() && path.current_target.is_none()
+            })
+            .min_by_key(|(entity, pos, _, _)| {
+                (
+                    manhattan_distance_coords((pos.x, pos.y), source_cell),
+                    *entity,
+                )
+            })
+            .and_then(|(entity, pos, _, _)| {
+                calculate_path_within(
+                    (pos.x, pos.y),
+                    source_cell,
+                    &network,
+                    &grid,
+                    pathfinding.max_path_length,
+                )
+                .map(|waypoints| (entity, waypoints))
+            })
+        else {
+            return true;
+        };
+
+        taken.insert(worker);
+        if let Ok((_, pos, _, mut path)) = idle_workers.get_mut(worker) {
+            path.current_target = waypoints.pop_front();
+            path.waypoints = waypoints;
+            if path.current_target.is_none() {
+                arrival_events.write(WorkerArrivedEvent {
+                    worker,
+                    position: (pos.x, pos.y),
+                });
+            }
+        }
+        commands.entity(worker).insert(Errand {
+            request: request.clone(),
+            loaded: false,
+        });
+        debug!(
+            ?worker,
+            source = ?request.source,
+            destination = ?request.destination,
+            "errand taken"
+        );
+        false
+    });
+}
+
+/// Loads an errand's items at its source, capped to what the source can give
+/// up and the worker can carry, then unloads them at the destination. What
+/// the worker has no room for is queued as a fresh request; whatever the
+/// destination cannot take is left to the usual emergency dropoff.
+pub fn handle_errand_arrivals(
+    mut commands: Commands,
+    mut events: MessageReader<WorkerArrivedEvent>,
+    mut workers: Query<(&mut Errand, &Cargo, &mut WorkerPath, &Position), With<Worker>>,
+    positions: Query<&Position, Without<Worker>>,
+    ports: (
+        Query<&OutputPort>,
+        Query<&NamedOutputPorts>,
+        Query<&StoragePort>,
+        Query<&InputPort>,
+        Query<&ItemReserve>,
+    ),
+    routing: (Res<NetworkConnectivity>, Res<Grid>, Res<PathfindingConfig>),
+    mut pending: ResMut<PendingDeliveries>,
+    mut transfer_events: MessageWriter<ItemTransferRequestEvent>,
+) {
+    let (output_ports, named_output_ports, storage_ports, input_ports, reserves) = ports;
+    let (network, grid, pathfinding) = routing;
+    for event in events.read() {
+        let Ok((mut errand, cargo, mut path, worker_pos)) = workers.get_mut(event.worker) else {
+            continue;
+        };
+
+        if errand.loaded {
+            let destination = errand.request.destination;
+            let space = get_available_space_at(destination, &input_ports, &storage_ports);
+            request_transfer_specific_items(
+                event.worker,
+                destination,
+                fit_transfer(
+                    &cargo.get_all_items(),
+                    input_ports.get(destination).ok(),
+                    space,
+                ),
+                &mut transfer_events,
+            );
+            commands.entity(event.worker).remove::<Errand>();
+            continue;
+        }
+
+        let source = errand.request.source;
+        let mut available = get_available_items_at(
+            source,
+            &output_ports,
+            &named_output_ports,
+            &storage_ports,
+            &input_ports,
+        );
+        if let Ok(port) = input_ports.get(source) {
+            for (item, qty) in port.releasable() {
+                let held = available.entry(item).or_default();
+                *held = (*held).max(qty);
+            }
+        }
+        let drawable = drawable_stock(&available, reserves.get(source).ok());
+        let wanted: HashMap<ItemId, u32> = errand
+            .request
+            .items
+            .iter()
+            .filter_map(|(&item, &qty)| {
+                let qty = qty.min(drawable.get(&item).copied().unwrap_or(0));
+                (qty > 0).then_some((item, qty))
+            })
+            .collect();
+        let items = cap_transfer_total(&wanted, cargo.capacity());
+        let left_behind: HashMap<ItemId, u32> = wanted
+            .iter()
+            .filter_map(|(&item, &qty)| {
+                let rest = qty - items.get(&item).copied().unwrap_or(0);
+                (rest > 0).then_some((item, rest))
+            })
+            .collect();
+
+        let destination = positions.get(errand.request.destination).ok();
+        let waypoints = destination.and_then(|destination| {
+            calculate_path_within(
+                (worker_pos.x, worker_pos.y),
+                (destination.x, destination.y),
+                &network,
+                &grid,
+                pathfinding.max_path_length,
+            )
+        });
+        match waypoints {
+            Some(mut waypoints) if !items.is_empty() && !waypoints.is_empty() => {
+                request_transfer_specific_items(source, event.worker, items, &mut transfer_events);
+                if !left_behind.is_empty() {
+                    pending.requests.push_back(DeliveryRequest {
+                        source,
+                        destination: errand.request.destination,
+                        items: left_behind,
+                    });
+                }
+                errand.loaded = true;
+                path.current_target = waypoints.pop_front();
+                path.waypoints = waypoints;
+            }
+            _ => {
+                debug!(worker = ?event.worker, ?source, "errand abandoned at its source");
+                commands.entity(event.worker).remove::<Errand>();
+            }
+        }
+    }
+}
+
+/// Puts back errands whose worker was given a workflow before it loaded up,
+/// so the delivery still happens.
+pub fn release_reassigned_errands(
+    mut commands: Commands,
+    workers: Query<(Entity, &Errand), With<WorkflowAssignment>>,
+    mut pending: ResMut<PendingDeliveries>,
+) {
+    for (worker, errand) in &workers {
+        if !errand.loaded {
+            pending.requests.push_front(errand.request.clone());
+        }
+        commands.entity(worker).remove::<Errand>();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::materials::{
+        execute_item_transfer, validate_item_transfer, ItemId, ItemTransferEvent,
+        ItemTransferValidationEvent,
+    };
+    use std::collections::{HashMap, VecDeque};
+
+    fn errand_app() -> App {
+        let mut network = NetworkConnectivity::default();
+        for x in 0..=2 {
+            network.add_connected_cell(x, 0);
+            network.add_core_network_cell(x, 0);
+        }
+
+        let mut app = App::new();
+        app.add_message::<DeliveryRequest>()
+            .add_message::<WorkerArrivedEvent>()
+            .add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .init_resource::<PendingDeliveries>()
+            .init_resource::<PathfindingConfig>()
+            .insert_resource(network)
+            .insert_resource(Grid::new(64.0))
+            .add_systems(
+                Update,
+                (
+                    dispatch_delivery_requests,
+                    handle_errand_arrivals,
+                    validate_item_transfer,
+                    execute_item_transfer,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn spawn_idle_worker(app: &mut App, cargo_capacity: u32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Worker,
+                Position { x: 0, y: 0 },
+                Cargo::new(cargo_capacity),
+                WorkerPath {
+                    waypoints: VecDeque::new(),
+                    current_target: None,
+                },
+            ))
+            .id()
+    }
+
+    #[test]
+    fn an_idle_worker_carries_a_delivery_request_to_its_destination() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = errand_app();
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ore, 30);
+        let storage = app
+            .world_mut()
+            .spawn((storage, Position { x: 0, y: 0 }))
+            .id();
+        let crafter = app
+            .world_mut()
+            .spawn((InputPort::new(20), Position { x: 2, y: 0 }))
+            .id();
+        let worker = spawn_idle_worker(&mut app, 10);
+
+        app.world_mut().write_message(DeliveryRequest {
+            source: storage,
+            destination: crafter,
+            items: HashMap::from([(ore, 6)]),
+        });
+        app.update();
+        app.update();
+
+        // Loaded at the storage and on its way, with nothing delivered yet.
+        assert!(app.world().get::<Errand>(worker).unwrap().loaded);
+        assert_eq!(
+            app.world()
+                .get::<Cargo>(worker)
+                .unwrap()
+                .get_item_quantity(ore),
+            6
+        );
+        assert!(app
+            .world()
+            .get::<WorkerPath>(worker)
+            .unwrap()
+            .current_target
+            .is_some());
+        assert_eq!(
+            app.world()
+                .get::<InputPort>(crafter)
+                .unwrap()
+                .get_item_quantity(ore),
+            0
+        );
+
+        *app.world_mut().get_mut::<Position>(worker).unwrap() = Position { x: 2, y: 0 };
+        app.world_mut().write_message(WorkerArrivedEvent {
+            worker,
+            position: (2, 0),
+        });
+        app.update();
+
+        assert!(app.world().get::<Errand>(worker).is_none());
+        assert!(app.world().get::<Cargo>(worker).unwrap().is_empty());
+        assert_eq!(
+            app.world()
+                .get::<InputPort>(crafter)
+                .unwrap()
+                .get_item_quantity(ore),
+            6
+        );
+        assert!(app
+            .world()
+            .resource::<PendingDeliveries>()
+            .requests
+            .is_empty());
+    }
+
+    #[test]
+    fn deliveries_to_high_priority_buildings_are_taken_first() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = errand_app();
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ore, 30);
+        let storage = app
+            .world_mut()
+            .spawn((storage, Position { x: 0, y: 0 }))
+            .id();
+        let smelter = app
+            .world_mut()
+            .spawn((InputPort::new(20), Position { x: 1, y: 0 }))
+            .id();
+        let lab = app
+            .world_mut()
+            .spawn((
+                InputPort::new(20),
+                Position { x: 2, y: 0 },
+                LogisticsPriority::High,
+            ))
+            .id();
+        let worker = spawn_idle_worker(&mut app, 10);
+
+        for destination in [smelter, lab] {
+            app.world_mut().write_message(DeliveryRequest {
+                source: storage,
+                destination,
+                items: HashMap::from([(ore, 5)]),
+            });
+        }
+        app.update();
+
+        let errand = app.world().get::<Errand>(worker).unwrap();
+        assert_eq!(errand.request.destination, lab);
+        let pending = &app.world().resource::<PendingDeliveries>().requests;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].destination, smelter);
+    }
+
+    #[test]
+    fn what_the_worker_cannot_carry_is_queued_again() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let coal = ItemId::named("Coal").unwrap();
+        let mut app = errand_app();
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ore, 30);
+        storage.add_item(coal, 30);
+        let storage = app
+            .world_mut()
+            .spawn((storage, Position { x: 0, y: 0 }))
+            .id();
+        let crafter = app
+            .world_mut()
+            .spawn((InputPort::new(20), Position { x: 2, y: 0 }))
+            .id();
+        let worker = spawn_idle_worker(&mut app, 10);
+
+        app.world_mut().write_message(DeliveryRequest {
+            source: storage,
+            destination: crafter,
+            items: HashMap::from([(ore, 8), (coal, 8)]),
+        });
+        app.update();
+        app.update();
+
+        let carried = app.world().get::<Cargo>(worker).unwrap().get_all_items();
+        assert_eq!(carried.values().sum::<u32>(), 10);
+        let pending = &app.world().resource::<PendingDeliveries>().requests;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].destination, crafter);
+        for item in [ore, coal] {
+            let queued = pending[0].items.get(&item).copied().unwrap_or(0);
+            assert_eq!(carried.get(&item).copied().unwrap_or(0) + queued, 8);
+        }
+    }
+}
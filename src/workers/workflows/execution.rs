@@ -1,28 +1,39 @@
 use super::components::{
-    StepTarget, WaitingForItems, WaitingForSpace, Workflow, WorkflowAction, WorkflowAssignment,
+    EmergencyDropoffAttempts, Errand, LogisticsConfig, PendingSequenceQueue, RelayHandoff,
+    RelayLeg, RequestCooldown, StepTarget, WaitingForItems, WaitingForSpace, WaitingForTarget,
+    Workflow, WorkflowAction, WorkflowAssignment, WorkflowStep,
 };
 use crate::{
     grid::{Grid, Position},
     materials::{
-        request_transfer_specific_items, Cargo, InputPort, InventoryAccess,
-        ItemTransferRequestEvent, OutputPort, StoragePort,
+        cap_transfer_total, combined_output_items, drawable_stock, fit_transfer,
+        request_transfer_specific_items, Cargo, InputPort, InventoryAccess, ItemId, ItemRegistry,
+        ItemReserve, ItemTransferRequestEvent, NamedOutputPorts, OutputPort, RecipeDef,
+        RecipeRegistry, StoragePort,
+    },
+    structures::{DispatchWeight, LogisticsPriority, RecipeChangeEvent, RecipeCrafter},
+    systems::{NetworkChangedEvent, NetworkConnectivity},
+    workers::{
+        pathfinding::{calculate_path_within, PathfindingConfig},
+        Worker, WorkerArrivedEvent, WorkerPath,
     },
-    systems::NetworkConnectivity,
-    workers::{pathfinding::calculate_path, Worker, WorkerArrivedEvent, WorkerPath},
 };
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet};
 
-fn get_available_items_at(
+pub(super) fn get_available_items_at(
     target: Entity,
     output_ports: &Query<&OutputPort>,
+    named_output_ports: &Query<&NamedOutputPorts>,
     storage_ports: &Query<&StoragePort>,
     input_ports: &Query<&InputPort>,
-) -> HashMap<String, u32> {
-    if let Ok(port) = output_ports.get(target) {
-        if !port.is_empty() {
-            return port.get_all_items();
-        }
+) -> HashMap<ItemId, u32> {
+    let outputs = combined_output_items(
+        output_ports.get(target).ok(),
+        named_output_ports.get(target).ok(),
+    );
+    if !outputs.is_empty() {
+        return outputs;
     }
     if let Ok(port) = storage_ports.get(target) {
         if !port.is_empty() {
@@ -37,7 +48,27 @@ fn get_available_items_at(
     HashMap::new()
 }
 
-fn get_available_space_at(
+/// What a pickup may take from `target`: its available items above any
+/// reserve it keeps.
+fn drawable_items_at(
+    target: Entity,
+    output_ports: &Query<&OutputPort>,
+    named_output_ports: &Query<&NamedOutputPorts>,
+    storage_ports: &Query<&StoragePort>,
+    input_ports: &Query<&InputPort>,
+    reserves: &Query<&ItemReserve>,
+) -> HashMap<ItemId, u32> {
+    let available = get_available_items_at(
+        target,
+        output_ports,
+        named_output_ports,
+        storage_ports,
+        input_ports,
+    );
+    drawable_stock(&available, reserves.get(target).ok())
+}
+
+pub(super) fn get_available_space_at(
     target: Entity,
     input_ports: &Query<&InputPort>,
     storage_ports: &Query<&StoragePort>,
@@ -51,19 +82,42 @@ fn get_available_space_at(
     0
 }
 
+/// What a step condition sees of `item` at a building: its input port, or
+/// its storage port for buildings without one.
+fn held_quantity(
+    input_port: Option<&InputPort>,
+    storage_port: Option<&StoragePort>,
+    item: ItemId,
+) -> Option<u32> {
+    input_port
+        .map(|port| port.get_item_quantity(item))
+        .or_else(|| storage_port.map(|port| port.get_item_quantity(item)))
+}
+
+/// Workers already carrying cargo only top up to capacity, so a pickup never
+/// asks for more than fits.
+fn free_cargo_space(cargo: &Cargo) -> u32 {
+    cargo.capacity().saturating_sub(cargo.get_total_quantity())
+}
+
 fn compute_pickup_items(
-    available: &HashMap<String, u32>,
-    filter: Option<&HashMap<String, u32>>,
-) -> HashMap<String, u32> {
+    available: &HashMap<ItemId, u32>,
+    filter: Option<&HashMap<ItemId, u32>>,
+    per_item_cap: Option<u32>,
+) -> HashMap<ItemId, u32> {
+    let cap = per_item_cap.unwrap_or(u32::MAX);
     match filter {
-        None => available.clone(),
+        None => available
+            .iter()
+            .map(|(&item, &qty)| (item, qty.min(cap)))
+            .collect(),
         Some(requested) => {
             let mut result = HashMap::new();
             for (item, &requested_qty) in requested {
                 if let Some(&available_qty) = available.get(item) {
-                    let qty = available_qty.min(requested_qty);
+                    let qty = available_qty.min(requested_qty).min(cap);
                     if qty > 0 {
-                        result.insert(item.clone(), qty);
+                        result.insert(*item, qty);
                     }
                 }
             }
@@ -73,9 +127,9 @@ fn compute_pickup_items(
 }
 
 fn compute_dropoff_items(
-    cargo_items: &HashMap<String, u32>,
-    filter: Option<&HashMap<String, u32>>,
-) -> HashMap<String, u32> {
+    cargo_items: &HashMap<ItemId, u32>,
+    filter: Option<&HashMap<ItemId, u32>>,
+) -> HashMap<ItemId, u32> {
     match filter {
         None => cargo_items.clone(),
         Some(requested) => {
@@ -84,7 +138,7 @@ fn compute_dropoff_items(
                 if let Some(&cargo_qty) = cargo_items.get(item) {
                     let qty = cargo_qty.min(requested_qty);
                     if qty > 0 {
-                        result.insert(item.clone(), qty);
+                        result.insert(*item, qty);
                     }
                 }
             }
@@ -93,11 +147,61 @@ fn compute_dropoff_items(
     }
 }
 
+/// A crafter's buffer target for each input is its input port's item limit.
+/// When the step after a pickup drops off at a specific crafter, the pickup
+/// only takes what tops that crafter up to target, less what the worker
+/// already carries toward it. Source stock and free cargo space cap it
+/// further; other pickups are left as they are.
+fn cap_to_buffer_target(
+    items: HashMap<ItemId, u32>,
+    workflow: Option<&Workflow>,
+    pickup_step: usize,
+    input_ports: &Query<&InputPort>,
+    cargo: &Cargo,
+) -> HashMap<ItemId, u32> {
+    let Some(StepTarget::Specific(crafter)) = workflow
+        .and_then(|workflow| workflow.steps.get(workflow.next_step(pickup_step)))
+        .filter(|step| matches!(step.action, WorkflowAction::Dropoff(_)))
+        .map(|step| &step.target)
+    else {
+        return items;
+    };
+    match input_ports.get(*crafter) {
+        Ok(input_port) if !input_port.item_limits.is_empty() => {
+            top_up_to_buffer(&items, input_port, cargo)
+        }
+        _ => items,
+    }
+}
+
+fn top_up_to_buffer(
+    items: &HashMap<ItemId, u32>,
+    input_port: &InputPort,
+    cargo: &Cargo,
+) -> HashMap<ItemId, u32> {
+    items
+        .iter()
+        .filter_map(|(&item, &qty)| {
+            let target = input_port.item_limits.get(&item)?;
+            let shortfall = target
+                .saturating_sub(input_port.get_item_quantity(item))
+                .saturating_sub(cargo.get_item_quantity(item));
+            let qty = qty.min(shortfall);
+            (qty > 0).then_some((item, qty))
+        })
+        .collect()
+}
+
+/// By-type targets rotate through the top-priority candidates in position
+/// order. Each candidate takes as many consecutive turns as its
+/// `DispatchWeight::of` its storage, so the rotation stays deterministic.
 fn resolve_step_target(
     step: &super::components::WorkflowStep,
     building_set: &HashSet<Entity>,
     positions: &Query<&Position>,
     names: &Query<&Name>,
+    priorities: &Query<&LogisticsPriority>,
+    weights: &Query<&StoragePort, With<DispatchWeight>>,
     round_robin_counters: &mut HashMap<usize, usize>,
     step_index: usize,
 ) -> Option<Entity> {
@@ -122,9 +226,9 @@ fn resolve_step_target(
                 })
                 .collect();
 
-            if candidates.is_empty() {
-                return None;
-            }
+            let priority_of = |entity: Entity| priorities.get(entity).copied().unwrap_or_default();
+            let top_priority = candidates.iter().map(|(e, _)| priority_of(*e)).max()?;
+            candidates.retain(|(e, _)| priority_of(*e) == top_priority);
 
             candidates.sort_by(|a, b| {
                 a.1.x
@@ -133,100 +237,496 @@ fn resolve_step_target(
                     .then_with(|| a.0.cmp(&b.0))
             });
 
+            // Reduced by their common divisor, so equal storages alternate
+            // instead of taking runs of turns each.
+            let mut weighted: Vec<(Entity, usize)> = candidates
+                .iter()
+                .map(|&(entity, _)| {
+                    let weight = weights
+                        .get(entity)
+                        .map_or(1, |storage| DispatchWeight::of(storage) as usize);
+                    (entity, weight)
+                })
+                .collect();
+            let divisor = weighted
+                .iter()
+                .fold(0, |acc, &(_, weight)| gcd(acc, weight));
+            for (_, weight) in &mut weighted {
+                *weight /= divisor;
+            }
+            let total_weight: usize = weighted.iter().map(|&(_, weight)| weight).sum();
+
             let counter = round_robin_counters.entry(step_index).or_insert(0);
-            let idx = *counter % candidates.len();
+            let mut slot = *counter % total_weight;
             *counter += 1;
 
-            Some(candidates[idx].0)
+            weighted.iter().find_map(|&(entity, weight)| {
+                if slot < weight {
+                    return Some(entity);
+                }
+                slot -= weight;
+                None
+            })
         }
     }
 }
 
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Item value of one craft of the active recipe, or `None` while the crafter
+/// has no recipe.
+fn recipe_output_value(
+    crafter: &RecipeCrafter,
+    recipes: &RecipeRegistry,
+    items: &ItemRegistry,
+) -> Option<u64> {
+    let outputs = recipes.get_outputs(crafter.get_active_recipe()?)?;
+    Some(
+        outputs
+            .iter()
+            .map(|(&item, &quantity)| items.value_of(item) * u64::from(quantity))
+            .sum(),
+    )
+}
+
+/// Whether `crafter` takes any of `cargo`: its active recipe uses the item
+/// and its input port has room for more of it.
+fn accepts_cargo(
+    cargo: &Cargo,
+    crafter: &RecipeCrafter,
+    input_port: &InputPort,
+    recipes: &RecipeRegistry,
+) -> bool {
+    let Some(inputs) = crafter
+        .get_active_recipe()
+        .and_then(|recipe| recipes.get_inputs(recipe))
+    else {
+        return false;
+    };
+    if input_port.get_total_quantity() >= input_port.capacity() {
+        return false;
+    }
+    cargo.items.keys().any(|&item| {
+        inputs.contains_key(&item)
+            && input_port
+                .item_limits
+                .get(&item)
+                .is_none_or(|&limit| input_port.get_item_quantity(item) < limit)
+    })
+}
+
+/// Narrows the candidates of a by-type step to the one whose output is worth
+/// the most, so scarce inputs feed the most valuable recipe first.
+/// `output_value` is `None` for candidates that cannot take the delivery;
+/// they drop out, so a full or unsuitable crafter passes its turn to the
+/// next most valuable one. With no candidate able to take it, the targets
+/// are left for `resolve_step_target` to rotate through as usual.
+fn most_valuable_targets(
+    type_name: &str,
+    targets: HashSet<Entity>,
+    names: &Query<&Name>,
+    priorities: &Query<&LogisticsPriority>,
+    output_value: impl Fn(Entity) -> Option<u64>,
+) -> HashSet<Entity> {
+    let is_candidate = |entity: Entity| names.get(entity).is_ok_and(|n| n.as_str() == type_name);
+    let priority_of = |entity: Entity| priorities.get(entity).copied().unwrap_or_default();
+    let ranked: Vec<(Entity, (LogisticsPriority, u64))> = targets
+        .iter()
+        .copied()
+        .filter(|&entity| is_candidate(entity))
+        .filter_map(|entity| Some((entity, (priority_of(entity), output_value(entity)?))))
+        .collect();
+    let Some(best) = ranked.iter().map(|&(_, rank)| rank).max() else {
+        return targets;
+    };
+
+    targets
+        .into_iter()
+        .filter(|&entity| !is_candidate(entity) || ranked.contains(&(entity, best)))
+        .collect()
+}
+
+/// Whether any building in the pool carries the type a by-type step names.
+fn has_type_candidate(
+    type_name: &str,
+    building_set: &HashSet<Entity>,
+    names: &Query<&Name>,
+    positions: &Query<&Position>,
+) -> bool {
+    building_set.iter().any(|&entity| {
+        names
+            .get(entity)
+            .is_ok_and(|name| name.as_str() == type_name)
+            && positions.contains(entity)
+    })
+}
+
+/// Whether the step names a building type the pool has none of, so a worker
+/// on it should wait for one rather than move on.
+fn lacks_type_candidate(
+    step: &WorkflowStep,
+    building_set: &HashSet<Entity>,
+    names: &Query<&Name>,
+    positions: &Query<&Position>,
+) -> bool {
+    match &step.target {
+        StepTarget::ByType(type_name) => {
+            !has_type_candidate(type_name, building_set, names, positions)
+        }
+        StepTarget::Specific(_) => false,
+    }
+}
+
+fn step_request_priority(
+    step: &super::components::WorkflowStep,
+    building_set: &HashSet<Entity>,
+    names: &Query<&Name>,
+    priorities: &Query<&LogisticsPriority>,
+) -> LogisticsPriority {
+    let priority_of = |entity: Entity| priorities.get(entity).copied().unwrap_or_default();
+    match &step.target {
+        StepTarget::Specific(entity) => priority_of(*entity),
+        StepTarget::ByType(type_name) => building_set
+            .iter()
+            .filter(|&&entity| names.get(entity).is_ok_and(|n| n.as_str() == type_name))
+            .map(|&entity| priority_of(entity))
+            .max()
+            .unwrap_or_default(),
+    }
+}
+
+/// The highest priority any of the workflow's steps would dispatch at.
+pub(crate) fn workflow_priority(
+    workflow: &Workflow,
+    names: &Query<&Name>,
+    priorities: &Query<&LogisticsPriority>,
+) -> LogisticsPriority {
+    workflow
+        .steps
+        .iter()
+        .map(|step| step_request_priority(step, &workflow.building_set, names, priorities))
+        .max()
+        .unwrap_or_default()
+}
+
+/// Buildings a step may resolve to right now, leaving out those cooling down.
+fn open_targets(
+    building_set: &HashSet<Entity>,
+    cooling_down: impl Fn(&Entity) -> bool,
+) -> HashSet<Entity> {
+    building_set
+        .iter()
+        .copied()
+        .filter(|entity| !cooling_down(entity))
+        .collect()
+}
+
+/// Drops requests that no longer apply, appends new ones in entity order, then
+/// keeps the queue sorted by priority (stable, so ties stay first-come).
+fn requeue_pending(
+    pending: &mut PendingSequenceQueue,
+    requests: &HashMap<Entity, LogisticsPriority>,
+) {
+    pending
+        .requests
+        .retain(|entity| requests.contains_key(entity));
+    let mut new_requests: Vec<Entity> = requests
+        .keys()
+        .copied()
+        .filter(|entity| !pending.requests.contains(entity))
+        .collect();
+    new_requests.sort();
+    pending.requests.extend(new_requests);
+    pending
+        .requests
+        .sort_by_key(|entity| std::cmp::Reverse(requests.get(entity).copied().unwrap_or_default()));
+}
+
+/// Queues the workers ready for their next step, at the priority that step
+/// would dispatch at. Workers already travelling or holding a target, and
+/// those on paused workflows, make no request.
+fn queue_step_requests<'a>(
+    pending: &mut PendingSequenceQueue,
+    workers: impl Iterator<Item = (Entity, &'a WorkflowAssignment, &'a WorkerPath)>,
+    workflows: &Query<&mut Workflow>,
+    names: &Query<&Name>,
+    priorities: &Query<&LogisticsPriority>,
+) {
+    let requests = workers
+        .filter(|(_, assignment, path)| {
+            path.current_target.is_none() && assignment.resolved_target.is_none()
+        })
+        .filter_map(|(worker_entity, assignment, _)| {
+            let workflow = workflows.get(assignment.workflow).ok()?;
+            if workflow.is_paused {
+                return None;
+            }
+            let step = workflow.steps.get(assignment.current_step)?;
+            Some((
+                worker_entity,
+                step_request_priority(step, &workflow.building_set, names, priorities),
+            ))
+        })
+        .collect();
+    requeue_pending(pending, &requests);
+}
+
+/// Whether the step has no condition or its condition holds right now.
+fn condition_holds(
+    step: &WorkflowStep,
+    inventories: &Query<(Option<&InputPort>, Option<&StoragePort>)>,
+) -> bool {
+    step.condition.as_ref().is_none_or(|condition| {
+        condition.holds(|building, item| {
+            let (input_port, storage_port) = inventories.get(building).ok()?;
+            held_quantity(input_port, storage_port, item)
+        })
+    })
+}
+
+/// Value per craft of the crafter's active recipe, or `None` when it would
+/// take none of `cargo`.
+fn cargo_output_value(
+    crafter: Entity,
+    cargo: Option<&Cargo>,
+    inventories: &Query<(Option<&InputPort>, Option<&StoragePort>)>,
+    output_values: &(
+        Query<&RecipeCrafter>,
+        Res<RecipeRegistry>,
+        Res<ItemRegistry>,
+    ),
+) -> Option<u64> {
+    let (crafters, recipes, items) = output_values;
+    let recipe_crafter = crafters.get(crafter).ok()?;
+    let (input_port, _) = inventories.get(crafter).ok()?;
+    if !accepts_cargo(cargo?, recipe_crafter, input_port?, recipes) {
+        return None;
+    }
+    recipe_output_value(recipe_crafter, recipes, items)
+}
+
+/// Sets the worker off towards its target, announcing the arrival at once
+/// when it is already there. Returns false when the target is gone or no
+/// path is found.
+fn route_worker(
+    worker: Entity,
+    from: Position,
+    target: Entity,
+    positions: &Query<&Position>,
+    path: &mut WorkerPath,
+    routing: &(Res<NetworkConnectivity>, Res<Grid>, Res<PathfindingConfig>),
+    arrival_events: &mut MessageWriter<WorkerArrivedEvent>,
+) -> bool {
+    let (network, grid, pathfinding) = routing;
+    let Ok(to) = positions.get(target) else {
+        return false;
+    };
+    let Some(mut waypoints) = calculate_path_within(
+        (from.x, from.y),
+        (to.x, to.y),
+        network,
+        grid,
+        pathfinding.max_path_length,
+    ) else {
+        return false;
+    };
+    path.current_target = waypoints.pop_front();
+    path.waypoints = waypoints;
+    if path.current_target.is_none() {
+        arrival_events.write(WorkerArrivedEvent {
+            worker,
+            position: (from.x, from.y),
+        });
+    }
+    true
+}
+
 pub fn process_workflow_workers(
+    mut commands: Commands,
     mut workers: Query<
         (Entity, &mut WorkflowAssignment, &Position, &mut WorkerPath),
         (
             With<Worker>,
             Without<WaitingForItems>,
             Without<WaitingForSpace>,
+            Without<WaitingForTarget>,
+        ),
+    >,
+    waiting_workers: Query<
+        (),
+        (
+            With<Worker>,
+            With<WorkflowAssignment>,
+            Or<(With<WaitingForItems>, With<WaitingForSpace>)>,
         ),
     >,
     mut workflows: Query<&mut Workflow>,
     positions: Query<&Position>,
     names: Query<&Name>,
-    network: Res<NetworkConnectivity>,
-    grid: Res<Grid>,
+    priorities: Query<&LogisticsPriority>,
+    weights: Query<&StoragePort, With<DispatchWeight>>,
+    cooldowns: Query<(), With<RequestCooldown>>,
+    config: Res<LogisticsConfig>,
+    mut pending: ResMut<PendingSequenceQueue>,
+    routing: (Res<NetworkConnectivity>, Res<Grid>, Res<PathfindingConfig>),
+    inventories: Query<(Option<&InputPort>, Option<&StoragePort>)>,
+    cargos: Query<&Cargo>,
+    output_values: (
+        Query<&RecipeCrafter>,
+        Res<RecipeRegistry>,
+        Res<ItemRegistry>,
+    ),
     mut arrival_events: MessageWriter<WorkerArrivedEvent>,
 ) {
-    for (worker_entity, mut assignment, worker_pos, mut path) in &mut workers {
-        let Ok(mut workflow) = workflows.get_mut(assignment.workflow) else {
-            continue;
-        };
-
-        if workflow.is_paused {
-            continue;
+    let mut active = waiting_workers.iter().count()
+        + workers
+            .iter()
+            .filter(|(_, assignment, _, _)| assignment.resolved_target.is_some())
+            .count();
+
+    queue_step_requests(
+        &mut pending,
+        workers
+            .iter()
+            .map(|(entity, assignment, _, path)| (entity, assignment, path)),
+        &workflows,
+        &names,
+        &priorities,
+    );
+
+    let mut dispatched = HashSet::new();
+    let mut cooled_this_frame = HashSet::new();
+    let mut attempts = 0;
+    for &worker_entity in &pending.requests {
+        if active >= config.max_active_sequences || attempts >= config.max_dispatches_per_frame {
+            break;
         }
 
-        if workflow.steps.is_empty() {
+        let Ok((_, mut assignment, worker_pos, mut path)) = workers.get_mut(worker_entity) else {
             continue;
-        }
-
+        };
+        let Ok(mut workflow) = workflows.get_mut(assignment.workflow) else {
+            continue;
+        };
         let Some(step) = workflow.steps.get(assignment.current_step).cloned() else {
             continue;
         };
+        attempts += 1;
 
-        if path.current_target.is_some() || assignment.resolved_target.is_some() {
+        if !condition_holds(&step, &inventories) {
+            let carrying = cargos.get(worker_entity).is_ok_and(|c| !c.is_empty());
+            assignment.current_step = workflow.skip_step(assignment.current_step, carrying);
             continue;
         }
 
         let wf = &mut *workflow;
+        let uses_cooldown =
+            matches!(step.action, WorkflowAction::Dropoff(_)) && config.request_cooldown_secs > 0.0;
+        let cooling_down = |entity: &Entity| {
+            uses_cooldown && (cooldowns.contains(*entity) || cooled_this_frame.contains(entity))
+        };
+        let mut open_targets = open_targets(&wf.building_set, cooling_down);
+        if let (StepTarget::ByType(type_name), WorkflowAction::Dropoff(_)) =
+            (&step.target, &step.action)
+        {
+            if config.prioritize_by_output_value {
+                let cargo = cargos.get(worker_entity).ok();
+                open_targets =
+                    most_valuable_targets(type_name, open_targets, &names, &priorities, |e| {
+                        cargo_output_value(e, cargo, &inventories, &output_values)
+                    });
+            }
+        }
         let Some(target_entity) = resolve_step_target(
             &step,
-            &wf.building_set,
+            &open_targets,
             &positions,
             &names,
+            &priorities,
+            &weights,
             &mut wf.round_robin_counters,
             assignment.current_step,
         ) else {
-            assignment.current_step = workflow.next_step(assignment.current_step);
+            // A by-type step with nothing to match waits for a building
+            // rather than spinning the worker through the other steps, and
+            // one whose target is cooling down stays queued.
+            if lacks_type_candidate(&step, &wf.building_set, &names, &positions) {
+                commands.entity(worker_entity).insert(WaitingForTarget {
+                    step: assignment.current_step,
+                });
+            } else if !wf.building_set.iter().any(cooling_down) {
+                assignment.current_step = workflow.next_step(assignment.current_step);
+            }
             continue;
         };
 
         assignment.resolved_target = Some(target_entity);
         assignment.resolved_action = Some(step.action.clone());
+        active += 1;
+        dispatched.insert(worker_entity);
+        if uses_cooldown {
+            cooled_this_frame.insert(target_entity);
+            commands
+                .entity(target_entity)
+                .insert(RequestCooldown::new(config.request_cooldown_secs));
+        }
 
-        let Ok(target_pos) = positions.get(target_entity) else {
-            assignment.current_step = workflow.next_step(assignment.current_step);
-            continue;
-        };
-
-        let start = (worker_pos.x, worker_pos.y);
-        let end = (target_pos.x, target_pos.y);
-
-        if let Some(mut waypoints) = calculate_path(start, end, &network, &grid) {
-            let first = waypoints.pop_front();
-            path.waypoints = waypoints;
-            path.current_target = first;
-
-            if path.current_target.is_none() {
-                arrival_events.write(WorkerArrivedEvent {
-                    worker: worker_entity,
-                    position: (worker_pos.x, worker_pos.y),
-                });
-            }
-        } else {
+        let routed = route_worker(
+            worker_entity,
+            *worker_pos,
+            target_entity,
+            &positions,
+            &mut path,
+            &routing,
+            &mut arrival_events,
+        );
+        if !routed {
             assignment.current_step = workflow.next_step(assignment.current_step);
         }
     }
+
+    pending
+        .requests
+        .retain(|entity| !dispatched.contains(entity));
+}
+
+/// Whether `step` of `workflow` has a condition that no longer holds.
+fn step_condition_fails(
+    workflow: Option<&Workflow>,
+    step: usize,
+    input_ports: &Query<&InputPort>,
+    storage_ports: &Query<&StoragePort>,
+) -> bool {
+    workflow
+        .and_then(|workflow| workflow.steps.get(step))
+        .and_then(|step| step.condition.as_ref())
+        .is_some_and(|condition| {
+            !condition.holds(|building, item| {
+                held_quantity(
+                    input_ports.get(building).ok(),
+                    storage_ports.get(building).ok(),
+                    item,
+                )
+            })
+        })
 }
 
 pub fn handle_workflow_arrivals(
     mut events: MessageReader<WorkerArrivedEvent>,
-    mut workers: Query<(&mut WorkflowAssignment, &Cargo), With<Worker>>,
-    workflows: Query<&Workflow>,
+    config: Res<LogisticsConfig>,
+    mut workers: Query<(&mut WorkflowAssignment, &Cargo), (With<Worker>, Without<RelayHandoff>)>,
+    mut workflows: Query<&mut Workflow>,
     output_ports: Query<&OutputPort>,
+    named_output_ports: Query<&NamedOutputPorts>,
     storage_ports: Query<&StoragePort>,
     input_ports: Query<&InputPort>,
+    reserves: Query<&ItemReserve>,
     mut transfer_events: MessageWriter<ItemTransferRequestEvent>,
     mut commands: Commands,
 ) {
@@ -243,21 +743,61 @@ pub fn handle_workflow_arrivals(
             continue;
         };
 
+        // The condition is checked again on arrival, since it may have
+        // stopped holding during the walk.
+        let condition_failed = step_condition_fails(
+            workflows.get(assignment.workflow).ok(),
+            assignment.current_step,
+            &input_ports,
+            &storage_ports,
+        );
+        if condition_failed {
+            if let Ok(workflow) = workflows.get(assignment.workflow) {
+                let carrying = cargo.get_total_quantity() > 0;
+                assignment.current_step = workflow.skip_step(assignment.current_step, carrying);
+            }
+            assignment.resolved_target = None;
+            continue;
+        }
+
         match &action {
             WorkflowAction::Pickup(filter) => {
-                let available =
-                    get_available_items_at(target, &output_ports, &storage_ports, &input_ports);
-                let items = compute_pickup_items(&available, filter.as_ref());
+                let available = drawable_items_at(
+                    target,
+                    &output_ports,
+                    &named_output_ports,
+                    &storage_ports,
+                    &input_ports,
+                    &reserves,
+                );
+                let items = cap_to_buffer_target(
+                    compute_pickup_items(&available, filter.as_ref(), config.max_per_item_per_trip),
+                    workflows.get(assignment.workflow).ok(),
+                    assignment.current_step,
+                    &input_ports,
+                    cargo,
+                );
 
                 if items.is_empty() {
+                    if let Ok(mut workflow) = workflows.get_mut(assignment.workflow) {
+                        workflow.record_step_wait(assignment.current_step);
+                    }
                     assignment.resolved_action = Some(action);
                     commands
                         .entity(event.worker)
-                        .insert(WaitingForItems::default());
+                        .insert(WaitingForItems::new(config.wait_retry_secs));
                     continue;
                 }
 
-                request_transfer_specific_items(target, event.worker, items, &mut transfer_events);
+                request_transfer_specific_items(
+                    target,
+                    event.worker,
+                    cap_transfer_total(&items, free_cargo_space(cargo)),
+                    &mut transfer_events,
+                );
+                if let Ok(mut workflow) = workflows.get_mut(assignment.workflow) {
+                    workflow.clear_step_waits(assignment.current_step);
+                }
             }
             WorkflowAction::Dropoff(filter) => {
                 let cargo_items = cargo.get_all_items();
@@ -270,9 +810,12 @@ pub fn handle_workflow_arrivals(
                     request_transfer_specific_items(
                         event.worker,
                         target,
-                        items,
+                        fit_transfer(&items, input_ports.get(target).ok(), space),
                         &mut transfer_events,
                     );
+                    if let Ok(mut building) = commands.get_entity(target) {
+                        building.remove::<RequestCooldown>();
+                    }
 
                     if space < total_to_drop {
                         assignment.resolved_action = Some(action);
@@ -298,14 +841,25 @@ pub fn handle_workflow_arrivals(
 pub fn recheck_waiting_workers(
     mut commands: Commands,
     time: Res<Time>,
-    mut workers: Query<(Entity, &mut WaitingForItems, &mut WorkflowAssignment), With<Worker>>,
-    workflows: Query<&Workflow>,
+    mut workers: Query<
+        (
+            Entity,
+            &mut WaitingForItems,
+            &mut WorkflowAssignment,
+            &Cargo,
+        ),
+        With<Worker>,
+    >,
+    mut workflows: Query<&mut Workflow>,
+    config: Res<LogisticsConfig>,
     output_ports: Query<&OutputPort>,
+    named_output_ports: Query<&NamedOutputPorts>,
     storage_ports: Query<&StoragePort>,
     input_ports: Query<&InputPort>,
+    reserves: Query<&ItemReserve>,
     mut transfer_events: MessageWriter<ItemTransferRequestEvent>,
 ) {
-    for (worker_entity, mut waiting, mut assignment) in &mut workers {
+    for (worker_entity, mut waiting, mut assignment, cargo) in &mut workers {
         waiting.timer.tick(time.delta());
 
         if !waiting.timer.just_finished() {
@@ -320,17 +874,38 @@ pub fn recheck_waiting_workers(
             continue;
         };
 
-        let available = get_available_items_at(target, &output_ports, &storage_ports, &input_ports);
-        let items = compute_pickup_items(&available, filter.as_ref());
+        let available = drawable_items_at(
+            target,
+            &output_ports,
+            &named_output_ports,
+            &storage_ports,
+            &input_ports,
+            &reserves,
+        );
+        let items = cap_to_buffer_target(
+            compute_pickup_items(&available, filter.as_ref(), config.max_per_item_per_trip),
+            workflows.get(assignment.workflow).ok(),
+            assignment.current_step,
+            &input_ports,
+            cargo,
+        );
 
-        if !items.is_empty() {
+        if items.is_empty() {
+            waiting.back_off(config.wait_backoff_factor, config.max_wait_retry_secs);
+        } else {
             commands.entity(worker_entity).remove::<WaitingForItems>();
-            request_transfer_specific_items(target, worker_entity, items, &mut transfer_events);
+            request_transfer_specific_items(
+                target,
+                worker_entity,
+                cap_transfer_total(&items, free_cargo_space(cargo)),
+                &mut transfer_events,
+            );
 
-            let Ok(workflow) = workflows.get(assignment.workflow) else {
+            let Ok(mut workflow) = workflows.get_mut(assignment.workflow) else {
                 continue;
             };
 
+            workflow.clear_step_waits(assignment.current_step);
             assignment.resolved_target = None;
             assignment.resolved_action = None;
             assignment.current_step = workflow.next_step(assignment.current_step);
@@ -338,6 +913,28 @@ pub fn recheck_waiting_workers(
     }
 }
 
+/// Releases workers held on a by-type step once a matching building is in
+/// the pool, or once their step or workflow changed under them.
+pub fn recheck_waiting_for_target(
+    mut commands: Commands,
+    workers: Query<(Entity, &WaitingForTarget, &WorkflowAssignment), With<Worker>>,
+    workflows: Query<&Workflow>,
+    names: Query<&Name>,
+    positions: Query<&Position>,
+) {
+    for (worker_entity, waiting, assignment) in &workers {
+        let still_unmatched = assignment.current_step == waiting.step
+            && workflows.get(assignment.workflow).is_ok_and(|workflow| {
+                workflow.steps.get(waiting.step).is_some_and(|step| {
+                    lacks_type_candidate(step, &workflow.building_set, &names, &positions)
+                })
+            });
+        if !still_unmatched {
+            commands.entity(worker_entity).remove::<WaitingForTarget>();
+        }
+    }
+}
+
 pub fn recheck_waiting_for_space(
     mut commands: Commands,
     time: Res<Time>,
@@ -425,7 +1022,12 @@ pub fn recheck_waiting_for_space(
         }
 
         let total_to_drop: u32 = items.values().sum();
-        request_transfer_specific_items(worker_entity, target, items, &mut transfer_events);
+        request_transfer_specific_items(
+            worker_entity,
+            target,
+            fit_transfer(&items, input_ports.get(target).ok(), space),
+            &mut transfer_events,
+        );
 
         if space >= total_to_drop {
             commands.entity(worker_entity).remove::<WaitingForSpace>();
@@ -441,9 +1043,86 @@ pub fn recheck_waiting_for_space(
     }
 }
 
-pub fn cleanup_invalid_workflow_refs(
+/// Cancels dropoffs already headed for a crafter whose recipe just changed
+/// when nothing the worker would drop is used by the new recipe. The
+/// worker keeps its cargo and moves on to the next step instead of filling
+/// the input port with items the crafter will only evacuate again.
+pub fn cancel_obsolete_deliveries(
     mut commands: Commands,
-    mut workers: Query<(Entity, &mut WorkflowAssignment)>,
+    mut recipe_events: MessageReader<RecipeChangeEvent>,
+    crafters: Query<&RecipeCrafter>,
+    recipes: Res<RecipeRegistry>,
+    mut workers: Query<(Entity, &mut WorkflowAssignment, &Cargo, &mut WorkerPath), With<Worker>>,
+    workflows: Query<&Workflow>,
+) {
+    let changed: HashMap<Entity, &RecipeDef> = recipe_events
+        .read()
+        .filter_map(|event| {
+            let crafter = crafters.get(event.building_entity).ok()?;
+            if crafter.get_active_recipe() != Some(&event.recipe_name) {
+                return None;
+            }
+            let recipe = recipes.get_definition(&event.recipe_name)?;
+            Some((event.building_entity, recipe))
+        })
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    let mut in_flight: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (worker_entity, assignment, _, _) in &workers {
+        if let (Some(target), Some(WorkflowAction::Dropoff(_))) =
+            (assignment.resolved_target, &assignment.resolved_action)
+        {
+            if changed.contains_key(&target) {
+                in_flight.entry(target).or_default().push(worker_entity);
+            }
+        }
+    }
+
+    for (target, deliveries) in in_flight {
+        let recipe = changed[&target];
+        for worker_entity in deliveries {
+            let Ok((_, mut assignment, cargo, mut path)) = workers.get_mut(worker_entity) else {
+                continue;
+            };
+            let Some(WorkflowAction::Dropoff(filter)) = &assignment.resolved_action else {
+                continue;
+            };
+            let items = compute_dropoff_items(&cargo.get_all_items(), filter.as_ref());
+            if items.keys().any(|&item| recipe.uses_item(item)) {
+                continue;
+            }
+            let Ok(workflow) = workflows.get(assignment.workflow) else {
+                continue;
+            };
+
+            info!(worker = ?worker_entity, building = ?target, "cancelling obsolete delivery");
+            path.waypoints.clear();
+            path.current_target = None;
+            commands.entity(worker_entity).remove::<WaitingForSpace>();
+            assignment.resolved_target = None;
+            assignment.resolved_action = None;
+            assignment.current_step = workflow.next_step(assignment.current_step);
+        }
+    }
+}
+
+pub fn tick_request_cooldowns(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cooldowns: Query<(Entity, &mut RequestCooldown)>,
+) {
+    for (entity, mut cooldown) in &mut cooldowns {
+        if cooldown.timer.tick(time.delta()).is_finished() {
+            commands.entity(entity).remove::<RequestCooldown>();
+        }
+    }
+}
+
+pub fn cleanup_invalid_workflow_refs(
+    mut workers: Query<&mut WorkflowAssignment>,
     mut workflows: Query<&mut Workflow>,
     positions: Query<&Position>,
 ) {
@@ -451,13 +1130,26 @@ pub fn cleanup_invalid_workflow_refs(
         workflow
             .building_set
             .retain(|entity| positions.get(*entity).is_ok());
+        // A condition on a removed building could never hold again, so the
+        // step runs unconditionally instead.
+        let stale_condition = |step: &WorkflowStep| {
+            step.condition
+                .as_ref()
+                .is_some_and(|condition| positions.get(condition.building()).is_err())
+        };
+        if workflow.steps.iter().any(stale_condition) {
+            for step in &mut workflow.steps {
+                if stale_condition(step) {
+                    step.condition = None;
+                }
+            }
+        }
     }
 
-    for (worker_entity, mut assignment) in &mut workers {
+    for mut assignment in &mut workers {
+        // Assignments to missing workflows are dropped by
+        // `reconcile_workflow_assignments`.
         let Ok(workflow) = workflows.get(assignment.workflow) else {
-            commands
-                .entity(worker_entity)
-                .remove::<WorkflowAssignment>();
             continue;
         };
 
@@ -481,40 +1173,108 @@ pub fn cleanup_invalid_workflow_refs(
     }
 }
 
+pub fn emergency_dropoff_enabled(config: Res<LogisticsConfig>) -> bool {
+    config.emergency_dropoff
+}
+
+/// Unassigned workers with cargo try the storages nearest first, one request
+/// per frame. Unloading anything restarts the count; once the retry budget is
+/// spent without progress the worker holds its cargo until a network change,
+/// such as a newly built storage, gives it a fresh budget.
 pub fn emergency_dropoff_unassigned_workers(
-    workers: Query<(Entity, &Cargo, &Position), (With<Worker>, Without<WorkflowAssignment>)>,
-    storage_ports: Query<(Entity, &Position), With<StoragePort>>,
+    mut commands: Commands,
+    config: Res<LogisticsConfig>,
+    mut network_events: MessageReader<NetworkChangedEvent>,
+    mut workers: Query<
+        (
+            Entity,
+            &Cargo,
+            &Position,
+            Option<&mut EmergencyDropoffAttempts>,
+        ),
+        (
+            With<Worker>,
+            Without<WorkflowAssignment>,
+            Without<RelayLeg>,
+            Without<Errand>,
+        ),
+    >,
+    assigned: Query<Entity, (With<EmergencyDropoffAttempts>, With<WorkflowAssignment>)>,
+    storage_ports: Query<(Entity, &Position, &StoragePort)>,
     mut transfer_events: MessageWriter<ItemTransferRequestEvent>,
 ) {
-    for (worker_entity, cargo, worker_pos) in &workers {
+    for worker_entity in &assigned {
+        commands
+            .entity(worker_entity)
+            .remove::<EmergencyDropoffAttempts>();
+    }
+    let network_changed = network_events.read().count() > 0;
+
+    for (worker_entity, cargo, worker_pos, attempts) in &mut workers {
         if cargo.is_empty() {
+            if attempts.is_some() {
+                commands
+                    .entity(worker_entity)
+                    .remove::<EmergencyDropoffAttempts>();
+            }
             continue;
         }
 
-        let mut nearest: Option<(Entity, i32)> = None;
-
-        for (storage_entity, storage_pos) in &storage_ports {
-            let dist = (worker_pos.x - storage_pos.x).abs() + (worker_pos.y - storage_pos.y).abs();
-            match nearest {
-                Some((_, best_dist)) if dist < best_dist => {
-                    nearest = Some((storage_entity, dist));
-                }
-                None => {
-                    nearest = Some((storage_entity, dist));
+        let total = cargo.get_total_quantity();
+        let attempt = if let Some(mut attempts) = attempts {
+            if total < attempts.last_total || network_changed {
+                attempts.attempts = 0;
+                attempts.holding = false;
+            }
+            attempts.last_total = total;
+
+            if attempts.attempts >= config.emergency_dropoff_retries {
+                if !attempts.holding {
+                    warn!(
+                        worker = ?worker_entity,
+                        retries = attempts.attempts,
+                        "no storage accepts the worker's cargo, holding it"
+                    );
+                    attempts.holding = true;
                 }
-                _ => {}
+                continue;
             }
-        }
+            attempts.attempts += 1;
+            attempts.attempts - 1
+        } else {
+            commands
+                .entity(worker_entity)
+                .insert(EmergencyDropoffAttempts {
+                    attempts: 1,
+                    last_total: total,
+                    holding: false,
+                });
+            0
+        };
 
-        if let Some((storage_entity, _)) = nearest {
-            let items = cargo.get_all_items();
-            request_transfer_specific_items(
-                worker_entity,
-                storage_entity,
-                items,
-                &mut transfer_events,
-            );
+        let mut storages: Vec<(i32, Entity)> = storage_ports
+            .iter()
+            .map(|(storage_entity, storage_pos, _)| {
+                let dist =
+                    (worker_pos.x - storage_pos.x).abs() + (worker_pos.y - storage_pos.y).abs();
+                (dist, storage_entity)
+            })
+            .collect();
+        if storages.is_empty() {
+            continue;
         }
+        storages.sort_unstable();
+
+        let (_, storage_entity) = storages[attempt as usize % storages.len()];
+        let space = storage_ports.get(storage_entity).map_or(0, |(_, _, port)| {
+            port.capacity().saturating_sub(port.get_total_quantity())
+        });
+        request_transfer_specific_items(
+            worker_entity,
+            storage_entity,
+            fit_transfer(&cargo.get_all_items(), None, space),
+            &mut transfer_events,
+        );
     }
 }
 
@@ -522,8 +1282,13 @@ pub fn emergency_dropoff_unassigned_workers(
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
-    use crate::workers::workflows::components::WorkflowStep;
+    use crate::materials::{
+        execute_item_transfer, validate_item_transfer, ItemTransferEvent,
+        ItemTransferValidationEvent,
+    };
+    use crate::workers::workflows::components::{StepCondition, WorkflowStep};
     use bevy::ecs::system::RunSystemOnce;
+    use std::collections::VecDeque;
 
     #[test]
     fn get_available_items_empty_returns_empty() {
@@ -533,10 +1298,16 @@ mod tests {
         app.world_mut()
             .run_system_once(
                 move |output_ports: Query<&OutputPort>,
+                      named_output_ports: Query<&NamedOutputPorts>,
                       storage_ports: Query<&StoragePort>,
                       input_ports: Query<&InputPort>| {
-                    let result =
-                        get_available_items_at(target, &output_ports, &storage_ports, &input_ports);
+                    let result = get_available_items_at(
+                        target,
+                        &output_ports,
+                        &named_output_ports,
+                        &storage_ports,
+                        &input_ports,
+                    );
                     assert!(result.is_empty());
                 },
             )
@@ -546,74 +1317,99 @@ mod tests {
     #[test]
     fn compute_pickup_items_none_filter_returns_all() {
         let mut available = HashMap::new();
-        available.insert("iron_ore".to_string(), 10);
-        available.insert("copper_ore".to_string(), 5);
+        available.insert(ItemId::named("Iron Ore").unwrap(), 10);
+        available.insert(ItemId::named("Copper Ore").unwrap(), 5);
 
-        let result = compute_pickup_items(&available, None);
+        let result = compute_pickup_items(&available, None, None);
 
         assert_eq!(result.len(), 2);
-        assert_eq!(result.get("iron_ore"), Some(&10));
-        assert_eq!(result.get("copper_ore"), Some(&5));
+        assert_eq!(result.get(&ItemId::named("Iron Ore").unwrap()), Some(&10));
+        assert_eq!(result.get(&ItemId::named("Copper Ore").unwrap()), Some(&5));
     }
 
     #[test]
     fn compute_pickup_items_some_filter_caps_at_available() {
         let mut available = HashMap::new();
-        available.insert("iron_ore".to_string(), 3);
+        available.insert(ItemId::named("Iron Ore").unwrap(), 3);
 
         let mut filter = HashMap::new();
-        filter.insert("iron_ore".to_string(), 10);
+        filter.insert(ItemId::named("Iron Ore").unwrap(), 10);
 
-        let result = compute_pickup_items(&available, Some(&filter));
+        let result = compute_pickup_items(&available, Some(&filter), None);
 
-        assert_eq!(result.get("iron_ore"), Some(&3));
+        assert_eq!(result.get(&ItemId::named("Iron Ore").unwrap()), Some(&3));
     }
 
     #[test]
     fn compute_pickup_items_some_filter_missing_item_excluded() {
         let mut available = HashMap::new();
-        available.insert("iron_ore".to_string(), 5);
+        available.insert(ItemId::named("Iron Ore").unwrap(), 5);
 
         let mut filter = HashMap::new();
-        filter.insert("copper_ore".to_string(), 10);
+        filter.insert(ItemId::named("Copper Ore").unwrap(), 10);
 
-        let result = compute_pickup_items(&available, Some(&filter));
+        let result = compute_pickup_items(&available, Some(&filter), None);
 
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn compute_pickup_items_respects_per_item_cap() {
+        let mut available = HashMap::new();
+        available.insert(ItemId::named("Iron Ore").unwrap(), 50);
+        available.insert(ItemId::named("Coal").unwrap(), 3);
+
+        let mut filter = HashMap::new();
+        filter.insert(ItemId::named("Iron Ore").unwrap(), 20);
+        filter.insert(ItemId::named("Coal").unwrap(), 20);
+
+        let result = compute_pickup_items(&available, Some(&filter), Some(5));
+
+        assert_eq!(result.get(&ItemId::named("Iron Ore").unwrap()), Some(&5));
+        assert_eq!(result.get(&ItemId::named("Coal").unwrap()), Some(&3));
+
+        let unfiltered = compute_pickup_items(&available, None, Some(5));
+        assert_eq!(
+            unfiltered.get(&ItemId::named("Iron Ore").unwrap()),
+            Some(&5)
+        );
+    }
+
     #[test]
     fn compute_dropoff_items_none_filter_returns_all() {
         let mut cargo_items = HashMap::new();
-        cargo_items.insert("iron_plate".to_string(), 8);
-        cargo_items.insert("copper_plate".to_string(), 4);
+        cargo_items.insert(ItemId::named("Iron Plate").unwrap(), 8);
+        cargo_items.insert(ItemId::named("Copper Ingot").unwrap(), 4);
 
         let result = compute_dropoff_items(&cargo_items, None);
 
         assert_eq!(result.len(), 2);
-        assert_eq!(result.get("iron_plate"), Some(&8));
-        assert_eq!(result.get("copper_plate"), Some(&4));
+        assert_eq!(result.get(&ItemId::named("Iron Plate").unwrap()), Some(&8));
+        assert_eq!(
+            result.get(&ItemId::named("Copper Ingot").unwrap()),
+            Some(&4)
+        );
     }
 
     #[test]
     fn compute_dropoff_items_some_filter_caps_at_cargo() {
         let mut cargo_items = HashMap::new();
-        cargo_items.insert("iron_plate".to_string(), 3);
+        cargo_items.insert(ItemId::named("Iron Plate").unwrap(), 3);
 
         let mut filter = HashMap::new();
-        filter.insert("iron_plate".to_string(), 10);
+        filter.insert(ItemId::named("Iron Plate").unwrap(), 10);
 
         let result = compute_dropoff_items(&cargo_items, Some(&filter));
 
-        assert_eq!(result.get("iron_plate"), Some(&3));
+        assert_eq!(result.get(&ItemId::named("Iron Plate").unwrap()), Some(&3));
     }
 
     #[test]
     fn compute_dropoff_items_some_filter_empty_cargo_returns_empty() {
-        let cargo_items: HashMap<String, u32> = HashMap::new();
+        let cargo_items: HashMap<ItemId, u32> = HashMap::new();
 
         let mut filter = HashMap::new();
-        filter.insert("iron_plate".to_string(), 5);
+        filter.insert(ItemId::named("Iron Plate").unwrap(), 5);
 
         let result = compute_dropoff_items(&cargo_items, Some(&filter));
 
@@ -632,15 +1428,29 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::Specific(building),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
 
         app.world_mut()
-            .run_system_once(move |positions: Query<&Position>, names: Query<&Name>| {
-                let mut rr = HashMap::new();
-                let result =
-                    resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                assert_eq!(result, Some(building));
-            })
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    let result = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    assert_eq!(result, Some(building));
+                },
+            )
             .unwrap();
     }
 
@@ -655,15 +1465,29 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::Specific(building),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
 
         app.world_mut()
-            .run_system_once(move |positions: Query<&Position>, names: Query<&Name>| {
-                let mut rr = HashMap::new();
-                let result =
-                    resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                assert!(result.is_none());
-            })
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    let result = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    assert!(result.is_none());
+                },
+            )
             .unwrap();
     }
 
@@ -689,21 +1513,116 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::ByType("Smelter".to_string()),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
 
         app.world_mut()
-            .run_system_once(move |positions: Query<&Position>, names: Query<&Name>| {
-                let mut rr = HashMap::new();
-                let r1 = resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                let r2 = resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                let r3 = resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                let r4 = resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-
-                assert_eq!(r1, Some(smelter_a));
-                assert_eq!(r2, Some(smelter_b));
-                assert_eq!(r3, Some(smelter_c));
-                assert_eq!(r4, Some(smelter_a));
-            })
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    let r1 = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    let r2 = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    let r3 = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    let r4 = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+
+                    assert_eq!(r1, Some(smelter_a));
+                    assert_eq!(r2, Some(smelter_b));
+                    assert_eq!(r3, Some(smelter_c));
+                    assert_eq!(r4, Some(smelter_a));
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn resolve_step_target_by_type_follows_dispatch_weights() {
+        let mut app = App::new();
+        let heavy = app
+            .world_mut()
+            .spawn((
+                Position { x: 2, y: 0 },
+                Name::new("Storage"),
+                StoragePort::new(200),
+                DispatchWeight,
+            ))
+            .id();
+        let light = app
+            .world_mut()
+            .spawn((Position { x: 5, y: 0 }, Name::new("Storage")))
+            .id();
+        let building_set = HashSet::from([heavy, light]);
+        let step = WorkflowStep {
+            target: StepTarget::ByType("Storage".to_string()),
+            action: WorkflowAction::Pickup(None),
+            condition: None,
+        };
+
+        app.world_mut()
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    let picks: Vec<Option<Entity>> = (0..6)
+                        .map(|_| {
+                            resolve_step_target(
+                                &step,
+                                &building_set,
+                                &positions,
+                                &names,
+                                &priorities,
+                                &weights,
+                                &mut rr,
+                                0,
+                            )
+                        })
+                        .collect();
+
+                    let heavy_count = picks.iter().filter(|&&p| p == Some(heavy)).count();
+                    let light_count = picks.iter().filter(|&&p| p == Some(light)).count();
+                    assert_eq!((heavy_count, light_count), (4, 2));
+                },
+            )
             .unwrap();
     }
 
@@ -719,15 +1638,29 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::ByType("Smelter".to_string()),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
 
         app.world_mut()
-            .run_system_once(move |positions: Query<&Position>, names: Query<&Name>| {
-                let mut rr = HashMap::new();
-                let result =
-                    resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                assert!(result.is_none());
-            })
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    let result = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    assert!(result.is_none());
+                },
+            )
             .unwrap();
     }
 
@@ -748,24 +1681,154 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::ByType("Smelter".to_string()),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
 
         app.world_mut()
-            .run_system_once(move |positions: Query<&Position>, names: Query<&Name>| {
-                let mut rr = HashMap::new();
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+
+                    let r_step0 = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    let r_step1 = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        1,
+                    );
 
-                let r_step0 =
-                    resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                let r_step1 =
-                    resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 1);
+                    assert_eq!(r_step0, Some(smelter_a));
+                    assert_eq!(r_step1, Some(smelter_a));
+
+                    let r_step0_again = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    assert_eq!(r_step0_again, Some(smelter_b));
+                },
+            )
+            .unwrap();
+    }
 
-                assert_eq!(r_step0, Some(smelter_a));
-                assert_eq!(r_step1, Some(smelter_a));
+    #[test]
+    fn resolve_step_target_by_type_prefers_high_priority() {
+        let mut app = App::new();
+        let normal = app
+            .world_mut()
+            .spawn((Position { x: 2, y: 0 }, Name::new("Smelter")))
+            .id();
+        let key = app
+            .world_mut()
+            .spawn((
+                Position { x: 5, y: 0 },
+                Name::new("Smelter"),
+                LogisticsPriority::High,
+            ))
+            .id();
+        let mut building_set = HashSet::new();
+        building_set.insert(normal);
+        building_set.insert(key);
+        let step = WorkflowStep {
+            target: StepTarget::ByType("Smelter".to_string()),
+            action: WorkflowAction::Dropoff(None),
+            condition: None,
+        };
 
-                let r_step0_again =
-                    resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                assert_eq!(r_step0_again, Some(smelter_b));
-            })
+        app.world_mut()
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    assert_eq!(
+                        priorities.get(normal).copied().unwrap_or_default(),
+                        LogisticsPriority::Medium
+                    );
+                    let mut rr = HashMap::new();
+                    for _ in 0..3 {
+                        let result = resolve_step_target(
+                            &step,
+                            &building_set,
+                            &positions,
+                            &names,
+                            &priorities,
+                            &weights,
+                            &mut rr,
+                            0,
+                        );
+                        assert_eq!(result, Some(key));
+                    }
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn resolve_step_target_by_type_skips_low_priority() {
+        let mut app = App::new();
+        let low = app
+            .world_mut()
+            .spawn((
+                Position { x: 2, y: 0 },
+                Name::new("Storage"),
+                LogisticsPriority::Low,
+            ))
+            .id();
+        let default = app
+            .world_mut()
+            .spawn((Position { x: 5, y: 0 }, Name::new("Storage")))
+            .id();
+        let mut building_set = HashSet::new();
+        building_set.insert(low);
+        building_set.insert(default);
+        let step = WorkflowStep {
+            target: StepTarget::ByType("Storage".to_string()),
+            action: WorkflowAction::Dropoff(None),
+            condition: None,
+        };
+
+        app.world_mut()
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    let result = resolve_step_target(
+                        &step,
+                        &building_set,
+                        &positions,
+                        &names,
+                        &priorities,
+                        &weights,
+                        &mut rr,
+                        0,
+                    );
+                    assert_eq!(result, Some(default));
+                },
+            )
             .unwrap();
     }
 
@@ -781,17 +1844,31 @@ mod tests {
         let step = WorkflowStep {
             target: StepTarget::ByType("Smelter".to_string()),
             action: WorkflowAction::Pickup(None),
+            condition: None,
         };
 
         app.world_mut()
-            .run_system_once(move |positions: Query<&Position>, names: Query<&Name>| {
-                let mut rr = HashMap::new();
-                for _ in 0..5 {
-                    let result =
-                        resolve_step_target(&step, &building_set, &positions, &names, &mut rr, 0);
-                    assert_eq!(result, Some(smelter));
-                }
-            })
+            .run_system_once(
+                move |positions: Query<&Position>,
+                      names: Query<&Name>,
+                      priorities: Query<&LogisticsPriority>,
+                      weights: Query<&StoragePort, With<DispatchWeight>>| {
+                    let mut rr = HashMap::new();
+                    for _ in 0..5 {
+                        let result = resolve_step_target(
+                            &step,
+                            &building_set,
+                            &positions,
+                            &names,
+                            &priorities,
+                            &weights,
+                            &mut rr,
+                            0,
+                        );
+                        assert_eq!(result, Some(smelter));
+                    }
+                },
+            )
             .unwrap();
     }
 
@@ -814,7 +1891,7 @@ mod tests {
     fn get_available_space_input_port_with_space() {
         let mut app = App::new();
         let mut port = InputPort::new(10);
-        port.add_item("iron_ore", 3);
+        port.add_item(ItemId::named("Iron Ore").unwrap(), 3);
         let target = app.world_mut().spawn(port).id();
 
         app.world_mut()
@@ -831,7 +1908,7 @@ mod tests {
     fn get_available_space_full_input_port() {
         let mut app = App::new();
         let mut port = InputPort::new(5);
-        port.add_item("iron_ore", 5);
+        port.add_item(ItemId::named("Iron Ore").unwrap(), 5);
         let target = app.world_mut().spawn(port).id();
 
         app.world_mut()
@@ -848,7 +1925,7 @@ mod tests {
     fn get_available_space_storage_port_fallback() {
         let mut app = App::new();
         let mut port = StoragePort::new(20);
-        port.add_item("copper_plate", 8);
+        port.add_item(ItemId::named("Copper Ingot").unwrap(), 8);
         let target = app.world_mut().spawn(port).id();
 
         app.world_mut()
@@ -860,4 +1937,624 @@ mod tests {
             )
             .unwrap();
     }
+
+    #[test]
+    fn recipe_change_cancels_deliveries_of_unused_inputs() {
+        let mut app = App::new();
+        app.add_message::<RecipeChangeEvent>()
+            .insert_resource(
+                RecipeRegistry::from_ron(
+                    r#"[
+                        (
+                            name: "Copper Wire",
+                            inputs: {"Copper Ingot": 1},
+                            outputs: {"Copper Wire": 2},
+                            crafting_time: 1.0,
+                        ),
+                    ]"#,
+                )
+                .unwrap(),
+            )
+            .add_systems(Update, cancel_obsolete_deliveries);
+
+        let crafter = app
+            .world_mut()
+            .spawn(RecipeCrafter {
+                current_recipe: Some("Copper Wire".to_string()),
+                available_recipes: vec!["Gear".to_string(), "Copper Wire".to_string()],
+                timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                in_progress: None,
+            })
+            .id();
+        let workflow = app
+            .world_mut()
+            .spawn(Workflow {
+                name: "feed assembler".to_string(),
+                building_set: HashSet::from([crafter]),
+                steps: vec![
+                    WorkflowStep {
+                        target: StepTarget::ByType("Storage".to_string()),
+                        action: WorkflowAction::Pickup(None),
+                        condition: None,
+                    },
+                    WorkflowStep {
+                        target: StepTarget::Specific(crafter),
+                        action: WorkflowAction::Dropoff(None),
+                        condition: None,
+                    },
+                ],
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 2,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+
+        let spawn_courier = |app: &mut App, item: &str| {
+            let mut cargo = Cargo::new(10);
+            cargo.add_item(ItemId::named(item).unwrap(), 5);
+            app.world_mut()
+                .spawn((
+                    Worker,
+                    cargo,
+                    WorkflowAssignment {
+                        workflow,
+                        current_step: 1,
+                        resolved_target: Some(crafter),
+                        resolved_action: Some(WorkflowAction::Dropoff(None)),
+                    },
+                    WorkerPath {
+                        waypoints: VecDeque::from([Vec2::new(64.0, 0.0)]),
+                        current_target: Some(Vec2::new(32.0, 0.0)),
+                    },
+                ))
+                .id()
+        };
+        let obsolete = spawn_courier(&mut app, "Iron Plate");
+        let still_needed = spawn_courier(&mut app, "Copper Ingot");
+
+        app.world_mut().write_message(RecipeChangeEvent {
+            building_entity: crafter,
+            recipe_name: "Copper Wire".to_string(),
+        });
+        app.update();
+
+        let assignment = app.world().get::<WorkflowAssignment>(obsolete).unwrap();
+        assert_eq!(assignment.resolved_target, None);
+        assert_eq!(assignment.current_step, 0);
+        let path = app.world().get::<WorkerPath>(obsolete).unwrap();
+        assert!(path.current_target.is_none() && path.waypoints.is_empty());
+        assert_eq!(
+            app.world()
+                .get::<Cargo>(obsolete)
+                .unwrap()
+                .get_item_quantity(ItemId::named("Iron Plate").unwrap()),
+            5
+        );
+
+        let assignment = app.world().get::<WorkflowAssignment>(still_needed).unwrap();
+        assert_eq!(assignment.resolved_target, Some(crafter));
+        assert_eq!(assignment.current_step, 1);
+    }
+
+    #[test]
+    fn emergency_dropoff_holds_cargo_once_every_storage_rejects_it() {
+        let coal = ItemId::named("Coal").unwrap();
+        let mut app = App::new();
+        app.add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .add_message::<NetworkChangedEvent>()
+            .insert_resource(LogisticsConfig {
+                emergency_dropoff_retries: 3,
+                ..Default::default()
+            })
+            .add_systems(
+                Update,
+                (
+                    emergency_dropoff_unassigned_workers,
+                    validate_item_transfer,
+                    execute_item_transfer,
+                )
+                    .chain(),
+            );
+
+        for x in [2, 5] {
+            let mut storage = StoragePort::new(10);
+            storage.add_item(ItemId::named("Iron Plate").unwrap(), 10);
+            app.world_mut().spawn((storage, Position { x, y: 0 }));
+        }
+        let mut cargo = Cargo::new(10);
+        cargo.add_item(coal, 4);
+        let worker = app
+            .world_mut()
+            .spawn((Worker, cargo, Position { x: 0, y: 0 }))
+            .id();
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let attempts = app.world().get::<EmergencyDropoffAttempts>(worker).unwrap();
+        assert_eq!(attempts.attempts, 3);
+        assert!(attempts.holding);
+        assert_eq!(
+            app.world()
+                .get::<Cargo>(worker)
+                .unwrap()
+                .get_item_quantity(coal),
+            4
+        );
+
+        app.world_mut()
+            .spawn((StoragePort::new(10), Position { x: 9, y: 0 }));
+        app.world_mut().write_message(NetworkChangedEvent);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world().get::<Cargo>(worker).unwrap().is_empty());
+    }
+
+    #[test]
+    fn by_type_step_without_matches_waits_until_a_building_joins() {
+        let mut app = dispatch_app(
+            LogisticsConfig::default(),
+            NetworkConnectivity::default(),
+            ItemRegistry::from_ron("[]").unwrap(),
+            RecipeRegistry::from_ron("[]").unwrap(),
+        );
+        app.add_systems(
+            Update,
+            (process_workflow_workers, recheck_waiting_for_target).chain(),
+        );
+
+        let workflow = spawn_workflow(
+            app.world_mut(),
+            HashSet::new(),
+            vec![
+                WorkflowStep {
+                    target: StepTarget::ByType("Storage".to_string()),
+                    action: WorkflowAction::Pickup(None),
+                    condition: None,
+                },
+                WorkflowStep {
+                    target: StepTarget::ByType("Smelter".to_string()),
+                    action: WorkflowAction::Dropoff(None),
+                    condition: None,
+                },
+            ],
+        );
+        let worker = spawn_assigned_worker(app.world_mut(), workflow);
+
+        for _ in 0..5 {
+            app.update();
+        }
+        let waiting = app.world().get::<WaitingForTarget>(worker).unwrap();
+        assert_eq!(waiting.step, 0);
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.current_step, 0);
+        assert_eq!(assignment.resolved_target, None);
+
+        let storage = app
+            .world_mut()
+            .spawn((Name::new("Storage"), Position { x: 1, y: 0 }))
+            .id();
+        app.world_mut()
+            .get_mut::<Workflow>(workflow)
+            .unwrap()
+            .building_set
+            .insert(storage);
+        app.update();
+        assert!(app.world().get::<WaitingForTarget>(worker).is_none());
+
+        app.update();
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.resolved_target, Some(storage));
+    }
+
+    /// Resources `process_workflow_workers` reads; callers add the systems.
+    fn dispatch_app(
+        config: LogisticsConfig,
+        network: NetworkConnectivity,
+        items: ItemRegistry,
+        recipes: RecipeRegistry,
+    ) -> App {
+        let mut app = App::new();
+        app.add_message::<WorkerArrivedEvent>()
+            .insert_resource(config)
+            .init_resource::<PendingSequenceQueue>()
+            .insert_resource(network)
+            .init_resource::<PathfindingConfig>()
+            .insert_resource(Grid::new(64.0))
+            .insert_resource(items)
+            .insert_resource(recipes);
+        app
+    }
+
+    fn spawn_workflow(
+        world: &mut World,
+        building_set: HashSet<Entity>,
+        steps: Vec<WorkflowStep>,
+    ) -> Entity {
+        world
+            .spawn(Workflow {
+                name: "test workflow".to_string(),
+                building_set,
+                steps,
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id()
+    }
+
+    /// Idle at the origin on the workflow's first step.
+    fn spawn_assigned_worker(world: &mut World, workflow: Entity) -> Entity {
+        world
+            .spawn((
+                Worker,
+                Position { x: 0, y: 0 },
+                WorkerPath {
+                    waypoints: VecDeque::new(),
+                    current_target: None,
+                },
+                WorkflowAssignment {
+                    workflow,
+                    current_step: 0,
+                    resolved_target: None,
+                    resolved_action: None,
+                },
+            ))
+            .id()
+    }
+
+    /// Recipe of the assembler the first plate delivery is sent to.
+    fn first_assembler_served(prioritize_by_output_value: bool, motors_full: bool) -> String {
+        let plate = ItemId::named("Iron Plate").unwrap();
+        let items = ItemRegistry::from_ron(
+            r#"[
+                (name: "Gear", tier: 1, value: Some(4)),
+                (name: "Motor", tier: 2, value: Some(30)),
+            ]"#,
+        )
+        .unwrap();
+        let recipes = RecipeRegistry::from_ron(
+            r#"[
+                (name: "Gear", inputs: {"Iron Plate": 2}, outputs: {"Gear": 1}),
+                (name: "Motor", inputs: {"Iron Plate": 2}, outputs: {"Motor": 1}),
+            ]"#,
+        )
+        .unwrap();
+        let config = LogisticsConfig {
+            prioritize_by_output_value,
+            ..Default::default()
+        };
+        let mut app = dispatch_app(config, NetworkConnectivity::default(), items, recipes);
+        app.add_systems(Update, process_workflow_workers);
+
+        let mut spawn_assembler = |recipe: &str, x: i32, full: bool| {
+            let mut input_port = InputPort::new(10);
+            if full {
+                input_port.add_item(plate, 10);
+            }
+            let crafter = RecipeCrafter {
+                timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                current_recipe: Some(recipe.to_string()),
+                available_recipes: Vec::new(),
+                in_progress: None,
+            };
+            let position = Position { x, y: 0 };
+            app.world_mut()
+                .spawn((Name::new("Assembler"), position, input_port, crafter))
+                .id()
+        };
+        let gears = spawn_assembler("Gear", 1, false);
+        let motors = spawn_assembler("Motor", 2, motors_full);
+        let step = WorkflowStep {
+            target: StepTarget::ByType("Assembler".to_string()),
+            action: WorkflowAction::Dropoff(None),
+            condition: None,
+        };
+        let workflow = spawn_workflow(app.world_mut(), HashSet::from([gears, motors]), vec![step]);
+        let mut cargo = Cargo::new(10);
+        cargo.add_item(plate, 2);
+        let worker = spawn_assigned_worker(app.world_mut(), workflow);
+        app.world_mut().entity_mut(worker).insert(cargo);
+
+        app.update();
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        let served = assignment.resolved_target.unwrap();
+        let crafter = app.world().get::<RecipeCrafter>(served).unwrap();
+        crafter.current_recipe.clone().unwrap()
+    }
+
+    #[test]
+    fn scarce_input_goes_to_the_crafter_with_the_most_valuable_output() {
+        assert_eq!(first_assembler_served(true, false), "Motor");
+        // Without the option, round robin starts at the nearer gear assembler.
+        assert_eq!(first_assembler_served(false, false), "Gear");
+    }
+
+    #[test]
+    fn a_full_valuable_crafter_passes_its_turn_to_the_next_one() {
+        assert_eq!(first_assembler_served(true, true), "Gear");
+    }
+
+    #[test]
+    fn conditional_pickup_is_skipped_until_the_crafter_runs_low() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut network = NetworkConnectivity::default();
+        for x in 0..=3 {
+            network.add_connected_cell(x, 0);
+            network.add_core_network_cell(x, 0);
+        }
+        let mut app = dispatch_app(
+            LogisticsConfig::default(),
+            network,
+            ItemRegistry::from_ron("[]").unwrap(),
+            RecipeRegistry::from_ron("[]").unwrap(),
+        );
+        app.add_systems(Update, process_workflow_workers);
+
+        let storage = app
+            .world_mut()
+            .spawn((StoragePort::new(100), Position { x: 1, y: 0 }))
+            .id();
+        let mut input_port = InputPort::new(30);
+        input_port.add_item(ore, 15);
+        let crafter = app
+            .world_mut()
+            .spawn((input_port, Position { x: 3, y: 0 }))
+            .id();
+        let workflow = spawn_workflow(
+            app.world_mut(),
+            HashSet::from([storage, crafter]),
+            vec![
+                WorkflowStep {
+                    target: StepTarget::Specific(storage),
+                    action: WorkflowAction::Pickup(None),
+                    condition: Some(StepCondition::ItemBelow {
+                        building: crafter,
+                        item: ore,
+                        threshold: 10,
+                    }),
+                },
+                WorkflowStep {
+                    target: StepTarget::Specific(crafter),
+                    action: WorkflowAction::Dropoff(None),
+                    condition: None,
+                },
+            ],
+        );
+        let worker = spawn_assigned_worker(app.world_mut(), workflow);
+
+        // The crafter is still stocked, so the pickup is skipped along with
+        // the dropoff that would deliver it, leaving the worker on the pickup.
+        app.update();
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.current_step, 0);
+        assert_eq!(assignment.resolved_target, None);
+
+        app.world_mut()
+            .get_mut::<InputPort>(crafter)
+            .unwrap()
+            .remove_item(ore, 10);
+        app.update();
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.current_step, 0);
+        assert_eq!(assignment.resolved_target, Some(storage));
+    }
+
+    #[test]
+    fn conditions_on_removed_buildings_are_dropped() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut world = World::new();
+        let storage = world.spawn(Position { x: 1, y: 0 }).id();
+        let crafter = world.spawn(Position { x: 3, y: 0 }).id();
+        let workflow = world
+            .spawn(Workflow {
+                name: "feed when low".to_string(),
+                building_set: HashSet::from([storage, crafter]),
+                steps: vec![WorkflowStep {
+                    target: StepTarget::Specific(storage),
+                    action: WorkflowAction::Pickup(None),
+                    condition: Some(StepCondition::ItemBelow {
+                        building: crafter,
+                        item: ore,
+                        threshold: 10,
+                    }),
+                }],
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+
+        world
+            .run_system_once(cleanup_invalid_workflow_refs)
+            .unwrap();
+        assert!(world.get::<Workflow>(workflow).unwrap().steps[0]
+            .condition
+            .is_some());
+
+        world.despawn(crafter);
+        world
+            .run_system_once(cleanup_invalid_workflow_refs)
+            .unwrap();
+        assert_eq!(
+            world.get::<Workflow>(workflow).unwrap().steps[0].condition,
+            None
+        );
+    }
+
+    #[test]
+    fn restock_pickup_tops_the_crafter_up_to_its_buffer_target() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = App::new();
+        app.add_message::<WorkerArrivedEvent>()
+            .add_message::<ItemTransferRequestEvent>()
+            .add_message::<ItemTransferValidationEvent>()
+            .add_message::<ItemTransferEvent>()
+            .init_resource::<LogisticsConfig>()
+            .add_systems(
+                Update,
+                (
+                    handle_workflow_arrivals,
+                    validate_item_transfer,
+                    execute_item_transfer,
+                )
+                    .chain(),
+            );
+
+        let mut storage = StoragePort::new(100);
+        storage.add_item(ore, 50);
+        let storage = app.world_mut().spawn(storage).id();
+        let mut input_port = InputPort::new(30);
+        input_port.item_limits.insert(ore, 20);
+        input_port.add_item(ore, 8);
+        let crafter = app.world_mut().spawn(input_port).id();
+        let workflow = app
+            .world_mut()
+            .spawn(Workflow {
+                name: "restock smelter".to_string(),
+                building_set: HashSet::from([storage, crafter]),
+                steps: vec![
+                    WorkflowStep {
+                        target: StepTarget::Specific(storage),
+                        action: WorkflowAction::Pickup(None),
+                        condition: None,
+                    },
+                    WorkflowStep {
+                        target: StepTarget::Specific(crafter),
+                        action: WorkflowAction::Dropoff(None),
+                        condition: None,
+                    },
+                ],
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+        let worker = app
+            .world_mut()
+            .spawn((
+                Worker,
+                Cargo::new(50),
+                WorkflowAssignment {
+                    workflow,
+                    current_step: 0,
+                    resolved_target: Some(storage),
+                    resolved_action: Some(WorkflowAction::Pickup(None)),
+                },
+            ))
+            .id();
+
+        app.world_mut().write_message(WorkerArrivedEvent {
+            worker,
+            position: (0, 0),
+        });
+        app.update();
+
+        let carried = app
+            .world()
+            .get::<Cargo>(worker)
+            .unwrap()
+            .get_item_quantity(ore);
+        assert_eq!(carried, 12, "only the shortfall to the buffer target");
+        assert_eq!(
+            app.world()
+                .get::<StoragePort>(storage)
+                .unwrap()
+                .get_item_quantity(ore),
+            38
+        );
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.current_step, 1);
+    }
+
+    #[test]
+    fn empty_pickups_count_waits_on_the_step_until_one_succeeds() {
+        let ore = ItemId::named("Iron Ore").unwrap();
+        let mut app = App::new();
+        app.add_message::<WorkerArrivedEvent>()
+            .add_message::<ItemTransferRequestEvent>()
+            .init_resource::<LogisticsConfig>()
+            .add_systems(Update, handle_workflow_arrivals);
+
+        let storage = app.world_mut().spawn(StoragePort::new(100)).id();
+        let crafter = app.world_mut().spawn(InputPort::new(30)).id();
+        let workflow = app
+            .world_mut()
+            .spawn(Workflow {
+                name: "feed smelter".to_string(),
+                building_set: HashSet::from([storage, crafter]),
+                steps: vec![
+                    WorkflowStep {
+                        target: StepTarget::Specific(storage),
+                        action: WorkflowAction::Pickup(None),
+                        condition: None,
+                    },
+                    WorkflowStep {
+                        target: StepTarget::Specific(crafter),
+                        action: WorkflowAction::Dropoff(None),
+                        condition: None,
+                    },
+                ],
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+        let worker = app
+            .world_mut()
+            .spawn((
+                Worker,
+                Cargo::new(50),
+                WorkflowAssignment {
+                    workflow,
+                    current_step: 0,
+                    resolved_target: Some(storage),
+                    resolved_action: Some(WorkflowAction::Pickup(None)),
+                },
+            ))
+            .id();
+        let arrive = |app: &mut App| {
+            app.world_mut().write_message(WorkerArrivedEvent {
+                worker,
+                position: (0, 0),
+            });
+            app.update();
+        };
+        let waits = |app: &App| {
+            app.world()
+                .get::<Workflow>(workflow)
+                .unwrap()
+                .step_waits
+                .get(&0)
+                .copied()
+        };
+
+        for expected in 1..=3 {
+            arrive(&mut app);
+            assert_eq!(waits(&app), Some(expected));
+        }
+
+        app.world_mut()
+            .get_mut::<StoragePort>(storage)
+            .unwrap()
+            .add_item(ore, 5);
+        arrive(&mut app);
+        assert_eq!(waits(&app), None, "a successful pickup resets the step");
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.current_step, 1);
+    }
 }
@@ -0,0 +1,180 @@
+use super::components::{
+    DedicatedWorker, WaitingForItems, WaitingForSpace, WaitingForTarget, Workflow,
+    WorkflowAssignment,
+};
+use crate::workers::Worker;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// What a worker's assignment components should look like given the
+/// workflows that exist right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repair {
+    /// Assigned to a workflow that no longer exists.
+    DropAssignment,
+    /// Waiting markers left behind without an assignment.
+    DropWaiting,
+    /// Dedicated marker without a dedicated workflow behind it.
+    DropDedicated,
+    /// Assigned to a dedicated workflow but missing the marker.
+    MarkDedicated,
+}
+
+fn needed_repair(
+    assignment: Option<&WorkflowAssignment>,
+    workflows: &Query<&Workflow>,
+    is_dedicated_worker: bool,
+    is_waiting: bool,
+) -> Option<Repair> {
+    let Some(assignment) = assignment else {
+        if is_dedicated_worker {
+            return Some(Repair::DropDedicated);
+        }
+        return is_waiting.then_some(Repair::DropWaiting);
+    };
+    let Ok(workflow) = workflows.get(assignment.workflow) else {
+        return Some(Repair::DropAssignment);
+    };
+    match (workflow.is_dedicated, is_dedicated_worker) {
+        (true, false) => Some(Repair::MarkDedicated),
+        (false, true) => Some(Repair::DropDedicated),
+        _ => None,
+    }
+}
+
+/// Keeps the worker and workflow sides of an assignment in agreement:
+/// workers never point at a missing workflow, the dedicated marker matches
+/// the workflow's flag, and waiting markers only exist on assigned workers.
+///
+/// In debug builds, a worker that still needs repair the frame after it was
+/// repaired means some system keeps re-breaking it, and panics.
+pub fn reconcile_workflow_assignments(
+    mut commands: Commands,
+    workers: Query<
+        (
+            Entity,
+            Option<&WorkflowAssignment>,
+            Has<DedicatedWorker>,
+            Has<WaitingForItems>,
+            Has<WaitingForSpace>,
+            Has<WaitingForTarget>,
+        ),
+        With<Worker>,
+    >,
+    workflows: Query<&Workflow>,
+    mut repaired_last_frame: Local<HashSet<Entity>>,
+) {
+    let mut repaired = HashSet::new();
+    for (worker, assignment, is_dedicated, waiting_items, waiting_space, waiting_target) in &workers
+    {
+        let Some(repair) = needed_repair(
+            assignment,
+            &workflows,
+            is_dedicated,
+            waiting_items || waiting_space || waiting_target,
+        ) else {
+            continue;
+        };
+
+        debug_assert!(
+            !repaired_last_frame.contains(&worker),
+            "worker {worker:?} is still out of sync with its workflow after repair ({repair:?})"
+        );
+        warn!(?worker, ?repair, "repairing workflow assignment desync");
+        repaired.insert(worker);
+
+        let mut entity = commands.entity(worker);
+        match repair {
+            Repair::DropAssignment => {
+                entity
+                    .remove::<WorkflowAssignment>()
+                    .remove::<DedicatedWorker>()
+                    .remove::<WaitingForItems>()
+                    .remove::<WaitingForSpace>()
+                    .remove::<WaitingForTarget>();
+            }
+            Repair::DropWaiting => {
+                entity
+                    .remove::<WaitingForItems>()
+                    .remove::<WaitingForSpace>()
+                    .remove::<WaitingForTarget>();
+            }
+            Repair::DropDedicated => {
+                entity.remove::<DedicatedWorker>();
+            }
+            Repair::MarkDedicated => {
+                entity.insert(DedicatedWorker);
+            }
+        }
+    }
+    *repaired_last_frame = repaired;
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn workflow(is_dedicated: bool) -> Workflow {
+        Workflow {
+            name: "Test".to_string(),
+            building_set: HashSet::new(),
+            steps: Vec::new(),
+            is_paused: false,
+            is_dedicated,
+            desired_worker_count: 1,
+            round_robin_counters: std::collections::HashMap::new(),
+            step_waits: std::collections::HashMap::new(),
+        }
+    }
+
+    fn assigned_to(workflow: Entity) -> WorkflowAssignment {
+        WorkflowAssignment {
+            workflow,
+            current_step: 0,
+            resolved_target: None,
+            resolved_action: None,
+        }
+    }
+
+    #[test]
+    fn desynced_assignments_are_reconciled_on_the_next_update() {
+        let mut app = App::new();
+        app.add_systems(Update, reconcile_workflow_assignments);
+
+        let world = app.world_mut();
+        let dedicated = world.spawn(workflow(true)).id();
+        let shared = world.spawn(workflow(false)).id();
+        let gone = world.spawn_empty().id();
+        world.despawn(gone);
+
+        let orphaned = world
+            .spawn((
+                Worker,
+                assigned_to(gone),
+                DedicatedWorker,
+                WaitingForItems::default(),
+            ))
+            .id();
+        let unmarked = world.spawn((Worker, assigned_to(dedicated))).id();
+        let stale_marker = world
+            .spawn((Worker, assigned_to(shared), DedicatedWorker))
+            .id();
+        let stale_wait = world.spawn((Worker, WaitingForSpace::default())).id();
+        let healthy = world.spawn((Worker, assigned_to(shared))).id();
+
+        app.update();
+
+        let world = app.world();
+        assert!(!world.entity(orphaned).contains::<WorkflowAssignment>());
+        assert!(!world.entity(orphaned).contains::<DedicatedWorker>());
+        assert!(!world.entity(orphaned).contains::<WaitingForItems>());
+        assert!(world.entity(unmarked).contains::<DedicatedWorker>());
+        assert!(!world.entity(stale_marker).contains::<DedicatedWorker>());
+        assert!(!world.entity(stale_wait).contains::<WaitingForSpace>());
+        assert!(world.entity(healthy).contains::<WorkflowAssignment>());
+
+        // Repaired workers stay repaired, so the debug invariant holds.
+        app.update();
+    }
+}
@@ -1,13 +1,20 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
 
-use crate::{grid::Position, workers::Worker};
+use crate::{
+    grid::Position,
+    structures::LogisticsPriority,
+    workers::{Worker, WorkerPath},
+};
 
 use super::components::{
-    AssignWorkersEvent, BatchAssignWorkersEvent, CreateWorkflowEvent, DeleteWorkflowEvent,
-    PauseWorkflowEvent, UnassignWorkersEvent, UpdateWorkflowEvent, WaitingForItems,
-    WaitingForSpace, Workflow, WorkflowAssignment, WorkflowRegistry,
+    AssignWorkersEvent, AutoStaffConfig, BatchAssignWorkersEvent, CreateWorkflowEvent,
+    DedicateWorkflowEvent, DedicatedWorker, DeleteWorkflowEvent, Errand, ManuallyUnassigned,
+    PauseWorkflowEvent, PendingSequenceQueue, RelayHandoff, RelayLeg, UnassignWorkersEvent,
+    UpdateWorkflowEvent, WaitingForItems, WaitingForSpace, WaitingForTarget, Workflow,
+    WorkflowAssignment, WorkflowRegistry,
 };
+use super::execution::workflow_priority;
 
 pub fn handle_create_workflow(
     mut commands: Commands,
@@ -21,32 +28,49 @@ pub fn handle_create_workflow(
                 building_set: event.building_set.clone(),
                 steps: event.steps.clone(),
                 is_paused: false,
+                is_dedicated: event.is_dedicated,
                 desired_worker_count: event.desired_worker_count,
                 round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
             })
             .id();
         registry.workflows.push(entity);
     }
 }
 
+/// Deleting a workflow also ends whatever its workers were doing for it: the
+/// trip in progress is dropped and queued dispatch requests are forgotten, so
+/// the workers come back idle where they stand.
 pub fn handle_delete_workflow(
     mut commands: Commands,
     mut events: MessageReader<DeleteWorkflowEvent>,
     mut registry: ResMut<WorkflowRegistry>,
-    assignments: Query<(Entity, &WorkflowAssignment)>,
+    mut pending: ResMut<PendingSequenceQueue>,
+    mut assignments: Query<(Entity, &WorkflowAssignment, Option<&mut WorkerPath>)>,
 ) {
     for event in events.read() {
-        commands.entity(event.workflow).despawn();
+        if let Ok(mut workflow) = commands.get_entity(event.workflow) {
+            workflow.despawn();
+        }
         registry.workflows.retain(|&e| e != event.workflow);
 
-        for (worker_entity, assignment) in &assignments {
-            if assignment.workflow == event.workflow {
-                commands
-                    .entity(worker_entity)
-                    .remove::<WorkflowAssignment>()
-                    .remove::<WaitingForItems>()
-                    .remove::<WaitingForSpace>();
+        for (worker_entity, assignment, path) in &mut assignments {
+            if assignment.workflow != event.workflow {
+                continue;
+            }
+            if let Some(mut path) = path {
+                path.waypoints.clear();
+                path.current_target = None;
             }
+            pending.requests.retain(|&entity| entity != worker_entity);
+            commands
+                .entity(worker_entity)
+                .remove::<WorkflowAssignment>()
+                .remove::<DedicatedWorker>()
+                .remove::<WaitingForItems>()
+                .remove::<WaitingForSpace>()
+                .remove::<WaitingForTarget>()
+                .remove::<RelayHandoff>();
         }
     }
 }
@@ -62,22 +86,142 @@ pub fn handle_pause_workflow(
     }
 }
 
+/// Toggles whether a workflow keeps its workers, marking or releasing every
+/// worker currently assigned to it.
+pub fn handle_dedicate_workflow(
+    mut commands: Commands,
+    mut events: MessageReader<DedicateWorkflowEvent>,
+    mut workflows: Query<&mut Workflow>,
+    assignments: Query<(Entity, &WorkflowAssignment)>,
+) {
+    for event in events.read() {
+        let Ok(mut workflow) = workflows.get_mut(event.workflow) else {
+            continue;
+        };
+        workflow.is_dedicated = !workflow.is_dedicated;
+
+        for (worker_entity, assignment) in &assignments {
+            if assignment.workflow != event.workflow {
+                continue;
+            }
+            if workflow.is_dedicated {
+                commands.entity(worker_entity).insert(DedicatedWorker);
+            } else {
+                commands.entity(worker_entity).remove::<DedicatedWorker>();
+            }
+        }
+    }
+}
+
+fn assign_worker(commands: &mut Commands, worker: Entity, workflow: Entity, dedicated: bool) {
+    let mut entity = commands.entity(worker);
+    entity.insert(WorkflowAssignment {
+        workflow,
+        current_step: 0,
+        resolved_target: None,
+        resolved_action: None,
+    });
+    entity.remove::<ManuallyUnassigned>();
+    if dedicated {
+        entity.insert(DedicatedWorker);
+    } else {
+        entity.remove::<DedicatedWorker>();
+    }
+}
+
 pub fn handle_assign_workers(
     mut commands: Commands,
     mut events: MessageReader<AssignWorkersEvent>,
+    workflows: Query<&Workflow>,
+    dedicated_workers: Query<&WorkflowAssignment, With<DedicatedWorker>>,
 ) {
     for event in events.read() {
+        let dedicated = workflows
+            .get(event.workflow)
+            .is_ok_and(|workflow| workflow.is_dedicated);
         for &worker in &event.workers {
-            commands.entity(worker).insert(WorkflowAssignment {
-                workflow: event.workflow,
-                current_step: 0,
-                resolved_target: None,
-                resolved_action: None,
-            });
+            if dedicated_workers
+                .get(worker)
+                .is_ok_and(|assignment| assignment.workflow != event.workflow)
+            {
+                continue;
+            }
+            assign_worker(&mut commands, worker, event.workflow, dedicated);
         }
     }
 }
 
+/// Hands every idle worker to the open workflow with the highest dispatch
+/// priority, breaking ties by the larger shortfall. Paused and stepless
+/// workflows are skipped, and no workflow is staffed past its desired count.
+/// Workers the player unassigned stay idle.
+pub fn auto_staff_idle_workers(
+    config: Res<AutoStaffConfig>,
+    idle_workers: Query<
+        Entity,
+        (
+            With<Worker>,
+            Without<WorkflowAssignment>,
+            Without<RelayLeg>,
+            Without<Errand>,
+            Without<ManuallyUnassigned>,
+        ),
+    >,
+    assigned_workers: Query<&WorkflowAssignment, With<Worker>>,
+    workflows: Query<(Entity, &Workflow)>,
+    names: Query<&Name>,
+    priorities: Query<&LogisticsPriority>,
+    mut assign_events: MessageWriter<AssignWorkersEvent>,
+) {
+    if !config.enabled {
+        return;
+    }
+    let mut idle: Vec<Entity> = idle_workers.iter().collect();
+    if idle.is_empty() {
+        return;
+    }
+    idle.sort_unstable();
+
+    let mut current: HashMap<Entity, u32> = HashMap::new();
+    for assignment in &assigned_workers {
+        *current.entry(assignment.workflow).or_default() += 1;
+    }
+    let mut open: Vec<(Entity, LogisticsPriority, u32)> = workflows
+        .iter()
+        .filter(|(_, workflow)| !workflow.is_paused && !workflow.steps.is_empty())
+        .filter_map(|(entity, workflow)| {
+            let assigned = current.get(&entity).copied().unwrap_or(0);
+            let shortfall = workflow.desired_worker_count.saturating_sub(assigned);
+            let priority = workflow_priority(workflow, &names, &priorities);
+            (shortfall > 0).then_some((entity, priority, shortfall))
+        })
+        .collect();
+
+    let mut assignments: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for worker in idle {
+        open.sort_by_key(|&(entity, priority, shortfall)| {
+            (
+                std::cmp::Reverse(priority),
+                std::cmp::Reverse(shortfall),
+                entity,
+            )
+        });
+        let Some((workflow, _, shortfall)) = open.first_mut() else {
+            break;
+        };
+        assignments.entry(*workflow).or_default().push(worker);
+        *shortfall -= 1;
+        open.retain(|&(_, _, shortfall)| shortfall > 0);
+    }
+
+    let mut assignments: Vec<(Entity, Vec<Entity>)> = assignments.into_iter().collect();
+    assignments.sort_unstable_by_key(|(workflow, _)| *workflow);
+    for (workflow, workers) in assignments {
+        info!(?workflow, count = workers.len(), "auto-staffing workflow");
+        assign_events.write(AssignWorkersEvent { workflow, workers });
+    }
+}
+
 pub fn handle_unassign_workers(
     mut commands: Commands,
     mut events: MessageReader<UnassignWorkersEvent>,
@@ -87,8 +231,12 @@ pub fn handle_unassign_workers(
             commands
                 .entity(worker)
                 .remove::<WorkflowAssignment>()
+                .remove::<DedicatedWorker>()
                 .remove::<WaitingForItems>()
-                .remove::<WaitingForSpace>();
+                .remove::<WaitingForSpace>()
+                .remove::<WaitingForTarget>()
+                .remove::<RelayHandoff>()
+                .insert(ManuallyUnassigned);
         }
     }
 }
@@ -96,7 +244,15 @@ pub fn handle_unassign_workers(
 pub fn handle_batch_assign_workers(
     mut events: MessageReader<BatchAssignWorkersEvent>,
     workflows: Query<&Workflow>,
-    idle_workers: Query<(Entity, &Position), (With<Worker>, Without<WorkflowAssignment>)>,
+    idle_workers: Query<
+        (Entity, &Position),
+        (
+            With<Worker>,
+            Without<WorkflowAssignment>,
+            Without<RelayLeg>,
+            Without<Errand>,
+        ),
+    >,
     assigned_workers: Query<&WorkflowAssignment, With<Worker>>,
     positions: Query<&Position>,
     mut commands: Commands,
@@ -153,12 +309,12 @@ pub fn handle_batch_assign_workers(
         candidates.sort_by_key(|&(_, dist)| dist);
 
         for (worker_entity, _) in candidates.into_iter().take(needed) {
-            commands.entity(worker_entity).insert(WorkflowAssignment {
-                workflow: event.workflow,
-                current_step: 0,
-                resolved_target: None,
-                resolved_action: None,
-            });
+            assign_worker(
+                &mut commands,
+                worker_entity,
+                event.workflow,
+                workflow.is_dedicated,
+            );
         }
     }
 }
@@ -174,6 +330,7 @@ pub fn handle_update_workflow(
             workflow.steps.clone_from(&event.steps);
             workflow.desired_worker_count = event.desired_worker_count;
             workflow.round_robin_counters.clear();
+            workflow.step_waits.clear();
         }
     }
 }
@@ -191,16 +348,19 @@ mod tests {
         app.add_message::<CreateWorkflowEvent>();
         app.add_message::<DeleteWorkflowEvent>();
         app.add_message::<PauseWorkflowEvent>();
+        app.add_message::<DedicateWorkflowEvent>();
         app.add_message::<AssignWorkersEvent>();
         app.add_message::<UnassignWorkersEvent>();
         app.add_message::<BatchAssignWorkersEvent>();
         app.init_resource::<WorkflowRegistry>();
+        app.init_resource::<PendingSequenceQueue>();
         app.add_systems(
             Update,
             (
                 handle_create_workflow,
                 handle_delete_workflow,
                 handle_pause_workflow,
+                handle_dedicate_workflow,
                 handle_assign_workers,
                 handle_unassign_workers,
                 handle_batch_assign_workers,
@@ -219,7 +379,9 @@ mod tests {
             steps: vec![WorkflowStep {
                 target: StepTarget::Specific(Entity::PLACEHOLDER),
                 action: WorkflowAction::Pickup(None),
+                condition: None,
             }],
+            is_dedicated: false,
             desired_worker_count: 2,
         });
         app.update();
@@ -243,6 +405,7 @@ mod tests {
             name: "to delete".to_string(),
             building_set: HashSet::new(),
             steps: vec![],
+            is_dedicated: false,
             desired_worker_count: 1,
         });
         app.update();
@@ -267,6 +430,7 @@ mod tests {
             name: "worker workflow".to_string(),
             building_set: HashSet::new(),
             steps: vec![],
+            is_dedicated: false,
             desired_worker_count: 1,
         });
         app.update();
@@ -296,6 +460,196 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn deleting_a_staffed_workflow_leaves_its_workers_idle() {
+        let mut app = setup_app();
+        let workflow = app.world_mut().spawn(Workflow {
+            name: "staffed".to_string(),
+            building_set: HashSet::new(),
+            steps: vec![],
+            is_paused: false,
+            is_dedicated: true,
+            desired_worker_count: 2,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        });
+        let workflow = workflow.id();
+        app.world_mut()
+            .resource_mut::<WorkflowRegistry>()
+            .workflows
+            .push(workflow);
+
+        let assigned = || WorkflowAssignment {
+            workflow,
+            current_step: 1,
+            resolved_target: Some(Entity::PLACEHOLDER),
+            resolved_action: None,
+        };
+        let travelling = app
+            .world_mut()
+            .spawn((
+                Worker,
+                assigned(),
+                DedicatedWorker,
+                WorkerPath {
+                    waypoints: [Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0)].into(),
+                    current_target: Some(Vec2::ZERO),
+                },
+            ))
+            .id();
+        let waiting = app
+            .world_mut()
+            .spawn((
+                Worker,
+                assigned(),
+                DedicatedWorker,
+                WaitingForItems::new(1.0),
+                WaitingForSpace::default(),
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<PendingSequenceQueue>()
+            .requests
+            .push(travelling);
+
+        app.world_mut()
+            .write_message(DeleteWorkflowEvent { workflow });
+        app.update();
+
+        assert!(app
+            .world()
+            .resource::<WorkflowRegistry>()
+            .workflows
+            .is_empty());
+        assert!(app
+            .world()
+            .resource::<PendingSequenceQueue>()
+            .requests
+            .is_empty());
+        for worker in [travelling, waiting] {
+            let entity = app.world().entity(worker);
+            assert!(!entity.contains::<WorkflowAssignment>());
+            assert!(!entity.contains::<DedicatedWorker>());
+            assert!(!entity.contains::<WaitingForItems>());
+            assert!(!entity.contains::<WaitingForSpace>());
+        }
+        let path = app.world().get::<WorkerPath>(travelling).unwrap();
+        assert!(path.waypoints.is_empty());
+        assert!(path.current_target.is_none());
+    }
+
+    #[test]
+    fn auto_staff_fills_understaffed_workflows_by_priority() {
+        let mut app = App::new();
+        app.add_message::<AssignWorkersEvent>()
+            .insert_resource(AutoStaffConfig { enabled: true })
+            .add_systems(
+                Update,
+                (auto_staff_idle_workers, handle_assign_workers).chain(),
+            );
+
+        let urgent_building = app.world_mut().spawn(LogisticsPriority::High).id();
+        let mut spawn_workflow = |target: Entity, desired_worker_count: u32| {
+            app.world_mut()
+                .spawn(Workflow {
+                    name: "auto".to_string(),
+                    building_set: HashSet::from([target]),
+                    steps: vec![WorkflowStep {
+                        target: StepTarget::Specific(target),
+                        action: WorkflowAction::Pickup(None),
+                        condition: None,
+                    }],
+                    is_paused: false,
+                    is_dedicated: false,
+                    desired_worker_count,
+                    round_robin_counters: HashMap::new(),
+                    step_waits: HashMap::new(),
+                })
+                .id()
+        };
+        let routine = spawn_workflow(Entity::PLACEHOLDER, 1);
+        let urgent = spawn_workflow(urgent_building, 1);
+        let full = spawn_workflow(Entity::PLACEHOLDER, 1);
+        app.world_mut().spawn((
+            Worker,
+            WorkflowAssignment {
+                workflow: full,
+                current_step: 0,
+                resolved_target: None,
+                resolved_action: None,
+            },
+        ));
+
+        let first = app.world_mut().spawn(Worker).id();
+        app.update();
+        let assigned_to = |app: &App, worker: Entity| {
+            app.world()
+                .get::<WorkflowAssignment>(worker)
+                .map(|assignment| assignment.workflow)
+        };
+        assert_eq!(assigned_to(&app, first), Some(urgent));
+
+        let second = app.world_mut().spawn(Worker).id();
+        let third = app.world_mut().spawn(Worker).id();
+        app.update();
+        let mut later = [assigned_to(&app, second), assigned_to(&app, third)];
+        later.sort();
+        assert_eq!(later, [None, Some(routine)]);
+    }
+
+    #[test]
+    fn auto_staff_leaves_manually_unassigned_workers_idle() {
+        let mut app = App::new();
+        app.add_message::<AssignWorkersEvent>()
+            .add_message::<UnassignWorkersEvent>()
+            .insert_resource(AutoStaffConfig { enabled: true })
+            .add_systems(
+                Update,
+                (
+                    handle_unassign_workers,
+                    auto_staff_idle_workers,
+                    handle_assign_workers,
+                )
+                    .chain(),
+            );
+
+        let workflow = app
+            .world_mut()
+            .spawn(Workflow {
+                name: "auto".to_string(),
+                building_set: HashSet::new(),
+                steps: vec![WorkflowStep {
+                    target: StepTarget::ByType("Storage".to_string()),
+                    action: WorkflowAction::Pickup(None),
+                    condition: None,
+                }],
+                is_paused: false,
+                is_dedicated: false,
+                desired_worker_count: 1,
+                round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
+            })
+            .id();
+        let worker = app.world_mut().spawn(Worker).id();
+        app.update();
+        assert!(app.world().get::<WorkflowAssignment>(worker).is_some());
+
+        app.world_mut().write_message(UnassignWorkersEvent {
+            workers: vec![worker],
+        });
+        app.update();
+        app.update();
+        assert!(app.world().get::<WorkflowAssignment>(worker).is_none());
+
+        app.world_mut().write_message(AssignWorkersEvent {
+            workflow,
+            workers: vec![worker],
+        });
+        app.update();
+        assert!(app.world().get::<ManuallyUnassigned>(worker).is_none());
+        assert!(app.world().get::<WorkflowAssignment>(worker).is_some());
+    }
+
     #[test]
     fn pause_workflow_toggles() {
         let mut app = setup_app();
@@ -304,6 +658,7 @@ mod tests {
             name: "pausable".to_string(),
             building_set: HashSet::new(),
             steps: vec![],
+            is_dedicated: false,
             desired_worker_count: 1,
         });
         app.update();
@@ -341,8 +696,10 @@ mod tests {
                 building_set: HashSet::new(),
                 steps: vec![],
                 is_paused: false,
+                is_dedicated: false,
                 desired_worker_count: 2,
                 round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
             })
             .id();
 
@@ -375,8 +732,10 @@ mod tests {
                 building_set: HashSet::new(),
                 steps: vec![],
                 is_paused: false,
+                is_dedicated: false,
                 desired_worker_count: 1,
                 round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
             })
             .id();
 
@@ -414,8 +773,10 @@ mod tests {
                 building_set,
                 steps: vec![],
                 is_paused: false,
+                is_dedicated: false,
                 desired_worker_count: 2,
                 round_robin_counters: HashMap::new(),
+                step_waits: HashMap::new(),
             })
             .id();
 
@@ -437,4 +798,63 @@ mod tests {
         assert!(app.world().get::<WorkflowAssignment>(near_worker).is_some());
         assert!(app.world().get::<WorkflowAssignment>(far_worker).is_none());
     }
+
+    #[test]
+    fn dedicated_workers_are_not_reassigned() {
+        let mut app = setup_app();
+
+        let building = app.world_mut().spawn(Position { x: 0, y: 0 }).id();
+        let spawn_workflow = |app: &mut App, name: &str| {
+            app.world_mut()
+                .spawn(Workflow {
+                    name: name.to_string(),
+                    building_set: HashSet::from([building]),
+                    steps: vec![],
+                    is_paused: false,
+                    is_dedicated: false,
+                    desired_worker_count: 1,
+                    round_robin_counters: HashMap::new(),
+                    step_waits: HashMap::new(),
+                })
+                .id()
+        };
+        let dedicated = spawn_workflow(&mut app, "dedicated");
+        let other = spawn_workflow(&mut app, "other");
+        let worker = app
+            .world_mut()
+            .spawn((Worker, Position { x: 1, y: 1 }))
+            .id();
+
+        app.world_mut().write_message(AssignWorkersEvent {
+            workflow: dedicated,
+            workers: vec![worker],
+        });
+        app.update();
+        app.world_mut().write_message(DedicateWorkflowEvent {
+            workflow: dedicated,
+        });
+        app.update();
+        assert!(app.world().get::<DedicatedWorker>(worker).is_some());
+
+        app.world_mut().write_message(AssignWorkersEvent {
+            workflow: other,
+            workers: vec![worker],
+        });
+        app.world_mut().write_message(BatchAssignWorkersEvent {
+            workflow: other,
+            count: 1,
+        });
+        app.update();
+
+        let assignment = app.world().get::<WorkflowAssignment>(worker).unwrap();
+        assert_eq!(assignment.workflow, dedicated);
+
+        app.world_mut().write_message(UnassignWorkersEvent {
+            workers: vec![worker],
+        });
+        app.update();
+
+        assert!(app.world().get::<WorkflowAssignment>(worker).is_none());
+        assert!(app.world().get::<DedicatedWorker>(worker).is_none());
+    }
 }
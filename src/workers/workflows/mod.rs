@@ -1,10 +1,20 @@
 pub mod components;
+pub mod errands;
 pub mod execution;
+pub mod invariants;
 pub mod management;
+pub mod relay;
+pub mod templates;
+pub mod throughput;
 
 pub use components::*;
+pub use errands::*;
 pub use execution::*;
+pub use invariants::*;
 pub use management::*;
+pub use relay::*;
+pub use templates::*;
+pub use throughput::*;
 
 use crate::workers::WorkersSystemSet;
 use bevy::prelude::*;
@@ -25,11 +35,21 @@ impl Plugin for WorkflowsPlugin {
         app.add_message::<CreateWorkflowEvent>()
             .add_message::<DeleteWorkflowEvent>()
             .add_message::<PauseWorkflowEvent>()
+            .add_message::<DedicateWorkflowEvent>()
             .add_message::<AssignWorkersEvent>()
             .add_message::<UnassignWorkersEvent>()
             .add_message::<BatchAssignWorkersEvent>()
             .add_message::<UpdateWorkflowEvent>()
+            .add_message::<SaveWorkflowTemplateEvent>()
+            .add_message::<DeliveryRequest>()
             .init_resource::<WorkflowRegistry>()
+            .init_resource::<WorkflowTemplateLibrary>()
+            .init_resource::<LogisticsConfig>()
+            .init_resource::<WorkflowConfig>()
+            .init_resource::<AutoStaffConfig>()
+            .init_resource::<PendingSequenceQueue>()
+            .init_resource::<PendingDeliveries>()
+            .init_resource::<WorkflowThroughput>()
             .configure_sets(
                 Update,
                 (
@@ -42,6 +62,7 @@ impl Plugin for WorkflowsPlugin {
                     .chain()
                     .in_set(WorkersSystemSet::TaskManagement),
             )
+            .add_systems(Startup, load_workflow_templates)
             .add_systems(
                 Update,
                 (
@@ -49,19 +70,47 @@ impl Plugin for WorkflowsPlugin {
                         handle_create_workflow,
                         handle_delete_workflow,
                         handle_pause_workflow,
+                        handle_dedicate_workflow,
                         handle_assign_workers,
                         handle_unassign_workers,
                         handle_batch_assign_workers,
                         handle_update_workflow,
+                        handle_save_workflow_template,
+                        cancel_obsolete_deliveries,
+                        auto_staff_idle_workers.before(handle_assign_workers),
                     )
                         .in_set(WorkflowSystemSet::Management),
-                    process_workflow_workers.in_set(WorkflowSystemSet::Processing),
-                    handle_workflow_arrivals.in_set(WorkflowSystemSet::Arrivals),
-                    (recheck_waiting_workers, recheck_waiting_for_space)
+                    (
+                        process_workflow_workers,
+                        split_long_deliveries,
+                        dispatch_delivery_requests,
+                    )
+                        .chain()
+                        .in_set(WorkflowSystemSet::Processing),
+                    (
+                        handle_workflow_arrivals,
+                        complete_relay_handoffs,
+                        finish_relay_deliveries,
+                        handle_errand_arrivals,
+                    )
+                        .chain()
+                        .in_set(WorkflowSystemSet::Arrivals),
+                    (
+                        recheck_waiting_workers,
+                        recheck_waiting_for_space,
+                        recheck_waiting_for_target,
+                        tick_request_cooldowns,
+                    )
                         .in_set(WorkflowSystemSet::Waiting),
                     (
-                        cleanup_invalid_workflow_refs,
-                        emergency_dropoff_unassigned_workers,
+                        (
+                            cleanup_invalid_workflow_refs,
+                            reconcile_workflow_assignments,
+                        )
+                            .chain(),
+                        release_reassigned_errands,
+                        emergency_dropoff_unassigned_workers.run_if(emergency_dropoff_enabled),
+                        record_workflow_deliveries,
                     )
                         .in_set(WorkflowSystemSet::Cleanup),
                 ),
@@ -0,0 +1,258 @@
+use super::components::{
+    Errand, LogisticsConfig, RelayHandoff, RelayLeg, RequestCooldown, Workflow, WorkflowAction,
+    WorkflowAssignment,
+};
+use super::execution::get_available_space_at;
+use crate::{
+    grid::{Grid, Position},
+    materials::{
+        cap_transfer_total, fit_transfer, request_transfer_specific_items, Cargo, InputPort,
+        InventoryAccess, ItemTransferRequestEvent, StoragePort,
+    },
+    systems::NetworkConnectivity,
+    workers::{
+        pathfinding::{calculate_path, manhattan_distance_coords},
+        Worker, WorkerArrivedEvent, WorkerPath,
+    },
+};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+fn set_path(path: &mut WorkerPath, mut waypoints: VecDeque<Vec2>) {
+    path.current_target = waypoints.pop_front();
+    path.waypoints = waypoints;
+}
+
+/// Splits loaded workers' long dropoff trips at their midpoint. The idle
+/// worker nearest that cell meets the carrier there, provided it can get
+/// there no later than the carrier would.
+pub fn split_long_deliveries(
+    mut commands: Commands,
+    config: Res<LogisticsConfig>,
+    mut carriers: Query<
+        (
+            Entity,
+            &WorkflowAssignment,
+            &Position,
+            &Cargo,
+            &mut WorkerPath,
+        ),
+        (With<Worker>, Without<RelayHandoff>),
+    >,
+    mut idle_workers: Query<
+        (Entity, &Position, &Cargo, &mut WorkerPath),
+        (
+            With<Worker>,
+            Without<WorkflowAssignment>,
+            Without<RelayLeg>,
+            Without<Errand>,
+        ),
+    >,
+    network: Res<NetworkConnectivity>,
+    grid: Res<Grid>,
+) {
+    // Two cells at least, so both legs of a split trip cover ground.
+    let Some(min_path) = config.relay_min_path.map(|len| len.max(2)) else {
+        return;
+    };
+
+    for (carrier, assignment, carrier_pos, cargo, mut path) in &mut carriers {
+        let (Some(target), Some(WorkflowAction::Dropoff(_))) =
+            (assignment.resolved_target, &assignment.resolved_action)
+        else {
+            continue;
+        };
+        let Some(current) = path.current_target else {
+            continue;
+        };
+        let remaining = path.waypoints.len() + 1;
+        if cargo.is_empty() || remaining < min_path {
+            continue;
+        }
+
+        // The carrier keeps the first half of its remaining cells.
+        let kept = remaining / 2;
+        let mid_point = if kept == 1 {
+            current
+        } else {
+            path.waypoints[kept - 2]
+        };
+        let Some(mid) = grid.world_to_grid_coordinates(mid_point) else {
+            continue;
+        };
+        let cell = (mid.grid_x, mid.grid_y);
+        let carrier_distance = manhattan_distance_coords((carrier_pos.x, carrier_pos.y), cell);
+
+        let Some((partner, partner_path)) = idle_workers
+            .iter()
+            .filter(|(_, _, cargo, path)| cargo.is_empty() && path.current_target.is_none())
+            .map(|(entity, pos, _, _)| {
+                (manhattan_distance_coords((pos.x, pos.y), cell), entity, pos)
+            })
+            .filter(|&(distance, _, _)| distance <= carrier_distance)
+            .min_by_key(|&(distance, entity, _)| (distance, entity))
+            .and_then(|(_, entity, pos)| {
+                calculate_path((pos.x, pos.y), cell, &network, &grid)
+                    .map(|waypoints| (entity, waypoints))
+            })
+        else {
+            continue;
+        };
+
+        path.waypoints.truncate(kept - 1);
+        if let Ok((_, _, _, mut leg_path)) = idle_workers.get_mut(partner) {
+            set_path(&mut leg_path, partner_path);
+        }
+        commands
+            .entity(carrier)
+            .insert(RelayHandoff { partner, cell });
+        commands.entity(partner).insert(RelayLeg {
+            carrier,
+            cell,
+            target,
+            delivering: false,
+        });
+        debug!(?carrier, ?partner, ?cell, "split delivery for relay");
+    }
+}
+
+/// Once both relay workers stand at the handoff cell, moves the carrier's
+/// cargo over, sends the partner on to the target and lets the carrier
+/// continue its workflow. A relay whose partner or carrier dropped out is
+/// called off and the carrier finishes the trip itself.
+pub fn complete_relay_handoffs(
+    mut commands: Commands,
+    mut carriers: Query<
+        (
+            Entity,
+            &RelayHandoff,
+            &mut WorkflowAssignment,
+            &Position,
+            &Cargo,
+            &mut WorkerPath,
+        ),
+        With<Worker>,
+    >,
+    mut partners: Query<
+        (Entity, &mut RelayLeg, &Position, &Cargo, &mut WorkerPath),
+        (With<Worker>, Without<WorkflowAssignment>),
+    >,
+    stale_legs: Query<(Entity, &RelayLeg), With<WorkflowAssignment>>,
+    workflows: Query<&Workflow>,
+    positions: Query<&Position, Without<Worker>>,
+    network: Res<NetworkConnectivity>,
+    grid: Res<Grid>,
+    mut transfer_events: MessageWriter<ItemTransferRequestEvent>,
+) {
+    for (carrier, handoff, mut assignment, carrier_pos, cargo, mut path) in &mut carriers {
+        let Ok((partner, mut leg, partner_pos, partner_cargo, mut partner_path)) =
+            partners.get_mut(handoff.partner)
+        else {
+            commands.entity(carrier).remove::<RelayHandoff>();
+            let target_pos = assignment
+                .resolved_target
+                .and_then(|target| positions.get(target).ok());
+            let resumed = target_pos.and_then(|target_pos| {
+                calculate_path(
+                    (carrier_pos.x, carrier_pos.y),
+                    (target_pos.x, target_pos.y),
+                    &network,
+                    &grid,
+                )
+            });
+            if let Some(waypoints) = resumed {
+                set_path(&mut path, waypoints);
+            } else {
+                assignment.resolved_target = None;
+                assignment.resolved_action = None;
+            }
+            continue;
+        };
+
+        let at_cell = |pos: &Position| (pos.x, pos.y) == handoff.cell;
+        if leg.delivering
+            || path.current_target.is_some()
+            || partner_path.current_target.is_some()
+            || !at_cell(carrier_pos)
+            || !at_cell(partner_pos)
+        {
+            continue;
+        }
+
+        let Ok(target_pos) = positions.get(leg.target) else {
+            continue;
+        };
+        let Some(waypoints) =
+            calculate_path(handoff.cell, (target_pos.x, target_pos.y), &network, &grid)
+        else {
+            continue;
+        };
+
+        let partner_space = partner_cargo
+            .capacity()
+            .saturating_sub(partner_cargo.get_total_quantity());
+        request_transfer_specific_items(
+            carrier,
+            partner,
+            cap_transfer_total(&cargo.get_all_items(), partner_space),
+            &mut transfer_events,
+        );
+        leg.delivering = true;
+        set_path(&mut partner_path, waypoints);
+        commands.entity(carrier).remove::<RelayHandoff>();
+
+        assignment.resolved_target = None;
+        assignment.resolved_action = None;
+        if let Ok(workflow) = workflows.get(assignment.workflow) {
+            assignment.current_step = workflow.next_step(assignment.current_step);
+        }
+        debug!(?carrier, ?partner, cell = ?handoff.cell, "relay handoff");
+    }
+
+    for (partner, leg, ..) in &partners {
+        let carrier_waiting = carriers
+            .get(leg.carrier)
+            .is_ok_and(|(_, handoff, ..)| handoff.partner == partner);
+        if !leg.delivering && !carrier_waiting {
+            commands.entity(partner).remove::<RelayLeg>();
+        }
+    }
+    for (worker, _) in &stale_legs {
+        commands.entity(worker).remove::<RelayLeg>();
+    }
+}
+
+/// Drops a relay partner's cargo at the target once it arrives. Whatever the
+/// target cannot take is left to the usual emergency dropoff.
+pub fn finish_relay_deliveries(
+    mut commands: Commands,
+    mut events: MessageReader<WorkerArrivedEvent>,
+    partners: Query<(&RelayLeg, &Cargo), With<Worker>>,
+    ports: (Query<&InputPort>, Query<&StoragePort>),
+    mut transfer_events: MessageWriter<ItemTransferRequestEvent>,
+) {
+    let (input_ports, storage_ports) = ports;
+    for event in events.read() {
+        let Ok((leg, cargo)) = partners.get(event.worker) else {
+            continue;
+        };
+        if !leg.delivering {
+            continue;
+        }
+        let space = get_available_space_at(leg.target, &input_ports, &storage_ports);
+        request_transfer_specific_items(
+            event.worker,
+            leg.target,
+            fit_transfer(
+                &cargo.get_all_items(),
+                input_ports.get(leg.target).ok(),
+                space,
+            ),
+            &mut transfer_events,
+        );
+        if let Ok(mut building) = commands.get_entity(leg.target) {
+            building.remove::<RequestCooldown>();
+        }
+        commands.entity(event.worker).remove::<RelayLeg>();
+    }
+}
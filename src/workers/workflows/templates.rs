@@ -0,0 +1,431 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::materials::ItemId;
+use crate::workers::workflows::components::{
+    StepCondition, StepTarget, Workflow, WorkflowAction, WorkflowStep,
+};
+
+pub const WORKFLOW_TEMPLATES_PATH: &str = "workflow_templates.ron";
+
+/// Filter items are saved by name so templates stay readable; a template
+/// naming an item the registry doesn't know fails to load.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TemplateAction {
+    Pickup(Option<HashMap<ItemId, u32>>),
+    Dropoff(Option<HashMap<ItemId, u32>>),
+}
+
+/// A step condition as saved. Entities don't outlive the session, so the
+/// watched building is kept by type and bound again once a pool is chosen.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TemplateCondition {
+    ItemBelow {
+        building_type: String,
+        item: ItemId,
+        threshold: u32,
+    },
+}
+
+impl TemplateCondition {
+    fn from_step(
+        condition: &StepCondition,
+        type_of: impl Fn(Entity) -> Option<String>,
+    ) -> Option<Self> {
+        match *condition {
+            StepCondition::ItemBelow {
+                building,
+                item,
+                threshold,
+            } => Some(Self::ItemBelow {
+                building_type: type_of(building)?,
+                item,
+                threshold,
+            }),
+        }
+    }
+
+    /// Binds the condition to the pool's only building of the saved type.
+    /// With none or several candidates it stays unbound, leaving the step
+    /// unconditional rather than guessing which building was meant.
+    #[must_use]
+    pub fn bind(
+        &self,
+        pool: &HashSet<Entity>,
+        type_of: impl Fn(Entity) -> Option<String>,
+    ) -> Option<StepCondition> {
+        match self {
+            Self::ItemBelow {
+                building_type,
+                item,
+                threshold,
+            } => {
+                let mut candidates = pool
+                    .iter()
+                    .copied()
+                    .filter(|&entity| type_of(entity).as_ref() == Some(building_type));
+                let building = candidates.next()?;
+                if candidates.next().is_some() {
+                    return None;
+                }
+                Some(StepCondition::ItemBelow {
+                    building,
+                    item: *item,
+                    threshold: *threshold,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TemplateStep {
+    pub building_type: String,
+    pub action: TemplateAction,
+    pub condition: Option<TemplateCondition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowTemplate {
+    pub name: String,
+    pub steps: Vec<TemplateStep>,
+    /// Defaults to shared for templates saved before dedication existed.
+    #[serde(default)]
+    pub is_dedicated: bool,
+    pub desired_worker_count: u32,
+}
+
+impl WorkflowTemplate {
+    /// Builds a template from a workflow. Specific targets are converted to
+    /// by-type targets using the building name; steps whose target has no
+    /// resolvable name are dropped, as are conditions on such buildings.
+    #[must_use]
+    pub fn from_workflow(workflow: &Workflow, type_of: impl Fn(Entity) -> Option<String>) -> Self {
+        let steps = workflow
+            .steps
+            .iter()
+            .filter_map(|step| {
+                let building_type = match &step.target {
+                    StepTarget::ByType(type_name) => type_name.clone(),
+                    StepTarget::Specific(entity) => type_of(*entity)?,
+                };
+                let action = match &step.action {
+                    WorkflowAction::Pickup(filter) => TemplateAction::Pickup(filter.clone()),
+                    WorkflowAction::Dropoff(filter) => TemplateAction::Dropoff(filter.clone()),
+                };
+                let condition = step
+                    .condition
+                    .as_ref()
+                    .and_then(|condition| TemplateCondition::from_step(condition, &type_of));
+                Some(TemplateStep {
+                    building_type,
+                    action,
+                    condition,
+                })
+            })
+            .collect();
+
+        Self {
+            name: workflow.name.clone(),
+            steps,
+            is_dedicated: workflow.is_dedicated,
+            desired_worker_count: workflow.desired_worker_count,
+        }
+    }
+
+    /// Conditions are left off; see `TemplateCondition::bind`.
+    #[must_use]
+    pub fn to_steps(&self) -> Vec<WorkflowStep> {
+        self.steps
+            .iter()
+            .map(|step| WorkflowStep {
+                target: StepTarget::ByType(step.building_type.clone()),
+                action: match &step.action {
+                    TemplateAction::Pickup(filter) => WorkflowAction::Pickup(filter.clone()),
+                    TemplateAction::Dropoff(filter) => WorkflowAction::Dropoff(filter.clone()),
+                },
+                condition: None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct WorkflowTemplateLibrary {
+    pub templates: Vec<WorkflowTemplate>,
+}
+
+impl WorkflowTemplateLibrary {
+    /// # Errors
+    /// Returns an error if the RON content fails to parse.
+    pub fn from_ron(ron_content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let templates: Vec<WorkflowTemplate> = ron::from_str(ron_content)?;
+        Ok(Self { templates })
+    }
+
+    /// # Errors
+    /// Returns an error if the templates fail to serialize.
+    pub fn to_ron(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(ron::ser::to_string_pretty(
+            &self.templates,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// Load templates from disk. A missing file yields an empty library.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::from_ron(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// # Errors
+    /// Returns an error if serialization or the file write fails.
+    pub fn save_to_file(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Inserts the template, replacing any existing template with the same name.
+    pub fn upsert(&mut self, template: WorkflowTemplate) {
+        if let Some(existing) = self.templates.iter_mut().find(|t| t.name == template.name) {
+            *existing = template;
+        } else {
+            self.templates.push(template);
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&WorkflowTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+}
+
+#[derive(Message)]
+pub struct SaveWorkflowTemplateEvent {
+    pub workflow: Entity,
+}
+
+pub fn load_workflow_templates(mut commands: Commands) {
+    match WorkflowTemplateLibrary::load_from_file(WORKFLOW_TEMPLATES_PATH) {
+        Ok(library) => commands.insert_resource(library),
+        Err(e) => {
+            error!("failed to load workflow templates: {e}");
+            commands.insert_resource(WorkflowTemplateLibrary::default());
+        }
+    }
+}
+
+pub fn handle_save_workflow_template(
+    mut events: MessageReader<SaveWorkflowTemplateEvent>,
+    workflows: Query<&Workflow>,
+    names: Query<&Name>,
+    mut library: ResMut<WorkflowTemplateLibrary>,
+) {
+    let mut changed = false;
+    for event in events.read() {
+        let Ok(workflow) = workflows.get(event.workflow) else {
+            continue;
+        };
+        let template = WorkflowTemplate::from_workflow(workflow, |entity| {
+            names.get(entity).ok().map(|n| n.as_str().to_string())
+        });
+        info!(name = %template.name, steps = template.steps.len(), "saved workflow template");
+        library.upsert(template);
+        changed = true;
+    }
+
+    if changed {
+        if let Err(e) = library.save_to_file(WORKFLOW_TEMPLATES_PATH) {
+            error!("failed to write workflow templates: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn make_workflow(steps: Vec<WorkflowStep>) -> Workflow {
+        Workflow {
+            name: "Ore Run".to_string(),
+            building_set: HashSet::new(),
+            steps,
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 3,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_specific_targets_converted_to_type() {
+        let workflow = make_workflow(vec![WorkflowStep {
+            target: StepTarget::Specific(Entity::PLACEHOLDER),
+            action: WorkflowAction::Pickup(None),
+            condition: None,
+        }]);
+
+        let template = WorkflowTemplate::from_workflow(&workflow, |_| Some("Smelter".to_string()));
+        assert_eq!(template.steps[0].building_type, "Smelter");
+    }
+
+    #[test]
+    fn dedicated_workflows_save_as_dedicated_templates() {
+        let mut workflow = make_workflow(Vec::new());
+        workflow.is_dedicated = true;
+
+        let mut library = WorkflowTemplateLibrary::default();
+        library.upsert(WorkflowTemplate::from_workflow(&workflow, |_| None));
+        let saved = WorkflowTemplateLibrary::from_ron(&library.to_ron().unwrap()).unwrap();
+
+        assert!(saved.get("Ore Run").unwrap().is_dedicated);
+    }
+
+    #[test]
+    fn test_unnamed_specific_targets_dropped() {
+        let workflow = make_workflow(vec![
+            WorkflowStep {
+                target: StepTarget::Specific(Entity::PLACEHOLDER),
+                action: WorkflowAction::Pickup(None),
+                condition: None,
+            },
+            WorkflowStep {
+                target: StepTarget::ByType("Storage".to_string()),
+                action: WorkflowAction::Dropoff(None),
+                condition: None,
+            },
+        ]);
+
+        let template = WorkflowTemplate::from_workflow(&workflow, |_| None);
+        assert_eq!(template.steps.len(), 1);
+        assert_eq!(template.steps[0].building_type, "Storage");
+    }
+
+    #[test]
+    fn test_library_ron_roundtrip() {
+        let mut filter = HashMap::new();
+        filter.insert(ItemId::named("Iron Ore").unwrap(), 5);
+        let mut library = WorkflowTemplateLibrary::default();
+        library.upsert(WorkflowTemplate {
+            name: "Ore Run".to_string(),
+            steps: vec![TemplateStep {
+                building_type: "Mining Drill".to_string(),
+                action: TemplateAction::Pickup(Some(filter)),
+                condition: None,
+            }],
+            is_dedicated: true,
+            desired_worker_count: 2,
+        });
+
+        let parsed = WorkflowTemplateLibrary::from_ron(&library.to_ron().unwrap()).unwrap();
+        assert_eq!(parsed.templates, library.templates);
+    }
+
+    #[test]
+    fn step_conditions_are_saved_by_building_type_and_bound_on_load() {
+        let mut world = World::new();
+        let crafter = world.spawn(Name::new("Assembler")).id();
+        let storage = world.spawn(Name::new("Storage")).id();
+        let type_of = |entity: Entity| {
+            world
+                .get::<Name>(entity)
+                .map(|name| name.as_str().to_string())
+        };
+        let iron = ItemId::named("Iron Plate").unwrap();
+        let condition = StepCondition::ItemBelow {
+            building: crafter,
+            item: iron,
+            threshold: 25,
+        };
+        let workflow = make_workflow(vec![
+            WorkflowStep {
+                target: StepTarget::Specific(storage),
+                action: WorkflowAction::Pickup(None),
+                condition: Some(condition.clone()),
+            },
+            WorkflowStep {
+                target: StepTarget::Specific(crafter),
+                action: WorkflowAction::Dropoff(None),
+                condition: None,
+            },
+        ]);
+
+        let mut library = WorkflowTemplateLibrary::default();
+        library.upsert(WorkflowTemplate::from_workflow(&workflow, type_of));
+        let saved = WorkflowTemplateLibrary::from_ron(&library.to_ron().unwrap()).unwrap();
+        let saved_condition = saved.get("Ore Run").unwrap().steps[0]
+            .condition
+            .clone()
+            .unwrap();
+        assert_eq!(
+            saved_condition,
+            TemplateCondition::ItemBelow {
+                building_type: "Assembler".to_string(),
+                item: iron,
+                threshold: 25,
+            }
+        );
+
+        let pool = HashSet::from([crafter, storage]);
+        assert_eq!(saved_condition.bind(&pool, type_of), Some(condition));
+        let second = world.spawn(Name::new("Assembler")).id();
+        let type_of = |entity: Entity| {
+            world
+                .get::<Name>(entity)
+                .map(|name| name.as_str().to_string())
+        };
+        let crowded = HashSet::from([crafter, second, storage]);
+        assert_eq!(saved_condition.bind(&crowded, type_of), None);
+    }
+
+    #[test]
+    fn templates_saved_before_conditions_still_load() {
+        let ron = r#"[(
+            name: "Ore Run",
+            steps: [(building_type: "Mining Drill", action: Pickup(None))],
+            desired_worker_count: 1,
+        )]"#;
+        let library = WorkflowTemplateLibrary::from_ron(ron).unwrap();
+        let template = library.get("Ore Run").unwrap();
+        assert_eq!(template.steps[0].condition, None);
+        assert!(!template.is_dedicated);
+    }
+
+    #[test]
+    fn templates_naming_unknown_items_fail_to_load() {
+        let ron = r#"[(
+            name: "Ore Run",
+            steps: [(building_type: "Mining Drill", action: Pickup(Some({"Unobtanium": 5})))],
+            desired_worker_count: 1,
+        )]"#;
+        assert!(WorkflowTemplateLibrary::from_ron(ron).is_err());
+    }
+
+    #[test]
+    fn test_upsert_replaces_same_name() {
+        let mut library = WorkflowTemplateLibrary::default();
+        let template = WorkflowTemplate {
+            name: "A".to_string(),
+            steps: Vec::new(),
+            is_dedicated: false,
+            desired_worker_count: 1,
+        };
+        library.upsert(template.clone());
+        library.upsert(WorkflowTemplate {
+            desired_worker_count: 4,
+            ..template
+        });
+
+        assert_eq!(library.templates.len(), 1);
+        assert_eq!(library.get("A").unwrap().desired_worker_count, 4);
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+use super::components::WorkflowAssignment;
+use crate::{materials::ItemTransferEvent, workers::Worker};
+
+/// Deliveries older than this no longer count towards a workflow's rate.
+pub const WORKFLOW_RATE_WINDOW_SECS: f32 = 60.0;
+
+/// Items each workflow's workers have delivered into buildings, stamped with
+/// the elapsed time of the delivery.
+#[derive(Resource, Debug, Default)]
+pub struct WorkflowThroughput {
+    deliveries: HashMap<Entity, VecDeque<(f32, u32)>>,
+}
+
+impl WorkflowThroughput {
+    pub fn record(&mut self, workflow: Entity, now: f32, quantity: u32) {
+        self.deliveries
+            .entry(workflow)
+            .or_default()
+            .push_back((now, quantity));
+    }
+
+    /// Drops deliveries that have left the window, and workflows left with
+    /// none, so deleted workflows age out on their own.
+    pub fn prune(&mut self, now: f32) {
+        self.deliveries.retain(|_, samples| {
+            while samples
+                .front()
+                .is_some_and(|&(at, _)| now - at >= WORKFLOW_RATE_WINDOW_SECS)
+            {
+                samples.pop_front();
+            }
+            !samples.is_empty()
+        });
+    }
+
+    /// Items delivered over the last minute.
+    #[must_use]
+    pub fn items_per_minute(&self, workflow: Entity) -> u32 {
+        self.deliveries.get(&workflow).map_or(0, |samples| {
+            samples.iter().map(|&(_, quantity)| quantity).sum()
+        })
+    }
+}
+
+/// Credits a workflow with every transfer one of its workers makes into a
+/// building. Worker-to-worker relay handoffs are not deliveries.
+pub fn record_workflow_deliveries(
+    time: Res<Time>,
+    mut transfers: MessageReader<ItemTransferEvent>,
+    assignments: Query<&WorkflowAssignment, With<Worker>>,
+    workers: Query<(), With<Worker>>,
+    mut throughput: ResMut<WorkflowThroughput>,
+) {
+    let now = time.elapsed_secs();
+    for transfer in transfers.read() {
+        if workers.contains(transfer.receiver) {
+            continue;
+        }
+        let Ok(assignment) = assignments.get(transfer.sender) else {
+            continue;
+        };
+        let quantity = transfer.items_transferred.values().sum();
+        throughput.record(assignment.workflow, now, quantity);
+    }
+    throughput.prune(now);
+}
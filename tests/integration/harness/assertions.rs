@@ -2,30 +2,33 @@ use bevy::prelude::*;
 
 use the_factory::{
     grid::Position,
-    materials::{InputPort, InventoryAccess, OutputPort, StoragePort},
+    materials::{InputPort, InventoryAccess, ItemId, ItemRegistry, OutputPort, StoragePort},
     systems::Operational,
 };
 
+use super::builders::item_id;
+
 pub fn assert_inventory_has(world: &World, entity: Entity, item: &str, expected_qty: u32) {
     let mut found = false;
     let mut actual_qty = 0;
     let mut port_type = "";
+    let item_id = item_id(item);
 
     if let Some(port) = world.get::<OutputPort>(entity) {
-        actual_qty = port.get_item_quantity(item);
+        actual_qty = port.get_item_quantity(item_id);
         port_type = "OutputPort";
         found = true;
     }
     if !found {
         if let Some(port) = world.get::<InputPort>(entity) {
-            actual_qty = port.get_item_quantity(item);
+            actual_qty = port.get_item_quantity(item_id);
             port_type = "InputPort";
             found = true;
         }
     }
     if !found {
         if let Some(port) = world.get::<StoragePort>(entity) {
-            actual_qty = port.get_item_quantity(item);
+            actual_qty = port.get_item_quantity(item_id);
             port_type = "StoragePort";
             found = true;
         }
@@ -41,6 +44,75 @@ pub fn assert_inventory_has(world: &World, entity: Entity, item: &str, expected_
     );
 }
 
+/// Lists every inventory port on the entity with its items sorted by name, so
+/// failure messages stay stable. `None` if the entity has no port at all.
+fn describe_inventory(world: &World, entity: Entity) -> Option<String> {
+    let ports: [(&str, Option<&dyn InventoryAccess>); 3] = [
+        (
+            "OutputPort",
+            world
+                .get::<OutputPort>(entity)
+                .map(|p| p as &dyn InventoryAccess),
+        ),
+        (
+            "InputPort",
+            world
+                .get::<InputPort>(entity)
+                .map(|p| p as &dyn InventoryAccess),
+        ),
+        (
+            "StoragePort",
+            world
+                .get::<StoragePort>(entity)
+                .map(|p| p as &dyn InventoryAccess),
+        ),
+    ];
+
+    let name_of = |item: ItemId| {
+        world
+            .get_resource::<ItemRegistry>()
+            .and_then(|registry| registry.name_of(item))
+            .map_or_else(|| format!("{item:?}"), ToString::to_string)
+    };
+    let described: Vec<String> = ports
+        .into_iter()
+        .filter_map(|(label, port)| {
+            let mut items: Vec<String> = port?
+                .items()
+                .iter()
+                .filter(|(_, &qty)| qty > 0)
+                .map(|(&item, qty)| format!("{} x{qty}", name_of(item)))
+                .collect();
+            items.sort_unstable();
+            Some(format!("{label} [{}]", items.join(", ")))
+        })
+        .collect();
+
+    (!described.is_empty()).then(|| described.join(", "))
+}
+
+/// Passes when the entity's ports hold at least `min_qty` of the item in
+/// total. On failure the message lists everything the entity holds.
+pub fn assert_inventory_contains(world: &World, entity: Entity, item: &str, min_qty: u32) {
+    let contents = describe_inventory(world, entity)
+        .unwrap_or_else(|| panic!("entity {entity:?} has no inventory port"));
+    let item_id = item_id(item);
+    let actual_qty = world
+        .get::<OutputPort>(entity)
+        .map_or(0, |p| p.get_item_quantity(item_id))
+        + world
+            .get::<InputPort>(entity)
+            .map_or(0, |p| p.get_item_quantity(item_id))
+        + world
+            .get::<StoragePort>(entity)
+            .map_or(0, |p| p.get_item_quantity(item_id));
+
+    assert!(
+        actual_qty >= min_qty,
+        "entity {entity:?}: expected at least {min_qty}x '{item}', found {actual_qty}; holds {contents}"
+    );
+}
+
 pub fn assert_inventory_empty(world: &World, entity: Entity) {
     let mut has_port = false;
 
@@ -113,3 +185,46 @@ pub fn assert_has_component<T: Component>(world: &World, entity: Entity) {
         std::any::type_name::<T>()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stocked_world() -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(ItemRegistry::load_from_assets().unwrap());
+        let mut storage = StoragePort::new(50);
+        storage.add_item(item_id("Iron Ore"), 3);
+        storage.add_item(item_id("Coal"), 2);
+        let entity = world.spawn(storage).id();
+        (world, entity)
+    }
+
+    #[test]
+    fn inventory_contains_accepts_enough_items() {
+        let (world, entity) = stocked_world();
+        assert_inventory_contains(&world, entity, "Iron Ore", 3);
+        assert_inventory_contains(&world, entity, "Coal", 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "found 3; holds StoragePort [Coal x2, Iron Ore x3]")]
+    fn inventory_contains_reports_actual_contents() {
+        let (world, entity) = stocked_world();
+        assert_inventory_contains(&world, entity, "Iron Ore", 5);
+    }
+
+    #[test]
+    fn inventory_empty_accepts_empty_ports() {
+        let mut world = World::new();
+        let entity = world.spawn((InputPort::new(10), OutputPort::new(10))).id();
+        assert_inventory_empty(&world, entity);
+    }
+
+    #[test]
+    #[should_panic(expected = "StoragePort not empty")]
+    fn inventory_empty_reports_leftover_items() {
+        let (world, entity) = stocked_world();
+        assert_inventory_empty(&world, entity);
+    }
+}
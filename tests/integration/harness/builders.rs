@@ -3,9 +3,9 @@ use bevy::prelude::*;
 use std::collections::VecDeque;
 
 use the_factory::{
-    grid::{Grid, Position},
-    materials::{Cargo, InputPort, InventoryAccess, OutputPort, StoragePort},
-    structures::{BuildingRegistry, ComputeConsumer},
+    grid::{CellChildren, Grid, Position},
+    materials::{Cargo, InputPort, InventoryAccess, ItemId, OutputPort, StoragePort},
+    structures::{BuildingRegistry, ComputeConsumer, Facing},
     systems::{NetworkChangedEvent, NetworkConnectivity},
     workers::{Speed, Worker, WorkerPath},
 };
@@ -46,8 +46,12 @@ pub fn spawn_worker(world: &mut World, x: i32, y: i32) -> Entity {
         .id()
 }
 
-#[allow(clippy::cast_precision_loss)]
 pub fn spawn_building(app: &mut App, name: &str, x: i32, y: i32) -> Entity {
+    spawn_building_facing(app, name, x, y, Facing::default())
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn spawn_building_facing(app: &mut App, name: &str, x: i32, y: i32, facing: Facing) -> Entity {
     let world_pos = {
         let grid = app.world().resource::<Grid>();
         grid.grid_to_world_coordinates(x, y)
@@ -59,22 +63,42 @@ pub fn spawn_building(app: &mut App, name: &str, x: i32, y: i32) -> Entity {
         .run_system_once(
             move |mut commands: Commands, registry: Res<BuildingRegistry>| {
                 registry
-                    .spawn_building(&mut commands, &name_owned, x, y, world_pos)
+                    .spawn_building(&mut commands, &name_owned, x, y, world_pos, facing)
                     .unwrap_or_else(|| {
                         panic!("failed to spawn building '{name_owned}' - not found in registry")
                     })
             },
         )
         .unwrap();
+    record_in_cell(app.world_mut(), entity, x, y);
 
     app.world_mut().write_message(NetworkChangedEvent);
 
     entity
 }
 
+/// Lists the building in its grid cell, as placement does in the game,
+/// spawning a bare cell when the test has not created one.
+fn record_in_cell(world: &mut World, entity: Entity, x: i32, y: i32) {
+    let mut cells = world.query::<(&Position, &mut CellChildren)>();
+    if let Some((_, mut children)) = cells
+        .iter_mut(world)
+        .find(|(pos, _)| pos.x == x && pos.y == y)
+    {
+        children.0.push(entity);
+        return;
+    }
+    world.spawn((Position { x, y }, CellChildren(vec![entity])));
+}
+
+/// Resolves an item name, failing the test on a misspelling.
+pub fn item_id(item: &str) -> ItemId {
+    ItemId::named(item).unwrap_or_else(|| panic!("unknown item {item:?}"))
+}
+
 pub fn add_items_to_input(world: &mut World, entity: Entity, item: &str, qty: u32) {
     if let Some(mut port) = world.get_mut::<InputPort>(entity) {
-        port.add_item(item, qty);
+        port.add_item(item_id(item), qty);
     } else {
         panic!("entity {entity:?} has no InputPort - cannot add items");
     }
@@ -82,7 +106,7 @@ pub fn add_items_to_input(world: &mut World, entity: Entity, item: &str, qty: u3
 
 pub fn add_items_to_output(world: &mut World, entity: Entity, item: &str, qty: u32) {
     if let Some(mut port) = world.get_mut::<OutputPort>(entity) {
-        port.add_item(item, qty);
+        port.add_item(item_id(item), qty);
     } else {
         panic!("entity {entity:?} has no OutputPort - cannot add items");
     }
@@ -90,7 +114,7 @@ pub fn add_items_to_output(world: &mut World, entity: Entity, item: &str, qty: u
 
 pub fn add_items_to_storage(world: &mut World, entity: Entity, item: &str, qty: u32) {
     if let Some(mut port) = world.get_mut::<StoragePort>(entity) {
-        port.add_item(item, qty);
+        port.add_item(item_id(item), qty);
     } else {
         panic!("entity {entity:?} has no StoragePort - cannot add items");
     }
@@ -1,9 +1,21 @@
+use bevy::ecs::system::RunSystemOnce;
 use bevy::prelude::*;
 use the_factory::{
+    grid::CellChildren,
     grid::Position,
+    materials::StoragePort,
     materials::{InputPort, InventoryAccess},
-    structures::{Building, ConstructionSite},
+    structures::{
+        Building, CancelConstructionEvent, CompletionFlash, ConstructionSite, CreativeMode, Facing,
+        Hub, Scaffold,
+    },
     systems::Operational,
+    ui::{
+        modes::placement::repeat_last_placed,
+        overlay::{creative_hotkeys, FinishAllConfirm},
+        SelectedBuilding,
+    },
+    workers::{resolve_spawn_cell, RallyPoint},
 };
 
 use crate::harness::*;
@@ -21,6 +33,7 @@ fn placement_creates_construction_site() {
             building_name: "Connector".to_string(),
             grid_x: 2,
             grid_y: 0,
+            facing: Facing::default(),
         });
     tick_n(&mut app, 3);
 
@@ -38,6 +51,33 @@ fn placement_creates_construction_site() {
     assert!(found, "should have created a ConstructionSite at (2,0)");
 }
 
+#[test]
+fn repeat_last_selects_the_last_placed_building() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0)]);
+
+    app.world_mut()
+        .write_message(the_factory::structures::PlaceBuildingRequestEvent {
+            building_name: "Connector".to_string(),
+            grid_x: 2,
+            grid_y: 0,
+            facing: Facing::South,
+        });
+    tick_n(&mut app, 3);
+
+    let mut keyboard = ButtonInput::<KeyCode>::default();
+    keyboard.press(KeyCode::KeyQ);
+    app.insert_resource(keyboard);
+    app.world_mut().run_system_once(repeat_last_placed).unwrap();
+
+    let selected = app.world().resource::<SelectedBuilding>();
+    assert_eq!(selected.building_name.as_deref(), Some("Connector"));
+    assert_eq!(selected.facing, Facing::South);
+}
+
 #[test]
 fn construction_completes_with_materials() {
     let mut app = headless_app();
@@ -51,6 +91,7 @@ fn construction_completes_with_materials() {
             building_name: "Connector".to_string(),
             grid_x: 2,
             grid_y: 0,
+            facing: Facing::default(),
         });
     tick_n(&mut app, 3);
 
@@ -107,6 +148,7 @@ fn completed_building_has_all_components() {
             building_name: "Connector".to_string(),
             grid_x: 2,
             grid_y: 0,
+            facing: Facing::default(),
         });
     tick_n(&mut app, 3);
 
@@ -150,6 +192,76 @@ fn completed_building_has_all_components() {
     assert_has_component::<Transform>(app.world(), building_entity);
 }
 
+#[test]
+fn scaffold_marks_site_and_clears_on_completion() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0)]);
+
+    app.world_mut()
+        .write_message(the_factory::structures::PlaceBuildingRequestEvent {
+            building_name: "Connector".to_string(),
+            grid_x: 2,
+            grid_y: 0,
+            facing: Facing::default(),
+        });
+    tick_n(&mut app, 3);
+
+    let site_entity = {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<(Entity, &Position), With<ConstructionSite>>();
+        let mut found = None;
+        for (entity, pos) in query.iter(app.world()) {
+            if pos.x == 2 && pos.y == 0 {
+                found = Some(entity);
+            }
+        }
+        found.expect("construction site should exist")
+    };
+
+    assert_has_component::<Scaffold>(app.world(), site_entity);
+
+    {
+        let world = app.world_mut();
+        add_items_to_input(world, site_entity, "Iron Ore", 10);
+        add_items_to_input(world, site_entity, "Copper Ore", 5);
+    }
+    tick_n(&mut app, 5);
+
+    let building_entity = {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<(Entity, &Position), With<Building>>();
+        let mut found = None;
+        for (entity, pos) in query.iter(app.world()) {
+            if pos.x == 2 && pos.y == 0 {
+                found = Some(entity);
+            }
+        }
+        found.expect("completed building should exist at (2,0)")
+    };
+
+    assert!(
+        app.world().get::<Scaffold>(building_entity).is_none(),
+        "completed building should not carry the scaffold marker"
+    );
+    assert_has_component::<CompletionFlash>(app.world(), building_entity);
+
+    tick_seconds(&mut app, 1.0);
+
+    assert!(
+        app.world()
+            .get::<CompletionFlash>(building_entity)
+            .is_none(),
+        "completion flash should expire"
+    );
+    let transform = app.world().get::<Transform>(building_entity).unwrap();
+    assert_eq!(transform.scale, Vec3::ONE);
+}
+
 #[test]
 fn placement_rejected_on_occupied_cell() {
     let mut app = headless_app();
@@ -163,6 +275,7 @@ fn placement_rejected_on_occupied_cell() {
             building_name: "Connector".to_string(),
             grid_x: 2,
             grid_y: 0,
+            facing: Facing::default(),
         });
     tick_n(&mut app, 3);
 
@@ -182,6 +295,7 @@ fn placement_rejected_on_occupied_cell() {
             building_name: "Connector".to_string(),
             grid_x: 2,
             grid_y: 0,
+            facing: Facing::default(),
         });
     tick_n(&mut app, 3);
 
@@ -213,6 +327,7 @@ fn auto_pull_delivers_materials() {
             building_name: "Connector".to_string(),
             grid_x: 2,
             grid_y: 0,
+            facing: Facing::default(),
         });
     tick_n(&mut app, 3);
 
@@ -234,11 +349,196 @@ fn auto_pull_delivers_materials() {
 
     if let Some(site_entity) = site_entity {
         let input = app.world().get::<InputPort>(site_entity).unwrap();
-        let iron = input.get_item_quantity("Iron Ore");
-        let copper = input.get_item_quantity("Copper Ore");
+        let iron = input.get_item_quantity(item_id("Iron Ore"));
+        let copper = input.get_item_quantity(item_id("Copper Ore"));
         assert!(
             iron > 0 || copper > 0,
             "auto-pull should have delivered some materials to the construction site"
         );
     }
 }
+
+#[test]
+fn cancel_construction_refunds_delivered_materials() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0)]);
+
+    app.world_mut()
+        .write_message(the_factory::structures::PlaceBuildingRequestEvent {
+            building_name: "Connector".to_string(),
+            grid_x: 2,
+            grid_y: 0,
+            facing: Facing::default(),
+        });
+    tick_n(&mut app, 3);
+
+    let site = app
+        .world_mut()
+        .query_filtered::<Entity, With<ConstructionSite>>()
+        .single(app.world())
+        .unwrap();
+    let hub = app
+        .world_mut()
+        .query_filtered::<Entity, With<Hub>>()
+        .single(app.world())
+        .unwrap();
+    let ore = item_id("Iron Ore");
+    let hub_before = app
+        .world()
+        .get::<StoragePort>(hub)
+        .unwrap()
+        .get_item_quantity(ore);
+    add_items_to_input(app.world_mut(), site, "Iron Ore", 4);
+
+    app.world_mut()
+        .write_message(CancelConstructionEvent { site });
+    tick_n(&mut app, 2);
+
+    assert!(app.world().get_entity(site).is_err(), "site should be gone");
+    let hub_after = app
+        .world()
+        .get::<StoragePort>(hub)
+        .unwrap()
+        .get_item_quantity(ore);
+    assert_eq!(hub_after, hub_before + 4);
+
+    let still_listed = app
+        .world_mut()
+        .query::<(&Position, &CellChildren)>()
+        .iter(app.world())
+        .any(|(pos, children)| pos.x == 2 && pos.y == 0 && children.0.contains(&site));
+    assert!(!still_listed, "site should be removed from its grid cell");
+}
+
+#[test]
+fn hub_cannot_be_removed() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let hub = app
+        .world_mut()
+        .query_filtered::<Entity, With<Hub>>()
+        .single(app.world())
+        .unwrap();
+    // Grid cells and the hub are both spawned at startup, so make sure the
+    // hub is listed in its cell the way a placed building would be.
+    let mut cells = app.world_mut().query::<(&Position, &mut CellChildren)>();
+    for (pos, mut children) in cells.iter_mut(app.world_mut()) {
+        if pos.x == 0 && pos.y == 0 && !children.0.contains(&hub) {
+            children.0.push(hub);
+        }
+    }
+
+    app.world_mut()
+        .write_message(the_factory::structures::RemoveBuildingEvent {
+            grid_x: 0,
+            grid_y: 0,
+        });
+    tick_n(&mut app, 2);
+
+    assert!(app.world().get_entity(hub).is_ok(), "hub should survive");
+    let still_listed = app
+        .world_mut()
+        .query::<(&Position, &CellChildren)>()
+        .iter(app.world())
+        .any(|(pos, children)| pos.x == 0 && pos.y == 0 && children.0.contains(&hub));
+    assert!(still_listed, "hub should stay in its grid cell");
+}
+
+#[test]
+fn worker_spawn_falls_back_to_nearest_building_without_hub() {
+    let rally_point = RallyPoint::default();
+    let buildings = [Position { x: 6, y: 1 }, Position { x: -2, y: 1 }];
+
+    assert_eq!(resolve_spawn_cell(&rally_point, true, buildings), (0, 0));
+    assert_eq!(resolve_spawn_cell(&rally_point, false, buildings), (-2, 1));
+    assert_eq!(resolve_spawn_cell(&rally_point, false, []), (0, 0));
+}
+
+fn building_at(app: &mut App, x: i32, y: i32) -> Option<Entity> {
+    app.world_mut()
+        .query_filtered::<(Entity, &Position), With<Building>>()
+        .iter(app.world())
+        .find(|(_, pos)| pos.x == x && pos.y == y)
+        .map(|(entity, _)| entity)
+}
+
+#[test]
+fn creative_mode_places_buildings_complete() {
+    let mut app = headless_app();
+    tick(&mut app);
+    ensure_grid_coordinates(app.world_mut(), &[(2, 0)]);
+    app.world_mut().resource_mut::<CreativeMode>().enabled = true;
+
+    app.world_mut()
+        .write_message(the_factory::structures::PlaceBuildingRequestEvent {
+            building_name: "Connector".to_string(),
+            grid_x: 2,
+            grid_y: 0,
+            facing: Facing::default(),
+        });
+    tick_n(&mut app, 3);
+
+    let building = building_at(&mut app, 2, 0).expect("creative placement should spawn a building");
+    assert_eq!(
+        app.world_mut()
+            .query::<&ConstructionSite>()
+            .iter(app.world())
+            .count(),
+        0,
+        "creative placement should skip construction"
+    );
+    assert_operational(app.world(), building);
+}
+
+#[test]
+fn finish_all_hotkey_completes_sites_in_progress() {
+    let mut app = headless_app();
+    tick(&mut app);
+    ensure_grid_coordinates(app.world_mut(), &[(2, 0)]);
+
+    app.world_mut()
+        .write_message(the_factory::structures::PlaceBuildingRequestEvent {
+            building_name: "Connector".to_string(),
+            grid_x: 2,
+            grid_y: 0,
+            facing: Facing::default(),
+        });
+    tick_n(&mut app, 3);
+    assert!(building_at(&mut app, 2, 0).is_none());
+
+    // Enabled after placing, so the connector is still a construction site.
+    app.world_mut().resource_mut::<CreativeMode>().enabled = true;
+    let mut keyboard = ButtonInput::<KeyCode>::default();
+    keyboard.press(KeyCode::F7);
+    app.insert_resource(keyboard);
+    app.init_resource::<FinishAllConfirm>();
+    // The first press only arms the confirmation.
+    app.world_mut().run_system_once(creative_hotkeys).unwrap();
+    tick_n(&mut app, 3);
+    assert!(building_at(&mut app, 2, 0).is_none());
+
+    {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(KeyCode::F7);
+        keyboard.clear();
+        keyboard.press(KeyCode::F7);
+    }
+    app.world_mut().run_system_once(creative_hotkeys).unwrap();
+    tick_n(&mut app, 3);
+
+    assert!(
+        building_at(&mut app, 2, 0).is_some(),
+        "the site should have been finished"
+    );
+    assert_eq!(
+        app.world_mut()
+            .query::<&ConstructionSite>()
+            .iter(app.world())
+            .count(),
+        0
+    );
+}
@@ -1,11 +1,16 @@
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet};
 use the_factory::{
-    materials::{Cargo, InventoryAccess, StoragePort},
-    structures::Hub,
-    workers::workflows::{
-        StepTarget, WaitingForItems, WaitingForSpace, Workflow, WorkflowAction, WorkflowAssignment,
-        WorkflowStep,
+    grid::{Grid, Position},
+    materials::{Cargo, InventoryAccess, OutputPort, StoragePort},
+    structures::{Facing, Hub},
+    systems::GameScore,
+    workers::{
+        workflows::{
+            LogisticsConfig, PendingSequenceQueue, StepTarget, WaitingForItems, WaitingForSpace,
+            Workflow, WorkflowAction, WorkflowAssignment, WorkflowStep,
+        },
+        RallyPoint, WorkerPath,
     },
 };
 
@@ -47,15 +52,19 @@ fn worker_completes_pickup_dropoff_cycle() {
                 WorkflowStep {
                     target: StepTarget::Specific(hub),
                     action: WorkflowAction::Pickup(None),
+                    condition: None,
                 },
                 WorkflowStep {
                     target: StepTarget::Specific(storage),
                     action: WorkflowAction::Dropoff(None),
+                    condition: None,
                 },
             ],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         })
         .id();
 
@@ -105,10 +114,13 @@ fn worker_waits_at_empty_source() {
             steps: vec![WorkflowStep {
                 target: StepTarget::Specific(storage),
                 action: WorkflowAction::Pickup(None),
+                condition: None,
             }],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         })
         .id();
 
@@ -152,7 +164,7 @@ fn worker_retries_full_destination() {
     {
         let world = app.world_mut();
         let mut cargo = world.get_mut::<Cargo>(worker).unwrap();
-        cargo.add_item("Coal", 5);
+        cargo.add_item(item_id("Coal"), 5);
     }
     tick(&mut app);
 
@@ -167,10 +179,13 @@ fn worker_retries_full_destination() {
             steps: vec![WorkflowStep {
                 target: StepTarget::Specific(storage),
                 action: WorkflowAction::Dropoff(None),
+                condition: None,
             }],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         })
         .id();
 
@@ -225,10 +240,13 @@ fn round_robin_distributes_evenly() {
             steps: vec![WorkflowStep {
                 target: StepTarget::ByType("Storage".to_string()),
                 action: WorkflowAction::Pickup(None),
+                condition: None,
             }],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 2,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         })
         .id();
 
@@ -298,10 +316,13 @@ fn arrival_fires_when_already_at_target() {
             steps: vec![WorkflowStep {
                 target: StepTarget::Specific(storage),
                 action: WorkflowAction::Pickup(None),
+                condition: None,
             }],
             is_paused: false,
+            is_dedicated: false,
             desired_worker_count: 1,
             round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
         })
         .id();
 
@@ -333,7 +354,7 @@ fn emergency_dropoff_on_unassignment() {
     {
         let world = app.world_mut();
         let mut cargo = world.get_mut::<Cargo>(worker).unwrap();
-        cargo.add_item("Iron Ore", 5);
+        cargo.add_item(item_id("Iron Ore"), 5);
     }
     tick(&mut app);
 
@@ -346,3 +367,501 @@ fn emergency_dropoff_on_unassignment() {
         cargo.items
     );
 }
+
+#[test]
+fn disabled_emergency_dropoff_leaves_cargo_with_unassigned_worker() {
+    let mut app = headless_app();
+    tick(&mut app);
+    app.world_mut()
+        .resource_mut::<LogisticsConfig>()
+        .emergency_dropoff = false;
+
+    let worker = spawn_worker(app.world_mut(), 0, 0);
+    {
+        let world = app.world_mut();
+        let mut cargo = world.get_mut::<Cargo>(worker).unwrap();
+        cargo.add_item(item_id("Iron Ore"), 5);
+    }
+    tick_n(&mut app, 11);
+
+    let cargo = app.world().get::<Cargo>(worker).unwrap();
+    assert_eq!(
+        cargo.items.get(&item_id("Iron Ore")).copied(),
+        Some(5),
+        "unassigned worker should hold its cargo when emergency dropoff is off"
+    );
+}
+
+#[test]
+fn sequence_cap_defers_excess_requests() {
+    let mut app = headless_app();
+    tick(&mut app);
+    app.world_mut()
+        .resource_mut::<LogisticsConfig>()
+        .max_active_sequences = 2;
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let storage = spawn_building(&mut app, "Storage", 3, 0);
+    tick_n(&mut app, 3);
+
+    let workers: Vec<Entity> = (0..3)
+        .map(|_| spawn_worker(app.world_mut(), 3, 0))
+        .collect();
+    tick(&mut app);
+
+    let mut building_set = HashSet::new();
+    building_set.insert(storage);
+
+    let workflow_entity = app
+        .world_mut()
+        .spawn(Workflow {
+            name: "cap test".to_string(),
+            building_set,
+            steps: vec![WorkflowStep {
+                target: StepTarget::Specific(storage),
+                action: WorkflowAction::Pickup(None),
+                condition: None,
+            }],
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 3,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        })
+        .id();
+
+    for &worker in &workers {
+        app.world_mut()
+            .entity_mut(worker)
+            .insert(WorkflowAssignment {
+                workflow: workflow_entity,
+                current_step: 0,
+                resolved_target: None,
+                resolved_action: None,
+            });
+    }
+
+    tick_n(&mut app, 60);
+
+    let active: Vec<Entity> = workers
+        .iter()
+        .copied()
+        .filter(|&w| {
+            app.world()
+                .get::<WorkflowAssignment>(w)
+                .unwrap()
+                .resolved_target
+                .is_some()
+        })
+        .collect();
+    assert_eq!(active.len(), 2, "only two sequences should be active");
+
+    let waiting = workers
+        .iter()
+        .copied()
+        .find(|w| !active.contains(w))
+        .unwrap();
+    let pending = app.world().resource::<PendingSequenceQueue>();
+    assert_eq!(
+        pending.requests,
+        vec![waiting],
+        "third request should wait in the pending queue"
+    );
+}
+
+#[test]
+fn dispatch_throttle_limits_assignments_per_update() {
+    let mut app = headless_app();
+    tick(&mut app);
+    {
+        let mut config = app.world_mut().resource_mut::<LogisticsConfig>();
+        config.max_dispatches_per_frame = 2;
+    }
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let storage = spawn_building(&mut app, "Storage", 3, 0);
+    tick_n(&mut app, 3);
+
+    let workers: Vec<Entity> = (0..6)
+        .map(|_| spawn_worker(app.world_mut(), 3, 0))
+        .collect();
+    tick(&mut app);
+
+    let mut building_set = HashSet::new();
+    building_set.insert(storage);
+
+    let workflow_entity = app
+        .world_mut()
+        .spawn(Workflow {
+            name: "throttle test".to_string(),
+            building_set,
+            steps: vec![WorkflowStep {
+                target: StepTarget::Specific(storage),
+                action: WorkflowAction::Pickup(None),
+                condition: None,
+            }],
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 6,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        })
+        .id();
+
+    for &worker in &workers {
+        app.world_mut()
+            .entity_mut(worker)
+            .insert(WorkflowAssignment {
+                workflow: workflow_entity,
+                current_step: 0,
+                resolved_target: None,
+                resolved_action: None,
+            });
+    }
+
+    let count_dispatched = |app: &App| {
+        workers
+            .iter()
+            .filter(|&&w| {
+                app.world()
+                    .get::<WorkflowAssignment>(w)
+                    .unwrap()
+                    .resolved_target
+                    .is_some()
+            })
+            .count()
+    };
+
+    tick(&mut app);
+    assert_eq!(
+        count_dispatched(&app),
+        2,
+        "first update should dispatch two"
+    );
+
+    tick(&mut app);
+    assert_eq!(
+        count_dispatched(&app),
+        4,
+        "second update should dispatch two more"
+    );
+
+    tick(&mut app);
+    assert_eq!(
+        count_dispatched(&app),
+        6,
+        "third update should dispatch the rest"
+    );
+}
+
+#[test]
+fn request_cooldown_holds_back_duplicate_dropoffs() {
+    let mut app = headless_app();
+    tick(&mut app);
+    app.world_mut()
+        .resource_mut::<LogisticsConfig>()
+        .request_cooldown_secs = 5.0;
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let storage = spawn_building(&mut app, "Storage", 3, 0);
+    tick_n(&mut app, 3);
+
+    let workers: Vec<Entity> = (0..3)
+        .map(|_| {
+            let worker = spawn_worker(app.world_mut(), 0, 0);
+            app.world_mut()
+                .get_mut::<Cargo>(worker)
+                .unwrap()
+                .add_item(item_id("Iron Ore"), 5);
+            worker
+        })
+        .collect();
+    tick(&mut app);
+
+    let workflow_entity = app
+        .world_mut()
+        .spawn(Workflow {
+            name: "cooldown test".to_string(),
+            building_set: HashSet::from([storage]),
+            steps: vec![WorkflowStep {
+                target: StepTarget::Specific(storage),
+                action: WorkflowAction::Dropoff(None),
+                condition: None,
+            }],
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 3,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        })
+        .id();
+
+    for &worker in &workers {
+        app.world_mut()
+            .entity_mut(worker)
+            .insert(WorkflowAssignment {
+                workflow: workflow_entity,
+                current_step: 0,
+                resolved_target: None,
+                resolved_action: None,
+            });
+    }
+
+    for _ in 0..10 {
+        tick(&mut app);
+        let dispatched = workers
+            .iter()
+            .filter(|&&w| {
+                app.world()
+                    .get::<WorkflowAssignment>(w)
+                    .unwrap()
+                    .resolved_target
+                    .is_some()
+            })
+            .count();
+        assert_eq!(
+            dispatched, 1,
+            "only one dropoff should head to the storage while it cools down"
+        );
+    }
+}
+
+#[test]
+fn waiting_retry_interval_backs_off_and_resets_after_pickup() {
+    let mut app = headless_app();
+    {
+        let mut config = app.world_mut().resource_mut::<LogisticsConfig>();
+        config.wait_retry_secs = 0.5;
+        config.wait_backoff_factor = 2.0;
+        config.max_wait_retry_secs = 4.0;
+    }
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let storage = spawn_building(&mut app, "Storage", 3, 0);
+    tick_n(&mut app, 3);
+
+    let worker = spawn_worker(app.world_mut(), 3, 0);
+    tick(&mut app);
+
+    let workflow_entity = app
+        .world_mut()
+        .spawn(Workflow {
+            name: "backoff test".to_string(),
+            building_set: HashSet::from([storage]),
+            steps: vec![WorkflowStep {
+                target: StepTarget::Specific(storage),
+                action: WorkflowAction::Pickup(None),
+                condition: None,
+            }],
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 1,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        })
+        .id();
+
+    app.world_mut()
+        .entity_mut(worker)
+        .insert(WorkflowAssignment {
+            workflow: workflow_entity,
+            current_step: 0,
+            resolved_target: None,
+            resolved_action: None,
+        });
+
+    let retry_millis = |world: &World| {
+        world
+            .get::<WaitingForItems>(worker)
+            .map(|waiting| waiting.timer.duration().as_millis())
+    };
+
+    tick_until(
+        &mut app,
+        60,
+        |world| retry_millis(world).is_some(),
+        "worker should start waiting at the empty storage",
+    );
+    let mut intervals = vec![retry_millis(app.world()).unwrap()];
+    for _ in 0..420 {
+        tick(&mut app);
+        let current = retry_millis(app.world()).unwrap();
+        if intervals.last() != Some(&current) {
+            intervals.push(current);
+        }
+    }
+    assert_eq!(intervals, vec![500, 1000, 2000, 4000]);
+
+    add_items_to_storage(app.world_mut(), storage, "Iron Ore", 5);
+    tick_until(
+        &mut app,
+        300,
+        |world| {
+            world
+                .get::<Cargo>(worker)
+                .is_some_and(|cargo| !cargo.is_empty())
+        },
+        "worker should pick up once items appear",
+    );
+    tick_until(
+        &mut app,
+        20,
+        |world| retry_millis(world).is_some(),
+        "worker should wait again after emptying the storage",
+    );
+    assert_eq!(retry_millis(app.world()), Some(500));
+}
+
+#[test]
+fn conveyors_carry_output_into_the_building_they_face() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0), (3, 1), (3, 2), (3, 3)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let smelter = spawn_building(&mut app, "Smelter", 3, 3);
+    let first = spawn_building_facing(&mut app, "Conveyor", 3, 2, Facing::South);
+    let second = spawn_building_facing(&mut app, "Conveyor", 3, 1, Facing::South);
+    let _sink = spawn_building(&mut app, "Item Sink", 3, 0);
+    tick_n(&mut app, 3);
+
+    add_items_to_output(app.world_mut(), smelter, "Iron Ingot", 6);
+
+    tick_until(
+        &mut app,
+        600,
+        |world| world.resource::<GameScore>().items_sunk == 6,
+        "all ingots should reach the sink",
+    );
+
+    let output = app.world().get::<OutputPort>(smelter).unwrap();
+    assert!(output.is_empty(), "smelter output: {:?}", output.items);
+    assert_inventory_empty(app.world(), first);
+    assert_inventory_empty(app.world(), second);
+}
+
+#[test]
+fn new_worker_heads_to_the_rally_point() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    app.world_mut().resource_mut::<RallyPoint>().rally = (2, 0);
+    let worker = spawn_worker(app.world_mut(), 0, 0);
+    tick(&mut app);
+
+    let rally_world = app
+        .world()
+        .resource::<Grid>()
+        .grid_to_world_coordinates(2, 0);
+    let path = app.world().get::<WorkerPath>(worker).unwrap();
+    let destination = path.waypoints.back().copied().or(path.current_target);
+    assert_eq!(destination, Some(rally_world));
+}
+
+#[test]
+fn relay_splits_a_long_delivery_between_two_workers() {
+    let mut app = headless_app();
+    tick(&mut app);
+    app.world_mut()
+        .resource_mut::<LogisticsConfig>()
+        .relay_min_path = Some(8);
+    let hub = find_hub(&mut app);
+
+    let route: Vec<(i32, i32)> = (2..=12).map(|x| (x, 0)).collect();
+    ensure_grid_coordinates(app.world_mut(), &route);
+    for x in 2..=11 {
+        spawn_building(&mut app, "Connector", x, 0);
+        tick_n(&mut app, 2);
+    }
+    let storage = spawn_building(&mut app, "Storage", 12, 0);
+    tick_n(&mut app, 3);
+
+    let carrier = spawn_worker(app.world_mut(), 0, 0);
+    let partner = spawn_worker(app.world_mut(), 5, 0);
+    tick(&mut app);
+
+    let workflow_entity = app
+        .world_mut()
+        .spawn(Workflow {
+            name: "relay test".to_string(),
+            building_set: HashSet::from([hub, storage]),
+            steps: vec![
+                WorkflowStep {
+                    target: StepTarget::Specific(hub),
+                    action: WorkflowAction::Pickup(None),
+                    condition: None,
+                },
+                WorkflowStep {
+                    target: StepTarget::Specific(storage),
+                    action: WorkflowAction::Dropoff(None),
+                    condition: None,
+                },
+            ],
+            is_paused: false,
+            is_dedicated: false,
+            desired_worker_count: 1,
+            round_robin_counters: HashMap::new(),
+            step_waits: HashMap::new(),
+        })
+        .id();
+    app.world_mut()
+        .entity_mut(carrier)
+        .insert(WorkflowAssignment {
+            workflow: workflow_entity,
+            current_step: 0,
+            resolved_target: None,
+            resolved_action: None,
+        });
+
+    let furthest_x = |app: &App, worker: Entity| app.world().get::<Position>(worker).unwrap().x;
+    let (mut carrier_max, mut partner_max) = (0, 0);
+    for _ in 0..900 {
+        app.update();
+        carrier_max = carrier_max.max(furthest_x(&app, carrier));
+        partner_max = partner_max.max(furthest_x(&app, partner));
+        if !app.world().get::<StoragePort>(storage).unwrap().is_empty() {
+            break;
+        }
+    }
+
+    assert!(
+        !app.world().get::<StoragePort>(storage).unwrap().is_empty(),
+        "the relayed cargo should reach the storage"
+    );
+    assert_eq!(carrier_max, 6, "the carrier should stop at the midpoint");
+    assert_eq!(
+        partner_max, 12,
+        "the partner should carry it the rest of the way"
+    );
+}
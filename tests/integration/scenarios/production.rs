@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use the_factory::{
     materials::{InputPort, InventoryAccess, OutputPort},
-    structures::RecipeCrafter,
+    structures::{Hub, RecipeCrafter, TechState},
+    systems::GameScore,
 };
 
 use crate::harness::*;
@@ -40,7 +41,7 @@ fn operational_crafter_produces_output() {
 
     let output_port = app.world().get::<OutputPort>(smelter).unwrap();
     assert!(
-        output_port.get_item_quantity("Iron Ingot") > 0,
+        output_port.get_item_quantity(item_id("Iron Ingot")) > 0,
         "smelter should have produced Iron Ingot, output: {:?}",
         output_port.items
     );
@@ -107,9 +108,9 @@ fn source_crafter_produces_without_inputs() {
 
     let input = app.world().get::<InputPort>(generator).unwrap();
     assert!(
-        input.get_item_quantity("Coal") < 10,
+        input.get_item_quantity(item_id("Coal")) < 10,
         "generator should have consumed some Coal, but still has {}",
-        input.get_item_quantity("Coal")
+        input.get_item_quantity(item_id("Coal"))
     );
 }
 
@@ -170,15 +171,132 @@ fn input_limits_match_recipe() {
 
     let input = app.world().get::<InputPort>(smelter).unwrap();
     assert_eq!(
-        input.item_limits.get("Iron Ore").copied().unwrap_or(0),
+        input
+            .item_limits
+            .get(&item_id("Iron Ore"))
+            .copied()
+            .unwrap_or(0),
         34,
         "Iron Ore limit should be 34, got {:?}",
         input.item_limits
     );
     assert_eq!(
-        input.item_limits.get("Coal").copied().unwrap_or(0),
+        input
+            .item_limits
+            .get(&item_id("Coal"))
+            .copied()
+            .unwrap_or(0),
         17,
         "Coal limit should be 17, got {:?}",
         input.item_limits
     );
 }
+
+#[test]
+fn sink_consumes_items_for_weighted_score() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let sink = spawn_building(&mut app, "Item Sink", 3, 0);
+    tick_n(&mut app, 3);
+
+    let score_before = app.world().resource::<GameScore>().total_score;
+
+    {
+        let world = app.world_mut();
+        add_items_to_input(world, sink, "Iron Ore", 4);
+        add_items_to_input(world, sink, "Iron Ingot", 2);
+    }
+    tick_n(&mut app, 2);
+
+    let score = app.world().resource::<GameScore>();
+    // Tier-weighted with base 1: Iron Ore (tier 0) = 1, Iron Ingot (tier 1) = 4.
+    assert_eq!(score.total_score - score_before, 4 + 2 * 4);
+    assert_eq!(score.items_sunk, 6);
+    assert_inventory_empty(app.world(), sink);
+}
+
+#[test]
+fn new_crafter_requests_inputs_from_nearby_storage() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let hub = {
+        let world = app.world_mut();
+        let mut query = world.query_filtered::<Entity, With<Hub>>();
+        query.single(world).unwrap()
+    };
+    add_items_to_storage(app.world_mut(), hub, "Coal", 20);
+
+    let smelter = spawn_building(&mut app, "Smelter", 3, 0);
+    {
+        let world = app.world_mut();
+        let mut crafter = world.get_mut::<RecipeCrafter>(smelter).unwrap();
+        crafter.current_recipe = Some("Iron Ingot".to_string());
+    }
+    tick_n(&mut app, 3);
+
+    let input = app.world().get::<InputPort>(smelter).unwrap();
+    assert!(
+        input.is_empty(),
+        "inputs should wait for a worker to carry them, input: {:?}",
+        input.items
+    );
+
+    spawn_worker(app.world_mut(), 0, 0);
+    tick_until(
+        &mut app,
+        1200,
+        |world| {
+            let input = world.get::<InputPort>(smelter).unwrap();
+            input.get_item_quantity(item_id("Iron Ore")) > 0
+                && input.get_item_quantity(item_id("Coal")) > 0
+        },
+        "an idle worker should carry the requested inputs from the hub",
+    );
+}
+
+#[test]
+fn research_lab_unlocks_its_target_recipe() {
+    let mut app = headless_app();
+    tick(&mut app);
+
+    let world = app.world_mut();
+    ensure_grid_coordinates(world, &[(2, 0), (3, 0)]);
+
+    let _connector = spawn_building(&mut app, "Connector", 2, 0);
+    tick_n(&mut app, 3);
+
+    let lab = spawn_building(&mut app, "Research Lab", 3, 0);
+    tick_n(&mut app, 3);
+
+    assert!(!app
+        .world()
+        .resource::<TechState>()
+        .is_unlocked("Electronic Circuit"));
+
+    {
+        let world = app.world_mut();
+        add_items_to_input(world, lab, "Gear", 20);
+        add_items_to_input(world, lab, "Copper Wire", 20);
+    }
+    tick_n(&mut app, 2);
+
+    assert!(app
+        .world()
+        .resource::<TechState>()
+        .is_unlocked("Electronic Circuit"));
+    assert_inventory_empty(app.world(), lab);
+}